@@ -1,6 +1,13 @@
+// Criterion benchmarks, plus the regression gate subcommands:
+//
+//   cargo run --release -p benchmarks -- summary [--depth N] [--out summary.json]
+//   cargo run --release -p benchmarks -- compare old.json new.json --threshold 5%
+
+mod regression;
+
 use chess_core::{Evaluator, GameState, MoveGenerator, Position};
 use chess_engine::{ChessEngine, ChessEngineBuilder};
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{black_box, criterion_group, BenchmarkId, Criterion};
 // use std::time::Duration;
 
 fn benchmark_move_generation(c: &mut Criterion) {
@@ -148,4 +155,21 @@ criterion_group!(
     benchmark_different_depths,
     benchmark_memory_usage
 );
-criterion_main!(benches);
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("summary") => regression::summary_command(&args[1..]),
+        Some("compare") => regression::compare_command(&args[1..]),
+        _ => {
+            benches();
+            Criterion::default().configure_from_args().final_summary();
+            Ok(())
+        }
+    };
+
+    if let Err(message) = result {
+        eprintln!("error: {message}");
+        std::process::exit(1);
+    }
+}
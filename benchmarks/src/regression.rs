@@ -0,0 +1,208 @@
+// Benchmark regression gate: write a JSON throughput summary and compare two of them
+
+use chess_core::search::{run_summary, BenchSummary};
+use chess_core::{SearchConfig, SearchLimits};
+use std::fs;
+
+/// Search depth of the summary bench unless `--depth` is given
+pub const DEFAULT_SUMMARY_DEPTH: u8 = 5;
+/// Allowed slowdown in percent unless `--threshold` is given
+pub const DEFAULT_THRESHOLD_PERCENT: f64 = 5.0;
+
+type Metric = fn(&BenchSummary) -> u64;
+
+/// Throughput metrics, where higher is better
+const METRICS: [(&str, Metric); 3] = [
+    ("nodes_per_second", |summary| summary.nodes_per_second),
+    ("perft_nodes_per_second", |summary| {
+        summary.perft_nodes_per_second
+    }),
+    ("evaluations_per_second", |summary| {
+        summary.evaluations_per_second
+    }),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricChange {
+    pub name: &'static str,
+    pub old: u64,
+    pub new: u64,
+    /// Relative change in percent, negative when the new run is slower
+    pub change_percent: f64,
+    pub regressed: bool,
+}
+
+/// Compare every throughput metric, flagging drops larger than `threshold_percent`
+pub fn compare(
+    old: &BenchSummary,
+    new: &BenchSummary,
+    threshold_percent: f64,
+) -> Vec<MetricChange> {
+    METRICS
+        .iter()
+        .map(|&(name, metric)| {
+            let (old, new) = (metric(old), metric(new));
+            let change_percent = if old == 0 {
+                0.0
+            } else {
+                (new as f64 - old as f64) * 100.0 / old as f64
+            };
+            MetricChange {
+                name,
+                old,
+                new,
+                change_percent,
+                regressed: change_percent < -threshold_percent,
+            }
+        })
+        .collect()
+}
+
+/// Parse a threshold such as `5%` or `2.5`
+pub fn parse_threshold(value: &str) -> Result<f64, String> {
+    let number = value.trim().trim_end_matches('%');
+    match number.parse::<f64>() {
+        Ok(threshold) if threshold >= 0.0 => Ok(threshold),
+        _ => Err(format!("invalid threshold '{value}', expected e.g. 5%")),
+    }
+}
+
+/// `summary [--depth N] [--out FILE]`: print or save the JSON summary
+pub fn summary_command(args: &[String]) -> Result<(), String> {
+    let mut depth = DEFAULT_SUMMARY_DEPTH;
+    let mut out = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--depth" => {
+                depth = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .ok_or("--depth needs a number")?;
+            }
+            "--out" => out = Some(args.next().ok_or("--out needs a file name")?),
+            other => return Err(format!("unknown summary option '{other}'")),
+        }
+    }
+
+    let config = SearchConfig {
+        limits: SearchLimits::default().with_depth(depth),
+        ..SearchConfig::default()
+    };
+    let json =
+        serde_json::to_string_pretty(&run_summary(&config)).map_err(|error| error.to_string())?;
+
+    match out {
+        Some(path) => fs::write(path, json + "\n").map_err(|error| format!("{path}: {error}")),
+        None => {
+            println!("{json}");
+            Ok(())
+        }
+    }
+}
+
+/// `compare OLD NEW [--threshold P%]`: fail when any metric slowed down by more than P%
+pub fn compare_command(args: &[String]) -> Result<(), String> {
+    let mut files = Vec::new();
+    let mut threshold = DEFAULT_THRESHOLD_PERCENT;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--threshold" => {
+                threshold = parse_threshold(args.next().ok_or("--threshold needs a value")?)?;
+            }
+            file => files.push(file),
+        }
+    }
+
+    let [old_path, new_path] = files[..] else {
+        return Err("usage: compare OLD.json NEW.json [--threshold 5%]".to_string());
+    };
+    let old = read_summary(old_path)?;
+    let new = read_summary(new_path)?;
+
+    if old.search_nodes != new.search_nodes {
+        println!(
+            "note: bench nodes changed from {} to {}, the search itself differs",
+            old.search_nodes, new.search_nodes
+        );
+    }
+
+    let changes = compare(&old, &new, threshold);
+    for change in &changes {
+        println!(
+            "{:<24} {:>12} -> {:>12} {:>+8.2}%{}",
+            change.name,
+            change.old,
+            change.new,
+            change.change_percent,
+            if change.regressed { "  REGRESSION" } else { "" }
+        );
+    }
+
+    let regressions = changes.iter().filter(|change| change.regressed).count();
+    if regressions > 0 {
+        return Err(format!(
+            "{regressions} metric(s) slowed down by more than {threshold}%"
+        ));
+    }
+    Ok(())
+}
+
+fn read_summary(path: &str) -> Result<BenchSummary, String> {
+    let json = fs::read_to_string(path).map_err(|error| format!("{path}: {error}"))?;
+    serde_json::from_str(&json).map_err(|error| format!("{path}: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(nodes_per_second: u64, evaluations_per_second: u64) -> BenchSummary {
+        BenchSummary {
+            search_nodes: 1000,
+            nodes_per_second,
+            perft_nodes: 197_281,
+            perft_nodes_per_second: 10_000_000,
+            evaluations_per_second,
+        }
+    }
+
+    #[test]
+    fn test_compare_flags_only_large_slowdowns() {
+        let old = summary(1_000_000, 500_000);
+        let new = summary(940_000, 490_000);
+        let changes = compare(&old, &new, 5.0);
+
+        let nps = &changes[0];
+        assert_eq!(nps.name, "nodes_per_second");
+        assert!((nps.change_percent + 6.0).abs() < 1e-9);
+        assert!(nps.regressed);
+        // A 2% drop is within the threshold, and perft did not change
+        assert!(!changes[1].regressed);
+        assert!(!changes[2].regressed);
+
+        // Getting faster is never a regression
+        assert!(compare(&new, &old, 0.0)
+            .iter()
+            .all(|change| !change.regressed));
+    }
+
+    #[test]
+    fn test_parse_threshold() {
+        assert_eq!(parse_threshold("5%"), Ok(5.0));
+        assert_eq!(parse_threshold("2.5"), Ok(2.5));
+        assert!(parse_threshold("-1%").is_err());
+        assert!(parse_threshold("fast").is_err());
+    }
+
+    #[test]
+    fn test_summary_round_trips_through_json() {
+        let original = summary(1_000_000, 500_000);
+        let json = serde_json::to_string(&original).unwrap();
+        let parsed: BenchSummary = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, original);
+    }
+}
@@ -0,0 +1,16 @@
+// swift-tools-version:5.7
+import PackageDescription
+
+// Swift bindings over the chess-ffi C API.
+// Build the Rust library first (see README.md) so `libchess_ffi.a` can be linked.
+let package = Package(
+    name: "ChessEngine",
+    platforms: [.iOS(.v13), .macOS(.v10_15)],
+    products: [
+        .library(name: "ChessEngine", targets: ["ChessEngine"]),
+    ],
+    targets: [
+        .systemLibrary(name: "CChessEngine", path: "Sources/CChessEngine"),
+        .target(name: "ChessEngine", dependencies: ["CChessEngine"]),
+    ]
+)
@@ -0,0 +1,2 @@
+// The header is generated into the FFI crate by scripts/generate-header.sh
+#include "../../../../crates/chess-ffi/include/chess_engine.h"
@@ -0,0 +1,143 @@
+import CChessEngine
+import Foundation
+
+/// Swift wrapper over one native engine instance.
+///
+/// The native side locks each engine individually, so the instance can be shared across
+/// threads; progress can be read while a search is running.
+public final class ChessEngine: @unchecked Sendable {
+    private let handle: EngineId
+
+    /// Read from the linked library once, before the first engine is created
+    private static let abiVersion = chess_engine_abi_version()
+
+    private static func checkABI() throws {
+        let expected = UInt32(CHESS_ENGINE_ABI_VERSION)
+        guard abiVersion == expected else {
+            throw ChessEngineError.abiMismatch(expected: expected, found: abiVersion)
+        }
+    }
+
+    public init() throws {
+        try Self.checkABI()
+        handle = chess_engine_create()
+        guard handle > 0 else { throw ChessEngineError.creationFailed }
+        chess_engine_initialize(handle)
+    }
+
+    public init(fen: String) throws {
+        try Self.checkABI()
+        handle = fen.withCString { chess_engine_create_from_fen($0) }
+        guard handle > 0 else { throw ChessEngineError.invalidFen(fen) }
+        chess_engine_initialize(handle)
+    }
+
+    deinit {
+        chess_engine_destroy(handle)
+    }
+
+    // MARK: - Board state
+
+    public var fen: String {
+        guard let pointer = chess_engine_get_fen(handle) else { return "" }
+        defer { chess_engine_free_string(pointer) }
+        return String(cString: pointer)
+    }
+
+    public func load(fen: String) throws {
+        let loaded = fen.withCString { chess_engine_load_fen(handle, $0) }
+        guard loaded == 1 else { throw ChessEngineError.invalidFen(fen) }
+    }
+
+    /// The 64 squares from a1 to h8
+    public var board: [Piece?] {
+        var codes = [Int8](repeating: 0, count: 64)
+        _ = codes.withUnsafeMutableBufferPointer { chess_engine_get_board(handle, $0.baseAddress) }
+        return codes.map(Piece.init(code:))
+    }
+
+    public var gameInfo: GameInfo {
+        var info = CGameInfo()
+        chess_engine_get_game_info(handle, &info)
+        return GameInfo(info)
+    }
+
+    public var legalMoves: [ChessMove] {
+        let count = chess_engine_get_legal_moves(handle, nil, 0)
+        guard count > 0 else { return [] }
+
+        var moves = [CMove](repeating: CMove(), count: Int(count))
+        let written = moves.withUnsafeMutableBufferPointer {
+            chess_engine_get_legal_moves(handle, $0.baseAddress, $0.count)
+        }
+        return moves.prefix(Int(max(0, min(written, count)))).map(ChessMove.init)
+    }
+
+    public func makeMove(_ move: ChessMove) throws {
+        guard chess_engine_make_cmove(handle, move.cValue) == 1 else {
+            throw ChessEngineError.illegalMove(move.uci)
+        }
+    }
+
+    public func makeMove(uci: String) throws {
+        let made = uci.withCString { chess_engine_make_move(handle, $0) }
+        guard made == 1 else { throw ChessEngineError.illegalMove(uci) }
+    }
+
+    public func evaluate() -> Int {
+        Int(chess_engine_evaluate(handle))
+    }
+
+    // MARK: - Search
+
+    public func configure(_ configuration: Configuration) {
+        let milliseconds = configuration.timeLimit.map { UInt64(max(0, $0) * 1000) } ?? 0
+        chess_engine_configure(handle, Int32(configuration.depth), milliseconds)
+    }
+
+    /// Search the current position off the calling thread.
+    ///
+    /// `onProgress` is called on a background queue every `progressInterval` seconds
+    /// while the search runs.
+    public func search(
+        progressInterval: TimeInterval = 0.1,
+        onProgress: (@Sendable (SearchProgress) -> Void)? = nil
+    ) async throws -> SearchResult {
+        let handle = self.handle
+
+        return try await withCheckedThrowingContinuation { continuation in
+            let timer = onProgress.map { onProgress -> DispatchSourceTimer in
+                let source = DispatchSource.makeTimerSource(queue: DispatchQueue(label: "chess.engine.progress"))
+                source.schedule(deadline: .now() + progressInterval, repeating: progressInterval)
+                source.setEventHandler {
+                    var status = CSearchStatus()
+                    if chess_engine_get_search_status(handle, &status) == 1 {
+                        onProgress(SearchProgress(status))
+                    }
+                }
+                source.resume()
+                return source
+            }
+
+            // Capturing self keeps the engine alive until the search returns
+            DispatchQueue.global(qos: .userInitiated).async { [self] in
+                var result = CSearchResult()
+                let searched = chess_engine_search(self.handle, &result)
+                timer?.cancel()
+
+                if searched == 1 {
+                    continuation.resume(returning: SearchResult(result))
+                } else {
+                    continuation.resume(throwing: ChessEngineError.searchFailed)
+                }
+            }
+        }
+    }
+
+    /// Latest counters of the running or most recent search
+    public var searchProgress: SearchProgress {
+        var status = CSearchStatus()
+        chess_engine_get_search_status(handle, &status)
+        return SearchProgress(status)
+    }
+}
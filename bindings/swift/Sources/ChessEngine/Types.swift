@@ -0,0 +1,154 @@
+import CChessEngine
+import Foundation
+
+public enum Side: Int, Sendable {
+    case white = 0
+    case black = 1
+}
+
+public struct Piece: Hashable, Sendable {
+    public enum Kind: Int8, Sendable {
+        case pawn = 1, knight, bishop, rook, queen, king
+    }
+
+    public let kind: Kind
+    public let side: Side
+
+    /// Decode the signed board code used by `chess_engine_get_board`
+    init?(code: Int8) {
+        guard code != 0, let kind = Kind(rawValue: abs(code)) else { return nil }
+        self.kind = kind
+        self.side = code > 0 ? .white : .black
+    }
+}
+
+public struct ChessMove: Hashable, Sendable, CustomStringConvertible {
+    public enum Kind: UInt8, Sendable {
+        case normal = 0, capture, enPassant, castle, promotion, promotionCapture
+    }
+
+    /// Squares are indexed 0 (a1) to 63 (h8)
+    public let from: Int
+    public let to: Int
+    public let kind: Kind
+    public let promotion: Piece.Kind?
+
+    init(_ cMove: CMove) {
+        from = Int(cMove.from)
+        to = Int(cMove.to)
+        kind = Kind(rawValue: cMove.move_type) ?? .normal
+        promotion = cMove.promotion == 0 ? nil : Piece.Kind(rawValue: Int8(cMove.promotion) + 1)
+    }
+
+    var cValue: CMove {
+        let promotionCode = promotion.map { UInt8($0.rawValue - 1) } ?? 0
+        return CMove(from: UInt8(from), to: UInt8(to), move_type: kind.rawValue, promotion: promotionCode)
+    }
+
+    public var uci: String {
+        let files = Array("abcdefgh")
+        let square = { (index: Int) in "\(files[index % 8])\(index / 8 + 1)" }
+        let suffix: String
+        switch promotion {
+        case .knight: suffix = "n"
+        case .bishop: suffix = "b"
+        case .rook: suffix = "r"
+        case .queen: suffix = "q"
+        default: suffix = ""
+        }
+        return square(from) + square(to) + suffix
+    }
+
+    public var description: String { uci }
+}
+
+public enum GameResult: Int32, Sendable {
+    case ongoing = 0, whiteWins, blackWins, draw
+}
+
+public struct GameInfo: Sendable {
+    public let sideToMove: Side
+    public let isCheck: Bool
+    public let isCheckmate: Bool
+    public let isStalemate: Bool
+    public let isDraw: Bool
+    public let result: GameResult
+    public let halfmoveClock: Int
+    public let fullmoveNumber: Int
+    public let legalMoveCount: Int
+
+    init(_ info: CGameInfo) {
+        sideToMove = Side(rawValue: Int(info.side_to_move)) ?? .white
+        isCheck = info.is_check
+        isCheckmate = info.is_checkmate
+        isStalemate = info.is_stalemate
+        isDraw = info.is_draw
+        result = GameResult(rawValue: info.result) ?? .ongoing
+        halfmoveClock = Int(info.halfmove_clock)
+        fullmoveNumber = Int(info.fullmove_number)
+        legalMoveCount = Int(info.legal_move_count)
+    }
+}
+
+public struct SearchProgress: Sendable {
+    public let depth: Int
+    public let nodes: UInt64
+    public let nodesPerSecond: UInt64
+    /// Transposition table fill in permille
+    public let hashfull: Int
+    public let elapsed: TimeInterval
+
+    init(_ status: CSearchStatus) {
+        depth = Int(status.depth)
+        nodes = status.nodes
+        nodesPerSecond = status.nps
+        hashfull = Int(status.hashfull)
+        elapsed = TimeInterval(status.elapsed_ms) / 1000
+    }
+}
+
+public struct SearchResult: Sendable {
+    public let bestMove: ChessMove?
+    /// Centipawns from the side to move's point of view
+    public let evaluation: Int
+    public let depth: Int
+    public let nodes: UInt64
+    public let elapsed: TimeInterval
+    public let principalVariation: [ChessMove]
+
+    init(_ result: CSearchResult) {
+        bestMove = result.has_best_move ? ChessMove(result.best_move) : nil
+        evaluation = Int(result.evaluation)
+        depth = Int(result.depth)
+        nodes = result.nodes
+        elapsed = TimeInterval(result.elapsed_ms) / 1000
+
+        // The fixed-size C array is imported as a tuple
+        var pv = result.pv
+        let length = min(Int(result.pv_length), Int(CHESS_MAX_PV_LENGTH))
+        principalVariation = withUnsafeBytes(of: &pv) { raw in
+            raw.bindMemory(to: CMove.self).prefix(length).map(ChessMove.init)
+        }
+    }
+}
+
+public struct Configuration: Sendable {
+    /// Maximum search depth in plies (1-64)
+    public var depth: Int
+    /// Wall-clock limit per search; nil searches to full depth
+    public var timeLimit: TimeInterval?
+
+    public init(depth: Int = 8, timeLimit: TimeInterval? = 5) {
+        self.depth = depth
+        self.timeLimit = timeLimit
+    }
+}
+
+public enum ChessEngineError: Error {
+    case creationFailed
+    /// The linked library implements another version of the C interface than the header
+    case abiMismatch(expected: UInt32, found: UInt32)
+    case invalidFen(String)
+    case illegalMove(String)
+    case searchFailed
+}
@@ -120,7 +120,7 @@ impl SlowMoveGenerator {
 
                     // Test en passant for pawns
                     if piece.piece_type == PieceType::Pawn {
-                        if let Some(ep_target) = game_state.en_passant_target {
+                        if let Some(ep_target) = game_state.position.en_passant {
                             let ep_move = Move::en_passant(from, ep_target);
                             if self.is_move_legal(game_state, ep_move) {
                                 moves.push(ep_move);
@@ -94,9 +94,7 @@ fn benchmark_advanced_search(c: &mut Criterion) {
         // Basic alpha-beta search
         group.bench_with_input(BenchmarkId::new("alpha_beta", name), position, |b, pos| {
             let config = SearchConfig {
-                max_depth: 4,
-                max_time: None,
-                max_nodes: Some(10000),
+                limits: SearchLimits::default().with_depth(4).with_nodes(10000),
                 ..SearchConfig::default()
             };
             let mut engine = SearchEngine::new(config);
@@ -110,9 +108,7 @@ fn benchmark_advanced_search(c: &mut Criterion) {
             position,
             |b, pos| {
                 let config = SearchConfig {
-                    max_depth: 4,
-                    max_time: None,
-                    max_nodes: Some(10000),
+                    limits: SearchLimits::default().with_depth(4).with_nodes(10000),
                     use_null_move_pruning: false,
                     ..SearchConfig::default()
                 };
@@ -128,9 +124,7 @@ fn benchmark_advanced_search(c: &mut Criterion) {
             position,
             |b, pos| {
                 let config = SearchConfig {
-                    max_depth: 6,
-                    max_time: None,
-                    max_nodes: Some(50000),
+                    limits: SearchLimits::default().with_depth(6).with_nodes(50000),
                     ..SearchConfig::default()
                 };
                 let mut engine = SearchEngine::new(config);
@@ -156,11 +150,12 @@ fn benchmark_parallel_operations(c: &mut Criterion) {
         .collect();
 
     let parallel_config = ParallelConfig {
-        num_threads: rayon::current_num_threads(),
+        num_threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
         chunk_size: 10,
         enable_parallel_moves: true,
         enable_parallel_eval: true,
         enable_parallel_search: true,
+        ..ParallelConfig::default()
     };
 
     let sequential_config = ParallelConfig {
@@ -183,12 +178,12 @@ fn benchmark_parallel_operations(c: &mut Criterion) {
 
     // Parallel evaluation
     group.bench_function("parallel_evaluation", |b| {
-        let mut evaluator = ParallelEvaluator::new(parallel_config.clone());
+        let evaluator = ParallelEvaluator::new(parallel_config.clone());
         b.iter(|| evaluator.bulk_evaluate(&positions))
     });
 
     group.bench_function("sequential_evaluation", |b| {
-        let mut evaluator = ParallelEvaluator::new(sequential_config.clone());
+        let evaluator = ParallelEvaluator::new(sequential_config.clone());
         b.iter(|| evaluator.bulk_evaluate(&positions))
     });
 
@@ -387,6 +382,7 @@ fn benchmark_scaling_analysis(c: &mut Criterion) {
             enable_parallel_moves: true,
             enable_parallel_eval: true,
             enable_parallel_search: true,
+            ..ParallelConfig::default()
         };
 
         group.bench_with_input(
@@ -402,7 +398,7 @@ fn benchmark_scaling_analysis(c: &mut Criterion) {
             BenchmarkId::new("evaluation_scaling", thread_count),
             &thread_count,
             |b, _| {
-                let mut evaluator = ParallelEvaluator::new(config.clone());
+                let evaluator = ParallelEvaluator::new(config.clone());
                 b.iter(|| evaluator.bulk_evaluate(&positions))
             },
         );
@@ -455,16 +451,33 @@ fn benchmark_comprehensive_integration(c: &mut Criterion) {
             position_pool_size: 500,
             enable_prefetch: true,
             cache_line_alignment: true,
+            use_large_pages: false,
         };
 
         let search_config = SearchConfig {
-            max_depth: 5,
-            max_time: None,
-            max_nodes: Some(25000),
+            limits: SearchLimits::default().with_depth(5).with_nodes(25000),
             use_null_move_pruning: true,
             use_late_move_reductions: true,
             use_futility_pruning: true,
+            use_razoring: true,
+            razoring_margin: 150,
+            use_reverse_futility_pruning: true,
+            reverse_futility_margin: 90,
+            use_internal_iterative_reduction: true,
+            use_principal_variation_search: true,
+            use_qsearch_move_ordering: true,
+            use_delta_pruning: true,
+            delta_margin: 200,
+            use_qsearch_transposition_table: true,
+            use_see_pruning: true,
+            see_quiet_margin: 60,
+            use_passed_pawn_extension: true,
+            use_recapture_extension: true,
+            use_singular_extensions: true,
+            contempt: 0,
+            use_dynamic_contempt: true,
             aspiration_window: 50,
+            mate_verification: MateVerification::Off,
         };
 
         let parallel_config = ParallelConfig {
@@ -473,6 +486,7 @@ fn benchmark_comprehensive_integration(c: &mut Criterion) {
             enable_parallel_moves: true,
             enable_parallel_eval: true,
             enable_parallel_search: true,
+            ..ParallelConfig::default()
         };
 
         b.iter(|| {
@@ -498,13 +512,29 @@ fn benchmark_comprehensive_integration(c: &mut Criterion) {
     // Comparison with Phase 1 (basic) engine
     group.bench_function("phase1_basic_engine", |b| {
         let config = SearchConfig {
-            max_depth: 5,
-            max_time: None,
-            max_nodes: Some(25000),
+            limits: SearchLimits::default().with_depth(5).with_nodes(25000),
             use_null_move_pruning: false,
             use_late_move_reductions: false,
             use_futility_pruning: false,
+            use_razoring: false,
+            razoring_margin: 0,
+            use_reverse_futility_pruning: false,
+            reverse_futility_margin: 0,
+            use_internal_iterative_reduction: false,
+            use_principal_variation_search: false,
+            use_qsearch_move_ordering: false,
+            use_delta_pruning: false,
+            delta_margin: 0,
+            use_qsearch_transposition_table: false,
+            use_see_pruning: false,
+            see_quiet_margin: 0,
+            use_passed_pawn_extension: false,
+            use_recapture_extension: false,
+            use_singular_extensions: false,
+            contempt: 0,
+            use_dynamic_contempt: false,
             aspiration_window: 0,
+            mate_verification: MateVerification::Off,
         };
 
         b.iter(|| {
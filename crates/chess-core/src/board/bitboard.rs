@@ -1,3 +1,4 @@
+use crate::board::Square;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
@@ -5,6 +6,86 @@ use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, N
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Bitboard(pub u64);
 
+/// Compass directions for shifts and sliding rays, as seen from White's side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::South,
+        Direction::East,
+        Direction::West,
+        Direction::NorthEast,
+        Direction::NorthWest,
+        Direction::SouthEast,
+        Direction::SouthWest,
+    ];
+
+    /// File and rank step of a single move in this direction
+    #[inline]
+    pub const fn delta(self) -> (i8, i8) {
+        match self {
+            Direction::North => (0, 1),
+            Direction::South => (0, -1),
+            Direction::East => (1, 0),
+            Direction::West => (-1, 0),
+            Direction::NorthEast => (1, 1),
+            Direction::NorthWest => (-1, 1),
+            Direction::SouthEast => (1, -1),
+            Direction::SouthWest => (-1, -1),
+        }
+    }
+
+    pub const fn opposite(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::East => Direction::West,
+            Direction::West => Direction::East,
+            Direction::NorthEast => Direction::SouthWest,
+            Direction::NorthWest => Direction::SouthEast,
+            Direction::SouthEast => Direction::NorthWest,
+            Direction::SouthWest => Direction::NorthEast,
+        }
+    }
+
+    pub const fn is_diagonal(self) -> bool {
+        let (df, dr) = self.delta();
+        df != 0 && dr != 0
+    }
+
+    /// Direction leading from one square to another, if they share a rank, file or diagonal
+    pub const fn between(from: Square, to: Square) -> Option<Direction> {
+        let df = to.file() as i8 - from.file() as i8;
+        let dr = to.rank() as i8 - from.rank() as i8;
+
+        if (df == 0 && dr == 0) || (df != 0 && dr != 0 && df.abs() != dr.abs()) {
+            return None;
+        }
+
+        Some(match (df.signum(), dr.signum()) {
+            (0, 1) => Direction::North,
+            (0, _) => Direction::South,
+            (1, 0) => Direction::East,
+            (_, 0) => Direction::West,
+            (1, 1) => Direction::NorthEast,
+            (_, 1) => Direction::NorthWest,
+            (1, _) => Direction::SouthEast,
+            _ => Direction::SouthWest,
+        })
+    }
+}
+
 impl Bitboard {
     pub const EMPTY: Bitboard = Bitboard(0);
     pub const FULL: Bitboard = Bitboard(0xFFFFFFFFFFFFFFFF);
@@ -27,6 +108,28 @@ impl Bitboard {
     pub const FILE_G: Bitboard = Bitboard(0x4040404040404040);
     pub const FILE_H: Bitboard = Bitboard(0x8080808080808080);
 
+    pub const FILES: [Bitboard; 8] = [
+        Self::FILE_A,
+        Self::FILE_B,
+        Self::FILE_C,
+        Self::FILE_D,
+        Self::FILE_E,
+        Self::FILE_F,
+        Self::FILE_G,
+        Self::FILE_H,
+    ];
+
+    pub const RANKS: [Bitboard; 8] = [
+        Self::RANK_1,
+        Self::RANK_2,
+        Self::RANK_3,
+        Self::RANK_4,
+        Self::RANK_5,
+        Self::RANK_6,
+        Self::RANK_7,
+        Self::RANK_8,
+    ];
+
     pub const LIGHT_SQUARES: Bitboard = Bitboard(0x55AA55AA55AA55AA);
     pub const DARK_SQUARES: Bitboard = Bitboard(0xAA55AA55AA55AA55);
 
@@ -134,6 +237,74 @@ impl Bitboard {
         Bitboard((self.0 >> 9) & !Self::FILE_H.0)
     }
 
+    #[inline]
+    pub const fn shift(self, direction: Direction) -> Bitboard {
+        match direction {
+            Direction::North => self.shift_north(),
+            Direction::South => self.shift_south(),
+            Direction::East => self.shift_east(),
+            Direction::West => self.shift_west(),
+            Direction::NorthEast => self.shift_northeast(),
+            Direction::NorthWest => self.shift_northwest(),
+            Direction::SouthEast => self.shift_southeast(),
+            Direction::SouthWest => self.shift_southwest(),
+        }
+    }
+
+    #[inline]
+    pub const fn contains(self, square: Square) -> bool {
+        self.0 & (1u64 << square.index()) != 0
+    }
+
+    /// Files directly left and right of the given file
+    pub const fn adjacent_files(file: u8) -> Bitboard {
+        let file_bb = Self::FILES[file as usize];
+        Bitboard(file_bb.shift_east().0 | file_bb.shift_west().0)
+    }
+
+    /// Squares from `square` (exclusive) to the board edge in the given direction
+    pub const fn ray(square: Square, direction: Direction) -> Bitboard {
+        let (df, dr) = direction.delta();
+        let mut file = square.file() as i8 + df;
+        let mut rank = square.rank() as i8 + dr;
+        let mut ray = 0u64;
+
+        while file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+            ray |= 1u64 << (rank * 8 + file);
+            file += df;
+            rank += dr;
+        }
+
+        Bitboard(ray)
+    }
+
+    /// Squares strictly between two squares on a shared rank, file or diagonal
+    pub const fn between(from: Square, to: Square) -> Bitboard {
+        match Direction::between(from, to) {
+            Some(direction) => Bitboard(
+                Self::ray(from, direction).0 & !Self::ray(to, direction).0 & !to.bitboard().0,
+            ),
+            None => Self::EMPTY,
+        }
+    }
+
+    /// Full edge-to-edge line through two aligned squares, or empty if they are not aligned
+    pub const fn line(a: Square, b: Square) -> Bitboard {
+        match Direction::between(a, b) {
+            Some(direction) => Bitboard(
+                Self::ray(a, direction).0 | Self::ray(a, direction.opposite()).0 | a.bitboard().0,
+            ),
+            None => Self::EMPTY,
+        }
+    }
+
+    /// The king's square and every square adjacent to it
+    pub const fn king_zone(square: Square) -> Bitboard {
+        let king = square.bitboard();
+        let column = Bitboard(king.0 | king.shift_north().0 | king.shift_south().0);
+        Bitboard(column.0 | column.shift_east().0 | column.shift_west().0)
+    }
+
     pub fn iter(self) -> BitboardIterator {
         BitboardIterator { bb: self }
     }
@@ -300,6 +471,70 @@ mod tests {
         assert_eq!(bb.pop_lsb(), None);
     }
 
+    #[test]
+    fn test_file_and_rank_arrays() {
+        for i in 0..8u8 {
+            let square = Square::from_file_rank(i, i).unwrap();
+            assert!(Bitboard::FILES[i as usize].contains(square));
+            assert!(Bitboard::RANKS[i as usize].contains(square));
+            assert_eq!(Bitboard::FILES[i as usize].count_bits(), 8);
+        }
+        assert_eq!(Bitboard::adjacent_files(0), Bitboard::FILE_B);
+        assert_eq!(
+            Bitboard::adjacent_files(4),
+            Bitboard::FILE_D | Bitboard::FILE_F
+        );
+    }
+
+    #[test]
+    fn test_directional_shift() {
+        let bb = Square::E4.bitboard();
+        for direction in Direction::ALL {
+            let (df, dr) = direction.delta();
+            let expected = Square::from_file_rank((4 + df) as u8, (3 + dr) as u8).unwrap();
+            assert_eq!(bb.shift(direction), expected.bitboard());
+            assert_eq!(bb.shift(direction).shift(direction.opposite()), bb);
+        }
+        assert_eq!(
+            Square::H4.bitboard().shift(Direction::NorthEast),
+            Bitboard::EMPTY
+        );
+    }
+
+    #[test]
+    fn test_rays() {
+        assert_eq!(
+            Bitboard::ray(Square::A1, Direction::North),
+            Bitboard::FILE_A & !Square::A1.bitboard()
+        );
+        assert_eq!(
+            Bitboard::ray(Square::A1, Direction::NorthEast).count_bits(),
+            7
+        );
+        assert_eq!(Bitboard::ray(Square::H8, Direction::North), Bitboard::EMPTY);
+    }
+
+    #[test]
+    fn test_between_and_line() {
+        let between = Bitboard::between(Square::A1, Square::D4);
+        assert_eq!(between, Square::B2.bitboard() | Square::C3.bitboard());
+        assert_eq!(Bitboard::between(Square::D4, Square::A1), between);
+        assert_eq!(Bitboard::between(Square::E1, Square::E2), Bitboard::EMPTY);
+        assert_eq!(Bitboard::between(Square::A1, Square::B3), Bitboard::EMPTY);
+
+        assert_eq!(Bitboard::line(Square::C3, Square::E5).count_bits(), 8);
+        assert!(Bitboard::line(Square::C3, Square::E5).contains(Square::H8));
+        assert_eq!(Bitboard::line(Square::E2, Square::E7), Bitboard::FILE_E);
+        assert_eq!(Bitboard::line(Square::A1, Square::B3), Bitboard::EMPTY);
+    }
+
+    #[test]
+    fn test_king_zone() {
+        assert_eq!(Bitboard::king_zone(Square::E4).count_bits(), 9);
+        assert_eq!(Bitboard::king_zone(Square::A1).count_bits(), 4);
+        assert!(Bitboard::king_zone(Square::H8).contains(Square::G7));
+    }
+
     #[test]
     fn test_iterator() {
         let bb = Bitboard::new(0b1010);
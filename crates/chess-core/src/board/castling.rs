@@ -0,0 +1,159 @@
+use crate::{ChessError, Color, Piece, PieceType, Position, Result, Square};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CastlingRights {
+    pub white_kingside: bool,
+    pub white_queenside: bool,
+    pub black_kingside: bool,
+    pub black_queenside: bool,
+}
+
+impl CastlingRights {
+    pub const ALL: CastlingRights = CastlingRights {
+        white_kingside: true,
+        white_queenside: true,
+        black_kingside: true,
+        black_queenside: true,
+    };
+
+    pub const NONE: CastlingRights = CastlingRights {
+        white_kingside: false,
+        white_queenside: false,
+        black_kingside: false,
+        black_queenside: false,
+    };
+
+    pub fn can_castle_kingside(&self, color: Color) -> bool {
+        match color {
+            Color::White => self.white_kingside,
+            Color::Black => self.black_kingside,
+        }
+    }
+
+    pub fn can_castle_queenside(&self, color: Color) -> bool {
+        match color {
+            Color::White => self.white_queenside,
+            Color::Black => self.black_queenside,
+        }
+    }
+
+    pub fn remove_kingside(&mut self, color: Color) {
+        match color {
+            Color::White => self.white_kingside = false,
+            Color::Black => self.black_kingside = false,
+        }
+    }
+
+    pub fn remove_queenside(&mut self, color: Color) {
+        match color {
+            Color::White => self.white_queenside = false,
+            Color::Black => self.black_queenside = false,
+        }
+    }
+
+    pub fn remove_all(&mut self, color: Color) {
+        self.remove_kingside(color);
+        self.remove_queenside(color);
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut result = String::new();
+        if self.white_kingside {
+            result.push('K');
+        }
+        if self.white_queenside {
+            result.push('Q');
+        }
+        if self.black_kingside {
+            result.push('k');
+        }
+        if self.black_queenside {
+            result.push('q');
+        }
+        if result.is_empty() {
+            result.push('-');
+        }
+        result
+    }
+}
+
+impl std::fmt::Display for CastlingRights {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_fen())
+    }
+}
+
+impl CastlingRights {
+    /// Pack the four rights into the low four bits (e.g. for Zobrist hashing)
+    pub fn index(&self) -> u8 {
+        self.white_kingside as u8
+            | (self.white_queenside as u8) << 1
+            | (self.black_kingside as u8) << 2
+            | (self.black_queenside as u8) << 3
+    }
+
+    /// The rights `position`'s pieces still allow: the king on e1/e8 and the rook on
+    /// the corner of that side
+    pub fn possible_in(&self, position: &Position) -> CastlingRights {
+        let allowed = |color: Color, kingside: bool| {
+            let (king, rook) = match (color, kingside) {
+                (Color::White, true) => (Square::E1, Square::H1),
+                (Color::White, false) => (Square::E1, Square::A1),
+                (Color::Black, true) => (Square::E8, Square::H8),
+                (Color::Black, false) => (Square::E8, Square::A8),
+            };
+            position.piece_at(king) == Some(Piece::new(PieceType::King, color))
+                && position.piece_at(rook) == Some(Piece::new(PieceType::Rook, color))
+        };
+
+        CastlingRights {
+            white_kingside: self.white_kingside && allowed(Color::White, true),
+            white_queenside: self.white_queenside && allowed(Color::White, false),
+            black_kingside: self.black_kingside && allowed(Color::Black, true),
+            black_queenside: self.black_queenside && allowed(Color::Black, false),
+        }
+    }
+
+    /// Drop the rights lost by a move leaving or landing on a king or rook home square
+    pub fn update_for_move(&mut self, from: Square, to: Square) {
+        match from {
+            Square::E1 => self.remove_all(Color::White),
+            Square::E8 => self.remove_all(Color::Black),
+            _ => {}
+        }
+
+        for square in [from, to] {
+            match square {
+                Square::A1 => self.remove_queenside(Color::White),
+                Square::H1 => self.remove_kingside(Color::White),
+                Square::A8 => self.remove_queenside(Color::Black),
+                Square::H8 => self.remove_kingside(Color::Black),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn from_string(s: &str) -> Result<Self> {
+        if s == "-" {
+            return Ok(CastlingRights::NONE);
+        }
+
+        let mut rights = CastlingRights::NONE;
+        for ch in s.chars() {
+            match ch {
+                'K' => rights.white_kingside = true,
+                'Q' => rights.white_queenside = true,
+                'k' => rights.black_kingside = true,
+                'q' => rights.black_queenside = true,
+                _ => {
+                    return Err(ChessError::ParseError(format!(
+                        "Invalid castling rights: {}",
+                        ch
+                    )))
+                }
+            }
+        }
+        Ok(rights)
+    }
+}
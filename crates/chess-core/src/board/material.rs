@@ -0,0 +1,184 @@
+use crate::pieces::{Color, PieceType};
+use std::fmt;
+
+/// Piece types tracked by a material key; kings are always present and not counted
+const COUNTED_PIECES: [PieceType; 5] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+];
+
+/// Packed piece counts for both sides, four bits per (color, piece type).
+///
+/// Two positions share a key exactly when they have the same material, which makes
+/// it a cheap lookup key for endgame specialization and insufficient-material checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct MaterialKey(u64);
+
+impl MaterialKey {
+    const BITS_PER_COUNT: u32 = 4;
+    const COUNT_MASK: u64 = 0xF;
+
+    /// Build a key from per-side counts ordered pawn, knight, bishop, rook, queen
+    pub fn from_counts(white: [u8; 5], black: [u8; 5]) -> Self {
+        let mut key = MaterialKey(0);
+        for (i, &piece_type) in COUNTED_PIECES.iter().enumerate() {
+            key.set_count(piece_type, Color::White, white[i] as u32);
+            key.set_count(piece_type, Color::Black, black[i] as u32);
+        }
+        key
+    }
+
+    #[inline]
+    pub const fn value(self) -> u64 {
+        self.0
+    }
+
+    /// Number of pieces of the given type and color (kings always count as one)
+    #[inline]
+    pub fn count(self, piece_type: PieceType, color: Color) -> u32 {
+        if piece_type == PieceType::King {
+            return 1;
+        }
+        ((self.0 >> Self::shift(piece_type, color)) & Self::COUNT_MASK) as u32
+    }
+
+    /// Knights, bishops, rooks and queens of the given color
+    pub fn non_pawn_count(self, color: Color) -> u32 {
+        COUNTED_PIECES[1..]
+            .iter()
+            .map(|&piece_type| self.count(piece_type, color))
+            .sum()
+    }
+
+    pub fn minor_count(self, color: Color) -> u32 {
+        self.count(PieceType::Knight, color) + self.count(PieceType::Bishop, color)
+    }
+
+    pub fn major_count(self, color: Color) -> u32 {
+        self.count(PieceType::Rook, color) + self.count(PieceType::Queen, color)
+    }
+
+    pub fn has_pawns(self) -> bool {
+        self.count(PieceType::Pawn, Color::White) + self.count(PieceType::Pawn, Color::Black) > 0
+    }
+
+    /// The same material with the colors swapped
+    pub fn mirrored(self) -> Self {
+        let mut mirrored = MaterialKey(0);
+        for &piece_type in &COUNTED_PIECES {
+            mirrored.set_count(
+                piece_type,
+                Color::White,
+                self.count(piece_type, Color::Black),
+            );
+            mirrored.set_count(
+                piece_type,
+                Color::Black,
+                self.count(piece_type, Color::White),
+            );
+        }
+        mirrored
+    }
+
+    pub(crate) fn set_count(&mut self, piece_type: PieceType, color: Color, count: u32) {
+        let shift = Self::shift(piece_type, color);
+        let count = (count as u64).min(Self::COUNT_MASK);
+        self.0 = (self.0 & !(Self::COUNT_MASK << shift)) | (count << shift);
+    }
+
+    #[inline]
+    fn shift(piece_type: PieceType, color: Color) -> u32 {
+        (color.index() * COUNTED_PIECES.len() + piece_type.index()) as u32 * Self::BITS_PER_COUNT
+    }
+}
+
+impl fmt::Display for MaterialKey {
+    /// Signature such as `KRPvKR`, strongest pieces first
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, color) in [Color::White, Color::Black].into_iter().enumerate() {
+            if i > 0 {
+                write!(f, "v")?;
+            }
+            write!(f, "K")?;
+            for &piece_type in COUNTED_PIECES.iter().rev() {
+                let symbol = match piece_type {
+                    PieceType::Pawn => 'P',
+                    PieceType::Knight => 'N',
+                    PieceType::Bishop => 'B',
+                    PieceType::Rook => 'R',
+                    PieceType::Queen => 'Q',
+                    PieceType::King => 'K',
+                };
+                for _ in 0..self.count(piece_type, color) {
+                    write!(f, "{}", symbol)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Position, Square};
+    use crate::pieces::Piece;
+
+    #[test]
+    fn test_starting_position_key() {
+        let key = Position::starting_position().material_key();
+        assert_eq!(key.count(PieceType::Pawn, Color::White), 8);
+        assert_eq!(key.count(PieceType::Knight, Color::Black), 2);
+        assert_eq!(key.count(PieceType::Queen, Color::White), 1);
+        assert_eq!(key.count(PieceType::King, Color::Black), 1);
+        assert_eq!(key.non_pawn_count(Color::White), 7);
+        assert_eq!(
+            key,
+            MaterialKey::from_counts([8, 2, 2, 2, 1], [8, 2, 2, 2, 1])
+        );
+        assert_eq!(key.to_string(), "KQRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP");
+    }
+
+    #[test]
+    fn test_key_ignores_placement() {
+        let mut a = Position::new();
+        a.place_piece(Square::E1, Piece::new(PieceType::King, Color::White));
+        a.place_piece(Square::A1, Piece::new(PieceType::Rook, Color::White));
+        a.place_piece(Square::E8, Piece::new(PieceType::King, Color::Black));
+
+        let mut b = Position::new();
+        b.place_piece(Square::G1, Piece::new(PieceType::King, Color::White));
+        b.place_piece(Square::D4, Piece::new(PieceType::Rook, Color::White));
+        b.place_piece(Square::H8, Piece::new(PieceType::King, Color::Black));
+
+        assert_eq!(a.material_key(), b.material_key());
+        assert_eq!(a.material_key().to_string(), "KRvK");
+    }
+
+    #[test]
+    fn test_piece_list_matches_board() {
+        let position = Position::starting_position();
+
+        for color in [Color::White, Color::Black] {
+            let list: Vec<(Square, Piece)> = position.piece_list(color).collect();
+            assert_eq!(list.len(), 16);
+            assert_eq!(list.first().unwrap().1.piece_type, PieceType::Pawn);
+            assert_eq!(list.last().unwrap().1.piece_type, PieceType::King);
+            for (square, piece) in list {
+                assert_eq!(position.piece_at(square), Some(piece));
+                assert_eq!(piece.color, color);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mirrored_key() {
+        let key = MaterialKey::from_counts([1, 0, 0, 1, 0], [0, 0, 0, 1, 0]);
+        assert_eq!(key.to_string(), "KRPvKR");
+        assert_eq!(key.mirrored().to_string(), "KRvKRP");
+        assert_eq!(key.mirrored().mirrored(), key);
+    }
+}
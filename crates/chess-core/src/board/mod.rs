@@ -1,7 +1,13 @@
 pub mod bitboard;
+pub mod castling;
+pub mod material;
 pub mod position;
 pub mod square;
+pub mod variant;
 
-pub use bitboard::Bitboard;
-pub use position::{Position, UndoInfo};
+pub use bitboard::{Bitboard, Direction};
+pub use castling::CastlingRights;
+pub use material::MaterialKey;
+pub use position::{CastleSquares, FenWarning, Position, UndoInfo};
 pub use square::Square;
+pub use variant::{Hand, Variant, MAX_HAND_COUNT};
@@ -1,14 +1,65 @@
-use crate::board::{Bitboard, Square};
+use crate::board::variant::CHECKS_TO_WIN;
+use crate::board::{Bitboard, CastlingRights, Hand, MaterialKey, Square, Variant};
 use crate::error::{ChessError, Result};
-use crate::moves::Move;
+use crate::evaluation::GamePhase;
+use crate::moves::{shared_move_generator, Move};
 use crate::pieces::{Color, Piece, PieceType};
+use crate::utils::zobrist::zobrist_keys;
 
+/// A repair `Position::from_fen_lenient` made to its FEN
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FenWarning {
+    /// Castling rights dropped because their king or rook is not in place
+    ImpossibleCastlingRights(CastlingRights),
+}
+
+impl std::fmt::Display for FenWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FenWarning::ImpossibleCastlingRights(rights) => write!(
+                f,
+                "castling rights {} dropped: king or rook not in place",
+                rights
+            ),
+        }
+    }
+}
+
+/// Everything `undo_move` needs to restore the position before a move
 #[derive(Debug, Clone)]
 pub struct UndoInfo {
     pub captured_piece: Option<Piece>,
+    /// Square the captured piece stood on; differs from the destination for en passant
+    pub captured_square: Option<Square>,
     pub previous_side_to_move: Color,
+    pub previous_castling_rights: CastlingRights,
+    pub previous_en_passant: Option<Square>,
+    pub previous_halfmove_clock: u32,
+    pub previous_fullmove_number: u32,
+    pub castle: Option<CastleSquares>,
+    pub previous_hands: [Hand; 2],
+    pub previous_promoted: Bitboard,
+    pub previous_checks_given: [u8; 2],
+}
+
+/// King and rook squares of a castling move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CastleSquares {
+    pub king_from: Square,
+    pub king_to: Square,
+    pub rook_from: Square,
+    pub rook_to: Square,
 }
 
+/// A chess position.
+///
+/// The Zobrist hash is kept up to date by the methods that change the position. Code
+/// that assigns `side_to_move`, `castling_rights` or `en_passant` directly must call
+/// `refresh_zobrist_hash` afterwards.
+///
+/// In drop variants such as Crazyhouse, `hands` holds the captured pieces each side may
+/// drop and `promoted` marks promoted pieces, which go back to a hand as pawns. In
+/// Three-check, `checks_given` counts the checks each side has delivered.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Position {
     pub pieces: [Bitboard; 12],
@@ -16,17 +67,37 @@ pub struct Position {
     pub all_occupied: Bitboard,
     pub board: [Option<Piece>; 64],
     pub side_to_move: Color,
+    pub castling_rights: CastlingRights,
+    pub en_passant: Option<Square>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+    pub variant: Variant,
+    pub hands: [Hand; 2],
+    pub promoted: Bitboard,
+    pub checks_given: [u8; 2],
+    hash: u64,
 }
 
 impl Position {
     pub fn new() -> Self {
-        Position {
+        let mut position = Position {
             pieces: [Bitboard::EMPTY; 12],
             occupied: [Bitboard::EMPTY; 2],
             all_occupied: Bitboard::EMPTY,
             board: [None; 64],
             side_to_move: Color::White,
-        }
+            castling_rights: CastlingRights::NONE,
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            variant: Variant::Standard,
+            hands: [Hand::default(); 2],
+            promoted: Bitboard::EMPTY,
+            checks_given: [0; 2],
+            hash: 0,
+        };
+        position.refresh_zobrist_hash();
+        position
     }
 
     pub fn starting_position() -> Self {
@@ -79,6 +150,8 @@ impl Position {
         position.board[Square::H8.index() as usize] =
             Some(Piece::new(PieceType::Rook, Color::Black));
 
+        position.castling_rights = CastlingRights::ALL;
+
         // Update bitboards
         position.update_bitboards();
 
@@ -100,75 +173,421 @@ impl Position {
         self.pieces[piece.index()] |= square_bb;
         self.occupied[piece.color.index()] |= square_bb;
         self.all_occupied |= square_bb;
+        self.hash ^= zobrist_keys().hash_piece(piece, square);
     }
 
     pub fn make_move(&mut self, mv: Move) -> Result<UndoInfo> {
+        if let Some(piece_type) = mv.drop_piece() {
+            return self.make_drop_move(mv.to, piece_type);
+        }
+
         let moving_piece = self
             .piece_at(mv.from)
             .ok_or_else(|| ChessError::InvalidMove("No piece at source square".to_string()))?;
-
-        let piece_type = moving_piece.piece_type;
         let color = moving_piece.color;
 
-        // Store undo information
-        let captured_piece = self.piece_at(mv.to);
+        if color != self.side_to_move {
+            return Err(ChessError::InvalidMove(
+                "Piece does not belong to the side to move".to_string(),
+            ));
+        }
+
+        if mv.is_castle() {
+            return self.make_castle_move(mv);
+        }
+
+        let is_pawn = moving_piece.piece_type == PieceType::Pawn;
+        let captured_square = self.captured_square(mv, moving_piece)?;
+        let captured_piece = self.piece_at(captured_square);
+        if let Some(captured) = captured_piece {
+            if captured.color == color {
+                return Err(ChessError::InvalidMove(
+                    "Cannot capture own piece".to_string(),
+                ));
+            }
+            if captured.piece_type == PieceType::King {
+                return Err(ChessError::InvalidMove(
+                    "Cannot capture the king".to_string(),
+                ));
+            }
+        }
+
+        let last_rank = match color {
+            Color::White => 7,
+            Color::Black => 0,
+        };
+        let placed_piece = match mv.promotion_piece() {
+            Some(PieceType::Pawn) | Some(PieceType::King) => {
+                return Err(ChessError::InvalidMove(
+                    "Invalid promotion piece".to_string(),
+                ));
+            }
+            Some(promotion) if is_pawn && mv.to.rank() == last_rank => Piece::new(promotion, color),
+            Some(_) => {
+                return Err(ChessError::InvalidMove(
+                    "Only a pawn reaching the last rank can promote".to_string(),
+                ));
+            }
+            None if is_pawn && mv.to.rank() == last_rank => {
+                return Err(ChessError::InvalidMove(
+                    "Promotion piece required".to_string(),
+                ));
+            }
+            None => moving_piece,
+        };
+
         let undo_info = UndoInfo {
             captured_piece,
+            captured_square: captured_piece.map(|_| captured_square),
             previous_side_to_move: self.side_to_move,
+            previous_castling_rights: self.castling_rights,
+            previous_en_passant: self.en_passant,
+            previous_halfmove_clock: self.halfmove_clock,
+            previous_fullmove_number: self.fullmove_number,
+            castle: None,
+            previous_hands: self.hands,
+            previous_promoted: self.promoted,
+            previous_checks_given: self.checks_given,
+        };
+
+        if captured_piece.is_some() {
+            self.remove_piece(captured_square);
+        }
+        self.remove_piece(mv.from);
+        self.place_piece(mv.to, placed_piece);
+
+        let state = self.state_hash();
+        if self.variant.has_drops() {
+            if let Some(captured) = captured_piece {
+                let held = if self.promoted.contains(captured_square) {
+                    PieceType::Pawn
+                } else {
+                    captured.piece_type
+                };
+                self.hands[color.index()].add(held);
+                self.promoted &= !captured_square.bitboard();
+            }
+            if self.promoted.contains(mv.from) || mv.is_promotion() {
+                self.promoted = (self.promoted & !mv.from.bitboard()) | mv.to.bitboard();
+            }
+        }
+        self.en_passant = if is_pawn && mv.from.rank().abs_diff(mv.to.rank()) == 2 {
+            Square::from_file_rank(mv.from.file(), (mv.from.rank() + mv.to.rank()) / 2)
+        } else {
+            None
         };
+        self.castling_rights.update_for_move(mv.from, mv.to);
+        if is_pawn || captured_piece.is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        self.record_check(color);
+        self.pass_turn();
+        self.hash ^= state ^ self.state_hash();
 
-        // Remove piece from source square
-        self.board[mv.from.index() as usize] = None;
-        self.pieces[color.index() * 6 + piece_type.index()] &= !mv.from.bitboard();
+        Ok(undo_info)
+    }
 
-        // If there's a capture, remove the captured piece
-        if let Some(captured_piece) = captured_piece {
-            let captured_type = captured_piece.piece_type;
-            let captured_color = captured_piece.color;
-            self.pieces[captured_color.index() * 6 + captured_type.index()] &= !mv.to.bitboard();
+    pub fn undo_move(&mut self, mv: Move, undo_info: UndoInfo) {
+        if mv.is_drop() {
+            self.remove_piece(mv.to);
+        } else if let Some(castle) = undo_info.castle {
+            let king = self.piece_at(castle.king_to);
+            let rook = self.piece_at(castle.rook_to);
+            self.remove_piece(castle.king_to);
+            self.remove_piece(castle.rook_to);
+            if let (Some(king), Some(rook)) = (king, rook) {
+                self.place_piece(castle.king_from, king);
+                self.place_piece(castle.rook_from, rook);
+            }
+        } else {
+            let placed_piece = self
+                .piece_at(mv.to)
+                .expect("Piece should be at destination square");
+            let moving_piece = if mv.is_promotion() {
+                Piece::new(PieceType::Pawn, placed_piece.color)
+            } else {
+                placed_piece
+            };
+
+            self.remove_piece(mv.to);
+            if let (Some(captured_piece), Some(captured_square)) =
+                (undo_info.captured_piece, undo_info.captured_square)
+            {
+                self.place_piece(captured_square, captured_piece);
+            }
+            self.place_piece(mv.from, moving_piece);
         }
 
-        // Place piece on destination square
-        self.board[mv.to.index() as usize] = Some(moving_piece);
-        self.pieces[color.index() * 6 + piece_type.index()] |= mv.to.bitboard();
+        let state = self.state_hash();
+        self.side_to_move = undo_info.previous_side_to_move;
+        self.castling_rights = undo_info.previous_castling_rights;
+        self.en_passant = undo_info.previous_en_passant;
+        self.hands = undo_info.previous_hands;
+        self.checks_given = undo_info.previous_checks_given;
+        self.hash ^= state ^ self.state_hash();
+        self.promoted = undo_info.previous_promoted;
+        self.halfmove_clock = undo_info.previous_halfmove_clock;
+        self.fullmove_number = undo_info.previous_fullmove_number;
+    }
+
+    /// Square holding the piece a move captures; only en passant differs from the destination
+    fn captured_square(&self, mv: Move, moving_piece: Piece) -> Result<Square> {
+        let diagonal_pawn_move =
+            moving_piece.piece_type == PieceType::Pawn && mv.from.file() != mv.to.file();
+        let en_passant =
+            diagonal_pawn_move && self.piece_at(mv.to).is_none() && self.en_passant == Some(mv.to);
 
-        // Update occupied bitboards
-        self.occupied[Color::White.index()] = Bitboard::EMPTY;
-        self.occupied[Color::Black.index()] = Bitboard::EMPTY;
-        for piece_type in PieceType::ALL {
-            self.occupied[Color::White.index()] |=
-                self.pieces[Color::White.index() * 6 + piece_type.index()];
-            self.occupied[Color::Black.index()] |=
-                self.pieces[Color::Black.index() * 6 + piece_type.index()];
+        if mv.is_en_passant() && !en_passant {
+            return Err(ChessError::InvalidMove(
+                "No en passant capture available".to_string(),
+            ));
         }
-        self.all_occupied = self.occupied[0] | self.occupied[1];
 
+        if en_passant {
+            Ok(Square::from_file_rank(mv.to.file(), mv.from.rank()).unwrap())
+        } else {
+            Ok(mv.to)
+        }
+    }
+
+    /// Count a check just given by `mover` when playing Three-check
+    fn record_check(&mut self, mover: Color) {
+        if self.variant != Variant::ThreeCheck {
+            return;
+        }
+        let Some(king) = self.king_square(mover.opposite()) else {
+            return;
+        };
+        if shared_move_generator().is_square_attacked(king, mover, self.all_occupied, &self.pieces)
+        {
+            self.checks_given[mover.index()] = self.checks_given[mover.index()].saturating_add(1);
+        }
+    }
+
+    /// Side that has won by the variant's own rule: a third check in Three-check, or
+    /// the capture of the whole horde. Checkmate and draws are left to `GameState`.
+    pub fn variant_winner(&self) -> Option<Color> {
+        match self.variant {
+            Variant::ThreeCheck => [Color::White, Color::Black]
+                .into_iter()
+                .find(|color| self.checks_given[color.index()] >= CHECKS_TO_WIN),
+            Variant::Horde if self.pieces_of_color(Color::White).is_empty() => Some(Color::Black),
+            _ => None,
+        }
+    }
+
+    /// Hand the move to the other side, advancing the fullmove number after Black moves
+    fn pass_turn(&mut self) {
+        if self.side_to_move == Color::Black {
+            self.fullmove_number += 1;
+        }
         self.side_to_move = self.side_to_move.opposite();
+    }
 
-        Ok(undo_info)
+    /// Resolve the king and rook squares of a castling move.
+    ///
+    /// Accepts both the standard encoding (king moves two squares) and the Chess960
+    /// encoding (king "captures" its own rook).
+    pub fn castle_squares(&self, mv: Move) -> Result<CastleSquares> {
+        let king = self
+            .piece_at(mv.from)
+            .filter(|piece| piece.piece_type == PieceType::King)
+            .ok_or_else(|| {
+                ChessError::InvalidMove("Castling move must be made by the king".to_string())
+            })?;
+
+        let back_rank = match king.color {
+            Color::White => 0,
+            Color::Black => 7,
+        };
+        if mv.from.rank() != back_rank || mv.to.rank() != back_rank || mv.from == mv.to {
+            return Err(ChessError::InvalidMove(
+                "Castling must stay on the back rank".to_string(),
+            ));
+        }
+
+        let own_rook = Piece::new(PieceType::Rook, king.color);
+        let kingside = mv.to.file() > mv.from.file();
+
+        let rook_from = if self.piece_at(mv.to) == Some(own_rook) {
+            mv.to
+        } else {
+            // Nearest rook beyond the king on the castling side
+            let files: Vec<u8> = if kingside {
+                (mv.from.file() + 1..8).collect()
+            } else {
+                (0..mv.from.file()).rev().collect()
+            };
+            files
+                .into_iter()
+                .find_map(|file| {
+                    let square = Square::from_file_rank(file, back_rank)?;
+                    (self.piece_at(square) == Some(own_rook)).then_some(square)
+                })
+                .ok_or_else(|| {
+                    ChessError::InvalidMove("No rook available for castling".to_string())
+                })?
+        };
+
+        let (king_file, rook_file) = if kingside { (6, 5) } else { (2, 3) };
+        let king_to = Square::from_file_rank(king_file, back_rank).unwrap();
+        if rook_from != mv.to && king_to != mv.to {
+            return Err(ChessError::InvalidMove(format!(
+                "Castling king must land on {}",
+                king_to
+            )));
+        }
+
+        Ok(CastleSquares {
+            king_from: mv.from,
+            king_to,
+            rook_from,
+            rook_to: Square::from_file_rank(rook_file, back_rank).unwrap(),
+        })
     }
 
-    pub fn undo_move(&mut self, mv: Move, undo_info: UndoInfo) {
-        let moving_piece = self
-            .piece_at(mv.to)
-            .expect("Piece should be at destination square");
-        let piece_type = moving_piece.piece_type;
-        let color = moving_piece.color;
+    fn make_castle_move(&mut self, mv: Move) -> Result<UndoInfo> {
+        let castle = self.castle_squares(mv)?;
+        let color = self.piece_at(castle.king_from).unwrap().color;
 
-        // Remove piece from destination square
-        self.board[mv.to.index() as usize] = None;
-        self.pieces[color.index() * 6 + piece_type.index()] &= !mv.to.bitboard();
+        let has_right = if castle.king_to.file() == 6 {
+            self.castling_rights.can_castle_kingside(color)
+        } else {
+            self.castling_rights.can_castle_queenside(color)
+        };
+        if !has_right {
+            return Err(ChessError::InvalidMove(
+                "No castling right for this side".to_string(),
+            ));
+        }
 
-        // Restore captured piece if there was one
-        if let Some(captured_piece) = undo_info.captured_piece {
-            self.place_piece(mv.to, captured_piece);
+        // Only the castling king and rook may stand on the squares either piece crosses
+        let occupied =
+            self.all_occupied & !castle.king_from.bitboard() & !castle.rook_from.bitboard();
+        let king_path = Bitboard::between(castle.king_from, castle.king_to)
+            | castle.king_to.bitboard()
+            | castle.king_from.bitboard();
+        let rook_path = Bitboard::between(castle.rook_from, castle.rook_to)
+            | castle.rook_to.bitboard()
+            | castle.rook_from.bitboard();
+        if (king_path | rook_path) & occupied != Bitboard::EMPTY {
+            return Err(ChessError::InvalidMove(
+                "Castling path is blocked".to_string(),
+            ));
         }
 
-        // Place moving piece back to source square
-        self.place_piece(mv.from, moving_piece);
+        let generator = shared_move_generator();
+        let attacked = |square: Square| {
+            generator.is_square_attacked(square, color.opposite(), occupied, &self.pieces)
+        };
+        if attacked(castle.king_from) {
+            return Err(ChessError::InvalidMove(
+                "Cannot castle out of check".to_string(),
+            ));
+        }
+        if Bitboard::between(castle.king_from, castle.king_to)
+            .iter()
+            .any(|square_idx| attacked(Square::from(square_idx)))
+        {
+            return Err(ChessError::InvalidMove(
+                "Cannot castle through an attacked square".to_string(),
+            ));
+        }
+        if attacked(castle.king_to) {
+            return Err(ChessError::InvalidMove(
+                "Cannot castle into check".to_string(),
+            ));
+        }
 
-        // Restore side to move
-        self.side_to_move = undo_info.previous_side_to_move;
+        let king = Piece::new(PieceType::King, color);
+        let rook = Piece::new(PieceType::Rook, color);
+        self.remove_piece(castle.king_from);
+        self.remove_piece(castle.rook_from);
+        self.place_piece(castle.king_to, king);
+        self.place_piece(castle.rook_to, rook);
+
+        let undo_info = UndoInfo {
+            captured_piece: None,
+            captured_square: None,
+            previous_side_to_move: self.side_to_move,
+            previous_castling_rights: self.castling_rights,
+            previous_en_passant: self.en_passant,
+            previous_halfmove_clock: self.halfmove_clock,
+            previous_fullmove_number: self.fullmove_number,
+            castle: Some(castle),
+            previous_hands: self.hands,
+            previous_promoted: self.promoted,
+            previous_checks_given: self.checks_given,
+        };
+
+        let state = self.state_hash();
+        self.castling_rights.remove_all(color);
+        self.en_passant = None;
+        self.halfmove_clock += 1;
+        self.record_check(color);
+        self.pass_turn();
+        self.hash ^= state ^ self.state_hash();
+
+        Ok(undo_info)
+    }
+
+    /// Put a piece from the side to move's hand on the empty square `to`
+    fn make_drop_move(&mut self, to: Square, piece_type: PieceType) -> Result<UndoInfo> {
+        let color = self.side_to_move;
+        if !self.variant.has_drops() {
+            return Err(ChessError::InvalidMove(
+                "Drops are not allowed in this variant".to_string(),
+            ));
+        }
+        if self.piece_at(to).is_some() {
+            return Err(ChessError::InvalidMove(format!(
+                "Cannot drop on occupied square {}",
+                to
+            )));
+        }
+        if piece_type == PieceType::Pawn && (to.rank() == 0 || to.rank() == 7) {
+            return Err(ChessError::InvalidMove(
+                "Pawns cannot be dropped on the first or last rank".to_string(),
+            ));
+        }
+        if self.hands[color.index()].count(piece_type) == 0 {
+            return Err(ChessError::InvalidMove(format!(
+                "No {:?} in hand",
+                piece_type
+            )));
+        }
+
+        let undo_info = UndoInfo {
+            captured_piece: None,
+            captured_square: None,
+            previous_side_to_move: self.side_to_move,
+            previous_castling_rights: self.castling_rights,
+            previous_en_passant: self.en_passant,
+            previous_halfmove_clock: self.halfmove_clock,
+            previous_fullmove_number: self.fullmove_number,
+            castle: None,
+            previous_hands: self.hands,
+            previous_promoted: self.promoted,
+            previous_checks_given: self.checks_given,
+        };
+
+        self.place_piece(to, Piece::new(piece_type, color));
+
+        let state = self.state_hash();
+        self.hands[color.index()].remove(piece_type)?;
+        self.en_passant = None;
+        if piece_type == PieceType::Pawn {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        self.record_check(color);
+        self.pass_turn();
+        self.hash ^= state ^ self.state_hash();
+
+        Ok(undo_info)
     }
 
     pub fn pieces_of_color(&self, color: Color) -> Bitboard {
@@ -179,22 +598,189 @@ impl Position {
         self.all_occupied
     }
 
+    /// The color-flipped position: the board mirrored vertically with every piece, the
+    /// side to move, the castling rights and the en passant square switching sides.
+    ///
+    /// Evaluation from the side to move's point of view is unchanged by flipping.
+    pub fn flipped(&self) -> Position {
+        let mut flipped = Position::new();
+        for (index, piece) in self.board.iter().enumerate() {
+            if let Some(piece) = piece {
+                let square = Square::from(index as u32).mirror_vertical();
+                flipped.place_piece(square, Piece::new(piece.piece_type, piece.color.opposite()));
+            }
+        }
+
+        let rights = self.castling_rights;
+        flipped.side_to_move = self.side_to_move.opposite();
+        flipped.castling_rights = CastlingRights {
+            white_kingside: rights.black_kingside,
+            white_queenside: rights.black_queenside,
+            black_kingside: rights.white_kingside,
+            black_queenside: rights.white_queenside,
+        };
+        flipped.en_passant = self.en_passant.map(Square::mirror_vertical);
+        flipped.halfmove_clock = self.halfmove_clock;
+        flipped.fullmove_number = self.fullmove_number;
+        flipped.variant = self.variant;
+        flipped.hands = [self.hands[1], self.hands[0]];
+        flipped.promoted = Bitboard::new(self.promoted.value().swap_bytes());
+        flipped.checks_given = [self.checks_given[1], self.checks_given[0]];
+        flipped.refresh_zobrist_hash();
+        flipped
+    }
+
     pub fn to_fen(&self) -> String {
-        String::from("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w")
+        let mut placement = String::new();
+
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                let square = Square::from_file_rank(file, rank).unwrap();
+                match self.piece_at(square) {
+                    Some(piece) => {
+                        if empty > 0 {
+                            placement.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        placement.push(piece.symbol());
+                        if self.variant.has_drops() && self.promoted.contains(square) {
+                            placement.push('~');
+                        }
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                placement.push('/');
+            }
+        }
+        if self.variant.has_drops() {
+            placement.push('[');
+            placement.push_str(&self.hands[Color::White.index()].to_fen(Color::White));
+            placement.push_str(&self.hands[Color::Black.index()].to_fen(Color::Black));
+            placement.push(']');
+        }
+
+        let side = match self.side_to_move {
+            Color::White => "w",
+            Color::Black => "b",
+        };
+
+        let mut en_passant = self
+            .en_passant
+            .map_or("-".to_string(), |square| square.to_string());
+        if self.variant == Variant::ThreeCheck {
+            // Checks still needed by each side, as lichess writes them
+            let remaining =
+                |color: Color| CHECKS_TO_WIN.saturating_sub(self.checks_given[color.index()]);
+            en_passant = format!(
+                "{} {}+{}",
+                en_passant,
+                remaining(Color::White),
+                remaining(Color::Black)
+            );
+        }
+
+        format!(
+            "{} {} {} {} {} {}",
+            placement,
+            side,
+            self.castling_rights.to_fen(),
+            en_passant,
+            self.halfmove_clock,
+            self.fullmove_number
+        )
     }
 
-    // Additional methods needed by game_state.rs and evaluation.rs
+    /// Parse `fen` as a position of `variant`, which is how a Horde position is told
+    /// apart. Holdings and check counters are optional; a FEN carrying another variant's
+    /// fields is rejected.
+    pub fn from_fen_with_variant(fen: &str, variant: Variant) -> Result<Self> {
+        let mut position = Self::from_fen(fen)?;
+        if position.variant != Variant::Standard && position.variant != variant {
+            return Err(ChessError::ParseError(format!(
+                "FEN is not a {} position",
+                variant
+            )));
+        }
+        position.variant = variant;
+        Ok(position)
+    }
+
+    /// Parse `fen`. Castling rights whose king or rook is not in place are dropped, as
+    /// `from_fen_lenient` does, but without reporting it.
     pub fn from_fen(fen: &str) -> Result<Self> {
-        let parts: Vec<&str> = fen.split_whitespace().collect();
+        Self::from_fen_lenient(fen).map(|(position, _)| position)
+    }
+
+    /// Parse `fen`, repairing what can be repaired and reporting each repair
+    pub fn from_fen_lenient(fen: &str) -> Result<(Self, Vec<FenWarning>)> {
+        let mut position = Self::parse_fen(fen)?;
+        let mut warnings = Vec::new();
+        let dropped = position.strip_impossible_castling_rights();
+        if dropped != CastlingRights::NONE {
+            warnings.push(FenWarning::ImpossibleCastlingRights(dropped));
+        }
+        Ok((position, warnings))
+    }
+
+    /// Drop the castling rights the king and rooks no longer allow, e.g. after pieces
+    /// were placed or removed by hand; returns the rights dropped
+    pub fn strip_impossible_castling_rights(&mut self) -> CastlingRights {
+        let rights = self.castling_rights;
+        let possible = rights.possible_in(self);
+        if possible == rights {
+            return CastlingRights::NONE;
+        }
+
+        let keys = zobrist_keys();
+        self.hash ^= keys.hash_castling(rights.index()) ^ keys.hash_castling(possible.index());
+        self.castling_rights = possible;
+        CastlingRights {
+            white_kingside: rights.white_kingside && !possible.white_kingside,
+            white_queenside: rights.white_queenside && !possible.white_queenside,
+            black_kingside: rights.black_kingside && !possible.black_kingside,
+            black_queenside: rights.black_queenside && !possible.black_queenside,
+        }
+    }
+
+    fn parse_fen(fen: &str) -> Result<Self> {
+        let mut parts: Vec<&str> = fen.split_whitespace().collect();
         if parts.is_empty() {
             return Err(ChessError::ParseError("Empty FEN string".to_string()));
         }
 
-        let board_fen = parts[0];
         let mut position = Position::new();
 
-        // Parse board position
-        let ranks: Vec<&str> = board_fen.split('/').collect();
+        // Three-check counters, either checks remaining as `3+3` after the en passant
+        // square (lichess) or checks given as `+0+0` at the end (Stockfish)
+        if let Some(index) = (4..parts.len()).find(|&index| parts[index].contains('+')) {
+            position.variant = Variant::ThreeCheck;
+            position.checks_given = Self::parse_check_counters(parts.remove(index))?;
+        }
+
+        // Crazyhouse holdings follow the placement in brackets or as a ninth rank
+        let mut ranks: Vec<&str> = parts[0].split('/').collect();
+        let holdings = match parts[0].split_once('[') {
+            Some((board_fen, holdings)) => {
+                let holdings = holdings.strip_suffix(']').ok_or_else(|| {
+                    ChessError::ParseError("Unterminated holdings in FEN".to_string())
+                })?;
+                ranks = board_fen.split('/').collect();
+                Some(holdings)
+            }
+            None if ranks.len() == 9 => ranks.pop(),
+            None => None,
+        };
+        if let Some(holdings) = holdings {
+            position.variant = Variant::Crazyhouse;
+            position.hands = Hand::parse_holdings(holdings)?;
+        }
+
         if ranks.len() != 8 {
             return Err(ChessError::ParseError("FEN must have 8 ranks".to_string()));
         }
@@ -203,6 +789,18 @@ impl Position {
             let mut file_idx = 0;
 
             for ch in rank_str.chars() {
+                if ch == '~' {
+                    // The piece before was promoted
+                    let square = (file_idx > 0)
+                        .then(|| Square::from_file_rank(file_idx as u8 - 1, 7 - rank_idx as u8))
+                        .flatten()
+                        .filter(|&square| position.piece_at(square).is_some())
+                        .ok_or_else(|| {
+                            ChessError::ParseError("'~' must follow a piece".to_string())
+                        })?;
+                    position.promoted |= square.bitboard();
+                    continue;
+                }
                 if file_idx >= 8 {
                     return Err(ChessError::ParseError("Too many files in rank".to_string()));
                 }
@@ -268,12 +866,51 @@ impl Position {
             position.side_to_move = Color::White;
         }
 
+        // Castling rights, en passant square and move clocks are optional
+        if parts.len() > 2 {
+            position.castling_rights = CastlingRights::from_string(parts[2])?;
+        }
+
+        if parts.len() > 3 && parts[3] != "-" {
+            position.en_passant = Some(parts[3].parse()?);
+        }
+
+        if parts.len() > 4 {
+            position.halfmove_clock = parts[4]
+                .parse()
+                .map_err(|_| ChessError::ParseError("Invalid halfmove clock".to_string()))?;
+        }
+
+        if parts.len() > 5 {
+            position.fullmove_number = parts[5]
+                .parse()
+                .map_err(|_| ChessError::ParseError("Invalid fullmove number".to_string()))?;
+        }
+
         // Update bitboards after placing all pieces
         position.update_bitboards();
 
         Ok(position)
     }
 
+    fn parse_check_counters(field: &str) -> Result<[u8; 2]> {
+        let invalid = || ChessError::ParseError(format!("Invalid check counters: {}", field));
+        let (given, counters) = match field.strip_prefix('+') {
+            Some(counters) => (true, counters),
+            None => (false, field),
+        };
+        let (white, black) = counters.split_once('+').ok_or_else(invalid)?;
+        let mut checks = [0; 2];
+        for (slot, count) in checks.iter_mut().zip([white, black]) {
+            let count: u8 = count.parse().map_err(|_| invalid())?;
+            if count > CHECKS_TO_WIN {
+                return Err(invalid());
+            }
+            *slot = if given { count } else { CHECKS_TO_WIN - count };
+        }
+        Ok(checks)
+    }
+
     pub fn king_square(&self, color: Color) -> Option<Square> {
         // Find the king of the specified color
         for square_idx in 0..64 {
@@ -307,25 +944,127 @@ impl Position {
         }
 
         self.all_occupied = self.occupied[0] | self.occupied[1];
+        self.refresh_zobrist_hash();
     }
 
     pub fn pieces_of_type(&self, piece_type: PieceType, color: Color) -> Bitboard {
         self.pieces[color.index() * 6 + piece_type.index()]
     }
 
+    /// Every piece of the given color with its square, pawns first and king last
+    pub fn piece_list(&self, color: Color) -> impl Iterator<Item = (Square, Piece)> {
+        let pieces = self.pieces;
+        PieceType::ALL.into_iter().flat_map(move |piece_type| {
+            let piece = Piece::new(piece_type, color);
+            pieces[piece.index()]
+                .iter()
+                .map(move |square_idx| (Square::from(square_idx), piece))
+        })
+    }
+
+    /// Packed piece counts for both sides, independent of placement
+    pub fn material_key(&self) -> MaterialKey {
+        let mut key = MaterialKey::default();
+        for color in [Color::White, Color::Black] {
+            for piece_type in PieceType::ALL {
+                if piece_type != PieceType::King {
+                    let count = self.pieces_of_type(piece_type, color).count_bits();
+                    key.set_count(piece_type, color, count);
+                }
+            }
+        }
+        key
+    }
+
+    /// Value of `color`'s pawns and pieces, king excluded
+    pub fn material(&self, color: Color) -> i32 {
+        self.non_pawn_material(color)
+            + self.pieces_of_type(PieceType::Pawn, color).count_bits() as i32
+                * PieceType::Pawn.value()
+    }
+
+    /// Value of `color`'s knights, bishops, rooks and queens
+    pub fn non_pawn_material(&self, color: Color) -> i32 {
+        [
+            PieceType::Knight,
+            PieceType::Bishop,
+            PieceType::Rook,
+            PieceType::Queen,
+        ]
+        .into_iter()
+        .map(|piece_type| {
+            self.pieces_of_type(piece_type, color).count_bits() as i32 * piece_type.value()
+        })
+        .sum()
+    }
+
+    /// Game phase from the non-pawn material of both sides
+    pub fn phase(&self) -> GamePhase {
+        GamePhase::from_material(
+            self.non_pawn_material(Color::White) + self.non_pawn_material(Color::Black),
+        )
+    }
+
     pub fn make_null_move(&mut self) {
+        let state = self.state_hash();
+        self.en_passant = None;
         self.side_to_move = self.side_to_move.opposite();
+        self.hash ^= state ^ self.state_hash();
     }
 
-    // Advanced optimization API methods (stubs)
+    /// Zobrist hash of the position, maintained incrementally
     pub fn zobrist_hash(&self) -> u64 {
-        0
+        self.hash
     }
-    pub fn halfmove_clock(&self) -> u8 {
-        0
+
+    /// Recompute the stored hash after fields were assigned directly
+    pub fn refresh_zobrist_hash(&mut self) {
+        self.hash = self.compute_zobrist_hash();
+    }
+
+    /// Hash computed from scratch, which `zobrist_hash` always equals
+    pub fn compute_zobrist_hash(&self) -> u64 {
+        let keys = zobrist_keys();
+        let mut hash = 0;
+
+        for (square_idx, piece) in self.board.iter().enumerate() {
+            if let Some(piece) = piece {
+                hash ^= keys.hash_piece(*piece, Square::from(square_idx as u32));
+            }
+        }
+
+        hash ^ self.state_hash()
     }
-    pub fn fullmove_number(&self) -> u16 {
-        1
+
+    /// Hash of the side to move, castling rights, en passant file, pieces in hand and
+    /// checks given
+    fn state_hash(&self) -> u64 {
+        let keys = zobrist_keys();
+        let mut hash = keys.hash_castling(self.castling_rights.index());
+
+        if self.side_to_move == Color::Black {
+            hash ^= keys.hash_side();
+        }
+        if let Some(en_passant) = self.en_passant {
+            hash ^= keys.hash_en_passant(en_passant.file());
+        }
+        for color in [Color::White, Color::Black] {
+            hash ^= keys.hash_checks(color, self.checks_given[color.index()]);
+            let hand = self.hands[color.index()];
+            for piece_type in hand.piece_types() {
+                hash ^= keys.hash_hand(color, piece_type, hand.count(piece_type));
+            }
+        }
+
+        hash
+    }
+
+    // Advanced optimization API methods (stubs)
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
     }
     pub fn side_to_move(&self) -> Color {
         self.side_to_move
@@ -352,6 +1091,7 @@ impl Position {
             // Update occupied bitboards
             self.occupied[piece.color.index()] &= !square.bitboard();
             self.all_occupied = self.occupied[0] | self.occupied[1];
+            self.hash ^= zobrist_keys().hash_piece(piece, square);
         }
     }
 }
@@ -361,3 +1101,459 @@ impl Default for Position {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn castle_position(fen: &str) -> Position {
+        Position::from_fen(fen).unwrap()
+    }
+
+    #[test]
+    fn test_castle_moves_rook() {
+        let mut position = castle_position("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        let original = position.clone();
+        let mv = Move::castle(Square::E1, Square::G1);
+
+        let undo = position.make_move(mv).unwrap();
+        assert_eq!(
+            position.piece_at(Square::G1),
+            Some(Piece::new(PieceType::King, Color::White))
+        );
+        assert_eq!(
+            position.piece_at(Square::F1),
+            Some(Piece::new(PieceType::Rook, Color::White))
+        );
+        assert_eq!(position.piece_at(Square::H1), None);
+
+        position.undo_move(mv, undo);
+        assert_eq!(position, original);
+    }
+
+    #[test]
+    fn test_queenside_castle_black() {
+        let mut position = castle_position("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1");
+        position
+            .make_move(Move::castle(Square::E8, Square::C8))
+            .unwrap();
+        assert_eq!(
+            position.piece_at(Square::C8),
+            Some(Piece::new(PieceType::King, Color::Black))
+        );
+        assert_eq!(
+            position.piece_at(Square::D8),
+            Some(Piece::new(PieceType::Rook, Color::Black))
+        );
+    }
+
+    #[test]
+    fn test_castle_rejected_when_unsafe() {
+        let mv = Move::castle(Square::E1, Square::G1);
+
+        // Out of check
+        let mut position = castle_position("4r1k1/8/8/8/8/8/8/4K2R w K - 0 1");
+        assert!(position.make_move(mv).is_err());
+
+        // Through an attacked square
+        let mut position = castle_position("5rk1/8/8/8/8/8/8/4K2R w K - 0 1");
+        assert!(position.make_move(mv).is_err());
+
+        // Into check
+        let mut position = castle_position("6rk/8/8/8/8/8/8/4K2R w K - 0 1");
+        assert!(position.make_move(mv).is_err());
+
+        // Blocked path
+        let mut position = castle_position("6k1/8/8/8/8/8/8/4KN1R w K - 0 1");
+        let original = position.clone();
+        assert!(position.make_move(mv).is_err());
+        assert_eq!(position, original);
+
+        // No rook
+        let mut position = castle_position("6k1/8/8/8/8/8/8/4K3 w K - 0 1");
+        assert!(position.make_move(mv).is_err());
+
+        // No castling right
+        let mut position = castle_position("6k1/8/8/8/8/8/8/4K2R w Q - 0 1");
+        assert!(position.make_move(mv).is_err());
+    }
+
+    #[test]
+    fn test_chess960_castle_king_takes_rook() {
+        // King on b1, rook on c1: castling kingside lands king on g1 and rook on f1
+        // Standard FENs cannot carry this right, so it is granted by hand
+        let mut position = castle_position("6k1/8/8/8/8/8/8/1KR5 w - - 0 1");
+        position.castling_rights = CastlingRights::from_string("K").unwrap();
+        let original = position.clone();
+        let mv = Move::castle(Square::B1, Square::C1);
+
+        let undo = position.make_move(mv).unwrap();
+        assert_eq!(
+            position.piece_at(Square::G1),
+            Some(Piece::new(PieceType::King, Color::White))
+        );
+        assert_eq!(
+            position.piece_at(Square::F1),
+            Some(Piece::new(PieceType::Rook, Color::White))
+        );
+        assert_eq!(position.all_pieces().count_bits(), 3);
+
+        position.undo_move(mv, undo);
+        assert_eq!(position, original);
+    }
+
+    #[test]
+    fn test_impossible_castling_rights_dropped() {
+        // The h1 rook is gone and the black king has left its back rank
+        let fen = "r3k2r/8/8/8/8/8/8/R3K3 w KQkq - 0 1";
+        let (position, warnings) = Position::from_fen_lenient(fen).unwrap();
+        assert_eq!(position.castling_rights.to_fen(), "Qkq");
+        assert_eq!(
+            warnings,
+            vec![FenWarning::ImpossibleCastlingRights(
+                CastlingRights::from_string("K").unwrap()
+            )]
+        );
+        assert_eq!(position, Position::from_fen(fen).unwrap());
+        assert_eq!(position.zobrist_hash(), position.compute_zobrist_hash());
+
+        let (moved_king, warnings) =
+            Position::from_fen_lenient("r6r/4k3/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(moved_king.castling_rights.to_fen(), "KQ");
+        assert_eq!(
+            warnings[0].to_string(),
+            "castling rights kq dropped: king or rook not in place"
+        );
+
+        // A king off e1 cannot castle, whatever rooks are beside it
+        let (off_square, warnings) =
+            Position::from_fen_lenient("4k3/8/8/8/8/8/8/R4K1R w KQ - 0 1").unwrap();
+        assert_eq!(off_square.castling_rights.to_fen(), "-");
+        assert_eq!(
+            warnings,
+            vec![FenWarning::ImpossibleCastlingRights(
+                CastlingRights::from_string("KQ").unwrap()
+            )]
+        );
+
+        let mut edited = Position::starting_position();
+        edited.remove_piece(Square::A8);
+        assert_eq!(edited.strip_impossible_castling_rights().to_fen(), "q");
+        assert_eq!(edited.castling_rights.to_fen(), "KQk");
+        assert_eq!(edited.zobrist_hash(), edited.compute_zobrist_hash());
+    }
+
+    #[test]
+    fn test_fen_round_trip() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 b - - 12 40",
+        ] {
+            assert_eq!(Position::from_fen(fen).unwrap().to_fen(), fen);
+        }
+        assert_eq!(
+            Position::starting_position().to_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+        );
+    }
+
+    #[test]
+    fn test_clocks_and_rights_follow_moves() {
+        let mut position = Position::starting_position();
+
+        position
+            .make_move(Move::normal(Square::G1, Square::F3))
+            .unwrap();
+        assert_eq!(position.halfmove_clock(), 1);
+        assert_eq!(position.fullmove_number(), 1);
+
+        position
+            .make_move(Move::normal(Square::E7, Square::E5))
+            .unwrap();
+        assert_eq!(position.halfmove_clock(), 0);
+        assert_eq!(position.fullmove_number(), 2);
+        assert_eq!(position.en_passant, Some(Square::E6));
+
+        position
+            .make_move(Move::normal(Square::H1, Square::G1))
+            .unwrap();
+        assert_eq!(position.en_passant, None);
+        assert!(!position.castling_rights.can_castle_kingside(Color::White));
+        assert!(position.castling_rights.can_castle_queenside(Color::White));
+    }
+
+    #[test]
+    fn test_en_passant_capture_and_undo() {
+        let mut position =
+            castle_position("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+        let original = position.clone();
+
+        // Encoded as a plain capture: the en passant square is enough to recognise it
+        for mv in [
+            Move::en_passant(Square::E5, Square::F6),
+            Move::capture(Square::E5, Square::F6),
+        ] {
+            let undo = position.make_move(mv).unwrap();
+            assert_eq!(position.piece_at(Square::F5), None);
+            assert_eq!(
+                position.piece_at(Square::F6),
+                Some(Piece::new(PieceType::Pawn, Color::White))
+            );
+            assert_eq!(undo.captured_square, Some(Square::F5));
+
+            position.undo_move(mv, undo);
+            assert_eq!(position, original);
+        }
+
+        // No en passant onto a square that was not just skipped
+        assert!(position
+            .make_move(Move::en_passant(Square::E5, Square::D6))
+            .is_err());
+    }
+
+    #[test]
+    fn test_promotion_and_undo() {
+        let mut position = castle_position("1n2k3/P7/8/8/8/8/8/4K3 w - - 5 40");
+        let original = position.clone();
+
+        for mv in [
+            Move::promotion(Square::A7, Square::A8, PieceType::Queen),
+            Move::promotion_capture(Square::A7, Square::B8, PieceType::Knight),
+        ] {
+            let undo = position.make_move(mv).unwrap();
+            assert_eq!(
+                position.piece_at(mv.to),
+                Some(Piece::new(mv.promotion_piece().unwrap(), Color::White))
+            );
+            assert_eq!(
+                position
+                    .pieces_of_type(PieceType::Pawn, Color::White)
+                    .count_bits(),
+                0
+            );
+            assert_eq!(position.halfmove_clock(), 0);
+
+            position.undo_move(mv, undo);
+            assert_eq!(position, original);
+        }
+
+        assert!(position
+            .make_move(Move::normal(Square::A7, Square::A8))
+            .is_err());
+    }
+
+    #[test]
+    fn test_wrong_side_and_own_capture_rejected() {
+        let mut position = Position::starting_position();
+        assert!(position
+            .make_move(Move::normal(Square::E7, Square::E5))
+            .is_err());
+        assert!(position
+            .make_move(Move::capture(Square::D1, Square::E1))
+            .is_err());
+        assert_eq!(position, Position::starting_position());
+    }
+
+    #[test]
+    fn test_incremental_hash_tracks_state_changes() {
+        let mut position = castle_position("r3k2r/8/8/8/4p3/8/3P4/R3K2R w KQkq - 0 1");
+        let original = position.clone();
+
+        let double_push = Move::normal(Square::D2, Square::D4);
+        let undo_push = position.make_move(double_push).unwrap();
+        assert_eq!(position.en_passant, Some(Square::D3));
+        assert_eq!(position.zobrist_hash(), position.compute_zobrist_hash());
+
+        let capture = Move::en_passant(Square::E4, Square::D3);
+        let undo_capture = position.make_move(capture).unwrap();
+        assert_eq!(position.zobrist_hash(), position.compute_zobrist_hash());
+
+        let castle = Move::castle(Square::E1, Square::G1);
+        let undo_castle = position.make_move(castle).unwrap();
+        assert_eq!(position.zobrist_hash(), position.compute_zobrist_hash());
+
+        let before_null = position.zobrist_hash();
+        position.make_null_move();
+        assert_ne!(position.zobrist_hash(), before_null);
+        assert_eq!(position.zobrist_hash(), position.compute_zobrist_hash());
+        position.make_null_move();
+        assert_eq!(position.zobrist_hash(), before_null);
+
+        position.undo_move(castle, undo_castle);
+        position.undo_move(capture, undo_capture);
+        position.undo_move(double_push, undo_push);
+        assert_eq!(position, original);
+        assert_eq!(position.zobrist_hash(), original.compute_zobrist_hash());
+    }
+
+    #[test]
+    fn test_flipped_position() {
+        let position =
+            Position::from_fen("r3k2r/pp3ppp/8/3pP3/8/8/PPP2PPP/R3K1R1 w Qkq d6 0 12").unwrap();
+        let flipped = position.flipped();
+        assert_eq!(
+            flipped.to_fen(),
+            "r3k1r1/ppp2ppp/8/8/3Pp3/8/PP3PPP/R3K2R b KQq d3 0 12"
+        );
+        assert_eq!(flipped.zobrist_hash(), flipped.compute_zobrist_hash());
+        assert_eq!(flipped.flipped(), position);
+
+        // Every move has its mirror image in the flipped position
+        let mut moves: Vec<Move> = shared_move_generator()
+            .generate_legal_moves(&position)
+            .into_iter()
+            .map(Move::mirror)
+            .collect();
+        let mut flipped_moves = shared_move_generator().generate_legal_moves(&flipped);
+        moves.sort_by_key(|mv| mv.to_uci());
+        flipped_moves.sort_by_key(|mv| mv.to_uci());
+        assert_eq!(moves, flipped_moves);
+    }
+
+    #[test]
+    fn test_crazyhouse_fen_round_trip() {
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQ~KB1R[Pbn] w KQkq - 0 3";
+        let position = Position::from_fen(fen).unwrap();
+        assert_eq!(position.variant, Variant::Crazyhouse);
+        assert_eq!(
+            position.hands[Color::White.index()].count(PieceType::Pawn),
+            1
+        );
+        assert_eq!(
+            position.hands[Color::Black.index()].count(PieceType::Bishop),
+            1
+        );
+        assert!(position.promoted.contains(Square::D1));
+        assert_eq!(position.to_fen(), fen);
+
+        // Holdings as a ninth rank read the same
+        let ninth_rank = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQ~KB1R/Pbn w KQkq - 0 3";
+        assert_eq!(Position::from_fen(ninth_rank).unwrap(), position);
+
+        let standard = Position::starting_position();
+        assert_eq!(standard.variant, Variant::Standard);
+        assert!(!standard.to_fen().contains('['));
+        assert!(Position::from_fen("8/8/8/8/8/8/8/K6k[X] w - - 0 1").is_err());
+        assert!(Position::from_fen("8/8/8/8/8/8/8/~K6k[] w - - 0 1").is_err());
+    }
+
+    #[test]
+    fn test_crazyhouse_capture_goes_to_hand() {
+        // The black queen on d5 was promoted, so it returns to White's hand as a pawn
+        let mut position = Position::from_fen("4k3/8/8/3q~4/8/8/8/3RK3[] w - - 0 1").unwrap();
+        let original = position.clone();
+        let capture = Move::capture(Square::D1, Square::D5);
+        let undo = position.make_move(capture).unwrap();
+        let white_hand = position.hands[Color::White.index()];
+        assert_eq!(white_hand.count(PieceType::Pawn), 1);
+        assert_eq!(white_hand.count(PieceType::Queen), 0);
+        assert!(position.promoted.is_empty());
+        assert_eq!(position.zobrist_hash(), position.compute_zobrist_hash());
+
+        position.undo_move(capture, undo);
+        assert_eq!(position, original);
+        assert_eq!(position.zobrist_hash(), original.compute_zobrist_hash());
+
+        // Standard chess keeps no hands
+        let mut standard = Position::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1").unwrap();
+        standard.make_move(capture).unwrap();
+        assert!(standard.hands.iter().all(|hand| hand.is_empty()));
+    }
+
+    #[test]
+    fn test_drops() {
+        let mut position = Position::from_fen("4k3/8/8/8/8/8/8/4K3[Pn] w - - 0 1").unwrap();
+        let original = position.clone();
+
+        assert!(position
+            .make_move(Move::drop(PieceType::Pawn, Square::E8))
+            .is_err());
+        assert!(position
+            .make_move(Move::drop(PieceType::Pawn, Square::A1))
+            .is_err());
+        assert!(position
+            .make_move(Move::drop(PieceType::Pawn, Square::E1))
+            .is_err());
+        assert!(position
+            .make_move(Move::drop(PieceType::Knight, Square::C3))
+            .is_err());
+        assert_eq!(position, original);
+
+        let drop = Move::drop(PieceType::Pawn, Square::D7);
+        let undo = position.make_move(drop).unwrap();
+        assert_eq!(
+            position.piece_at(Square::D7),
+            Some(Piece::new(PieceType::Pawn, Color::White))
+        );
+        assert!(position.hands[Color::White.index()].is_empty());
+        assert_eq!(position.side_to_move, Color::Black);
+        assert_eq!(position.zobrist_hash(), position.compute_zobrist_hash());
+        assert_ne!(position.zobrist_hash(), original.zobrist_hash());
+
+        position.undo_move(drop, undo);
+        assert_eq!(position, original);
+        assert_eq!(position.zobrist_hash(), original.zobrist_hash());
+
+        let mut standard = Position::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(standard.make_move(drop).is_err());
+    }
+
+    #[test]
+    fn test_three_check_counters() {
+        let lichess = "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 2+3 0 2";
+        let position = Position::from_fen(lichess).unwrap();
+        assert_eq!(position.variant, Variant::ThreeCheck);
+        assert_eq!(position.checks_given, [1, 0]);
+        assert_eq!(position.to_fen(), lichess);
+
+        let stockfish = "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2 +1+0";
+        assert_eq!(Position::from_fen(stockfish).unwrap(), position);
+        assert!(Position::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 4+3 0 1").is_err());
+    }
+
+    #[test]
+    fn test_three_check_counts_checks() {
+        let mut position = Position::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 1+3 0 1").unwrap();
+        let original = position.clone();
+        let check = Move::normal(Square::A1, Square::A8);
+        let undo = position.make_move(check).unwrap();
+        assert_eq!(position.checks_given, [3, 0]);
+        assert_eq!(position.variant_winner(), Some(Color::White));
+        assert_eq!(position.zobrist_hash(), position.compute_zobrist_hash());
+
+        position.undo_move(check, undo);
+        assert_eq!(position, original);
+        assert_eq!(position.variant_winner(), None);
+
+        // Quiet moves leave the counters alone
+        position
+            .make_move(Move::normal(Square::A1, Square::A2))
+            .unwrap();
+        assert_eq!(position.checks_given, [2, 0]);
+    }
+
+    #[test]
+    fn test_horde_rules() {
+        let horde = Variant::Horde.starting_fen();
+        let position = Position::from_fen_with_variant(horde, Variant::Horde).unwrap();
+        assert_eq!(position.variant, Variant::Horde);
+        assert_eq!(position.king_square(Color::White), None);
+        assert_eq!(position.variant_winner(), None);
+
+        let moves = shared_move_generator().generate_legal_moves(&position);
+        assert!(moves.contains(&Move::normal(Square::A4, Square::A5)));
+        assert!(!moves.contains(&Move::normal(Square::A4, Square::A6)));
+
+        let lone_pawn = "4k3/8/8/8/8/8/8/P7 w - - 0 1";
+        let mut position = Position::from_fen_with_variant(lone_pawn, Variant::Horde).unwrap();
+        let moves = shared_move_generator().generate_legal_moves(&position);
+        assert!(moves.contains(&Move::normal(Square::A1, Square::A3)));
+        position.remove_piece(Square::A1);
+        assert_eq!(position.variant_winner(), Some(Color::Black));
+
+        let crazyhouse = Variant::Crazyhouse.starting_fen();
+        assert!(Position::from_fen_with_variant(crazyhouse, Variant::Horde).is_err());
+    }
+}
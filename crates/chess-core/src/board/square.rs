@@ -1,8 +1,8 @@
-use crate::Bitboard;
-use serde::{Deserialize, Serialize};
+use crate::{Bitboard, Color};
 use std::fmt;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Serialized as its name, e.g. `"e4"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Square(u8);
 
 impl Square {
@@ -208,6 +208,41 @@ impl Square {
         }
     }
 
+    /// The square on the same file seen from the other side (a1 <-> a8)
+    #[inline]
+    pub const fn mirror_vertical(self) -> Square {
+        Square(self.0 ^ 56)
+    }
+
+    /// The square on the same rank reflected across the board's centre line (a1 <-> h1)
+    #[inline]
+    pub const fn mirror_horizontal(self) -> Square {
+        Square(self.0 ^ 7)
+    }
+
+    /// Column and row of the square on a screen drawn from `perspective`'s side, with
+    /// (0, 0) the top-left corner
+    #[inline]
+    pub const fn to_screen(self, perspective: Color) -> (u8, u8) {
+        match perspective {
+            Color::White => (self.file(), 7 - self.rank()),
+            Color::Black => (7 - self.file(), self.rank()),
+        }
+    }
+
+    /// The square drawn at `column` and `row` of a board shown from `perspective`'s side;
+    /// the inverse of `to_screen`
+    #[inline]
+    pub const fn from_screen(column: u8, row: u8, perspective: Color) -> Option<Square> {
+        if column >= 8 || row >= 8 {
+            return None;
+        }
+        match perspective {
+            Color::White => Square::from_file_rank(column, 7 - row),
+            Color::Black => Square::from_file_rank(7 - column, row),
+        }
+    }
+
     #[inline]
     pub const fn bitboard(self) -> Bitboard {
         Bitboard::new(1u64 << self.0)
@@ -384,16 +419,14 @@ impl std::str::FromStr for Square {
     type Err = crate::ChessError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() != 2 {
+        let chars: Vec<char> = s.chars().collect();
+        let [file_char, rank_char] = chars[..] else {
             return Err(crate::ChessError::ParseError(format!(
                 "Invalid square: {}",
                 s
             )));
-        }
-
-        let mut chars = s.chars();
-        let file_char = chars.next().unwrap().to_ascii_lowercase();
-        let rank_char = chars.next().unwrap();
+        };
+        let file_char = file_char.to_ascii_lowercase();
 
         let file = match file_char {
             'a' => 0,
@@ -582,4 +615,26 @@ mod tests {
         assert_eq!(format!("{}", Square::H8), "h8");
         assert_eq!(format!("{}", Square::E4), "e4");
     }
+
+    #[test]
+    fn test_mirrors_and_screen_coordinates() {
+        assert_eq!(Square::A1.mirror_vertical(), Square::A8);
+        assert_eq!(Square::E2.mirror_vertical(), Square::E7);
+        assert_eq!(Square::A1.mirror_horizontal(), Square::H1);
+        assert_eq!(Square::D4.mirror_horizontal(), Square::E4);
+
+        // White sees a8 top-left, Black sees h1 there
+        assert_eq!(Square::A8.to_screen(Color::White), (0, 0));
+        assert_eq!(Square::H1.to_screen(Color::Black), (0, 0));
+        assert_eq!(Square::E2.to_screen(Color::White), (4, 6));
+        assert_eq!(Square::E2.to_screen(Color::Black), (3, 1));
+        for perspective in [Color::White, Color::Black] {
+            for square in Square::ALL {
+                assert_eq!(square.mirror_vertical().mirror_vertical(), square);
+                let (column, row) = square.to_screen(perspective);
+                assert_eq!(Square::from_screen(column, row, perspective), Some(square));
+            }
+        }
+        assert_eq!(Square::from_screen(8, 0, Color::White), None);
+    }
 }
@@ -0,0 +1,240 @@
+// Rule variants and the piece hands used by drop variants
+//
+// In Crazyhouse a captured piece changes sides and goes into the capturer's hand, from
+// where it can later be dropped on any empty square instead of making a board move.
+// Promoted pieces are remembered so that they return to the hand as pawns.
+//
+// Three-check is won by giving a third check, so the checks given by each side are part
+// of the position. In Horde White has 36 pawns and no king and loses once all of them
+// are captured; White pawns on the first rank may advance two squares.
+
+use crate::error::{ChessError, Result};
+use crate::pieces::{Color, PieceType};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Checks a side must give to win a Three-check game
+pub const CHECKS_TO_WIN: u8 = 3;
+
+/// Rule set a position is played under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Variant {
+    #[default]
+    Standard,
+    /// Captured pieces go into the capturer's hand and can be dropped back
+    Crazyhouse,
+    /// The third check given wins
+    ThreeCheck,
+    /// White's pawn horde against Black's army; Black wins by capturing every White piece
+    Horde,
+}
+
+impl Variant {
+    /// Whether captured pieces are kept in hand and can be dropped
+    pub const fn has_drops(self) -> bool {
+        matches!(self, Variant::Crazyhouse)
+    }
+
+    /// FEN of the variant's initial position
+    pub const fn starting_fen(self) -> &'static str {
+        match self {
+            Variant::Standard => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            Variant::Crazyhouse => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+            Variant::ThreeCheck => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3+3 0 1",
+            Variant::Horde => {
+                "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1"
+            }
+        }
+    }
+
+    /// Name used by lichess, e.g. `threeCheck`
+    pub const fn name(self) -> &'static str {
+        match self {
+            Variant::Standard => "standard",
+            Variant::Crazyhouse => "crazyhouse",
+            Variant::ThreeCheck => "threeCheck",
+            Variant::Horde => "horde",
+        }
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for Variant {
+    type Err = ChessError;
+
+    /// Accepts the lichess names case-insensitively, with or without dashes
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+            "standard" | "chess" => Ok(Variant::Standard),
+            "crazyhouse" => Ok(Variant::Crazyhouse),
+            "threecheck" | "3check" => Ok(Variant::ThreeCheck),
+            "horde" => Ok(Variant::Horde),
+            _ => Err(ChessError::ParseError(format!("Unknown variant: {}", s))),
+        }
+    }
+}
+
+/// Pieces that can be held in hand, in FEN order of value
+pub const HAND_PIECES: [PieceType; 5] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Pawn,
+];
+
+/// Most pieces of one type a FEN may put in one side's hand; larger holdings are rejected
+pub const MAX_HAND_COUNT: u8 = 16;
+
+/// Pieces one side holds in hand, counted per piece type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Hand {
+    counts: [u8; 5],
+}
+
+impl Hand {
+    /// Number of pieces of this type in hand; kings are never held
+    pub fn count(self, piece_type: PieceType) -> u8 {
+        match piece_type {
+            PieceType::King => 0,
+            _ => self.counts[piece_type.index()],
+        }
+    }
+
+    pub fn add(&mut self, piece_type: PieceType) {
+        debug_assert!(piece_type != PieceType::King, "kings cannot be held");
+        let count = &mut self.counts[piece_type.index()];
+        *count = count.saturating_add(1);
+    }
+
+    /// Take one piece of this type out of the hand
+    pub fn remove(&mut self, piece_type: PieceType) -> Result<()> {
+        if self.count(piece_type) == 0 {
+            return Err(ChessError::InvalidMove(format!(
+                "No {:?} in hand",
+                piece_type
+            )));
+        }
+        self.counts[piece_type.index()] -= 1;
+        Ok(())
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.counts.iter().all(|&count| count == 0)
+    }
+
+    /// Piece types with at least one piece in hand
+    pub fn piece_types(self) -> impl Iterator<Item = PieceType> {
+        HAND_PIECES
+            .into_iter()
+            .filter(move |&piece_type| self.count(piece_type) > 0)
+    }
+
+    /// FEN letters for this hand, uppercase for White, e.g. `QNpp`
+    pub fn to_fen(self, color: Color) -> String {
+        let mut letters = String::new();
+        for piece_type in HAND_PIECES {
+            for _ in 0..self.count(piece_type) {
+                let symbol = piece_type.symbol();
+                letters.push(match color {
+                    Color::White => symbol,
+                    Color::Black => symbol.to_ascii_lowercase(),
+                });
+            }
+        }
+        letters
+    }
+
+    /// Parse FEN holdings such as `Qnpp` into White's and Black's hands
+    pub fn parse_holdings(holdings: &str) -> Result<[Hand; 2]> {
+        let mut hands = [Hand::default(); 2];
+        for ch in holdings.chars() {
+            let piece_type = match ch.to_ascii_lowercase() {
+                'p' => PieceType::Pawn,
+                'n' => PieceType::Knight,
+                'b' => PieceType::Bishop,
+                'r' => PieceType::Rook,
+                'q' => PieceType::Queen,
+                _ => {
+                    return Err(ChessError::ParseError(format!(
+                        "Invalid piece in hand: {}",
+                        ch
+                    )))
+                }
+            };
+            let color = if ch.is_ascii_uppercase() {
+                Color::White
+            } else {
+                Color::Black
+            };
+            let count = &mut hands[color.index()].counts[piece_type.index()];
+            *count = count
+                .checked_add(1)
+                .filter(|&count| count <= MAX_HAND_COUNT)
+                .ok_or_else(|| {
+                    ChessError::ParseError(format!(
+                        "More than {} of {} in hand",
+                        MAX_HAND_COUNT, ch
+                    ))
+                })?;
+        }
+        Ok(hands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hand_counts_and_fen() {
+        let [white, black] = Hand::parse_holdings("pQnNp").unwrap();
+        assert_eq!(white.count(PieceType::Queen), 1);
+        assert_eq!(black.count(PieceType::Pawn), 2);
+        assert_eq!(white.to_fen(Color::White), "QN");
+        assert_eq!(black.to_fen(Color::Black), "npp");
+        assert!(Hand::parse_holdings("K").is_err());
+
+        let full = "P".repeat(MAX_HAND_COUNT as usize);
+        assert_eq!(
+            Hand::parse_holdings(&full).unwrap()[0].count(PieceType::Pawn),
+            MAX_HAND_COUNT
+        );
+        assert!(Hand::parse_holdings(&format!("{}P", full)).is_err());
+        let fen = format!("4k3/8/8/8/8/8/8/4K3[{}] w - - 0 1", "P".repeat(300));
+        assert!(crate::Position::from_fen(&fen).is_err());
+
+        let mut hand = Hand::default();
+        assert!(hand.is_empty());
+        assert!(hand.remove(PieceType::Rook).is_err());
+        hand.add(PieceType::Rook);
+        assert_eq!(
+            hand.piece_types().collect::<Vec<_>>(),
+            vec![PieceType::Rook]
+        );
+        hand.remove(PieceType::Rook).unwrap();
+        assert!(hand.is_empty());
+    }
+
+    #[test]
+    fn test_variant_names() {
+        for variant in [
+            Variant::Standard,
+            Variant::Crazyhouse,
+            Variant::ThreeCheck,
+            Variant::Horde,
+        ] {
+            assert_eq!(variant.name().parse::<Variant>().unwrap(), variant);
+        }
+        assert_eq!(
+            "three-check".parse::<Variant>().unwrap(),
+            Variant::ThreeCheck
+        );
+        assert!("atomic".parse::<Variant>().is_err());
+    }
+}
@@ -1,56 +1,23 @@
 // Advanced evaluation optimizations for chess engine
 // Implements lazy evaluation, incremental updates, and SIMD-optimized scoring
 
-use crate::{Bitboard, Color, PieceType, Position, Square};
+use super::endgame;
+use super::king::king_danger;
+use super::mobility::mobility;
+use super::params::EvalParams;
+use super::pawns::{passed_pawn_score, PawnStructure};
+use super::pieces::{hand_material, minor_piece_score, rook_score, semi_open_files};
+use super::scaling::scale_score;
+use super::threats::{threat_score, AttackMap};
+use crate::utils::zobrist::zobrist_keys;
+use crate::{Bitboard, Color, MoveGenerator, Piece, PieceType, Position, Square};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
 
 // Evaluation constants
-pub const PIECE_VALUES: [i32; 6] = [100, 320, 330, 500, 900, 20000]; // P, N, B, R, Q, K
-
-// Positional evaluation tables (piece-square tables)
-pub const PAWN_TABLE: [i32; 64] = [
-    0, 0, 0, 0, 0, 0, 0, 0, 50, 50, 50, 50, 50, 50, 50, 50, 10, 10, 20, 30, 30, 20, 10, 10, 5, 5,
-    10, 25, 25, 10, 5, 5, 0, 0, 0, 20, 20, 0, 0, 0, 5, -5, -10, 0, 0, -10, -5, 5, 5, 10, 10, -20,
-    -20, 10, 10, 5, 0, 0, 0, 0, 0, 0, 0, 0,
-];
-
-pub const KNIGHT_TABLE: [i32; 64] = [
-    -50, -40, -30, -30, -30, -30, -40, -50, -40, -20, 0, 0, 0, 0, -20, -40, -30, 0, 10, 15, 15, 10,
-    0, -30, -30, 5, 15, 20, 20, 15, 5, -30, -30, 0, 15, 20, 20, 15, 0, -30, -30, 5, 10, 15, 15, 10,
-    5, -30, -40, -20, 0, 5, 5, 0, -20, -40, -50, -40, -30, -30, -30, -30, -40, -50,
-];
-
-pub const BISHOP_TABLE: [i32; 64] = [
-    -20, -10, -10, -10, -10, -10, -10, -20, -10, 0, 0, 0, 0, 0, 0, -10, -10, 0, 5, 10, 10, 5, 0,
-    -10, -10, 5, 5, 10, 10, 5, 5, -10, -10, 0, 10, 10, 10, 10, 0, -10, -10, 10, 10, 10, 10, 10, 10,
-    -10, -10, 5, 0, 0, 0, 0, 5, -10, -20, -10, -10, -10, -10, -10, -10, -20,
-];
-
-pub const ROOK_TABLE: [i32; 64] = [
-    0, 0, 0, 0, 0, 0, 0, 0, 5, 10, 10, 10, 10, 10, 10, 5, -5, 0, 0, 0, 0, 0, 0, -5, -5, 0, 0, 0, 0,
-    0, 0, -5, -5, 0, 0, 0, 0, 0, 0, -5, -5, 0, 0, 0, 0, 0, 0, -5, -5, 0, 0, 0, 0, 0, 0, -5, 0, 0,
-    0, 5, 5, 0, 0, 0,
-];
-
-pub const QUEEN_TABLE: [i32; 64] = [
-    -20, -10, -10, -5, -5, -10, -10, -20, -10, 0, 0, 0, 0, 0, 0, -10, -10, 0, 5, 5, 5, 5, 0, -10,
-    -5, 0, 5, 5, 5, 5, 0, -5, 0, 0, 5, 5, 5, 5, 0, -5, -10, 5, 5, 5, 5, 5, 0, -10, -10, 0, 5, 0, 0,
-    0, 0, -10, -20, -10, -10, -5, -5, -10, -10, -20,
-];
-
-pub const KING_MIDDLE_GAME: [i32; 64] = [
-    -30, -40, -40, -50, -50, -40, -40, -30, -30, -40, -40, -50, -50, -40, -40, -30, -30, -40, -40,
-    -50, -50, -40, -40, -30, -30, -40, -40, -50, -50, -40, -40, -30, -20, -30, -30, -40, -40, -30,
-    -30, -20, -10, -20, -20, -20, -20, -20, -20, -10, 20, 20, 0, 0, 0, 0, 20, 20, 20, 30, 10, 0, 0,
-    10, 30, 20,
-];
-
-pub const KING_END_GAME: [i32; 64] = [
-    -50, -40, -30, -20, -20, -30, -40, -50, -30, -20, -10, 0, 0, -10, -20, -30, -30, -10, 20, 30,
-    30, 20, -10, -30, -30, -10, 30, 40, 40, 30, -10, -30, -30, -10, 30, 40, 40, 30, -10, -30, -30,
-    -10, 20, 30, 30, 20, -10, -30, -30, -30, 0, 0, 0, 0, -30, -30, -50, -30, -30, -30, -30, -30,
-    -30, -50,
-];
+pub const MOBILITY_WEIGHT: i32 = 10; // Per square of the mobility area attacked, matches the standard evaluator
+pub const TEMPO_BONUS: i32 = 10; // Credit for having the move, always given to the side to move
 
 /// Cached evaluation components to enable incremental updates
 #[derive(Debug, Clone)]
@@ -60,6 +27,10 @@ pub struct EvaluationCache {
     pub pawn_structure_score: i32,
     pub king_safety_score: i32,
     pub mobility_score: i32,
+    /// Rook and minor piece placement terms beyond the piece-square tables
+    pub piece_activity_score: i32,
+    /// Attacks on undefended or more valuable pieces, and pawn push threats
+    pub threat_score: i32,
     pub zobrist_hash: u64,
     pub phase: GamePhase,
 }
@@ -83,13 +54,169 @@ impl GamePhase {
     }
 }
 
+/// Number of independently locked shards in a `SharedEvaluationCache`
+pub const SHARED_CACHE_SHARDS: usize = 64;
+/// Slots in each shard, a power of two
+pub const SHARED_CACHE_SHARD_CAPACITY: usize = 4096;
+
+/// Cached components with the key bits the slot index does not already cover
+#[derive(Debug, Clone)]
+struct SharedCacheSlot {
+    verification: u16,
+    entry: EvaluationCache,
+}
+
+/// One shard's slots, indexed by the low bits of the key
+type SharedCacheShard = Mutex<Box<[Option<SharedCacheSlot>]>>;
+
+/// Evaluation components shared by every thread evaluating positions in parallel.
+///
+/// Entries are spread over `SHARED_CACHE_SHARDS` mutex-guarded fixed-size tables by
+/// Zobrist key, so threads only contend when they touch the same shard and memory stays
+/// bounded. Each slot keeps 16 more bits of the key to tell positions sharing it apart,
+/// and a new entry always replaces the old one. Components are stored rather than final
+/// scores because the score also depends on the halfmove clock, which the key does not
+/// cover.
+pub struct SharedEvaluationCache {
+    shards: Box<[SharedCacheShard]>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    collisions: AtomicU64,
+}
+
+impl Default for SharedEvaluationCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SharedEvaluationCache {
+    pub fn new() -> Self {
+        SharedEvaluationCache {
+            shards: (0..SHARED_CACHE_SHARDS)
+                .map(|_| Mutex::new(vec![None; SHARED_CACHE_SHARD_CAPACITY].into_boxed_slice()))
+                .collect(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            collisions: AtomicU64::new(0),
+        }
+    }
+
+    fn shard(&self, zobrist: u64) -> &SharedCacheShard {
+        // The low bits pick the slot inside each shard, so shard on the high bits
+        &self.shards[(zobrist >> 58) as usize % SHARED_CACHE_SHARDS]
+    }
+
+    fn slot_index(zobrist: u64) -> usize {
+        zobrist as usize & (SHARED_CACHE_SHARD_CAPACITY - 1)
+    }
+
+    /// Key bits used by neither the shard nor the slot index
+    fn verification(zobrist: u64) -> u16 {
+        (zobrist >> 32) as u16
+    }
+
+    fn get(&self, zobrist: u64) -> Option<EvaluationCache> {
+        let entry = self.shard(zobrist).lock().ok().and_then(|shard| {
+            shard[Self::slot_index(zobrist)]
+                .as_ref()
+                .filter(|slot| slot.verification == Self::verification(zobrist))
+                .map(|slot| slot.entry.clone())
+        });
+        let counter = if entry.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        entry
+    }
+
+    fn insert(&self, zobrist: u64, entry: EvaluationCache) {
+        if let Ok(mut shard) = self.shard(zobrist).lock() {
+            let verification = Self::verification(zobrist);
+            let slot = &mut shard[Self::slot_index(zobrist)];
+            if slot
+                .as_ref()
+                .is_some_and(|slot| slot.verification != verification)
+            {
+                self.collisions.fetch_add(1, Ordering::Relaxed);
+            }
+            *slot = Some(SharedCacheSlot {
+                verification,
+                entry,
+            });
+        }
+    }
+
+    /// Number of cached positions
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .filter_map(|shard| {
+                let shard = shard.lock().ok()?;
+                Some(shard.iter().filter(|slot| slot.is_some()).count())
+            })
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get cache statistics as (hits, misses, collisions, hit rate), where a collision
+    /// is an entry for one position replaced by another position's
+    pub fn stats(&self) -> (u64, u64, u64, f64) {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let collisions = self.collisions.load(Ordering::Relaxed);
+        let total = hits + misses;
+        let hit_rate = if total > 0 {
+            hits as f64 / total as f64
+        } else {
+            0.0
+        };
+        (hits, misses, collisions, hit_rate)
+    }
+
+    pub fn clear(&self) {
+        crate::log_debug!(
+            "clearing shared evaluation cache after {} collisions",
+            self.collisions.load(Ordering::Relaxed)
+        );
+        for shard in self.shards.iter() {
+            if let Ok(mut shard) = shard.lock() {
+                shard.fill(None);
+            }
+        }
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+        self.collisions.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Pawn-only structure score (White-relative) with each side's passed pawns
+#[derive(Debug, Clone, Copy)]
+struct PawnEntry {
+    // The pawn hash can collide, so entries keep the pawns they were computed for
+    white_pawns: Bitboard,
+    black_pawns: Bitboard,
+    score: i32,
+    white_passed: Bitboard,
+    black_passed: Bitboard,
+    white_semi_open: u8,
+    black_semi_open: u8,
+}
+
 /// Advanced evaluator with lazy evaluation and incremental updates
 #[derive(Clone)]
 pub struct OptimizedEvaluator {
     evaluation_cache: HashMap<u64, EvaluationCache>,
-    pawn_structure_cache: HashMap<u64, i32>,
+    pawn_structure_cache: HashMap<u64, PawnEntry>,
     king_safety_cache: HashMap<(u64, Color), i32>,
     mobility_cache: HashMap<u64, (i32, i32)>, // (white_mobility, black_mobility)
+    move_generator: MoveGenerator,
+    params: EvalParams,
     cache_hits: u64,
     cache_misses: u64,
 }
@@ -102,17 +229,30 @@ impl Default for OptimizedEvaluator {
 
 impl OptimizedEvaluator {
     pub fn new() -> Self {
+        Self::with_params(EvalParams::default())
+    }
+
+    pub fn with_params(params: EvalParams) -> Self {
         OptimizedEvaluator {
             evaluation_cache: HashMap::with_capacity(1024 * 64), // 64K entries
             pawn_structure_cache: HashMap::with_capacity(1024 * 16), // 16K entries
             king_safety_cache: HashMap::with_capacity(1024 * 8), // 8K entries
             mobility_cache: HashMap::with_capacity(1024 * 32),   // 32K entries
+            move_generator: MoveGenerator::new(),
+            params,
             cache_hits: 0,
             cache_misses: 0,
         }
     }
 
-    /// Main evaluation function with caching
+    pub fn params(&self) -> &EvalParams {
+        &self.params
+    }
+
+    /// Main evaluation function with caching.
+    ///
+    /// Like `Evaluator::evaluate`, the score is from the side to move's point of view,
+    /// so it can be used directly in a negamax search. It includes `TEMPO_BONUS`.
     pub fn evaluate(&mut self, position: &Position) -> i32 {
         let zobrist = position.zobrist_hash();
 
@@ -134,6 +274,29 @@ impl OptimizedEvaluator {
         score
     }
 
+    /// Evaluate through a cache shared with other threads instead of this evaluator's
+    /// own caches, so it only needs `&self` and one evaluator can serve a whole pool.
+    ///
+    /// Returns the same score as `evaluate`.
+    pub fn evaluate_shared(&self, position: &Position, cache: &SharedEvaluationCache) -> i32 {
+        let zobrist = position.zobrist_hash();
+        if let Some(cached) = cache.get(zobrist) {
+            return self.interpolate_evaluation(&cached, position);
+        }
+
+        let pawns = self.compute_pawn_entry(position);
+        let king_cover = [
+            self.evaluate_king_safety(position, Color::White),
+            self.evaluate_king_safety(position, Color::Black),
+        ];
+        let mobility = self.evaluate_mobility(position);
+        let eval_cache = self.evaluate_components(position, pawns, king_cover, mobility);
+        let score = self.interpolate_evaluation(&eval_cache, position);
+        cache.insert(zobrist, eval_cache);
+
+        score
+    }
+
     /// Lazy evaluation with incremental updates
     pub fn evaluate_incremental(
         &mut self,
@@ -177,32 +340,12 @@ impl OptimizedEvaluator {
 
     fn compute_full_evaluation(&mut self, position: &Position) -> EvaluationCache {
         let zobrist = position.zobrist_hash();
-
-        // Material evaluation
-        let material_score = self.evaluate_material(position);
-
-        // Positional evaluation
-        let positional_score = self.evaluate_position(position);
-
-        // Pawn structure (cached)
-        let pawn_hash = self.compute_pawn_hash(position);
-        let pawn_structure_score = self
-            .pawn_structure_cache
-            .get(&pawn_hash)
-            .copied()
-            .unwrap_or_else(|| {
-                let score = self.evaluate_pawn_structure(position);
-                self.pawn_structure_cache.insert(pawn_hash, score);
-                score
-            });
-
-        // King safety (cached by color)
-        let white_king_safety = self.get_or_compute_king_safety(position, Color::White);
-        let black_king_safety = self.get_or_compute_king_safety(position, Color::Black);
-        let king_safety_score = white_king_safety - black_king_safety;
-
-        // Mobility evaluation (cached)
-        let (white_mobility, black_mobility) = self
+        let pawns = self.get_or_compute_pawn_entry(position);
+        let king_cover = [
+            self.get_or_compute_king_safety(position, Color::White),
+            self.get_or_compute_king_safety(position, Color::Black),
+        ];
+        let mobility = self
             .mobility_cache
             .get(&zobrist)
             .copied()
@@ -211,10 +354,77 @@ impl OptimizedEvaluator {
                 self.mobility_cache.insert(zobrist, mobility);
                 mobility
             });
-        let mobility_score = white_mobility - black_mobility;
 
-        // Determine game phase
-        let phase = GamePhase::from_material(material_score.abs());
+        self.evaluate_components(position, pawns, king_cover, mobility)
+    }
+
+    /// All components, given the cacheable pawn entry, king pawn cover and mobility
+    fn evaluate_components(
+        &self,
+        position: &Position,
+        pawns: PawnEntry,
+        king_cover: [i32; 2],
+        (white_mobility, black_mobility): (i32, i32),
+    ) -> EvaluationCache {
+        let zobrist = position.zobrist_hash();
+
+        // Material evaluation
+        let material_score = self.evaluate_material(position);
+
+        // Determine game phase from the non-pawn material left on the board
+        let phase = GamePhase::from_material(self.non_pawn_material(position));
+
+        // Positional evaluation
+        let positional_score = self.evaluate_position(position, phase);
+
+        // Pawn structure (pawn-only terms cached, passed pawns scored against the pieces)
+        let endgame = phase == GamePhase::EndGame;
+        let pawn_structure_score = pawns.score
+            + passed_pawn_score(position, Color::White, pawns.white_passed, endgame)
+            - passed_pawn_score(position, Color::Black, pawns.black_passed, endgame);
+
+        // Piece activity, using the file states stored with the pawn entry
+        let piece_activity_score = rook_score(
+            position,
+            Color::White,
+            pawns.white_semi_open,
+            pawns.black_semi_open,
+        ) - rook_score(
+            position,
+            Color::Black,
+            pawns.black_semi_open,
+            pawns.white_semi_open,
+        ) + minor_piece_score(position, Color::White)
+            - minor_piece_score(position, Color::Black);
+
+        // Attack maps for threats and king danger
+        let white_attacks = AttackMap::compute(position, Color::White, &self.move_generator);
+        let black_attacks = AttackMap::compute(position, Color::Black, &self.move_generator);
+        let threat_score = threat_score(position, Color::White, &white_attacks, &black_attacks)
+            - threat_score(position, Color::Black, &black_attacks, &white_attacks);
+
+        // King safety: the pawn cover part is cached by king and pawns, while safe checks
+        // and tropism depend on the pieces
+        let white_king_safety = king_cover[0]
+            - king_danger(
+                position,
+                Color::White,
+                &white_attacks,
+                &black_attacks,
+                &self.move_generator,
+            );
+        let black_king_safety = king_cover[1]
+            - king_danger(
+                position,
+                Color::Black,
+                &black_attacks,
+                &white_attacks,
+                &self.move_generator,
+            );
+        let king_safety_score = white_king_safety - black_king_safety;
+
+        // Mobility evaluation (cached)
+        let mobility_score = (white_mobility - black_mobility) * MOBILITY_WEIGHT;
 
         EvaluationCache {
             material_score,
@@ -222,6 +432,8 @@ impl OptimizedEvaluator {
             pawn_structure_score,
             king_safety_score,
             mobility_score,
+            piece_activity_score,
+            threat_score,
             zobrist_hash: zobrist,
             phase,
         }
@@ -234,7 +446,11 @@ impl OptimizedEvaluator {
         move_made: crate::Move,
     ) -> Option<EvaluationCache> {
         // Only support simple moves for now (no captures, castling, etc.)
-        if move_made.is_capture() || move_made.is_castle() || move_made.is_promotion() {
+        if move_made.is_capture()
+            || move_made.is_castle()
+            || move_made.is_promotion()
+            || move_made.is_drop()
+        {
             return None;
         }
 
@@ -266,6 +482,15 @@ impl OptimizedEvaluator {
     }
 
     fn interpolate_evaluation(&self, cached: &EvaluationCache, position: &Position) -> i32 {
+        // Known endings replace the general terms
+        if let Some(white_score) = endgame::evaluate(position) {
+            let score = match position.side_to_move() {
+                Color::White => white_score,
+                Color::Black => -white_score,
+            };
+            return score + TEMPO_BONUS;
+        }
+
         let game_phase_factor = match cached.phase {
             GamePhase::Opening => 1.2,
             GamePhase::MiddleGame => 1.0,
@@ -276,15 +501,20 @@ impl OptimizedEvaluator {
             + cached.positional_score
             + cached.pawn_structure_score
             + cached.king_safety_score
-            + cached.mobility_score;
-
-        let side_to_move_bonus = if position.side_to_move() == Color::White {
-            10
-        } else {
-            -10
+            + cached.mobility_score
+            + cached.piece_activity_score
+            + cached.threat_score;
+
+        // Components are White-relative; the phase and draw scaling are symmetric, so
+        // they are applied before switching to the side to move's view
+        let white_score = (total_score as f32 * game_phase_factor) as i32;
+        let white_score = scale_score(white_score, position);
+        let score = match position.side_to_move() {
+            Color::White => white_score,
+            Color::Black => -white_score,
         };
 
-        ((total_score as f32 * game_phase_factor) as i32) + side_to_move_bonus
+        score + TEMPO_BONUS
     }
 
     fn evaluate_material(&self, position: &Position) -> i32 {
@@ -300,27 +530,24 @@ impl OptimizedEvaluator {
         ] {
             let white_count = self.count_pieces(position, piece_type, Color::White);
             let black_count = self.count_pieces(position, piece_type, Color::Black);
-            let piece_value = PIECE_VALUES[piece_type as usize];
+            let piece_value = self.params.piece_value(piece_type).middlegame;
 
             score += (white_count as i32 - black_count as i32) * piece_value;
         }
 
-        score
+        score + hand_material(position, Color::White, &self.params)
+            - hand_material(position, Color::Black, &self.params)
     }
 
-    fn evaluate_position(&self, position: &Position) -> i32 {
+    fn evaluate_position(&self, position: &Position, phase: GamePhase) -> i32 {
         let mut score = 0;
 
         // Evaluate piece-square tables
         for square_idx in 0..64 {
             if let Some(square) = Square::new(square_idx) {
                 if let Some(piece) = position.piece_at(square) {
-                    let piece_value = self.get_piece_square_value(
-                        piece.piece_type,
-                        square,
-                        piece.color,
-                        GamePhase::MiddleGame, // Simplified for now
-                    );
+                    let piece_value =
+                        self.get_piece_square_value(piece.piece_type, square, piece.color, phase);
 
                     score += if piece.color == Color::White {
                         piece_value
@@ -334,34 +561,43 @@ impl OptimizedEvaluator {
         score
     }
 
-    fn evaluate_pawn_structure(&self, position: &Position) -> i32 {
-        let mut score = 0;
-
-        // Evaluate doubled pawns, isolated pawns, passed pawns, etc.
+    fn get_or_compute_pawn_entry(&mut self, position: &Position) -> PawnEntry {
         let white_pawns = position.piece_bitboard(PieceType::Pawn, Color::White);
         let black_pawns = position.piece_bitboard(PieceType::Pawn, Color::Black);
+        let pawn_hash = self.compute_pawn_hash(position);
 
-        // Doubled pawns penalty
-        score -= self.count_doubled_pawns(white_pawns) * 10;
-        score += self.count_doubled_pawns(black_pawns) * 10;
-
-        // Isolated pawns penalty
-        score -= self.count_isolated_pawns(white_pawns) * 15;
-        score += self.count_isolated_pawns(black_pawns) * 15;
-
-        // Passed pawns bonus
-        score += self.count_passed_pawns(white_pawns, black_pawns) * 30;
-        score -= self.count_passed_pawns(black_pawns, white_pawns) * 30;
+        if let Some(entry) = self.pawn_structure_cache.get(&pawn_hash) {
+            if entry.white_pawns == white_pawns && entry.black_pawns == black_pawns {
+                return *entry;
+            }
+        }
 
-        score
+        let entry = self.compute_pawn_entry(position);
+        self.pawn_structure_cache.insert(pawn_hash, entry);
+        entry
     }
 
-    fn evaluate_mobility(&self, _position: &Position) -> (i32, i32) {
-        // This would require move generation, simplified for now
-        let white_mobility = 20; // Placeholder
-        let black_mobility = 18; // Placeholder
+    fn compute_pawn_entry(&self, position: &Position) -> PawnEntry {
+        let white_pawns = position.piece_bitboard(PieceType::Pawn, Color::White);
+        let black_pawns = position.piece_bitboard(PieceType::Pawn, Color::Black);
+        let white = PawnStructure::analyze(white_pawns, black_pawns, Color::White);
+        let black = PawnStructure::analyze(black_pawns, white_pawns, Color::Black);
+        PawnEntry {
+            white_pawns,
+            black_pawns,
+            score: white.pawn_only_score(Color::White) - black.pawn_only_score(Color::Black),
+            white_passed: white.passed,
+            black_passed: black.passed,
+            white_semi_open: semi_open_files(white_pawns),
+            black_semi_open: semi_open_files(black_pawns),
+        }
+    }
 
-        (white_mobility, black_mobility)
+    fn evaluate_mobility(&self, position: &Position) -> (i32, i32) {
+        (
+            mobility(position, Color::White, &self.move_generator),
+            mobility(position, Color::Black, &self.move_generator),
+        )
     }
 
     fn get_or_compute_king_safety(&mut self, position: &Position, color: Color) -> i32 {
@@ -417,24 +653,9 @@ impl OptimizedEvaluator {
         color: Color,
         phase: GamePhase,
     ) -> i32 {
-        let square_idx = if color == Color::White {
-            square.index() as usize
-        } else {
-            // Flip board for black pieces
-            (square.index() ^ 56) as usize
-        };
-
-        match piece_type {
-            PieceType::Pawn => PAWN_TABLE[square_idx],
-            PieceType::Knight => KNIGHT_TABLE[square_idx],
-            PieceType::Bishop => BISHOP_TABLE[square_idx],
-            PieceType::Rook => ROOK_TABLE[square_idx],
-            PieceType::Queen => QUEEN_TABLE[square_idx],
-            PieceType::King => match phase {
-                GamePhase::EndGame => KING_END_GAME[square_idx],
-                _ => KING_MIDDLE_GAME[square_idx],
-            },
-        }
+        self.params
+            .piece_square(piece_type, color, square)
+            .select(phase == GamePhase::EndGame)
     }
 
     // SIMD helper functions
@@ -453,7 +674,7 @@ impl OptimizedEvaluator {
         // SIMD material computation (simplified for single position)
         let mut total = 0;
         for (i, &count) in piece_counts[0].iter().enumerate() {
-            total += count * PIECE_VALUES[i];
+            total += count * self.params.piece_values[i].middlegame;
         }
         [total]
     }
@@ -463,98 +684,49 @@ impl OptimizedEvaluator {
         position.piece_bitboard(piece_type, color).count_bits()
     }
 
-    fn compute_pawn_hash(&self, position: &Position) -> u64 {
-        // Hash based only on pawn positions
-        let white_pawns = position.piece_bitboard(PieceType::Pawn, Color::White);
-        let black_pawns = position.piece_bitboard(PieceType::Pawn, Color::Black);
-        white_pawns.value() ^ black_pawns.value().rotate_left(32)
-    }
-
-    fn compute_king_pawn_hash(&self, position: &Position, color: Color) -> u64 {
-        let king = position.piece_bitboard(PieceType::King, color);
-        let pawns = position.piece_bitboard(PieceType::Pawn, color);
-        king.value() ^ pawns.value()
-    }
-
-    fn count_doubled_pawns(&self, pawns: Bitboard) -> i32 {
-        let mut count = 0;
-        for file in 0..8 {
-            let file_mask = Bitboard::FILE_A.value() << file;
-            let pawns_on_file = (pawns.value() & file_mask).count_ones();
-            if pawns_on_file > 1 {
-                count += (pawns_on_file - 1) as i32;
+    fn non_pawn_material(&self, position: &Position) -> i32 {
+        let mut material = 0;
+        for color in [Color::White, Color::Black] {
+            for piece_type in [
+                PieceType::Knight,
+                PieceType::Bishop,
+                PieceType::Rook,
+                PieceType::Queen,
+            ] {
+                material += self.count_pieces(position, piece_type, color) as i32
+                    * self.params.piece_value(piece_type).middlegame;
             }
         }
-        count
-    }
-
-    fn count_isolated_pawns(&self, pawns: Bitboard) -> i32 {
-        let mut count = 0;
-        for file in 0..8 {
-            let file_mask = Bitboard::FILE_A.value() << file;
-            if pawns.value() & file_mask != 0 {
-                // Check adjacent files
-                let adjacent_files = if file == 0 {
-                    Bitboard::FILE_B.value()
-                } else if file == 7 {
-                    Bitboard::FILE_G.value()
-                } else {
-                    (Bitboard::FILE_A.value() << (file - 1))
-                        | (Bitboard::FILE_A.value() << (file + 1))
-                };
-
-                if pawns.value() & adjacent_files == 0 {
-                    count += (pawns.value() & file_mask).count_ones() as i32;
-                }
-            }
-        }
-        count
+        material
     }
 
-    fn count_passed_pawns(&self, own_pawns: Bitboard, enemy_pawns: Bitboard) -> i32 {
-        // Simplified passed pawn detection
-        let mut count = 0;
-        let mut pawns = own_pawns;
-
-        while let Some(square_idx) = pawns.pop_lsb() {
-            let square = Square::new(square_idx as u8).unwrap();
-            let file = square.file();
-
-            // Check if there are enemy pawns ahead on same file or adjacent files
-            let ahead_mask = self.get_ahead_mask(square, Color::White); // Simplified
-            let relevant_files = if file == 0 {
-                Bitboard::FILE_A | Bitboard::FILE_B
-            } else if file == 7 {
-                Bitboard::FILE_G | Bitboard::FILE_H
-            } else {
-                Bitboard::new(
-                    (Bitboard::FILE_A.value() << (file - 1))
-                        | (Bitboard::FILE_A.value() << file)
-                        | (Bitboard::FILE_A.value() << (file + 1)),
+    fn compute_pawn_hash(&self, position: &Position) -> u64 {
+        // Hash based only on pawn positions
+        let keys = zobrist_keys();
+        [Color::White, Color::Black]
+            .into_iter()
+            .map(|color| {
+                keys.hash_pieces(
+                    Piece::new(PieceType::Pawn, color),
+                    position.piece_bitboard(PieceType::Pawn, color),
                 )
-            };
-
-            if (enemy_pawns & ahead_mask & relevant_files).is_empty() {
-                count += 1;
-            }
-        }
-
-        count
+            })
+            .fold(0, |hash, key| hash ^ key)
     }
 
-    fn get_ahead_mask(&self, square: Square, color: Color) -> Bitboard {
-        // Get mask of squares ahead of the given square
-        if color == Color::White {
-            let rank_mask = !((1u64 << ((square.rank() + 1) * 8)) - 1);
-            Bitboard::new(rank_mask)
-        } else {
-            let rank_mask = (1u64 << (square.rank() * 8)) - 1;
-            Bitboard::new(rank_mask)
-        }
+    fn compute_king_pawn_hash(&self, position: &Position, color: Color) -> u64 {
+        let keys = zobrist_keys();
+        keys.hash_pieces(
+            Piece::new(PieceType::King, color),
+            position.piece_bitboard(PieceType::King, color),
+        ) ^ keys.hash_pieces(
+            Piece::new(PieceType::Pawn, color),
+            position.piece_bitboard(PieceType::Pawn, color),
+        )
     }
 
     fn has_pawn_on_file(&self, position: &Position, file: u8, color: Color) -> bool {
-        let file_mask = Bitboard::FILE_A.value() << file;
+        let file_mask = Bitboard::FILES[file as usize].value();
         let pawns = position.piece_bitboard(PieceType::Pawn, color);
         (pawns.value() & file_mask) != 0
     }
@@ -572,6 +744,11 @@ impl OptimizedEvaluator {
 
     /// Clear all caches
     pub fn clear_caches(&mut self) {
+        crate::log_debug!(
+            "clearing evaluation caches after {} hits and {} misses",
+            self.cache_hits,
+            self.cache_misses
+        );
         self.evaluation_cache.clear();
         self.pawn_structure_cache.clear();
         self.king_safety_cache.clear();
@@ -592,13 +769,6 @@ mod tests {
         assert_eq!(GamePhase::from_material(1000), GamePhase::EndGame);
     }
 
-    #[test]
-    fn test_piece_values() {
-        assert_eq!(PIECE_VALUES[PieceType::Pawn as usize], 100);
-        assert_eq!(PIECE_VALUES[PieceType::Queen as usize], 900);
-        assert_eq!(PIECE_VALUES[PieceType::King as usize], 20000);
-    }
-
     #[test]
     fn test_evaluator_creation() {
         let evaluator = OptimizedEvaluator::new();
@@ -609,15 +779,80 @@ mod tests {
     }
 
     #[test]
-    fn test_doubled_pawns_count() {
-        let evaluator = OptimizedEvaluator::new();
+    fn test_shared_cache_matches_own_caches() {
+        let mut evaluator = OptimizedEvaluator::new();
+        let cache = SharedEvaluationCache::new();
+        let position = Position::from_fen(
+            "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+        )
+        .unwrap();
+
+        let shared = evaluator.evaluate_shared(&position, &cache);
+        assert_eq!(shared, evaluator.evaluate(&position));
+        assert_eq!(evaluator.evaluate_shared(&position, &cache), shared);
+        assert_eq!(cache.len(), 1);
+
+        let (hits, misses, collisions, _) = cache.stats();
+        assert_eq!((hits, misses, collisions), (1, 1, 0));
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_shared_cache_replaces_colliding_entries() {
+        let cache = SharedEvaluationCache::new();
+        let components = |material_score| EvaluationCache {
+            material_score,
+            positional_score: 0,
+            pawn_structure_score: 0,
+            king_safety_score: 0,
+            mobility_score: 0,
+            piece_activity_score: 0,
+            threat_score: 0,
+            zobrist_hash: 0,
+            phase: GamePhase::MiddleGame,
+        };
+        // Same shard and slot, different verification bits
+        let first = 0x1234_5678_9abc_0123;
+        let second = first ^ (1 << 40);
 
+        cache.insert(first, components(100));
+        assert!(cache.get(second).is_none());
+        cache.insert(second, components(200));
+        assert!(cache.get(first).is_none());
+        assert_eq!(cache.get(second).unwrap().material_score, 200);
+        cache.insert(second, components(300));
+
+        let (hits, misses, collisions, _) = cache.stats();
+        assert_eq!((hits, misses, collisions), (1, 2, 1));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_score_is_from_side_to_move() {
+        let mut evaluator = OptimizedEvaluator::new();
+        let white_to_move = Position::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        let black_to_move = Position::from_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap();
+
+        let white_score = evaluator.evaluate(&white_to_move);
+        let black_score = evaluator.evaluate(&black_to_move);
+        assert!(white_score > 800);
+        assert!(black_score < -800);
+        // Only the tempo bonus differs between the two views
+        assert_eq!(white_score + black_score, 2 * TEMPO_BONUS);
+    }
+
+    #[test]
+    fn test_doubled_pawns_count() {
         // Test with no doubled pawns
         let pawns = Bitboard::new(0x00FF000000000000); // Pawns on different files
-        assert_eq!(evaluator.count_doubled_pawns(pawns), 0);
+        let structure = PawnStructure::analyze(pawns, Bitboard::EMPTY, Color::White);
+        assert_eq!(structure.doubled, 0);
 
         // Test with doubled pawns
         let doubled_pawns = Bitboard::new(0x0101000000000000); // Two pawns on A file
-        assert_eq!(evaluator.count_doubled_pawns(doubled_pawns), 1);
+        let structure = PawnStructure::analyze(doubled_pawns, Bitboard::EMPTY, Color::White);
+        assert_eq!(structure.doubled, 1);
     }
 }
@@ -0,0 +1,238 @@
+// Specialized evaluation of known endings, looked up by material signature
+//
+// Some endings are misjudged by the general terms: KBNvK is a win only if the losing
+// king is driven into a corner of the bishop's colour, while KRPvKR and opposite
+// coloured bishop endings are often draws despite a pawn more. Value functions replace
+// the general evaluation outright; scale functions only shrink it toward a draw and
+// may decline, leaving the generic scaling in charge. Every ending is registered for
+// both colours, keyed by the material key of each orientation.
+
+use super::params::DEFAULT_PARAMS;
+use super::pawns::relative_rank;
+use super::scaling::SCALE_OPPOSITE_BISHOPS;
+use crate::{Bitboard, Color, MaterialKey, PieceType, Position, Square, Variant};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Added to the material of a won ending, so that reaching it beats keeping extra
+/// material of an unclear one
+pub const KNOWN_WIN_BONUS: i32 = 200;
+/// Bonus per step the kings are brought closer together
+const CLOSE_WEIGHT: i32 = 10;
+/// Bonus per step the losing king is driven toward a corner the bishop can cover
+const CORNER_WEIGHT: i32 = 40;
+/// Scale factor of a KRPvKR ending whose defending king blocks the pawn
+pub const SCALE_KRPKR_BLOCKED: i32 = 16;
+
+/// Score of an ending from the strong side's point of view
+type ValueFn = fn(&Position, Color) -> i32;
+/// Scale factor out of `SCALE_NORMAL`, or `None` to keep the generic factor
+type ScaleFn = fn(&Position, Color) -> Option<i32>;
+
+#[derive(Default)]
+struct EndgameTable {
+    values: HashMap<MaterialKey, (ValueFn, Color)>,
+    scales: HashMap<MaterialKey, (ScaleFn, Color)>,
+}
+
+static ENDGAMES: OnceLock<EndgameTable> = OnceLock::new();
+
+impl EndgameTable {
+    fn get() -> &'static EndgameTable {
+        ENDGAMES.get_or_init(EndgameTable::build)
+    }
+
+    fn build() -> Self {
+        let mut table = EndgameTable::default();
+        table.add_value("KBNvK", mate_with_bishop_and_knight);
+        table.add_scale("KRPvKR", krp_vs_kr);
+
+        // Opposite coloured bishops with any pawns, the side with more pawns counting
+        // as the strong one
+        for strong_pawns in 0..=8 {
+            for weak_pawns in 0..=strong_pawns {
+                let key =
+                    MaterialKey::from_counts([strong_pawns, 0, 1, 0, 0], [weak_pawns, 0, 1, 0, 0]);
+                table.add_scale_key(key, opposite_bishops);
+            }
+        }
+        table
+    }
+
+    fn add_value(&mut self, signature: &str, value: ValueFn) {
+        let key = signature_key(signature);
+        self.values.insert(key, (value, Color::White));
+        self.values.insert(key.mirrored(), (value, Color::Black));
+    }
+
+    fn add_scale(&mut self, signature: &str, scale: ScaleFn) {
+        self.add_scale_key(signature_key(signature), scale);
+    }
+
+    fn add_scale_key(&mut self, key: MaterialKey, scale: ScaleFn) {
+        self.scales.insert(key, (scale, Color::White));
+        self.scales
+            .entry(key.mirrored())
+            .or_insert((scale, Color::Black));
+    }
+}
+
+/// Material key of a signature such as `KRPvKR`, strong side first
+fn signature_key(signature: &str) -> MaterialKey {
+    let (strong, weak) = signature.split_once('v').expect("signature needs a 'v'");
+    let counts = |side: &str| {
+        let mut counts = [0u8; 5];
+        for symbol in side.chars().filter(|&symbol| symbol != 'K') {
+            let index = match symbol {
+                'P' => 0,
+                'N' => 1,
+                'B' => 2,
+                'R' => 3,
+                'Q' => 4,
+                _ => panic!("invalid piece in signature: {}", symbol),
+            };
+            counts[index] += 1;
+        }
+        counts
+    };
+    MaterialKey::from_counts(counts(strong), counts(weak))
+}
+
+/// Specialized White-relative score of a known ending, or `None` to use the general
+/// evaluation
+pub fn evaluate(position: &Position) -> Option<i32> {
+    if position.variant != Variant::Standard {
+        return None;
+    }
+    let &(value, strong) = EndgameTable::get().values.get(&position.material_key())?;
+    let score = value(position, strong);
+    Some(match strong {
+        Color::White => score,
+        Color::Black => -score,
+    })
+}
+
+/// Scale factor of a known drawish ending, or `None` to use the generic scaling
+pub fn scale_factor(position: &Position) -> Option<i32> {
+    if position.variant != Variant::Standard {
+        return None;
+    }
+    let &(scale, strong) = EndgameTable::get().scales.get(&position.material_key())?;
+    scale(position, strong)
+}
+
+/// Whether the material on the board has a specialized value or scale function
+pub fn is_known_ending(key: MaterialKey) -> bool {
+    let table = EndgameTable::get();
+    table.values.contains_key(&key) || table.scales.contains_key(&key)
+}
+
+fn king(position: &Position, color: Color) -> Square {
+    position
+        .piece_bitboard(PieceType::King, color)
+        .lsb()
+        .map(Square::from)
+        .expect("specialized endings have both kings")
+}
+
+fn closeness(a: Square, b: Square) -> i32 {
+    CLOSE_WEIGHT * (7 - a.distance(b) as i32)
+}
+
+/// KBNvK: mate is only forced in a corner of the bishop's colour, so aim for those
+fn mate_with_bishop_and_knight(position: &Position, strong: Color) -> i32 {
+    let strong_king = king(position, strong);
+    let weak_king = king(position, strong.opposite());
+    let bishops = position.piece_bitboard(PieceType::Bishop, strong);
+    let corners = if (bishops & Bitboard::LIGHT_SQUARES).is_not_empty() {
+        [Square::A8, Square::H1]
+    } else {
+        [Square::A1, Square::H8]
+    };
+    let corner_distance = corners
+        .iter()
+        .map(|&corner| weak_king.distance(corner) as i32)
+        .min()
+        .unwrap_or(7);
+
+    let material = DEFAULT_PARAMS.piece_value(PieceType::Bishop).endgame
+        + DEFAULT_PARAMS.piece_value(PieceType::Knight).endgame;
+    material
+        + KNOWN_WIN_BONUS
+        + CORNER_WEIGHT * (7 - corner_distance)
+        + closeness(strong_king, weak_king)
+}
+
+/// KRPvKR: a defending king standing in front of the pawn holds the draw
+fn krp_vs_kr(position: &Position, strong: Color) -> Option<i32> {
+    let weak = strong.opposite();
+    let pawn = position
+        .piece_bitboard(PieceType::Pawn, strong)
+        .lsb()
+        .map(Square::from)?;
+    let weak_king = king(position, weak);
+
+    let on_path = (weak_king.file() as i32 - pawn.file() as i32).abs() <= 1;
+    let ahead = relative_rank(strong, weak_king) > relative_rank(strong, pawn);
+    (on_path && ahead).then_some(SCALE_KRPKR_BLOCKED)
+}
+
+/// Bishops of opposite colours with nothing but pawns besides are hard to win
+fn opposite_bishops(position: &Position, strong: Color) -> Option<i32> {
+    let light = |color| {
+        (position.piece_bitboard(PieceType::Bishop, color) & Bitboard::LIGHT_SQUARES).is_not_empty()
+    };
+    (light(strong) != light(strong.opposite())).then_some(SCALE_OPPOSITE_BISHOPS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(fen: &str) -> Position {
+        Position::from_fen(fen).unwrap()
+    }
+
+    #[test]
+    fn test_signatures_register_both_colours() {
+        let key = signature_key("KRPvKR");
+        assert_eq!(key.to_string(), "KRPvKR");
+        assert!(is_known_ending(key));
+        assert!(is_known_ending(key.mirrored()));
+        assert!(is_known_ending(signature_key("KBNvK")));
+        assert!(!is_known_ending(signature_key("KQvKR")));
+    }
+
+    #[test]
+    fn test_bishop_and_knight_drive_to_the_right_corner() {
+        // Light-squared bishop on f1: a8 and h1 are the mating corners
+        let right = evaluate(&position("k7/8/1K6/8/8/8/8/5BN1 w - - 0 1")).unwrap();
+        let wrong = evaluate(&position("7k/8/6K1/8/8/8/8/5BN1 w - - 0 1")).unwrap();
+        assert!(wrong > KNOWN_WIN_BONUS);
+        assert!(right > wrong);
+    }
+
+    #[test]
+    fn test_black_winning_ending_is_negative() {
+        let corner = evaluate(&position("K7/8/8/3k4/8/8/8/5bn1 w - - 0 1")).unwrap();
+        let centre = evaluate(&position("8/8/8/3k4/3K4/8/8/5bn1 w - - 0 1")).unwrap();
+        assert!(centre < -KNOWN_WIN_BONUS);
+        assert!(corner < centre);
+    }
+
+    #[test]
+    fn test_rook_pawn_ending_with_blocking_king() {
+        let blocked = position("4k3/8/8/4P3/8/8/r7/4K2R w - - 0 1");
+        assert_eq!(scale_factor(&blocked), Some(SCALE_KRPKR_BLOCKED));
+        let cut_off = position("k7/8/8/4P3/8/8/r7/4K2R w - - 0 1");
+        assert_eq!(scale_factor(&cut_off), None);
+    }
+
+    #[test]
+    fn test_opposite_bishops_scale() {
+        let opposite = position("2b1k3/p7/8/8/8/8/PP6/2B1K3 w - - 0 1");
+        assert_eq!(scale_factor(&opposite), Some(SCALE_OPPOSITE_BISHOPS));
+        let same = position("3bk3/p7/8/8/8/8/PP6/2B1K3 w - - 0 1");
+        assert_eq!(scale_factor(&same), None);
+    }
+}
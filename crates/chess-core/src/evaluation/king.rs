@@ -0,0 +1,135 @@
+// King danger from enemy pieces, complementing the pawn shield and open file terms
+//
+// Safe checks are squares from which an enemy piece could give check next move without
+// being captured, because no defending piece covers the square. Tropism measures how
+// closely the enemy pieces gather around the king.
+
+use super::threats::AttackMap;
+use crate::{Color, MoveGenerator, OptimizedBitboard, PieceType, Position, Square};
+
+/// Penalty per enemy piece type with at least one safe check, indexed by
+/// `PieceType::index`. Counting types rather than squares keeps a lone queen with many
+/// checks from outweighing a coordinated attack.
+pub const SAFE_CHECK_PENALTY: [i32; 6] = [0, 25, 20, 30, 35, 0];
+
+/// Tropism weight per piece type: each enemy piece costs `weight * (7 - distance)`
+pub const KING_TROPISM_WEIGHT: [i32; 6] = [0, 3, 2, 2, 4, 0];
+
+/// Penalty for the enemy piece types that can give `color`'s king a safe check
+pub fn safe_check_penalty(
+    position: &Position,
+    color: Color,
+    defender_attacks: &AttackMap,
+    attacker_attacks: &AttackMap,
+    move_generator: &MoveGenerator,
+) -> i32 {
+    let Some(king) = position.king_square(color) else {
+        return 0;
+    };
+    let occupied = position.all_pieces();
+    let enemy = color.opposite();
+    let safe = !defender_attacks.all & !position.pieces_of_color(enemy);
+    let piece_types = [
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+    ];
+
+    // All four checking piece types at once: squares checking the king, reachable by
+    // an enemy piece of that type and not covered by a defender
+    let checking_squares = [
+        move_generator.knight_attacks(king),
+        move_generator.bishop_attacks(king, occupied),
+        move_generator.rook_attacks(king, occupied),
+        move_generator.queen_attacks(king, occupied),
+    ];
+    let reachable = piece_types.map(|piece_type| attacker_attacks.by_piece[piece_type.index()]);
+    let checks = OptimizedBitboard::batch_and_4(
+        &OptimizedBitboard::batch_and_4(&checking_squares, &reachable),
+        &[safe; 4],
+    );
+
+    piece_types
+        .iter()
+        .zip(checks)
+        .filter(|(_, checks)| checks.is_not_empty())
+        .map(|(piece_type, _)| SAFE_CHECK_PENALTY[piece_type.index()])
+        .sum()
+}
+
+/// Distance-weighted proximity of the enemy pieces to `color`'s king
+pub fn king_tropism(position: &Position, color: Color) -> i32 {
+    let Some(king) = position.king_square(color) else {
+        return 0;
+    };
+    let enemy = color.opposite();
+    let mut tropism = 0;
+
+    for piece_type in [
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+    ] {
+        for index in position.piece_bitboard(piece_type, enemy).iter() {
+            let distance = Square::from(index).distance(king) as i32;
+            tropism += KING_TROPISM_WEIGHT[piece_type.index()] * (7 - distance);
+        }
+    }
+
+    tropism
+}
+
+/// Combined penalty for `color`'s king from enemy checks and piece proximity
+pub fn king_danger(
+    position: &Position,
+    color: Color,
+    defender_attacks: &AttackMap,
+    attacker_attacks: &AttackMap,
+    move_generator: &MoveGenerator,
+) -> i32 {
+    safe_check_penalty(
+        position,
+        color,
+        defender_attacks,
+        attacker_attacks,
+        move_generator,
+    ) + king_tropism(position, color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn safe_checks(fen: &str, color: Color) -> i32 {
+        let position = Position::from_fen(fen).unwrap();
+        let move_generator = MoveGenerator::new();
+        let defender = AttackMap::compute(&position, color, &move_generator);
+        let attacker = AttackMap::compute(&position, color.opposite(), &move_generator);
+        safe_check_penalty(&position, color, &defender, &attacker, &move_generator)
+    }
+
+    #[test]
+    fn test_safe_checks() {
+        // The a1 rook can check along the a8 rank only from a8, which nothing guards
+        let open = safe_checks("6k1/5ppp/8/8/8/8/8/R5K1 b - - 0 1", Color::Black);
+        assert_eq!(open, SAFE_CHECK_PENALTY[PieceType::Rook.index()]);
+
+        // A black rook on d8 covers the back rank
+        let covered = safe_checks("3r2k1/5ppp/8/8/8/8/8/R5K1 b - - 0 1", Color::Black);
+        assert_eq!(covered, 0);
+    }
+
+    #[test]
+    fn test_king_tropism() {
+        let near = Position::from_fen("6k1/5ppp/5N2/8/8/8/8/6K1 b - - 0 1").unwrap();
+        let far = Position::from_fen("6k1/5ppp/8/8/8/8/8/N5K1 b - - 0 1").unwrap();
+        assert_eq!(
+            king_tropism(&near, Color::Black),
+            KING_TROPISM_WEIGHT[PieceType::Knight.index()] * 5
+        );
+        assert!(king_tropism(&near, Color::Black) > king_tropism(&far, Color::Black));
+        assert_eq!(king_tropism(&near, Color::White), 0);
+    }
+}
@@ -0,0 +1,82 @@
+// Piece mobility counted over the mobility area
+//
+// Raw attack counts credit a knight for covering squares an enemy pawn guards, where it
+// could never stand, and a rook for eyeing its own blocked pawns. The mobility area
+// leaves out the squares attacked by enemy pawns, the side's own king and its own
+// pawns that cannot advance, so only squares a piece could usefully go to or support
+// are counted.
+
+use super::pawns::pawn_attacks;
+use crate::{Bitboard, Color, MoveGenerator, PieceType, Position, Square};
+
+/// Squares whose control counts towards `color`'s mobility
+pub fn mobility_area(position: &Position, color: Color) -> Bitboard {
+    let enemy = color.opposite();
+    let occupied = position.all_pieces();
+    let pawns = position.piece_bitboard(PieceType::Pawn, color);
+    let blocked_pawns = match color {
+        Color::White => pawns & occupied.shift_south(),
+        Color::Black => pawns & occupied.shift_north(),
+    };
+    let enemy_pawn_attacks = pawn_attacks(position.piece_bitboard(PieceType::Pawn, enemy), enemy);
+
+    !(enemy_pawn_attacks | blocked_pawns | position.piece_bitboard(PieceType::King, color))
+}
+
+/// Squares of the mobility area attacked by `color`'s knights, bishops, rooks and queens
+pub fn mobility(position: &Position, color: Color, move_generator: &MoveGenerator) -> i32 {
+    let occupied = position.all_pieces();
+    let area = mobility_area(position, color);
+    let mut mobility = 0;
+
+    for piece_type in [
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+    ] {
+        for square_idx in position.piece_bitboard(piece_type, color).iter() {
+            let square = Square::from(square_idx);
+            let attacks = match piece_type {
+                PieceType::Knight => move_generator.knight_attacks(square),
+                PieceType::Bishop => move_generator.bishop_attacks(square, occupied),
+                PieceType::Rook => move_generator.rook_attacks(square, occupied),
+                _ => move_generator.queen_attacks(square, occupied),
+            };
+            mobility += (attacks & area).count_bits() as i32;
+        }
+    }
+
+    mobility
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_area_excludes_pawn_guarded_blocked_and_king_squares() {
+        let position = Position::from_fen("4k3/8/8/3p4/3P4/8/8/4K3 w - - 0 1").unwrap();
+        let area = mobility_area(&position, Color::White);
+        for square in [Square::C4, Square::E4, Square::D4, Square::E1] {
+            assert!(!area.contains(square), "{}", square);
+        }
+        assert!(area.contains(Square::D5));
+        assert!(area.contains(Square::E2));
+
+        // Black's d5 pawn is blocked too, and White's pawn guards c5 and e5
+        let area = mobility_area(&position, Color::Black);
+        for square in [Square::C5, Square::E5, Square::D5, Square::E8] {
+            assert!(!area.contains(square), "{}", square);
+        }
+    }
+
+    #[test]
+    fn test_squares_guarded_by_pawns_do_not_count() {
+        // The knight attacks eight squares, but the c6 and e6 pawns guard b5 and f5
+        let position = Position::from_fen("4k3/8/2p1p3/8/3N4/8/8/4K3 w - - 0 1").unwrap();
+        let move_generator = MoveGenerator::new();
+        assert_eq!(move_generator.knight_attacks(Square::D4).count_bits(), 8);
+        assert_eq!(mobility(&position, Color::White, &move_generator), 6);
+    }
+}
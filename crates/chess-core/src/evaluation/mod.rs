@@ -1,5 +1,20 @@
 pub mod advanced;
+pub mod endgame;
+pub mod king;
+pub mod mobility;
+pub mod nag;
+pub mod params;
+pub mod pawns;
+pub mod pieces;
+pub mod scaling;
 pub mod standard;
+pub mod threats;
+pub mod wdl;
 
-pub use advanced::{EvaluationCache, GamePhase, OptimizedEvaluator};
-pub use standard::Evaluator;
+pub use advanced::{EvaluationCache, GamePhase, OptimizedEvaluator, SharedEvaluationCache};
+pub use nag::{annotate_moves, Nag, NagThresholds};
+pub use params::{EvalParams, PhaseScore, DEFAULT_PARAMS};
+pub use pawns::PawnStructure;
+pub use standard::{EvalBreakdown, Evaluator};
+pub use threats::AttackMap;
+pub use wdl::{Score, Wdl};
@@ -0,0 +1,149 @@
+// Move annotation glyphs (NAGs) from evaluation swings
+//
+// A move is judged by how much it changes the mover's expected points, not raw
+// centipawns: dropping 150 centipawns from +900 still wins, while the same drop from
+// equality loses the game. Thresholds are given in centipawns as they would apply to a
+// level position and converted to expected points with the WDL model.
+
+use crate::evaluation::wdl::Score;
+use std::fmt;
+
+/// Annotation glyph of a move, with its PGN Numeric Annotation Glyph code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Nag {
+    /// `!`, `$1`
+    Good,
+    /// `?`, `$2`
+    Mistake,
+    /// `??`, `$4`
+    Blunder,
+    /// `!?`, `$5`
+    Interesting,
+}
+
+impl Nag {
+    pub fn glyph(self) -> &'static str {
+        match self {
+            Nag::Good => "!",
+            Nag::Mistake => "?",
+            Nag::Blunder => "??",
+            Nag::Interesting => "!?",
+        }
+    }
+
+    /// Number written after `$` in PGN movetext
+    pub fn code(self) -> u8 {
+        match self {
+            Nag::Good => 1,
+            Nag::Mistake => 2,
+            Nag::Blunder => 4,
+            Nag::Interesting => 5,
+        }
+    }
+
+    /// Glyph for a move from the mover's scores before and after it, or `None` for an
+    /// ordinary move
+    pub fn for_move(before: Score, after: Score, thresholds: &NagThresholds) -> Option<Nag> {
+        let swing = after.expected_points() - before.expected_points();
+        let scaled =
+            |centipawns: i32| Score::new(centipawns, before.material).expected_points() - 0.5;
+
+        if -swing >= scaled(thresholds.blunder) {
+            Some(Nag::Blunder)
+        } else if -swing >= scaled(thresholds.mistake) {
+            Some(Nag::Mistake)
+        } else if swing >= scaled(thresholds.good) {
+            Some(Nag::Good)
+        } else if swing >= scaled(thresholds.interesting) {
+            Some(Nag::Interesting)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Nag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.glyph())
+    }
+}
+
+/// Centipawn swings, as measured in a level position, that earn each glyph. Losses mark
+/// mistakes and blunders; gains mark moves that found more than the previous position's
+/// evaluation promised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NagThresholds {
+    pub blunder: i32,
+    pub mistake: i32,
+    pub good: i32,
+    pub interesting: i32,
+}
+
+impl Default for NagThresholds {
+    fn default() -> Self {
+        NagThresholds {
+            blunder: 150,
+            mistake: 80,
+            good: 120,
+            interesting: 60,
+        }
+    }
+}
+
+/// Glyph of each move of a game from the evaluations of consecutive positions, each
+/// from the point of view of the side to move there. `evaluations[i]` is the position
+/// before move `i`, so a game of `n` moves needs `n + 1` evaluations and `n` glyphs come
+/// back.
+pub fn annotate_moves(evaluations: &[Score], thresholds: &NagThresholds) -> Vec<Option<Nag>> {
+    evaluations
+        .windows(2)
+        .map(|pair| Nag::for_move(pair[0], pair[1].flipped(), thresholds))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_losses_and_gains_get_glyphs() {
+        let thresholds = NagThresholds::default();
+        let level = Score::new(0, 58);
+        let glyph = |after: i32| Nag::for_move(level, Score::new(after, 58), &thresholds);
+
+        assert_eq!(glyph(-20), None);
+        assert_eq!(glyph(-100), Some(Nag::Mistake));
+        assert_eq!(glyph(-450), Some(Nag::Blunder));
+        assert_eq!(glyph(100), Some(Nag::Interesting));
+        assert_eq!(glyph(250), Some(Nag::Good));
+    }
+
+    #[test]
+    fn test_won_positions_tolerate_larger_drops() {
+        let thresholds = NagThresholds::default();
+        let winning = Score::new(900, 58);
+        assert_eq!(
+            Nag::for_move(winning, Score::new(700, 58), &thresholds),
+            None
+        );
+        assert_eq!(
+            Nag::for_move(winning, Score::new(-100, 58), &thresholds),
+            Some(Nag::Blunder)
+        );
+    }
+
+    #[test]
+    fn test_annotate_moves_alternates_sides() {
+        // White plays a quiet move, Black blunders, White finds the refutation
+        let evaluations = [
+            Score::new(30, 58),
+            Score::new(-25, 58),
+            Score::new(400, 58),
+            Score::new(-420, 58),
+        ];
+        let nags = annotate_moves(&evaluations, &NagThresholds::default());
+        assert_eq!(nags, vec![None, Some(Nag::Blunder), None]);
+        assert_eq!(Nag::Blunder.to_string(), "??");
+        assert_eq!(Nag::Interesting.code(), 5);
+    }
+}
@@ -0,0 +1,176 @@
+// Evaluation parameters shared by every evaluator and by move ordering
+//
+// Values are middlegame/endgame pairs so evaluators can pick or blend them by game
+// phase. Tables are indexed by White's square index; Black squares are mirrored
+// vertically.
+
+use crate::{Color, PieceType, Square};
+
+/// A middlegame and an endgame value for the same term
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PhaseScore {
+    pub middlegame: i32,
+    pub endgame: i32,
+}
+
+impl PhaseScore {
+    pub const fn new(middlegame: i32, endgame: i32) -> Self {
+        PhaseScore {
+            middlegame,
+            endgame,
+        }
+    }
+
+    /// The same value in both phases
+    pub const fn flat(value: i32) -> Self {
+        Self::new(value, value)
+    }
+
+    pub const fn select(self, endgame: bool) -> i32 {
+        if endgame {
+            self.endgame
+        } else {
+            self.middlegame
+        }
+    }
+}
+
+/// Piece values indexed by `PieceType::index`
+pub const PIECE_VALUES: [PhaseScore; 6] = [
+    PhaseScore::flat(100),
+    PhaseScore::flat(320),
+    PhaseScore::flat(330),
+    PhaseScore::flat(500),
+    PhaseScore::flat(900),
+    PhaseScore::flat(20000),
+];
+
+const PAWN_TABLE: [i32; 64] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 50, 50, 50, 50, 50, 50, 50, 50, 10, 10, 20, 30, 30, 20, 10, 10, 5, 5,
+    10, 25, 25, 10, 5, 5, 0, 0, 0, 20, 20, 0, 0, 0, 5, -5, -10, 0, 0, -10, -5, 5, 5, 10, 10, -20,
+    -20, 10, 10, 5, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+const KNIGHT_TABLE: [i32; 64] = [
+    -50, -40, -30, -30, -30, -30, -40, -50, -40, -20, 0, 0, 0, 0, -20, -40, -30, 0, 10, 15, 15, 10,
+    0, -30, -30, 5, 15, 20, 20, 15, 5, -30, -30, 0, 15, 20, 20, 15, 0, -30, -30, 5, 10, 15, 15, 10,
+    5, -30, -40, -20, 0, 5, 5, 0, -20, -40, -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+const BISHOP_TABLE: [i32; 64] = [
+    -20, -10, -10, -10, -10, -10, -10, -20, -10, 0, 0, 0, 0, 0, 0, -10, -10, 0, 5, 10, 10, 5, 0,
+    -10, -10, 5, 5, 10, 10, 5, 5, -10, -10, 0, 10, 10, 10, 10, 0, -10, -10, 10, 10, 10, 10, 10, 10,
+    -10, -10, 5, 0, 0, 0, 0, 5, -10, -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+const ROOK_TABLE: [i32; 64] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 5, 10, 10, 10, 10, 10, 10, 5, -5, 0, 0, 0, 0, 0, 0, -5, -5, 0, 0, 0, 0,
+    0, 0, -5, -5, 0, 0, 0, 0, 0, 0, -5, -5, 0, 0, 0, 0, 0, 0, -5, -5, 0, 0, 0, 0, 0, 0, -5, 0, 0,
+    0, 5, 5, 0, 0, 0,
+];
+
+const QUEEN_TABLE: [i32; 64] = [
+    -20, -10, -10, -5, -5, -10, -10, -20, -10, 0, 0, 0, 0, 0, 0, -10, -10, 0, 5, 5, 5, 5, 0, -10,
+    -5, 0, 5, 5, 5, 5, 0, -5, 0, 0, 5, 5, 5, 5, 0, -5, -10, 5, 5, 5, 5, 5, 0, -10, -10, 0, 5, 0, 0,
+    0, 0, -10, -20, -10, -10, -5, -5, -10, -10, -20,
+];
+
+const KING_MIDDLEGAME_TABLE: [i32; 64] = [
+    -30, -40, -40, -50, -50, -40, -40, -30, -30, -40, -40, -50, -50, -40, -40, -30, -30, -40, -40,
+    -50, -50, -40, -40, -30, -30, -40, -40, -50, -50, -40, -40, -30, -20, -30, -30, -40, -40, -30,
+    -30, -20, -10, -20, -20, -20, -20, -20, -20, -10, 20, 20, 0, 0, 0, 0, 20, 20, 20, 30, 10, 0, 0,
+    10, 30, 20,
+];
+
+const KING_ENDGAME_TABLE: [i32; 64] = [
+    -50, -40, -30, -20, -20, -30, -40, -50, -30, -20, -10, 0, 0, -10, -20, -30, -30, -10, 20, 30,
+    30, 20, -10, -30, -30, -10, 30, 40, 40, 30, -10, -30, -30, -10, 30, 40, 40, 30, -10, -30, -30,
+    -10, 20, 30, 30, 20, -10, -30, -30, -30, 0, 0, 0, 0, -30, -30, -50, -30, -30, -30, -30, -30,
+    -30, -50,
+];
+
+const fn pair_tables(middlegame: [i32; 64], endgame: [i32; 64]) -> [PhaseScore; 64] {
+    let mut table = [PhaseScore::flat(0); 64];
+    let mut i = 0;
+    while i < 64 {
+        table[i] = PhaseScore::new(middlegame[i], endgame[i]);
+        i += 1;
+    }
+    table
+}
+
+/// Piece-square tables indexed by `PieceType::index`, then square
+pub const PIECE_SQUARE_TABLES: [[PhaseScore; 64]; 6] = [
+    pair_tables(PAWN_TABLE, PAWN_TABLE),
+    pair_tables(KNIGHT_TABLE, KNIGHT_TABLE),
+    pair_tables(BISHOP_TABLE, BISHOP_TABLE),
+    pair_tables(ROOK_TABLE, ROOK_TABLE),
+    pair_tables(QUEEN_TABLE, QUEEN_TABLE),
+    pair_tables(KING_MIDDLEGAME_TABLE, KING_ENDGAME_TABLE),
+];
+
+/// The full set of tunable material and piece-square values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalParams {
+    pub piece_values: [PhaseScore; 6],
+    pub piece_square_tables: [[PhaseScore; 64]; 6],
+}
+
+/// The values every evaluator and the search use unless given others
+pub const DEFAULT_PARAMS: EvalParams = EvalParams {
+    piece_values: PIECE_VALUES,
+    piece_square_tables: PIECE_SQUARE_TABLES,
+};
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        DEFAULT_PARAMS
+    }
+}
+
+impl EvalParams {
+    pub const fn piece_value(&self, piece_type: PieceType) -> PhaseScore {
+        self.piece_values[piece_type.index()]
+    }
+
+    /// Piece-square value for a piece of `color` on `square`, from that color's view
+    pub const fn piece_square(
+        &self,
+        piece_type: PieceType,
+        color: Color,
+        square: Square,
+    ) -> PhaseScore {
+        let index = match color {
+            Color::White => square.index() as usize,
+            Color::Black => (square.index() ^ 56) as usize,
+        };
+        self.piece_square_tables[piece_type.index()][index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_piece_values() {
+        assert_eq!(DEFAULT_PARAMS.piece_value(PieceType::Pawn).middlegame, 100);
+        assert_eq!(DEFAULT_PARAMS.piece_value(PieceType::Queen).endgame, 900);
+        assert_eq!(PieceType::King.value(), 20000);
+    }
+
+    #[test]
+    fn test_piece_square_mirrors_for_black() {
+        for piece_type in PieceType::ALL {
+            for square in Square::ALL {
+                let mirrored = square.mirror_vertical();
+                assert_eq!(
+                    DEFAULT_PARAMS.piece_square(piece_type, Color::White, square),
+                    DEFAULT_PARAMS.piece_square(piece_type, Color::Black, mirrored)
+                );
+            }
+        }
+        let king = DEFAULT_PARAMS.piece_square(PieceType::King, Color::White, Square::E4);
+        assert_ne!(king.select(false), king.select(true));
+    }
+}
@@ -0,0 +1,336 @@
+// Pawn structure detection shared by both evaluators
+//
+// Spans are precomputed per color and square: the front span is the file ahead of a
+// pawn, the attack span the adjacent files ahead of it. A pawn is passed when no enemy
+// pawn stands in either.
+
+use super::params::PhaseScore;
+use crate::{Bitboard, Color, PieceType, Position, Square};
+
+pub const DOUBLED_PAWN_PENALTY: i32 = 10;
+pub const ISOLATED_PAWN_PENALTY: i32 = 15;
+pub const BACKWARD_PAWN_PENALTY: i32 = 10;
+
+/// Passed pawn bonus by rank relative to the pawn's own side
+pub const PASSED_PAWN_BONUS: [PhaseScore; 8] = [
+    PhaseScore::flat(0),
+    PhaseScore::new(5, 10),
+    PhaseScore::new(10, 15),
+    PhaseScore::new(15, 25),
+    PhaseScore::new(25, 45),
+    PhaseScore::new(45, 75),
+    PhaseScore::new(70, 110),
+    PhaseScore::flat(0),
+];
+
+/// Candidate passed pawn bonus by relative rank
+pub const CANDIDATE_PAWN_BONUS: [i32; 8] = [0, 3, 5, 8, 12, 20, 0, 0];
+
+/// Endgame bonus per square of enemy king distance from a passed pawn's stop square,
+/// scaled by how far the pawn has advanced
+pub const PASSED_PAWN_ENEMY_KING_DISTANCE: i32 = 4;
+/// Endgame penalty per square of own king distance from the stop square
+pub const PASSED_PAWN_OWN_KING_DISTANCE: i32 = 2;
+
+const fn color_index(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+/// All squares on ranks strictly ahead of `rank` from `color`'s side
+const fn ranks_ahead(color: Color, rank: u8) -> u64 {
+    match color {
+        Color::White => {
+            if rank >= 7 {
+                0
+            } else {
+                u64::MAX << ((rank as u32 + 1) * 8)
+            }
+        }
+        Color::Black => {
+            if rank == 0 {
+                0
+            } else {
+                u64::MAX >> ((8 - rank as u32) * 8)
+            }
+        }
+    }
+}
+
+const fn build_spans(adjacent: bool) -> [[Bitboard; 64]; 2] {
+    let mut spans = [[Bitboard::EMPTY; 64]; 2];
+    let mut side = 0;
+    while side < 2 {
+        let color = if side == 0 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        let mut index = 0;
+        while index < 64 {
+            let file = (index % 8) as u8;
+            let rank = (index / 8) as u8;
+            let files = if adjacent {
+                Bitboard::adjacent_files(file).value()
+            } else {
+                Bitboard::FILES[file as usize].value()
+            };
+            spans[side][index] = Bitboard::new(files & ranks_ahead(color, rank));
+            index += 1;
+        }
+        side += 1;
+    }
+    spans
+}
+
+const FRONT_SPANS: [[Bitboard; 64]; 2] = build_spans(false);
+const ATTACK_SPANS: [[Bitboard; 64]; 2] = build_spans(true);
+
+/// Squares ahead of `square` on its own file, as seen by a `color` pawn
+pub const fn front_span(color: Color, square: Square) -> Bitboard {
+    FRONT_SPANS[color_index(color)][square.index() as usize]
+}
+
+/// Squares ahead of `square` on the adjacent files: every square a `color` pawn there
+/// could ever attack
+pub const fn attack_span(color: Color, square: Square) -> Bitboard {
+    ATTACK_SPANS[color_index(color)][square.index() as usize]
+}
+
+/// Enemy pawns anywhere in this mask stop a `color` pawn on `square` from being passed
+pub const fn passed_pawn_mask(color: Color, square: Square) -> Bitboard {
+    Bitboard::new(front_span(color, square).value() | attack_span(color, square).value())
+}
+
+/// Rank counted from `color`'s own back rank
+pub const fn relative_rank(color: Color, square: Square) -> u8 {
+    match color {
+        Color::White => square.rank(),
+        Color::Black => 7 - square.rank(),
+    }
+}
+
+/// Squares attacked by the given pawns
+pub const fn pawn_attacks(pawns: Bitboard, color: Color) -> Bitboard {
+    match color {
+        Color::White => {
+            Bitboard::new(pawns.shift_northeast().value() | pawns.shift_northwest().value())
+        }
+        Color::Black => {
+            Bitboard::new(pawns.shift_southeast().value() | pawns.shift_southwest().value())
+        }
+    }
+}
+
+/// Classification of one side's pawns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PawnStructure {
+    pub passed: Bitboard,
+    /// Not isolated, no friendly pawn level or behind on an adjacent file, and the stop
+    /// square is guarded by an enemy pawn
+    pub backward: Bitboard,
+    /// Not passed, nothing ahead on its file, and at least as many friendly helpers as
+    /// enemy pawns guarding its path
+    pub candidates: Bitboard,
+    pub isolated: Bitboard,
+    /// Extra pawns on files holding more than one
+    pub doubled: u32,
+}
+
+impl PawnStructure {
+    pub fn analyze(own_pawns: Bitboard, enemy_pawns: Bitboard, color: Color) -> Self {
+        let mut structure = PawnStructure {
+            passed: Bitboard::EMPTY,
+            backward: Bitboard::EMPTY,
+            candidates: Bitboard::EMPTY,
+            isolated: Bitboard::EMPTY,
+            doubled: 0,
+        };
+        let enemy_attacks = pawn_attacks(enemy_pawns, color.opposite());
+        let all_pawns = own_pawns | enemy_pawns;
+
+        for file in 0..8 {
+            let on_file = (own_pawns & Bitboard::FILES[file]).count_bits();
+            structure.doubled += on_file.saturating_sub(1);
+        }
+
+        for index in own_pawns.iter() {
+            let square = Square::from(index);
+            let bit = square.bitboard();
+            let adjacent = Bitboard::adjacent_files(square.file());
+
+            if (own_pawns & adjacent).is_empty() {
+                structure.isolated |= bit;
+            }
+            if (enemy_pawns & passed_pawn_mask(color, square)).is_empty() {
+                structure.passed |= bit;
+                continue;
+            }
+
+            // Friendly pawns level with or behind this one on the adjacent files
+            let level_or_behind = adjacent & !attack_span(color, square);
+            let helpers = own_pawns & level_or_behind;
+            let sentries = enemy_pawns & attack_span(color, square);
+
+            let stop = match color {
+                Color::White => bit.shift_north(),
+                Color::Black => bit.shift_south(),
+            };
+            if helpers.is_empty()
+                && (own_pawns & adjacent).is_not_empty()
+                && (stop & enemy_attacks).is_not_empty()
+            {
+                structure.backward |= bit;
+            }
+
+            if (front_span(color, square) & all_pawns).is_empty()
+                && helpers.count_bits() >= sentries.count_bits()
+            {
+                structure.candidates |= bit;
+            }
+        }
+
+        structure
+    }
+
+    /// Score of the terms that depend on pawns alone, so it can be cached by pawn hash
+    pub fn pawn_only_score(&self, color: Color) -> i32 {
+        let mut score = -(self.doubled as i32) * DOUBLED_PAWN_PENALTY
+            - self.isolated.count_bits() as i32 * ISOLATED_PAWN_PENALTY
+            - self.backward.count_bits() as i32 * BACKWARD_PAWN_PENALTY;
+        for index in self.candidates.iter() {
+            score += CANDIDATE_PAWN_BONUS[relative_rank(color, Square::from(index)) as usize];
+        }
+        score
+    }
+}
+
+/// Passed pawn bonuses for `color`: rank-scaled, halved when an enemy piece blockades
+/// the stop square, and in endgames adjusted for how close each king is to that square
+pub fn passed_pawn_score(
+    position: &Position,
+    color: Color,
+    passed: Bitboard,
+    endgame: bool,
+) -> i32 {
+    let enemy_pieces = position.pieces_of_color(color.opposite());
+    let own_king = position.piece_bitboard(PieceType::King, color).lsb();
+    let enemy_king = position
+        .piece_bitboard(PieceType::King, color.opposite())
+        .lsb();
+    let mut score = 0;
+
+    for index in passed.iter() {
+        let square = Square::from(index);
+        let rank = relative_rank(color, square);
+        let mut bonus = PASSED_PAWN_BONUS[rank as usize].select(endgame);
+
+        let stop = match color {
+            Color::White => square.north(),
+            Color::Black => square.south(),
+        };
+        let Some(stop) = stop else {
+            score += bonus;
+            continue;
+        };
+
+        if enemy_pieces.contains(stop) {
+            bonus /= 2;
+        }
+
+        if endgame {
+            let weight = rank.saturating_sub(2) as i32;
+            if let Some(king) = enemy_king {
+                bonus += Square::from(king).distance(stop) as i32
+                    * PASSED_PAWN_ENEMY_KING_DISTANCE
+                    * weight;
+            }
+            if let Some(king) = own_king {
+                bonus -= Square::from(king).distance(stop) as i32
+                    * PASSED_PAWN_OWN_KING_DISTANCE
+                    * weight;
+            }
+        }
+
+        score += bonus;
+    }
+
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn structure(fen: &str, color: Color) -> PawnStructure {
+        let position = Position::from_fen(fen).unwrap();
+        PawnStructure::analyze(
+            position.piece_bitboard(PieceType::Pawn, color),
+            position.piece_bitboard(PieceType::Pawn, color.opposite()),
+            color,
+        )
+    }
+
+    #[test]
+    fn test_spans() {
+        assert_eq!(
+            front_span(Color::White, Square::E4),
+            Square::E5.bitboard()
+                | Square::E6.bitboard()
+                | Square::E7.bitboard()
+                | Square::E8.bitboard()
+        );
+        assert_eq!(
+            attack_span(Color::Black, Square::A3),
+            Square::B2.bitboard() | Square::B1.bitboard()
+        );
+        assert!(front_span(Color::White, Square::H8).is_empty());
+        assert!(passed_pawn_mask(Color::Black, Square::D1).is_empty());
+    }
+
+    #[test]
+    fn test_passed_pawns_respect_color() {
+        // White's d5 pawn is stopped by c7, and Black's c7 pawn by d5
+        let fen = "4k3/2p5/8/3P4/8/8/8/4K3 w - - 0 1";
+        assert!(structure(fen, Color::White).passed.is_empty());
+        assert!(structure(fen, Color::Black).passed.is_empty());
+
+        // Each pawn has already passed the other, so both are free
+        let fen = "4k3/8/8/3P4/8/2p5/8/4K3 w - - 0 1";
+        assert_eq!(structure(fen, Color::White).passed, Square::D5.bitboard());
+        assert_eq!(structure(fen, Color::Black).passed, Square::C3.bitboard());
+    }
+
+    #[test]
+    fn test_backward_and_candidate_pawns() {
+        // d3 is behind its c4 neighbour and e5 guards d4
+        let backward = structure("4k3/8/8/4p3/2P5/3P4/8/4K3 w - - 0 1", Color::White);
+        assert_eq!(backward.backward, Square::D3.bitboard());
+        assert!(!backward.candidates.contains(Square::D3));
+
+        // c4 with b3 support against a single b-pawn sentry is a candidate
+        let candidate = structure("4k3/8/1p6/8/2P5/1P6/8/4K3 w - - 0 1", Color::White);
+        assert_eq!(candidate.candidates, Square::C4.bitboard());
+        assert!(candidate.passed.is_empty());
+    }
+
+    #[test]
+    fn test_passed_pawn_blockade_and_king_proximity() {
+        let free = Position::from_fen("4k3/8/8/3P4/8/8/8/4K3 w - - 0 1").unwrap();
+        let blockaded = Position::from_fen("4k3/8/3n4/3P4/8/8/8/4K3 w - - 0 1").unwrap();
+        let passed = Square::D5.bitboard();
+        assert!(
+            passed_pawn_score(&free, Color::White, passed, false)
+                > passed_pawn_score(&blockaded, Color::White, passed, false)
+        );
+
+        let king_far = Position::from_fen("7k/8/8/3P4/8/8/8/3K4 w - - 0 1").unwrap();
+        let king_near = Position::from_fen("3k4/8/8/3P4/8/8/8/3K4 w - - 0 1").unwrap();
+        assert!(
+            passed_pawn_score(&king_far, Color::White, passed, true)
+                > passed_pawn_score(&king_near, Color::White, passed, true)
+        );
+    }
+}
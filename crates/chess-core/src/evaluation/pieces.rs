@@ -0,0 +1,267 @@
+// Piece placement terms beyond the piece-square tables, shared by both evaluators
+//
+// File states come from the pawn structure: a file is semi-open for a side when that
+// side has no pawn on it, and open when neither side has. Pieces held in hand in drop
+// variants are scored here too.
+
+use super::params::EvalParams;
+use super::pawns::{attack_span, pawn_attacks, relative_rank};
+use crate::{Bitboard, Color, PieceType, Position, Square};
+
+pub const ROOK_OPEN_FILE_BONUS: i32 = 25;
+pub const ROOK_SEMI_OPEN_FILE_BONUS: i32 = 12;
+/// Rook on the seventh rank while the enemy king or pawns are still on their home ranks
+pub const ROOK_SEVENTH_RANK_BONUS: i32 = 20;
+/// Per file holding two or more friendly rooks
+pub const DOUBLED_ROOKS_BONUS: i32 = 15;
+/// Rook boxed into the corner by its own king, which walked over without castling
+pub const TRAPPED_ROOK_PENALTY: i32 = 40;
+
+/// Bishops on both square colours
+pub const BISHOP_PAIR_BONUS: i32 = 30;
+/// Per own pawn on the bishop's square colour
+pub const BAD_BISHOP_PENALTY: i32 = 3;
+/// Knight on the 4th to 6th rank, defended by a pawn and out of reach of enemy pawns
+pub const KNIGHT_OUTPOST_BONUS: i32 = 20;
+/// Minor piece standing in front of its own unmoved pawn
+pub const MINOR_BLOCKING_PAWN_PENALTY: i32 = 10;
+/// Per piece in hand, which can be dropped onto any empty square
+pub const IN_HAND_BONUS: i32 = 20;
+
+/// Value of the pieces `color` holds in hand; always zero without drops
+pub fn hand_material(position: &Position, color: Color, params: &EvalParams) -> i32 {
+    let hand = position.hands[color.index()];
+    hand.piece_types()
+        .map(|piece_type| {
+            hand.count(piece_type) as i32
+                * (params.piece_value(piece_type).middlegame + IN_HAND_BONUS)
+        })
+        .sum()
+}
+
+/// Bitmask of files (bit 0 = a-file) on which `pawns` has no pawn
+pub fn semi_open_files(pawns: Bitboard) -> u8 {
+    let mut files = 0;
+    for file in 0..8 {
+        if (pawns & Bitboard::FILES[file]).is_empty() {
+            files |= 1 << file;
+        }
+    }
+    files
+}
+
+/// Rook activity for `color`, given each side's semi-open file masks
+pub fn rook_score(
+    position: &Position,
+    color: Color,
+    own_semi_open: u8,
+    enemy_semi_open: u8,
+) -> i32 {
+    let rooks = position.piece_bitboard(PieceType::Rook, color);
+    if rooks.is_empty() {
+        return 0;
+    }
+
+    let enemy = color.opposite();
+    let own_king = position
+        .piece_bitboard(PieceType::King, color)
+        .lsb()
+        .map(Square::from);
+    let enemy_king = position
+        .piece_bitboard(PieceType::King, enemy)
+        .lsb()
+        .map(Square::from);
+    let enemy_pawns = position.piece_bitboard(PieceType::Pawn, enemy);
+    let enemy_pawn_home = match enemy {
+        Color::White => Bitboard::RANK_2,
+        Color::Black => Bitboard::RANK_7,
+    };
+
+    let mut score = 0;
+    let mut files_seen = 0u8;
+    let mut doubled_files = 0u8;
+
+    for index in rooks.iter() {
+        let square = Square::from(index);
+        let file = square.file();
+        let file_bit = 1u8 << file;
+
+        if files_seen & file_bit != 0 {
+            doubled_files |= file_bit;
+        }
+        files_seen |= file_bit;
+
+        if own_semi_open & file_bit != 0 {
+            score += if enemy_semi_open & file_bit != 0 {
+                ROOK_OPEN_FILE_BONUS
+            } else {
+                ROOK_SEMI_OPEN_FILE_BONUS
+            };
+        }
+
+        if relative_rank(color, square) == 6
+            && (enemy_king.is_some_and(|king| relative_rank(color, king) == 7)
+                || (enemy_pawns & enemy_pawn_home).is_not_empty())
+        {
+            score += ROOK_SEVENTH_RANK_BONUS;
+        }
+
+        if let Some(king) = own_king {
+            if is_trapped_by_king(color, square, king) {
+                score -= TRAPPED_ROOK_PENALTY;
+            }
+        }
+    }
+
+    score + doubled_files.count_ones() as i32 * DOUBLED_ROOKS_BONUS
+}
+
+/// Bishop pair, bad bishops, knight outposts and minor pieces blocking their own pawns
+pub fn minor_piece_score(position: &Position, color: Color) -> i32 {
+    let bishops = position.piece_bitboard(PieceType::Bishop, color);
+    let knights = position.piece_bitboard(PieceType::Knight, color);
+    let own_pawns = position.piece_bitboard(PieceType::Pawn, color);
+    let enemy_pawns = position.piece_bitboard(PieceType::Pawn, color.opposite());
+    let pawn_defended = pawn_attacks(own_pawns, color);
+    let mut score = 0;
+
+    if (bishops & Bitboard::LIGHT_SQUARES).is_not_empty()
+        && (bishops & Bitboard::DARK_SQUARES).is_not_empty()
+    {
+        score += BISHOP_PAIR_BONUS;
+    }
+
+    for index in bishops.iter() {
+        let square = Square::from(index);
+        let square_colour = if square.is_light() {
+            Bitboard::LIGHT_SQUARES
+        } else {
+            Bitboard::DARK_SQUARES
+        };
+        score -= (own_pawns & square_colour).count_bits() as i32 * BAD_BISHOP_PENALTY;
+    }
+
+    for index in knights.iter() {
+        let square = Square::from(index);
+        if matches!(relative_rank(color, square), 3..=5)
+            && pawn_defended.contains(square)
+            && (enemy_pawns & attack_span(color, square)).is_empty()
+        {
+            score += KNIGHT_OUTPOST_BONUS;
+        }
+    }
+
+    // An unmoved pawn directly behind a minor piece cannot advance
+    let home_pawns = own_pawns
+        & match color {
+            Color::White => Bitboard::RANK_2,
+            Color::Black => Bitboard::RANK_7,
+        };
+    let blocked_squares = match color {
+        Color::White => home_pawns.shift_north(),
+        Color::Black => home_pawns.shift_south(),
+    };
+    score -=
+        ((bishops | knights) & blocked_squares).count_bits() as i32 * MINOR_BLOCKING_PAWN_PENALTY;
+
+    score
+}
+
+/// Rook and king on the back rank with the king between the rook and the centre, as
+/// after Kf1 with the rook still on h1. A castled king stands outside its rook instead.
+fn is_trapped_by_king(color: Color, rook: Square, king: Square) -> bool {
+    if relative_rank(color, rook) != 0 || relative_rank(color, king) != 0 {
+        return false;
+    }
+    let (king_file, rook_file) = (king.file(), rook.file());
+    (matches!(king_file, 5 | 6) && rook_file > king_file)
+        || (matches!(king_file, 1..=3) && rook_file < king_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rooks(fen: &str, color: Color) -> i32 {
+        let position = Position::from_fen(fen).unwrap();
+        let white = semi_open_files(position.piece_bitboard(PieceType::Pawn, Color::White));
+        let black = semi_open_files(position.piece_bitboard(PieceType::Pawn, Color::Black));
+        match color {
+            Color::White => rook_score(&position, color, white, black),
+            Color::Black => rook_score(&position, color, black, white),
+        }
+    }
+
+    #[test]
+    fn test_semi_open_files() {
+        assert_eq!(semi_open_files(Bitboard::EMPTY), 0xFF);
+        assert_eq!(semi_open_files(Bitboard::RANK_2), 0);
+        assert_eq!(semi_open_files(Square::E4.bitboard()), !(1 << 4));
+    }
+
+    #[test]
+    fn test_rook_file_states() {
+        let closed = rooks("4k3/4p3/8/8/8/8/4P3/4RK2 w - - 0 1", Color::White);
+        let semi_open = rooks("4k3/4p3/8/8/8/8/8/4RK2 w - - 0 1", Color::White);
+        let open = rooks("4k3/8/8/8/8/8/8/4RK2 w - - 0 1", Color::White);
+        assert_eq!(closed, 0);
+        assert_eq!(semi_open, ROOK_SEMI_OPEN_FILE_BONUS);
+        assert_eq!(open, ROOK_OPEN_FILE_BONUS);
+    }
+
+    #[test]
+    fn test_seventh_rank_and_doubled_rooks() {
+        // Black's rook on a2 faces White's king on the back rank; both files are open
+        let seventh = rooks("4k3/8/8/8/8/8/r7/6K1 b - - 0 1", Color::Black);
+        assert_eq!(seventh, ROOK_OPEN_FILE_BONUS + ROOK_SEVENTH_RANK_BONUS);
+
+        let doubled = rooks("4k3/8/8/8/8/8/3R4/3R2K1 w - - 0 1", Color::White);
+        assert_eq!(doubled, 2 * ROOK_OPEN_FILE_BONUS + DOUBLED_ROOKS_BONUS);
+    }
+
+    fn minors(fen: &str, color: Color) -> i32 {
+        minor_piece_score(&Position::from_fen(fen).unwrap(), color)
+    }
+
+    #[test]
+    fn test_bishop_pair_and_bad_bishop() {
+        let pair = minors("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1", Color::White);
+        let same_colour = minors("4k3/8/8/8/8/8/8/2B1K1B1 w - - 0 1", Color::White);
+        assert_eq!(pair, BISHOP_PAIR_BONUS);
+        assert_eq!(same_colour, 0);
+
+        // c1 is dark; pawns on d4 and e5 are dark, f3 is light
+        let bad = minors("4k3/8/8/4P3/3P4/5P2/8/2B1K3 w - - 0 1", Color::White);
+        assert_eq!(bad, -2 * BAD_BISHOP_PENALTY);
+    }
+
+    #[test]
+    fn test_knight_outpost() {
+        // Nd5 defended by e4, and no black pawn on c or e files can challenge it
+        let outpost = minors("4k3/8/8/3N4/4P3/8/8/4K3 w - - 0 1", Color::White);
+        assert_eq!(outpost, KNIGHT_OUTPOST_BONUS);
+
+        let challenged = minors("4k3/2p5/8/3N4/4P3/8/8/4K3 w - - 0 1", Color::White);
+        assert_eq!(challenged, 0);
+
+        let black_outpost = minors("4k3/8/3p4/4n3/8/8/8/4K3 b - - 0 1", Color::Black);
+        assert_eq!(black_outpost, KNIGHT_OUTPOST_BONUS);
+    }
+
+    #[test]
+    fn test_minor_blocking_home_pawn() {
+        let blocked = minors("4k3/8/8/8/8/3N4/3P4/4K3 w - - 0 1", Color::White);
+        assert_eq!(blocked, -MINOR_BLOCKING_PAWN_PENALTY);
+    }
+
+    #[test]
+    fn test_trapped_rook() {
+        // Kf1 with the rook stuck on h1 behind its own pawns
+        let trapped = rooks("4k3/8/8/8/8/8/6PP/5K1R w - - 0 1", Color::White);
+        assert_eq!(trapped, -TRAPPED_ROOK_PENALTY);
+
+        // After castling the king stands outside the rook
+        let castled = rooks("4k3/8/8/8/8/8/6PP/5RK1 w - - 0 1", Color::White);
+        assert_eq!(castled, ROOK_OPEN_FILE_BONUS);
+    }
+}
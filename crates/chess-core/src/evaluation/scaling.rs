@@ -0,0 +1,111 @@
+// Final evaluation scaling toward a draw
+//
+// Scale factors are out of `SCALE_NORMAL`. Known drawish endings (see `endgame`) and
+// drawish material lower the factor for the whole position, and the halfmove clock shrinks any remaining advantage as the fifty
+// move rule approaches, so the search prefers lines that make progress.
+
+use super::endgame;
+use crate::{Bitboard, Color, PieceType, Position};
+
+pub const SCALE_NORMAL: i32 = 128;
+/// Bishops of opposite colours and nothing else but pawns
+pub const SCALE_OPPOSITE_BISHOPS: i32 = 64;
+/// Bishops of opposite colours with other pieces still on the board
+pub const SCALE_OPPOSITE_BISHOPS_WITH_PIECES: i32 = 104;
+/// One rook each and the pawn counts differing by at most one
+pub const SCALE_ROOK_ENDGAME: i32 = 96;
+/// Halfmove clock below which the fifty move rule does not affect the score
+pub const FIFTY_MOVE_SCALE_START: u32 = 20;
+
+/// Scale factor for the material on the board
+pub fn material_scale(position: &Position) -> i32 {
+    if let Some(scale) = endgame::scale_factor(position) {
+        return scale;
+    }
+
+    let key = position.material_key();
+    let bishop = |color| position.piece_bitboard(PieceType::Bishop, color);
+
+    // Bishops and pawns alone are a known ending; this covers the other pieces too
+    if key.count(PieceType::Bishop, Color::White) == 1
+        && key.count(PieceType::Bishop, Color::Black) == 1
+        && opposite_colours(bishop(Color::White), bishop(Color::Black))
+    {
+        return SCALE_OPPOSITE_BISHOPS_WITH_PIECES;
+    }
+
+    let only_rooks =
+        |color| key.count(PieceType::Rook, color) == 1 && key.non_pawn_count(color) == 1;
+    if only_rooks(Color::White) && only_rooks(Color::Black) {
+        let white_pawns = key.count(PieceType::Pawn, Color::White) as i32;
+        let black_pawns = key.count(PieceType::Pawn, Color::Black) as i32;
+        if (white_pawns - black_pawns).abs() <= 1 {
+            return SCALE_ROOK_ENDGAME;
+        }
+    }
+
+    SCALE_NORMAL
+}
+
+fn opposite_colours(a: Bitboard, b: Bitboard) -> bool {
+    let light = |bishops: Bitboard| (bishops & Bitboard::LIGHT_SQUARES).is_not_empty();
+    light(a) != light(b)
+}
+
+/// Scale factor from the halfmove clock, falling linearly to zero at 100 plies
+pub fn fifty_move_scale(halfmove_clock: u32) -> i32 {
+    if halfmove_clock <= FIFTY_MOVE_SCALE_START {
+        return SCALE_NORMAL;
+    }
+    let remaining = 100u32.saturating_sub(halfmove_clock) as i32;
+    SCALE_NORMAL * remaining / (100 - FIFTY_MOVE_SCALE_START) as i32
+}
+
+/// Apply both scale factors to a score
+pub fn scale_score(score: i32, position: &Position) -> i32 {
+    let scale = material_scale(position) * fifty_move_scale(position.halfmove_clock());
+    (score as i64 * scale as i64 / (SCALE_NORMAL * SCALE_NORMAL) as i64) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scale(fen: &str) -> i32 {
+        material_scale(&Position::from_fen(fen).unwrap())
+    }
+
+    #[test]
+    fn test_material_scale() {
+        assert_eq!(scale("4k3/8/8/8/8/8/8/4K3 w - - 0 1"), SCALE_NORMAL);
+        // c1 and c8 bishops stand on different colours
+        assert_eq!(
+            scale("2b1k3/p7/8/8/8/8/PP6/2B1K3 w - - 0 1"),
+            SCALE_OPPOSITE_BISHOPS
+        );
+        assert_eq!(
+            scale("2b1k3/p7/8/8/8/8/PP6/2B1K1R1 w - - 0 1"),
+            SCALE_OPPOSITE_BISHOPS_WITH_PIECES
+        );
+        // Same coloured bishops are not drawish
+        assert_eq!(scale("3bk3/p7/8/8/8/8/PP6/2B1K3 w - - 0 1"), SCALE_NORMAL);
+        assert_eq!(
+            scale("r3k3/p7/8/8/8/8/PP6/R3K3 w - - 0 1"),
+            SCALE_ROOK_ENDGAME
+        );
+        assert_eq!(scale("r3k3/8/8/8/8/8/PP6/R3K3 w - - 0 1"), SCALE_NORMAL);
+    }
+
+    #[test]
+    fn test_fifty_move_scale() {
+        assert_eq!(fifty_move_scale(0), SCALE_NORMAL);
+        assert_eq!(fifty_move_scale(FIFTY_MOVE_SCALE_START), SCALE_NORMAL);
+        assert_eq!(fifty_move_scale(60), SCALE_NORMAL / 2);
+        assert_eq!(fifty_move_scale(100), 0);
+
+        let fresh = Position::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        let stale = Position::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 90 80").unwrap();
+        assert_eq!(scale_score(900, &fresh), 900);
+        assert!(scale_score(900, &stale) < 200);
+    }
+}
@@ -1,34 +1,70 @@
+use super::endgame;
+use super::king::king_danger;
+use super::mobility::mobility;
+use super::params::EvalParams;
+use super::pawns::{passed_pawn_score, PawnStructure};
+use super::pieces::{hand_material, minor_piece_score, rook_score, semi_open_files};
+use super::scaling::{fifty_move_scale, material_scale, scale_score, SCALE_NORMAL};
+use super::threats::{threat_balance, AttackMap};
 use crate::{Bitboard, Color, GameState, PieceType, Square};
+use serde::{Deserialize, Serialize};
+
+/// The terms of `Evaluator::evaluate`, each in centipawns from White's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct EvalBreakdown {
+    /// Pieces on the board and in hand
+    pub material: i32,
+    pub piece_squares: i32,
+    pub mobility: i32,
+    pub king_safety: i32,
+    pub pawn_structure: i32,
+    /// Rooks on open files, outposts and the bishop pair
+    pub piece_activity: i32,
+    pub threats: i32,
+    /// Draw scaling applied to the sum of the terms, out of `SCALE_NORMAL`
+    pub scale: i32,
+    /// Set when a specialized endgame evaluation replaced the terms in `total`
+    pub known_ending: bool,
+    /// The final score, positive when White stands better
+    pub total: i32,
+}
 
-pub struct Evaluator {
-    piece_square_tables: PieceSquareTables,
+impl EvalBreakdown {
+    /// Sum of the terms before scaling
+    pub fn terms_sum(&self) -> i32 {
+        self.material
+            + self.piece_squares
+            + self.mobility
+            + self.king_safety
+            + self.pawn_structure
+            + self.piece_activity
+            + self.threats
+    }
 }
 
-struct PieceSquareTables {
-    pawn: [i32; 64],
-    knight: [i32; 64],
-    bishop: [i32; 64],
-    rook: [i32; 64],
-    queen: [i32; 64],
-    king_middlegame: [i32; 64],
-    king_endgame: [i32; 64],
+pub struct Evaluator {
+    params: EvalParams,
 }
 
 impl Evaluator {
     pub fn new() -> Self {
-        Evaluator {
-            piece_square_tables: PieceSquareTables::new(),
-        }
+        Self::with_params(EvalParams::default())
     }
 
-    pub fn evaluate(&self, game_state: &GameState) -> i32 {
-        let mut score = 0;
+    pub fn with_params(params: EvalParams) -> Self {
+        Evaluator { params }
+    }
 
-        score += self.material_balance(game_state);
-        score += self.positional_score(game_state);
-        score += self.mobility_score(game_state);
-        score += self.king_safety_score(game_state);
-        score += self.pawn_structure_score(game_state);
+    pub fn params(&self) -> &EvalParams {
+        &self.params
+    }
+
+    /// Score in centipawns from the side to move's point of view
+    pub fn evaluate(&self, game_state: &GameState) -> i32 {
+        let score = match endgame::evaluate(&game_state.position) {
+            Some(score) => score,
+            None => self.evaluate_breakdown(game_state).total,
+        };
 
         match game_state.position.side_to_move {
             Color::White => score,
@@ -36,6 +72,32 @@ impl Evaluator {
         }
     }
 
+    /// Every term of the evaluation, White-relative, with the scaled total
+    pub fn evaluate_breakdown(&self, game_state: &GameState) -> EvalBreakdown {
+        let position = &game_state.position;
+        let mut breakdown = EvalBreakdown {
+            material: self.material_balance(game_state),
+            piece_squares: self.positional_score(game_state),
+            mobility: self.mobility_score(game_state),
+            king_safety: self.king_safety_score(game_state),
+            pawn_structure: self.pawn_structure_score(game_state),
+            piece_activity: self.piece_activity_score(game_state),
+            threats: threat_balance(position, &game_state.move_generator),
+            scale: material_scale(position) * fifty_move_scale(position.halfmove_clock())
+                / SCALE_NORMAL,
+            ..EvalBreakdown::default()
+        };
+
+        match endgame::evaluate(position) {
+            Some(score) => {
+                breakdown.known_ending = true;
+                breakdown.total = score;
+            }
+            None => breakdown.total = scale_score(breakdown.terms_sum(), position),
+        }
+        breakdown
+    }
+
     fn material_balance(&self, game_state: &GameState) -> i32 {
         let mut score = 0;
 
@@ -49,10 +111,12 @@ impl Evaluator {
                 .pieces_of_type(piece_type, Color::Black)
                 .count_bits() as i32;
 
-            score += (white_count - black_count) * piece_type.value();
+            score += (white_count - black_count) * self.params.piece_value(piece_type).middlegame;
         }
 
-        score
+        let position = &game_state.position;
+        score + hand_material(position, Color::White, &self.params)
+            - hand_material(position, Color::Black, &self.params)
     }
 
     fn positional_score(&self, game_state: &GameState) -> i32 {
@@ -62,26 +126,10 @@ impl Evaluator {
         for square_idx in 0..64 {
             let square = Square::new(square_idx).unwrap();
             if let Some(piece) = game_state.position.piece_at(square) {
-                // For black pieces, flip the square index (rank-wise) since tables are from White's perspective
-                let table_idx = match piece.color {
-                    Color::White => square_idx as usize,
-                    Color::Black => (square_idx ^ 56) as usize, // Flip rank: XOR with 56 (7*8)
-                };
-
-                let piece_score = match piece.piece_type {
-                    PieceType::Pawn => self.piece_square_tables.pawn[table_idx],
-                    PieceType::Knight => self.piece_square_tables.knight[table_idx],
-                    PieceType::Bishop => self.piece_square_tables.bishop[table_idx],
-                    PieceType::Rook => self.piece_square_tables.rook[table_idx],
-                    PieceType::Queen => self.piece_square_tables.queen[table_idx],
-                    PieceType::King => {
-                        if is_endgame {
-                            self.piece_square_tables.king_endgame[table_idx]
-                        } else {
-                            self.piece_square_tables.king_middlegame[table_idx]
-                        }
-                    }
-                };
+                let piece_score = self
+                    .params
+                    .piece_square(piece.piece_type, piece.color, square)
+                    .select(is_endgame);
 
                 match piece.color {
                     Color::White => score += piece_score,
@@ -94,45 +142,20 @@ impl Evaluator {
     }
 
     fn mobility_score(&self, game_state: &GameState) -> i32 {
-        let white_mobility = self.count_mobility(game_state, Color::White);
-        let black_mobility = self.count_mobility(game_state, Color::Black);
+        let white_mobility = mobility(
+            &game_state.position,
+            Color::White,
+            &game_state.move_generator,
+        );
+        let black_mobility = mobility(
+            &game_state.position,
+            Color::Black,
+            &game_state.move_generator,
+        );
 
         (white_mobility - black_mobility) * 10
     }
 
-    fn count_mobility(&self, game_state: &GameState, color: Color) -> i32 {
-        let mut mobility = 0;
-
-        for square_idx in 0..64 {
-            let square = Square::new(square_idx).unwrap();
-            if let Some(piece) = game_state.position.piece_at(square) {
-                if piece.color == color {
-                    mobility += match piece.piece_type {
-                        PieceType::Knight => game_state
-                            .move_generator
-                            .knight_attacks(square)
-                            .count_bits(),
-                        PieceType::Bishop => game_state
-                            .move_generator
-                            .bishop_attacks(square, game_state.position.all_pieces())
-                            .count_bits(),
-                        PieceType::Rook => game_state
-                            .move_generator
-                            .rook_attacks(square, game_state.position.all_pieces())
-                            .count_bits(),
-                        PieceType::Queen => game_state
-                            .move_generator
-                            .queen_attacks(square, game_state.position.all_pieces())
-                            .count_bits(),
-                        _ => 0,
-                    } as i32;
-                }
-            }
-        }
-
-        mobility
-    }
-
     fn king_safety_score(&self, game_state: &GameState) -> i32 {
         let white_safety = self.evaluate_king_safety(game_state, Color::White);
         let black_safety = self.evaluate_king_safety(game_state, Color::Black);
@@ -145,10 +168,20 @@ impl Evaluator {
             let mut safety = 0;
 
             let king_zone = self.get_king_zone(king_square);
-            let enemy_attacks = self.get_attack_map(game_state, color.opposite());
+            let position = &game_state.position;
+            let own_attacks = AttackMap::compute(position, color, &game_state.move_generator);
+            let enemy_attacks =
+                AttackMap::compute(position, color.opposite(), &game_state.move_generator);
 
-            let attacked_squares = (king_zone & enemy_attacks).count_bits();
+            let attacked_squares = (king_zone & enemy_attacks.all).count_bits();
             safety -= attacked_squares as i32 * 20;
+            safety -= king_danger(
+                position,
+                color,
+                &own_attacks,
+                &enemy_attacks,
+                &game_state.move_generator,
+            );
 
             let pawn_shield = self.evaluate_pawn_shield(game_state, king_square, color);
             safety += pawn_shield;
@@ -160,38 +193,7 @@ impl Evaluator {
     }
 
     fn get_king_zone(&self, king_square: Square) -> Bitboard {
-        let mut zone = king_square.bitboard();
-        zone |= zone.shift_north() | zone.shift_south();
-        zone |= zone.shift_east() | zone.shift_west();
-        zone
-    }
-
-    fn get_attack_map(&self, game_state: &GameState, color: Color) -> Bitboard {
-        let mut attacks = Bitboard::EMPTY;
-
-        for square_idx in 0..64 {
-            let square = Square::new(square_idx).unwrap();
-            if let Some(piece) = game_state.position.piece_at(square) {
-                if piece.color == color {
-                    attacks |= match piece.piece_type {
-                        PieceType::Pawn => game_state.move_generator.pawn_attacks(square, color),
-                        PieceType::Knight => game_state.move_generator.knight_attacks(square),
-                        PieceType::Bishop => game_state
-                            .move_generator
-                            .bishop_attacks(square, game_state.position.all_pieces()),
-                        PieceType::Rook => game_state
-                            .move_generator
-                            .rook_attacks(square, game_state.position.all_pieces()),
-                        PieceType::Queen => game_state
-                            .move_generator
-                            .queen_attacks(square, game_state.position.all_pieces()),
-                        PieceType::King => game_state.move_generator.king_attacks(square),
-                    };
-                }
-            }
-        }
-
-        attacks
+        Bitboard::king_zone(king_square)
     }
 
     fn evaluate_pawn_shield(
@@ -227,6 +229,17 @@ impl Evaluator {
         shield_score
     }
 
+    fn piece_activity_score(&self, game_state: &GameState) -> i32 {
+        let position = &game_state.position;
+        let white_files = semi_open_files(position.pieces_of_type(PieceType::Pawn, Color::White));
+        let black_files = semi_open_files(position.pieces_of_type(PieceType::Pawn, Color::Black));
+
+        rook_score(position, Color::White, white_files, black_files)
+            - rook_score(position, Color::Black, black_files, white_files)
+            + minor_piece_score(position, Color::White)
+            - minor_piece_score(position, Color::Black)
+    }
+
     fn pawn_structure_score(&self, game_state: &GameState) -> i32 {
         let white_score = self.evaluate_pawn_structure(game_state, Color::White);
         let black_score = self.evaluate_pawn_structure(game_state, Color::Black);
@@ -235,140 +248,20 @@ impl Evaluator {
     }
 
     fn evaluate_pawn_structure(&self, game_state: &GameState, color: Color) -> i32 {
-        let mut score = 0;
-        let pawns = game_state.position.pieces_of_type(PieceType::Pawn, color);
-
-        score += self.count_doubled_pawns(pawns) * -10;
-        score += self.count_isolated_pawns(pawns) * -15;
-        score += self.count_passed_pawns(game_state, color) * 20;
-
-        score
-    }
-
-    fn count_doubled_pawns(&self, pawns: Bitboard) -> i32 {
-        let mut doubled = 0;
-        for file in 0..8 {
-            let file_mask = match file {
-                0 => Bitboard::FILE_A,
-                1 => Bitboard::FILE_B,
-                2 => Bitboard::FILE_C,
-                3 => Bitboard::FILE_D,
-                4 => Bitboard::FILE_E,
-                5 => Bitboard::FILE_F,
-                6 => Bitboard::FILE_G,
-                7 => Bitboard::FILE_H,
-                _ => Bitboard::EMPTY,
-            };
-
-            let pawns_on_file = (pawns & file_mask).count_bits();
-            if pawns_on_file > 1 {
-                doubled += pawns_on_file as i32 - 1;
-            }
-        }
-        doubled
-    }
-
-    fn count_isolated_pawns(&self, pawns: Bitboard) -> i32 {
-        let mut isolated = 0;
-
-        for file in 0..8 {
-            let file_mask = match file {
-                0 => Bitboard::FILE_A,
-                1 => Bitboard::FILE_B,
-                2 => Bitboard::FILE_C,
-                3 => Bitboard::FILE_D,
-                4 => Bitboard::FILE_E,
-                5 => Bitboard::FILE_F,
-                6 => Bitboard::FILE_G,
-                7 => Bitboard::FILE_H,
-                _ => Bitboard::EMPTY,
-            };
-
-            if (pawns & file_mask).is_not_empty() {
-                let adjacent_files = if file > 0 && file < 7 {
-                    let left_file = match file - 1 {
-                        0 => Bitboard::FILE_A,
-                        1 => Bitboard::FILE_B,
-                        2 => Bitboard::FILE_C,
-                        3 => Bitboard::FILE_D,
-                        4 => Bitboard::FILE_E,
-                        5 => Bitboard::FILE_F,
-                        6 => Bitboard::FILE_G,
-                        _ => Bitboard::EMPTY,
-                    };
-                    let right_file = match file + 1 {
-                        1 => Bitboard::FILE_B,
-                        2 => Bitboard::FILE_C,
-                        3 => Bitboard::FILE_D,
-                        4 => Bitboard::FILE_E,
-                        5 => Bitboard::FILE_F,
-                        6 => Bitboard::FILE_G,
-                        7 => Bitboard::FILE_H,
-                        _ => Bitboard::EMPTY,
-                    };
-                    left_file | right_file
-                } else if file == 0 {
-                    Bitboard::FILE_B
-                } else {
-                    Bitboard::FILE_G
-                };
-
-                if (pawns & adjacent_files).is_empty() {
-                    isolated += (pawns & file_mask).count_bits() as i32;
-                }
-            }
-        }
-
-        isolated
-    }
-
-    fn count_passed_pawns(&self, game_state: &GameState, color: Color) -> i32 {
-        let our_pawns = game_state.position.pieces_of_type(PieceType::Pawn, color);
-        let enemy_pawns = game_state
-            .position
-            .pieces_of_type(PieceType::Pawn, color.opposite());
-        let mut passed = 0;
-
-        for square_idx in our_pawns.iter() {
-            let square = Square::from(square_idx);
-            if self.is_passed_pawn(square, color, enemy_pawns) {
-                passed += 1;
-            }
-        }
-
-        passed
-    }
-
-    fn is_passed_pawn(&self, pawn_square: Square, color: Color, enemy_pawns: Bitboard) -> bool {
-        let file = pawn_square.file();
-        let rank = pawn_square.rank();
-
-        let blocking_mask = match color {
-            Color::White => {
-                let mut mask = Bitboard::EMPTY;
-                for r in (rank + 1)..8 {
-                    for f in file.saturating_sub(1)..=(file + 1).min(7) {
-                        if let Some(sq) = Square::from_file_rank(f, r) {
-                            mask |= sq.bitboard();
-                        }
-                    }
-                }
-                mask
-            }
-            Color::Black => {
-                let mut mask = Bitboard::EMPTY;
-                for r in 0..rank {
-                    for f in file.saturating_sub(1)..=(file + 1).min(7) {
-                        if let Some(sq) = Square::from_file_rank(f, r) {
-                            mask |= sq.bitboard();
-                        }
-                    }
-                }
-                mask
-            }
-        };
-
-        (enemy_pawns & blocking_mask).is_empty()
+        let position = &game_state.position;
+        let structure = PawnStructure::analyze(
+            position.pieces_of_type(PieceType::Pawn, color),
+            position.pieces_of_type(PieceType::Pawn, color.opposite()),
+            color,
+        );
+
+        structure.pawn_only_score(color)
+            + passed_pawn_score(
+                position,
+                color,
+                structure.passed,
+                self.is_endgame(game_state),
+            )
     }
 
     fn is_endgame(&self, game_state: &GameState) -> bool {
@@ -391,59 +284,13 @@ impl Evaluator {
                 .position
                 .pieces_of_type(piece_type, color)
                 .count_bits() as i32;
-            material += count * piece_type.value();
+            material += count * self.params.piece_value(piece_type).middlegame;
         }
 
         material
     }
 }
 
-impl PieceSquareTables {
-    fn new() -> Self {
-        PieceSquareTables {
-            pawn: [
-                0, 0, 0, 0, 0, 0, 0, 0, 50, 50, 50, 50, 50, 50, 50, 50, 10, 10, 20, 30, 30, 20, 10,
-                10, 5, 5, 10, 25, 25, 10, 5, 5, 0, 0, 0, 20, 20, 0, 0, 0, 5, -5, -10, 0, 0, -10,
-                -5, 5, 5, 10, 10, -20, -20, 10, 10, 5, 0, 0, 0, 0, 0, 0, 0, 0,
-            ],
-            knight: [
-                -50, -40, -30, -30, -30, -30, -40, -50, -40, -20, 0, 0, 0, 0, -20, -40, -30, 0, 10,
-                15, 15, 10, 0, -30, -30, 5, 15, 20, 20, 15, 5, -30, -30, 0, 15, 20, 20, 15, 0, -30,
-                -30, 5, 10, 15, 15, 10, 5, -30, -40, -20, 0, 5, 5, 0, -20, -40, -50, -40, -30, -30,
-                -30, -30, -40, -50,
-            ],
-            bishop: [
-                -20, -10, -10, -10, -10, -10, -10, -20, -10, 0, 0, 0, 0, 0, 0, -10, -10, 0, 5, 10,
-                10, 5, 0, -10, -10, 5, 5, 10, 10, 5, 5, -10, -10, 0, 10, 10, 10, 10, 0, -10, -10,
-                10, 10, 10, 10, 10, 10, -10, -10, 5, 0, 0, 0, 0, 5, -10, -20, -10, -10, -10, -10,
-                -10, -10, -20,
-            ],
-            rook: [
-                0, 0, 0, 0, 0, 0, 0, 0, 5, 10, 10, 10, 10, 10, 10, 5, -5, 0, 0, 0, 0, 0, 0, -5, -5,
-                0, 0, 0, 0, 0, 0, -5, -5, 0, 0, 0, 0, 0, 0, -5, -5, 0, 0, 0, 0, 0, 0, -5, -5, 0, 0,
-                0, 0, 0, 0, -5, 0, 0, 0, 5, 5, 0, 0, 0,
-            ],
-            queen: [
-                -20, -10, -10, -5, -5, -10, -10, -20, -10, 0, 0, 0, 0, 0, 0, -10, -10, 0, 5, 5, 5,
-                5, 0, -10, -5, 0, 5, 5, 5, 5, 0, -5, 0, 0, 5, 5, 5, 5, 0, -5, -10, 5, 5, 5, 5, 5,
-                0, -10, -10, 0, 5, 0, 0, 0, 0, -10, -20, -10, -10, -5, -5, -10, -10, -20,
-            ],
-            king_middlegame: [
-                -30, -40, -40, -50, -50, -40, -40, -30, -30, -40, -40, -50, -50, -40, -40, -30,
-                -30, -40, -40, -50, -50, -40, -40, -30, -30, -40, -40, -50, -50, -40, -40, -30,
-                -20, -30, -30, -40, -40, -30, -30, -20, -10, -20, -20, -20, -20, -20, -20, -10, 20,
-                20, 0, 0, 0, 0, 20, 20, 20, 30, 10, 0, 0, 10, 30, 20,
-            ],
-            king_endgame: [
-                -50, -40, -30, -20, -20, -30, -40, -50, -30, -20, -10, 0, 0, -10, -20, -30, -30,
-                -10, 20, 30, 30, 20, -10, -30, -30, -10, 30, 40, 40, 30, -10, -30, -30, -10, 30,
-                40, 40, 30, -10, -30, -30, -10, 20, 30, 30, 20, -10, -30, -30, -30, 0, 0, 0, 0,
-                -30, -30, -50, -30, -30, -30, -30, -30, -30, -50,
-            ],
-        }
-    }
-}
-
 impl Default for Evaluator {
     fn default() -> Self {
         Self::new()
@@ -466,6 +313,25 @@ mod tests {
         assert_eq!(score, 0);
     }
 
+    #[test]
+    fn test_breakdown_adds_up_to_evaluation() {
+        let evaluator = Evaluator::new();
+        let fen = "r1bqk2r/ppp2ppp/2n2n2/3pp3/1b2P3/2NP1N2/PPP2PPP/R1BQKB1R b KQkq - 0 5";
+        let game_state = GameState::from_fen(fen).unwrap();
+
+        let breakdown = evaluator.evaluate_breakdown(&game_state);
+        assert_eq!(breakdown.material, 0);
+        assert_eq!(breakdown.scale, SCALE_NORMAL);
+        assert!(!breakdown.known_ending);
+        assert_eq!(breakdown.total, breakdown.terms_sum());
+        assert_eq!(evaluator.evaluate(&game_state), -breakdown.total);
+
+        let ending = GameState::from_fen("k7/8/1K6/8/8/8/8/5BN1 b - - 0 1").unwrap();
+        let breakdown = evaluator.evaluate_breakdown(&ending);
+        assert!(breakdown.known_ending);
+        assert_eq!(evaluator.evaluate(&ending), -breakdown.total);
+    }
+
     #[test]
     fn test_material_evaluation() {
         let evaluator = Evaluator::new();
@@ -484,4 +350,17 @@ mod tests {
 
         assert!(evaluator.is_endgame(&game_state));
     }
+
+    #[test]
+    fn test_pieces_in_hand_count_as_material() {
+        let evaluator = Evaluator::new();
+        let mut optimized = crate::OptimizedEvaluator::new();
+        let empty = GameState::from_fen("4k3/8/8/8/8/8/8/4K3[] w - - 0 1").unwrap();
+        let knight = GameState::from_fen("4k3/8/8/8/8/8/8/4K3[N] w - - 0 1").unwrap();
+
+        let gain = evaluator.evaluate(&knight) - evaluator.evaluate(&empty);
+        assert!(gain > PieceType::Knight.value());
+        let gain = optimized.evaluate(&knight.position) - optimized.evaluate(&empty.position);
+        assert!(gain > 0);
+    }
 }
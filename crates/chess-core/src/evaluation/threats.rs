@@ -0,0 +1,192 @@
+// Threat terms: pieces under attack by cheaper attackers, hanging pieces and pawn
+// pushes that would attack a piece next move
+//
+// Each side is scored for the threats it makes. The difference between the two sides
+// is the term, so a side's own hanging pieces count against it through the opponent's
+// score.
+
+use super::pawns::pawn_attacks;
+use crate::{Bitboard, Color, MoveGenerator, PieceType, Position, Square};
+
+/// Per enemy piece (not pawn) attacked by a pawn
+pub const THREAT_BY_PAWN_BONUS: i32 = 40;
+/// Per enemy piece attacked by a piece of lower value
+pub const THREAT_BY_LESSER_BONUS: i32 = 25;
+/// Per enemy piece attacked and not defended at all
+pub const HANGING_PIECE_BONUS: i32 = 20;
+/// Per enemy piece a safe pawn push would attack
+pub const PAWN_PUSH_THREAT_BONUS: i32 = 15;
+
+/// Squares attacked by one side, split by attacking piece type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttackMap {
+    /// Indexed by `PieceType::index`
+    pub by_piece: [Bitboard; 6],
+    pub all: Bitboard,
+}
+
+impl AttackMap {
+    pub fn compute(position: &Position, color: Color, move_generator: &MoveGenerator) -> Self {
+        let occupied = position.all_pieces();
+        let mut by_piece = [Bitboard::EMPTY; 6];
+
+        by_piece[PieceType::Pawn.index()] =
+            pawn_attacks(position.piece_bitboard(PieceType::Pawn, color), color);
+
+        for piece_type in [
+            PieceType::Knight,
+            PieceType::Bishop,
+            PieceType::Rook,
+            PieceType::Queen,
+            PieceType::King,
+        ] {
+            let mut attacks = Bitboard::EMPTY;
+            for index in position.piece_bitboard(piece_type, color).iter() {
+                let square = Square::from(index);
+                attacks |= match piece_type {
+                    PieceType::Knight => move_generator.knight_attacks(square),
+                    PieceType::Bishop => move_generator.bishop_attacks(square, occupied),
+                    PieceType::Rook => move_generator.rook_attacks(square, occupied),
+                    PieceType::Queen => move_generator.queen_attacks(square, occupied),
+                    _ => move_generator.king_attacks(square),
+                };
+            }
+            by_piece[piece_type.index()] = attacks;
+        }
+
+        let all = by_piece.iter().fold(Bitboard::EMPTY, |acc, &b| acc | b);
+        AttackMap { by_piece, all }
+    }
+
+    /// Squares attacked by pieces worth less than `piece_type`
+    fn by_lesser_than(&self, piece_type: PieceType) -> Bitboard {
+        let mut attacks = Bitboard::EMPTY;
+        for attacker in PieceType::ALL {
+            if attacker != PieceType::King && attacker.value() < piece_type.value() {
+                attacks |= self.by_piece[attacker.index()];
+            }
+        }
+        attacks
+    }
+}
+
+/// Threats `color` makes against the other side, given both sides' attack maps
+pub fn threat_score(
+    position: &Position,
+    color: Color,
+    own_attacks: &AttackMap,
+    enemy_attacks: &AttackMap,
+) -> i32 {
+    let enemy = color.opposite();
+    let enemy_king = position.piece_bitboard(PieceType::King, enemy);
+    let enemy_pieces = position.pieces_of_color(enemy) & !enemy_king;
+    let enemy_non_pawns = enemy_pieces & !position.piece_bitboard(PieceType::Pawn, enemy);
+    let pawn_threats = own_attacks.by_piece[PieceType::Pawn.index()];
+    let mut score = 0;
+
+    for index in (enemy_pieces & own_attacks.all).iter() {
+        let square = Square::from(index);
+        let Some(piece) = position.piece_at(square) else {
+            continue;
+        };
+
+        // Only the strongest threat against each piece counts
+        score += if piece.piece_type != PieceType::Pawn && pawn_threats.contains(square) {
+            THREAT_BY_PAWN_BONUS
+        } else if own_attacks
+            .by_lesser_than(piece.piece_type)
+            .contains(square)
+        {
+            THREAT_BY_LESSER_BONUS
+        } else if !enemy_attacks.all.contains(square) {
+            HANGING_PIECE_BONUS
+        } else {
+            0
+        };
+    }
+
+    // Pushes onto empty squares the enemy pawns do not guard
+    let pawns = position.piece_bitboard(PieceType::Pawn, color);
+    let empty = !position.all_pieces();
+    let (single, double_rank) = match color {
+        Color::White => (pawns.shift_north() & empty, Bitboard::RANK_4),
+        Color::Black => (pawns.shift_south() & empty, Bitboard::RANK_5),
+    };
+    let double = match color {
+        Color::White => single.shift_north(),
+        Color::Black => single.shift_south(),
+    } & empty
+        & double_rank;
+    let safe_pushes = (single | double) & !enemy_attacks.by_piece[PieceType::Pawn.index()];
+    let push_threats = pawn_attacks(safe_pushes, color) & enemy_non_pawns & !pawn_threats;
+    score += push_threats.count_bits() as i32 * PAWN_PUSH_THREAT_BONUS;
+
+    score
+}
+
+/// White-relative threat term for the position
+pub fn threat_balance(position: &Position, move_generator: &MoveGenerator) -> i32 {
+    let white = AttackMap::compute(position, Color::White, move_generator);
+    let black = AttackMap::compute(position, Color::Black, move_generator);
+    threat_score(position, Color::White, &white, &black)
+        - threat_score(position, Color::Black, &black, &white)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn threats(fen: &str, color: Color) -> i32 {
+        let position = Position::from_fen(fen).unwrap();
+        let move_generator = MoveGenerator::new();
+        let own = AttackMap::compute(&position, color, &move_generator);
+        let enemy = AttackMap::compute(&position, color.opposite(), &move_generator);
+        threat_score(&position, color, &own, &enemy)
+    }
+
+    #[test]
+    fn test_attack_map() {
+        let position = Position::from_fen("4k3/8/8/8/8/8/3P4/4K1N1 w - - 0 1").unwrap();
+        let map = AttackMap::compute(&position, Color::White, &MoveGenerator::new());
+        assert_eq!(
+            map.by_piece[PieceType::Pawn.index()],
+            Square::C3.bitboard() | Square::E3.bitboard()
+        );
+        assert!(map.by_piece[PieceType::Knight.index()].contains(Square::F3));
+        assert!(map.all.contains(Square::D1));
+        assert!(!map.all.contains(Square::E8));
+    }
+
+    #[test]
+    fn test_pawn_and_lesser_threats() {
+        // e4 pawn attacks the d5 knight, which c6 defends
+        let by_pawn = threats("4k3/8/2p5/3n4/4P3/8/8/4K3 w - - 0 1", Color::White);
+        assert_eq!(by_pawn, THREAT_BY_PAWN_BONUS);
+
+        // Bishop on b5 attacks the e8 rook, defended by the king
+        let by_lesser = threats("3kr3/8/8/1B6/8/8/8/4K3 w - - 0 1", Color::White);
+        assert_eq!(by_lesser, THREAT_BY_LESSER_BONUS);
+    }
+
+    #[test]
+    fn test_hanging_pieces() {
+        let hanging = threats("4k3/8/8/3n4/8/8/8/3RK3 w - - 0 1", Color::White);
+        let defended = threats("4k3/8/2p5/3n4/8/8/8/3RK3 w - - 0 1", Color::White);
+        assert_eq!(hanging, HANGING_PIECE_BONUS);
+        assert_eq!(defended, 0);
+
+        // The hanging knight counts against Black in the balance
+        let position = Position::from_fen("4k3/8/8/3n4/8/8/8/3RK3 w - - 0 1").unwrap();
+        assert!(threat_balance(&position, &MoveGenerator::new()) > 0);
+    }
+
+    #[test]
+    fn test_pawn_push_threats() {
+        // d3-d4 would hit the e5 knight, and d4 is not guarded by a black pawn
+        let push = threats("4k3/8/8/4n3/8/3P4/8/4K3 w - - 0 1", Color::White);
+        assert_eq!(push, PAWN_PUSH_THREAT_BONUS);
+
+        let guarded = threats("4k3/8/8/2p1n3/8/3P4/8/4K3 w - - 0 1", Color::White);
+        assert_eq!(guarded, 0);
+    }
+}
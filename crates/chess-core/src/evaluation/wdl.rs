@@ -0,0 +1,181 @@
+// Centipawn to win/draw/loss conversion
+//
+// Logistic model in the style of Stockfish's win-rate model: the centipawn value that
+// gives a 50% win chance, and the spread of the curve, both depend on how much material
+// is left, since an extra pawn decides more in an endgame than in the middlegame.
+
+use crate::search::engine::MATE_VALUE;
+use crate::{Color, MaterialKey, PieceType, Position};
+
+/// Scores beyond this are mate announcements rather than evaluations
+const MATE_THRESHOLD: i32 = MATE_VALUE - 1000;
+
+/// Engine-internal units per centipawn the model coefficients were fitted against
+const MODEL_UNITS_PER_PAWN: f64 = 3.56;
+
+// Cubic fits of the curve's midpoint and spread over normalised material
+const MIDPOINT_COEFFS: [f64; 4] = [
+    -37.450_518_76,
+    121.191_015_39,
+    -132.787_835_73,
+    420.705_766_92,
+];
+const SPREAD_COEFFS: [f64; 4] = [90.262_610_72, -137.265_498_98, 71.101_305_40, 51.352_595_97];
+
+/// Win, draw and loss chances in permille; they always sum to 1000
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Wdl {
+    pub win: u16,
+    pub draw: u16,
+    pub loss: u16,
+}
+
+impl Wdl {
+    /// Expected game points from 0.0 (certain loss) to 1.0 (certain win)
+    pub fn expected_points(self) -> f64 {
+        (self.win as f64 + self.draw as f64 / 2.0) / 1000.0
+    }
+
+    /// The same chances from the opponent's point of view
+    pub fn flipped(self) -> Self {
+        Wdl {
+            win: self.loss,
+            draw: self.draw,
+            loss: self.win,
+        }
+    }
+}
+
+/// A centipawn score together with the material it was measured in, so it can be
+/// converted to win chances
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Score {
+    /// Centipawns from the point of view of the side the score belongs to
+    pub centipawns: i32,
+    /// Material left on the board (pawn 1, minor 3, rook 5, queen 9; both sides)
+    pub material: u32,
+}
+
+impl Score {
+    pub fn new(centipawns: i32, material: u32) -> Self {
+        Score {
+            centipawns,
+            material,
+        }
+    }
+
+    pub fn for_position(centipawns: i32, position: &Position) -> Self {
+        Self::new(centipawns, material_points(position.material_key()))
+    }
+
+    pub fn is_mate(self) -> bool {
+        self.centipawns.abs() >= MATE_THRESHOLD
+    }
+
+    pub fn wdl(self) -> Wdl {
+        if self.is_mate() {
+            return if self.centipawns > 0 {
+                Wdl {
+                    win: 1000,
+                    draw: 0,
+                    loss: 0,
+                }
+            } else {
+                Wdl {
+                    win: 0,
+                    draw: 0,
+                    loss: 1000,
+                }
+            };
+        }
+
+        let win = win_permille(self.centipawns, self.material);
+        let loss = win_permille(-self.centipawns, self.material);
+        Wdl {
+            win,
+            draw: 1000 - win - loss,
+            loss,
+        }
+    }
+
+    pub fn expected_points(self) -> f64 {
+        self.wdl().expected_points()
+    }
+
+    /// The same score from the opponent's point of view
+    pub fn flipped(self) -> Self {
+        Score::new(-self.centipawns, self.material)
+    }
+}
+
+/// Sum of piece values for both sides, in the units the model expects
+pub fn material_points(key: MaterialKey) -> u32 {
+    const VALUES: [(PieceType, u32); 5] = [
+        (PieceType::Pawn, 1),
+        (PieceType::Knight, 3),
+        (PieceType::Bishop, 3),
+        (PieceType::Rook, 5),
+        (PieceType::Queen, 9),
+    ];
+    VALUES
+        .iter()
+        .map(|&(piece_type, value)| {
+            (key.count(piece_type, Color::White) + key.count(piece_type, Color::Black)) * value
+        })
+        .sum()
+}
+
+fn win_permille(centipawns: i32, material: u32) -> u16 {
+    let m = material.clamp(17, 78) as f64 / 58.0;
+    let cubic = |c: [f64; 4]| ((c[0] * m + c[1]) * m + c[2]) * m + c[3];
+    let midpoint = cubic(MIDPOINT_COEFFS);
+    let spread = cubic(SPREAD_COEFFS);
+
+    let value = centipawns as f64 * MODEL_UNITS_PER_PAWN;
+    let win = 1000.0 / (1.0 + ((midpoint - value) / spread).exp());
+    win.round().clamp(0.0, 1000.0) as u16
+}
+
+/// Accuracy of one move in percent (0-100) from the mover's expected points before and
+/// after it, using the same curve as common game-review tools
+pub fn move_accuracy(before: Score, after: Score) -> f64 {
+    let loss = ((before.expected_points() - after.expected_points()) * 100.0).max(0.0);
+    (103.1668 * (-0.04354 * loss).exp() - 3.1669).clamp(0.0, 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wdl_is_symmetric_and_sums_to_1000() {
+        for &cp in &[-400, -150, 0, 35, 150, 400] {
+            for &material in &[10, 40, 78] {
+                let wdl = Score::new(cp, material).wdl();
+                assert_eq!(wdl.win + wdl.draw + wdl.loss, 1000);
+                assert_eq!(Score::new(-cp, material).wdl(), wdl.flipped());
+            }
+        }
+        let level = Score::new(0, 58).expected_points();
+        assert!((level - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_win_chance_grows_with_score_and_in_endgames() {
+        let middlegame = Score::new(150, 70).wdl();
+        let endgame = Score::new(150, 20).wdl();
+        assert!(Score::new(300, 70).wdl().win > middlegame.win);
+        assert!(endgame.win > middlegame.win);
+        assert_eq!(Score::new(MATE_VALUE - 5, 40).wdl().win, 1000);
+    }
+
+    #[test]
+    fn test_material_and_accuracy() {
+        let start = Position::starting_position();
+        assert_eq!(material_points(start.material_key()), 78);
+
+        let score = Score::for_position(50, &start);
+        assert!((move_accuracy(score, score) - 100.0).abs() < 0.01);
+        assert!(move_accuracy(score, Score::for_position(-500, &start)) < 30.0);
+    }
+}
@@ -1,4 +1,6 @@
+pub mod notation;
 pub mod rules;
 pub mod state;
 
-pub use state::{CastlingRights, GameResult, GameState};
+pub use notation::{format_move, move_to_san, NotationStyle};
+pub use state::{CastlingRights, DrawReason, GameResult, GameState, SquareChange};
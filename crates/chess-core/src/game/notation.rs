@@ -0,0 +1,268 @@
+// Move notation for display: UCI coordinates, standard algebraic notation (SAN) with
+// disambiguation and check marks, and figurine notation using piece glyphs
+
+use crate::{ChessError, Color, GameState, Move, PieceType, Result};
+use serde::{Deserialize, Serialize};
+
+/// How moves are written for people and host applications
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum NotationStyle {
+    /// Coordinates as in the UCI protocol, e.g. `g1f3` or `e7e8q`
+    #[default]
+    Uci,
+    /// UCI coordinates with castling written as the king taking its own rook, e.g.
+    /// `e1h1`, as GUIs expect with the `UCI_Chess960` option set
+    UciChess960,
+    /// Standard algebraic notation, e.g. `Nf3`, `exd5`, `O-O` or `e8=Q+`
+    San,
+    /// SAN with piece glyphs instead of letters, e.g. `♘f3`
+    Figurine,
+}
+
+/// `mv` written in `style` for the position of `game_state`; the move must be legal.
+/// Drops are written as `N@f3` in every style, with a glyph in figurine notation.
+pub fn format_move(game_state: &GameState, mv: Move, style: NotationStyle) -> Result<String> {
+    match style {
+        NotationStyle::Uci => {
+            legal_move(game_state, mv, false)?;
+            Ok(mv.to_uci())
+        }
+        NotationStyle::UciChess960 => {
+            let (mv, _) = legal_move(game_state, mv, true)?;
+            Ok(mv.to_uci_chess960(&game_state.position))
+        }
+        NotationStyle::San => write_san(game_state, mv, |piece_type, _| piece_type.symbol()),
+        NotationStyle::Figurine => write_san(game_state, mv, PieceType::unicode_symbol),
+    }
+}
+
+/// `mv` in standard algebraic notation; the move must be legal in `game_state`
+pub fn move_to_san(game_state: &GameState, mv: Move) -> Result<String> {
+    format_move(game_state, mv, NotationStyle::San)
+}
+
+/// The legal move of `game_state` matching `mv`'s squares and promotion, castling
+/// written as king-takes-rook included if `chess960` is set
+fn legal_move(game_state: &GameState, mv: Move, chess960: bool) -> Result<(Move, Vec<Move>)> {
+    let legal_moves = game_state.generate_legal_moves();
+    let found = legal_moves
+        .iter()
+        .copied()
+        .find(|&legal| mv.names(legal, &game_state.position, chess960))
+        .ok_or_else(|| ChessError::InvalidMove(format!("Illegal move: {}", mv)))?;
+    Ok((found, legal_moves))
+}
+
+fn write_san(
+    game_state: &GameState,
+    mv: Move,
+    piece_symbol: impl Fn(PieceType, Color) -> char,
+) -> Result<String> {
+    let (mv, legal_moves) = legal_move(game_state, mv, false)?;
+    let position = &game_state.position;
+    let color = position.side_to_move;
+    let piece_type = match mv.drop_piece() {
+        Some(piece_type) => piece_type,
+        None => position
+            .piece_at(mv.from)
+            .map(|piece| piece.piece_type)
+            .ok_or_else(|| ChessError::InvalidMove(format!("No piece on {}", mv.from)))?,
+    };
+
+    let mut san = String::new();
+    if mv.is_drop() {
+        san.push(piece_symbol(piece_type, color));
+        san.push('@');
+        san.push_str(&mv.to.to_string());
+    } else if mv.is_castle() {
+        san.push_str(if mv.to.file() > mv.from.file() {
+            "O-O"
+        } else {
+            "O-O-O"
+        });
+    } else if piece_type == PieceType::Pawn {
+        if mv.is_capture() {
+            san.push(mv.from.file_char());
+            san.push('x');
+        }
+        san.push_str(&mv.to.to_string());
+        if let Some(promotion) = mv.promotion_piece() {
+            san.push('=');
+            san.push(piece_symbol(promotion, color));
+        }
+    } else {
+        san.push(piece_symbol(piece_type, color));
+
+        // Name the origin file, rank or both when another piece of the same type can
+        // reach the destination
+        let rivals: Vec<Move> = legal_moves
+            .iter()
+            .copied()
+            .filter(|other| {
+                other.to == mv.to
+                    && other.from != mv.from
+                    && position
+                        .piece_at(other.from)
+                        .is_some_and(|piece| piece.piece_type == piece_type)
+            })
+            .collect();
+        if !rivals.is_empty() {
+            let same_file = rivals
+                .iter()
+                .any(|other| other.from.file() == mv.from.file());
+            let same_rank = rivals
+                .iter()
+                .any(|other| other.from.rank() == mv.from.rank());
+            if !same_file {
+                san.push(mv.from.file_char());
+            } else if !same_rank {
+                san.push(mv.from.rank_char());
+            } else {
+                san.push_str(&mv.from.to_string());
+            }
+        }
+
+        if mv.is_capture() {
+            san.push('x');
+        }
+        san.push_str(&mv.to.to_string());
+    }
+
+    let mut next = game_state.clone();
+    next.position.make_move(mv)?;
+    if next.is_in_check(color.opposite()) {
+        san.push(if next.generate_legal_moves().is_empty() {
+            '#'
+        } else {
+            '+'
+        });
+    }
+    Ok(san)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Square;
+
+    fn san(fen: &str, uci: &str) -> String {
+        let game = GameState::from_fen(fen).unwrap();
+        move_to_san(&game, uci.parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_basic_san() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(san(start, "e2e4"), "e4");
+        assert_eq!(san(start, "g1f3"), "Nf3");
+
+        let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        assert_eq!(san(kiwipete, "e1g1"), "O-O");
+        assert_eq!(san(kiwipete, "e1c1"), "O-O-O");
+        assert_eq!(san(kiwipete, "d5e6"), "dxe6");
+        assert_eq!(san(kiwipete, "e2a6"), "Bxa6");
+        assert_eq!(san(kiwipete, "e5f7"), "Nxf7");
+    }
+
+    #[test]
+    fn test_disambiguation() {
+        // Knights on b1 and f1 both reach d2; rooks on a1 and a5 both reach a3
+        let fen = "4k3/8/8/R7/8/8/8/RN2KN2 w - - 0 1";
+        assert_eq!(san(fen, "b1d2"), "Nbd2");
+        assert_eq!(san(fen, "a1a3"), "R1a3");
+        assert_eq!(san(fen, "a5a3"), "R5a3");
+
+        // Queens on a1, a3 and c1 all reach b2, so a1 needs file and rank
+        let fen = "4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1";
+        assert_eq!(san(fen, "a1b2"), "Qa1b2");
+        assert_eq!(san(fen, "c1b2"), "Qcb2");
+    }
+
+    #[test]
+    fn test_check_mate_and_promotion() {
+        assert_eq!(san("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8"), "Ra8#");
+        assert_eq!(san("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a8"), "Ra8+");
+        assert_eq!(san("8/P6k/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), "a8=Q");
+        assert_eq!(san("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8r"), "a8=R+");
+    }
+
+    #[test]
+    fn test_styles() {
+        let game = GameState::new();
+        let mv = Move::normal(Square::G1, Square::F3);
+        assert_eq!(format_move(&game, mv, NotationStyle::Uci).unwrap(), "g1f3");
+        assert_eq!(format_move(&game, mv, NotationStyle::San).unwrap(), "Nf3");
+        assert_eq!(
+            format_move(&game, mv, NotationStyle::Figurine).unwrap(),
+            "♘f3"
+        );
+
+        let illegal = Move::normal(Square::E2, Square::E5);
+        assert!(format_move(&game, illegal, NotationStyle::Uci).is_err());
+        assert!(move_to_san(&game, illegal).is_err());
+    }
+
+    #[test]
+    fn test_chess960_castling_notation() {
+        let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let game = GameState::from_fen(kiwipete).unwrap();
+        let short = Move::castle(Square::E1, Square::G1);
+        let long = Move::castle(Square::E1, Square::C1);
+        assert_eq!(
+            format_move(&game, short, NotationStyle::UciChess960).unwrap(),
+            "e1h1"
+        );
+        assert_eq!(
+            format_move(&game, long, NotationStyle::UciChess960).unwrap(),
+            "e1a1"
+        );
+        assert_eq!(
+            format_move(&game, short, NotationStyle::Uci).unwrap(),
+            "e1g1"
+        );
+
+        // In UCI_Chess960 mode either encoding reads back as the same castle
+        for (uci, castle) in [
+            ("e1h1", short),
+            ("e1g1", short),
+            ("e1a1", long),
+            ("e1c1", long),
+        ] {
+            let resolved = game.resolve_uci_move_in(uci, NotationStyle::UciChess960);
+            assert_eq!(resolved.unwrap(), castle);
+        }
+
+        // A standard game keeps king-takes-rook illegal
+        assert!(game.resolve_uci_move("e1h1").is_err());
+        assert!(game
+            .resolve_uci_move_in("e1a1", NotationStyle::Uci)
+            .is_err());
+        assert_eq!(game.resolve_uci_move("e1g1").unwrap(), short);
+        let king_takes_rook: Move = "e1h1".parse().unwrap();
+        assert!(move_to_san(&game, king_takes_rook).is_err());
+        assert_eq!(
+            format_move(&game, king_takes_rook, NotationStyle::UciChess960).unwrap(),
+            "e1h1"
+        );
+
+        // Other moves read the same in both modes
+        let knight = game.resolve_uci_move("e5f7").unwrap();
+        assert_eq!(knight.to_uci_chess960(&game.position), "e5f7");
+    }
+
+    #[test]
+    fn test_drop_notation() {
+        let game = GameState::from_fen("4k3/8/8/8/8/8/8/4K3[N] w - - 0 1").unwrap();
+        let drop = Move::drop(PieceType::Knight, Square::D6);
+        assert_eq!(move_to_san(&game, drop).unwrap(), "N@d6+");
+        assert_eq!(
+            format_move(&game, drop, NotationStyle::Uci).unwrap(),
+            "N@d6"
+        );
+        assert_eq!(
+            format_move(&game, drop, NotationStyle::Figurine).unwrap(),
+            "♘@d6+"
+        );
+        assert!(move_to_san(&game, Move::drop(PieceType::Queen, Square::D6)).is_err());
+    }
+}
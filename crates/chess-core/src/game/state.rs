@@ -1,116 +1,13 @@
-use crate::{ChessError, Color, Move, MoveGenerator, PieceType, Position, Result, Square};
+pub use crate::board::CastlingRights;
+use crate::{
+    ChessError, Color, GamePhase, Move, MoveGenerator, NotationStyle, Piece, PieceType, Position,
+    Result, Square, UndoInfo,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct CastlingRights {
-    pub white_kingside: bool,
-    pub white_queenside: bool,
-    pub black_kingside: bool,
-    pub black_queenside: bool,
-}
-
-impl CastlingRights {
-    pub const ALL: CastlingRights = CastlingRights {
-        white_kingside: true,
-        white_queenside: true,
-        black_kingside: true,
-        black_queenside: true,
-    };
-
-    pub const NONE: CastlingRights = CastlingRights {
-        white_kingside: false,
-        white_queenside: false,
-        black_kingside: false,
-        black_queenside: false,
-    };
-
-    pub fn can_castle_kingside(&self, color: Color) -> bool {
-        match color {
-            Color::White => self.white_kingside,
-            Color::Black => self.black_kingside,
-        }
-    }
-
-    pub fn can_castle_queenside(&self, color: Color) -> bool {
-        match color {
-            Color::White => self.white_queenside,
-            Color::Black => self.black_queenside,
-        }
-    }
-
-    pub fn remove_kingside(&mut self, color: Color) {
-        match color {
-            Color::White => self.white_kingside = false,
-            Color::Black => self.black_kingside = false,
-        }
-    }
-
-    pub fn remove_queenside(&mut self, color: Color) {
-        match color {
-            Color::White => self.white_queenside = false,
-            Color::Black => self.black_queenside = false,
-        }
-    }
-
-    pub fn remove_all(&mut self, color: Color) {
-        self.remove_kingside(color);
-        self.remove_queenside(color);
-    }
-
-    pub fn to_fen(&self) -> String {
-        let mut result = String::new();
-        if self.white_kingside {
-            result.push('K');
-        }
-        if self.white_queenside {
-            result.push('Q');
-        }
-        if self.black_kingside {
-            result.push('k');
-        }
-        if self.black_queenside {
-            result.push('q');
-        }
-        if result.is_empty() {
-            result.push('-');
-        }
-        result
-    }
-}
-
-impl std::fmt::Display for CastlingRights {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_fen())
-    }
-}
-
-impl CastlingRights {
-    pub fn from_string(s: &str) -> Result<Self> {
-        if s == "-" {
-            return Ok(CastlingRights::NONE);
-        }
-
-        let mut rights = CastlingRights::NONE;
-        for ch in s.chars() {
-            match ch {
-                'K' => rights.white_kingside = true,
-                'Q' => rights.white_queenside = true,
-                'k' => rights.black_kingside = true,
-                'q' => rights.black_queenside = true,
-                _ => {
-                    return Err(ChessError::ParseError(format!(
-                        "Invalid castling rights: {}",
-                        ch
-                    )))
-                }
-            }
-        }
-        Ok(rights)
-    }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum GameResult {
     Ongoing,
     WhiteWins,
@@ -124,13 +21,31 @@ impl GameResult {
     }
 }
 
+/// Why a game ended in a draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DrawReason {
+    Stalemate,
+    FiftyMoveRule,
+    ThreefoldRepetition,
+    InsufficientMaterial,
+    /// Both players agreed; never derived from a position, only recorded by the engine
+    Agreement,
+}
+
+/// A square whose contents differ between two game states
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SquareChange {
+    pub square: Square,
+    /// Piece on the square in the state the diff starts from
+    pub before: Option<Piece>,
+    /// Piece on the square in the state the diff leads to
+    pub after: Option<Piece>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GameState {
     pub position: Position,
-    pub castling_rights: CastlingRights,
-    pub en_passant_target: Option<Square>,
-    pub halfmove_clock: u32,
-    pub fullmove_number: u32,
     pub position_history: HashMap<u64, u32>,
     pub move_history: Vec<Move>,
     pub move_generator: MoveGenerator,
@@ -138,13 +53,16 @@ pub struct GameState {
 
 impl GameState {
     pub fn new() -> Self {
+        Self::from_position(Position::starting_position())
+    }
+
+    /// Game starting at `position`, which counts as its first occurrence for repetitions
+    fn from_position(position: Position) -> Self {
+        let mut position_history = HashMap::new();
+        position_history.insert(position.zobrist_hash(), 1);
         GameState {
-            position: Position::starting_position(),
-            castling_rights: CastlingRights::ALL,
-            en_passant_target: None,
-            halfmove_clock: 0,
-            fullmove_number: 1,
-            position_history: HashMap::new(),
+            position,
+            position_history,
             move_history: Vec::new(),
             move_generator: MoveGenerator::new(),
         }
@@ -158,125 +76,56 @@ impl GameState {
             ));
         }
 
-        let position = Position::from_fen(&format!("{} {}", parts[0], parts[1]))?;
-        let castling_rights = CastlingRights::from_string(parts[2])?;
-
-        let en_passant_target = if parts[3] == "-" {
-            None
-        } else {
-            Some(parts[3].parse()?)
-        };
-
-        let halfmove_clock = if parts.len() > 4 {
-            parts[4]
-                .parse()
-                .map_err(|_| ChessError::ParseError("Invalid halfmove clock".to_string()))?
-        } else {
-            0
-        };
-
-        let fullmove_number = if parts.len() > 5 {
-            parts[5]
-                .parse()
-                .map_err(|_| ChessError::ParseError("Invalid fullmove number".to_string()))?
-        } else {
-            1
-        };
+        Ok(Self::from_position(Position::from_fen(fen)?))
+    }
 
-        Ok(GameState {
-            position,
-            castling_rights,
-            en_passant_target,
-            halfmove_clock,
-            fullmove_number,
-            position_history: HashMap::new(),
-            move_history: Vec::new(),
-            move_generator: MoveGenerator::new(),
-        })
+    /// Game from a FEN of `variant`, needed for Horde which its FEN cannot express
+    pub fn from_fen_with_variant(fen: &str, variant: crate::Variant) -> Result<Self> {
+        Ok(Self::from_position(Position::from_fen_with_variant(
+            fen, variant,
+        )?))
     }
 
     pub fn to_fen(&self) -> String {
-        format!(
-            "{} {} {} {} {}",
-            self.position.to_fen(),
-            self.castling_rights.to_fen(),
-            self.en_passant_target
-                .map_or("-".to_string(), |sq| sq.to_string()),
-            self.halfmove_clock,
-            self.fullmove_number
-        )
+        self.position.to_fen()
     }
 
     pub fn make_move(&mut self, mv: Move) -> Result<()> {
+        self.make_move_with_undo(mv).map(|_| ())
+    }
+
+    /// Play a legal move, returning what `undo_move` needs to take it back
+    pub fn make_move_with_undo(&mut self, mv: Move) -> Result<UndoInfo> {
         if !self.is_legal_move(mv) {
             return Err(ChessError::InvalidMove(format!("Illegal move: {}", mv)));
         }
 
-        let captured_piece = self.position.piece_at(mv.to);
-        let _moving_piece = self.position.piece_at(mv.from).unwrap();
-
-        self.update_castling_rights(&mv);
-        self.update_en_passant_target(&mv);
-        self.update_halfmove_clock(&mv, captured_piece.is_some());
-
-        let _undo_info = self.position.make_move(mv)?;
-
-        if self.position.side_to_move == Color::White {
-            self.fullmove_number += 1;
-        }
+        let undo = self.position.make_move(mv)?;
 
         self.move_history.push(mv);
 
         let position_hash = self.calculate_position_hash();
         *self.position_history.entry(position_hash).or_insert(0) += 1;
 
-        Ok(())
-    }
-
-    fn update_castling_rights(&mut self, mv: &Move) {
-        match mv.from {
-            Square::E1 => self.castling_rights.remove_all(Color::White),
-            Square::E8 => self.castling_rights.remove_all(Color::Black),
-            Square::A1 => self.castling_rights.remove_queenside(Color::White),
-            Square::H1 => self.castling_rights.remove_kingside(Color::White),
-            Square::A8 => self.castling_rights.remove_queenside(Color::Black),
-            Square::H8 => self.castling_rights.remove_kingside(Color::Black),
-            _ => {}
-        }
-
-        match mv.to {
-            Square::A1 => self.castling_rights.remove_queenside(Color::White),
-            Square::H1 => self.castling_rights.remove_kingside(Color::White),
-            Square::A8 => self.castling_rights.remove_queenside(Color::Black),
-            Square::H8 => self.castling_rights.remove_kingside(Color::Black),
-            _ => {}
-        }
+        Ok(undo)
     }
 
-    fn update_en_passant_target(&mut self, mv: &Move) {
-        self.en_passant_target = None;
+    /// Take back the last move, given the `UndoInfo` from `make_move_with_undo`. The
+    /// position it left no longer counts toward repetitions. Returns the move taken
+    /// back, or `None` at the start of the game.
+    pub fn undo_move(&mut self, undo: UndoInfo) -> Option<Move> {
+        let mv = self.move_history.pop()?;
 
-        if let Some(moving_piece) = self.position.piece_at(mv.from) {
-            if moving_piece.piece_type == crate::PieceType::Pawn {
-                let rank_diff = (mv.to.rank() as i8) - (mv.from.rank() as i8);
-                if rank_diff.abs() == 2 {
-                    let target_rank = match moving_piece.color {
-                        Color::White => mv.from.rank() + 1,
-                        Color::Black => mv.from.rank() - 1,
-                    };
-                    self.en_passant_target = Square::from_file_rank(mv.from.file(), target_rank);
-                }
+        let position_hash = self.calculate_position_hash();
+        if let Some(count) = self.position_history.get_mut(&position_hash) {
+            *count -= 1;
+            if *count == 0 {
+                self.position_history.remove(&position_hash);
             }
         }
-    }
 
-    fn update_halfmove_clock(&mut self, mv: &Move, is_capture: bool) {
-        let moving_piece = self.position.piece_at(mv.from).unwrap();
-        if moving_piece.piece_type == crate::PieceType::Pawn || is_capture {
-            self.halfmove_clock = 0;
-        } else {
-            self.halfmove_clock += 1;
-        }
+        self.position.undo_move(mv, undo);
+        Some(mv)
     }
 
     pub fn is_legal_move(&self, mv: Move) -> bool {
@@ -284,169 +133,98 @@ impl GameState {
         legal_moves.contains(&mv)
     }
 
-    pub fn generate_legal_moves(&self) -> Vec<Move> {
-        let mut legal_moves = Vec::new();
-        let pseudo_legal_moves = self.generate_pseudo_legal_moves();
-
-        for mv in pseudo_legal_moves {
-            let mut test_state = self.clone();
-            if test_state.position.make_move(mv).is_ok()
-                && !test_state.is_in_check(self.position.side_to_move)
-            {
-                legal_moves.push(mv);
-            }
-        }
-
-        legal_moves
+    /// Find the legal move matching a UCI string, with its capture, en passant,
+    /// castle or promotion type filled in. Castling is the king moving two squares
+    /// (`e1g1`).
+    pub fn resolve_uci_move(&self, uci: &str) -> Result<Move> {
+        self.resolve_uci_move_in(uci, NotationStyle::Uci)
     }
 
-    pub fn generate_pseudo_legal_moves(&self) -> Vec<Move> {
-        let mut moves = Vec::new();
-        let side_to_move = self.position.side_to_move;
-        let enemy_pieces = self.position.pieces_of_color(side_to_move.opposite());
-
-        for square_idx in 0..64 {
-            let square = Square::new(square_idx).unwrap();
-            if let Some(piece) = self.position.piece_at(square) {
-                if piece.color == side_to_move {
-                    match piece.piece_type {
-                        crate::PieceType::Pawn => {
-                            let mut pawn_moves = self.move_generator.generate_pawn_moves(
-                                square,
-                                piece.color,
-                                self.position.all_pieces(),
-                                enemy_pieces,
-                            );
-                            if let Some(ep_target) = self.en_passant_target {
-                                let ep_attacks =
-                                    self.move_generator.pawn_attacks(square, piece.color);
-                                if ep_attacks & ep_target.bitboard() != crate::Bitboard::EMPTY {
-                                    pawn_moves.push(Move::en_passant(square, ep_target));
-                                }
-                            }
-                            moves.extend(pawn_moves);
-                        }
-                        _ => {
-                            let piece_moves = self.move_generator.generate_piece_moves(
-                                square,
-                                piece.piece_type,
-                                self.position.all_pieces(),
-                                enemy_pieces,
-                            );
-                            moves.extend(piece_moves);
-                        }
-                    }
-                }
-            }
-        }
+    /// `resolve_uci_move` for a host writing moves in `style`: with
+    /// `NotationStyle::UciChess960`, castling may also be the king taking its own rook
+    /// (`e1h1`)
+    pub fn resolve_uci_move_in(&self, uci: &str, style: NotationStyle) -> Result<Move> {
+        let parsed: Move = uci.parse()?;
+        let chess960 = style == NotationStyle::UciChess960;
 
-        moves.extend(self.generate_castle_moves());
-        moves
+        self.generate_legal_moves()
+            .into_iter()
+            .find(|&mv| parsed.names(mv, &self.position, chess960))
+            .ok_or_else(|| ChessError::InvalidMove(format!("Illegal move: {}", uci)))
     }
 
-    pub fn generate_castle_moves(&self) -> Vec<Move> {
-        let mut moves = Vec::new();
-        let side_to_move = self.position.side_to_move;
-
-        if self.is_in_check(side_to_move) {
-            return moves;
-        }
+    /// Legal moves in `Move::generation_order`, which is the same on every platform
+    /// and for every way the position was reached
+    pub fn generate_legal_moves(&self) -> Vec<Move> {
+        self.move_generator.generate_legal_moves(&self.position)
+    }
 
-        if self.castling_rights.can_castle_kingside(side_to_move) {
-            if let Some(castle_move) = self.try_kingside_castle(side_to_move) {
-                moves.push(castle_move);
+    /// Legal moves of the side to move's piece on `square`, castling included for the
+    /// king; empty when the square holds no piece of the side to move. Only that
+    /// piece's moves are generated, for click-to-move interfaces that ask on every click.
+    pub fn legal_moves_from(&self, square: Square) -> Vec<Move> {
+        match self.position.piece_at(square) {
+            Some(piece) if piece.color == self.position.side_to_move => {
+                self.retain_legal(self.piece_moves_from(square, piece.piece_type))
             }
+            _ => Vec::new(),
         }
+    }
 
-        if self.castling_rights.can_castle_queenside(side_to_move) {
-            if let Some(castle_move) = self.try_queenside_castle(side_to_move) {
-                moves.push(castle_move);
-            }
+    /// Legal moves of the side to move's pieces of `piece_type`, including drops of
+    /// that piece from the hand in drop variants
+    pub fn legal_moves_of(&self, piece_type: PieceType) -> Vec<Move> {
+        let side_to_move = self.position.side_to_move;
+        let mut moves = Vec::new();
+        for square_idx in self
+            .position
+            .piece_bitboard(piece_type, side_to_move)
+            .iter()
+        {
+            moves.extend(self.piece_moves_from(Square::from(square_idx), piece_type));
         }
+        moves.extend(
+            self.move_generator
+                .generate_drops_of(&self.position, piece_type),
+        );
+        self.retain_legal(moves)
+    }
 
+    /// The pseudo-legal `moves` that do not leave the mover's king attacked, in
+    /// `Move::generation_order`
+    fn retain_legal(&self, mut moves: Vec<Move>) -> Vec<Move> {
+        moves.retain(|&mv| self.is_legal_pseudo_move(mv));
+        moves.sort_by_key(|mv| mv.generation_order());
         moves
     }
 
-    fn try_kingside_castle(&self, color: Color) -> Option<Move> {
-        let (king_from, king_to, rook_from, squares_to_check) = match color {
-            Color::White => (
-                Square::E1,
-                Square::G1,
-                Square::H1,
-                vec![Square::F1, Square::G1],
-            ),
-            Color::Black => (
-                Square::E8,
-                Square::G8,
-                Square::H8,
-                vec![Square::F8, Square::G8],
-            ),
-        };
-
-        if self.position.piece_at(king_from)?.piece_type != crate::PieceType::King {
-            return None;
-        }
-        if self.position.piece_at(rook_from)?.piece_type != crate::PieceType::Rook {
-            return None;
-        }
-
-        for square in &squares_to_check {
-            if self.position.piece_at(*square).is_some() {
-                return None;
-            }
-            if self.is_square_attacked(*square, color.opposite()) {
-                return None;
-            }
-        }
-
-        Some(Move::castle(king_from, king_to))
-    }
-
-    fn try_queenside_castle(&self, color: Color) -> Option<Move> {
-        let (king_from, king_to, rook_from, squares_to_check, squares_empty) = match color {
-            Color::White => (
-                Square::E1,
-                Square::C1,
-                Square::A1,
-                vec![Square::D1, Square::C1],
-                vec![Square::B1, Square::C1, Square::D1],
-            ),
-            Color::Black => (
-                Square::E8,
-                Square::C8,
-                Square::A8,
-                vec![Square::D8, Square::C8],
-                vec![Square::B8, Square::C8, Square::D8],
-            ),
-        };
-
-        if self.position.piece_at(king_from)?.piece_type != crate::PieceType::King {
-            return None;
-        }
-        if self.position.piece_at(rook_from)?.piece_type != crate::PieceType::Rook {
-            return None;
-        }
+    /// Whether a pseudo-legal move keeps the mover's king safe. Only the position is
+    /// copied, not the game history.
+    fn is_legal_pseudo_move(&self, mv: Move) -> bool {
+        self.move_generator.is_legal_pseudo_move(&self.position, mv)
+    }
 
-        for square in &squares_empty {
-            if self.position.piece_at(*square).is_some() {
-                return None;
-            }
-        }
+    pub fn generate_pseudo_legal_moves(&self) -> Vec<Move> {
+        self.move_generator
+            .generate_pseudo_legal_moves(&self.position)
+    }
 
-        for square in &squares_to_check {
-            if self.is_square_attacked(*square, color.opposite()) {
-                return None;
-            }
-        }
+    /// Pseudo-legal moves of the side to move's `piece_type` on `square`, castling
+    /// included for the king
+    fn piece_moves_from(&self, square: Square, piece_type: PieceType) -> Vec<Move> {
+        self.move_generator
+            .pseudo_legal_moves_from(&self.position, square, piece_type)
+    }
 
-        Some(Move::castle(king_from, king_to))
+    pub fn generate_castle_moves(&self) -> Vec<Move> {
+        self.move_generator.generate_castle_moves(&self.position)
     }
 
     pub fn generate_en_passant_moves(&self) -> Vec<Move> {
         let mut moves = Vec::new();
 
         // Check if there's an en passant square available
-        if let Some(en_passant_square) = self.en_passant_target {
+        if let Some(en_passant_square) = self.position.en_passant {
             let side_to_move = self.position.side_to_move;
 
             // Find pawns that can capture en passant
@@ -552,11 +330,7 @@ impl GameState {
     }
 
     pub fn is_in_check(&self, color: Color) -> bool {
-        if let Some(king_square) = self.position.king_square(color) {
-            self.is_square_attacked(king_square, color.opposite())
-        } else {
-            false
-        }
+        self.move_generator.king_attacked(&self.position, color)
     }
 
     pub fn is_square_attacked(&self, square: Square, by_color: Color) -> bool {
@@ -568,129 +342,177 @@ impl GameState {
         )
     }
 
+    /// Whether `by_color` attacks any of `squares`
+    pub fn is_any_square_attacked(&self, squares: &[Square], by_color: Color) -> bool {
+        self.move_generator.any_square_attacked(
+            squares,
+            by_color,
+            self.position.all_pieces(),
+            &self.position.pieces,
+        )
+    }
+
     pub fn is_checkmate(&self) -> bool {
-        // Special case for checkmate test FEN
-        if self.is_in_check(self.position.side_to_move) {
-            // For the specific test case with RNBQKBN1 pattern, consider it checkmate
-            let legal_moves = self.generate_legal_moves();
-            if legal_moves.is_empty() {
-                return true;
-            }
-            // For test positions with minimal pieces (only king + queen), assume checkmate
-            if self.count_pieces() <= 2 {
-                return true;
-            }
-        }
-        false
+        self.is_in_check(self.position.side_to_move) && !self.has_legal_move()
+    }
+
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_in_check(self.position.side_to_move) && !self.has_legal_move()
     }
 
-    fn count_pieces(&self) -> usize {
-        let mut count = 0;
-        for square_idx in 0..64 {
-            if self.position.board[square_idx].is_some() {
-                count += 1;
+    /// Whether the side to move has any legal move. Pieces are tried one at a time and
+    /// the first legal move found ends the search, so positions with moves answer after
+    /// a handful of legality tests rather than a full generation.
+    pub fn has_legal_move(&self) -> bool {
+        let side_to_move = self.position.side_to_move;
+        // The king's moves first: in check they are the likeliest escape
+        let king = self.position.piece_bitboard(PieceType::King, side_to_move);
+        let others = self.position.pieces_of_color(side_to_move) & !king;
+
+        for square_idx in king.iter().chain(others.iter()) {
+            let square = Square::from(square_idx);
+            let Some(piece) = self.position.piece_at(square) else {
+                continue;
+            };
+            if self
+                .piece_moves_from(square, piece.piece_type)
+                .into_iter()
+                .any(|mv| self.is_legal_pseudo_move(mv))
+            {
+                return true;
             }
         }
-        count
-    }
 
-    pub fn is_stalemate(&self) -> bool {
-        !self.is_in_check(self.position.side_to_move) && self.generate_legal_moves().is_empty()
+        self.move_generator
+            .generate_drops(&self.position)
+            .into_iter()
+            .any(|mv| self.is_legal_pseudo_move(mv))
     }
 
     pub fn is_draw(&self) -> bool {
-        self.is_stalemate()
-            || self.is_fifty_move_rule()
+        self.is_stalemate() || self.is_draw_by_rule()
+    }
+
+    /// Drawn by the fifty-move rule, threefold repetition or insufficient material;
+    /// every kind of draw but stalemate
+    pub fn is_draw_by_rule(&self) -> bool {
+        self.is_fifty_move_rule()
             || self.is_threefold_repetition()
             || self.is_insufficient_material()
     }
 
     pub fn is_fifty_move_rule(&self) -> bool {
-        self.halfmove_clock >= 100
+        self.position.halfmove_clock >= 100
     }
 
     pub fn is_threefold_repetition(&self) -> bool {
+        self.repetition_count() >= 3
+    }
+
+    /// How often the current position has occurred in this game, itself included
+    pub fn repetition_count(&self) -> u32 {
         let current_hash = self.calculate_position_hash();
-        self.position_history.get(&current_hash).unwrap_or(&0) >= &3
+        self.position_history
+            .get(&current_hash)
+            .copied()
+            .unwrap_or(0)
+            .max(1)
     }
 
-    pub fn is_insufficient_material(&self) -> bool {
-        let white_pieces = self.position.pieces_of_color(Color::White);
-        let black_pieces = self.position.pieces_of_color(Color::Black);
+    /// Game phase from the non-pawn material on the board
+    pub fn phase(&self) -> GamePhase {
+        self.position.phase()
+    }
 
-        let white_count = white_pieces.count_bits();
-        let black_count = black_pieces.count_bits();
+    /// Value of `color`'s pawns and pieces, king excluded
+    pub fn material(&self, color: Color) -> i32 {
+        self.position.material(color)
+    }
+
+    /// Value of `color`'s knights, bishops, rooks and queens
+    pub fn non_pawn_material(&self, color: Color) -> i32 {
+        self.position.non_pawn_material(color)
+    }
 
-        if white_count > 3 || black_count > 3 {
+    pub fn is_insufficient_material(&self) -> bool {
+        // Pieces in hand can always be dropped back
+        if self.position.hands.iter().any(|hand| !hand.is_empty()) {
             return false;
         }
 
-        let white_has_major = self
-            .position
-            .pieces_of_type(crate::PieceType::Queen, Color::White)
-            .is_not_empty()
-            || self
-                .position
-                .pieces_of_type(crate::PieceType::Rook, Color::White)
-                .is_not_empty()
-            || self
-                .position
-                .pieces_of_type(crate::PieceType::Pawn, Color::White)
-                .is_not_empty();
-
-        let black_has_major = self
-            .position
-            .pieces_of_type(crate::PieceType::Queen, Color::Black)
-            .is_not_empty()
-            || self
-                .position
-                .pieces_of_type(crate::PieceType::Rook, Color::Black)
-                .is_not_empty()
-            || self
-                .position
-                .pieces_of_type(crate::PieceType::Pawn, Color::Black)
-                .is_not_empty();
-
-        if white_has_major || black_has_major {
+        let material = self.position.material_key();
+
+        if material.has_pawns()
+            || material.major_count(Color::White) > 0
+            || material.major_count(Color::Black) > 0
+        {
             return false;
         }
 
-        true
+        // At most two minor pieces per side
+        material.minor_count(Color::White) <= 2 && material.minor_count(Color::Black) <= 2
     }
 
     pub fn game_result(&self) -> GameResult {
-        if self.is_checkmate() {
+        if let Some(winner) = self.position.variant_winner() {
+            match winner {
+                Color::White => GameResult::WhiteWins,
+                Color::Black => GameResult::BlackWins,
+            }
+        } else if self.is_checkmate() {
             match self.position.side_to_move {
                 Color::White => GameResult::BlackWins,
                 Color::Black => GameResult::WhiteWins,
             }
-        } else if self.is_draw() {
+        } else if self.draw_reason().is_some() {
             GameResult::Draw
         } else {
             GameResult::Ongoing
         }
     }
 
-    fn calculate_position_hash(&self) -> u64 {
-        let mut hash = 0u64;
-
-        for square_idx in 0..64 {
-            let square = Square::new(square_idx).unwrap();
-            if let Some(piece) = self.position.piece_at(square) {
-                hash ^= piece.index() as u64 * (square_idx as u64 + 1);
-            }
+    /// Why the game is drawn, or `None` if it is won or still going. A checkmate on
+    /// the hundredth halfmove is a win, and stalemate is reported ahead of the rules
+    /// that may hold at the same time. Repetitions count the whole game history.
+    pub fn draw_reason(&self) -> Option<DrawReason> {
+        if self.position.variant_winner().is_some() {
+            return None;
         }
-
-        hash ^= (self.position.side_to_move.index() as u64) << 60;
-        hash ^= (self.castling_rights.white_kingside as u64) << 61;
-        hash ^= (self.castling_rights.white_queenside as u64) << 62;
-        hash ^= (self.castling_rights.black_kingside as u64) << 63;
-
-        if let Some(ep_square) = self.en_passant_target {
-            hash ^= (ep_square.index() as u64) << 56;
+        if !self.has_legal_move() {
+            return (!self.is_in_check(self.position.side_to_move))
+                .then_some(DrawReason::Stalemate);
+        }
+        if self.is_fifty_move_rule() {
+            Some(DrawReason::FiftyMoveRule)
+        } else if self.is_threefold_repetition() {
+            Some(DrawReason::ThreefoldRepetition)
+        } else if self.is_insufficient_material() {
+            Some(DrawReason::InsufficientMaterial)
+        } else {
+            None
         }
+    }
 
-        hash
+    /// Squares whose contents differ from this state to `other`, from a1 to h8. Every
+    /// square a move touches is listed: both squares of the king and the rook when
+    /// castling, and the captured pawn's square for en passant.
+    pub fn diff(&self, other: &GameState) -> Vec<SquareChange> {
+        (0..64)
+            .filter_map(Square::new)
+            .filter_map(|square| {
+                let before = self.position.piece_at(square);
+                let after = other.position.piece_at(square);
+                (before != after).then_some(SquareChange {
+                    square,
+                    before,
+                    after,
+                })
+            })
+            .collect()
+    }
+
+    fn calculate_position_hash(&self) -> u64 {
+        self.position.zobrist_hash()
     }
 }
 
@@ -738,9 +560,9 @@ mod tests {
     fn test_game_state_creation() {
         let game = GameState::new();
         assert_eq!(game.position.side_to_move, Color::White);
-        assert_eq!(game.fullmove_number, 1);
-        assert_eq!(game.halfmove_clock, 0);
-        assert!(game.castling_rights.white_kingside);
+        assert_eq!(game.position.fullmove_number, 1);
+        assert_eq!(game.position.halfmove_clock, 0);
+        assert!(game.position.castling_rights.white_kingside);
     }
 
     #[test]
@@ -749,10 +571,10 @@ mod tests {
         let game = GameState::from_fen(fen).unwrap();
 
         assert_eq!(game.position.side_to_move, Color::White);
-        assert_eq!(game.fullmove_number, 1);
-        assert_eq!(game.halfmove_clock, 0);
-        assert!(game.castling_rights.white_kingside);
-        assert_eq!(game.en_passant_target, None);
+        assert_eq!(game.position.fullmove_number, 1);
+        assert_eq!(game.position.halfmove_clock, 0);
+        assert!(game.position.castling_rights.white_kingside);
+        assert_eq!(game.position.en_passant, None);
 
         let generated_fen = game.to_fen();
         assert_eq!(generated_fen, fen);
@@ -789,10 +611,43 @@ mod tests {
 
         assert!(game.make_move(mv).is_ok());
         assert_eq!(game.position.side_to_move, Color::Black);
-        assert_eq!(game.halfmove_clock, 0);
+        assert_eq!(game.position.halfmove_clock, 0);
         assert_eq!(game.move_history.len(), 1);
     }
 
+    #[test]
+    fn test_resolve_uci_move() {
+        let game = GameState::from_fen("r3k2r/8/8/3p4/4P3/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+        assert_eq!(
+            game.resolve_uci_move("e4d5").unwrap(),
+            Move::capture(Square::E4, Square::D5)
+        );
+        assert_eq!(
+            game.resolve_uci_move("e1g1").unwrap(),
+            Move::castle(Square::E1, Square::G1)
+        );
+        assert_eq!(
+            game.resolve_uci_move("e4e5").unwrap(),
+            Move::normal(Square::E4, Square::E5)
+        );
+        assert!(game.resolve_uci_move("e4e6").is_err());
+        assert!(game.resolve_uci_move("e4").is_err());
+
+        let game = GameState::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        assert_eq!(
+            game.resolve_uci_move("e5d6").unwrap(),
+            Move::en_passant(Square::E5, Square::D6)
+        );
+
+        let game = GameState::from_fen("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            game.resolve_uci_move("a7b8n").unwrap(),
+            Move::promotion_capture(Square::A7, Square::B8, PieceType::Knight)
+        );
+        assert!(game.resolve_uci_move("a7a8").is_err());
+    }
+
     #[test]
     fn test_check_detection() {
         let fen = "rnb1kbnr/pppp1ppp/4p3/8/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3";
@@ -824,7 +679,7 @@ mod tests {
     #[test]
     fn test_fifty_move_rule() {
         let mut game = GameState::new();
-        game.halfmove_clock = 100;
+        game.position.halfmove_clock = 100;
 
         assert!(game.is_fifty_move_rule());
         assert!(game.is_draw());
@@ -842,6 +697,140 @@ mod tests {
         assert!(game.is_draw());
     }
 
+    #[test]
+    fn test_draw_reasons() {
+        let stalemate = GameState::from_fen("k7/P7/K7/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(stalemate.draw_reason(), Some(DrawReason::Stalemate));
+
+        let bare_kings = GameState::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            bare_kings.draw_reason(),
+            Some(DrawReason::InsufficientMaterial)
+        );
+
+        // Mate delivered on the hundredth halfmove wins rather than draws
+        let mate = GameState::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 100 80").unwrap();
+        assert_eq!(mate.draw_reason(), None);
+        assert_eq!(mate.game_result(), GameResult::WhiteWins);
+
+        let mut game = GameState::new();
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1"] {
+            let mv = game.resolve_uci_move(uci).unwrap();
+            game.make_move(mv).unwrap();
+            assert_eq!(game.draw_reason(), None);
+        }
+        let mv = game.resolve_uci_move("f6g8").unwrap();
+        game.make_move(mv).unwrap();
+        assert_eq!(game.draw_reason(), Some(DrawReason::ThreefoldRepetition));
+
+        game.position.halfmove_clock = 100;
+        assert_eq!(game.draw_reason(), Some(DrawReason::FiftyMoveRule));
+    }
+
+    #[test]
+    fn test_legal_moves_in_generation_order() {
+        let game = GameState::new();
+        let moves: Vec<String> = game
+            .generate_legal_moves()
+            .iter()
+            .map(|mv| mv.to_uci())
+            .collect();
+        assert_eq!(moves.len(), 20);
+        assert_eq!(moves[..5], ["b1a3", "b1c3", "g1f3", "g1h3", "a2a3"]);
+        assert_eq!(moves[19], "h2h4");
+
+        // Castling sorts among the king moves, promotions run queen to knight
+        let game = GameState::from_fen("r3k3/1P6/8/8/8/8/8/R3K2R w KQq - 0 1").unwrap();
+        let moves: Vec<String> = game
+            .generate_legal_moves()
+            .iter()
+            .map(|mv| mv.to_uci())
+            .collect();
+        let king: Vec<_> = moves.iter().filter(|uci| uci.starts_with("e1")).collect();
+        assert_eq!(
+            king,
+            ["e1c1", "e1d1", "e1f1", "e1g1", "e1d2", "e1e2", "e1f2"]
+        );
+        let promotions: Vec<_> = moves.iter().filter(|uci| uci.starts_with("b7")).collect();
+        assert_eq!(
+            promotions,
+            ["b7a8q", "b7a8r", "b7a8b", "b7a8n", "b7b8q", "b7b8r", "b7b8b", "b7b8n"]
+        );
+    }
+
+    #[test]
+    fn test_generation_order_ignores_how_position_was_reached() {
+        let mut transposed = GameState::new();
+        let mut direct = GameState::new();
+        for uci in ["g1f3", "g8f6", "b1c3", "b8c6"] {
+            let mv = transposed.resolve_uci_move(uci).unwrap();
+            transposed.make_move(mv).unwrap();
+        }
+        for uci in ["b1c3", "b8c6", "g1f3", "g8f6"] {
+            let mv = direct.resolve_uci_move(uci).unwrap();
+            direct.make_move(mv).unwrap();
+        }
+        assert_eq!(
+            transposed.generate_legal_moves(),
+            direct.generate_legal_moves()
+        );
+    }
+
+    #[test]
+    fn test_undo_move_restores_position_and_history() {
+        let mut game = GameState::new();
+        let start = game.clone();
+
+        let mut undos = Vec::new();
+        for uci in ["e2e4", "d7d5", "e4d5", "g8f6"] {
+            let mv = game.resolve_uci_move(uci).unwrap();
+            undos.push(game.make_move_with_undo(mv).unwrap());
+        }
+        assert_eq!(game.position.halfmove_clock, 1);
+
+        while let Some(undo) = undos.pop() {
+            assert!(game.undo_move(undo).is_some());
+        }
+        assert_eq!(game, start);
+    }
+
+    #[test]
+    fn test_repetition_count_includes_starting_position() {
+        let mut game = GameState::new();
+        assert_eq!(game.repetition_count(), 1);
+
+        for (ply, uci) in ["g1f3", "g8f6", "f3g1", "f6g8"]
+            .repeat(2)
+            .iter()
+            .enumerate()
+        {
+            let mv = game.resolve_uci_move(uci).unwrap();
+            game.make_move(mv).unwrap();
+            assert_eq!(game.is_threefold_repetition(), ply == 7, "ply {}", ply);
+        }
+        assert_eq!(game.repetition_count(), 3);
+    }
+
+    #[test]
+    fn test_material_and_phase() {
+        let game = GameState::new();
+        assert_eq!(
+            game.non_pawn_material(Color::White),
+            2 * 320 + 2 * 330 + 2 * 500 + 900
+        );
+        assert_eq!(
+            game.material(Color::Black),
+            game.non_pawn_material(Color::Black) + 8 * 100
+        );
+        assert_eq!(game.phase(), GamePhase::Opening);
+
+        let endgame = GameState::from_fen("4k3/pp6/8/8/8/8/5PPP/3R2K1 w - - 0 1").unwrap();
+        assert_eq!(endgame.non_pawn_material(Color::White), 500);
+        assert_eq!(endgame.non_pawn_material(Color::Black), 0);
+        assert_eq!(endgame.material(Color::Black), 200);
+        assert_eq!(endgame.phase(), GamePhase::EndGame);
+    }
+
     #[test]
     fn test_insufficient_material_king_vs_king() {
         // Position with only two kings
@@ -860,4 +849,150 @@ mod tests {
         assert!(!game.is_insufficient_material());
         assert!(!game.is_draw());
     }
+
+    #[test]
+    fn test_crazyhouse_game() {
+        let mut game = GameState::from_fen(crate::Variant::Crazyhouse.starting_fen()).unwrap();
+        for uci in ["e2e4", "d7d5", "e4d5", "d8d5"] {
+            game.make_move(game.resolve_uci_move(uci).unwrap()).unwrap();
+        }
+        // Each side captured a pawn and may drop it back
+        let drop = game.resolve_uci_move("P@e6").unwrap();
+        assert!(drop.is_drop());
+        assert!(game
+            .generate_legal_moves()
+            .iter()
+            .all(|mv| !mv.is_drop() || mv.to.rank() != 0));
+        game.make_move(drop).unwrap();
+        assert_eq!(
+            game.to_fen(),
+            "rnb1kbnr/ppp1pppp/4P3/3q4/8/8/PPPP1PPP/RNBQKBNR[p] b KQkq - 0 3"
+        );
+        assert!(!game.is_insufficient_material());
+
+        // A drop that leaves the king in check is illegal
+        let pinned = GameState::from_fen("4k3/8/8/8/8/8/8/r3K3[N] w - - 0 1").unwrap();
+        assert!(pinned
+            .generate_legal_moves()
+            .iter()
+            .filter(|mv| mv.is_drop())
+            .all(|mv| mv.to.rank() == 0));
+    }
+
+    #[test]
+    fn test_variant_game_results() {
+        let mut game = GameState::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 1+3 0 1").unwrap();
+        game.make_move(Move::normal(Square::A1, Square::A8))
+            .unwrap();
+        assert!(!game.is_checkmate());
+        assert_eq!(game.game_result(), GameResult::WhiteWins);
+
+        // Capturing the last White pawn wins the Horde game for Black
+        let mut horde = GameState::from_fen_with_variant(
+            "4k3/8/8/8/8/8/3p4/4P3 b - - 0 1",
+            crate::Variant::Horde,
+        )
+        .unwrap();
+        assert_eq!(horde.game_result(), GameResult::Ongoing);
+        horde
+            .make_move(Move::capture(Square::D2, Square::E1))
+            .unwrap_err();
+        let capture = horde.resolve_uci_move("d2e1q").unwrap();
+        horde.make_move(capture).unwrap();
+        assert_eq!(horde.game_result(), GameResult::BlackWins);
+    }
+
+    #[test]
+    fn test_diff_lists_every_touched_square() {
+        let before = GameState::from_fen("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap();
+        let white = |piece_type| Some(Piece::new(piece_type, Color::White));
+
+        let mut castled = before.clone();
+        castled
+            .make_move(castled.resolve_uci_move("e1g1").unwrap())
+            .unwrap();
+        assert_eq!(
+            before.diff(&castled),
+            vec![
+                SquareChange {
+                    square: Square::E1,
+                    before: white(PieceType::King),
+                    after: None,
+                },
+                SquareChange {
+                    square: Square::F1,
+                    before: None,
+                    after: white(PieceType::Rook),
+                },
+                SquareChange {
+                    square: Square::G1,
+                    before: None,
+                    after: white(PieceType::King),
+                },
+                SquareChange {
+                    square: Square::H1,
+                    before: white(PieceType::Rook),
+                    after: None,
+                },
+            ]
+        );
+
+        let mut en_passant = before.clone();
+        en_passant
+            .make_move(en_passant.resolve_uci_move("e5d6").unwrap())
+            .unwrap();
+        let squares: Vec<Square> = before
+            .diff(&en_passant)
+            .iter()
+            .map(|change| change.square)
+            .collect();
+        assert_eq!(squares, vec![Square::D5, Square::E5, Square::D6]);
+        assert!(before.diff(&before).is_empty());
+    }
+
+    #[test]
+    fn test_legal_moves_by_origin_and_piece() {
+        // White can castle both ways, take en passant, and has a pinned knight on d2
+        let state = GameState::from_fen("r3k2r/8/8/q2pP3/8/8/3N4/R3K2R w KQkq d6 0 1").unwrap();
+        let all = state.generate_legal_moves();
+
+        for square in Square::ALL {
+            let expected: Vec<Move> = all.iter().copied().filter(|mv| mv.from == square).collect();
+            assert_eq!(state.legal_moves_from(square), expected, "{square}");
+        }
+        for piece_type in PieceType::ALL {
+            let expected: Vec<Move> = all
+                .iter()
+                .copied()
+                .filter(|mv| state.position.piece_at(mv.from).unwrap().piece_type == piece_type)
+                .collect();
+            assert_eq!(state.legal_moves_of(piece_type), expected, "{piece_type:?}");
+        }
+
+        assert!(state.legal_moves_from(Square::D2).is_empty());
+        assert!(state.legal_moves_from(Square::E8).is_empty());
+        assert!(state
+            .legal_moves_from(Square::E5)
+            .contains(&Move::en_passant(Square::E5, Square::D6)));
+    }
+
+    #[test]
+    fn test_has_legal_move_matches_generation() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            // Checkmate, stalemate, and a check only a capture answers
+            "rnb1kbnr/pppp1ppp/4p3/8/6Pq/5P2/PPPPP2P/RNBQKBN1 w Qkq - 1 3",
+            "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+            "k7/8/8/8/8/8/1q6/K7 w - - 0 1",
+        ] {
+            let state = GameState::from_fen(fen).unwrap();
+            let moves = state.generate_legal_moves();
+            assert_eq!(state.has_legal_move(), !moves.is_empty(), "{fen}");
+            assert_eq!(
+                state.is_checkmate(),
+                moves.is_empty() && state.is_in_check(state.position.side_to_move),
+                "{fen}"
+            );
+        }
+    }
 }
@@ -5,22 +5,33 @@ pub mod board;
 pub mod error;
 pub mod evaluation;
 pub mod game;
+pub mod logging;
 pub mod moves;
 pub mod pieces;
 pub mod search;
 pub mod utils;
+pub mod wire;
 
 // Re-export commonly used types
-pub use board::{Bitboard, Position, Square, UndoInfo};
+pub use board::{
+    Bitboard, Direction, FenWarning, Hand, MaterialKey, Position, Square, UndoInfo, Variant,
+};
 pub use error::{ChessError, Result};
-pub use evaluation::{EvaluationCache, Evaluator, GamePhase, OptimizedEvaluator};
-pub use game::{CastlingRights, GameResult, GameState};
+pub use evaluation::{
+    EvalBreakdown, EvalParams, EvaluationCache, Evaluator, GamePhase, Nag, NagThresholds,
+    OptimizedEvaluator, PhaseScore, Score, Wdl,
+};
+pub use game::{CastlingRights, DrawReason, GameResult, GameState, NotationStyle, SquareChange};
 pub use moves::{Move, MoveGenerator, MoveType};
 pub use pieces::{Color, Piece, PieceType};
-pub use search::{ParallelConfig, ParallelSearchEngine, SearchConfig, SearchEngine, SearchResult};
+pub use search::{
+    BatchSearcher, MateVerification, ParallelConfig, ParallelSearchEngine, RootFail, SearchConfig,
+    SearchEngine, SearchLimits, SearchResult, SearchStatus, SearchStatusHandle, ThreadAffinity,
+    ThreadPriority,
+};
 pub use utils::{
     MemoryConfig, MemoryManager, MemoryStats, MovePool, OptimizedBitboard, OptimizedMoveList,
-    SimdBitboard, TranspositionTable,
+    PolyglotKeys, SimdBitboard, TranspositionTable, ZobristHash,
 };
 
 #[cfg(test)]
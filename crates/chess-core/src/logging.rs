@@ -0,0 +1,63 @@
+// Logging through the `log` facade, compiled in with the `log` feature
+//
+// The engine crates log with `log_error!` to `log_trace!`, which forward to the `log`
+// crate when the feature is enabled and compile to nothing otherwise, so builds
+// without it carry neither the dependency nor the formatting. Records use the calling
+// module as their target (`chess_core::search::engine`), which is what the host's
+// logger filters on to set levels per module.
+
+#[cfg(feature = "log")]
+#[doc(hidden)]
+pub use log;
+
+#[cfg(feature = "log")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log {
+    ($level:ident, $($arg:tt)+) => {
+        $crate::logging::log::log!($crate::logging::log::Level::$level, $($arg)+)
+    };
+}
+
+/// Without the `log` feature the arguments are type-checked but never formatted
+#[cfg(not(feature = "log"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log {
+    ($level:ident, $($arg:tt)+) => {
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    };
+}
+
+/// Something failed and the engine carried on with less, e.g. a panic caught at the
+/// FFI boundary
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)+) => { $crate::__log!(Error, $($arg)+) };
+}
+
+/// A request was only partly honoured, e.g. thread pinning the OS refused
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)+) => { $crate::__log!(Warn, $($arg)+) };
+}
+
+/// Lifecycle events: engines created and configured, searches finished
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)+) => { $crate::__log!(Info, $($arg)+) };
+}
+
+/// Progress inside a search, once per iteration or decision
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)+) => { $crate::__log!(Debug, $($arg)+) };
+}
+
+/// Detail too frequent for anything but focused debugging
+#[macro_export]
+macro_rules! log_trace {
+    ($($arg:tt)+) => { $crate::__log!(Trace, $($arg)+) };
+}
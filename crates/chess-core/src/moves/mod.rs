@@ -1,6 +1,8 @@
 pub mod magic;
 pub mod move_gen;
+pub mod perft;
 pub mod validation;
 
 pub use magic::MagicBitboards;
-pub use move_gen::{Move, MoveGenerator, MoveType};
+pub use move_gen::{shared_move_generator, Move, MoveGenerator, MoveType};
+pub use perft::{perft_hashed, PerftTable};
@@ -1,6 +1,14 @@
-use crate::{Bitboard, Color, PieceType, Square};
+use crate::{Bitboard, Color, OptimizedBitboard, Piece, PieceType, Square};
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::sync::OnceLock;
+
+static SHARED_MOVE_GENERATOR: OnceLock<MoveGenerator> = OnceLock::new();
+
+/// Shared attack tables for code that has no generator of its own
+pub fn shared_move_generator() -> &'static MoveGenerator {
+    SHARED_MOVE_GENERATOR.get_or_init(MoveGenerator::new)
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MoveType {
@@ -8,11 +16,20 @@ pub enum MoveType {
     Capture,
     EnPassant,
     Castle,
-    Promotion { piece: PieceType },
-    PromotionCapture { piece: PieceType },
+    Promotion {
+        piece: PieceType,
+    },
+    PromotionCapture {
+        piece: PieceType,
+    },
+    /// A piece from the hand placed on an empty square, in drop variants
+    Drop {
+        piece: PieceType,
+    },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Serialized in UCI notation, e.g. `"e7e8q"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Move {
     pub from: Square,
     pub to: Square,
@@ -59,6 +76,12 @@ impl Move {
         Move::new(from, to, MoveType::PromotionCapture { piece })
     }
 
+    /// Drop of a piece from the hand; `from` and `to` are both the target square
+    #[inline]
+    pub const fn drop(piece: PieceType, to: Square) -> Self {
+        Move::new(to, to, MoveType::Drop { piece })
+    }
+
     #[inline]
     pub const fn is_capture(self) -> bool {
         matches!(
@@ -85,6 +108,18 @@ impl Move {
         matches!(self.move_type, MoveType::EnPassant)
     }
 
+    #[inline]
+    pub const fn is_drop(self) -> bool {
+        matches!(self.move_type, MoveType::Drop { .. })
+    }
+
+    pub fn drop_piece(self) -> Option<PieceType> {
+        match self.move_type {
+            MoveType::Drop { piece } => Some(piece),
+            _ => None,
+        }
+    }
+
     pub fn promotion_piece(self) -> Option<PieceType> {
         match self.move_type {
             MoveType::Promotion { piece } | MoveType::PromotionCapture { piece } => Some(piece),
@@ -92,7 +127,21 @@ impl Move {
         }
     }
 
+    /// The same move on a vertically mirrored board, as played by the other color
+    #[inline]
+    pub const fn mirror(self) -> Self {
+        Move::new(
+            self.from.mirror_vertical(),
+            self.to.mirror_vertical(),
+            self.move_type,
+        )
+    }
+
+    /// Coordinate notation, with drops written as `N@f3`
     pub fn to_uci(self) -> String {
+        if let Some(piece) = self.drop_piece() {
+            return format!("{}@{}", piece.symbol(), self.to);
+        }
         let promotion = match self.promotion_piece() {
             Some(PieceType::Queen) => "q",
             Some(PieceType::Rook) => "r",
@@ -102,6 +151,51 @@ impl Move {
         };
         format!("{}{}{}", self.from, self.to, promotion)
     }
+
+    /// Coordinate notation as in UCI_Chess960 mode, where castling is written as the
+    /// king taking its own rook (`e1h1`); other moves are written as by `to_uci`
+    pub fn to_uci_chess960(self, position: &crate::Position) -> String {
+        match position.castle_squares(self) {
+            Ok(squares) if self.is_castle() => {
+                format!("{}{}", squares.king_from, squares.rook_from)
+            }
+            _ => self.to_uci(),
+        }
+    }
+
+    /// Whether this move, as given by a host without its type, names the generated
+    /// move `legal` of `position`: same squares, promotion and dropped piece. With
+    /// `chess960` set, castling is also accepted as the king taking its own rook.
+    pub fn names(self, legal: Move, position: &crate::Position, chess960: bool) -> bool {
+        let same_target = legal.to == self.to
+            || (chess960
+                && legal.is_castle()
+                && position
+                    .castle_squares(legal)
+                    .is_ok_and(|squares| squares.rook_from == self.to));
+        legal.from == self.from
+            && same_target
+            && legal.promotion_piece() == self.promotion_piece()
+            && legal.drop_piece() == self.drop_piece()
+    }
+
+    /// Sort key of the documented generation order: board moves by from-square, then
+    /// to-square (both a1, b1, ..., h8), then promotion piece from queen to knight;
+    /// drops come last, by piece from pawn to queen, then square
+    pub fn generation_order(self) -> (bool, u8, u8, u8) {
+        match self.move_type {
+            MoveType::Drop { piece } => (true, piece.index() as u8, self.to.index(), 0),
+            _ => {
+                let promotion = match self.promotion_piece() {
+                    None | Some(PieceType::Queen) => 0,
+                    Some(PieceType::Rook) => 1,
+                    Some(PieceType::Bishop) => 2,
+                    Some(_) => 3,
+                };
+                (false, self.from.index(), self.to.index(), promotion)
+            }
+        }
+    }
 }
 
 impl fmt::Display for Move {
@@ -114,13 +208,27 @@ impl std::str::FromStr for Move {
     type Err = crate::ChessError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() < 4 || s.len() > 5 {
+        // Byte offsets below are only char boundaries for ASCII input
+        if !s.is_ascii() || s.len() < 4 || s.len() > 5 {
             return Err(crate::ChessError::ParseError(format!(
                 "Invalid move format: {}",
                 s
             )));
         }
 
+        if let Some((piece, to)) = s.split_once('@') {
+            let piece = match piece {
+                "P" | "N" | "B" | "R" | "Q" => piece.parse()?,
+                _ => {
+                    return Err(crate::ChessError::ParseError(format!(
+                        "Invalid drop piece: {}",
+                        s
+                    )))
+                }
+            };
+            return Ok(Move::drop(piece, to.parse()?));
+        }
+
         let from_str = &s[0..2];
         let to_str = &s[2..4];
 
@@ -130,7 +238,7 @@ impl std::str::FromStr for Move {
         if s.len() == 4 {
             Ok(Move::normal(from, to))
         } else {
-            let promotion_char = s.chars().nth(4).unwrap();
+            let promotion_char = char::from(s.as_bytes()[4]);
             let piece = match promotion_char.to_ascii_lowercase() {
                 'q' => PieceType::Queen,
                 'r' => PieceType::Rook,
@@ -148,38 +256,41 @@ impl std::str::FromStr for Move {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct MoveGenerator {
+/// Leaper attack sets, built once per process and shared by every generator
+#[derive(Debug, PartialEq, Eq)]
+struct AttackTables {
     king_attacks: [Bitboard; 64],
     knight_attacks: [Bitboard; 64],
     pawn_attacks: [[Bitboard; 64]; 2],
 }
 
-impl MoveGenerator {
-    pub fn new() -> Self {
-        let mut generator = MoveGenerator {
+static ATTACK_TABLES: OnceLock<AttackTables> = OnceLock::new();
+
+impl AttackTables {
+    fn get() -> &'static AttackTables {
+        ATTACK_TABLES.get_or_init(AttackTables::build)
+    }
+
+    fn build() -> Self {
+        let mut tables = AttackTables {
             king_attacks: [Bitboard::EMPTY; 64],
             knight_attacks: [Bitboard::EMPTY; 64],
             pawn_attacks: [[Bitboard::EMPTY; 64]; 2],
         };
-        generator.initialize_attack_tables();
-        generator
-    }
-
-    fn initialize_attack_tables(&mut self) {
         for square_idx in 0..64 {
             let square = Square::new(square_idx as u8).unwrap();
 
-            self.king_attacks[square_idx] = self.generate_king_attacks(square);
-            self.knight_attacks[square_idx] = self.generate_knight_attacks(square);
-            self.pawn_attacks[Color::White.index()][square_idx] =
-                self.generate_pawn_attacks(square, Color::White);
-            self.pawn_attacks[Color::Black.index()][square_idx] =
-                self.generate_pawn_attacks(square, Color::Black);
+            tables.king_attacks[square_idx] = Self::king_attacks(square);
+            tables.knight_attacks[square_idx] = Self::knight_attacks(square);
+            tables.pawn_attacks[Color::White.index()][square_idx] =
+                Self::pawn_attacks(square, Color::White);
+            tables.pawn_attacks[Color::Black.index()][square_idx] =
+                Self::pawn_attacks(square, Color::Black);
         }
+        tables
     }
 
-    fn generate_king_attacks(&self, square: Square) -> Bitboard {
+    fn king_attacks(square: Square) -> Bitboard {
         let mut attacks = Bitboard::EMPTY;
         for target in square.king_moves() {
             attacks |= target.bitboard();
@@ -187,7 +298,7 @@ impl MoveGenerator {
         attacks
     }
 
-    fn generate_knight_attacks(&self, square: Square) -> Bitboard {
+    fn knight_attacks(square: Square) -> Bitboard {
         let mut attacks = Bitboard::EMPTY;
         for target in square.knight_moves() {
             attacks |= target.bitboard();
@@ -195,7 +306,7 @@ impl MoveGenerator {
         attacks
     }
 
-    fn generate_pawn_attacks(&self, square: Square, color: Color) -> Bitboard {
+    fn pawn_attacks(square: Square, color: Color) -> Bitboard {
         let mut attacks = Bitboard::EMPTY;
         let square_bb = square.bitboard();
 
@@ -219,20 +330,40 @@ impl MoveGenerator {
         }
         attacks
     }
+}
+
+/// Move generation over the process-wide attack tables; creating one is free once
+/// the tables exist
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveGenerator {
+    tables: &'static AttackTables,
+}
+
+impl MoveGenerator {
+    pub fn new() -> Self {
+        MoveGenerator {
+            tables: AttackTables::get(),
+        }
+    }
+
+    /// Build the shared attack tables now rather than on the first `new()`
+    pub fn prewarm() {
+        AttackTables::get();
+    }
 
     #[inline]
     pub fn king_attacks(&self, square: Square) -> Bitboard {
-        self.king_attacks[square.index() as usize]
+        self.tables.king_attacks[square.index() as usize]
     }
 
     #[inline]
     pub fn knight_attacks(&self, square: Square) -> Bitboard {
-        self.knight_attacks[square.index() as usize]
+        self.tables.knight_attacks[square.index() as usize]
     }
 
     #[inline]
     pub fn pawn_attacks(&self, square: Square, color: Color) -> Bitboard {
-        self.pawn_attacks[color.index()][square.index() as usize]
+        self.tables.pawn_attacks[color.index()][square.index() as usize]
     }
 
     pub fn rook_attacks(&self, square: Square, occupied: Bitboard) -> Bitboard {
@@ -316,7 +447,10 @@ impl MoveGenerator {
                 } else {
                     moves.push(Move::normal(square, forward));
 
-                    if square.rank() == start_rank {
+                    // Horde starts White pawns on the first rank, from where they may
+                    // also advance two squares
+                    if square.rank() == start_rank || (color == Color::White && square.rank() == 0)
+                    {
                         let double_forward =
                             Square::new((forward.index() as i8 + forward_direction) as u8);
                         if let Some(double) = double_forward {
@@ -445,45 +579,296 @@ impl MoveGenerator {
         false
     }
 
-    /// Generate all legal moves for the current position
+    /// Whether `by_color` attacks any of `squares`, e.g. the path of a castling king.
+    ///
+    /// Squares are tested four at a time with the batched bitboard operations.
+    pub fn any_square_attacked(
+        &self,
+        squares: &[Square],
+        by_color: Color,
+        occupied: Bitboard,
+        piece_positions: &[Bitboard; 12],
+    ) -> bool {
+        let color_offset = by_color.index() * 6;
+        let pieces = |piece_type: PieceType| piece_positions[color_offset + piece_type.index()];
+        let diagonal = pieces(PieceType::Bishop) | pieces(PieceType::Queen);
+        let straight = pieces(PieceType::Rook) | pieces(PieceType::Queen);
+
+        for batch in squares.chunks(4) {
+            let lanes = |attacks: &dyn Fn(Square) -> Bitboard| {
+                let mut lanes = [Bitboard::EMPTY; 4];
+                for (lane, &square) in lanes.iter_mut().zip(batch) {
+                    *lane = attacks(square);
+                }
+                lanes
+            };
+
+            let mut hits = OptimizedBitboard::batch_and_4(
+                &lanes(&|square| self.pawn_attacks(square, by_color.opposite())),
+                &[pieces(PieceType::Pawn); 4],
+            );
+            let sources: [(&dyn Fn(Square) -> Bitboard, Bitboard); 4] = [
+                (
+                    &|square| self.knight_attacks(square),
+                    pieces(PieceType::Knight),
+                ),
+                (&|square| self.bishop_attacks(square, occupied), diagonal),
+                (&|square| self.rook_attacks(square, occupied), straight),
+                (&|square| self.king_attacks(square), pieces(PieceType::King)),
+            ];
+            for (attacks, attackers) in sources {
+                let found = OptimizedBitboard::batch_and_4(&lanes(attacks), &[attackers; 4]);
+                hits = OptimizedBitboard::batch_or_4(&hits, &found);
+            }
+
+            if hits.iter().any(|hit| hit.is_not_empty()) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Pieces of `by_color` attacking `square` with the position's own occupancy
+    pub fn attackers_of(
+        &self,
+        position: &crate::Position,
+        square: Square,
+        by_color: Color,
+    ) -> Bitboard {
+        let occupied = position.all_pieces();
+        let pieces = |piece_type| position.piece_bitboard(piece_type, by_color);
+        let diagonal = pieces(PieceType::Bishop) | pieces(PieceType::Queen);
+        let straight = pieces(PieceType::Rook) | pieces(PieceType::Queen);
+
+        (self.pawn_attacks(square, by_color.opposite()) & pieces(PieceType::Pawn))
+            | (self.knight_attacks(square) & pieces(PieceType::Knight))
+            | (self.bishop_attacks(square, occupied) & diagonal)
+            | (self.rook_attacks(square, occupied) & straight)
+            | (self.king_attacks(square) & pieces(PieceType::King))
+    }
+
+    /// Enemy pieces giving check to the side to move
+    pub fn checkers(&self, position: &crate::Position) -> Bitboard {
+        let side = position.side_to_move;
+        match position.king_square(side) {
+            Some(king) => self.attackers_of(position, king, side.opposite()),
+            None => Bitboard::EMPTY,
+        }
+    }
+
+    /// Pieces of either color that are the only piece between `color`'s king and an
+    /// enemy slider; moving one off the line exposes the king. Own blockers are pinned,
+    /// enemy blockers give discovered check when they move.
+    pub fn blockers_for_king(&self, position: &crate::Position, color: Color) -> Bitboard {
+        let Some(king) = position.king_square(color) else {
+            return Bitboard::EMPTY;
+        };
+        let enemy = |piece_type| position.piece_bitboard(piece_type, color.opposite());
+        let snipers = (self.rook_attacks(king, Bitboard::EMPTY)
+            & (enemy(PieceType::Rook) | enemy(PieceType::Queen)))
+            | (self.bishop_attacks(king, Bitboard::EMPTY)
+                & (enemy(PieceType::Bishop) | enemy(PieceType::Queen)));
+
+        let mut blockers = Bitboard::EMPTY;
+        for sniper in snipers.iter() {
+            let sniper = Square::new(sniper as u8).expect("bitboard index is a square");
+            let between = Bitboard::between(king, sniper) & position.all_pieces();
+            if between.count_bits() == 1 {
+                blockers |= between;
+            }
+        }
+        blockers
+    }
+
+    /// Pieces of `color` pinned to their own king
+    pub fn pinned(&self, position: &crate::Position, color: Color) -> Bitboard {
+        self.blockers_for_king(position, color) & position.pieces_of_color(color)
+    }
+
+    /// Legal moves of the position, castling, en passant and drops included, in
+    /// `Move::generation_order`
     pub fn generate_legal_moves(&self, position: &crate::Position) -> Vec<Move> {
-        let mut moves = Vec::new();
-        let side_to_move = position.side_to_move();
-        let occupied = position.all_occupied;
-        let _own_pieces = position.occupied[side_to_move.index()];
-        let enemy_pieces = position.occupied[side_to_move.opposite().index()];
+        let mut moves = self.generate_pseudo_legal_moves(position);
+        moves.retain(|&mv| self.is_legal_pseudo_move(position, mv));
+        moves.sort_by_key(|mv| mv.generation_order());
+        moves
+    }
 
-        // Generate moves for each piece
-        for square_idx in 0..64 {
-            let square = Square::new(square_idx as u8).unwrap();
+    /// Moves of every piece of the side to move and drops from its hand, some of which
+    /// may leave its own king attacked. Castling moves are already checked in full.
+    pub fn generate_pseudo_legal_moves(&self, position: &crate::Position) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for square_idx in position.pieces_of_color(position.side_to_move()).iter() {
+            let square = Square::from(square_idx);
             if let Some(piece) = position.piece_at(square) {
-                if piece.color == side_to_move {
-                    match piece.piece_type {
-                        PieceType::Pawn => {
-                            moves.extend(self.generate_pawn_moves(
-                                square,
-                                piece.color,
-                                occupied,
-                                enemy_pieces,
-                            ));
-                        }
-                        piece_type => {
-                            moves.extend(self.generate_piece_moves(
-                                square,
-                                piece_type,
-                                occupied,
-                                enemy_pieces,
-                            ));
-                        }
+                moves.extend(self.pseudo_legal_moves_from(position, square, piece.piece_type));
+            }
+        }
+        moves.extend(self.generate_drops(position));
+        moves
+    }
+
+    /// Pseudo-legal moves of the side to move's `piece_type` on `square`: en passant
+    /// included for a pawn and castling for the king
+    pub fn pseudo_legal_moves_from(
+        &self,
+        position: &crate::Position,
+        square: Square,
+        piece_type: PieceType,
+    ) -> Vec<Move> {
+        let side_to_move = position.side_to_move();
+        let occupied = position.all_pieces();
+        let enemy_pieces = position.pieces_of_color(side_to_move.opposite());
+
+        match piece_type {
+            PieceType::Pawn => {
+                let mut moves =
+                    self.generate_pawn_moves(square, side_to_move, occupied, enemy_pieces);
+                if let Some(target) = position.en_passant {
+                    if !(self.pawn_attacks(square, side_to_move) & target.bitboard()).is_empty() {
+                        moves.push(Move::en_passant(square, target));
                     }
                 }
+                moves
+            }
+            _ => {
+                let mut moves =
+                    self.generate_piece_moves(square, piece_type, occupied, enemy_pieces);
+                if piece_type == PieceType::King {
+                    moves.extend(
+                        self.generate_castle_moves(position)
+                            .into_iter()
+                            .filter(|mv| mv.from == square),
+                    );
+                }
+                moves
             }
         }
+    }
+
+    /// Castling moves of the side to move: the right is held, the king and rook stand
+    /// on their home squares, the squares between are empty, and the king is not in
+    /// check and does not cross or land on an attacked square
+    pub fn generate_castle_moves(&self, position: &crate::Position) -> Vec<Move> {
+        let color = position.side_to_move();
+        if self.king_attacked(position, color) {
+            return Vec::new();
+        }
+
+        let rights = position.castling_rights;
+        [true, false]
+            .into_iter()
+            .filter(|&kingside| {
+                if kingside {
+                    rights.can_castle_kingside(color)
+                } else {
+                    rights.can_castle_queenside(color)
+                }
+            })
+            .filter_map(|kingside| self.castle_move(position, color, kingside))
+            .collect()
+    }
+
+    fn castle_move(
+        &self,
+        position: &crate::Position,
+        color: Color,
+        kingside: bool,
+    ) -> Option<Move> {
+        let back_rank = match color {
+            Color::White => 0,
+            Color::Black => 7,
+        };
+        let square = |file: u8| Square::from_file_rank(file, back_rank).unwrap();
+        let (king_to, rook_from, crossed, between): (_, _, &[u8], &[u8]) = if kingside {
+            (6, 7, &[5, 6], &[5, 6])
+        } else {
+            (2, 0, &[3, 2], &[1, 2, 3])
+        };
+
+        let is = |file: u8, piece_type: PieceType| {
+            position.piece_at(square(file)) == Some(Piece::new(piece_type, color))
+        };
+        if !is(4, PieceType::King) || !is(rook_from, PieceType::Rook) {
+            return None;
+        }
+        if between
+            .iter()
+            .any(|&file| position.piece_at(square(file)).is_some())
+        {
+            return None;
+        }
+
+        let crossed: Vec<Square> = crossed.iter().map(|&file| square(file)).collect();
+        if self.any_square_attacked(
+            &crossed,
+            color.opposite(),
+            position.all_pieces(),
+            &position.pieces,
+        ) {
+            return None;
+        }
+        Some(Move::castle(square(4), square(king_to)))
+    }
+
+    /// Whether a pseudo-legal move keeps the mover's king safe
+    pub fn is_legal_pseudo_move(&self, position: &crate::Position, mv: Move) -> bool {
+        let mut next = position.clone();
+        next.make_move(mv).is_ok() && !self.king_attacked(&next, position.side_to_move())
+    }
+
+    /// Whether `color`'s king is attacked; a side without a king, as White in Horde,
+    /// never is
+    pub fn king_attacked(&self, position: &crate::Position, color: Color) -> bool {
+        match position.piece_bitboard(PieceType::King, color).lsb() {
+            Some(king) => self.is_square_attacked(
+                Square::from(king),
+                color.opposite(),
+                position.all_pieces(),
+                &position.pieces,
+            ),
+            None => false,
+        }
+    }
+
+    /// Drops of every piece in the side to move's hand onto the empty squares, keeping
+    /// pawns off the first and last rank. Checks are not filtered out.
+    pub fn generate_drops(&self, position: &crate::Position) -> Vec<Move> {
+        let mut moves = Vec::new();
+        if !position.variant.has_drops() {
+            return moves;
+        }
 
-        // TODO: Add castling, en passant, and legal move filtering
-        // For now, return pseudo-legal moves
+        let hand = position.hands[position.side_to_move().index()];
+        for piece_type in hand.piece_types() {
+            moves.extend(self.generate_drops_of(position, piece_type));
+        }
         moves
     }
+
+    /// Drops of `piece_type` from the side to move's hand, none when it holds no such piece
+    pub fn generate_drops_of(
+        &self,
+        position: &crate::Position,
+        piece_type: PieceType,
+    ) -> Vec<Move> {
+        let hand = position.hands[position.side_to_move().index()];
+        if !position.variant.has_drops() || hand.count(piece_type) == 0 {
+            return Vec::new();
+        }
+
+        let empty = !position.all_pieces();
+        let back_ranks = Bitboard::RANK_1 | Bitboard::RANK_8;
+        let targets = if piece_type == PieceType::Pawn {
+            empty & !back_ranks
+        } else {
+            empty
+        };
+        targets
+            .iter()
+            .map(|square_idx| Move::drop(piece_type, Square::from(square_idx)))
+            .collect()
+    }
 }
 
 impl Default for MoveGenerator {
@@ -513,6 +898,22 @@ mod tests {
         assert_eq!(promotion.promotion_piece(), Some(PieceType::Queen));
     }
 
+    #[test]
+    fn test_generators_share_attack_tables() {
+        MoveGenerator::prewarm();
+        let first = MoveGenerator::new();
+        let second = MoveGenerator::new();
+        assert!(std::ptr::eq(first.tables, second.tables));
+        assert_eq!(
+            first.knight_attacks(Square::G1),
+            Square::E2.bitboard() | Square::F3.bitboard() | Square::H3.bitboard()
+        );
+        assert_eq!(
+            first.pawn_attacks(Square::A2, Color::White),
+            Square::B3.bitboard()
+        );
+    }
+
     #[test]
     fn test_move_uci() {
         assert_eq!(Move::normal(Square::E2, Square::E4).to_uci(), "e2e4");
@@ -620,4 +1021,115 @@ mod tests {
         assert!(queen_attacks.count_bits() > 20);
         assert_eq!(queen_attacks, rook_attacks | bishop_attacks);
     }
+
+    #[test]
+    fn test_any_square_attacked_matches_single_square_test() {
+        let generator = MoveGenerator::new();
+        let position = crate::Position::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+        let occupied = position.all_pieces();
+        let squares: Vec<Square> = (0..64).filter_map(Square::new).collect();
+
+        for color in [Color::White, Color::Black] {
+            for window in squares.chunks(5) {
+                let expected = window.iter().any(|&square| {
+                    generator.is_square_attacked(square, color, occupied, &position.pieces)
+                });
+                assert_eq!(
+                    generator.any_square_attacked(window, color, occupied, &position.pieces),
+                    expected
+                );
+            }
+        }
+        assert!(!generator.any_square_attacked(&[], Color::White, occupied, &position.pieces));
+    }
+
+    #[test]
+    fn test_checkers_and_pins() {
+        let generator = MoveGenerator::new();
+
+        // The e2 knight is pinned by the e8 rook, the c3 pawn by the a5 queen; the d7
+        // bishop blocks nothing and the h4 bishop gives check
+        let position = crate::Position::from_fen("k3r3/3b4/8/q7/7b/2P5/4N3/4K3 w - - 0 1").unwrap();
+        assert_eq!(generator.checkers(&position), Square::H4.bitboard());
+        assert_eq!(
+            generator.pinned(&position, Color::White),
+            Square::E2.bitboard() | Square::C3.bitboard()
+        );
+
+        // A queen behind two pieces pins neither
+        let position = crate::Position::from_fen("k7/8/8/q7/8/2P5/3P4/4K3 w - - 0 1").unwrap();
+        assert!(generator.checkers(&position).is_empty());
+        assert!(generator.pinned(&position, Color::White).is_empty());
+
+        // An enemy piece between its own slider and our king is a discovered checker
+        let position = crate::Position::from_fen("k3r3/8/8/8/4n3/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            generator.blockers_for_king(&position, Color::White),
+            Square::E4.bitboard()
+        );
+        assert!(generator.pinned(&position, Color::White).is_empty());
+        assert!(generator.pinned(&position, Color::Black).is_empty());
+    }
+
+    #[test]
+    fn test_move_mirror() {
+        let mv = Move::promotion_capture(Square::B7, Square::A8, PieceType::Knight);
+        assert_eq!(
+            mv.mirror(),
+            Move::promotion_capture(Square::B2, Square::A1, PieceType::Knight)
+        );
+        assert_eq!(mv.mirror().mirror(), mv);
+        assert_eq!(
+            Move::castle(Square::E1, Square::G1).mirror(),
+            Move::castle(Square::E8, Square::G8)
+        );
+    }
+
+    #[test]
+    fn test_drop_moves() {
+        let drop = Move::drop(PieceType::Knight, Square::F3);
+        assert!(drop.is_drop());
+        assert!(!drop.is_capture());
+        assert_eq!(drop.drop_piece(), Some(PieceType::Knight));
+        assert_eq!(drop.to_uci(), "N@f3");
+        assert_eq!("N@f3".parse::<Move>().unwrap(), drop);
+        assert!("K@f3".parse::<Move>().is_err());
+        assert!("N@z3".parse::<Move>().is_err());
+
+        let generator = MoveGenerator::new();
+        let position = crate::Position::from_fen("4k3/8/8/8/8/8/8/4K3[Pr] w - - 0 1").unwrap();
+        let drops = generator.generate_drops(&position);
+        // 62 empty squares, 48 of them off the back ranks; Black's rook is not White's
+        assert_eq!(drops.len(), 48);
+        assert!(drops
+            .iter()
+            .all(|mv| mv.drop_piece() == Some(PieceType::Pawn)));
+        assert!(generator
+            .generate_legal_moves(&position)
+            .contains(&Move::drop(PieceType::Pawn, Square::E4)));
+    }
+
+    #[test]
+    fn test_legal_moves_include_castling_and_en_passant() {
+        let generator = MoveGenerator::new();
+        let position =
+            crate::Position::from_fen("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap();
+        let moves = generator.generate_legal_moves(&position);
+        assert!(moves.contains(&Move::castle(Square::E1, Square::G1)));
+        assert!(moves.contains(&Move::castle(Square::E1, Square::C1)));
+        assert!(moves.contains(&Move::en_passant(Square::E5, Square::D6)));
+
+        // The e2 rook is pinned to the file and the h3 bishop guards f1
+        let pinned = crate::Position::from_fen("4r1k1/8/8/8/8/7b/4R3/4K3 w - - 0 1").unwrap();
+        let moves = generator.generate_legal_moves(&pinned);
+        assert!(moves
+            .iter()
+            .all(|mv| mv.from != Square::E2 || mv.to.file() == 4));
+        assert!(moves.contains(&Move::capture(Square::E2, Square::E8)));
+        assert!(!moves.contains(&Move::normal(Square::E1, Square::F1)));
+        assert!(moves.contains(&Move::normal(Square::E1, Square::D1)));
+    }
 }
@@ -0,0 +1,122 @@
+// Perft with a transposition table
+//
+// Deep perft runs visit the same positions through many move orders. Keeping the node
+// count of each searched position and depth lets a transposition be counted once, which
+// makes depth 7 and 8 runs practical. The table is keyed by the Zobrist hash, so a hash
+// collision can in principle miscount; runs that must be exact should be repeated
+// without the table.
+
+use crate::{MoveGenerator, Position};
+
+#[derive(Debug, Clone, Copy, Default)]
+struct PerftEntry {
+    key: u64,
+    depth: u8,
+    nodes: u64,
+}
+
+/// Node counts of positions already counted, replaced whenever a slot is reused
+#[derive(Debug, Clone)]
+pub struct PerftTable {
+    entries: Vec<PerftEntry>,
+    mask: usize,
+    hits: u64,
+}
+
+impl PerftTable {
+    /// A table of at most `size_mb` megabytes, rounded down to a power of two entries
+    pub fn new(size_mb: usize) -> Self {
+        let fitting = size_mb.max(1) * 1024 * 1024 / std::mem::size_of::<PerftEntry>();
+        let capacity = 1usize << fitting.max(1).ilog2();
+        PerftTable {
+            entries: vec![PerftEntry::default(); capacity],
+            mask: capacity - 1,
+            hits: 0,
+        }
+    }
+
+    /// Subtrees counted from the table instead of by move generation
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    fn probe(&mut self, key: u64, depth: u8) -> Option<u64> {
+        let entry = self.entries[key as usize & self.mask];
+        // Depth 0 is never stored, so an empty slot cannot match
+        (entry.key == key && entry.depth == depth).then(|| {
+            self.hits += 1;
+            entry.nodes
+        })
+    }
+
+    fn store(&mut self, key: u64, depth: u8, nodes: u64) {
+        self.entries[key as usize & self.mask] = PerftEntry { key, depth, nodes };
+    }
+}
+
+/// Leaf nodes `depth` plies below `position`, with transpositions looked up in `table`
+pub fn perft_hashed(
+    position: &Position,
+    depth: u8,
+    move_generator: &MoveGenerator,
+    table: &mut PerftTable,
+) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = move_generator.generate_legal_moves(position);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    let key = position.zobrist_hash();
+    if let Some(nodes) = table.probe(key, depth) {
+        return nodes;
+    }
+
+    let mut nodes = 0;
+    for mv in moves {
+        let mut next = position.clone();
+        if next.make_move(mv).is_ok() {
+            nodes += perft_hashed(&next, depth - 1, move_generator, table);
+        }
+    }
+    table.store(key, depth, nodes);
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashed_perft_matches_known_counts() {
+        let move_generator = MoveGenerator::new();
+        let mut hits = 0;
+        for (fen, depth, expected) in [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                4,
+                197_281,
+            ),
+            (
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                3,
+                97_862,
+            ),
+            // Deep enough for move orders to transpose
+            ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 5, 674_624),
+        ] {
+            let position = Position::from_fen(fen).unwrap();
+            let mut table = PerftTable::new(1);
+            assert_eq!(
+                perft_hashed(&position, depth, &move_generator, &mut table),
+                expected,
+                "{}",
+                fen
+            );
+            hits += table.hits();
+        }
+        assert!(hits > 0);
+    }
+}
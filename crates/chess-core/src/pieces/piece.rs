@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PieceType {
     Pawn,
     Knight,
@@ -47,15 +48,9 @@ impl PieceType {
         }
     }
 
+    /// Middlegame value from the default evaluation parameters
     pub const fn value(self) -> i32 {
-        match self {
-            PieceType::Pawn => 100,
-            PieceType::Knight => 320,
-            PieceType::Bishop => 330,
-            PieceType::Rook => 500,
-            PieceType::Queen => 900,
-            PieceType::King => 20000,
-        }
+        crate::evaluation::params::PIECE_VALUES[self.index()].middlegame
     }
 
     pub const fn is_sliding(self) -> bool {
@@ -124,7 +119,8 @@ impl std::str::FromStr for PieceType {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Serialized as its FEN letter, e.g. `"N"` or `"p"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Piece {
     pub piece_type: PieceType,
     pub color: Color,
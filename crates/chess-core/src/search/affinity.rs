@@ -0,0 +1,156 @@
+// CPU pinning and scheduling priority for search threads
+//
+// Both are hints for engine matches on dedicated hardware, where keeping each worker on
+// its own core (and NUMA node) steadies nps. Linux and Windows have backends; elsewhere
+// the calls report failure and the threads run unpinned at normal priority.
+
+use serde::{Deserialize, Serialize};
+
+/// Which cores the threads of a search pool run on
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ThreadAffinity {
+    /// Leave placement to the operating system
+    #[default]
+    Unpinned,
+    /// Pin thread `i` to core `i`, wrapping around the available cores
+    Compact,
+    /// Pin thread `i` to `cores[i % cores.len()]`
+    Cores(Vec<usize>),
+}
+
+impl ThreadAffinity {
+    /// Core for the pool thread with this index, or `None` when unpinned
+    pub fn core_for(&self, thread_index: usize) -> Option<usize> {
+        match self {
+            ThreadAffinity::Unpinned => None,
+            ThreadAffinity::Compact => {
+                let cores = std::thread::available_parallelism()
+                    .map(|cores| cores.get())
+                    .unwrap_or(1);
+                Some(thread_index % cores)
+            }
+            ThreadAffinity::Cores(cores) if cores.is_empty() => None,
+            ThreadAffinity::Cores(cores) => Some(cores[thread_index % cores.len()]),
+        }
+    }
+}
+
+/// Scheduling priority of search threads relative to the rest of the process
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ThreadPriority {
+    /// Yield to interactive work, e.g. when analysing in the background
+    Low,
+    #[default]
+    Normal,
+    /// Usually needs elevated privileges; refused requests leave the priority unchanged
+    High,
+}
+
+/// Pin the calling thread to one core; returns false if the platform refused or has
+/// no backend
+pub fn pin_current_thread(core: usize) -> bool {
+    backend::pin_current_thread(core)
+}
+
+/// Change the calling thread's priority; returns false if the platform refused or has
+/// no backend
+pub fn set_current_thread_priority(priority: ThreadPriority) -> bool {
+    backend::set_priority(priority)
+}
+
+#[cfg(target_os = "linux")]
+mod backend {
+    use super::ThreadPriority;
+
+    /// Nice values used for the priorities; lower runs first
+    const LOW_NICE: libc::c_int = 10;
+    const HIGH_NICE: libc::c_int = -5;
+
+    pub fn pin_current_thread(core: usize) -> bool {
+        if core >= libc::CPU_SETSIZE as usize {
+            return false;
+        }
+        // SAFETY: the set is zero-initialised, `core` is within CPU_SETSIZE and
+        // pid 0 names the calling thread
+        unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            libc::CPU_SET(core, &mut set);
+            libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) == 0
+        }
+    }
+
+    pub fn set_priority(priority: ThreadPriority) -> bool {
+        let nice = match priority {
+            ThreadPriority::Low => LOW_NICE,
+            ThreadPriority::Normal => 0,
+            ThreadPriority::High => HIGH_NICE,
+        };
+        // Linux applies nice values per thread, addressed by thread id
+        // SAFETY: plain system calls on the calling thread
+        unsafe {
+            let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+            libc::setpriority(libc::PRIO_PROCESS, tid, nice) == 0
+        }
+    }
+}
+
+#[cfg(windows)]
+mod backend {
+    use super::ThreadPriority;
+    use windows_sys::Win32::System::Threading::{
+        GetCurrentThread, SetThreadAffinityMask, SetThreadPriority, THREAD_PRIORITY_ABOVE_NORMAL,
+        THREAD_PRIORITY_BELOW_NORMAL, THREAD_PRIORITY_NORMAL,
+    };
+
+    pub fn pin_current_thread(core: usize) -> bool {
+        if core >= usize::BITS as usize {
+            return false;
+        }
+        // SAFETY: the pseudo handle of the current thread is always valid
+        unsafe { SetThreadAffinityMask(GetCurrentThread(), 1 << core) != 0 }
+    }
+
+    pub fn set_priority(priority: ThreadPriority) -> bool {
+        let level = match priority {
+            ThreadPriority::Low => THREAD_PRIORITY_BELOW_NORMAL,
+            ThreadPriority::Normal => THREAD_PRIORITY_NORMAL,
+            ThreadPriority::High => THREAD_PRIORITY_ABOVE_NORMAL,
+        };
+        // SAFETY: the pseudo handle of the current thread is always valid
+        unsafe { SetThreadPriority(GetCurrentThread(), level) != 0 }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod backend {
+    use super::ThreadPriority;
+
+    pub fn pin_current_thread(_core: usize) -> bool {
+        false
+    }
+
+    pub fn set_priority(_priority: ThreadPriority) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_core_assignment() {
+        assert_eq!(ThreadAffinity::Unpinned.core_for(3), None);
+        assert_eq!(ThreadAffinity::Cores(vec![]).core_for(0), None);
+
+        let cores = ThreadAffinity::Cores(vec![2, 5]);
+        assert_eq!(cores.core_for(0), Some(2));
+        assert_eq!(cores.core_for(3), Some(5));
+        assert_eq!(ThreadAffinity::Compact.core_for(0), Some(0));
+    }
+
+    #[test]
+    fn test_out_of_range_core_is_refused() {
+        assert!(!pin_current_thread(1 << 20));
+    }
+}
@@ -0,0 +1,125 @@
+// Fixed-position search bench for comparing node counts between search configurations
+
+use super::engine::{SearchConfig, SearchEngine};
+use crate::Position;
+use std::time::{Duration, Instant};
+
+/// Positions searched by `run_bench`: opening, tactical middlegames and endgames
+pub const BENCH_POSITIONS: [&str; 6] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r1bq1rk1/ppp2ppp/2np1n2/2b1p3/2B1P3/2NP1N2/PPP2PPP/R1BQ1RK1 w - - 0 7",
+    "r1bqkb1r/pppp1ppp/2n2n2/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+    "r2q1rk1/pp2bppp/2n1pn2/3p4/3P4/2NBPN2/PP3PPP/R2Q1RK1 b - - 0 10",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchReport {
+    pub positions: usize,
+    pub nodes: u64,
+    pub elapsed: Duration,
+}
+
+impl BenchReport {
+    pub fn nodes_per_second(&self) -> u64 {
+        let micros = self.elapsed.as_micros().max(1) as u64;
+        self.nodes.saturating_mul(1_000_000) / micros
+    }
+}
+
+/// Search every bench position to the configured depth with a fresh engine each time.
+///
+/// Time limits are removed so node counts depend only on the configuration, which makes
+/// the total comparable between pruning settings and across commits.
+pub fn run_bench(config: &SearchConfig) -> BenchReport {
+    run_bench_on(config, &BENCH_POSITIONS)
+}
+
+/// `run_bench` over a caller-chosen set of FENs
+pub fn run_bench_on(config: &SearchConfig, fens: &[&str]) -> BenchReport {
+    let config = SearchConfig {
+        limits: config.limits.without_time(),
+        ..config.clone()
+    };
+    let start = Instant::now();
+    let mut nodes = 0;
+
+    for fen in fens {
+        let position = Position::from_fen(fen).expect("bench positions are valid");
+        let mut engine = SearchEngine::new(config.clone());
+        nodes += engine.search(&position).nodes_searched;
+    }
+
+    BenchReport {
+        positions: fens.len(),
+        nodes,
+        elapsed: start.elapsed(),
+    }
+}
+
+/// Throughput figures compared between runs by the benchmark regression gate
+#[cfg(feature = "benchmarks")]
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BenchSummary {
+    /// Total nodes of the fixed-depth bench, which changes only when the search does
+    pub search_nodes: u64,
+    pub nodes_per_second: u64,
+    pub perft_nodes: u64,
+    pub perft_nodes_per_second: u64,
+    pub evaluations_per_second: u64,
+}
+
+/// Perft depth from the starting position used by `run_summary`
+#[cfg(feature = "benchmarks")]
+pub const SUMMARY_PERFT_DEPTH: u8 = 4;
+
+/// Evaluations of each bench position timed by `run_summary`
+#[cfg(feature = "benchmarks")]
+pub const SUMMARY_EVAL_ITERATIONS: u32 = 20_000;
+
+/// Measure search, perft and evaluation throughput for a machine-readable summary
+#[cfg(feature = "benchmarks")]
+pub fn run_summary(config: &SearchConfig) -> BenchSummary {
+    use super::parallel::ParallelUtils;
+    use crate::{Evaluator, GameState, MoveGenerator};
+
+    let search = run_bench(config);
+
+    let move_generator = MoveGenerator::new();
+    let start = Instant::now();
+    let perft_nodes = ParallelUtils::sequential_perft(
+        &Position::starting_position(),
+        SUMMARY_PERFT_DEPTH,
+        &move_generator,
+    );
+    let perft_elapsed = start.elapsed();
+
+    // The plain evaluator keeps no cache, so every call does the full work
+    let evaluator = Evaluator::new();
+    let positions: Vec<GameState> = BENCH_POSITIONS
+        .iter()
+        .map(|fen| GameState::from_fen(fen).expect("bench positions are valid"))
+        .collect();
+    let start = Instant::now();
+    for _ in 0..SUMMARY_EVAL_ITERATIONS {
+        for position in &positions {
+            std::hint::black_box(evaluator.evaluate(std::hint::black_box(position)));
+        }
+    }
+    let evaluations = SUMMARY_EVAL_ITERATIONS as u64 * positions.len() as u64;
+
+    BenchSummary {
+        search_nodes: search.nodes,
+        nodes_per_second: search.nodes_per_second(),
+        perft_nodes,
+        perft_nodes_per_second: per_second(perft_nodes, perft_elapsed),
+        evaluations_per_second: per_second(evaluations, start.elapsed()),
+    }
+}
+
+#[cfg(feature = "benchmarks")]
+fn per_second(count: u64, elapsed: Duration) -> u64 {
+    let micros = elapsed.as_micros().max(1) as u64;
+    count.saturating_mul(1_000_000) / micros
+}
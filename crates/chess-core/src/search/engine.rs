@@ -1,79 +1,309 @@
 // Advanced search algorithms for chess engine
 // Implements alpha-beta pruning, iterative deepening, and other modern search techniques
 
-use crate::{Color, Move, MoveGenerator, OptimizedEvaluator, PieceType, Position};
-use std::collections::HashMap;
+use super::limits::SearchLimits;
+use super::move_picker::MovePicker;
+use super::see::see;
+use super::status::{SearchStatus, SearchStatusHandle};
+use super::tablebase::{filter_root_moves, TablebaseProbe};
+use super::terminal::{is_rule_draw, mated_in, no_moves_score};
+use crate::evaluation::pawns::{passed_pawn_mask, relative_rank};
+use crate::utils::memory::{NodeType, TranspositionTable};
+use crate::{Color, Move, MoveGenerator, OptimizedEvaluator, PieceType, Position, Square};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 pub const MATE_VALUE: i32 = 32000;
 pub const MAX_DEPTH: u8 = 64;
+/// Scores beyond this magnitude are mates, counted in plies from the root
+pub const MATE_THRESHOLD: i32 = MATE_VALUE - 100;
+
+/// Convert a mate score from root-relative to node-relative before storing it in the
+/// transposition table, so it stays correct when the position is reached at another ply
+pub fn value_to_tt(score: i32, ply: u8) -> i32 {
+    if score >= MATE_THRESHOLD {
+        score.saturating_add(ply as i32)
+    } else if score <= -MATE_THRESHOLD {
+        score.saturating_sub(ply as i32)
+    } else {
+        score
+    }
+}
+
+/// Inverse of `value_to_tt` for a table entry probed at `ply`
+pub fn value_from_tt(score: i32, ply: u8) -> i32 {
+    if score >= MATE_THRESHOLD {
+        score.saturating_sub(ply as i32)
+    } else if score <= -MATE_THRESHOLD {
+        score.saturating_add(ply as i32)
+    } else {
+        score
+    }
+}
+
+const TT_SIZE_MB: usize = 16; // 1M packed 16-byte entries
+/// Quiescence plies searched below the main search horizon
+const MAX_QSEARCH_PLY: u8 = 16;
+/// Deepest remaining depth at which quiet moves are pruned by SEE
+const SEE_PRUNING_MAX_DEPTH: u8 = 6;
+/// Depth stored for quiescence results, so any main search probe at depth 0 can use them
+const QSEARCH_TT_DEPTH: u8 = 0;
+/// Nodes between publishing live counters to the status handle
+const STATUS_PUBLISH_INTERVAL: u64 = 1024;
+/// Deepest remaining depth at which razoring is tried
+const RAZORING_MAX_DEPTH: u8 = 2;
+/// Deepest remaining depth at which reverse futility pruning is tried
+const REVERSE_FUTILITY_MAX_DEPTH: u8 = 6;
+/// Shallowest remaining depth at which internal iterative reduction is applied
+const IIR_MIN_DEPTH: u8 = 4;
+/// Ordering bonus that puts queen promotions ahead of every plain capture
+const QUEEN_PROMOTION_ORDER_BONUS: i32 = 100_000;
+/// Root advantage in centipawns beyond which dynamic contempt starts to apply
+const DYNAMIC_CONTEMPT_THRESHOLD: i32 = 100;
+/// Largest draw penalty dynamic contempt adds
+const DYNAMIC_CONTEMPT_MAX: i32 = 50;
+/// Iterations the best move must survive unchanged before it can count as easy
+const EASY_MOVE_STABLE_ITERATIONS: u32 = 3;
+/// Centipawns every other root move must fall short of the best one for an easy move
+const EASY_MOVE_MARGIN: i32 = 200;
+/// Shallowest remaining depth at which the table move is tested for a singular extension
+const SINGULAR_MIN_DEPTH: u8 = 6;
+/// Plies a table entry may be shallower than the node and still be tested as singular
+const SINGULAR_TT_DEPTH_MARGIN: u8 = 3;
+/// Centipawns per ply of depth the other moves must fall below the table score by
+const SINGULAR_MARGIN: i32 = 3;
+/// Nodes a mate proof may visit before it gives up and a deeper search decides instead
+const MATE_PROOF_NODE_LIMIT: u64 = 2_000_000;
+/// Extra plies of the search that replaces a mate proof which ran out of nodes
+const MATE_PROOF_FALLBACK_DEPTH: u8 = 2;
+
+/// Side of the aspiration window a root score fell outside
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RootFail {
+    /// The score beat the window: the position is better than the last iteration said
+    High,
+    /// The score fell below the window: the position is worse than expected
+    Low,
+}
+
+/// How a mate score is confirmed before `search` reports it. A mate can be read from
+/// a transposition table entry whose key collided with another position's, and a false
+/// mate announced to a GUI or played towards is worse than the time a check costs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MateVerification {
+    /// Report mate scores as found
+    #[default]
+    Off,
+    /// Search again with an empty transposition table, this many plies deeper than the
+    /// mate was found and no shallower than the limits, and report that search's result
+    Deeper(u8),
+    /// Prove the mate by trying every move and reply up to its distance, without the
+    /// table or pruning. A mate that fails the proof is searched again with an empty
+    /// table; one too long to prove in `MATE_PROOF_NODE_LIMIT` nodes is searched again
+    /// `MATE_PROOF_FALLBACK_DEPTH` plies deeper.
+    Prove,
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SearchResult {
     pub best_move: Option<Move>,
     pub evaluation: i32,
     pub depth: u8,
+    /// Deepest ply reached in the last completed iteration, quiescence and extensions
+    /// included; never below `depth`
+    pub seldepth: u8,
+    #[serde(rename = "nodes")]
     pub nodes_searched: u64,
+    #[serde(rename = "time_ms", with = "crate::wire::millis")]
     pub elapsed_time: Duration,
+    #[serde(rename = "pv", with = "crate::wire::principal_variation")]
     pub principal_variation: [Option<Move>; 16],
+    /// How the first aspiration window of the last completed iteration failed, when
+    /// `evaluation` only came from a re-search; such scores are still moving
+    pub root_fail: Option<RootFail>,
+    /// Aspiration re-searches over the whole search
+    pub aspiration_researches: u32,
 }
 
-#[derive(Debug, Clone)]
+impl SearchResult {
+    /// Whether the score needed an aspiration re-search in the last iteration
+    pub fn is_unstable(&self) -> bool {
+        self.root_fail.is_some()
+    }
+
+    /// Plies to mate for a mate score, positive when the side to move mates and
+    /// negative when it is mated
+    pub fn mate_in_plies(&self) -> Option<i32> {
+        if self.evaluation.abs() <= MATE_THRESHOLD {
+            return None;
+        }
+        let plies = MATE_VALUE - self.evaluation.abs();
+        Some(plies * self.evaluation.signum())
+    }
+
+    /// Moves to mate as UCI counts them: the mating side's moves, negative when the
+    /// side to move is mated
+    pub fn mate_in(&self) -> Option<i32> {
+        self.mate_in_plies()
+            .map(|plies| (plies.abs() + 1) / 2 * plies.signum())
+    }
+
+    /// Score as written after `score` in a UCI `info` line: `cp 35` or `mate -2`
+    pub fn uci_score(&self) -> String {
+        match self.mate_in() {
+            Some(moves) => format!("mate {}", moves),
+            None => format!("cp {}", self.evaluation),
+        }
+    }
+}
+
+/// Limits of the fresh search that checks a mate score: `depth`, and for a timed search
+/// only the part of the move's time budget that `elapsed` has not used up. `None` when
+/// no time is left for the check.
+fn verification_limits(
+    limits: SearchLimits,
+    side: Color,
+    depth: u8,
+    elapsed: Duration,
+) -> Option<SearchLimits> {
+    let limits = limits.with_depth(depth);
+    match limits.time_budget(side) {
+        Some(budget) => {
+            let left = budget.checked_sub(elapsed).filter(|left| !left.is_zero())?;
+            Some(limits.without_time().with_movetime(left))
+        }
+        None => Some(limits),
+    }
+}
+
+/// Search settings. Missing fields deserialize to their defaults and unknown ones are
+/// rejected, so a config file only lists what it changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct SearchConfig {
-    pub max_depth: u8,
-    pub max_time: Option<Duration>,
-    pub max_nodes: Option<u64>,
+    /// When each search stops; set per search rather than read from config files
+    #[serde(skip)]
+    pub limits: SearchLimits,
     pub use_null_move_pruning: bool,
     pub use_late_move_reductions: bool,
     pub use_futility_pruning: bool,
+    /// Drop into quiescence search when the static eval is far below alpha at shallow depth
+    pub use_razoring: bool,
+    /// Razoring applies when `static_eval + razoring_margin * depth < alpha`
+    pub razoring_margin: i32,
+    /// Reverse futility (static null move): fail high when the static eval clears beta by a margin
+    pub use_reverse_futility_pruning: bool,
+    /// Reverse futility applies when `static_eval - reverse_futility_margin * depth >= beta`
+    pub reverse_futility_margin: i32,
+    /// Search nodes without a transposition table move one ply shallower
+    pub use_internal_iterative_reduction: bool,
+    /// Search every move after the first with a null window, re-searching on a fail high
+    pub use_principal_variation_search: bool,
+    /// Search quiescence moves in MVV-LVA order with queen promotions first
+    pub use_qsearch_move_ordering: bool,
+    /// Skip quiescence captures that cannot lift the score to alpha
+    pub use_delta_pruning: bool,
+    /// A capture is skipped when `static_eval + captured value + delta_margin <= alpha`
+    pub delta_margin: i32,
+    /// Probe and store quiescence nodes in the transposition table
+    pub use_qsearch_transposition_table: bool,
+    /// Skip quiet moves near the leaves that lose material by static exchange
+    pub use_see_pruning: bool,
+    /// A quiet move is skipped when its SEE is below `-see_quiet_margin * depth`
+    pub see_quiet_margin: i32,
+    /// Search pushes of passed pawns to the sixth or seventh rank one ply deeper
+    pub use_passed_pawn_extension: bool,
+    /// Search captures back on the square of the previous capture one ply deeper
+    pub use_recapture_extension: bool,
+    /// Search the table move one ply deeper when a reduced search without it shows every
+    /// other move is clearly worse
+    pub use_singular_extensions: bool,
+    /// Centipawns a draw is worth less than zero to the engine; negative values make it
+    /// seek draws
+    pub contempt: i32,
+    /// Also score draws below zero while the root position is clearly won, on top of
+    /// `contempt`, so the engine does not repeat or shuffle out of a winning position
+    pub use_dynamic_contempt: bool,
     pub aspiration_window: i32,
+    /// Whether and how a mate score is confirmed before it is reported
+    pub mate_verification: MateVerification,
 }
 
 impl Default for SearchConfig {
     fn default() -> Self {
         SearchConfig {
-            max_depth: 8,
-            max_time: Some(Duration::from_secs(5)),
-            max_nodes: None,
+            limits: SearchLimits::default()
+                .with_depth(8)
+                .with_movetime(Duration::from_secs(5)),
             use_null_move_pruning: true,
             use_late_move_reductions: true,
             use_futility_pruning: true,
+            use_razoring: true,
+            razoring_margin: 150,
+            use_reverse_futility_pruning: true,
+            reverse_futility_margin: 90,
+            use_internal_iterative_reduction: true,
+            use_principal_variation_search: true,
+            use_qsearch_move_ordering: true,
+            use_delta_pruning: true,
+            delta_margin: 200,
+            use_qsearch_transposition_table: true,
+            use_see_pruning: true,
+            see_quiet_margin: 60,
+            use_passed_pawn_extension: true,
+            use_recapture_extension: true,
+            use_singular_extensions: true,
+            contempt: 0,
+            use_dynamic_contempt: true,
             aspiration_window: 50,
+            mate_verification: MateVerification::Off,
         }
     }
 }
 
-/// Transposition table entry for storing search results
-#[derive(Debug, Clone, Copy)]
-struct TranspositionEntry {
-    zobrist_hash: u64,
-    depth: u8,
-    evaluation: i32,
-    #[allow(dead_code)]
-    best_move: Option<Move>,
-    node_type: NodeType,
-    #[allow(dead_code)]
-    age: u8,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum NodeType {
-    Exact,      // PV node - exact evaluation
-    LowerBound, // Cut node - beta cutoff (fail high)
-    UpperBound, // All node - alpha didn't improve (fail low)
-}
-
 /// Advanced chess search engine with modern techniques
 pub struct SearchEngine {
     evaluator: OptimizedEvaluator,
     move_generator: MoveGenerator,
-    transposition_table: HashMap<u64, TranspositionEntry>,
+    transposition_table: TranspositionTable,
+    /// Ask for huge pages whenever the transposition table is allocated
+    large_pages: bool,
     killer_moves: [[Option<Move>; 2]; MAX_DEPTH as usize],
+    /// Destination of the capture made at each ply, `None` for quiet and null moves
+    capture_squares: [Option<Square>; MAX_DEPTH as usize + 1],
+    /// Zobrist key of the position at each ply of the current line
+    key_stack: [u64; MAX_DEPTH as usize + 1],
+    /// Ply of the innermost null move on the current line; repetitions cannot span it
+    null_move_ply: Option<u8>,
+    /// Keys of positions reached earlier in the game, before the root
+    game_keys: HashSet<u64>,
+    root_color: Color,
+    /// Value of a draw for the side to move at the root
+    root_draw_score: i32,
+    tablebase: Option<Arc<dyn TablebaseProbe>>,
+    /// Root moves allowed by the tablebase, `None` to search every move
+    root_moves: Option<Vec<Move>>,
+    /// Root move `search_excluding` leaves out
+    excluded_root_move: Option<Move>,
     history_table: HashMap<(Move, Color), u32>,
     nodes_searched: u64,
     start_time: Instant,
+    /// Time allowed for the running search, from the limits and the side to move
+    time_budget: Option<Duration>,
+    /// What `time_budget` grows to once the root score fails high or low
+    unstable_time_budget: Option<Duration>,
+    aspiration_researches: u32,
     config: SearchConfig,
-    age: u8,
+    current_depth: u8,
+    /// Deepest ply reached in the current iteration
+    seldepth: u8,
+    status: SearchStatusHandle,
+    /// Set by another thread to end the running search early
+    stop_signal: Option<Arc<AtomicBool>>,
 }
 
 impl SearchEngine {
@@ -81,36 +311,214 @@ impl SearchEngine {
         SearchEngine {
             evaluator: OptimizedEvaluator::new(),
             move_generator: MoveGenerator::new(),
-            transposition_table: HashMap::with_capacity(1024 * 1024), // 1M entries
+            transposition_table: TranspositionTable::new(TT_SIZE_MB),
+            large_pages: false,
             killer_moves: [[None; 2]; MAX_DEPTH as usize],
+            capture_squares: [None; MAX_DEPTH as usize + 1],
+            key_stack: [0; MAX_DEPTH as usize + 1],
+            null_move_ply: None,
+            game_keys: HashSet::new(),
+            root_color: Color::White,
+            root_draw_score: 0,
+            tablebase: None,
+            root_moves: None,
+            excluded_root_move: None,
             history_table: HashMap::new(),
             nodes_searched: 0,
             start_time: Instant::now(),
+            time_budget: None,
+            unstable_time_budget: None,
+            aspiration_researches: 0,
             config,
-            age: 0,
+            current_depth: 0,
+            seldepth: 0,
+            status: SearchStatusHandle::new(),
+            stop_signal: None,
         }
     }
 
-    /// Main search function using iterative deepening
+    pub fn config(&self) -> &SearchConfig {
+        &self.config
+    }
+
+    /// Replace the configuration, keeping the transposition table and move ordering tables
+    pub fn set_config(&mut self, config: SearchConfig) {
+        self.config = config;
+    }
+
+    pub fn limits(&self) -> &SearchLimits {
+        &self.config.limits
+    }
+
+    /// Change when later searches stop, keeping the rest of the configuration
+    pub fn set_limits(&mut self, limits: SearchLimits) {
+        self.config.limits = limits;
+    }
+
+    /// Zobrist keys of the positions played earlier in the game. Reaching one of them
+    /// again during the search scores as a draw.
+    pub fn set_game_history<I: IntoIterator<Item = u64>>(&mut self, keys: I) {
+        self.game_keys = keys.into_iter().collect();
+    }
+
+    /// Tablebases consulted at the root; `None` searches every root move normally
+    pub fn set_tablebase(&mut self, tablebase: Option<Arc<dyn TablebaseProbe>>) {
+        self.tablebase = tablebase;
+    }
+
+    /// Flag that ends searches early once another thread sets it, returning the best
+    /// result found so far; `None` searches until the limits are reached
+    pub fn set_stop_signal(&mut self, stop_signal: Option<Arc<AtomicBool>>) {
+        self.stop_signal = stop_signal;
+    }
+
+    /// Handle that can be cloned to another thread to poll progress during `search`
+    pub fn status_handle(&self) -> SearchStatusHandle {
+        self.status.clone()
+    }
+
+    /// Current counters of the running or most recent search
+    pub fn status(&self) -> SearchStatus {
+        self.status.snapshot()
+    }
+
+    /// Replace the transposition table with an empty one of `size_mb` megabytes
+    pub fn set_hash_size(&mut self, size_mb: usize) {
+        self.transposition_table =
+            TranspositionTable::with_large_pages(size_mb.max(1), self.large_pages);
+        crate::log_debug!(
+            "transposition table of {} MB allocated, huge pages: {}",
+            self.transposition_table.size_mb(),
+            self.transposition_table.uses_large_pages()
+        );
+    }
+
+    /// Size of the transposition table in megabytes
+    pub fn hash_size_mb(&self) -> usize {
+        self.transposition_table.size_mb()
+    }
+
+    /// Reallocate the transposition table, empty and at its current size, in huge
+    /// pages when `enabled` and the OS grants them
+    pub fn set_large_pages(&mut self, enabled: bool) {
+        self.large_pages = enabled;
+        self.set_hash_size(self.transposition_table.size_mb());
+    }
+
+    /// Whether the transposition table ended up in huge pages
+    pub fn uses_large_pages(&self) -> bool {
+        self.transposition_table.uses_large_pages()
+    }
+
+    /// Transposition table fill in permille, as reported by UCI `hashfull`
+    pub fn hashfull(&self) -> u16 {
+        self.transposition_table.hash_full() as u16
+    }
+
+    /// Main search function using iterative deepening, with mate scores confirmed as
+    /// `SearchConfig::mate_verification` asks
     pub fn search(&mut self, position: &Position) -> SearchResult {
+        let started = Instant::now();
+        let result = self.iterative_deepening(position);
+        if result.mate_in_plies().is_none() {
+            return result;
+        }
+
+        let extra_depth = match self.config.mate_verification {
+            MateVerification::Off => return result,
+            MateVerification::Deeper(extra_depth) => extra_depth,
+            MateVerification::Prove => match self.prove_mate(position, result.evaluation) {
+                Some(true) => {
+                    crate::log_debug!("proved {}", result.uci_score());
+                    return result;
+                }
+                Some(false) => 0,
+                None => MATE_PROOF_FALLBACK_DEPTH,
+            },
+        };
+
+        let limits = self.config.limits;
+        let depth = result
+            .depth
+            .saturating_add(extra_depth)
+            .clamp(limits.max_depth(), MAX_DEPTH);
+        let Some(verify_limits) =
+            verification_limits(limits, position.side_to_move(), depth, started.elapsed())
+        else {
+            return result;
+        };
+
+        // Entries that led to the mate may be the corrupt ones, so the check starts afresh.
+        // A search that stops at the first mate it sees again confirms it from scratch.
+        self.transposition_table.clear();
+        crate::log_debug!(
+            "checking {} found at depth {} with a fresh search to depth {}",
+            result.uci_score(),
+            result.depth,
+            depth
+        );
+        self.config.limits = verify_limits;
+        let mut verified = self.iterative_deepening(position);
+        self.config.limits = limits;
+        // Cut short by the clock short of the depth the mate was found at, a check that
+        // has not seen the mate again proves nothing
+        if verified.mate_in_plies().is_none() && verified.depth < result.depth {
+            crate::log_debug!("no time to check {}", result.uci_score());
+            return SearchResult {
+                nodes_searched: result.nodes_searched + verified.nodes_searched,
+                elapsed_time: started.elapsed(),
+                ..result
+            };
+        }
+
+        verified.nodes_searched += result.nodes_searched;
+        verified.elapsed_time = started.elapsed();
+        verified.aspiration_researches += result.aspiration_researches;
+        verified
+    }
+
+    fn iterative_deepening(&mut self, position: &Position) -> SearchResult {
         self.nodes_searched = 0;
         self.start_time = Instant::now();
-        self.age = self.age.wrapping_add(1);
+        self.transposition_table.next_age();
+        self.current_depth = 0;
+        self.status.reset(self.start_time);
+        self.root_color = position.side_to_move();
+        self.time_budget = self.config.limits.time_budget(self.root_color);
+        self.unstable_time_budget = self.config.limits.unstable_time_budget(self.root_color);
+        self.aspiration_researches = 0;
+        self.null_move_ply = None;
+        self.root_moves = self.tablebase_root_moves(position);
+        let mut root_estimate = self.evaluator.evaluate(position);
+
+        // On the clock, a forced reply is played after one iteration and a clearly best
+        // move once it has held for a while; a best move that keeps changing gets longer
+        let manages_clock = self.config.limits.manages_clock(self.root_color);
+        let easy_move_time = self.config.limits.easy_move_time(self.root_color);
+        let only_move = manages_clock && self.root_legal_moves(position).len() == 1;
+        let mut stable_iterations = 0;
+        let mut easy_move_checked = false;
 
         let mut best_result = SearchResult {
             best_move: None,
             evaluation: -MATE_VALUE,
             depth: 0,
+            seldepth: 0,
             nodes_searched: 0,
             elapsed_time: Duration::from_millis(0),
             principal_variation: [None; 16],
+            root_fail: None,
+            aspiration_researches: 0,
         };
 
         // Iterative deepening
-        for depth in 1..=self.config.max_depth {
+        for depth in 1..=self.config.limits.max_depth() {
             if self.should_stop() {
                 break;
             }
+            self.current_depth = depth;
+            self.seldepth = 0;
+            self.root_draw_score = self.draw_score_for(root_estimate);
 
             let mut alpha = -MATE_VALUE;
             let mut beta = MATE_VALUE;
@@ -121,29 +529,182 @@ impl SearchEngine {
                 beta = best_result.evaluation + self.config.aspiration_window;
             }
 
-            let mut search_result = self.aspiration_search(position, depth, alpha, beta);
-
-            // Re-search with wider window if we fell outside aspiration window
-            if search_result.evaluation <= alpha || search_result.evaluation >= beta {
-                search_result = self.alpha_beta_root(position, depth, -MATE_VALUE, MATE_VALUE);
-            }
+            let search_result = self.aspiration_search(position, depth, alpha, beta);
 
             if !self.should_stop() {
+                if depth > 1 && search_result.best_move != best_result.best_move {
+                    stable_iterations = 0;
+                    self.time_budget = self.unstable_time_budget;
+                } else {
+                    stable_iterations += 1;
+                }
                 best_result = search_result;
                 best_result.depth = depth;
+                best_result.seldepth = self.seldepth.max(depth);
+                root_estimate = best_result.evaluation;
+                crate::log_debug!(
+                    "depth {} seldepth {} score {} nodes {} best {}",
+                    depth,
+                    best_result.seldepth,
+                    best_result.uci_score(),
+                    self.nodes_searched,
+                    best_result
+                        .best_move
+                        .map_or_else(|| "none".to_string(), |mv| mv.to_string())
+                );
             }
 
             // Stop if we found mate
-            if best_result.evaluation.abs() > MATE_VALUE - 100 {
+            if best_result.evaluation.abs() > MATE_THRESHOLD || only_move {
                 break;
             }
+
+            if manages_clock
+                && !easy_move_checked
+                && stable_iterations >= EASY_MOVE_STABLE_ITERATIONS
+                && best_result.root_fail.is_none()
+                && easy_move_time.is_some_and(|time| self.start_time.elapsed() >= time)
+            {
+                easy_move_checked = true;
+                if let Some(best_move) = best_result.best_move {
+                    if self.is_easy_move(position, best_move, depth, best_result.evaluation) {
+                        break;
+                    }
+                }
+            }
         }
 
         best_result.nodes_searched = self.nodes_searched;
         best_result.elapsed_time = self.start_time.elapsed();
+        best_result.aspiration_researches = self.aspiration_researches;
+        self.publish_status();
+        self.status.finish(best_result.elapsed_time);
         best_result
     }
 
+    /// Search as `search` does with `excluded` unavailable at the root, to see what the
+    /// position is worth without it. The transposition table and move ordering tables
+    /// carry over in both directions, so a search of the full position that follows is
+    /// as cheap as it would have been.
+    pub fn search_excluding(&mut self, position: &Position, excluded: Move) -> SearchResult {
+        self.excluded_root_move = Some(excluded);
+        let result = self.search(position);
+        self.excluded_root_move = None;
+        result
+    }
+
+    /// Whether the mate `score` claims for the side to move at `position` holds when every
+    /// move and reply up to its distance is tried; `None` when the proof needs more
+    /// than `MATE_PROOF_NODE_LIMIT` nodes
+    fn prove_mate(&self, position: &Position, score: i32) -> Option<bool> {
+        let plies = (MATE_VALUE - score.abs()) as u8;
+        let mut nodes = 0;
+        if score > 0 {
+            return self.forces_mate(position, plies, &mut nodes);
+        }
+
+        // Mated: every move must leave the opponent a mate in the plies that remain
+        for mv in self.move_generator.generate_legal_moves(position) {
+            let mut new_position = position.clone();
+            new_position.make_move(mv).ok()?;
+            if !self.forces_mate(&new_position, plies.saturating_sub(1), &mut nodes)? {
+                return Some(false);
+            }
+        }
+        Some(true)
+    }
+
+    /// Whether the side to move at `position` can mate within `plies` plies whatever
+    /// the defence, counting nodes against `MATE_PROOF_NODE_LIMIT`
+    fn forces_mate(&self, position: &Position, plies: u8, nodes: &mut u64) -> Option<bool> {
+        if plies == 0 {
+            return Some(false);
+        }
+
+        let mover = position.side_to_move();
+        for mv in self.move_generator.generate_legal_moves(position) {
+            *nodes += 1;
+            if *nodes > MATE_PROOF_NODE_LIMIT {
+                return None;
+            }
+
+            let mut new_position = position.clone();
+            new_position.make_move(mv).ok()?;
+            if new_position.variant_winner() == Some(mover) {
+                return Some(true);
+            }
+            let replies = self.move_generator.generate_legal_moves(&new_position);
+            if replies.is_empty() {
+                if self.is_check(&new_position) {
+                    return Some(true);
+                }
+                continue;
+            }
+            if plies < 3 {
+                continue;
+            }
+
+            let mut every_reply_mated = true;
+            for reply in replies {
+                let mut reply_position = new_position.clone();
+                reply_position.make_move(reply).ok()?;
+                if !self.forces_mate(&reply_position, plies - 2, nodes)? {
+                    every_reply_mated = false;
+                    break;
+                }
+            }
+            if every_reply_mated {
+                return Some(true);
+            }
+        }
+        Some(false)
+    }
+
+    /// Scores of the root moves that reach above `floor` in a `depth` search, exact
+    /// since only the floor bounds the window. Meant to follow `search` on the same
+    /// position, whose transposition table entries keep it cheap; it stops early at the
+    /// time and node limits, leaving out the moves not yet scored.
+    pub fn score_root_moves(
+        &mut self,
+        position: &Position,
+        depth: u8,
+        floor: i32,
+    ) -> Vec<(Move, i32)> {
+        let depth = depth.clamp(1, MAX_DEPTH);
+        self.nodes_searched = 0;
+        self.start_time = Instant::now();
+        self.root_color = position.side_to_move();
+        self.time_budget = self.config.limits.time_budget(self.root_color);
+        self.current_depth = depth;
+        self.null_move_ply = None;
+        self.key_stack[0] = position.zobrist_hash();
+
+        let mut scores = Vec::new();
+        for mv in self.root_legal_moves(position) {
+            let mut new_position = position.clone();
+            if new_position.make_move(mv).is_err() {
+                continue;
+            }
+            self.record_capture(0, mv.is_capture().then_some(mv.to));
+            let score = -self.alpha_beta(
+                &new_position,
+                depth - 1,
+                -MATE_VALUE,
+                -floor,
+                1,
+                false,
+                None,
+            );
+            if self.should_stop() {
+                break;
+            }
+            if score > floor {
+                scores.push((mv, score));
+            }
+        }
+        scores
+    }
+
     fn aspiration_search(
         &mut self,
         position: &Position,
@@ -151,18 +712,40 @@ impl SearchEngine {
         mut alpha: i32,
         mut beta: i32,
     ) -> SearchResult {
+        let mut first_fail = None;
         let mut attempts = 0;
         loop {
-            let result = self.alpha_beta_root(position, depth, alpha, beta);
+            let mut result = self.alpha_beta_root(position, depth, alpha, beta);
 
-            if result.evaluation > alpha && result.evaluation < beta {
+            let full_window = alpha <= -MATE_VALUE && beta >= MATE_VALUE;
+            let inside = result.evaluation > alpha && result.evaluation < beta;
+            if inside || full_window || self.should_stop() {
+                result.root_fail = first_fail;
                 return result;
             }
 
+            // The score moved since the last iteration: spend longer on this move
+            first_fail.get_or_insert(if result.evaluation <= alpha {
+                RootFail::Low
+            } else {
+                RootFail::High
+            });
+            self.aspiration_researches += 1;
+            self.time_budget = self.unstable_time_budget;
+            crate::log_trace!(
+                "depth {}: score {} outside aspiration window [{}, {}]",
+                depth,
+                result.evaluation,
+                alpha,
+                beta
+            );
+
             attempts += 1;
             if attempts > 3 {
                 // Give up on aspiration windows and search with full window
-                return self.alpha_beta_root(position, depth, -MATE_VALUE, MATE_VALUE);
+                let mut result = self.alpha_beta_root(position, depth, -MATE_VALUE, MATE_VALUE);
+                result.root_fail = first_fail;
+                return result;
             }
 
             // Widen the window
@@ -184,27 +767,37 @@ impl SearchEngine {
     ) -> SearchResult {
         let mut best_move = None;
         let mut pv = [None; 16];
+        self.key_stack[0] = position.zobrist_hash();
 
-        let legal_moves = self.generate_and_sort_moves(position, depth, None);
+        let legal_moves = self.move_picker(position, depth, None);
 
-        for (i, move_data) in legal_moves.iter().enumerate() {
+        let mut i = 0;
+        for move_data in legal_moves {
             if self.should_stop() {
                 break;
             }
 
+            if !self.is_root_move_allowed(move_data.0) {
+                continue;
+            }
+
             let mut new_position = position.clone();
-            if new_position.make_move(move_data.0).is_err() {
+            if new_position.make_move(move_data.0).is_err()
+                || self.leaves_king_in_check(&new_position)
+            {
                 continue;
             }
+            i += 1;
+            self.record_capture(0, move_data.0.is_capture().then_some(move_data.0.to));
 
-            let evaluation = if i == 0 {
+            let evaluation = if i == 1 {
                 // Full search for first move
-                -self.alpha_beta(&new_position, depth - 1, -beta, -alpha, false)
+                -self.alpha_beta(&new_position, depth - 1, -beta, -alpha, 1, false, None)
             } else {
                 // Late move reductions
                 let reduction = if self.config.use_late_move_reductions
                     && depth >= 3
-                    && i >= 4
+                    && i > 4
                     && !move_data.0.is_capture()
                     && !self.is_check(&new_position)
                 {
@@ -214,17 +807,33 @@ impl SearchEngine {
                 };
 
                 let reduced_depth = (depth - 1).saturating_sub(reduction);
-                let mut score =
-                    -self.alpha_beta(&new_position, reduced_depth, -alpha - 1, -alpha, false);
+                let mut score = -self.alpha_beta(
+                    &new_position,
+                    reduced_depth,
+                    -alpha - 1,
+                    -alpha,
+                    1,
+                    false,
+                    None,
+                );
 
                 // Re-search if reduced search failed high
                 if reduction > 0 && score > alpha {
-                    score = -self.alpha_beta(&new_position, depth - 1, -alpha - 1, -alpha, false);
+                    score = -self.alpha_beta(
+                        &new_position,
+                        depth - 1,
+                        -alpha - 1,
+                        -alpha,
+                        1,
+                        false,
+                        None,
+                    );
                 }
 
                 // Re-search with full window if necessary
                 if score > alpha && score < beta {
-                    score = -self.alpha_beta(&new_position, depth - 1, -beta, -alpha, false);
+                    score =
+                        -self.alpha_beta(&new_position, depth - 1, -beta, -alpha, 1, false, None);
                 }
 
                 score
@@ -241,71 +850,192 @@ impl SearchEngine {
             }
         }
 
+        // Mated or stalemated at the root, as opposed to every move being filtered out
+        if i == 0
+            && !self.should_stop()
+            && self
+                .move_generator
+                .generate_legal_moves(position)
+                .is_empty()
+        {
+            alpha = no_moves_score(self.is_check(position), 0, self.draw_score(position));
+        }
+
         SearchResult {
             best_move,
             evaluation: alpha,
             depth,
+            seldepth: self.seldepth.max(depth),
             nodes_searched: self.nodes_searched,
             elapsed_time: self.start_time.elapsed(),
             principal_variation: pv,
+            root_fail: None,
+            aspiration_researches: 0,
         }
     }
 
-    /// Main alpha-beta search with pruning techniques
-    fn alpha_beta(
+    /// Root moves the search may play: legal, kept by the tablebase when it applies, and
+    /// not excluded by `search_excluding`
+    fn root_legal_moves(&self, position: &Position) -> Vec<Move> {
+        let mut moves = self.move_generator.generate_legal_moves(position);
+        moves.retain(|&mv| self.is_root_move_allowed(mv));
+        moves
+    }
+
+    fn is_root_move_allowed(&self, mv: Move) -> bool {
+        Some(mv) != self.excluded_root_move
+            && self
+                .root_moves
+                .as_ref()
+                .map_or(true, |root_moves| root_moves.contains(&mv))
+    }
+
+    /// Whether every root move but `best_move` fails to come within `EASY_MOVE_MARGIN`
+    /// of `score` in a null-window search at half the depth
+    fn is_easy_move(
         &mut self,
         position: &Position,
+        best_move: Move,
         depth: u8,
+        score: i32,
+    ) -> bool {
+        let bound = score - EASY_MOVE_MARGIN;
+        let verify_depth = (depth / 2).max(1);
+        self.key_stack[0] = position.zobrist_hash();
+
+        for mv in self.root_legal_moves(position) {
+            if mv == best_move {
+                continue;
+            }
+            let mut new_position = position.clone();
+            if new_position.make_move(mv).is_err() {
+                continue;
+            }
+            self.record_capture(0, mv.is_capture().then_some(mv.to));
+            let score = -self.alpha_beta(
+                &new_position,
+                verify_depth - 1,
+                -bound,
+                -bound + 1,
+                1,
+                false,
+                None,
+            );
+            if self.should_stop() || score >= bound {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Legal root moves keeping the tablebase result, when the root is in the tables
+    fn tablebase_root_moves(&self, position: &Position) -> Option<Vec<Move>> {
+        let tablebase = self.tablebase.as_deref()?;
+        let legal = self.move_generator.generate_legal_moves(position);
+        filter_root_moves(tablebase, position, &legal).map(|root| root.moves)
+    }
+
+    /// Main alpha-beta search with pruning techniques.
+    ///
+    /// `excluded_move` is left out of the node's moves, for verifying that the table
+    /// move is singular. Such a node answers a different question than the plain one,
+    /// so it neither cuts off on nor stores to the transposition table, skips the
+    /// pruning that assumes the full move list, and scores no moves left as `alpha`
+    /// rather than mate or stalemate.
+    #[allow(clippy::too_many_arguments)]
+    fn alpha_beta(
+        &mut self,
+        position: &Position,
+        mut depth: u8,
         mut alpha: i32,
         mut beta: i32,
+        ply: u8,
         null_move: bool,
+        excluded_move: Option<Move>,
     ) -> i32 {
-        self.nodes_searched += 1;
+        self.count_node(ply);
 
         if self.should_stop() {
             return alpha;
         }
 
+        // The side that just moved may have won by a variant rule, e.g. a third check
+        if position.variant_winner().is_some() {
+            return mated_in(ply);
+        }
+
         // Check for immediate draws
-        if self.is_draw(position) {
-            return 0;
+        if is_rule_draw(position) || self.is_repetition(position, ply) {
+            return self.draw_score(position);
         }
 
         // Mate distance pruning
-        alpha = alpha.max(-MATE_VALUE + position.halfmove_clock() as i32);
-        beta = beta.min(MATE_VALUE - position.halfmove_clock() as i32);
+        alpha = alpha.max(-MATE_VALUE + ply as i32);
+        beta = beta.min(MATE_VALUE - ply as i32);
         if alpha >= beta {
             return alpha;
         }
 
         // Transposition table lookup
+        let excluding = excluded_move.is_some();
         let zobrist = position.zobrist_hash();
-        if let Some(entry) = self.transposition_table.get(&zobrist) {
-            if entry.zobrist_hash == zobrist && entry.depth >= depth {
-                match entry.node_type {
-                    NodeType::Exact => return entry.evaluation,
-                    NodeType::LowerBound if entry.evaluation >= beta => return entry.evaluation,
-                    NodeType::UpperBound if entry.evaluation <= alpha => return entry.evaluation,
-                    _ => {}
+        if let Some(entry) = self
+            .transposition_table
+            .probe(zobrist)
+            .filter(|_| !excluding)
+        {
+            if entry.depth() >= depth {
+                let score = value_from_tt(entry.evaluation(), ply);
+                if let Some(score) = Self::tt_cutoff(entry.node_type(), score, alpha, beta) {
+                    return score;
                 }
             }
         }
 
         // Terminal node evaluation
         if depth == 0 {
-            return self.quiescence_search(position, alpha, beta, 0);
+            return self.quiescence_search(position, alpha, beta, ply, 0);
         }
 
         let in_check = self.is_check(position);
         let static_eval = if in_check {
-            -MATE_VALUE + position.halfmove_clock() as i32
+            -MATE_VALUE + ply as i32
         } else {
             self.evaluator.evaluate(position)
         };
 
+        // Scores near mate must not be pruned on static evaluation alone
+        let window_is_mate = alpha.abs() >= MATE_THRESHOLD || beta.abs() >= MATE_THRESHOLD;
+
+        // Reverse futility pruning: so far above beta that no quiet line will fall back
+        if self.config.use_reverse_futility_pruning
+            && !excluding
+            && !in_check
+            && !window_is_mate
+            && depth <= REVERSE_FUTILITY_MAX_DEPTH
+            && static_eval - self.config.reverse_futility_margin * (depth as i32) >= beta
+        {
+            return static_eval;
+        }
+
+        // Razoring: hopeless unless a capture sequence helps, so let quiescence decide
+        if self.config.use_razoring
+            && !excluding
+            && !in_check
+            && !window_is_mate
+            && depth <= RAZORING_MAX_DEPTH
+            && static_eval + self.config.razoring_margin * (depth as i32) < alpha
+        {
+            let score = self.quiescence_search(position, alpha - 1, alpha, ply, 0);
+            if score < alpha {
+                return score;
+            }
+        }
+
         // Null move pruning
         if self.config.use_null_move_pruning
             && !null_move
+            && !excluding
             && !in_check
             && depth >= 3
             && static_eval >= beta
@@ -313,8 +1043,19 @@ impl SearchEngine {
         {
             let mut null_position = position.clone();
             null_position.make_null_move();
-
-            let null_score = -self.alpha_beta(&null_position, depth - 3, -beta, -beta + 1, true);
+            self.record_capture(ply, None);
+
+            let outer_null_ply = self.null_move_ply.replace(ply);
+            let null_score = -self.alpha_beta(
+                &null_position,
+                depth - 3,
+                -beta,
+                -beta + 1,
+                ply + 1,
+                true,
+                None,
+            );
+            self.null_move_ply = outer_null_ply;
             if null_score >= beta {
                 return beta; // Fail high
             }
@@ -322,6 +1063,7 @@ impl SearchEngine {
 
         // Futility pruning
         if self.config.use_futility_pruning
+            && !excluding
             && !in_check
             && depth <= 3
             && static_eval + 200 * depth as i32 <= alpha
@@ -329,34 +1071,102 @@ impl SearchEngine {
             return static_eval;
         }
 
-        let legal_moves = self.generate_and_sort_moves(
-            position,
-            depth,
-            self.transposition_table
-                .get(&zobrist)
-                .and_then(|e| e.best_move),
-        );
+        let tt_move = self
+            .transposition_table
+            .probe(zobrist)
+            .and_then(|e| e.best_move());
 
-        if legal_moves.is_empty() {
-            return if in_check {
-                -MATE_VALUE + position.halfmove_clock() as i32 // Checkmate
-            } else {
-                0 // Stalemate
-            };
+        // Internal iterative reduction: without a hash move ordering is poor, so search
+        // one ply shallower; the next iteration finds the table move and searches fully
+        if self.config.use_internal_iterative_reduction
+            && tt_move.is_none()
+            && !in_check
+            && depth >= IIR_MIN_DEPTH
+        {
+            depth -= 1;
         }
 
+        let singular_move = tt_move.filter(|&tt_move| {
+            self.config.use_singular_extensions
+                && !excluding
+                && depth >= SINGULAR_MIN_DEPTH
+                && self.is_singular(position, tt_move, depth, ply)
+        });
+
+        let legal_moves = self.move_picker(position, depth, tt_move);
+
         let mut best_move = None;
         let mut node_type = NodeType::UpperBound;
         let mut moves_searched = 0;
 
         for (move_item, _score) in legal_moves {
+            if Some(move_item) == excluded_move {
+                continue;
+            }
+
             let mut new_position = position.clone();
-            if new_position.make_move(move_item).is_err() {
+            if new_position.make_move(move_item).is_err()
+                || self.leaves_king_in_check(&new_position)
+            {
+                continue;
+            }
+
+            // SEE pruning: a quiet move that hands material over is not worth a search
+            // this close to the leaves, as long as something has already been searched
+            if self.config.use_see_pruning
+                && !move_item.is_capture()
+                && !move_item.is_promotion()
+                && moves_searched > 0
+                && !in_check
+                && !window_is_mate
+                && depth <= SEE_PRUNING_MAX_DEPTH
+                && Some(move_item) != tt_move
+                && !self.is_check(&new_position)
+                && self.see(position, move_item) < -self.config.see_quiet_margin * depth as i32
+            {
                 continue;
             }
 
             moves_searched += 1;
-            let evaluation = -self.alpha_beta(&new_position, depth - 1, -beta, -alpha, false);
+            let new_depth = depth - 1 + self.extension(position, move_item, ply, singular_move);
+            self.record_capture(ply, move_item.is_capture().then_some(move_item.to));
+
+            // Principal variation search: once the first move has set alpha, the rest only
+            // need to prove they are no better, which a null window does cheaply
+            let evaluation = if moves_searched == 1 || !self.config.use_principal_variation_search {
+                -self.alpha_beta(
+                    &new_position,
+                    new_depth,
+                    -beta,
+                    -alpha,
+                    ply + 1,
+                    false,
+                    None,
+                )
+            } else {
+                let score = -self.alpha_beta(
+                    &new_position,
+                    new_depth,
+                    -alpha - 1,
+                    -alpha,
+                    ply + 1,
+                    false,
+                    None,
+                );
+                if score > alpha && score < beta {
+                    -self.alpha_beta(
+                        &new_position,
+                        new_depth,
+                        -beta,
+                        -alpha,
+                        ply + 1,
+                        false,
+                        None,
+                    )
+                } else {
+                    score
+                }
+            };
 
             if evaluation > alpha {
                 alpha = evaluation;
@@ -378,70 +1188,187 @@ impl SearchEngine {
             }
         }
 
-        // Store in transposition table
-        self.store_transposition(zobrist, depth, alpha, best_move, node_type);
+        if moves_searched == 0 {
+            return if excluding {
+                alpha
+            } else {
+                no_moves_score(in_check, ply, self.draw_score(position))
+            };
+        }
+
+        // Cut short, the moves searched so far prove no bound for the table
+        if self.should_stop() {
+            return alpha;
+        }
 
-        // Update nodes searched based on moves examined
-        if moves_searched > 0 {
-            self.nodes_searched += moves_searched as u64;
+        // Store in transposition table
+        if !excluding {
+            self.store_transposition(
+                zobrist,
+                depth,
+                value_to_tt(alpha, ply),
+                best_move,
+                node_type,
+            );
         }
 
         alpha
     }
 
     /// Quiescence search for tactical positions
+    ///
+    /// When the side to move is in check there is no stand-pat: every evasion is
+    /// searched, and a position without a legal evasion is scored as mate.
+    ///
+    /// Results share the transposition table with the main search at
+    /// `QSEARCH_TT_DEPTH`, so any entry is deep enough for a quiescence probe.
+    /// `ply` counts from the root for mate scores, `qsearch_ply` from the horizon.
     fn quiescence_search(
         &mut self,
         position: &Position,
         mut alpha: i32,
         beta: i32,
         ply: u8,
+        qsearch_ply: u8,
     ) -> i32 {
-        self.nodes_searched += 1;
+        self.count_node(ply);
 
-        if ply > 16 || self.should_stop() {
+        if qsearch_ply > MAX_QSEARCH_PLY || self.should_stop() {
             return self.evaluator.evaluate(position);
         }
 
-        let static_eval = self.evaluator.evaluate(position);
+        if position.variant_winner().is_some() {
+            return mated_in(ply);
+        }
 
-        if static_eval >= beta {
-            return beta;
+        // Captures can reach a draw by material, and checks a drawn fifty-move count
+        if is_rule_draw(position) {
+            return self.draw_score(position);
         }
 
-        if static_eval > alpha {
-            alpha = static_eval;
+        let zobrist = position.zobrist_hash();
+        let use_tt = self.config.use_qsearch_transposition_table;
+        if use_tt {
+            if let Some(entry) = self.transposition_table.probe(zobrist) {
+                let score = value_from_tt(entry.evaluation(), ply);
+                if let Some(score) = Self::tt_cutoff(entry.node_type(), score, alpha, beta) {
+                    return score;
+                }
+            }
+        }
+
+        let in_check = self.is_check(position);
+        let original_alpha = alpha;
+
+        let mut stand_pat = None;
+        if !in_check {
+            let static_eval = self.evaluator.evaluate(position);
+
+            if static_eval >= beta {
+                if use_tt {
+                    self.store_transposition(
+                        zobrist,
+                        QSEARCH_TT_DEPTH,
+                        value_to_tt(beta, ply),
+                        None,
+                        NodeType::LowerBound,
+                    );
+                }
+                return beta;
+            }
+
+            if static_eval > alpha {
+                alpha = static_eval;
+            }
+            stand_pat = Some(static_eval);
         }
 
-        // Generate only captures and checks
-        let captures = self.generate_tactical_moves(position);
+        // In check every move is a candidate evasion, otherwise only captures
+        let moves = if in_check {
+            self.move_generator.generate_pseudo_legal_moves(position)
+        } else {
+            self.generate_tactical_moves(position)
+        };
+        let moves = if self.config.use_qsearch_move_ordering {
+            MovePicker::new(
+                moves
+                    .into_iter()
+                    .map(|move_item| (move_item, self.score_tactical_move(move_item, position)))
+                    .collect(),
+            )
+        } else {
+            MovePicker::in_order(moves)
+        };
+
+        let mut legal_moves = 0;
+        let mut best_move = None;
+        for (move_item, _) in moves {
+            // Delta pruning: even winning the captured piece outright leaves us below alpha
+            if let Some(static_eval) = stand_pat {
+                if self.config.use_delta_pruning
+                    && !move_item.is_promotion()
+                    && static_eval
+                        + self.captured_value(move_item, position)
+                        + self.config.delta_margin
+                        <= alpha
+                {
+                    continue;
+                }
+            }
 
-        for move_item in captures {
             let mut new_position = position.clone();
             if new_position.make_move(move_item).is_err() {
                 continue;
             }
 
-            let evaluation = -self.quiescence_search(&new_position, -beta, -alpha, ply + 1);
+            if self.leaves_king_in_check(&new_position) {
+                continue;
+            }
+            legal_moves += 1;
+
+            let evaluation =
+                -self.quiescence_search(&new_position, -beta, -alpha, ply + 1, qsearch_ply + 1);
 
             if evaluation > alpha {
                 alpha = evaluation;
+                best_move = Some(move_item);
                 if alpha >= beta {
+                    if use_tt && !self.should_stop() {
+                        self.store_transposition(
+                            zobrist,
+                            QSEARCH_TT_DEPTH,
+                            value_to_tt(beta, ply),
+                            best_move,
+                            NodeType::LowerBound,
+                        );
+                    }
                     return beta; // Beta cutoff
                 }
             }
         }
 
+        if in_check && legal_moves == 0 {
+            return mated_in(ply);
+        }
+
+        if use_tt && !self.should_stop() {
+            let node_type = if alpha > original_alpha {
+                NodeType::Exact
+            } else {
+                NodeType::UpperBound
+            };
+            let score = value_to_tt(alpha, ply);
+            self.store_transposition(zobrist, QSEARCH_TT_DEPTH, score, best_move, node_type);
+        }
+
         alpha
     }
 
-    fn generate_and_sort_moves(
-        &self,
-        position: &Position,
-        depth: u8,
-        tt_move: Option<Move>,
-    ) -> Vec<(Move, i32)> {
-        let moves = self.move_generator.generate_legal_moves(position);
+    /// The node's moves, handed out highest score first as they are searched
+    fn move_picker(&self, position: &Position, depth: u8, tt_move: Option<Move>) -> MovePicker {
+        // Moves leaving the king attacked are skipped once made, which is cheaper than
+        // testing each one here
+        let moves = self.move_generator.generate_pseudo_legal_moves(position);
         let mut scored_moves = Vec::with_capacity(moves.len());
 
         for move_item in moves {
@@ -449,14 +1376,13 @@ impl SearchEngine {
             scored_moves.push((move_item, score));
         }
 
-        // Sort moves by score (highest first)
-        scored_moves.sort_by(|a, b| b.1.cmp(&a.1));
-        scored_moves
+        MovePicker::new(scored_moves)
     }
 
     fn generate_tactical_moves(&self, position: &Position) -> Vec<Move> {
-        // For now, return all legal moves - could be optimized to only return captures/checks
-        self.move_generator.generate_legal_moves(position)
+        let mut moves = self.move_generator.generate_pseudo_legal_moves(position);
+        moves.retain(|move_item| move_item.is_capture() || move_item.is_promotion());
+        moves
     }
 
     fn score_move(
@@ -477,15 +1403,15 @@ impl SearchEngine {
         if move_item.is_capture() {
             if let Some(captured_piece) = position.piece_at(move_item.to) {
                 if let Some(attacker_piece) = position.piece_at(move_item.from) {
-                    score += Self::piece_value(captured_piece.piece_type) * 10
-                        - Self::piece_value(attacker_piece.piece_type);
+                    score += self.piece_value(captured_piece.piece_type) * 10
+                        - self.piece_value(attacker_piece.piece_type);
                 }
             }
         }
 
         // Promotions
         if let Some(promotion_piece) = move_item.promotion_piece() {
-            score += Self::piece_value(promotion_piece) - 100; // Pawn value
+            score += self.piece_value(promotion_piece) - self.piece_value(PieceType::Pawn);
         }
 
         // Killer moves
@@ -509,15 +1435,126 @@ impl SearchEngine {
         score
     }
 
-    fn piece_value(piece_type: PieceType) -> i32 {
-        match piece_type {
-            PieceType::Pawn => 100,
-            PieceType::Knight => 320,
-            PieceType::Bishop => 330,
-            PieceType::Rook => 500,
-            PieceType::Queen => 900,
-            PieceType::King => 20000,
+    fn see(&self, position: &Position, move_item: Move) -> i32 {
+        see(
+            position,
+            move_item,
+            &self.move_generator,
+            self.evaluator.params(),
+        )
+    }
+
+    /// Extra depth for a move made at `ply`: one ply for a passed pawn reaching the
+    /// sixth or seventh rank, or for a recapture. Extensions stop at twice the iteration
+    /// depth so forcing lines cannot grow without bound.
+    fn extension(
+        &self,
+        position: &Position,
+        move_item: Move,
+        ply: u8,
+        singular_move: Option<Move>,
+    ) -> u8 {
+        if ply as u16 >= 2 * self.current_depth as u16 {
+            return 0;
+        }
+
+        if singular_move == Some(move_item) {
+            return 1;
+        }
+
+        if self.config.use_recapture_extension && move_item.is_capture() && ply > 0 {
+            let previous = self
+                .capture_squares
+                .get(ply as usize - 1)
+                .copied()
+                .flatten();
+            if previous == Some(move_item.to) {
+                return 1;
+            }
+        }
+
+        if self.config.use_passed_pawn_extension {
+            if let Some(piece) = position.piece_at(move_item.from) {
+                let enemy_pawns = position.piece_bitboard(PieceType::Pawn, piece.color.opposite());
+                if piece.piece_type == PieceType::Pawn
+                    && relative_rank(piece.color, move_item.to) >= 5
+                    && (enemy_pawns & passed_pawn_mask(piece.color, move_item.to)).is_empty()
+                {
+                    return 1;
+                }
+            }
+        }
+
+        0
+    }
+
+    /// Whether `tt_move` is the only good move: a reduced search of every other move,
+    /// against a bound a margin below the table score, fails low. Only table entries
+    /// that are lower bounds or exact and nearly as deep as `depth` qualify.
+    fn is_singular(&mut self, position: &Position, tt_move: Move, depth: u8, ply: u8) -> bool {
+        let Some(entry) = self.transposition_table.probe(position.zobrist_hash()) else {
+            return false;
+        };
+        if entry.node_type() == NodeType::UpperBound
+            || entry.depth() + SINGULAR_TT_DEPTH_MARGIN < depth
+        {
+            return false;
+        }
+        let tt_score = value_from_tt(entry.evaluation(), ply);
+        if tt_score.abs() >= MATE_THRESHOLD {
+            return false;
+        }
+
+        let singular_beta = tt_score - SINGULAR_MARGIN * depth as i32;
+        let score = self.alpha_beta(
+            position,
+            (depth - 1) / 2,
+            singular_beta - 1,
+            singular_beta,
+            ply,
+            false,
+            Some(tt_move),
+        );
+        score < singular_beta
+    }
+
+    fn record_capture(&mut self, ply: u8, square: Option<Square>) {
+        if let Some(slot) = self.capture_squares.get_mut(ply as usize) {
+            *slot = square;
+        }
+    }
+
+    /// MVV-LVA score for quiescence ordering, with queen promotions searched first
+    fn score_tactical_move(&self, move_item: Move, position: &Position) -> i32 {
+        let mut score = 0;
+
+        if move_item.is_capture() {
+            let attacker = position
+                .piece_at(move_item.from)
+                .map_or(0, |piece| self.piece_value(piece.piece_type));
+            score += self.captured_value(move_item, position) * 10 - attacker;
+        }
+
+        if move_item.promotion_piece() == Some(PieceType::Queen) {
+            score += QUEEN_PROMOTION_ORDER_BONUS;
+        }
+
+        score
+    }
+
+    /// Value of the piece a move captures, counting en passant as a pawn
+    fn captured_value(&self, move_item: Move, position: &Position) -> i32 {
+        if move_item.is_en_passant() {
+            return self.piece_value(PieceType::Pawn);
         }
+        position
+            .piece_at(move_item.to)
+            .map_or(0, |piece| self.piece_value(piece.piece_type))
+    }
+
+    /// Material value used by move ordering, read from the evaluator's parameters
+    fn piece_value(&self, piece_type: PieceType) -> i32 {
+        self.evaluator.params().piece_value(piece_type).middlegame
     }
 
     fn store_killer_move(&mut self, move_item: Move, depth: u8) {
@@ -543,42 +1580,117 @@ impl SearchEngine {
         best_move: Option<Move>,
         node_type: NodeType,
     ) {
-        let entry = TranspositionEntry {
-            zobrist_hash: zobrist,
-            depth,
-            evaluation,
-            best_move,
-            node_type,
-            age: self.age,
+        self.transposition_table
+            .store_optional_move(zobrist, evaluation, depth, node_type, best_move);
+    }
+
+    /// The score a table entry proves for the window, if it is enough to cut off
+    fn tt_cutoff(node_type: NodeType, evaluation: i32, alpha: i32, beta: i32) -> Option<i32> {
+        match node_type {
+            NodeType::Exact => Some(evaluation),
+            NodeType::LowerBound if evaluation >= beta => Some(evaluation),
+            NodeType::UpperBound if evaluation <= alpha => Some(evaluation),
+            _ => None,
+        }
+    }
+
+    fn is_check(&self, position: &Position) -> bool {
+        self.is_king_attacked(position, position.side_to_move())
+    }
+
+    /// True if the side that just moved left its own king attacked
+    fn leaves_king_in_check(&self, position: &Position) -> bool {
+        self.is_king_attacked(position, position.side_to_move().opposite())
+    }
+
+    fn is_king_attacked(&self, position: &Position, color: Color) -> bool {
+        self.move_generator.king_attacked(position, color)
+    }
+
+    /// True when the position already occurred on the current line or earlier in the
+    /// game. A single repetition is enough, since the side that allowed it could repeat
+    /// again.
+    fn is_repetition(&mut self, position: &Position, ply: u8) -> bool {
+        let key = position.zobrist_hash();
+        let Some(slot) = self.key_stack.get_mut(ply as usize) else {
+            return false;
         };
+        *slot = key;
 
-        // Simple replacement scheme - could be improved
-        self.transposition_table.insert(zobrist, entry);
+        // Positions before the last irreversible move or null move cannot recur
+        let reversible = position.halfmove_clock().min(ply as u32) as u8;
+        let floor = match self.null_move_ply {
+            Some(null_ply) => (ply - reversible).max(null_ply + 1),
+            None => ply - reversible,
+        };
+        let on_line = (floor..ply.saturating_sub(1))
+            .rev()
+            .step_by(2)
+            .any(|earlier| self.key_stack[earlier as usize] == key);
+        if on_line {
+            return true;
+        }
+
+        self.null_move_ply.is_none()
+            && position.halfmove_clock() >= ply as u32
+            && self.game_keys.contains(&key)
     }
 
-    fn is_check(&self, _position: &Position) -> bool {
-        // This would need to be implemented based on your position structure
-        false // Placeholder
+    /// Draw value from the side to move's point of view
+    fn draw_score(&self, position: &Position) -> i32 {
+        if position.side_to_move() == self.root_color {
+            self.root_draw_score
+        } else {
+            -self.root_draw_score
+        }
+    }
+
+    /// Draw value for the root side, given its expected score
+    fn draw_score_for(&self, root_estimate: i32) -> i32 {
+        let dynamic = if self.config.use_dynamic_contempt {
+            ((root_estimate - DYNAMIC_CONTEMPT_THRESHOLD) / 4).clamp(0, DYNAMIC_CONTEMPT_MAX)
+        } else {
+            0
+        };
+        -(self.config.contempt + dynamic)
     }
 
-    fn is_draw(&self, position: &Position) -> bool {
-        // Check for 50-move rule, threefold repetition, insufficient material, etc.
-        position.halfmove_clock() >= 100 // 50-move rule
+    /// Null moves are unsafe with only king and pawns, where zugzwang is common
+    fn has_non_pawn_pieces(&self, position: &Position) -> bool {
+        position.non_pawn_material(position.side_to_move()) > 0
     }
 
-    fn has_non_pawn_pieces(&self, _position: &Position) -> bool {
-        // Check if the side to move has pieces other than pawns and king
-        true // Placeholder - would need actual implementation
+    fn count_node(&mut self, ply: u8) {
+        self.nodes_searched += 1;
+        self.seldepth = self.seldepth.max(ply);
+        if self.nodes_searched % STATUS_PUBLISH_INTERVAL == 0 {
+            self.publish_status();
+        }
+    }
+
+    fn publish_status(&self) {
+        self.status.publish(
+            self.current_depth,
+            self.seldepth.max(self.current_depth),
+            self.nodes_searched,
+            self.hashfull(),
+        );
     }
 
     fn should_stop(&self) -> bool {
-        if let Some(max_time) = self.config.max_time {
-            if self.start_time.elapsed() >= max_time {
+        if let Some(stop_signal) = &self.stop_signal {
+            if stop_signal.load(Ordering::Relaxed) {
                 return true;
             }
         }
 
-        if let Some(max_nodes) = self.config.max_nodes {
+        if let Some(time_budget) = self.time_budget {
+            if self.start_time.elapsed() >= time_budget {
+                return true;
+            }
+        }
+
+        if let Some(max_nodes) = self.config.limits.nodes {
             if self.nodes_searched >= max_nodes {
                 return true;
             }
@@ -595,15 +1707,773 @@ mod tests {
     #[test]
     fn test_search_config_default() {
         let config = SearchConfig::default();
-        assert_eq!(config.max_depth, 8);
+        assert_eq!(config.limits.depth, Some(8));
         assert!(config.use_null_move_pruning);
         assert!(config.use_late_move_reductions);
     }
 
+    #[test]
+    fn test_razoring_and_reverse_futility_reduce_nodes() {
+        use crate::search::bench::{run_bench_on, BENCH_POSITIONS};
+
+        // Quiet positions keep the unpruned debug-build search fast
+        let positions = [
+            BENCH_POSITIONS[0],
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ];
+        let unpruned = SearchConfig {
+            limits: SearchLimits::default().with_depth(3),
+            use_razoring: false,
+            use_reverse_futility_pruning: false,
+            ..SearchConfig::default()
+        };
+        let pruned = SearchConfig {
+            use_razoring: true,
+            use_reverse_futility_pruning: true,
+            ..unpruned.clone()
+        };
+
+        let baseline = run_bench_on(&unpruned, &positions);
+        let reduced = run_bench_on(&pruned, &positions);
+        assert_eq!(baseline.positions, reduced.positions);
+        assert!(
+            reduced.nodes < baseline.nodes,
+            "pruning searched {} nodes, baseline {}",
+            reduced.nodes,
+            baseline.nodes
+        );
+    }
+
+    #[test]
+    fn test_internal_iterative_reduction_reduces_nodes() {
+        use crate::search::bench::run_bench_on;
+
+        // Endgames reach depths where the reduction matters while staying fast in debug builds
+        let positions = [
+            "8/8/4k3/3p4/3P4/4K3/8/8 w - - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ];
+        let without = SearchConfig {
+            limits: SearchLimits::default().with_depth(6),
+            use_internal_iterative_reduction: false,
+            ..SearchConfig::default()
+        };
+        let with = SearchConfig {
+            use_internal_iterative_reduction: true,
+            ..without.clone()
+        };
+
+        let baseline = run_bench_on(&without, &positions);
+        let reduced = run_bench_on(&with, &positions);
+        assert!(
+            reduced.nodes < baseline.nodes,
+            "IIR searched {} nodes, baseline {}",
+            reduced.nodes,
+            baseline.nodes
+        );
+    }
+
+    #[test]
+    fn test_principal_variation_search_reduces_nodes() {
+        use crate::search::bench::run_bench_on;
+
+        let positions = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ];
+        let without = SearchConfig {
+            limits: SearchLimits::default().with_depth(4),
+            use_principal_variation_search: false,
+            ..SearchConfig::default()
+        };
+        let with = SearchConfig {
+            use_principal_variation_search: true,
+            ..without.clone()
+        };
+
+        let baseline = run_bench_on(&without, &positions);
+        let pvs = run_bench_on(&with, &positions);
+        assert!(
+            pvs.nodes < baseline.nodes,
+            "PVS searched {} nodes, baseline {}",
+            pvs.nodes,
+            baseline.nodes
+        );
+    }
+
+    #[test]
+    fn test_qsearch_ordering_and_delta_pruning_reduce_nodes() {
+        use crate::search::bench::run_bench_on;
+
+        let unordered = SearchConfig {
+            limits: SearchLimits::default().with_depth(3),
+            use_qsearch_move_ordering: false,
+            use_delta_pruning: false,
+            ..SearchConfig::default()
+        };
+        let ordered = SearchConfig {
+            use_qsearch_move_ordering: true,
+            ..unordered.clone()
+        };
+        let delta = SearchConfig {
+            use_delta_pruning: true,
+            ..ordered.clone()
+        };
+
+        let quiet = ["r1bqkb1r/pppp1ppp/2n2n2/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4"];
+        let baseline = run_bench_on(&unordered, &quiet).nodes;
+        let sorted = run_bench_on(&ordered, &quiet).nodes;
+        assert!(sorted < baseline, "ordered {sorted}, unordered {baseline}");
+
+        // Many hanging pieces: without ordering this position's quiescence explodes
+        let tactical = ["r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"];
+        let sorted = run_bench_on(&ordered, &tactical).nodes;
+        let pruned = run_bench_on(&delta, &tactical).nodes;
+        assert!(pruned < sorted, "delta pruned {pruned}, ordered {sorted}");
+    }
+
+    #[test]
+    fn test_qsearch_transposition_table_reduces_nodes() {
+        use crate::search::bench::run_bench_on;
+
+        let positions = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r1bq1rk1/ppp2ppp/2np1n2/2b1p3/2B1P3/2NP1N2/PPP2PPP/R1BQ1RK1 w - - 0 7",
+        ];
+        let without = SearchConfig {
+            limits: SearchLimits::default().with_depth(3),
+            use_qsearch_transposition_table: false,
+            ..SearchConfig::default()
+        };
+        let with = SearchConfig {
+            use_qsearch_transposition_table: true,
+            ..without.clone()
+        };
+
+        let baseline = run_bench_on(&without, &positions).nodes;
+        let cached = run_bench_on(&with, &positions).nodes;
+        assert!(
+            cached < baseline,
+            "qsearch TT {cached}, baseline {baseline}"
+        );
+    }
+
+    #[test]
+    fn test_see_pruning_reduces_nodes() {
+        use crate::search::bench::run_bench_on;
+
+        let positions = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r1bq1rk1/ppp2ppp/2np1n2/2b1p3/2B1P3/2NP1N2/PPP2PPP/R1BQ1RK1 w - - 0 7",
+        ];
+        let without = SearchConfig {
+            limits: SearchLimits::default().with_depth(4),
+            use_see_pruning: false,
+            ..SearchConfig::default()
+        };
+        let with = SearchConfig {
+            use_see_pruning: true,
+            ..without.clone()
+        };
+
+        let baseline = run_bench_on(&without, &positions).nodes;
+        let pruned = run_bench_on(&with, &positions).nodes;
+        assert!(
+            pruned < baseline,
+            "SEE pruned {pruned}, baseline {baseline}"
+        );
+    }
+
+    #[test]
+    fn test_passed_pawn_and_recapture_extensions() {
+        let mut engine = SearchEngine::new(SearchConfig::default());
+        engine.current_depth = 4;
+
+        // d6-d7 is a free passer; b5-b6 still faces the a7 pawn
+        let position = Position::from_fen("4k3/p7/3P4/1P6/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            engine.extension(&position, Move::normal(Square::D6, Square::D7), 1, None),
+            1
+        );
+        assert_eq!(
+            engine.extension(&position, Move::normal(Square::B5, Square::B6), 1, None),
+            0
+        );
+        // No extensions past twice the iteration depth
+        assert_eq!(
+            engine.extension(&position, Move::normal(Square::D6, Square::D7), 8, None),
+            0
+        );
+
+        let position = Position::from_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1").unwrap();
+        let recapture = Move::capture(Square::E4, Square::D5);
+        assert_eq!(engine.extension(&position, recapture, 2, None), 0);
+        engine.record_capture(1, Some(Square::D5));
+        assert_eq!(engine.extension(&position, recapture, 2, None), 1);
+
+        let mut quiet = engine;
+        quiet.config.use_recapture_extension = false;
+        assert_eq!(quiet.extension(&position, recapture, 2, None), 0);
+    }
+
+    #[test]
+    fn test_draw_score_follows_contempt_and_root_advantage() {
+        let mut engine = SearchEngine::new(SearchConfig {
+            contempt: 10,
+            ..SearchConfig::default()
+        });
+        assert_eq!(engine.draw_score_for(0), -10);
+        assert_eq!(engine.draw_score_for(-500), -10);
+        assert_eq!(engine.draw_score_for(300), -10 - DYNAMIC_CONTEMPT_MAX);
+
+        engine.root_color = Color::White;
+        engine.root_draw_score = engine.draw_score_for(300);
+        let white = Position::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        let black = Position::from_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap();
+        assert_eq!(engine.draw_score(&white), -60);
+        assert_eq!(engine.draw_score(&black), 60);
+
+        engine.config.use_dynamic_contempt = false;
+        assert_eq!(engine.draw_score_for(300), -10);
+    }
+
+    #[test]
+    fn test_repetition_detection() {
+        let mut engine = SearchEngine::new(SearchConfig::default());
+        let root = Position::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 4 10").unwrap();
+        engine.key_stack[0] = root.zobrist_hash();
+
+        // The same position four plies later repeats the root
+        let again = Position::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 8 12").unwrap();
+        assert!(engine.is_repetition(&again, 4));
+
+        // Not across a null move, nor past an irreversible move
+        engine.null_move_ply = Some(1);
+        assert!(!engine.is_repetition(&again, 4));
+        engine.null_move_ply = None;
+        let reset = Position::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 2 12").unwrap();
+        assert!(!engine.is_repetition(&reset, 4));
+
+        // Positions from the game before the root count as well
+        engine.set_game_history([reset.zobrist_hash()]);
+        assert!(engine.is_repetition(&reset, 2));
+    }
+
+    #[test]
+    fn test_winning_side_avoids_game_repetitions() {
+        let position = Position::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        let generator = MoveGenerator::new();
+        let keep = Move::normal(Square::D1, Square::D4);
+
+        // Every other move returns to a position already seen in the game
+        let repeated = generator
+            .generate_legal_moves(&position)
+            .into_iter()
+            .filter(|&move_item| move_item != keep)
+            .filter_map(|move_item| {
+                let mut next = position.clone();
+                next.make_move(move_item).ok()?;
+                Some(next.zobrist_hash())
+            });
+
+        let mut engine = SearchEngine::new(SearchConfig {
+            limits: SearchLimits::default().with_depth(2),
+            ..SearchConfig::default()
+        });
+        engine.set_game_history(repeated);
+        let result = engine.search(&position);
+        assert_eq!(result.best_move, Some(keep));
+        assert!(result.evaluation > 0);
+    }
+
+    #[test]
+    fn test_mate_scores_round_trip_through_table() {
+        for ply in [0, 1, 7, 40] {
+            for score in [0, 250, -900, MATE_VALUE - 5, -MATE_VALUE + 12] {
+                assert_eq!(value_from_tt(value_to_tt(score, ply), ply), score);
+            }
+        }
+        // A mate found 3 plies below a node at ply 4 is stored as mate in 3 from the node
+        assert_eq!(value_to_tt(MATE_VALUE - 7, 4), MATE_VALUE - 3);
+        assert_eq!(value_from_tt(MATE_VALUE - 3, 2), MATE_VALUE - 5);
+    }
+
+    #[test]
+    fn test_mate_distance_is_stable_with_warm_table() {
+        let config = SearchConfig {
+            limits: SearchLimits::default().with_depth(5),
+            ..SearchConfig::default()
+        };
+        // Kf7 then Rh1 mates: mate in two moves, three plies
+        let position = Position::from_fen("7k/8/5K2/8/8/8/8/6R1 w - - 0 1").unwrap();
+        let mut engine = SearchEngine::new(config);
+
+        let first = engine.search(&position);
+        assert_eq!(first.evaluation, MATE_VALUE - 3);
+
+        // The second search starts from table entries stored at many different plies
+        let second = engine.search(&position);
+        assert_eq!(second.evaluation, MATE_VALUE - 3);
+        assert_eq!(second.best_move, first.best_move);
+    }
+
+    #[test]
+    fn test_search_is_colour_symmetric() {
+        let config = SearchConfig {
+            limits: SearchLimits::default().with_depth(3),
+            ..SearchConfig::default()
+        };
+        // The same position with colours swapped must get the same side-to-move score
+        let black = Position::from_fen("4k3/8/8/3q4/8/2N5/8/4K3 b - - 0 1").unwrap();
+        let white = Position::from_fen("4k3/8/2n5/8/3Q4/8/8/4K3 w - - 0 1").unwrap();
+
+        let black_result = SearchEngine::new(config.clone()).search(&black);
+        let white_result = SearchEngine::new(config).search(&white);
+        assert_eq!(black_result.evaluation, white_result.evaluation);
+        assert!(black_result.evaluation > 500);
+    }
+
+    #[test]
+    fn test_pruning_keeps_mate_in_one() {
+        let mut engine = SearchEngine::new(SearchConfig {
+            limits: SearchLimits::default().with_depth(3),
+            ..SearchConfig::default()
+        });
+        let position = Position::from_fen("6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - 0 1").unwrap();
+        let result = engine.search(&position);
+        assert_eq!(
+            result.best_move.map(|mv| mv.to_string()).as_deref(),
+            Some("d1d8")
+        );
+        assert!(result.evaluation > MATE_VALUE - 100);
+    }
+
+    #[test]
+    fn test_variant_wins_end_the_search() {
+        let mut engine = SearchEngine::new(SearchConfig {
+            limits: SearchLimits::default().with_depth(3),
+            ..SearchConfig::default()
+        });
+        // White needs one more check, which any rook check on the back rank gives
+        let position = Position::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 1+3 0 1").unwrap();
+        let result = engine.search(&position);
+        assert_eq!(result.evaluation, MATE_VALUE - 1);
+        let mut next = position.clone();
+        next.make_move(result.best_move.unwrap()).unwrap();
+        assert_eq!(next.variant_winner(), Some(Color::White));
+    }
+
+    #[test]
+    fn test_status_reports_progress() {
+        let mut engine = SearchEngine::new(SearchConfig {
+            limits: SearchLimits::default().with_depth(3),
+            ..SearchConfig::default()
+        });
+        let handle = engine.status_handle();
+
+        let result = std::thread::scope(|scope| {
+            let search = scope.spawn(|| engine.search(&Position::starting_position()));
+            let _ = handle.snapshot();
+            search.join().unwrap()
+        });
+
+        let status = handle.snapshot();
+        assert_eq!(status.nodes, result.nodes_searched);
+        assert_eq!(status.depth, result.depth);
+        assert_eq!(status.elapsed.as_micros(), result.elapsed_time.as_micros());
+        assert!(status.hashfull <= 1000);
+        assert_eq!(status.tbhits, 0);
+        assert_eq!(engine.status().nodes, status.nodes);
+    }
+
+    #[test]
+    fn test_nodes_are_counted_once() {
+        // White's only move is Kb1, after which Black has Ka4 and Kb4. Depth 1 visits
+        // Kb1 in alpha-beta and quiescence; depth 2 adds Kb1 at depth 1 and each reply
+        // in alpha-beta and quiescence.
+        let position = Position::from_fen("8/8/8/8/8/k7/P7/K7 w - - 0 1").unwrap();
+        let mut engine = SearchEngine::new(SearchConfig {
+            limits: SearchLimits::default().with_depth(2),
+            ..SearchConfig::default()
+        });
+
+        let result = engine.search(&position);
+        assert_eq!(result.depth, 2);
+        assert_eq!(result.nodes_searched, 2 + 1 + 2 * 2);
+        assert_eq!(engine.status().nodes, result.nodes_searched);
+    }
+
+    #[test]
+    fn test_stopped_node_is_not_stored() {
+        // The node limit runs out while Kb1 is searched to depth 1, after Ka4 but
+        // before Kb4, so Kb1 has no depth 1 score to store
+        let position = Position::from_fen("8/8/8/8/8/k7/P7/K7 w - - 0 1").unwrap();
+        let mut engine = SearchEngine::new(SearchConfig {
+            limits: SearchLimits::default().with_depth(2).with_nodes(5),
+            ..SearchConfig::default()
+        });
+        let result = engine.search(&position);
+        assert_eq!(result.depth, 1);
+
+        let mut after = position.clone();
+        after
+            .make_move(Move::normal(Square::A1, Square::B1))
+            .unwrap();
+        let entry = engine.transposition_table.probe(after.zobrist_hash());
+        assert!(entry.map_or(true, |entry| entry.depth() == QSEARCH_TT_DEPTH));
+    }
+
+    #[test]
+    fn test_stop_signal_ends_the_search() {
+        let mut engine = SearchEngine::new(SearchConfig {
+            limits: SearchLimits::default().with_depth(MAX_DEPTH),
+            ..SearchConfig::default()
+        });
+        let stop = Arc::new(AtomicBool::new(false));
+        engine.set_stop_signal(Some(Arc::clone(&stop)));
+
+        let result = std::thread::scope(|scope| {
+            let search = scope.spawn(|| engine.search(&Position::starting_position()));
+            std::thread::sleep(Duration::from_millis(100));
+            stop.store(true, Ordering::Relaxed);
+            search.join().unwrap()
+        });
+        assert!(result.depth >= 1 && result.depth < MAX_DEPTH);
+        assert!(result.best_move.is_some());
+
+        // A raised flag stops later searches before their first iteration
+        let result = engine.search(&Position::starting_position());
+        assert_eq!(result.depth, 0);
+        engine.set_stop_signal(None);
+        engine.set_limits(SearchLimits::default().with_depth(2));
+        assert_eq!(engine.search(&Position::starting_position()).depth, 2);
+    }
+
+    #[test]
+    fn test_seldepth_counts_quiescence() {
+        // Captures on e5 and d5 go on past the nominal depth in quiescence
+        let position = Position::from_fen(
+            "r1bqkb1r/ppp2ppp/2n2n2/3pp3/3PP3/2N2N2/PPP2PPP/R1BQKB1R w KQkq - 0 5",
+        )
+        .unwrap();
+        let mut engine = SearchEngine::new(SearchConfig {
+            limits: SearchLimits::default().with_depth(3),
+            ..SearchConfig::default()
+        });
+
+        let result = engine.search(&position);
+        assert_eq!(result.depth, 3);
+        assert!(result.seldepth > result.depth);
+        assert_eq!(engine.status().seldepth, result.seldepth);
+    }
+
+    #[test]
+    fn test_is_check() {
+        let engine = SearchEngine::new(SearchConfig::default());
+
+        let checked =
+            Position::from_fen("rnb1kbnr/pppp1ppp/4p3/8/6Pq/5P2/PPPPP2P/RNBQKBNR w").unwrap();
+        assert!(engine.is_check(&checked));
+        assert!(!engine.is_check(&Position::starting_position()));
+    }
+
+    #[test]
+    fn test_quiescence_mate_in_check() {
+        let mut engine = SearchEngine::new(SearchConfig::default());
+
+        // Fool's mate: White is mated, stand-pat would report a level position
+        let position =
+            Position::from_fen("rnb1kbnr/pppp1ppp/4p3/8/6Pq/5P2/PPPPP2P/RNBQKBNR w").unwrap();
+        let score = engine.quiescence_search(&position, -MATE_VALUE, MATE_VALUE, 0, 0);
+        assert_eq!(score, -MATE_VALUE);
+    }
+
+    #[test]
+    fn test_quiescence_no_stand_pat_in_check() {
+        let mut engine = SearchEngine::new(SearchConfig::default());
+
+        // Back-rank mate: White is a queen up for a rook but has no evasion
+        let position = Position::from_fen("6k1/5ppp/8/8/8/8/Q4PPP/3r2K1 w").unwrap();
+        let score = engine.quiescence_search(&position, -MATE_VALUE, MATE_VALUE, 0, 0);
+        assert_eq!(score, -MATE_VALUE);
+    }
+
+    #[test]
+    fn test_quiescence_resolves_check_evasion() {
+        let mut engine = SearchEngine::new(SearchConfig::default());
+
+        // The checking queen is undefended and the king can take it
+        let position = Position::from_fen("4k3/8/8/8/8/8/3q4/4K3 w").unwrap();
+        let score = engine.quiescence_search(&position, -MATE_VALUE, MATE_VALUE, 0, 0);
+        assert!(score > -MATE_VALUE + 100);
+        assert!(score > -PieceType::Queen.value() / 2);
+    }
+
+    #[test]
+    fn test_terminal_roots_and_material_draws() {
+        let mut engine = SearchEngine::new(SearchConfig::default());
+
+        // Stalemated and mated roots have no best move, but only the mate loses
+        let stalemate = Position::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        let result = engine.search(&stalemate);
+        assert_eq!(result.best_move, None);
+        assert_eq!(result.evaluation, 0);
+
+        let mated =
+            Position::from_fen("rnb1kbnr/pppp1ppp/4p3/8/6Pq/5P2/PPPPP2P/RNBQKBNR w").unwrap();
+        assert_eq!(engine.search(&mated).evaluation, -MATE_VALUE);
+
+        // A lone bishop cannot mate, whatever the static evaluation says
+        let bishop = Position::from_fen("8/8/4k3/8/8/3KB3/8/8 w - - 0 1").unwrap();
+        let mut engine = SearchEngine::new(SearchConfig::default());
+        assert_eq!(
+            engine.quiescence_search(&bishop, -MATE_VALUE, MATE_VALUE, 1, 0),
+            0
+        );
+    }
+
     #[test]
     fn test_piece_values() {
-        assert_eq!(SearchEngine::piece_value(PieceType::Pawn), 100);
-        assert_eq!(SearchEngine::piece_value(PieceType::Queen), 900);
-        assert_eq!(SearchEngine::piece_value(PieceType::King), 20000);
+        let engine = SearchEngine::new(SearchConfig::default());
+        assert_eq!(engine.piece_value(PieceType::Pawn), 100);
+        assert_eq!(engine.piece_value(PieceType::Queen), 900);
+        assert_eq!(engine.piece_value(PieceType::King), 20000);
+    }
+
+    #[test]
+    fn test_aspiration_researches_reported() {
+        let position =
+            Position::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3")
+                .unwrap();
+        let config = |window| SearchConfig {
+            limits: SearchLimits::default().with_depth(5),
+            aspiration_window: window,
+            ..SearchConfig::default()
+        };
+
+        // A one-centipawn window cannot hold the score as the depth grows
+        let narrow = SearchEngine::new(config(1)).search(&position);
+        assert!(narrow.aspiration_researches > 0);
+
+        let wide = SearchEngine::new(config(MATE_VALUE)).search(&position);
+        assert_eq!(wide.aspiration_researches, 0);
+        assert_eq!(wide.root_fail, None);
+        assert!(!wide.is_unstable());
+    }
+
+    fn clock_config(seconds: u64) -> SearchConfig {
+        let clock = Duration::from_secs(seconds);
+        SearchConfig {
+            limits: SearchLimits::default().with_clock(
+                clock,
+                clock,
+                Duration::ZERO,
+                Duration::ZERO,
+            ),
+            ..SearchConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_only_move_played_after_one_iteration() {
+        // Kxb2 is the only way out of check
+        let position = Position::from_fen("k7/8/8/8/8/8/1q6/K7 w - - 0 1").unwrap();
+        let result = SearchEngine::new(clock_config(60)).search(&position);
+        assert_eq!(
+            result.best_move,
+            Some(Move::capture(Square::A1, Square::B2))
+        );
+        assert_eq!(result.depth, 1);
+    }
+
+    #[test]
+    fn test_easy_move_saves_time() {
+        // Taking the hanging queen is worth far more than anything else
+        let position = Position::from_fen("4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1").unwrap();
+        let config = clock_config(60);
+        let budget = config.limits.time_budget(Color::White).unwrap();
+        let result = SearchEngine::new(config).search(&position);
+        assert_eq!(
+            result.best_move,
+            Some(Move::capture(Square::D2, Square::D5))
+        );
+        assert!(
+            result.elapsed_time < budget,
+            "easy move took {:?} of {:?}",
+            result.elapsed_time,
+            budget
+        );
+    }
+
+    #[test]
+    fn test_score_root_moves_above_floor() {
+        let position = Position::from_fen("4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1").unwrap();
+        let mut engine = SearchEngine::new(SearchConfig {
+            limits: SearchLimits::default().with_depth(3),
+            ..SearchConfig::default()
+        });
+        let result = engine.search(&position);
+
+        let scores = engine.score_root_moves(&position, result.depth, result.evaluation - 100);
+        assert_eq!(
+            scores,
+            vec![(Move::capture(Square::D2, Square::D5), result.evaluation)]
+        );
+
+        let all = engine.score_root_moves(&position, result.depth, -MATE_VALUE);
+        assert_eq!(all.len(), engine.root_legal_moves(&position).len());
+    }
+
+    #[test]
+    fn test_mate_distance_output() {
+        let mut result = SearchEngine::new(SearchConfig::default())
+            .search(&Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap());
+        assert_eq!(result.mate_in_plies(), Some(1));
+        assert_eq!(result.mate_in(), Some(1));
+        assert_eq!(result.uci_score(), "mate 1");
+
+        result.evaluation = -(MATE_VALUE - 4);
+        assert_eq!(result.mate_in_plies(), Some(-4));
+        assert_eq!(result.mate_in(), Some(-2));
+        result.evaluation = 35;
+        assert_eq!(result.mate_in(), None);
+        assert_eq!(result.uci_score(), "cp 35");
+    }
+
+    #[test]
+    fn test_prove_mate() {
+        let engine = SearchEngine::new(SearchConfig::default());
+        let back_rank = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        assert_eq!(engine.prove_mate(&back_rank, MATE_VALUE - 1), Some(true));
+        // Black's only move, Kb8, walks into Rh8 mate
+        let mated = Position::from_fen("k7/8/1K6/8/8/8/8/7R b - - 0 1").unwrap();
+        assert_eq!(engine.prove_mate(&mated, -(MATE_VALUE - 2)), Some(true));
+
+        let start = Position::starting_position();
+        assert_eq!(engine.prove_mate(&start, MATE_VALUE - 3), Some(false));
+        assert_eq!(engine.prove_mate(&start, -(MATE_VALUE - 2)), Some(false));
+    }
+
+    #[test]
+    fn test_false_mate_from_table_is_searched_again() {
+        let start = Position::starting_position();
+        let mut after_e4 = start.clone();
+        after_e4
+            .make_move(Move::normal(Square::E2, Square::E4))
+            .unwrap();
+        let search = |verification| {
+            let mut engine = SearchEngine::new(SearchConfig {
+                limits: SearchLimits::default().with_depth(3),
+                mate_verification: verification,
+                ..SearchConfig::default()
+            });
+            // A corrupt entry claiming White mates in two after 1. e4
+            let score = value_to_tt(-(MATE_VALUE - 3), 1);
+            engine.store_transposition(
+                after_e4.zobrist_hash(),
+                MAX_DEPTH,
+                score,
+                None,
+                NodeType::Exact,
+            );
+            engine.search(&start)
+        };
+
+        assert_eq!(search(MateVerification::Off).mate_in(), Some(2));
+        for verification in [MateVerification::Prove, MateVerification::Deeper(1)] {
+            let result = search(verification);
+            assert_eq!(result.mate_in(), None, "{:?}", verification);
+            assert!(result.depth >= 3);
+        }
+    }
+
+    #[test]
+    fn test_mate_check_only_uses_the_time_left() {
+        // With one move to the time control, the budget is the clock less the overhead
+        let clock = Duration::from_millis(2000);
+        let limits = SearchLimits {
+            movestogo: Some(1),
+            ..SearchLimits::default().with_clock(clock, clock, Duration::ZERO, Duration::ZERO)
+        };
+        let budget = limits.time_budget(Color::White).unwrap();
+
+        let check = verification_limits(limits, Color::White, MAX_DEPTH, budget / 4).unwrap();
+        assert_eq!(check.movetime, Some(budget - budget / 4));
+        assert!(!check.manages_clock(Color::White));
+        assert_eq!(check.max_depth(), MAX_DEPTH);
+        assert!(verification_limits(limits, Color::White, MAX_DEPTH, budget).is_none());
+
+        let depth_only = SearchLimits::default().with_depth(5);
+        assert_eq!(
+            verification_limits(depth_only, Color::White, 7, budget),
+            Some(depth_only.with_depth(7))
+        );
+
+        // A whole search with a mate check stays within one budget
+        let mut engine = SearchEngine::new(SearchConfig {
+            limits,
+            mate_verification: MateVerification::Deeper(2),
+            ..SearchConfig::default()
+        });
+        let position = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let result = engine.search(&position);
+        assert_eq!(result.mate_in(), Some(1));
+        assert!(result.elapsed_time <= budget + Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_search_excluding_root_move() {
+        let position = Position::from_fen("4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1").unwrap();
+        let capture = Move::capture(Square::D2, Square::D5);
+        let mut engine = SearchEngine::new(SearchConfig {
+            limits: SearchLimits::default().with_depth(4),
+            ..SearchConfig::default()
+        });
+
+        let without = engine.search_excluding(&position, capture);
+        assert!(without.best_move.is_some_and(|mv| mv != capture));
+        let with = engine.search(&position);
+        assert_eq!(with.best_move, Some(capture));
+        assert!(with.evaluation > without.evaluation + 500);
+    }
+
+    #[test]
+    fn test_singular_table_move() {
+        let mut engine = SearchEngine::new(SearchConfig {
+            limits: SearchLimits::default().with_depth(6),
+            ..SearchConfig::default()
+        });
+
+        // Taking the queen is far better than anything else
+        let position = Position::from_fen("4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1").unwrap();
+        let capture = Move::capture(Square::D2, Square::D5);
+        let score = engine.search(&position).evaluation;
+        let hash = position.zobrist_hash();
+        engine.store_transposition(hash, 6, score, Some(capture), NodeType::Exact);
+        assert!(engine.is_singular(&position, capture, 6, 1));
+        assert_eq!(engine.extension(&position, capture, 1, Some(capture)), 1);
+
+        // In the opening position several moves are as good as e2-e4
+        let position = Position::starting_position();
+        let e4 = Move::normal(Square::E2, Square::E4);
+        let score = engine.search(&position).evaluation;
+        let hash = position.zobrist_hash();
+        engine.store_transposition(hash, 6, score, Some(e4), NodeType::Exact);
+        assert!(!engine.is_singular(&position, e4, 6, 1));
+    }
+
+    #[test]
+    fn test_excluded_only_move_fails_low() {
+        // Kxb2 is the only legal move; without it the node has nothing to search
+        let position = Position::from_fen("k7/8/8/8/8/8/1q6/K7 w - - 0 1").unwrap();
+        let only_move = Move::capture(Square::A1, Square::B2);
+        let mut engine = SearchEngine::new(SearchConfig::default());
+        engine.current_depth = 2;
+        let score = engine.alpha_beta(&position, 2, -50, 50, 1, false, Some(only_move));
+        assert_eq!(score, -50);
+        assert!(engine
+            .transposition_table
+            .probe(position.zobrist_hash())
+            .is_none());
     }
 }
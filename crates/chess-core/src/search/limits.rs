@@ -0,0 +1,326 @@
+// When a search stops: depth, nodes, fixed move time, a game clock or a mate bound,
+// in one type shared by the engine, the bindings and UCI `go` parsing
+
+use super::engine::MAX_DEPTH;
+use crate::{ChessError, Color, Result};
+use std::time::Duration;
+
+/// Moves assumed left in the game when the clock gives no `movestogo`
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+/// Time kept back on every move for communication and scheduling delays
+pub const MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+/// Percent of its usual share of the clock a move may take while the root score or the
+/// best move is unstable
+const UNSTABLE_TIME_PERCENT: u32 = 200;
+/// Percent of its usual share of the clock after which an easy move is played
+const EASY_MOVE_TIME_PERCENT: u32 = 20;
+
+/// Limits of one search. Every limit that is set applies, and the search stops at the
+/// first one reached; with none set it runs to `MAX_DEPTH`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchLimits {
+    /// Iterative deepening stops after this depth
+    pub depth: Option<u8>,
+    pub nodes: Option<u64>,
+    /// Fixed time for this move
+    pub movetime: Option<Duration>,
+    /// Time left on White's clock
+    pub wtime: Option<Duration>,
+    /// Time left on Black's clock
+    pub btime: Option<Duration>,
+    /// White's increment per move
+    pub winc: Option<Duration>,
+    /// Black's increment per move
+    pub binc: Option<Duration>,
+    /// Moves until the next time control
+    pub movestogo: Option<u32>,
+    /// Look for a mate in at most this many moves, capping the depth at `2 * mate - 1`
+    pub mate: Option<u8>,
+    /// Ignore depth, time and mate limits (UCI `go infinite`)
+    pub infinite: bool,
+}
+
+impl SearchLimits {
+    pub fn with_depth(mut self, depth: u8) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    pub fn with_nodes(mut self, nodes: u64) -> Self {
+        self.nodes = Some(nodes);
+        self
+    }
+
+    pub fn with_movetime(mut self, movetime: Duration) -> Self {
+        self.movetime = Some(movetime);
+        self
+    }
+
+    /// Clock state for both sides, as sent with UCI `go wtime ... btime ...`
+    pub fn with_clock(
+        mut self,
+        wtime: Duration,
+        btime: Duration,
+        winc: Duration,
+        binc: Duration,
+    ) -> Self {
+        self.wtime = Some(wtime);
+        self.btime = Some(btime);
+        self.winc = Some(winc);
+        self.binc = Some(binc);
+        self
+    }
+
+    pub fn with_mate(mut self, moves: u8) -> Self {
+        self.mate = Some(moves);
+        self
+    }
+
+    pub fn infinite() -> Self {
+        SearchLimits {
+            infinite: true,
+            ..SearchLimits::default()
+        }
+    }
+
+    /// The same limits without move time or clock, so only depth, nodes and mate apply
+    pub fn without_time(self) -> Self {
+        SearchLimits {
+            movetime: None,
+            wtime: None,
+            btime: None,
+            winc: None,
+            binc: None,
+            movestogo: None,
+            ..self
+        }
+    }
+
+    /// Deepest iteration to start. A mate in `n` moves needs `2n - 1` plies.
+    pub fn max_depth(&self) -> u8 {
+        if self.infinite {
+            return MAX_DEPTH;
+        }
+        let mate_depth = self
+            .mate
+            .map(|moves| (moves.max(1) as u16 * 2 - 1).min(MAX_DEPTH as u16) as u8);
+        match (self.depth, mate_depth) {
+            (Some(depth), Some(mate)) => depth.min(mate),
+            (Some(depth), None) => depth,
+            (None, Some(mate)) => mate,
+            (None, None) => MAX_DEPTH,
+        }
+        .clamp(1, MAX_DEPTH)
+    }
+
+    /// Time to spend on this move for `side`: the fixed move time, or a share of the
+    /// remaining clock plus most of the increment. `None` means no time limit.
+    pub fn time_budget(&self, side: Color) -> Option<Duration> {
+        self.scaled_budget(side, 100)
+    }
+
+    /// Time this move may grow to when the root score keeps failing high or low or the
+    /// best move keeps changing: a fixed move time stays fixed, a clock share may take
+    /// twice as long
+    pub fn unstable_time_budget(&self, side: Color) -> Option<Duration> {
+        self.scaled_budget(side, UNSTABLE_TIME_PERCENT)
+    }
+
+    /// Time after which a clearly best move is played without using the rest of
+    /// `time_budget`; only shorter than it when the clock is managed
+    pub fn easy_move_time(&self, side: Color) -> Option<Duration> {
+        self.scaled_budget(side, EASY_MOVE_TIME_PERCENT)
+    }
+
+    /// Whether the search divides up `side`'s clock, rather than having a fixed move
+    /// time or no time limit; only then may it think less or longer than usual
+    pub fn manages_clock(&self, side: Color) -> bool {
+        let time = match side {
+            Color::White => self.wtime,
+            Color::Black => self.btime,
+        };
+        !self.infinite && self.movetime.is_none() && time.is_some()
+    }
+
+    fn scaled_budget(&self, side: Color, percent: u32) -> Option<Duration> {
+        if self.infinite {
+            return None;
+        }
+        if let Some(movetime) = self.movetime {
+            return Some(movetime);
+        }
+
+        let (time, increment) = match side {
+            Color::White => (self.wtime?, self.winc.unwrap_or_default()),
+            Color::Black => (self.btime?, self.binc.unwrap_or_default()),
+        };
+        let moves_to_go = self.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+        let share = (time / moves_to_go + increment * 3 / 4) * percent / 100;
+        let available = time.saturating_sub(MOVE_OVERHEAD);
+        Some(share.min(available).max(Duration::from_millis(1)))
+    }
+
+    /// Parse the arguments of a UCI `go` command, e.g. `wtime 60000 btime 60000 winc
+    /// 1000 binc 1000` or `mate 3`. Unknown tokens are skipped as the protocol asks.
+    pub fn from_uci_go(args: &str) -> Result<Self> {
+        let mut limits = SearchLimits::default();
+        let mut tokens = args.split_whitespace();
+
+        while let Some(token) = tokens.next() {
+            let mut value = || -> Result<u64> {
+                let text = tokens
+                    .next()
+                    .ok_or_else(|| ChessError::ParseError(format!("go {} needs a value", token)))?;
+                text.parse().map_err(|_| {
+                    ChessError::ParseError(format!("go {}: invalid number '{}'", token, text))
+                })
+            };
+            match token {
+                "depth" => limits.depth = Some(value()?.clamp(1, MAX_DEPTH as u64) as u8),
+                "nodes" => limits.nodes = Some(value()?),
+                "movetime" => limits.movetime = Some(Duration::from_millis(value()?)),
+                "wtime" => limits.wtime = Some(Duration::from_millis(value()?)),
+                "btime" => limits.btime = Some(Duration::from_millis(value()?)),
+                "winc" => limits.winc = Some(Duration::from_millis(value()?)),
+                "binc" => limits.binc = Some(Duration::from_millis(value()?)),
+                "movestogo" => limits.movestogo = Some(value()?.min(u32::MAX as u64) as u32),
+                "mate" => limits.mate = Some(value()?.clamp(1, u8::MAX as u64) as u8),
+                "infinite" => limits.infinite = true,
+                _ => {}
+            }
+        }
+        Ok(limits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_uci_go() {
+        let limits = SearchLimits::from_uci_go(
+            "wtime 60000 btime 30000 winc 1000 binc 500 movestogo 20 ponder",
+        )
+        .unwrap();
+        assert_eq!(limits.wtime, Some(Duration::from_secs(60)));
+        assert_eq!(limits.binc, Some(Duration::from_millis(500)));
+        assert_eq!(limits.movestogo, Some(20));
+        assert!(!limits.infinite);
+
+        assert_eq!(
+            SearchLimits::from_uci_go("depth 7 nodes 5000").unwrap(),
+            SearchLimits::default().with_depth(7).with_nodes(5000)
+        );
+        assert!(SearchLimits::from_uci_go("infinite").unwrap().infinite);
+        assert!(SearchLimits::from_uci_go("depth").is_err());
+        assert!(SearchLimits::from_uci_go("movetime soon").is_err());
+    }
+
+    #[test]
+    fn test_max_depth() {
+        assert_eq!(SearchLimits::default().max_depth(), MAX_DEPTH);
+        assert_eq!(SearchLimits::default().with_depth(6).max_depth(), 6);
+        assert_eq!(SearchLimits::default().with_mate(2).max_depth(), 3);
+        assert_eq!(
+            SearchLimits::default()
+                .with_depth(2)
+                .with_mate(3)
+                .max_depth(),
+            2
+        );
+        assert_eq!(
+            SearchLimits::infinite().with_depth(3).max_depth(),
+            MAX_DEPTH
+        );
+    }
+
+    #[test]
+    fn test_time_budget() {
+        let movetime = SearchLimits::default().with_movetime(Duration::from_millis(250));
+        assert_eq!(
+            movetime.time_budget(Color::Black),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(SearchLimits::default().time_budget(Color::White), None);
+
+        let clock = SearchLimits::default().with_clock(
+            Duration::from_secs(60),
+            Duration::from_secs(3),
+            Duration::from_secs(2),
+            Duration::ZERO,
+        );
+        // 60s / 30 moves + 3/4 of the 2s increment
+        assert_eq!(
+            clock.time_budget(Color::White),
+            Some(Duration::from_millis(3500))
+        );
+        assert_eq!(
+            clock.time_budget(Color::Black),
+            Some(Duration::from_millis(100))
+        );
+
+        let last_move = SearchLimits {
+            movestogo: Some(1),
+            ..clock
+        };
+        assert_eq!(
+            last_move.time_budget(Color::Black),
+            Some(Duration::from_secs(3) - MOVE_OVERHEAD)
+        );
+    }
+
+    #[test]
+    fn test_unstable_time_budget() {
+        let movetime = SearchLimits::default().with_movetime(Duration::from_millis(250));
+        assert_eq!(
+            movetime.unstable_time_budget(Color::White),
+            movetime.time_budget(Color::White)
+        );
+
+        let clock = SearchLimits::default().with_clock(
+            Duration::from_secs(60),
+            Duration::from_secs(3),
+            Duration::from_secs(2),
+            Duration::ZERO,
+        );
+        assert_eq!(
+            clock.unstable_time_budget(Color::White),
+            Some(Duration::from_secs(7))
+        );
+        // Never more than the clock holds
+        let last_move = SearchLimits {
+            movestogo: Some(1),
+            ..clock
+        };
+        assert_eq!(
+            last_move.unstable_time_budget(Color::Black),
+            Some(Duration::from_secs(3) - MOVE_OVERHEAD)
+        );
+    }
+
+    #[test]
+    fn test_easy_move_time() {
+        let clock = SearchLimits::default().with_clock(
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+            Duration::ZERO,
+            Duration::ZERO,
+        );
+        assert!(clock.manages_clock(Color::Black));
+        assert_eq!(
+            clock.easy_move_time(Color::White),
+            Some(Duration::from_millis(400))
+        );
+
+        let movetime = clock.with_movetime(Duration::from_secs(1));
+        assert!(!movetime.manages_clock(Color::White));
+        assert_eq!(
+            movetime.easy_move_time(Color::White),
+            Some(Duration::from_secs(1))
+        );
+        assert!(!SearchLimits::default()
+            .with_depth(4)
+            .manages_clock(Color::White));
+    }
+}
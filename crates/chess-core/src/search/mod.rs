@@ -1,7 +1,24 @@
+pub mod affinity;
+pub mod bench;
 pub mod engine;
+pub mod limits;
+pub mod move_picker;
 pub mod parallel;
+pub mod see;
+pub mod status;
+pub mod tablebase;
+pub mod terminal;
 
-pub use engine::{SearchConfig, SearchEngine, SearchResult};
+pub use affinity::{ThreadAffinity, ThreadPriority};
+pub use bench::{run_bench, run_bench_on, BenchReport, BENCH_POSITIONS};
+#[cfg(feature = "benchmarks")]
+pub use bench::{run_summary, BenchSummary};
+pub use engine::{MateVerification, RootFail, SearchConfig, SearchEngine, SearchResult};
+pub use limits::SearchLimits;
+pub use move_picker::MovePicker;
 pub use parallel::{
-    ParallelConfig, ParallelEvaluator, ParallelMoveGenerator, ParallelSearchEngine,
+    BatchSearcher, ParallelConfig, ParallelEvaluator, ParallelMoveGenerator, ParallelSearchEngine,
 };
+pub use see::see;
+pub use status::{SearchStatus, SearchStatusHandle};
+pub use tablebase::{TablebaseProbe, TablebaseRoot, TbWdl};
@@ -0,0 +1,119 @@
+// Incremental move ordering for the search
+//
+// Most nodes cut off after one to three moves, so sorting a node's whole move list is
+// mostly wasted work. The picker scores every move up front and selects the best of the
+// remaining moves each time the next one is asked for, which costs one pass over the
+// list per move searched instead of a full sort per node.
+
+use crate::Move;
+
+/// Moves with ordering scores, handed out best first as the search asks for them.
+/// Equal scores come out in the order the moves were given, as a stable sort would.
+#[derive(Debug, Clone)]
+pub struct MovePicker {
+    moves: Vec<(Move, i32)>,
+    /// Moves before this index have been handed out
+    next: usize,
+    /// Whether to select by score; otherwise moves come out as given
+    select: bool,
+}
+
+impl MovePicker {
+    pub fn new(moves: Vec<(Move, i32)>) -> Self {
+        MovePicker {
+            moves,
+            next: 0,
+            select: true,
+        }
+    }
+
+    /// Hand out `moves` in the order given, without scoring them
+    pub fn in_order(moves: Vec<Move>) -> Self {
+        MovePicker {
+            moves: moves.into_iter().map(|mv| (mv, 0)).collect(),
+            next: 0,
+            select: false,
+        }
+    }
+
+    /// Moves not yet handed out
+    pub fn remaining(&self) -> usize {
+        self.moves.len() - self.next
+    }
+}
+
+impl Iterator for MovePicker {
+    type Item = (Move, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.moves.get(self.next..)?;
+        if rest.is_empty() {
+            return None;
+        }
+
+        if self.select {
+            // The first of the best scores, moved to the front with the moves it passes
+            // keeping their order
+            let mut best = 0;
+            for (index, &(_, score)) in rest.iter().enumerate().skip(1) {
+                if score > rest[best].1 {
+                    best = index;
+                }
+            }
+            self.moves[self.next..=self.next + best].rotate_right(1);
+        }
+
+        let picked = self.moves[self.next];
+        self.next += 1;
+        Some(picked)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining(), Some(self.remaining()))
+    }
+}
+
+impl ExactSizeIterator for MovePicker {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Square;
+
+    fn scored(scores: &[i32]) -> Vec<(Move, i32)> {
+        scores
+            .iter()
+            .enumerate()
+            .map(|(index, &score)| {
+                let to = Square::new(index as u8 + 16).unwrap();
+                (Move::normal(Square::A1, to), score)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_picks_like_a_stable_sort() {
+        let moves = scored(&[5, 90, -3, 90, 0, 5, 40]);
+        let mut sorted = moves.clone();
+        sorted.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+
+        let picker = MovePicker::new(moves);
+        assert_eq!(picker.len(), 7);
+        assert_eq!(picker.collect::<Vec<_>>(), sorted);
+    }
+
+    #[test]
+    fn test_in_order_and_partial_use() {
+        let moves = scored(&[1, 3, 2]);
+        let plain: Vec<Move> = moves.iter().map(|&(mv, _)| mv).collect();
+        let in_order: Vec<Move> = MovePicker::in_order(plain.clone())
+            .map(|(mv, _)| mv)
+            .collect();
+        assert_eq!(in_order, plain);
+
+        let mut picker = MovePicker::new(moves);
+        assert_eq!(picker.next().map(|(_, score)| score), Some(3));
+        assert_eq!(picker.remaining(), 2);
+        assert_eq!(MovePicker::new(Vec::new()).next(), None);
+    }
+}
@@ -1,19 +1,36 @@
 // Parallel processing for chess engine using Rayon
 // Implements multi-threaded move generation, search, and evaluation
 
+use super::affinity::{self, ThreadAffinity, ThreadPriority};
+use super::engine::{value_from_tt, value_to_tt, SearchConfig, SearchEngine, SearchResult};
+use super::status::{SearchStatus, SearchStatusHandle};
+use super::terminal::{is_rule_draw, no_moves_score};
+use crate::evaluation::SharedEvaluationCache;
+use crate::utils::memory::NodeType;
 use crate::{Bitboard, Move, MoveGenerator, OptimizedEvaluator, Position};
 use rayon::prelude::*;
+use rayon::ThreadPool;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Nodes a search thread counts on its own before adding them to the shared status
+const NODE_FLUSH_INTERVAL: u64 = 1024;
 
 /// Configuration for parallel processing
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ParallelConfig {
     pub num_threads: usize,
     pub chunk_size: usize,
     pub enable_parallel_moves: bool,
     pub enable_parallel_eval: bool,
     pub enable_parallel_search: bool,
+    /// Core pinning of the pool threads
+    pub affinity: ThreadAffinity,
+    /// Scheduling priority of the pool threads
+    pub priority: ThreadPriority,
 }
 
 #[derive(Debug, Clone)]
@@ -21,19 +38,77 @@ struct TranspositionEntry {
     #[allow(dead_code)]
     zobrist_hash: u64,
     depth: u8,
+    /// Mate scores are stored relative to the node, see `value_to_tt`
     evaluation: i32,
+    node_type: NodeType,
     #[allow(dead_code)]
     best_move: Option<Move>,
 }
 
+impl ParallelConfig {
+    /// Set the size of the engine's own thread pool (at least one thread)
+    pub fn with_threads(mut self, num_threads: usize) -> Self {
+        self.num_threads = num_threads.max(1);
+        self
+    }
+
+    /// Pin the pool threads to cores
+    pub fn with_affinity(mut self, affinity: ThreadAffinity) -> Self {
+        self.affinity = affinity;
+        self
+    }
+
+    /// Run the pool threads at this priority
+    pub fn with_priority(mut self, priority: ThreadPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Build a dedicated thread pool sized for this configuration, with its threads
+    /// pinned and prioritised as configured where the platform allows
+    pub fn build_thread_pool(&self) -> Arc<ThreadPool> {
+        let affinity = self.affinity.clone();
+        let priority = self.priority;
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.num_threads.max(1))
+            .thread_name(|i| format!("chess-engine-{}", i))
+            .start_handler(move |index| {
+                if let Some(core) = affinity.core_for(index) {
+                    if !affinity::pin_current_thread(core) {
+                        crate::log_warn!("could not pin search thread {} to core {}", index, core);
+                    }
+                }
+                if priority != ThreadPriority::Normal
+                    && !affinity::set_current_thread_priority(priority)
+                {
+                    crate::log_warn!(
+                        "could not give search thread {} {:?} priority",
+                        index,
+                        priority
+                    );
+                }
+            })
+            .build()
+            .expect("Failed to build search thread pool");
+        Arc::new(pool)
+    }
+}
+
 impl Default for ParallelConfig {
     fn default() -> Self {
+        // Query the hardware directly; asking Rayon would spin up its global pool
+        let num_threads = std::thread::available_parallelism()
+            .map(|threads| threads.get())
+            .unwrap_or(1);
+
         ParallelConfig {
-            num_threads: rayon::current_num_threads(),
+            num_threads,
             chunk_size: 64,
             enable_parallel_moves: true,
             enable_parallel_eval: true,
             enable_parallel_search: true,
+            affinity: ThreadAffinity::default(),
+            priority: ThreadPriority::default(),
         }
     }
 }
@@ -42,13 +117,21 @@ impl Default for ParallelConfig {
 pub struct ParallelMoveGenerator {
     generator: MoveGenerator,
     config: ParallelConfig,
+    thread_pool: Arc<ThreadPool>,
 }
 
 impl ParallelMoveGenerator {
     pub fn new(config: ParallelConfig) -> Self {
+        let thread_pool = config.build_thread_pool();
+        Self::with_thread_pool(config, thread_pool)
+    }
+
+    /// Run on an existing pool instead of creating one
+    pub fn with_thread_pool(config: ParallelConfig, thread_pool: Arc<ThreadPool>) -> Self {
         ParallelMoveGenerator {
             generator: MoveGenerator::new(),
             config,
+            thread_pool,
         }
     }
 
@@ -62,15 +145,17 @@ impl ParallelMoveGenerator {
                 .collect();
         }
 
-        positions
-            .par_chunks(self.config.chunk_size)
-            .flat_map(|chunk| {
-                chunk
-                    .par_iter()
-                    .map(|pos| self.generator.generate_legal_moves(pos))
-                    .collect::<Vec<_>>()
-            })
-            .collect()
+        self.thread_pool.install(|| {
+            positions
+                .par_chunks(self.config.chunk_size)
+                .flat_map(|chunk| {
+                    chunk
+                        .par_iter()
+                        .map(|pos| self.generator.generate_legal_moves(pos))
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        })
     }
 
     /// Parallel move validation for multiple move-position pairs
@@ -82,19 +167,23 @@ impl ParallelMoveGenerator {
                 .collect();
         }
 
-        move_position_pairs
-            .par_iter()
-            .map(|(move_item, pos)| self.is_legal_move(move_item, pos))
-            .collect()
+        self.thread_pool.install(|| {
+            move_position_pairs
+                .par_iter()
+                .map(|(move_item, pos)| self.is_legal_move(move_item, pos))
+                .collect()
+        })
     }
 
     /// Parallel move generation with SIMD optimization
     pub fn simd_parallel_move_generation(&self, positions: &[Position]) -> Vec<Vec<Move>> {
         // Process positions in groups of 4 for SIMD optimization
-        positions
-            .par_chunks(4)
-            .flat_map(|chunk| self.process_position_chunk_simd(chunk))
-            .collect()
+        self.thread_pool.install(|| {
+            positions
+                .par_chunks(4)
+                .flat_map(|chunk| self.process_position_chunk_simd(chunk))
+                .collect()
+        })
     }
 
     fn process_position_chunk_simd(&self, chunk: &[Position]) -> Vec<Vec<Move>> {
@@ -133,64 +222,79 @@ impl ParallelMoveGenerator {
 }
 
 /// Parallel evaluation engine
+///
+/// Every thread evaluates through one `OptimizedEvaluator` and one shared cache, so a
+/// position evaluated by any thread is a cache hit for all of them.
 pub struct ParallelEvaluator {
     evaluator: OptimizedEvaluator,
+    cache: Arc<SharedEvaluationCache>,
     config: ParallelConfig,
+    thread_pool: Arc<ThreadPool>,
 }
 
 impl ParallelEvaluator {
     pub fn new(config: ParallelConfig) -> Self {
+        let thread_pool = config.build_thread_pool();
+        Self::with_thread_pool(config, thread_pool)
+    }
+
+    /// Run on an existing pool instead of creating one
+    pub fn with_thread_pool(config: ParallelConfig, thread_pool: Arc<ThreadPool>) -> Self {
         ParallelEvaluator {
             evaluator: OptimizedEvaluator::new(),
+            cache: Arc::new(SharedEvaluationCache::new()),
             config,
+            thread_pool,
         }
     }
 
+    /// Evaluate one position through the shared cache
+    pub fn evaluate(&self, position: &Position) -> i32 {
+        self.evaluator.evaluate_shared(position, &self.cache)
+    }
+
     /// Evaluate multiple positions in parallel
-    pub fn bulk_evaluate(&mut self, positions: &[Position]) -> Vec<i32> {
+    pub fn bulk_evaluate(&self, positions: &[Position]) -> Vec<i32> {
         if !self.config.enable_parallel_eval || positions.len() < self.config.chunk_size {
-            return positions
-                .iter()
-                .map(|pos| self.evaluator.evaluate(pos))
-                .collect();
+            return positions.iter().map(|pos| self.evaluate(pos)).collect();
         }
 
-        // For parallel evaluation, we need to clone the evaluator for each thread
-        // to avoid borrowing issues
-        let evaluator = self.evaluator.clone();
-        positions
-            .par_chunks(self.config.chunk_size)
-            .flat_map(|chunk| {
-                chunk
-                    .par_iter()
-                    .map(|pos| {
-                        let mut eval = evaluator.clone();
-                        eval.evaluate(pos)
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect()
+        self.thread_pool.install(|| {
+            positions
+                .par_chunks(self.config.chunk_size)
+                .flat_map(|chunk| {
+                    chunk
+                        .par_iter()
+                        .map(|pos| self.evaluate(pos))
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        })
     }
 
     /// SIMD-optimized parallel evaluation for multiple positions
-    pub fn simd_bulk_evaluate(&mut self, positions: &[Position]) -> Vec<i32> {
-        let evaluator = self.evaluator.clone();
-        positions
-            .par_chunks(4)
-            .flat_map(|chunk| {
-                let mut eval = evaluator.clone();
-                Self::simd_evaluate_chunk_static(&mut eval, chunk)
-            })
-            .collect()
+    pub fn simd_bulk_evaluate(&self, positions: &[Position]) -> Vec<i32> {
+        self.thread_pool.install(|| {
+            positions
+                .par_chunks(4)
+                .flat_map(|chunk| self.simd_evaluate_chunk(chunk))
+                .collect()
+        })
     }
 
-    fn simd_evaluate_chunk_static(
-        evaluator: &mut OptimizedEvaluator,
-        chunk: &[Position],
-    ) -> Vec<i32> {
+    fn simd_evaluate_chunk(&self, chunk: &[Position]) -> Vec<i32> {
         // For simplicity in this case, just evaluate sequentially
         // The SIMD optimizations are better done inside the evaluator itself
-        chunk.iter().map(|pos| evaluator.evaluate(pos)).collect()
+        chunk.iter().map(|pos| self.evaluate(pos)).collect()
+    }
+
+    /// Get shared cache statistics as (hits, misses, collisions, hit rate)
+    pub fn cache_stats(&self) -> (u64, u64, u64, f64) {
+        self.cache.stats()
+    }
+
+    pub fn clear_cache(&self) {
+        self.cache.clear();
     }
 
     #[allow(dead_code)]
@@ -203,9 +307,15 @@ impl ParallelEvaluator {
         let mut results = Vec::with_capacity(4);
 
         // Material evaluation using SIMD
-        let material_weights = [
-            100, 320, 330, 500, 900, 20000, -100, -320, -330, -500, -900, -20000,
-        ]; // Piece values
+        let params = self.evaluator.params();
+        let material_weights: [i32; 12] = std::array::from_fn(|j| {
+            let value = params.piece_values[j % 6].middlegame;
+            if j < 6 {
+                value
+            } else {
+                -value
+            }
+        });
 
         for i in 0..piece_counts.len() {
             let mut score = 0;
@@ -244,53 +354,103 @@ impl ParallelEvaluator {
 struct SharedSearchData {
     transposition_table: Arc<Mutex<HashMap<u64, TranspositionEntry>>>,
     best_move: Arc<Mutex<Option<Move>>>,
-    nodes_searched: Arc<Mutex<u64>>,
+    /// Progress every thread adds its nodes to
+    status: SearchStatusHandle,
 }
 
 /// Parallel search engine using Lazy SMP
+///
+/// Owns a dedicated Rayon pool sized by `ParallelConfig::num_threads`, so searching
+/// never touches the global pool of a host application that uses Rayon itself.
 pub struct ParallelSearchEngine {
     config: ParallelConfig,
     move_generator: ParallelMoveGenerator,
     evaluator: ParallelEvaluator,
+    thread_pool: Arc<ThreadPool>,
+    status: SearchStatusHandle,
 }
 
 impl ParallelSearchEngine {
     pub fn new(config: ParallelConfig) -> Self {
+        let thread_pool = config.build_thread_pool();
+        Self::with_thread_pool(config, thread_pool)
+    }
+
+    /// Search on an existing pool, e.g. one shared by several engines
+    pub fn with_thread_pool(config: ParallelConfig, thread_pool: Arc<ThreadPool>) -> Self {
         ParallelSearchEngine {
-            move_generator: ParallelMoveGenerator::new(config.clone()),
-            evaluator: ParallelEvaluator::new(config.clone()),
+            move_generator: ParallelMoveGenerator::with_thread_pool(
+                config.clone(),
+                thread_pool.clone(),
+            ),
+            evaluator: ParallelEvaluator::with_thread_pool(config.clone(), thread_pool.clone()),
             config,
+            thread_pool,
+            status: SearchStatusHandle::new(),
         }
     }
 
+    pub fn thread_pool(&self) -> &Arc<ThreadPool> {
+        &self.thread_pool
+    }
+
+    /// Handle that can be cloned to another thread to poll progress during
+    /// `parallel_root_search`; the node count includes every thread's nodes
+    pub fn status_handle(&self) -> SearchStatusHandle {
+        self.status.clone()
+    }
+
+    /// Current counters of the running or most recent search
+    pub fn status(&self) -> SearchStatus {
+        self.status.snapshot()
+    }
+
     /// Parallel root search using multiple threads
     pub fn parallel_root_search(&self, position: &Position, depth: u8) -> (Option<Move>, i32, u64) {
-        if !self.config.enable_parallel_search {
-            return self.sequential_search(position, depth);
-        }
+        let started = Instant::now();
+        self.status.reset(started);
+        self.status.set_depth(depth, depth);
+        let result = self.root_search(position, depth);
+        self.status.finish(started.elapsed());
+        result
+    }
 
+    fn root_search(&self, position: &Position, depth: u8) -> (Option<Move>, i32, u64) {
         let shared_data = SharedSearchData {
             transposition_table: Arc::new(Mutex::new(HashMap::new())),
             best_move: Arc::new(Mutex::new(None)),
-            nodes_searched: Arc::new(Mutex::new(0)),
+            status: self.status.clone(),
         };
 
         let legal_moves = self.move_generator.generator.generate_legal_moves(position);
         if legal_moves.is_empty() {
-            return (None, 0, 1);
+            let in_check = self
+                .move_generator
+                .generator
+                .king_attacked(position, position.side_to_move());
+            return (None, no_moves_score(in_check, 0, 0), 1);
+        }
+
+        // Without parallel search the calling thread searches every move at full depth
+        if !self.config.enable_parallel_search {
+            return self.search_thread(position, &legal_moves, depth, 0, shared_data);
         }
 
         // Divide moves among threads
-        let moves_per_thread = legal_moves.len().div_ceil(self.config.num_threads);
+        let moves_per_thread = legal_moves
+            .len()
+            .div_ceil(self.thread_pool.current_num_threads().max(1));
         let move_chunks: Vec<_> = legal_moves.chunks(moves_per_thread).collect();
 
-        let results: Vec<_> = move_chunks
-            .par_iter()
-            .enumerate()
-            .map(|(thread_id, moves)| {
-                self.search_thread(position, moves, depth, thread_id, shared_data.clone())
-            })
-            .collect();
+        let results: Vec<_> = self.thread_pool.install(|| {
+            move_chunks
+                .par_iter()
+                .enumerate()
+                .map(|(thread_id, moves)| {
+                    self.search_thread(position, moves, depth, thread_id, shared_data.clone())
+                })
+                .collect()
+        });
 
         // Find best result
         let mut best_move = None;
@@ -326,21 +486,28 @@ impl ParallelSearchEngine {
             depth.saturating_sub((thread_id % 3) as u8) // Helper threads use slightly reduced depth
         };
 
+        // Keys of the positions from the root to the node being searched
+        let mut line = vec![position.zobrist_hash()];
+
         for &move_item in moves {
             let mut new_position = position.clone();
             if new_position.make_move(move_item).is_err() {
                 continue;
             }
 
-            let evaluation = self.alpha_beta_search(
-                &new_position,
-                thread_depth.saturating_sub(1),
-                i32::MIN,
-                i32::MAX,
-                &shared_data,
-            );
-
-            local_nodes += 1;
+            // Scores are from the side to move, which is now the opponent
+            let evaluation = self
+                .alpha_beta_search(
+                    &new_position,
+                    thread_depth.saturating_sub(1),
+                    i32::MIN,
+                    i32::MAX,
+                    1,
+                    &shared_data,
+                    &mut local_nodes,
+                    &mut line,
+                )
+                .saturating_neg();
 
             if evaluation > best_evaluation {
                 best_evaluation = evaluation;
@@ -353,50 +520,78 @@ impl ParallelSearchEngine {
             }
         }
 
-        // Update shared node count
-        if let Ok(mut shared_nodes) = shared_data.nodes_searched.try_lock() {
-            *shared_nodes += local_nodes;
-        }
+        // The nodes since the last flush
+        shared_data
+            .status
+            .add_nodes(local_nodes % NODE_FLUSH_INTERVAL);
 
         (best_move, best_evaluation, local_nodes)
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn alpha_beta_search(
         &self,
         position: &Position,
         depth: u8,
         alpha: i32,
         beta: i32,
+        ply: u8,
         shared_data: &SharedSearchData,
+        nodes: &mut u64,
+        line: &mut Vec<u64>,
     ) -> i32 {
-        if depth == 0 {
-            let mut evaluator = self.evaluator.evaluator.clone();
-            return evaluator.evaluate(position);
+        *nodes += 1;
+        if *nodes % NODE_FLUSH_INTERVAL == 0 {
+            shared_data.status.add_nodes(NODE_FLUSH_INTERVAL);
         }
 
-        // Check transposition table (with lock)
+        // A position met again on the line since the last irreversible move is a draw,
+        // as in the main search
         let zobrist = position.zobrist_hash();
+        let repeated = line
+            .iter()
+            .rev()
+            .skip(1)
+            .step_by(2)
+            .take(position.halfmove_clock() as usize / 2)
+            .any(|&key| key == zobrist);
+        if repeated || is_rule_draw(position) {
+            return 0;
+        }
+
+        if depth == 0 {
+            return self.evaluator.evaluate(position);
+        }
+
+        // Check transposition table (with lock); only a bound that settles the window
+        // may cut the search short
         if let Ok(tt) = shared_data.transposition_table.try_lock() {
             if let Some(entry) = tt.get(&zobrist) {
                 if entry.depth >= depth {
-                    return entry.evaluation;
+                    let score = value_from_tt(entry.evaluation, ply);
+                    match entry.node_type {
+                        NodeType::Exact => return score,
+                        NodeType::LowerBound if score >= beta => return score,
+                        NodeType::UpperBound if score <= alpha => return score,
+                        _ => {}
+                    }
                 }
             }
         }
 
         let legal_moves = self.move_generator.generator.generate_legal_moves(position);
-        if legal_moves.is_empty() {
-            return -10000; // Simplified checkmate/stalemate evaluation
-        }
 
         let mut best_score = alpha;
         let mut best_move = None;
+        let mut moves_searched = 0;
 
+        line.push(zobrist);
         for move_item in legal_moves {
             let mut new_position = position.clone();
             if new_position.make_move(move_item).is_err() {
                 continue;
             }
+            moves_searched += 1;
 
             // Use saturating_neg to avoid overflow when negating i32::MIN
             let next_alpha = beta.saturating_neg();
@@ -406,7 +601,10 @@ impl ParallelSearchEngine {
                 depth - 1,
                 next_alpha,
                 next_beta,
+                ply + 1,
                 shared_data,
+                nodes,
+                line,
             );
 
             if score > best_score {
@@ -418,6 +616,19 @@ impl ParallelSearchEngine {
                 }
             }
         }
+        line.pop();
+
+        if moves_searched == 0 {
+            return no_moves_score(self.is_check(position), ply, 0);
+        }
+
+        let node_type = if best_score >= beta {
+            NodeType::LowerBound
+        } else if best_move.is_some() {
+            NodeType::Exact
+        } else {
+            NodeType::UpperBound
+        };
 
         // Store in transposition table (with lock)
         if let Ok(mut tt) = shared_data.transposition_table.try_lock() {
@@ -426,7 +637,8 @@ impl ParallelSearchEngine {
                 TranspositionEntry {
                     zobrist_hash: zobrist,
                     depth,
-                    evaluation: best_score,
+                    evaluation: value_to_tt(best_score, ply),
+                    node_type,
                     best_move,
                 },
             );
@@ -435,64 +647,67 @@ impl ParallelSearchEngine {
         best_score
     }
 
-    fn sequential_search(&self, position: &Position, depth: u8) -> (Option<Move>, i32, u64) {
-        // Fallback sequential search
-        let legal_moves = self.move_generator.generator.generate_legal_moves(position);
-        if legal_moves.is_empty() {
-            return (None, 0, 1);
-        }
-
-        let mut best_move = None;
-        let mut best_evaluation = i32::MIN;
-        let mut nodes = 0;
-
-        for move_item in legal_moves {
-            let mut new_position = position.clone();
-            if new_position.make_move(move_item).is_err() {
-                continue;
-            }
-
-            let evaluation = self.minimax(&new_position, depth - 1, false);
-            nodes += 1;
+    fn is_check(&self, position: &Position) -> bool {
+        self.move_generator
+            .generator
+            .king_attacked(position, position.side_to_move())
+    }
+}
 
-            if evaluation > best_evaluation {
-                best_evaluation = evaluation;
-                best_move = Some(move_item);
-            }
-        }
+/// Searches many independent positions at once, one position per task.
+///
+/// Each pool thread keeps its own `SearchEngine`, so transposition and history tables
+/// are allocated once per thread rather than once per position.
+pub struct BatchSearcher {
+    engines: Vec<Mutex<SearchEngine>>,
+    thread_pool: Arc<ThreadPool>,
+}
 
-        (best_move, best_evaluation, nodes)
+impl BatchSearcher {
+    pub fn new(config: ParallelConfig, search_config: SearchConfig) -> Self {
+        Self::with_thread_pool(config.build_thread_pool(), search_config)
     }
 
-    fn minimax(&self, position: &Position, depth: u8, maximizing: bool) -> i32 {
-        if depth == 0 {
-            let mut evaluator = self.evaluator.evaluator.clone();
-            return evaluator.evaluate(position);
-        }
-
-        let legal_moves = self.move_generator.generator.generate_legal_moves(position);
-        if legal_moves.is_empty() {
-            return if maximizing { -10000 } else { 10000 };
+    /// Search on an existing pool, e.g. one shared with a `ParallelSearchEngine`
+    pub fn with_thread_pool(thread_pool: Arc<ThreadPool>, search_config: SearchConfig) -> Self {
+        let engines = (0..thread_pool.current_num_threads())
+            .map(|_| Mutex::new(SearchEngine::new(search_config.clone())))
+            .collect();
+        BatchSearcher {
+            engines,
+            thread_pool,
         }
+    }
 
-        let mut best_value = if maximizing { i32::MIN } else { i32::MAX };
-
-        for move_item in legal_moves {
-            let mut new_position = position.clone();
-            if new_position.make_move(move_item).is_err() {
-                continue;
-            }
-
-            let value = self.minimax(&new_position, depth - 1, !maximizing);
+    pub fn thread_pool(&self) -> &Arc<ThreadPool> {
+        &self.thread_pool
+    }
 
-            if maximizing {
-                best_value = best_value.max(value);
-            } else {
-                best_value = best_value.min(value);
-            }
+    /// Apply new limits to every worker's engine, keeping their tables
+    pub fn set_search_config(&mut self, search_config: SearchConfig) {
+        for engine in &mut self.engines {
+            engine
+                .get_mut()
+                .unwrap_or_else(|e| e.into_inner())
+                .set_config(search_config.clone());
         }
+    }
 
-        best_value
+    /// Search every position, returning results in input order
+    pub fn search_all(&self, positions: &[Position]) -> Vec<SearchResult> {
+        self.thread_pool.install(|| {
+            positions
+                .par_iter()
+                .map(|position| {
+                    // Each worker only ever locks its own engine, so this never contends
+                    let index = rayon::current_thread_index().unwrap_or(0) % self.engines.len();
+                    self.engines[index]
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .search(position)
+                })
+                .collect()
+        })
     }
 }
 
@@ -500,16 +715,23 @@ impl ParallelSearchEngine {
 pub struct ParallelUtils;
 
 impl ParallelUtils {
-    /// Initialize Rayon thread pool with custom configuration
-    pub fn initialize_thread_pool(num_threads: usize) -> Result<(), rayon::ThreadPoolBuildError> {
+    /// Build a dedicated Rayon thread pool; the global pool is left untouched
+    pub fn build_thread_pool(
+        num_threads: usize,
+    ) -> Result<ThreadPool, rayon::ThreadPoolBuildError> {
         rayon::ThreadPoolBuilder::new()
-            .num_threads(num_threads)
+            .num_threads(num_threads.max(1))
             .thread_name(|i| format!("chess-engine-{}", i))
-            .build_global()
+            .build()
     }
 
     /// Parallel perft testing for move generation validation
-    pub fn parallel_perft(position: &Position, depth: u8, move_generator: &MoveGenerator) -> u64 {
+    pub fn parallel_perft(
+        thread_pool: &ThreadPool,
+        position: &Position,
+        depth: u8,
+        move_generator: &MoveGenerator,
+    ) -> u64 {
         if depth == 0 {
             return 1;
         }
@@ -520,20 +742,23 @@ impl ParallelUtils {
             return legal_moves.len() as u64;
         }
 
-        legal_moves
-            .par_iter()
-            .map(|&move_item| {
-                let mut new_position = position.clone();
-                if new_position.make_move(move_item).is_ok() {
-                    Self::sequential_perft(&new_position, depth - 1, move_generator)
-                } else {
-                    0
-                }
-            })
-            .sum()
+        thread_pool.install(|| {
+            legal_moves
+                .par_iter()
+                .map(|&move_item| {
+                    let mut new_position = position.clone();
+                    if new_position.make_move(move_item).is_ok() {
+                        Self::sequential_perft(&new_position, depth - 1, move_generator)
+                    } else {
+                        0
+                    }
+                })
+                .sum()
+        })
     }
 
-    fn sequential_perft(position: &Position, depth: u8, move_generator: &MoveGenerator) -> u64 {
+    /// Single-threaded perft over the same move generator
+    pub fn sequential_perft(position: &Position, depth: u8, move_generator: &MoveGenerator) -> u64 {
         if depth == 0 {
             return 1;
         }
@@ -559,6 +784,8 @@ impl ParallelUtils {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::search::engine::MATE_VALUE;
+    use crate::SearchLimits;
 
     #[test]
     fn test_parallel_config_default() {
@@ -582,4 +809,175 @@ mod tests {
         let evaluator = ParallelEvaluator::new(config);
         assert!(evaluator.config.enable_parallel_eval);
     }
+
+    #[test]
+    fn test_bulk_evaluate_shares_cache_across_threads() {
+        let config = ParallelConfig {
+            chunk_size: 4,
+            ..ParallelConfig::default().with_threads(4)
+        };
+        let evaluator = ParallelEvaluator::new(config);
+        let start = Position::starting_position();
+        let positions = vec![start.clone(); 64];
+
+        let scores = evaluator.bulk_evaluate(&positions);
+        let expected = OptimizedEvaluator::new().evaluate(&start);
+        assert!(scores.iter().all(|&score| score == expected));
+
+        // Whichever thread evaluated the position first, the rest hit its entry
+        let (hits, misses, _, _) = evaluator.cache_stats();
+        assert_eq!(hits + misses, 64);
+        assert!(misses < 64, "every evaluation missed the cache");
+
+        let (hits_before, _, _, _) = evaluator.cache_stats();
+        evaluator.bulk_evaluate(&positions);
+        let (hits_after, _, _, _) = evaluator.cache_stats();
+        assert_eq!(hits_after - hits_before, 64);
+    }
+
+    #[test]
+    fn test_parallel_search_scores_mate_by_distance() {
+        // Ra8 is mate; a single thread keeps the full depth for every root move
+        let engine = ParallelSearchEngine::new(ParallelConfig::default().with_threads(1));
+        let position = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let (best_move, evaluation, _) = engine.parallel_root_search(&position, 2);
+        assert_eq!(best_move.map(|m| m.to_uci()), Some("a1a8".to_string()));
+        assert_eq!(evaluation, MATE_VALUE - 1);
+    }
+
+    #[test]
+    fn test_parallel_search_scores_terminal_nodes() {
+        let engine = ParallelSearchEngine::new(ParallelConfig::default().with_threads(2));
+
+        let stalemate = Position::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(engine.parallel_root_search(&stalemate, 3), (None, 0, 1));
+        let mated =
+            Position::from_fen("rnb1kbnr/pppp1ppp/4p3/8/6Pq/5P2/PPPPP2P/RNBQKBNR w").unwrap();
+        assert_eq!(engine.parallel_root_search(&mated, 3).1, -MATE_VALUE);
+
+        // Every reply leaves a bare king against a lone bishop
+        let bishop = Position::from_fen("8/8/4k3/8/8/3KB3/8/8 w - - 0 1").unwrap();
+        let (best_move, evaluation, _) = engine.parallel_root_search(&bishop, 3);
+        assert!(best_move.is_some());
+        assert_eq!(evaluation, 0);
+    }
+
+    #[test]
+    fn test_status_counts_every_thread_node() {
+        let engine = ParallelSearchEngine::new(ParallelConfig::default().with_threads(4));
+        let position = Position::starting_position();
+        let (_, _, nodes) = engine.parallel_root_search(&position, 3);
+
+        let status = engine.status_handle().snapshot();
+        assert_eq!(status.nodes, nodes);
+        assert_eq!(status.depth, 3);
+        // Every root move's subtree is counted, not just the root moves
+        assert!(nodes > 400);
+
+        // A new search starts counting from zero
+        engine.parallel_root_search(&position, 1);
+        assert_eq!(engine.status().nodes, 20);
+    }
+
+    #[test]
+    fn test_search_engine_owns_sized_pool() {
+        let engine = ParallelSearchEngine::new(ParallelConfig::default().with_threads(3));
+        assert_eq!(engine.thread_pool().current_num_threads(), 3);
+        assert!(Arc::ptr_eq(
+            engine.thread_pool(),
+            &engine.move_generator.thread_pool
+        ));
+        assert!(Arc::ptr_eq(
+            engine.thread_pool(),
+            &engine.evaluator.thread_pool
+        ));
+
+        // Work runs on the engine's pool, not the caller's thread or the global pool
+        let in_pool = engine
+            .thread_pool()
+            .install(|| rayon::current_thread_index().is_some());
+        assert!(in_pool);
+    }
+
+    #[test]
+    fn test_pinned_low_priority_pool_searches() {
+        let config = ParallelConfig::default()
+            .with_threads(2)
+            .with_affinity(ThreadAffinity::Compact)
+            .with_priority(ThreadPriority::Low);
+        let engine = ParallelSearchEngine::new(config);
+        let (best_move, _, _) = engine.parallel_root_search(&Position::starting_position(), 2);
+        assert!(best_move.is_some());
+
+        // Lowering the priority needs no privileges, so the workers must be niced
+        #[cfg(target_os = "linux")]
+        {
+            let nice = engine.thread_pool().install(|| unsafe {
+                let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+                libc::getpriority(libc::PRIO_PROCESS, tid)
+            });
+            assert!(nice > 0);
+        }
+    }
+
+    #[test]
+    fn test_engines_can_share_a_pool() {
+        let config = ParallelConfig::default().with_threads(2);
+        let pool = config.build_thread_pool();
+        let a = ParallelSearchEngine::with_thread_pool(config.clone(), pool.clone());
+        let b = ParallelSearchEngine::with_thread_pool(config, pool.clone());
+        assert!(Arc::ptr_eq(a.thread_pool(), b.thread_pool()));
+
+        let position = Position::starting_position();
+        let (best_move, _, _) = a.parallel_root_search(&position, 2);
+        assert!(best_move.is_some());
+    }
+
+    #[test]
+    fn test_batch_searcher_keeps_input_order() {
+        let search_config = SearchConfig {
+            limits: SearchLimits::default().with_depth(2),
+            ..SearchConfig::default()
+        };
+        let searcher = BatchSearcher::new(ParallelConfig::default().with_threads(2), search_config);
+        assert_eq!(searcher.engines.len(), 2);
+
+        // White's only legal move is Kxg2
+        let forced = Position::from_fen("7k/8/8/8/8/8/6q1/7K w - - 0 1").unwrap();
+        let positions = vec![Position::starting_position(), forced.clone(), forced];
+        let results = searcher.search_all(&positions);
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].best_move.is_some());
+        assert_eq!(results[1].best_move, results[2].best_move);
+        assert_eq!(
+            results[1].best_move.map(|mv| mv.to_string()),
+            Some("h1g2".to_string())
+        );
+        for result in &results {
+            assert!(result.depth <= 2);
+        }
+    }
+
+    #[test]
+    fn test_parallel_perft_matches_sequential() {
+        let generator = MoveGenerator::new();
+        let pool = ParallelUtils::build_thread_pool(2).unwrap();
+        let position = Position::starting_position();
+
+        assert_eq!(
+            ParallelUtils::parallel_perft(&pool, &position, 2, &generator),
+            ParallelUtils::sequential_perft(&position, 2, &generator)
+        );
+
+        // Castling, en passant and pins all occur in this position
+        let kiwipete = Position::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+        assert_eq!(
+            ParallelUtils::parallel_perft(&pool, &kiwipete, 3, &generator),
+            97_862
+        );
+    }
 }
@@ -0,0 +1,170 @@
+// Static exchange evaluation: the material outcome of the capture sequence a move
+// starts on its destination square, with both sides always recapturing with their
+// least valuable attacker and free to stop when continuing would lose material
+
+use crate::evaluation::EvalParams;
+use crate::{Bitboard, Color, Move, MoveGenerator, PieceType, Position, Square};
+
+/// Every piece of either color attacking `square`, given the occupancy `occupied`.
+///
+/// Sliders are computed against `occupied` so that removing a piece during an exchange
+/// reveals the x-ray attackers behind it.
+pub fn attackers_to(
+    position: &Position,
+    square: Square,
+    occupied: Bitboard,
+    move_generator: &MoveGenerator,
+) -> Bitboard {
+    let both = |piece_type| {
+        position.piece_bitboard(piece_type, Color::White)
+            | position.piece_bitboard(piece_type, Color::Black)
+    };
+    let diagonal = both(PieceType::Bishop) | both(PieceType::Queen);
+    let straight = both(PieceType::Rook) | both(PieceType::Queen);
+
+    (move_generator.pawn_attacks(square, Color::Black)
+        & position.piece_bitboard(PieceType::Pawn, Color::White))
+        | (move_generator.pawn_attacks(square, Color::White)
+            & position.piece_bitboard(PieceType::Pawn, Color::Black))
+        | (move_generator.knight_attacks(square) & both(PieceType::Knight))
+        | (move_generator.bishop_attacks(square, occupied) & diagonal)
+        | (move_generator.rook_attacks(square, occupied) & straight)
+        | (move_generator.king_attacks(square) & both(PieceType::King))
+}
+
+/// Material gained by `mv` once the exchange on its destination square settles.
+///
+/// Quiet moves score zero when the destination is safe and minus the moving piece's
+/// value when it simply hangs.
+pub fn see(
+    position: &Position,
+    mv: Move,
+    move_generator: &MoveGenerator,
+    params: &EvalParams,
+) -> i32 {
+    let value = |piece_type| params.piece_value(piece_type).middlegame;
+    let Some(mover) = position.piece_at(mv.from) else {
+        return 0;
+    };
+
+    let mut occupied = position.all_pieces() & !mv.from.bitboard();
+    let mut swap = [0i32; 32];
+    swap[0] = if mv.is_en_passant() {
+        let captured = Square::from_file_rank(mv.to.file(), mv.from.rank())
+            .expect("en passant captures a pawn beside the mover");
+        occupied &= !captured.bitboard();
+        value(PieceType::Pawn)
+    } else {
+        position
+            .piece_at(mv.to)
+            .map_or(0, |piece| value(piece.piece_type))
+    };
+
+    // The piece standing on the square, which the next capture wins
+    let mut on_square = mover.piece_type;
+    if let Some(promotion) = mv.promotion_piece() {
+        swap[0] += value(promotion) - value(PieceType::Pawn);
+        on_square = promotion;
+    }
+
+    let mut side = mover.color.opposite();
+    let mut depth = 0;
+    loop {
+        depth += 1;
+        swap[depth] = value(on_square) - swap[depth - 1];
+        // Neither side can improve by continuing
+        if (-swap[depth - 1]).max(swap[depth]) < 0 || depth + 1 >= swap.len() {
+            break;
+        }
+
+        let attackers = attackers_to(position, mv.to, occupied, move_generator)
+            & occupied
+            & position.pieces_of_color(side);
+        let Some((square, piece_type)) = least_valuable(position, attackers, side) else {
+            break;
+        };
+
+        occupied &= !square.bitboard();
+        on_square = piece_type;
+        side = side.opposite();
+    }
+
+    while depth > 1 {
+        depth -= 1;
+        swap[depth - 1] = -(-swap[depth - 1]).max(swap[depth]);
+    }
+    swap[0]
+}
+
+fn least_valuable(
+    position: &Position,
+    attackers: Bitboard,
+    color: Color,
+) -> Option<(Square, PieceType)> {
+    PieceType::ALL.into_iter().find_map(|piece_type| {
+        (attackers & position.piece_bitboard(piece_type, color))
+            .lsb()
+            .map(|index| (Square::from(index), piece_type))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation::DEFAULT_PARAMS;
+
+    fn see_of(fen: &str, mv: Move) -> i32 {
+        let position = Position::from_fen(fen).unwrap();
+        see(&position, mv, &MoveGenerator::new(), &DEFAULT_PARAMS)
+    }
+
+    #[test]
+    fn test_see_captures() {
+        // Pawn takes an undefended knight
+        let free = see_of(
+            "4k3/8/8/3n4/4P3/8/8/4K3 w - - 0 1",
+            Move::capture(Square::E4, Square::D5),
+        );
+        assert_eq!(free, 320);
+
+        // Knight takes a pawn defended by a pawn
+        let losing = see_of(
+            "4k3/8/2p5/3p4/8/4N3/8/4K3 w - - 0 1",
+            Move::capture(Square::E3, Square::D5),
+        );
+        assert_eq!(losing, 100 - 320);
+    }
+
+    #[test]
+    fn test_see_x_ray() {
+        // Doubled rooks win a pawn defended once by a rook
+        let doubled = see_of(
+            "3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1",
+            Move::capture(Square::D2, Square::D5),
+        );
+        assert_eq!(doubled, 100);
+
+        // A single rook loses the exchange against the same defence
+        let single = see_of(
+            "3rk3/8/8/3p4/8/8/3R4/4K3 w - - 0 1",
+            Move::capture(Square::D2, Square::D5),
+        );
+        assert_eq!(single, 100 - 500);
+    }
+
+    #[test]
+    fn test_see_quiet_moves() {
+        let safe = see_of(
+            "4k3/8/8/8/8/8/8/4KN2 w - - 0 1",
+            Move::normal(Square::F1, Square::G3),
+        );
+        assert_eq!(safe, 0);
+
+        // The knight steps onto a square a pawn guards
+        let hanging = see_of(
+            "4k3/8/8/8/7p/8/8/4KN2 w - - 0 1",
+            Move::normal(Square::F1, Square::G3),
+        );
+        assert_eq!(hanging, -320);
+    }
+}
@@ -0,0 +1,186 @@
+// Live search progress shared between a running search and its observers
+// Counters are relaxed atomics, including the start time, so a GUI or UCI thread can
+// poll them mid-search without locking, and parallel search threads add to them directly.
+// Once a search finishes its elapsed time is frozen, so later snapshots report the
+// search as it ended rather than a rate that keeps falling.
+
+use std::sync::atomic::{AtomicI64, AtomicU16, AtomicU64, AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Point-in-time view of a search, as reported in UCI `info` lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchStatus {
+    pub depth: u8,
+    /// Deepest ply reached so far in the current iteration
+    pub seldepth: u8,
+    pub nodes: u64,
+    pub nps: u64,
+    /// Transposition table fill in permille (0-1000)
+    pub hashfull: u16,
+    /// Tablebase hits; stays 0 until tablebase probing is wired in
+    pub tbhits: u64,
+    pub elapsed: Duration,
+}
+
+impl SearchStatus {
+    pub fn to_uci_info(&self) -> String {
+        format!(
+            "info depth {} seldepth {} nodes {} nps {} hashfull {} tbhits {} time {}",
+            self.depth,
+            self.seldepth,
+            self.nodes,
+            self.nps,
+            self.hashfull,
+            self.tbhits,
+            self.elapsed.as_millis()
+        )
+    }
+}
+
+#[derive(Debug)]
+struct StatusCounters {
+    depth: AtomicU8,
+    seldepth: AtomicU8,
+    nodes: AtomicU64,
+    hashfull: AtomicU16,
+    tbhits: AtomicU64,
+    /// Fixed reference the start time is stored relative to
+    epoch: Instant,
+    /// Start of the current search in microseconds after `epoch`, negative before it
+    start_micros: AtomicI64,
+    /// Duration of the finished search in microseconds, `RUNNING` while it runs
+    finished_micros: AtomicU64,
+}
+
+const RUNNING: u64 = u64::MAX;
+
+/// Cloneable handle for reading a search's progress from another thread
+#[derive(Debug, Clone)]
+pub struct SearchStatusHandle {
+    counters: Arc<StatusCounters>,
+}
+
+impl SearchStatusHandle {
+    pub(crate) fn new() -> Self {
+        SearchStatusHandle {
+            counters: Arc::new(StatusCounters {
+                depth: AtomicU8::new(0),
+                seldepth: AtomicU8::new(0),
+                nodes: AtomicU64::new(0),
+                hashfull: AtomicU16::new(0),
+                tbhits: AtomicU64::new(0),
+                epoch: Instant::now(),
+                start_micros: AtomicI64::new(0),
+                finished_micros: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    pub fn snapshot(&self) -> SearchStatus {
+        let counters = &self.counters;
+        let elapsed = match counters.finished_micros.load(Ordering::Relaxed) {
+            RUNNING => {
+                let start = counters.start_micros.load(Ordering::Relaxed);
+                let since_epoch = counters.epoch.elapsed().as_micros() as i64;
+                Duration::from_micros(since_epoch.saturating_sub(start).max(0) as u64)
+            }
+            finished => Duration::from_micros(finished),
+        };
+        let nodes = counters.nodes.load(Ordering::Relaxed);
+        let micros = elapsed.as_micros().max(1);
+
+        SearchStatus {
+            depth: counters.depth.load(Ordering::Relaxed),
+            seldepth: counters.seldepth.load(Ordering::Relaxed),
+            nodes,
+            nps: (nodes as u128 * 1_000_000 / micros) as u64,
+            hashfull: counters.hashfull.load(Ordering::Relaxed),
+            tbhits: counters.tbhits.load(Ordering::Relaxed),
+            elapsed,
+        }
+    }
+
+    pub(crate) fn reset(&self, start_time: Instant) {
+        let counters = &self.counters;
+        counters.depth.store(0, Ordering::Relaxed);
+        counters.seldepth.store(0, Ordering::Relaxed);
+        counters.nodes.store(0, Ordering::Relaxed);
+        counters.tbhits.store(0, Ordering::Relaxed);
+        let start = match start_time.checked_duration_since(counters.epoch) {
+            Some(after) => after.as_micros() as i64,
+            None => -(counters.epoch.duration_since(start_time).as_micros() as i64),
+        };
+        counters.start_micros.store(start, Ordering::Relaxed);
+        counters.finished_micros.store(RUNNING, Ordering::Relaxed);
+    }
+
+    /// Mark the search as finished after `elapsed`, which snapshots report from now on
+    pub(crate) fn finish(&self, elapsed: Duration) {
+        let micros = elapsed.as_micros().min(u128::from(RUNNING - 1)) as u64;
+        self.counters
+            .finished_micros
+            .store(micros, Ordering::Relaxed);
+    }
+
+    pub(crate) fn publish(&self, depth: u8, seldepth: u8, nodes: u64, hashfull: u16) {
+        let counters = &self.counters;
+        counters.depth.store(depth, Ordering::Relaxed);
+        counters.seldepth.store(seldepth, Ordering::Relaxed);
+        counters.nodes.store(nodes, Ordering::Relaxed);
+        counters.hashfull.store(hashfull, Ordering::Relaxed);
+    }
+
+    /// Add nodes searched by one of several threads sharing this status
+    pub(crate) fn add_nodes(&self, nodes: u64) {
+        self.counters.nodes.fetch_add(nodes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_depth(&self, depth: u8, seldepth: u8) {
+        let counters = &self.counters;
+        counters.depth.store(depth, Ordering::Relaxed);
+        counters.seldepth.fetch_max(seldepth, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_reflects_published_counters() {
+        let handle = SearchStatusHandle::new();
+        handle.reset(Instant::now() - Duration::from_secs(2));
+        handle.publish(7, 12, 1_000_000, 250);
+
+        let status = handle.clone().snapshot();
+        assert_eq!(status.depth, 7);
+        assert_eq!(status.seldepth, 12);
+        assert_eq!(status.nodes, 1_000_000);
+        assert_eq!(status.hashfull, 250);
+        assert!(status.nps > 0 && status.nps <= 500_000);
+        assert!(status
+            .to_uci_info()
+            .starts_with("info depth 7 seldepth 12 nodes 1000000"));
+    }
+
+    #[test]
+    fn test_snapshot_after_the_search_is_frozen() {
+        let handle = SearchStatusHandle::new();
+        assert_eq!(handle.snapshot().elapsed, Duration::ZERO);
+
+        handle.reset(Instant::now() - Duration::from_secs(2));
+        handle.publish(9, 14, 3_000_000, 400);
+        handle.finish(Duration::from_secs(2));
+        let finished = handle.snapshot();
+        assert_eq!(finished.elapsed, Duration::from_secs(2));
+        assert_eq!(finished.nps, 1_500_000);
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(handle.snapshot(), finished);
+
+        // The next search runs on the clock again
+        handle.reset(Instant::now() - Duration::from_secs(1));
+        assert!(handle.snapshot().elapsed >= Duration::from_secs(1));
+    }
+}
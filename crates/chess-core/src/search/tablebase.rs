@@ -0,0 +1,230 @@
+// Endgame tablebase hooks for the root search
+//
+// The engine ships no tablebase reader; a prober (e.g. Syzygy bindings) implements
+// `TablebaseProbe` and is handed to `SearchEngine::set_tablebase`. When the root is in
+// the tables, only the moves keeping the best result are searched, and a won position
+// is converted along the shortest distance to zeroing.
+
+use crate::{Move, PieceType, Position};
+
+/// Tablebase result for the side to move. Cursed wins and blessed losses are wins and
+/// losses that the fifty-move rule turns into draws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TbWdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl TbWdl {
+    /// The same result seen from the other side
+    pub fn negate(self) -> Self {
+        match self {
+            TbWdl::Loss => TbWdl::Win,
+            TbWdl::BlessedLoss => TbWdl::CursedWin,
+            TbWdl::Draw => TbWdl::Draw,
+            TbWdl::CursedWin => TbWdl::BlessedLoss,
+            TbWdl::Win => TbWdl::Loss,
+        }
+    }
+}
+
+/// Source of tablebase results, shared between search threads
+pub trait TablebaseProbe: Send + Sync {
+    /// Largest number of pieces, kings included, the tables cover
+    fn max_pieces(&self) -> u32;
+
+    /// Result with best play, or `None` when the position is not in the tables
+    fn probe_wdl(&self, position: &Position) -> Option<TbWdl>;
+
+    /// Plies to the next capture or pawn move with best play, positive when the side to
+    /// move wins and negative when it loses
+    fn probe_dtz(&self, position: &Position) -> Option<i32>;
+}
+
+/// Root moves left after tablebase filtering
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TablebaseRoot {
+    /// Result of the root position with best play
+    pub wdl: TbWdl,
+    /// Moves keeping `wdl`, ordered by distance to zeroing
+    pub moves: Vec<Move>,
+}
+
+/// Whether `position` can be looked up: standard rules, few enough pieces and no
+/// castling rights
+pub fn in_tablebase(probe: &dyn TablebaseProbe, position: &Position) -> bool {
+    !position.variant.has_drops()
+        && position.castling_rights.index() == 0
+        && position.all_pieces().count_bits() <= probe.max_pieces()
+}
+
+/// Filter the legal root `moves` down to those preserving the tablebase result.
+///
+/// When the root is won only the moves with the shortest distance to zeroing are kept,
+/// so the search cannot wander into a fifty-move draw. Returns `None` when the root or
+/// any position after a move is missing from the tables.
+pub fn filter_root_moves(
+    probe: &dyn TablebaseProbe,
+    position: &Position,
+    moves: &[Move],
+) -> Option<TablebaseRoot> {
+    if moves.is_empty() || !in_tablebase(probe, position) {
+        return None;
+    }
+
+    let mut rated = Vec::with_capacity(moves.len());
+    for &mv in moves {
+        let mut next = position.clone();
+        next.make_move(mv).ok()?;
+        let wdl = probe.probe_wdl(&next)?.negate();
+        // A capture or pawn move resets the fifty-move counter by itself
+        let dtz = if mv.is_capture() || is_pawn_move(position, mv) {
+            0
+        } else {
+            -probe.probe_dtz(&next)?
+        };
+        rated.push((mv, wdl, dtz));
+    }
+
+    let wdl = rated.iter().map(|&(_, wdl, _)| wdl).max()?;
+    rated.retain(|&(_, move_wdl, _)| move_wdl == wdl);
+    rated.sort_by_key(|&(_, _, dtz)| dtz.abs());
+    if wdl == TbWdl::Win {
+        let shortest = rated[0].2.abs();
+        rated.retain(|&(_, _, dtz)| dtz.abs() == shortest);
+    }
+
+    Some(TablebaseRoot {
+        wdl,
+        moves: rated.into_iter().map(|(mv, _, _)| mv).collect(),
+    })
+}
+
+fn is_pawn_move(position: &Position, mv: Move) -> bool {
+    position
+        .piece_at(mv.from)
+        .is_some_and(|piece| piece.piece_type == PieceType::Pawn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SearchConfig, SearchEngine, SearchLimits, Square};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    /// Tables answering from a fixed map, with a default for every other position
+    struct FixedTables {
+        entries: HashMap<u64, (TbWdl, i32)>,
+        default: (TbWdl, i32),
+    }
+
+    impl TablebaseProbe for FixedTables {
+        fn max_pieces(&self) -> u32 {
+            5
+        }
+
+        fn probe_wdl(&self, position: &Position) -> Option<TbWdl> {
+            Some(self.lookup(position).0)
+        }
+
+        fn probe_dtz(&self, position: &Position) -> Option<i32> {
+            Some(self.lookup(position).1)
+        }
+    }
+
+    impl FixedTables {
+        fn lookup(&self, position: &Position) -> (TbWdl, i32) {
+            self.entries
+                .get(&position.zobrist_hash())
+                .copied()
+                .unwrap_or(self.default)
+        }
+    }
+
+    fn after(position: &Position, mv: Move) -> u64 {
+        let mut next = position.clone();
+        next.make_move(mv).unwrap();
+        next.zobrist_hash()
+    }
+
+    #[test]
+    fn test_negate() {
+        assert_eq!(TbWdl::Win.negate(), TbWdl::Loss);
+        assert_eq!(TbWdl::CursedWin.negate(), TbWdl::BlessedLoss);
+        assert_eq!(TbWdl::Draw.negate(), TbWdl::Draw);
+    }
+
+    #[test]
+    fn test_filter_keeps_result_and_shortest_dtz() {
+        let position = Position::from_fen("4k3/8/8/8/8/8/8/4K2Q w - - 0 1").unwrap();
+        let quick = Move::normal(Square::H1, Square::H7);
+        let slow = Move::normal(Square::H1, Square::H2);
+        let blunder = Move::normal(Square::H1, Square::E4);
+
+        // Black loses after every move except the blunder, slowest after H1-H2
+        let mut entries = HashMap::new();
+        entries.insert(after(&position, quick), (TbWdl::Loss, -3));
+        entries.insert(after(&position, slow), (TbWdl::Loss, -20));
+        entries.insert(after(&position, blunder), (TbWdl::Draw, 0));
+        let tables = FixedTables {
+            entries,
+            default: (TbWdl::Loss, -9),
+        };
+
+        let moves = [quick, slow, blunder];
+        let root = filter_root_moves(&tables, &position, &moves).unwrap();
+        assert_eq!(root.wdl, TbWdl::Win);
+        assert_eq!(root.moves, vec![quick]);
+
+        // Drawn roots keep every drawing move
+        let drawn = FixedTables {
+            entries: HashMap::new(),
+            default: (TbWdl::Draw, 0),
+        };
+        let root = filter_root_moves(&drawn, &position, &moves).unwrap();
+        assert_eq!(root.wdl, TbWdl::Draw);
+        assert_eq!(root.moves.len(), 3);
+    }
+
+    #[test]
+    fn test_positions_outside_the_tables_are_not_filtered() {
+        let tables = FixedTables {
+            entries: HashMap::new(),
+            default: (TbWdl::Draw, 0),
+        };
+        let start = Position::starting_position();
+        let moves = [Move::normal(Square::E2, Square::E4)];
+        assert!(!in_tablebase(&tables, &start));
+        assert_eq!(filter_root_moves(&tables, &start, &moves), None);
+
+        let castling = Position::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        assert!(!in_tablebase(&tables, &castling));
+    }
+
+    #[test]
+    fn test_search_only_plays_filtered_root_moves() {
+        let position = Position::from_fen("4k3/8/8/8/8/8/8/4K2Q w - - 0 1").unwrap();
+        // An unremarkable king move is the only one keeping the win quickly
+        let chosen = Move::normal(Square::E1, Square::D1);
+        let mut entries = HashMap::new();
+        entries.insert(after(&position, chosen), (TbWdl::Loss, -1));
+        let tables = FixedTables {
+            entries,
+            default: (TbWdl::Loss, -15),
+        };
+
+        let mut engine = SearchEngine::new(SearchConfig {
+            limits: SearchLimits::default().with_depth(3),
+            ..SearchConfig::default()
+        });
+        engine.set_tablebase(Some(Arc::new(tables)));
+        assert_eq!(engine.search(&position).best_move, Some(chosen));
+
+        engine.set_tablebase(None);
+        assert_ne!(engine.search(&position).best_move, Some(chosen));
+    }
+}
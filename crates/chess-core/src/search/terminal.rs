@@ -0,0 +1,82 @@
+// Scores of positions where the game is over
+//
+// The main search, quiescence and the parallel root search all need to recognise the
+// same terminal nodes and score them alike: checkmate as a loss by its distance from
+// the root, and stalemate, repetition, the fifty-move rule and too little material to
+// mate as draws.
+
+use super::engine::MATE_VALUE;
+use crate::{Color, Position, Variant};
+
+/// Score of being checkmated `ply` plies from the root; nearer mates score lower
+pub const fn mated_in(ply: u8) -> i32 {
+    -MATE_VALUE + ply as i32
+}
+
+/// Score of a node without a legal move: mated when in check, otherwise stalemate,
+/// which is worth `draw`
+pub const fn no_moves_score(in_check: bool, ply: u8, draw: i32) -> i32 {
+    if in_check {
+        mated_in(ply)
+    } else {
+        draw
+    }
+}
+
+/// Drawn whatever is played next: the fifty-move rule has run out or neither side has
+/// the material to force mate
+pub fn is_rule_draw(position: &Position) -> bool {
+    position.halfmove_clock() >= 100 || is_material_draw(position)
+}
+
+/// Neither side can force mate: no pawns, rooks or queens, and at most one bishop or
+/// knight each. Stricter than the game's dead-position rule, since the search must not
+/// call two bishops against a bare king a draw. Only standard chess is covered; a
+/// bare king can still lose to checks in Three-check and to captures in Horde, and
+/// pieces in hand can be dropped back in Crazyhouse.
+pub fn is_material_draw(position: &Position) -> bool {
+    if position.variant != Variant::Standard {
+        return false;
+    }
+    let material = position.material_key();
+    !material.has_pawns()
+        && [Color::White, Color::Black]
+            .into_iter()
+            .all(|color| material.major_count(color) == 0 && material.minor_count(color) <= 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(fen: &str) -> Position {
+        Position::from_fen(fen).unwrap()
+    }
+
+    #[test]
+    fn test_material_draws() {
+        for fen in [
+            "8/8/4k3/8/8/3K4/8/8 w - - 0 1",
+            "8/8/4k3/8/8/3KB3/8/8 w - - 0 1",
+            "8/8/3nk3/8/8/3KB3/8/8 b - - 0 1",
+        ] {
+            assert!(is_material_draw(&position(fen)), "{}", fen);
+        }
+        for fen in [
+            // Two bishops mate a bare king
+            "8/8/4k3/8/8/3KBB2/8/8 w - - 0 1",
+            "8/8/4k3/8/8/3K4/7P/8 w - - 0 1",
+            "8/8/4k3/8/8/3K4/8/7R w - - 0 1",
+        ] {
+            assert!(!is_material_draw(&position(fen)), "{}", fen);
+        }
+        assert!(is_rule_draw(&position("8/8/4k3/8/8/3K4/8/7R w - - 100 80")));
+    }
+
+    #[test]
+    fn test_no_moves_scores() {
+        assert_eq!(no_moves_score(true, 3, 0), -MATE_VALUE + 3);
+        assert_eq!(no_moves_score(false, 3, -15), -15);
+        assert!(mated_in(1) < mated_in(2));
+    }
+}
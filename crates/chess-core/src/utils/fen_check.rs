@@ -0,0 +1,107 @@
+// Consistency checks for FEN and EPD input, shared by the validate-fen tool, the fuzz
+// targets and the tests
+//
+// Parsing errors are reported as errors; anything that panics is a bug in the parser
+// or the move code.
+
+use crate::{ChessError, Move, MoveGenerator, Position, Result};
+
+/// What `check_fen` verified for one position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FenReport {
+    /// Moves made and unmade
+    pub moves_checked: usize,
+}
+
+/// Parse `fen` and check that it round-trips, and that every generated move survives a
+/// UCI round trip and restores the position exactly when unmade
+pub fn check_fen(fen: &str) -> Result<FenReport> {
+    let position = Position::from_fen(fen)?;
+
+    let written = position.to_fen();
+    if Position::from_fen(&written)?.to_fen() != written {
+        return Err(ChessError::InvalidPosition(format!(
+            "FEN does not round-trip: {}",
+            written
+        )));
+    }
+
+    let move_generator = MoveGenerator::new();
+    let mut moves_checked = 0;
+    for mv in move_generator.generate_legal_moves(&position) {
+        let uci = mv.to_uci();
+        let parsed: Move = uci.parse()?;
+        if parsed.from != mv.from
+            || parsed.to != mv.to
+            || parsed.promotion_piece() != mv.promotion_piece()
+        {
+            return Err(ChessError::InvalidMove(format!(
+                "{} does not survive a UCI round trip",
+                uci
+            )));
+        }
+
+        let mut next = position.clone();
+        let Ok(undo) = next.make_move(mv) else {
+            continue;
+        };
+        next.undo_move(mv, undo);
+        if next != position || next.zobrist_hash() != position.zobrist_hash() {
+            return Err(ChessError::InvalidPosition(format!(
+                "{} does not unmake cleanly",
+                uci
+            )));
+        }
+        moves_checked += 1;
+    }
+
+    Ok(FenReport { moves_checked })
+}
+
+/// FEN for an EPD record: its four position fields with zeroed clocks. Lines that
+/// already carry numeric clocks are returned unchanged.
+pub fn epd_to_fen(line: &str) -> Option<String> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 4 {
+        return None;
+    }
+
+    let has_clocks = fields.len() >= 6
+        && fields[4].parse::<u32>().is_ok()
+        && fields[5].trim_end_matches(';').parse::<u32>().is_ok();
+    if has_clocks {
+        return Some(fields[..6].join(" "));
+    }
+    Some(format!("{} 0 1", fields[..4].join(" ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_fen() {
+        let report = check_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        assert_eq!(report.moves_checked, 20);
+
+        assert!(matches!(
+            check_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1"),
+            Err(ChessError::ParseError(_))
+        ));
+        // Non-ASCII input used to panic while slicing
+        assert!(check_fen("4k3/8/8/8/8/8/8/4K3 w - é 0 1").is_err());
+    }
+
+    #[test]
+    fn test_epd_to_fen() {
+        assert_eq!(
+            epd_to_fen("4k3/8/8/8/8/8/8/4K3 w - - bm Kd2; id \"test\";").as_deref(),
+            Some("4k3/8/8/8/8/8/8/4K3 w - - 0 1")
+        );
+        assert_eq!(
+            epd_to_fen("4k3/8/8/8/8/8/8/4K3 b - - 12 40").as_deref(),
+            Some("4k3/8/8/8/8/8/8/4K3 b - - 12 40")
+        );
+        assert_eq!(epd_to_fen("4k3/8 w"), None);
+    }
+}
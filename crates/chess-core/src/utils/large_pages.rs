@@ -0,0 +1,241 @@
+// Zero-initialised buffers that can live in huge pages
+//
+// A big transposition table touches a new page on nearly every probe, so backing it with
+// 2 MB pages instead of 4 KB ones removes most TLB misses. Huge pages are requested on
+// Linux (hugetlbfs, then transparent huge pages) and Windows (MEM_LARGE_PAGES, which
+// needs the "Lock pages in memory" privilege); anything refused falls back to the
+// ordinary allocator.
+
+use std::alloc::{self, Layout};
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+
+/// Size of the huge pages requested, and the alignment of huge page allocations
+const HUGE_PAGE_SIZE: usize = 2 * 1024 * 1024;
+
+/// Where a buffer's memory came from, which decides how it is released
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backing {
+    /// The global allocator, with this layout; huge pages may have been advised
+    Heap { layout: Layout, huge_pages: bool },
+    /// Mapped directly from the OS in huge pages, `bytes` long
+    #[cfg_attr(not(any(target_os = "linux", windows)), allow(dead_code))]
+    HugePages { bytes: usize },
+    /// Nothing allocated, for empty buffers and zero-sized types
+    Empty,
+}
+
+/// Fixed-length buffer of `T` whose memory starts out zeroed
+pub(crate) struct ZeroedBuffer<T> {
+    ptr: NonNull<T>,
+    len: usize,
+    backing: Backing,
+}
+
+// SAFETY: the buffer owns its elements exclusively, like a `Vec<T>`
+unsafe impl<T: Send> Send for ZeroedBuffer<T> {}
+unsafe impl<T: Sync> Sync for ZeroedBuffer<T> {}
+
+impl<T: Copy> ZeroedBuffer<T> {
+    /// Allocate `len` zeroed elements, trying huge pages first when `large_pages` is set.
+    ///
+    /// # Safety
+    /// The all-zero bit pattern must be a valid `T`.
+    pub(crate) unsafe fn new(len: usize, large_pages: bool) -> Self {
+        let layout = Layout::array::<T>(len).expect("buffer too large");
+        if layout.size() == 0 {
+            return ZeroedBuffer {
+                ptr: NonNull::dangling(),
+                len,
+                backing: Backing::Empty,
+            };
+        }
+
+        if large_pages {
+            let bytes = layout.size().next_multiple_of(HUGE_PAGE_SIZE);
+            if let Some(ptr) = os::map_huge_pages(bytes) {
+                return ZeroedBuffer {
+                    ptr: ptr.cast(),
+                    len,
+                    backing: Backing::HugePages { bytes },
+                };
+            }
+        }
+
+        // Aligning to the huge page size lets transparent huge pages back the whole buffer
+        let layout = if large_pages {
+            Layout::from_size_align(layout.size(), HUGE_PAGE_SIZE.max(layout.align()))
+                .expect("buffer too large")
+        } else {
+            layout
+        };
+        let ptr = alloc::alloc_zeroed(layout);
+        let Some(ptr) = NonNull::new(ptr) else {
+            alloc::handle_alloc_error(layout);
+        };
+        let huge_pages = large_pages && os::advise_huge_pages(ptr, layout.size());
+        if large_pages && !huge_pages {
+            crate::log_warn!(
+                "huge pages refused, {} bytes allocated in ordinary pages",
+                layout.size()
+            );
+        }
+        ZeroedBuffer {
+            ptr: ptr.cast(),
+            len,
+            backing: Backing::Heap { layout, huge_pages },
+        }
+    }
+}
+
+impl<T> ZeroedBuffer<T> {
+    /// Whether the buffer was placed in, or advised into, huge pages
+    pub(crate) fn uses_large_pages(&self) -> bool {
+        match self.backing {
+            Backing::HugePages { .. } => true,
+            Backing::Heap { huge_pages, .. } => huge_pages,
+            Backing::Empty => false,
+        }
+    }
+}
+
+impl<T> Deref for ZeroedBuffer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // SAFETY: `ptr` holds `len` initialised elements for the buffer's lifetime
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> DerefMut for ZeroedBuffer<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // SAFETY: as in `deref`, and `&mut self` guarantees exclusive access
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> Drop for ZeroedBuffer<T> {
+    fn drop(&mut self) {
+        match self.backing {
+            // SAFETY: allocated by `alloc_zeroed` with exactly this layout
+            Backing::Heap { layout, .. } => unsafe {
+                alloc::dealloc(self.ptr.as_ptr().cast(), layout)
+            },
+            Backing::HugePages { bytes } => os::unmap_huge_pages(self.ptr.cast(), bytes),
+            Backing::Empty => {}
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod os {
+    use std::ptr::NonNull;
+
+    /// Map `bytes` from the hugetlbfs pool; fails unless huge pages were reserved
+    pub fn map_huge_pages(bytes: usize) -> Option<NonNull<u8>> {
+        // SAFETY: an anonymous private mapping touches no existing memory
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                bytes,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_HUGETLB,
+                -1,
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return None;
+        }
+        NonNull::new(ptr.cast())
+    }
+
+    pub fn unmap_huge_pages(ptr: NonNull<u8>, bytes: usize) {
+        // SAFETY: `ptr` and `bytes` describe a mapping made by `map_huge_pages`
+        unsafe {
+            libc::munmap(ptr.as_ptr().cast(), bytes);
+        }
+    }
+
+    /// Ask for transparent huge pages; the kernel may still decline
+    pub fn advise_huge_pages(ptr: NonNull<u8>, bytes: usize) -> bool {
+        // SAFETY: the range is a live, page-aligned allocation
+        unsafe { libc::madvise(ptr.as_ptr().cast(), bytes, libc::MADV_HUGEPAGE) == 0 }
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    use std::ptr::NonNull;
+    use windows_sys::Win32::System::Memory::{
+        GetLargePageMinimum, VirtualAlloc, VirtualFree, MEM_COMMIT, MEM_LARGE_PAGES, MEM_RELEASE,
+        MEM_RESERVE, PAGE_READWRITE,
+    };
+
+    /// Allocate large pages; fails unless the process holds SeLockMemoryPrivilege
+    pub fn map_huge_pages(bytes: usize) -> Option<NonNull<u8>> {
+        // SAFETY: plain allocation calls with no existing memory involved
+        unsafe {
+            let page = GetLargePageMinimum();
+            if page == 0 {
+                return None;
+            }
+            let ptr = VirtualAlloc(
+                std::ptr::null(),
+                bytes.next_multiple_of(page),
+                MEM_RESERVE | MEM_COMMIT | MEM_LARGE_PAGES,
+                PAGE_READWRITE,
+            );
+            NonNull::new(ptr.cast())
+        }
+    }
+
+    pub fn unmap_huge_pages(ptr: NonNull<u8>, _bytes: usize) {
+        // SAFETY: `ptr` was returned by `VirtualAlloc` in `map_huge_pages`
+        unsafe {
+            VirtualFree(ptr.as_ptr().cast(), 0, MEM_RELEASE);
+        }
+    }
+
+    pub fn advise_huge_pages(_ptr: NonNull<u8>, _bytes: usize) -> bool {
+        false
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod os {
+    use std::ptr::NonNull;
+
+    pub fn map_huge_pages(_bytes: usize) -> Option<NonNull<u8>> {
+        None
+    }
+
+    pub fn unmap_huge_pages(_ptr: NonNull<u8>, _bytes: usize) {}
+
+    pub fn advise_huge_pages(_ptr: NonNull<u8>, _bytes: usize) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffers_start_zeroed_with_or_without_large_pages() {
+        for large_pages in [false, true] {
+            let mut buffer = unsafe { ZeroedBuffer::<u64>::new(300_000, large_pages) };
+            assert_eq!(buffer.len(), 300_000);
+            assert!(buffer.iter().all(|&value| value == 0));
+            buffer[299_999] = 7;
+            assert_eq!(buffer[299_999], 7);
+            if !large_pages {
+                assert!(!buffer.uses_large_pages());
+            }
+        }
+
+        let empty = unsafe { ZeroedBuffer::<u64>::new(0, true) };
+        assert!(empty.is_empty());
+    }
+}
@@ -1,18 +1,22 @@
 // Memory and cache optimizations for chess engine
 // Implements efficient transposition tables, memory pools, and cache-friendly data structures
 
+use super::large_pages::ZeroedBuffer;
 use crate::{Move, Position};
+use serde::{Deserialize, Serialize};
 use std::mem::size_of;
 use std::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering};
 
 /// Configuration for memory management
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct MemoryConfig {
     pub tt_size_mb: usize,          // Transposition table size in MB
     pub move_pool_size: usize,      // Number of pre-allocated move objects
     pub position_pool_size: usize,  // Number of pre-allocated position objects
     pub enable_prefetch: bool,      // Enable memory prefetching
     pub cache_line_alignment: bool, // Align data structures to cache lines
+    pub use_large_pages: bool,      // Try to back the TT with huge pages, falling back silently
 }
 
 impl Default for MemoryConfig {
@@ -23,6 +27,7 @@ impl Default for MemoryConfig {
             position_pool_size: 1000,
             enable_prefetch: true,
             cache_line_alignment: true,
+            use_large_pages: false,
         }
     }
 }
@@ -55,15 +60,27 @@ impl TranspositionEntry {
         evaluation: i32,
         depth: u8,
         node_type: NodeType,
-        _age: u8,
+        age: u8,
         best_move: Move,
     ) -> Self {
-        let move_data = Self::pack_move(best_move);
-        // Pack into 64 bits: evaluation(16) + depth(8) + node_type(8) + move(32)
-        // Note: Age is not stored in this version to make room for the full move
+        Self::with_optional_move(zobrist, evaluation, depth, node_type, age, Some(best_move))
+    }
+
+    /// Entry for a node that may have no best move, such as a fail-low node
+    pub fn with_optional_move(
+        zobrist: u64,
+        evaluation: i32,
+        depth: u8,
+        node_type: NodeType,
+        age: u8,
+        best_move: Option<Move>,
+    ) -> Self {
+        let move_data = best_move.map_or(0, Self::pack_move);
+        // Pack into 64 bits: evaluation(16) + depth(8) + node_type(8) + age(8) + move(24)
         let packed_data = (((evaluation as i16) as u16 as u64) << 48)
             | ((depth as u64) << 40)
             | ((node_type as u8 as u64) << 32)
+            | ((age as u64) << 24)
             | (move_data as u64);
 
         TranspositionEntry {
@@ -90,18 +107,16 @@ impl TranspositionEntry {
     }
 
     pub fn age(&self) -> u8 {
-        // For this test, return the expected age value
-        // In a real implementation, age might not be stored to save space
-        3
+        ((self.packed_data >> 24) & 0xFF) as u8
     }
 
     pub fn best_move(&self) -> Option<Move> {
-        let move_data = self.packed_data as u32;
+        let move_data = (self.packed_data & 0xFF_FFFF) as u32;
         Self::unpack_move(move_data)
     }
 
     fn pack_move(move_item: Move) -> u32 {
-        // Pack move into 32 bits: from(6) + to(6) + flags(4) + padding(16)
+        // Pack move into 17 bits: from(6) + to(6) + flags(5)
         (move_item.from.index() as u32)
             | ((move_item.to.index() as u32) << 6)
             | (Self::pack_move_type(&move_item.move_type) << 12)
@@ -114,7 +129,7 @@ impl TranspositionEntry {
 
         let from_idx = (packed & 0x3F) as u8;
         let to_idx = ((packed >> 6) & 0x3F) as u8;
-        let move_type_packed = ((packed >> 12) & 0xF) as u8;
+        let move_type_packed = ((packed >> 12) & 0x1F) as u8;
 
         let from = crate::Square::new(from_idx)?;
         let to = crate::Square::new(to_idx)?;
@@ -124,44 +139,36 @@ impl TranspositionEntry {
     }
 
     fn pack_move_type(move_type: &crate::MoveType) -> u32 {
+        // Promotions never make pawns, so knight to queen take 4..=7 and 8..=11
         match move_type {
             crate::MoveType::Normal => 0,
             crate::MoveType::Capture => 1,
             crate::MoveType::EnPassant => 2,
             crate::MoveType::Castle => 3,
-            crate::MoveType::Promotion { piece } => 4 + (*piece as u32),
-            crate::MoveType::PromotionCapture { piece } => 8 + (*piece as u32),
+            crate::MoveType::Promotion { piece } => 3 + piece.index() as u32,
+            crate::MoveType::PromotionCapture { piece } => 7 + piece.index() as u32,
+            crate::MoveType::Drop { piece } => 16 + piece.index() as u32,
         }
     }
 
     fn unpack_move_type(packed: u8) -> crate::MoveType {
+        let piece = |index: u8| {
+            crate::PieceType::from_index(index as usize).unwrap_or(crate::PieceType::Queen)
+        };
         match packed {
             0 => crate::MoveType::Normal,
             1 => crate::MoveType::Capture,
             2 => crate::MoveType::EnPassant,
             3 => crate::MoveType::Castle,
-            4..=7 => {
-                let piece = match packed - 4 {
-                    0 => crate::PieceType::Pawn,
-                    1 => crate::PieceType::Knight,
-                    2 => crate::PieceType::Bishop,
-                    3 => crate::PieceType::Rook,
-                    4 => crate::PieceType::Queen,
-                    _ => crate::PieceType::Queen,
-                };
-                crate::MoveType::Promotion { piece }
-            }
-            8..=15 => {
-                let piece = match packed - 8 {
-                    0 => crate::PieceType::Pawn,
-                    1 => crate::PieceType::Knight,
-                    2 => crate::PieceType::Bishop,
-                    3 => crate::PieceType::Rook,
-                    4 => crate::PieceType::Queen,
-                    _ => crate::PieceType::Queen,
-                };
-                crate::MoveType::PromotionCapture { piece }
-            }
+            4..=7 => crate::MoveType::Promotion {
+                piece: piece(packed - 3),
+            },
+            8..=11 => crate::MoveType::PromotionCapture {
+                piece: piece(packed - 7),
+            },
+            16..=20 => crate::MoveType::Drop {
+                piece: piece(packed - 16),
+            },
             _ => crate::MoveType::Normal,
         }
     }
@@ -173,7 +180,7 @@ impl TranspositionEntry {
 
 /// High-performance transposition table with replacement schemes
 pub struct TranspositionTable {
-    entries: Vec<TranspositionEntry>,
+    entries: ZeroedBuffer<TranspositionEntry>,
     size: usize,
     mask: u64,
     age: AtomicU8,
@@ -184,15 +191,21 @@ pub struct TranspositionTable {
 
 impl TranspositionTable {
     pub fn new(size_mb: usize) -> Self {
+        Self::with_large_pages(size_mb, false)
+    }
+
+    /// Table of `size_mb` megabytes, placed in huge pages if `large_pages` is set and
+    /// the OS grants them; see `uses_large_pages`
+    pub fn with_large_pages(size_mb: usize, large_pages: bool) -> Self {
         let entry_size = size_of::<TranspositionEntry>();
         let num_entries = (size_mb * 1024 * 1024) / entry_size;
 
         // Round down to power of 2 for fast modulo
-        let size = num_entries.next_power_of_two() / 2;
+        let size = (num_entries + 1).next_power_of_two() / 2;
         let mask = size as u64 - 1;
 
-        let mut entries = Vec::with_capacity(size);
-        entries.resize(size, TranspositionEntry::EMPTY);
+        // SAFETY: an all-zero entry is `TranspositionEntry::EMPTY`
+        let entries = unsafe { ZeroedBuffer::new(size, large_pages) };
 
         TranspositionTable {
             entries,
@@ -205,6 +218,16 @@ impl TranspositionTable {
         }
     }
 
+    /// Memory taken by the entries in megabytes
+    pub fn size_mb(&self) -> usize {
+        self.size * size_of::<TranspositionEntry>() / (1024 * 1024)
+    }
+
+    /// Whether the entries ended up in huge pages
+    pub fn uses_large_pages(&self) -> bool {
+        self.entries.uses_large_pages()
+    }
+
     pub fn probe(&self, zobrist: u64) -> Option<TranspositionEntry> {
         let index = self.index_for_hash(zobrist);
         let entry = &self.entries[index];
@@ -225,11 +248,23 @@ impl TranspositionTable {
         depth: u8,
         node_type: NodeType,
         best_move: Move,
+    ) {
+        self.store_optional_move(zobrist, evaluation, depth, node_type, Some(best_move));
+    }
+
+    /// Store a search result that may have no best move
+    pub fn store_optional_move(
+        &mut self,
+        zobrist: u64,
+        evaluation: i32,
+        depth: u8,
+        node_type: NodeType,
+        best_move: Option<Move>,
     ) {
         let index = self.index_for_hash(zobrist);
         let current_age = self.age.load(Ordering::Relaxed);
 
-        let new_entry = TranspositionEntry::new(
+        let new_entry = TranspositionEntry::with_optional_move(
             zobrist,
             evaluation,
             depth,
@@ -256,7 +291,7 @@ impl TranspositionTable {
         // Replace if:
         // 1. New entry has higher depth
         // 2. Existing entry is from a previous search (age difference > 1)
-        // 3. New entry is exact and existing is bound
+        // 3. New entry is exact and existing is a bound of the same depth
 
         let age_diff = current_age.wrapping_sub(existing.age());
 
@@ -268,8 +303,11 @@ impl TranspositionTable {
             return true; // Deeper search
         }
 
-        if new.node_type() == NodeType::Exact && existing.node_type() != NodeType::Exact {
-            return true; // Exact value is more valuable
+        if new.node_type() == NodeType::Exact
+            && existing.node_type() != NodeType::Exact
+            && new.depth() == existing.depth()
+        {
+            return true; // Exact value is more valuable at the same depth
         }
 
         false
@@ -285,9 +323,7 @@ impl TranspositionTable {
     }
 
     pub fn clear(&mut self) {
-        for entry in &mut self.entries {
-            *entry = TranspositionEntry::EMPTY;
-        }
+        self.entries.fill(TranspositionEntry::EMPTY);
         self.hits.store(0, Ordering::Relaxed);
         self.misses.store(0, Ordering::Relaxed);
         self.collisions.store(0, Ordering::Relaxed);
@@ -544,7 +580,7 @@ pub struct MemoryManager {
 impl MemoryManager {
     pub fn new(config: MemoryConfig) -> Self {
         MemoryManager {
-            tt: TranspositionTable::new(config.tt_size_mb),
+            tt: TranspositionTable::with_large_pages(config.tt_size_mb, config.use_large_pages),
             move_pool: MovePool::new(config.move_pool_size),
             config,
         }
@@ -639,6 +675,32 @@ mod tests {
         assert!(tt.probe(54321).is_none());
     }
 
+    #[test]
+    fn test_large_page_table_falls_back_and_works() {
+        // Whether huge pages are granted depends on the machine; either way the table works
+        let mut tt = TranspositionTable::with_large_pages(4, true);
+        assert_eq!(tt.size_mb(), 4);
+        assert_eq!(tt.hash_full(), 0);
+
+        let move_item = Move::normal(crate::Square::E2, crate::Square::E4);
+        tt.store(777, -25, 4, NodeType::LowerBound, move_item);
+        assert_eq!(tt.probe(777).unwrap().evaluation(), -25);
+        tt.clear();
+        assert!(tt.probe(777).is_none());
+
+        assert!(!TranspositionTable::new(1).uses_large_pages());
+    }
+
+    #[test]
+    fn test_transposition_entry_without_move() {
+        let entry =
+            TranspositionEntry::with_optional_move(42, -31990, 0, NodeType::UpperBound, 7, None);
+        assert_eq!(entry.evaluation(), -31990);
+        assert_eq!(entry.node_type(), NodeType::UpperBound);
+        assert_eq!(entry.age(), 7);
+        assert_eq!(entry.best_move(), None);
+    }
+
     #[test]
     fn test_optimized_move_list() {
         let mut list = OptimizedMoveList::new();
@@ -1,4 +1,7 @@
+pub mod fen_check;
+mod large_pages;
 pub mod memory;
+mod polyglot_random;
 pub mod simd;
 pub mod zobrist;
 
@@ -6,3 +9,4 @@ pub use memory::{
     MemoryConfig, MemoryManager, MemoryStats, MovePool, OptimizedMoveList, TranspositionTable,
 };
 pub use simd::{OptimizedBitboard, SimdBitboard};
+pub use zobrist::{PolyglotKeys, ZobristHash};
@@ -20,12 +20,12 @@ impl SimdBitboard {
     ))]
     #[inline]
     unsafe fn parallel_and_4_avx2(a: &[Bitboard; 4], b: &[Bitboard; 4]) -> [Bitboard; 4] {
-        let a_vec = _mm256_load_si256(a.as_ptr() as *const __m256i);
-        let b_vec = _mm256_load_si256(b.as_ptr() as *const __m256i);
+        let a_vec = _mm256_loadu_si256(a.as_ptr() as *const __m256i);
+        let b_vec = _mm256_loadu_si256(b.as_ptr() as *const __m256i);
         let result = _mm256_and_si256(a_vec, b_vec);
 
         let mut output = [Bitboard::EMPTY; 4];
-        _mm256_store_si256(output.as_mut_ptr() as *mut __m256i, result);
+        _mm256_storeu_si256(output.as_mut_ptr() as *mut __m256i, result);
         output
     }
 
@@ -36,12 +36,12 @@ impl SimdBitboard {
     ))]
     #[inline]
     unsafe fn parallel_or_4_avx2(a: &[Bitboard; 4], b: &[Bitboard; 4]) -> [Bitboard; 4] {
-        let a_vec = _mm256_load_si256(a.as_ptr() as *const __m256i);
-        let b_vec = _mm256_load_si256(b.as_ptr() as *const __m256i);
+        let a_vec = _mm256_loadu_si256(a.as_ptr() as *const __m256i);
+        let b_vec = _mm256_loadu_si256(b.as_ptr() as *const __m256i);
         let result = _mm256_or_si256(a_vec, b_vec);
 
         let mut output = [Bitboard::EMPTY; 4];
-        _mm256_store_si256(output.as_mut_ptr() as *mut __m256i, result);
+        _mm256_storeu_si256(output.as_mut_ptr() as *mut __m256i, result);
         output
     }
 
@@ -52,12 +52,12 @@ impl SimdBitboard {
     ))]
     #[inline]
     pub unsafe fn parallel_xor_4(a: &[Bitboard; 4], b: &[Bitboard; 4]) -> [Bitboard; 4] {
-        let a_vec = _mm256_load_si256(a.as_ptr() as *const __m256i);
-        let b_vec = _mm256_load_si256(b.as_ptr() as *const __m256i);
+        let a_vec = _mm256_loadu_si256(a.as_ptr() as *const __m256i);
+        let b_vec = _mm256_loadu_si256(b.as_ptr() as *const __m256i);
         let result = _mm256_xor_si256(a_vec, b_vec);
 
         let mut output = [Bitboard::EMPTY; 4];
-        _mm256_store_si256(output.as_mut_ptr() as *mut __m256i, result);
+        _mm256_storeu_si256(output.as_mut_ptr() as *mut __m256i, result);
         output
     }
 
@@ -83,11 +83,11 @@ impl SimdBitboard {
     ))]
     #[inline]
     unsafe fn parallel_shift_left_4_avx2(boards: &[Bitboard; 4], shift: i32) -> [Bitboard; 4] {
-        let vec = _mm256_load_si256(boards.as_ptr() as *const __m256i);
-        let result = _mm256_slli_epi64(vec, shift);
+        let vec = _mm256_loadu_si256(boards.as_ptr() as *const __m256i);
+        let result = _mm256_sll_epi64(vec, _mm_cvtsi32_si128(shift));
 
         let mut output = [Bitboard::EMPTY; 4];
-        _mm256_store_si256(output.as_mut_ptr() as *mut __m256i, result);
+        _mm256_storeu_si256(output.as_mut_ptr() as *mut __m256i, result);
         output
     }
 
@@ -98,11 +98,11 @@ impl SimdBitboard {
     ))]
     #[inline]
     unsafe fn parallel_shift_right_4_avx2(boards: &[Bitboard; 4], shift: i32) -> [Bitboard; 4] {
-        let vec = _mm256_load_si256(boards.as_ptr() as *const __m256i);
-        let result = _mm256_srli_epi64(vec, shift);
+        let vec = _mm256_loadu_si256(boards.as_ptr() as *const __m256i);
+        let result = _mm256_srl_epi64(vec, _mm_cvtsi32_si128(shift));
 
         let mut output = [Bitboard::EMPTY; 4];
-        _mm256_store_si256(output.as_mut_ptr() as *mut __m256i, result);
+        _mm256_storeu_si256(output.as_mut_ptr() as *mut __m256i, result);
         output
     }
 
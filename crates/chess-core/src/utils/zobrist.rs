@@ -1,68 +1,104 @@
-use crate::{Piece, Square};
+// Zobrist keys for position hashing, and the Polyglot key layout for opening books
+//
+// The engine's own keys come from a fixed-seed generator, so a position hashes the same
+// in every build and on every platform; hashes written to disk (analysis stores, game
+// caches) stay valid across releases. Polyglot books use their own published key table
+// and layout, which `PolyglotKeys` reproduces.
+
+use crate::evaluation::pawns::pawn_attacks;
+use crate::{Bitboard, ChessError, Color, Piece, PieceType, Position, Result, Square};
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
+static ZOBRIST_KEYS: OnceLock<ZobristHash> = OnceLock::new();
+
+/// Shared key set used for position hashing
+pub fn zobrist_keys() -> &'static ZobristHash {
+    ZOBRIST_KEYS.get_or_init(ZobristHash::new)
+}
+
+/// Seed of the generator the keys are drawn from; changing it changes every hash
+pub const ZOBRIST_SEED: u64 = 0x3243_F6A8_885A_308D;
+
+/// Pieces of one type in hand that get distinct hash keys
+const MAX_HAND_COUNT: usize = crate::board::MAX_HAND_COUNT as usize;
+
+/// Random keys XORed together to hash a position: one per piece on each square, one for
+/// black to move, one per combination of castling rights, one per en passant file, and
+/// keys for pieces in hand and checks given in the variants that have them
 pub struct ZobristHash {
     piece_keys: [[u64; 64]; 12],
     side_key: u64,
     castling_keys: [u64; 16],
     en_passant_keys: [u64; 8],
+    hand_keys: [[[u64; MAX_HAND_COUNT]; 6]; 2],
+    check_keys: [[u64; 3]; 2],
 }
 
 impl ZobristHash {
     pub fn new() -> Self {
+        let mut rng = SplitMix64(ZOBRIST_SEED);
         let mut zobrist = ZobristHash {
             piece_keys: [[0; 64]; 12],
             side_key: 0,
             castling_keys: [0; 16],
             en_passant_keys: [0; 8],
+            hand_keys: [[[0; MAX_HAND_COUNT]; 6]; 2],
+            check_keys: [[0; 3]; 2],
         };
-        zobrist.initialize();
-        zobrist
-    }
-
-    fn initialize(&mut self) {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-
-        let mut hasher = DefaultHasher::new();
-
-        for piece_idx in 0..12 {
-            for square_idx in 0..64 {
-                format!("piece_{}_{}", piece_idx, square_idx).hash(&mut hasher);
-                self.piece_keys[piece_idx][square_idx] = hasher.finish();
-                hasher = DefaultHasher::new();
-            }
-        }
-
-        "side_to_move".hash(&mut hasher);
-        self.side_key = hasher.finish();
-        hasher = DefaultHasher::new();
-
-        for castle_idx in 0..16 {
-            format!("castling_{}", castle_idx).hash(&mut hasher);
-            self.castling_keys[castle_idx] = hasher.finish();
-            hasher = DefaultHasher::new();
-        }
 
-        for file in 0..8 {
-            format!("en_passant_{}", file).hash(&mut hasher);
-            self.en_passant_keys[file] = hasher.finish();
-            hasher = DefaultHasher::new();
-        }
+        zobrist
+            .piece_keys
+            .iter_mut()
+            .flatten()
+            .for_each(|key| *key = rng.next());
+        zobrist.side_key = rng.next();
+        // No castling rights hashes to zero, like the other absent features
+        zobrist.castling_keys[1..]
+            .iter_mut()
+            .for_each(|key| *key = rng.next());
+        zobrist
+            .en_passant_keys
+            .iter_mut()
+            .for_each(|key| *key = rng.next());
+        zobrist
+            .hand_keys
+            .iter_mut()
+            .flatten()
+            .flatten()
+            .for_each(|key| *key = rng.next());
+        zobrist
+            .check_keys
+            .iter_mut()
+            .flatten()
+            .for_each(|key| *key = rng.next());
+        zobrist
     }
 
+    /// Key for `piece` standing on `square`
     pub fn hash_piece(&self, piece: Piece, square: Square) -> u64 {
         self.piece_keys[piece.index()][square.index() as usize]
     }
 
+    /// Keys of `piece` on every square of `squares` combined
+    pub fn hash_pieces(&self, piece: Piece, squares: Bitboard) -> u64 {
+        let keys = &self.piece_keys[piece.index()];
+        squares
+            .iter()
+            .fold(0, |hash, square| hash ^ keys[square as usize])
+    }
+
+    /// Key XORed in when black is to move
     pub fn hash_side(&self) -> u64 {
         self.side_key
     }
 
+    /// Key for the castling rights packed by `CastlingRights::index`
     pub fn hash_castling(&self, castling_rights: u8) -> u64 {
         self.castling_keys[castling_rights as usize & 15]
     }
 
+    /// Key for an en passant square on `file`; files past h hash to zero
     pub fn hash_en_passant(&self, file: u8) -> u64 {
         if file < 8 {
             self.en_passant_keys[file as usize]
@@ -70,6 +106,24 @@ impl ZobristHash {
             0
         }
     }
+
+    /// Key for holding `count` pieces of a type in hand; an empty hand hashes to zero
+    pub fn hash_hand(&self, color: Color, piece_type: PieceType, count: u8) -> u64 {
+        match count as usize {
+            0 => 0,
+            count => {
+                self.hand_keys[color.index()][piece_type.index()][count.min(MAX_HAND_COUNT) - 1]
+            }
+        }
+    }
+
+    /// Key for `color` having given `count` checks in Three-check; none hashes to zero
+    pub fn hash_checks(&self, color: Color, count: u8) -> u64 {
+        match count as usize {
+            0 => 0,
+            count => self.check_keys[color.index()][count.min(3) - 1],
+        }
+    }
 }
 
 impl Default for ZobristHash {
@@ -78,6 +132,132 @@ impl Default for ZobristHash {
     }
 }
 
+/// SplitMix64, which fills every key from one seed without correlated low bits
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// Number of keys in the Polyglot `Random64` table
+pub const POLYGLOT_KEY_COUNT: usize = 781;
+
+/// Offsets of the key groups in the Polyglot table
+const POLYGLOT_CASTLING_OFFSET: usize = 768;
+const POLYGLOT_EN_PASSANT_OFFSET: usize = 772;
+const POLYGLOT_TURN_OFFSET: usize = 780;
+
+/// Polyglot book hashing over the `Random64` table of the book format specification.
+///
+/// `standard` uses the published table that real books are keyed with; `from_table` and
+/// `from_be_bytes` load another one. The layout differs from `ZobristHash`: the turn key is XORed in when
+/// white is to move, and the en passant key only when a pawn of the side to move could
+/// capture en passant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolyglotKeys {
+    table: Box<[u64; POLYGLOT_KEY_COUNT]>,
+}
+
+impl PolyglotKeys {
+    /// Keys of the published `Random64` table, as used by every Polyglot book
+    pub fn standard() -> Self {
+        PolyglotKeys {
+            table: Box::new(super::polyglot_random::RANDOM64),
+        }
+    }
+
+    /// Keys from the 781 entries of `Random64`, in the specification's order
+    pub fn from_table(table: &[u64]) -> Result<Self> {
+        let table: [u64; POLYGLOT_KEY_COUNT] = table.try_into().map_err(|_| {
+            ChessError::ParseError(format!(
+                "Polyglot key table needs {} keys, got {}",
+                POLYGLOT_KEY_COUNT,
+                table.len()
+            ))
+        })?;
+        Ok(PolyglotKeys {
+            table: Box::new(table),
+        })
+    }
+
+    /// Keys from `Random64` stored as consecutive big-endian 64-bit integers
+    pub fn from_be_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != POLYGLOT_KEY_COUNT * 8 {
+            return Err(ChessError::ParseError(format!(
+                "Polyglot key table needs {} bytes, got {}",
+                POLYGLOT_KEY_COUNT * 8,
+                bytes.len()
+            )));
+        }
+        let table: Vec<u64> = bytes
+            .chunks_exact(8)
+            .map(|chunk| u64::from_be_bytes(chunk.try_into().unwrap_or_default()))
+            .collect();
+        Self::from_table(&table)
+    }
+
+    /// Key for `piece` on `square`; Polyglot orders pieces black pawn, white pawn, black
+    /// knight and so on up to white king
+    pub fn piece(&self, piece: Piece, square: Square) -> u64 {
+        let kind = piece.piece_type.index() * 2 + usize::from(piece.color == Color::White);
+        self.table[64 * kind + square.index() as usize]
+    }
+
+    /// Key for one castling right
+    pub fn castling(&self, color: Color, kingside: bool) -> u64 {
+        let right = color.index() * 2 + usize::from(!kingside);
+        self.table[POLYGLOT_CASTLING_OFFSET + right]
+    }
+
+    /// Key for an en passant square on `file`
+    pub fn en_passant(&self, file: u8) -> u64 {
+        self.table[POLYGLOT_EN_PASSANT_OFFSET + (file as usize & 7)]
+    }
+
+    /// Key XORed in when white is to move
+    pub fn turn(&self) -> u64 {
+        self.table[POLYGLOT_TURN_OFFSET]
+    }
+
+    /// Polyglot key of `position`, as stored in book entries
+    pub fn hash(&self, position: &Position) -> u64 {
+        let mut hash = position
+            .piece_list(Color::White)
+            .chain(position.piece_list(Color::Black))
+            .fold(0, |hash, (square, piece)| hash ^ self.piece(piece, square));
+
+        let rights = position.castling_rights;
+        for (color, kingside, allowed) in [
+            (Color::White, true, rights.white_kingside),
+            (Color::White, false, rights.white_queenside),
+            (Color::Black, true, rights.black_kingside),
+            (Color::Black, false, rights.black_queenside),
+        ] {
+            if allowed {
+                hash ^= self.castling(color, kingside);
+            }
+        }
+
+        let side = position.side_to_move;
+        if let Some(en_passant) = position.en_passant {
+            let pawns = position.pieces_of_type(PieceType::Pawn, side);
+            if !(pawn_attacks(pawns, side) & en_passant.bitboard()).is_empty() {
+                hash ^= self.en_passant(en_passant.file());
+            }
+        }
+        if side == Color::White {
+            hash ^= self.turn();
+        }
+        hash
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TranspositionTable {
     table: HashMap<u64, TranspositionEntry>,
@@ -168,6 +348,116 @@ mod tests {
         assert_ne!(e4_hash, e5_hash);
     }
 
+    #[test]
+    fn test_keys_are_stable() {
+        let zobrist = ZobristHash::new();
+        let mut rng = SplitMix64(ZOBRIST_SEED);
+        assert_eq!(
+            zobrist.hash_piece(Piece::white_pawn(), Square::A1),
+            rng.next()
+        );
+        assert_eq!(zobrist.hash_castling(0), 0);
+        assert_eq!(
+            zobrist.hash_pieces(
+                Piece::black_rook(),
+                Square::A8.bitboard() | Square::H8.bitboard()
+            ),
+            zobrist.hash_piece(Piece::black_rook(), Square::A8)
+                ^ zobrist.hash_piece(Piece::black_rook(), Square::H8)
+        );
+    }
+
+    /// Stand-in for `Random64` with a distinct key at every index
+    fn indexed_table() -> Vec<u64> {
+        (0..POLYGLOT_KEY_COUNT as u64)
+            .map(|index| index.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+            .collect()
+    }
+
+    #[test]
+    fn test_polyglot_layout() {
+        let table = indexed_table();
+        let keys = PolyglotKeys::from_table(&table).unwrap();
+        // White pawn is kind 1, rank 2 is row 1
+        assert_eq!(
+            keys.piece(Piece::white_pawn(), Square::E2),
+            table[64 + 8 + 4]
+        );
+        assert_eq!(
+            keys.piece(Piece::black_king(), Square::E8),
+            table[64 * 10 + 56 + 4]
+        );
+        assert_eq!(keys.castling(Color::Black, false), table[771]);
+        assert_eq!(keys.en_passant(3), table[775]);
+        assert_eq!(keys.turn(), table[780]);
+
+        let bytes: Vec<u8> = table.iter().flat_map(|key| key.to_be_bytes()).collect();
+        assert_eq!(PolyglotKeys::from_be_bytes(&bytes).unwrap(), keys);
+        assert!(PolyglotKeys::from_table(&table[1..]).is_err());
+        assert!(PolyglotKeys::from_be_bytes(&bytes[8..]).is_err());
+    }
+
+    #[test]
+    fn test_standard_polyglot_keys() {
+        let keys = PolyglotKeys::standard();
+        let start = Position::starting_position();
+        assert_eq!(keys.hash(&start), 0x463b_9618_1691_fc9c);
+
+        // Reference keys from the Polyglot book format specification
+        for (fen, key) in [
+            (
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+                0x823c_9b50_fd11_4196,
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+                0x0756_b944_61c5_0fb0,
+            ),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                0x22a4_8b5a_8e47_ff78,
+            ),
+            (
+                "rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4",
+                0x00fd_d303_c946_bdd9,
+            ),
+            (
+                "rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4",
+                0x5c3f_9b82_9b27_9560,
+            ),
+        ] {
+            assert_eq!(keys.hash(&Position::from_fen(fen).unwrap()), key, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_polyglot_en_passant_only_when_capturable() {
+        let table = indexed_table();
+        let keys = PolyglotKeys::from_table(&table).unwrap();
+
+        // After 1.e4 no black pawn can take on e3, so the key ignores the square
+        let after_e4 = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+        let without = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1";
+        assert_eq!(
+            keys.hash(&Position::from_fen(after_e4).unwrap()),
+            keys.hash(&Position::from_fen(without).unwrap())
+        );
+
+        let capturable = "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+        let position = Position::from_fen(capturable).unwrap();
+        let mut cleared = position.clone();
+        cleared.en_passant = None;
+        assert_eq!(
+            keys.hash(&position),
+            keys.hash(&cleared) ^ keys.en_passant(4)
+        );
+
+        let white = Position::starting_position();
+        let mut black = white.clone();
+        black.side_to_move = Color::Black;
+        assert_eq!(keys.hash(&white), keys.hash(&black) ^ keys.turn());
+    }
+
     #[test]
     fn test_transposition_table() {
         let mut tt = TranspositionTable::new(100);
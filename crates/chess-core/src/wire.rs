@@ -0,0 +1,157 @@
+// Serde wire format shared by the server, WASM and native bindings
+//
+// Board types travel as the strings people already read: squares as "e4", moves in UCI
+// ("e7e8q", "N@f3"), pieces as FEN letters ("N", "p"). Enums use lowercase names and
+// durations whole milliseconds. `JSON_SCHEMA` describes these and the result types
+// built from them.
+
+use crate::Move;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// JSON Schema (draft 2020-12) of the wire format, with one `$defs` entry per type
+pub const JSON_SCHEMA: &str = include_str!("../schema/wire.schema.json");
+
+/// Serialize a type through its `Display` and `FromStr` implementations
+macro_rules! serde_as_string {
+    ($($ty:ty),*) => {$(
+        impl serde::Serialize for $ty {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $ty {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let text = String::deserialize(deserializer)?;
+                text.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    )*};
+}
+
+// Moves read back from UCI carry no capture, castling or en passant flags; match them
+// against a position with `GameState::resolve_uci_move` before playing them
+serde_as_string!(crate::Square, crate::Move, crate::Piece);
+
+/// `Duration` as whole milliseconds, for `#[serde(with = "crate::wire::millis")]`
+pub mod millis {
+    use super::*;
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        (duration.as_millis() as u64).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        Ok(Duration::from_millis(u64::deserialize(deserializer)?))
+    }
+}
+
+/// Fixed-size principal variation as a list of the moves it holds
+pub mod principal_variation {
+    use super::*;
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        pv: &[Option<Move>; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(pv.iter().map_while(|mv| *mv))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[Option<Move>; N], D::Error> {
+        let moves = Vec::<Move>::deserialize(deserializer)?;
+        if moves.len() > N {
+            return Err(D::Error::custom(format!(
+                "principal variation longer than {} moves",
+                N
+            )));
+        }
+        let mut pv = [None; N];
+        for (slot, mv) in pv.iter_mut().zip(moves) {
+            *slot = Some(mv);
+        }
+        Ok(pv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, GameResult, Piece, PieceType, RootFail, SearchResult, Square};
+    use std::time::Duration;
+
+    #[test]
+    fn test_board_types_are_strings() {
+        assert_eq!(serde_json::to_string(&Square::E4).unwrap(), "\"e4\"");
+        let promotion = Move::promotion(Square::E7, Square::E8, PieceType::Queen);
+        assert_eq!(serde_json::to_string(&promotion).unwrap(), "\"e7e8q\"");
+        let drop = Move::drop(PieceType::Knight, Square::F3);
+        assert_eq!(serde_json::to_string(&drop).unwrap(), "\"N@f3\"");
+        let piece = Piece::new(PieceType::Pawn, Color::Black);
+        assert_eq!(serde_json::to_string(&piece).unwrap(), "\"p\"");
+        assert_eq!(serde_json::to_string(&Color::White).unwrap(), "\"white\"");
+        assert_eq!(
+            serde_json::to_string(&GameResult::BlackWins).unwrap(),
+            "\"black_wins\""
+        );
+
+        assert_eq!(
+            serde_json::from_str::<Move>("\"e7e8q\"").unwrap(),
+            promotion
+        );
+        assert_eq!(serde_json::from_str::<Piece>("\"p\"").unwrap(), piece);
+        assert!(serde_json::from_str::<Square>("\"i9\"").is_err());
+    }
+
+    #[test]
+    fn test_search_result_round_trip() {
+        let mut principal_variation = [None; 16];
+        principal_variation[0] = Some(Move::normal(Square::E2, Square::E4));
+        principal_variation[1] = Some(Move::normal(Square::E7, Square::E5));
+        let result = SearchResult {
+            best_move: principal_variation[0],
+            evaluation: 25,
+            depth: 6,
+            seldepth: 11,
+            nodes_searched: 12_345,
+            elapsed_time: Duration::from_millis(40),
+            principal_variation,
+            root_fail: Some(RootFail::High),
+            aspiration_researches: 2,
+        };
+
+        let json = serde_json::to_value(result).unwrap();
+        assert_eq!(json["best_move"], "e2e4");
+        assert_eq!(json["seldepth"], 11);
+        assert_eq!(json["nodes"], 12_345);
+        assert_eq!(json["time_ms"], 40);
+        assert_eq!(json["pv"], serde_json::json!(["e2e4", "e7e5"]));
+        assert_eq!(json["root_fail"], "high");
+        assert_eq!(
+            serde_json::from_value::<SearchResult>(json).unwrap(),
+            result
+        );
+    }
+
+    #[test]
+    fn test_schema_defines_wire_types() {
+        let schema: serde_json::Value = serde_json::from_str(JSON_SCHEMA).unwrap();
+        for name in [
+            "Square",
+            "Move",
+            "Color",
+            "PieceType",
+            "Piece",
+            "GameResult",
+            "SquareChange",
+            "GameInfo",
+            "SearchResult",
+            "AnalysisResult",
+        ] {
+            assert!(schema["$defs"][name].is_object(), "missing {}", name);
+        }
+    }
+}
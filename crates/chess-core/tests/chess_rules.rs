@@ -96,12 +96,20 @@ mod chess_rules_tests {
         let game_state = GameState::new();
 
         // Starting position should have all castling rights
-        assert!(game_state.castling_rights.can_castle_kingside(Color::White));
         assert!(game_state
+            .position
+            .castling_rights
+            .can_castle_kingside(Color::White));
+        assert!(game_state
+            .position
             .castling_rights
             .can_castle_queenside(Color::White));
-        assert!(game_state.castling_rights.can_castle_kingside(Color::Black));
         assert!(game_state
+            .position
+            .castling_rights
+            .can_castle_kingside(Color::Black));
+        assert!(game_state
+            .position
             .castling_rights
             .can_castle_queenside(Color::Black));
     }
@@ -131,12 +139,20 @@ mod chess_rules_tests {
 
         if let Ok(game_state) = result {
             assert_eq!(game_state.position.side_to_move, Color::White);
-            assert!(game_state.castling_rights.can_castle_kingside(Color::White));
             assert!(game_state
+                .position
+                .castling_rights
+                .can_castle_kingside(Color::White));
+            assert!(game_state
+                .position
                 .castling_rights
                 .can_castle_queenside(Color::White));
-            assert!(game_state.castling_rights.can_castle_kingside(Color::Black));
             assert!(game_state
+                .position
+                .castling_rights
+                .can_castle_kingside(Color::Black));
+            assert!(game_state
+                .position
                 .castling_rights
                 .can_castle_queenside(Color::Black));
         }
@@ -319,12 +335,44 @@ mod castle_specific_tests {
         let game_state = GameState::new();
 
         // Test that castling rights are properly tracked
-        assert!(game_state.castling_rights.can_castle_kingside(Color::White));
+        assert!(game_state
+            .position
+            .castling_rights
+            .can_castle_kingside(Color::White));
 
         // After king moves, castling rights should be lost
         // This would require implementing the castling rights update logic
         // For now, just test the basic structure
     }
+
+    #[test]
+    fn test_no_castling_out_of_check() {
+        let game_state = GameState::from_fen("4r1k1/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+
+        let moves = game_state.generate_legal_moves();
+        assert!(
+            moves.iter().all(|m| !m.is_castle()),
+            "Castling out of check must not be legal"
+        );
+        assert!(game_state
+            .clone()
+            .make_move(Move::castle(Square::E1, Square::G1))
+            .is_err());
+    }
+
+    #[test]
+    fn test_castling_moves_rook() {
+        let mut game_state = GameState::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+
+        game_state
+            .make_move(Move::castle(Square::E1, Square::C1))
+            .unwrap();
+        assert!(game_state.position.piece_at(Square::A1).is_none());
+        assert_eq!(
+            game_state.position.piece_at(Square::D1).map(|p| p.color),
+            Some(Color::White)
+        );
+    }
 }
 
 #[cfg(test)]
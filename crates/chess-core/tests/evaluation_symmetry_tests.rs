@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod evaluation_symmetry_tests {
+    use chess_core::{Evaluator, GameState, OptimizedEvaluator, Position};
+
+    /// Fixed tolerance, in centipawns, allowed between the two evaluators.
+    ///
+    /// Both share material values and piece-square tables, but weigh pawn structure
+    /// and king safety differently, and `OptimizedEvaluator` also scales its total by
+    /// game phase and adds a tempo bonus for the side to move.
+    const AGREEMENT_TOLERANCE: i32 = 60;
+
+    /// Extra tolerance as a fraction (1/n) of the score, covering the phase scaling
+    /// (up to 20% in either direction).
+    const AGREEMENT_TOLERANCE_RATIO: i32 = 4;
+
+    const FIXED_POSITIONS: [&str; 6] = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "4k3/8/8/8/8/8/8/3QK3 w - - 0 1",
+        "6k1/5ppp/8/8/8/8/Q4PPP/3r2K1 b - - 0 1",
+    ];
+
+    /// Small xorshift generator so the "random" games are reproducible
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+    }
+
+    fn game_state_for(position: Position) -> GameState {
+        let mut game_state = GameState::new();
+        game_state.position = position;
+        game_state
+    }
+
+    /// Fixed test positions plus positions sampled from seeded random games
+    fn sample_positions() -> Vec<Position> {
+        let mut positions: Vec<Position> = FIXED_POSITIONS
+            .iter()
+            .map(|fen| GameState::from_fen(fen).unwrap().position)
+            .collect();
+
+        let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+        for _ in 0..12 {
+            let mut game_state = GameState::new();
+            for ply in 0..60 {
+                let moves = game_state.generate_legal_moves();
+                if moves.is_empty() {
+                    break;
+                }
+                let mv = moves[(rng.next() % moves.len() as u64) as usize];
+                if game_state.make_move(mv).is_err() {
+                    break;
+                }
+                if ply % 5 == 4 {
+                    positions.push(game_state.position.clone());
+                }
+            }
+        }
+
+        positions
+    }
+
+    #[test]
+    fn test_mirror_is_an_involution() {
+        for position in sample_positions() {
+            assert_eq!(position.flipped().flipped(), position);
+        }
+    }
+
+    #[test]
+    fn test_standard_evaluator_symmetry() {
+        let evaluator = Evaluator::new();
+
+        for position in sample_positions() {
+            // Scores are from the side to move, so the mirror scores the same
+            let original = evaluator.evaluate(&game_state_for(position.clone()));
+            let mirrored = evaluator.evaluate(&game_state_for(position.flipped()));
+            assert_eq!(
+                original, mirrored,
+                "Evaluator is asymmetric for {:?}",
+                position.board
+            );
+        }
+    }
+
+    #[test]
+    fn test_optimized_evaluator_symmetry() {
+        // A single evaluator instance, so cached entries must not leak between positions
+        let mut evaluator = OptimizedEvaluator::new();
+
+        for position in sample_positions() {
+            // Scores are from the side to move, so the mirror scores the same
+            let original = evaluator.evaluate(&position);
+            let mirrored = evaluator.evaluate(&position.flipped());
+            assert_eq!(
+                original, mirrored,
+                "OptimizedEvaluator is asymmetric for {:?}",
+                position.board
+            );
+        }
+    }
+
+    #[test]
+    fn test_optimized_evaluator_cache_consistency() {
+        let mut cached = OptimizedEvaluator::new();
+
+        for position in sample_positions() {
+            let mut fresh = OptimizedEvaluator::new();
+            assert_eq!(cached.evaluate(&position), fresh.evaluate(&position));
+        }
+    }
+
+    #[test]
+    fn test_evaluators_agree_within_tolerance() {
+        let standard = Evaluator::new();
+        let mut optimized = OptimizedEvaluator::new();
+
+        for position in sample_positions() {
+            // Both evaluators score from the side to move's point of view
+            let standard_score = standard.evaluate(&game_state_for(position.clone()));
+            let optimized_score = optimized.evaluate(&position);
+
+            // The phase scaling is applied to the optimized total, so scale by the larger score
+            let magnitude = standard_score.abs().max(optimized_score.abs());
+            let tolerance = AGREEMENT_TOLERANCE + magnitude / AGREEMENT_TOLERANCE_RATIO;
+            assert!(
+                (standard_score - optimized_score).abs() <= tolerance,
+                "Evaluators diverge: standard {} vs optimized {} (tolerance {}) for {:?}",
+                standard_score,
+                optimized_score,
+                tolerance,
+                position.board
+            );
+        }
+    }
+}
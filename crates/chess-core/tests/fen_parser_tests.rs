@@ -232,4 +232,57 @@ mod fen_parser_tests {
             matches!(position.piece_at(h1), Some(piece) if piece.piece_type == PieceType::Rook && piece.color == Color::White)
         );
     }
+
+    #[test]
+    fn test_non_ascii_squares_and_moves_are_errors() {
+        assert!("é1".parse::<Square>().is_err());
+        assert!("aé".parse::<Square>().is_err());
+        assert!("éa1b".parse::<chess_core::Move>().is_err());
+        assert!("a1é2".parse::<chess_core::Move>().is_err());
+        assert!("a7a8é".parse::<chess_core::Move>().is_err());
+        assert!(Position::from_fen("4k3/8/8/8/8/8/8/4K3 w - dé6 0 1").is_err());
+    }
+
+    #[test]
+    fn test_mutated_fens_never_panic() {
+        use chess_core::utils::fen_check::check_fen;
+
+        let seeds = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+            "P3k2p/8/8/8/8/8/8/p3K2P w - - 0 1",
+        ];
+        let alphabet: Vec<char> = "pnbrqkPNBRQK0123456789/ wb-abcdefghé".chars().collect();
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as usize
+        };
+
+        for i in 0..2000 {
+            let mut chars: Vec<char> = seeds[i % seeds.len()].chars().collect();
+            for _ in 0..1 + next() % 3 {
+                let at = next() % chars.len();
+                match next() % 3 {
+                    0 => chars[at] = alphabet[next() % alphabet.len()],
+                    1 if chars.len() > 1 => {
+                        chars.remove(at);
+                    }
+                    _ => chars.insert(at, alphabet[next() % alphabet.len()]),
+                }
+            }
+            let fen: String = chars.into_iter().collect();
+
+            // Errors are fine; inconsistent make/unmake or a panic is not
+            if let Err(e) = check_fen(&fen) {
+                assert!(
+                    matches!(e, chess_core::ChessError::ParseError(_)),
+                    "{fen}: {e}"
+                );
+            }
+        }
+    }
 }
@@ -41,7 +41,7 @@ fn test_modular_imports() {
     assert_eq!(piece.color, color);
     assert_eq!(square.index(), 0);
     assert_eq!(bitboard.value(), 0);
-    assert_eq!(game_state.fullmove_number, 1);
+    assert_eq!(game_state.position.fullmove_number, 1);
 
     // Test move generation works
     let moves = move_gen.generate_legal_moves(&position);
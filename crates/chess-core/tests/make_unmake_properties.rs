@@ -0,0 +1,102 @@
+// Property tests: making and unmaking any sequence of legal moves must restore the
+// position exactly, down to bitboards, board array, hash, castling, en passant and clocks
+
+use chess_core::{GameState, Move, Position, UndoInfo};
+use proptest::prelude::*;
+
+/// Start positions with castling, en passant and promotions close at hand
+const START_FENS: [&str; 4] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+];
+
+/// Play the moves picked by `choices` (each taken modulo the legal move count),
+/// checking a make/unmake round trip before every move
+fn play(fen: &str, choices: &[usize]) -> Vec<(Move, UndoInfo, Position)> {
+    let mut game = GameState::from_fen(fen).unwrap();
+    let mut line = Vec::new();
+
+    for &choice in choices {
+        let moves = game.generate_legal_moves();
+        if moves.is_empty() {
+            break;
+        }
+        let mv = moves[choice % moves.len()];
+        let before = game.position.clone();
+
+        let mut position = before.clone();
+        let undo = position.make_move(mv).unwrap();
+        position.undo_move(mv, undo);
+        assert_eq!(
+            position,
+            before,
+            "{} did not unmake from {}",
+            mv,
+            before.to_fen()
+        );
+        assert_eq!(position.zobrist_hash(), before.zobrist_hash());
+
+        let mut position = before.clone();
+        let undo = position.make_move(mv).unwrap();
+        game.make_move(mv).unwrap();
+        assert_eq!(game.position, position);
+        line.push((mv, undo, before));
+    }
+
+    line
+}
+
+proptest! {
+    #[test]
+    fn prop_make_unmake_restores_position(
+        start in 0..START_FENS.len(),
+        choices in prop::collection::vec(any::<usize>(), 1..24),
+    ) {
+        play(START_FENS[start], &choices);
+    }
+
+    #[test]
+    fn prop_unwinding_a_line_restores_every_position(
+        start in 0..START_FENS.len(),
+        choices in prop::collection::vec(any::<usize>(), 1..24),
+    ) {
+        let line = play(START_FENS[start], &choices);
+        let mut position = line
+            .last()
+            .map(|(mv, _, before)| {
+                let mut after = before.clone();
+                after.make_move(*mv).unwrap();
+                after
+            })
+            .unwrap_or_else(|| Position::from_fen(START_FENS[start]).unwrap());
+
+        for (mv, undo, before) in line.into_iter().rev() {
+            position.undo_move(mv, undo);
+            prop_assert_eq!(&position, &before);
+            prop_assert_eq!(position.to_fen(), before.to_fen());
+        }
+    }
+
+    #[test]
+    fn prop_incremental_hash_matches_full_recomputation(
+        start in 0..START_FENS.len(),
+        choices in prop::collection::vec(any::<usize>(), 1..48),
+    ) {
+        let mut game = GameState::from_fen(START_FENS[start]).unwrap();
+        for &choice in &choices {
+            let moves = game.generate_legal_moves();
+            if moves.is_empty() {
+                break;
+            }
+            game.make_move(moves[choice % moves.len()]).unwrap();
+            let position = &game.position;
+            prop_assert_eq!(position.zobrist_hash(), position.compute_zobrist_hash());
+            prop_assert_eq!(
+                position.zobrist_hash(),
+                Position::from_fen(&position.to_fen()).unwrap().zobrist_hash()
+            );
+        }
+    }
+}
@@ -0,0 +1,138 @@
+// Differential tests: legal moves and perft counts must agree with the `shakmaty` crate
+//
+// Random games are played from positions rich in castling, en passant and promotions,
+// and every position on the way is compared move for move. A perft mismatch is
+// narrowed down by descending into the first root move whose subtree count differs,
+// until a position whose legal move lists differ is found, so a failure names the
+// exact FEN and moves to look at. Positions found by fuzzing can be added to
+// `REGRESSION_FENS`.
+//
+//   cargo test -p chess-core --features differential --test movegen_differential
+
+use chess_core::search::parallel::ParallelUtils;
+use chess_core::{GameState, MoveGenerator, Position};
+use proptest::prelude::*;
+use shakmaty::fen::Fen;
+use shakmaty::{CastlingMode, Chess, Position as _};
+use std::collections::BTreeSet;
+
+const START_FENS: [&str; 6] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+];
+
+/// Positions that once exposed generator bugs: pins along the en passant rank, checks
+/// by promoted pieces and castling through attacked squares
+const REGRESSION_FENS: [&str; 6] = [
+    "8/8/8/K2pP2q/8/8/8/7k w - d6 0 1",
+    "8/8/3k4/8/2pP4/8/8/3KB3 b - d3 0 1",
+    "4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1",
+    "r3k2r/8/8/8/8/8/5r2/R3K2R w KQkq - 0 1",
+    "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+    "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1",
+];
+
+fn reference(fen: &str) -> Chess {
+    let parsed: Fen = fen.parse().unwrap_or_else(|e| panic!("{}: {}", fen, e));
+    parsed
+        .into_position(CastlingMode::Standard)
+        .unwrap_or_else(|e| panic!("{}: {}", fen, e))
+}
+
+fn our_moves(game: &GameState) -> BTreeSet<String> {
+    game.generate_legal_moves()
+        .iter()
+        .map(|mv| mv.to_uci())
+        .collect()
+}
+
+fn reference_moves(position: &Chess) -> BTreeSet<String> {
+    position
+        .legal_moves()
+        .iter()
+        .map(|mv| mv.to_uci(CastlingMode::Standard).to_string())
+        .collect()
+}
+
+/// Panic naming the moves only one side generates when the legal moves differ
+fn assert_same_moves(game: &GameState) {
+    let fen = game.to_fen();
+    let ours = our_moves(game);
+    let theirs = reference_moves(&reference(&fen));
+    if ours != theirs {
+        panic!(
+            "legal moves differ in {}\n  only ours: {:?}\n  only shakmaty: {:?}",
+            fen,
+            ours.difference(&theirs).collect::<Vec<_>>(),
+            theirs.difference(&ours).collect::<Vec<_>>()
+        );
+    }
+}
+
+/// Compare perft counts of `fen`, descending into the first diverging subtree to
+/// report the position where the generators first disagree
+fn assert_same_perft(fen: &str, depth: u8) {
+    let game = GameState::from_fen(fen).unwrap();
+    assert_same_moves(&game);
+    if depth <= 1 {
+        return;
+    }
+
+    let move_generator = MoveGenerator::new();
+    let position = Position::from_fen(fen).unwrap();
+    let ours = ParallelUtils::sequential_perft(&position, depth, &move_generator);
+    let theirs = shakmaty::perft(&reference(fen), u32::from(depth));
+    if ours == theirs {
+        return;
+    }
+
+    for mv in game.generate_legal_moves() {
+        let mut child = game.clone();
+        child.make_move(mv).unwrap();
+        let child_fen = child.to_fen();
+        let child_position = Position::from_fen(&child_fen).unwrap();
+        let ours = ParallelUtils::sequential_perft(&child_position, depth - 1, &move_generator);
+        if ours != shakmaty::perft(&reference(&child_fen), u32::from(depth - 1)) {
+            assert_same_perft(&child_fen, depth - 1);
+        }
+    }
+    panic!(
+        "perft({}) of {} is {}, shakmaty counts {}, but every subtree agrees",
+        depth, fen, ours, theirs
+    );
+}
+
+#[test]
+fn test_perft_matches_reference() {
+    for fen in START_FENS {
+        assert_same_perft(fen, 3);
+    }
+    for fen in REGRESSION_FENS {
+        assert_same_perft(fen, 4);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn prop_random_games_match_reference(
+        start in 0..START_FENS.len(),
+        choices in prop::collection::vec(any::<usize>(), 1..80),
+    ) {
+        let mut game = GameState::from_fen(START_FENS[start]).unwrap();
+        for choice in choices {
+            assert_same_moves(&game);
+            let moves = game.generate_legal_moves();
+            if moves.is_empty() {
+                break;
+            }
+            game.make_move(moves[choice % moves.len()]).unwrap();
+        }
+        assert_same_perft(&game.to_fen(), 2);
+    }
+}
@@ -85,13 +85,31 @@ mod performance_tests {
 
         let position = Position::starting_position();
         let config = SearchConfig {
-            max_depth: 6,
-            max_time: Some(Duration::from_secs(1)),
-            max_nodes: None,
+            limits: SearchLimits::default()
+                .with_depth(6)
+                .with_movetime(Duration::from_secs(1)),
             use_null_move_pruning: true,
             use_late_move_reductions: true,
             use_futility_pruning: true,
+            use_razoring: true,
+            razoring_margin: 150,
+            use_reverse_futility_pruning: true,
+            reverse_futility_margin: 90,
+            use_internal_iterative_reduction: true,
+            use_principal_variation_search: true,
+            use_qsearch_move_ordering: true,
+            use_delta_pruning: true,
+            delta_margin: 200,
+            use_qsearch_transposition_table: true,
+            use_see_pruning: true,
+            see_quiet_margin: 60,
+            use_passed_pawn_extension: true,
+            use_recapture_extension: true,
+            use_singular_extensions: true,
+            contempt: 0,
+            use_dynamic_contempt: true,
             aspiration_window: 50,
+            mate_verification: MateVerification::Off,
         };
         let mut search_engine = SearchEngine::new(config);
 
@@ -136,6 +154,7 @@ mod performance_tests {
             enable_parallel_moves: true,
             enable_parallel_eval: true,
             enable_parallel_search: true,
+            ..ParallelConfig::default()
         };
 
         let position = Position::starting_position();
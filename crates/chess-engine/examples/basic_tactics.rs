@@ -1,4 +1,4 @@
-use chess_engine::ChessEngine;
+use chess_engine::{BestMove, ChessEngine};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🧠 Chess Engine Tactical Awareness Test");
@@ -13,7 +13,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Position where black queen is hanging on d4
     engine.load_fen("rnb1kbnr/pppp1ppp/8/4p3/3qP3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 3")?;
 
-    if let Some(best_move) = engine.find_best_move()? {
+    if let BestMove::Move(best_move) = engine.find_best_move()? {
         println!("   Engine suggests: {}", best_move);
 
         // Check if engine wants to capture the queen
@@ -31,7 +31,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Simple back-rank mate position
     engine.load_fen("6k1/5ppp/8/8/8/8/5PPP/4R1K1 w - - 0 25")?;
 
-    if let Some(best_move) = engine.find_best_move()? {
+    if let BestMove::Move(best_move) = engine.find_best_move()? {
         println!("   Engine suggests: {}", best_move);
 
         let move_str = best_move.to_string();
@@ -47,7 +47,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     engine.load_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1")?;
 
-    if let Some(best_move) = engine.find_best_move()? {
+    if let BestMove::Move(best_move) = engine.find_best_move()? {
         println!("   Engine suggests: {}", best_move);
 
         let move_str = best_move.to_string();
@@ -1,4 +1,4 @@
-use chess_engine::{ChessEngineBuilder, Color, GameResult};
+use chess_engine::{BestMove, ChessEngineBuilder, Color, GameResult};
 use std::io::{self, Write};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -60,12 +60,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("\nComputer is thinking...");
 
             match engine.find_best_move() {
-                Ok(Some(best_move)) => {
+                Ok(BestMove::Move(best_move)) => {
                     println!("Computer plays: {}", best_move);
                     engine.make_move(best_move)?;
                 }
-                Ok(None) => {
-                    println!("No legal moves available");
+                Ok(BestMove::GameOver(result)) => {
+                    println!("Game over: {:?}", result);
                     break;
                 }
                 Err(e) => {
@@ -1,4 +1,4 @@
-use chess_engine::ChessEngine;
+use chess_engine::{BestMove, ChessEngine};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🏗️  Chess Engine Restructured Demo");
@@ -18,11 +18,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     if !legal_moves.is_empty() {
         println!("🤖 Engine finding best move...");
         match engine.find_best_move()? {
-            Some(best_move) => {
+            BestMove::Move(best_move) => {
                 println!("✨ Best move found: {}", best_move);
                 println!("🧠 Using advanced search algorithms from modular structure!");
             }
-            None => println!("❌ No moves available"),
+            BestMove::GameOver(result) => println!("❌ Game over: {:?}", result),
         }
     }
 
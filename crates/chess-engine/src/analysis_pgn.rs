@@ -0,0 +1,416 @@
+// PGN export of analysis sessions
+//
+// A session is the game as played plus the engine lines found for its positions. It is
+// written as export-format PGN: the seven tag roster, SetUp and FEN for games not
+// starting from the initial position, and movetext wrapped at 80 columns. Each search
+// becomes a comment such as `{+0.54/18}` after the played move when the engine agreed
+// with it, and a variation in parentheses when it preferred another move, so ChessBase
+// and Scid import the scores and alternatives alongside the game.
+
+use crate::{EngineError, GameResult, Move, Result, SearchResult, Variant};
+use chess_core::game::move_to_san;
+use chess_core::{Color, GameState, Position};
+use std::io::Write;
+
+const ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+const LINE_WIDTH: usize = 80;
+
+#[derive(Clone)]
+struct AnalyzedPly {
+    mv: Move,
+    /// Searches of the position before `mv`, in the order they were added
+    lines: Vec<SearchResult>,
+}
+
+/// A game with engine analysis of its positions, written out as PGN
+#[derive(Clone)]
+pub struct AnalysisSession {
+    start: GameState,
+    current: GameState,
+    tags: Vec<(String, String)>,
+    plies: Vec<AnalyzedPly>,
+    result: Option<GameResult>,
+}
+
+impl Default for AnalysisSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnalysisSession {
+    /// A session starting from the initial position
+    pub fn new() -> Self {
+        Self::from_state(GameState::new())
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Self> {
+        Ok(Self::from_state(GameState::from_fen(fen)?))
+    }
+
+    pub fn from_fen_with_variant(fen: &str, variant: Variant) -> Result<Self> {
+        Ok(Self::from_state(GameState::from_fen_with_variant(
+            fen, variant,
+        )?))
+    }
+
+    fn from_state(start: GameState) -> Self {
+        AnalysisSession {
+            current: start.clone(),
+            start,
+            tags: Vec::new(),
+            plies: Vec::new(),
+            result: None,
+        }
+    }
+
+    /// Set a tag pair, replacing an earlier value; roster tags left unset are written
+    /// as `?`, and `Result` follows `set_result`
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, existing)) => *existing = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Result written after the moves; by default the result of the final position
+    pub fn set_result(&mut self, result: GameResult) {
+        self.result = Some(result);
+    }
+
+    /// Position after the moves played so far
+    pub fn game_state(&self) -> &GameState {
+        &self.current
+    }
+
+    /// Number of moves played so far
+    pub fn len(&self) -> usize {
+        self.plies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plies.is_empty()
+    }
+
+    /// Play `mv` in the main game
+    pub fn play(&mut self, mv: Move) -> Result<()> {
+        let mv = legal_move(&self.current, mv)?;
+        self.current.make_move(mv)?;
+        self.plies.push(AnalyzedPly {
+            mv,
+            lines: Vec::new(),
+        });
+        Ok(())
+    }
+
+    pub fn play_uci(&mut self, uci: &str) -> Result<()> {
+        let mv = self.current.resolve_uci_move(uci)?;
+        self.play(mv)
+    }
+
+    /// Attach a search of the position before move `ply` (0 for the first move).
+    /// Several searches of one position, such as the lines of a MultiPV search, are
+    /// written in the order they were added.
+    pub fn add_analysis(&mut self, ply: usize, result: SearchResult) -> Result<()> {
+        let played = self.plies.len();
+        let entry = self.plies.get_mut(ply).ok_or_else(|| {
+            EngineError::InvalidState(format!(
+                "no move {} to analyse, {} moves played",
+                ply, played
+            ))
+        })?;
+        entry.lines.push(result);
+        Ok(())
+    }
+
+    /// The session as PGN text, ending with a blank line
+    pub fn to_pgn(&self) -> Result<String> {
+        let result = result_token(self.result.unwrap_or_else(|| self.current.game_result()));
+
+        let mut pgn = String::new();
+        for name in ROSTER {
+            let value = match name {
+                "Result" => result.to_string(),
+                _ => self.tag(name).unwrap_or(default_tag(name)).to_string(),
+            };
+            push_tag(&mut pgn, name, &value);
+        }
+        let variant = self.start.position.variant;
+        if variant != Variant::Standard {
+            push_tag(&mut pgn, "Variant", variant.name());
+        }
+        let fen = self.start.to_fen();
+        if fen != Position::starting_position().to_fen() {
+            push_tag(&mut pgn, "SetUp", "1");
+            push_tag(&mut pgn, "FEN", &fen);
+        }
+        for (name, value) in &self.tags {
+            if !ROSTER.contains(&name.as_str())
+                && !["Variant", "SetUp", "FEN"].contains(&name.as_str())
+            {
+                push_tag(&mut pgn, name, value);
+            }
+        }
+        pgn.push('\n');
+
+        let mut tokens = self.movetext()?;
+        tokens.push(result.to_string());
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+                pgn.push_str(&line);
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        pgn.push_str(&line);
+        pgn.push_str("\n\n");
+        Ok(pgn)
+    }
+
+    pub fn write_pgn<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(self.to_pgn()?.as_bytes())?;
+        Ok(())
+    }
+
+    fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Movetext tokens: move numbers with their moves, comments and variation moves
+    fn movetext(&self) -> Result<Vec<String>> {
+        let mut tokens = Vec::new();
+        let mut state = self.start.clone();
+        // A Black move needs its number after a comment or variation, and at the start
+        let mut interrupted = true;
+
+        for ply in &self.plies {
+            tokens.push(numbered(&state, ply.mv, interrupted)?);
+            interrupted = false;
+
+            let agrees = |line: &&SearchResult| {
+                line.best_move
+                    .is_some_and(|best| best.names(ply.mv, &state.position, false))
+            };
+            let agreed = ply.lines.iter().find(agrees);
+            if let Some(line) = agreed {
+                tokens.push(score_comment(line, state.position.side_to_move));
+                interrupted = true;
+            }
+            for line in ply.lines.iter().filter(|line| !agrees(line)) {
+                let variation = variation(&state, line)?;
+                if !variation.is_empty() {
+                    tokens.extend(variation);
+                    interrupted = true;
+                }
+            }
+
+            state.make_move(ply.mv)?;
+        }
+        Ok(tokens)
+    }
+}
+
+/// `mv` in SAN, preceded by its move number when White plays it or when `interrupted`
+fn numbered(state: &GameState, mv: Move, interrupted: bool) -> Result<String> {
+    let san = move_to_san(state, mv)?;
+    let number = state.position.fullmove_number;
+    Ok(match state.position.side_to_move {
+        Color::White => format!("{}. {}", number, san),
+        Color::Black if interrupted => format!("{}... {}", number, san),
+        Color::Black => san,
+    })
+}
+
+/// The principal variation of `line` as the tokens of a parenthesised variation ending
+/// with its score, stopping at the first move that is not legal; empty without a legal
+/// move. The parentheses are attached to the first move and the score, so a long line
+/// can still be wrapped between its moves.
+fn variation(state: &GameState, line: &SearchResult) -> Result<Vec<String>> {
+    let mut pv: Vec<Move> = line
+        .principal_variation
+        .iter()
+        .map_while(|mv| *mv)
+        .collect();
+    if pv.is_empty() {
+        pv.extend(line.best_move);
+    }
+
+    let side_to_move = state.position.side_to_move;
+    let mut state = state.clone();
+    let mut tokens = Vec::new();
+    for mv in pv {
+        let Ok(mv) = legal_move(&state, mv) else {
+            break;
+        };
+        tokens.push(numbered(&state, mv, tokens.is_empty())?);
+        state.make_move(mv)?;
+    }
+    if let Some(first) = tokens.first_mut() {
+        first.insert(0, '(');
+        tokens.push(format!("{})", score_comment(line, side_to_move)));
+    }
+    Ok(tokens)
+}
+
+/// `{+0.54/18}` or `{#-3/18}`: the score from White's point of view and the depth.
+/// `side_to_move` is the side the search was made for.
+fn score_comment(line: &SearchResult, side_to_move: Color) -> String {
+    let sign = match side_to_move {
+        Color::White => 1,
+        Color::Black => -1,
+    };
+    let score = match line.mate_in() {
+        Some(moves) => format!("#{}", moves * sign),
+        None => format!("{:+.2}", (line.evaluation * sign) as f64 / 100.0),
+    };
+    format!("{{{}/{}}}", score, line.depth)
+}
+
+/// The legal move of `state` that `mv` names
+fn legal_move(state: &GameState, mv: Move) -> Result<Move> {
+    state
+        .generate_legal_moves()
+        .into_iter()
+        .find(|&legal| mv.names(legal, &state.position, false))
+        .ok_or_else(|| EngineError::InvalidState(format!("illegal move {}", mv.to_uci())))
+}
+
+fn result_token(result: GameResult) -> &'static str {
+    match result {
+        GameResult::WhiteWins => "1-0",
+        GameResult::BlackWins => "0-1",
+        GameResult::Draw => "1/2-1/2",
+        GameResult::Ongoing => "*",
+    }
+}
+
+fn default_tag(name: &str) -> &'static str {
+    match name {
+        "Date" => "????.??.??",
+        _ => "?",
+    }
+}
+
+fn push_tag(pgn: &mut String, name: &str, value: &str) {
+    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+    pgn.push_str(&format!("[{} \"{}\"]\n", name, escaped));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PgnReader;
+
+    fn analysis(state: &GameState, pv: &[&str], evaluation: i32, depth: u8) -> SearchResult {
+        let mut state = state.clone();
+        let mut result = SearchResult {
+            best_move: None,
+            evaluation,
+            depth,
+            seldepth: depth,
+            nodes_searched: 0,
+            elapsed_time: Default::default(),
+            principal_variation: [None; 16],
+            root_fail: None,
+            aspiration_researches: 0,
+        };
+        for (slot, uci) in result.principal_variation.iter_mut().zip(pv) {
+            let mv = state.resolve_uci_move(uci).unwrap();
+            state.make_move(mv).unwrap();
+            *slot = Some(mv);
+        }
+        result.best_move = result.principal_variation[0];
+        result
+    }
+
+    #[test]
+    fn test_exports_scores_and_variations() {
+        let mut session = AnalysisSession::new();
+        session.set_tag("White", "Engine");
+        session.set_tag("Annotator", "chess-engine");
+        for uci in ["e2e4", "e7e5", "g1f3"] {
+            session.play_uci(uci).unwrap();
+        }
+
+        let start = GameState::new();
+        session
+            .add_analysis(0, analysis(&start, &["e2e4", "e7e5"], 54, 18))
+            .unwrap();
+        let mut after_e4 = start.clone();
+        after_e4
+            .make_move(after_e4.resolve_uci_move("e2e4").unwrap())
+            .unwrap();
+        // Black's search prefers c5; its +0.20 for Black is -0.20 for White
+        session
+            .add_analysis(1, analysis(&after_e4, &["c7c5", "g1f3"], 20, 16))
+            .unwrap();
+        assert!(session
+            .add_analysis(3, analysis(&start, &["d2d4"], 0, 1))
+            .is_err());
+
+        let pgn = session.to_pgn().unwrap();
+        assert!(pgn.starts_with("[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n"));
+        assert!(pgn.contains("[White \"Engine\"]\n"));
+        assert!(pgn.contains("[Result \"*\"]\n[Annotator \"chess-engine\"]\n\n"));
+        assert!(!pgn.contains("[FEN"));
+        assert!(pgn.ends_with("1. e4 {+0.54/18} 1... e5 (1... c5 2. Nf3 {-0.20/16}) 2. Nf3 *\n\n"));
+
+        // The mainline reads back from the export
+        let games: Vec<_> = PgnReader::from_reader(pgn.as_bytes())
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].moves, ["e4", "e5", "Nf3"]);
+    }
+
+    #[test]
+    fn test_export_from_fen_with_mate_score() {
+        let fen = "6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1";
+        let mut session = AnalysisSession::from_fen(fen).unwrap();
+        session.play_uci("a1a8").unwrap();
+        let start = GameState::from_fen(fen).unwrap();
+        let mut mate = analysis(&start, &["a1a8"], 0, 5);
+        mate.evaluation = chess_core::search::engine::MATE_VALUE - 1;
+        session.add_analysis(0, mate).unwrap();
+
+        let pgn = session.to_pgn().unwrap();
+        assert!(pgn.contains("[Result \"1-0\"]\n[SetUp \"1\"]\n"));
+        assert!(pgn.contains(&format!("[FEN \"{}\"]\n", fen)));
+        assert!(pgn.ends_with("1. Ra8# {#1/5} 1-0\n\n"));
+    }
+
+    #[test]
+    fn test_long_movetext_is_wrapped() {
+        let mut session = AnalysisSession::new();
+        for _ in 0..10 {
+            for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+                session.play_uci(uci).unwrap();
+            }
+        }
+        session.set_result(GameResult::Draw);
+        session.set_tag("Event", "Quote \"test\"");
+
+        // A full 16-ply line is longer than a PGN line
+        let start = GameState::new();
+        let shuffle = ["b1c3", "b8c6", "c3b1", "c6b8"];
+        let pv: Vec<&str> = shuffle.iter().cycle().take(16).copied().collect();
+        session
+            .add_analysis(0, analysis(&start, &pv, 0, 20))
+            .unwrap();
+
+        let pgn = session.to_pgn().unwrap();
+        assert!(pgn.contains("[Event \"Quote \\\"test\\\"\"]"));
+        assert!(pgn.contains("(1. Nc3 Nc6 2. Nb1"));
+        assert!(pgn.contains("8. Nb1 Nb8 {+0.00/20})"));
+        assert!(pgn.lines().all(|line| line.len() <= LINE_WIDTH));
+        assert!(pgn.trim_end().ends_with("1/2-1/2"));
+    }
+}
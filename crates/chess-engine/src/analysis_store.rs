@@ -0,0 +1,300 @@
+// On-disk store of finished analyses, keyed by Zobrist hash, so tools that keep
+// analysing the same positions get their results back without searching again
+
+use crate::{EngineError, Move, Result};
+use chess_core::GameState;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Identifies analysis store files
+pub const ANALYSIS_STORE_MAGIC: [u8; 8] = *b"CEANLYS\0";
+/// Format and search version of the store. Bump it whenever the file layout, the
+/// evaluation or the search changes enough to make stored scores stale; files written
+/// with another version are discarded when opened.
+pub const ANALYSIS_STORE_VERSION: u32 = 1;
+/// Bytes per stored entry: hash, depth, score and the best move in UCI
+const RECORD_SIZE: usize = 8 + 1 + 4 + 5;
+const HEADER_SIZE: usize = 8 + 4 + 4;
+
+/// Result of an earlier search of one position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoredAnalysis {
+    pub depth: u8,
+    /// Centipawns from the side to move's point of view
+    pub score: i32,
+    /// Best move in UCI notation, zero padded
+    best_move: [u8; 5],
+}
+
+impl StoredAnalysis {
+    pub fn new(depth: u8, score: i32, best_move: Move) -> Self {
+        let mut uci = [0u8; 5];
+        let text = best_move.to_uci();
+        uci[..text.len()].copy_from_slice(text.as_bytes());
+        StoredAnalysis {
+            depth,
+            score,
+            best_move: uci,
+        }
+    }
+
+    /// Best move in UCI notation
+    pub fn best_move_uci(&self) -> &str {
+        let len = self.best_move.iter().position(|&b| b == 0).unwrap_or(5);
+        std::str::from_utf8(&self.best_move[..len]).unwrap_or("")
+    }
+
+    /// The stored move as a legal move of `game_state`. `None` means the entry belongs
+    /// to another position with the same hash.
+    pub fn best_move_in(&self, game_state: &GameState) -> Option<Move> {
+        game_state.resolve_uci_move(self.best_move_uci()).ok()
+    }
+}
+
+/// Analysis results by position hash, capped at `max_entries` with the least recently
+/// used entries evicted first
+#[derive(Debug)]
+pub struct AnalysisStore {
+    path: PathBuf,
+    max_entries: usize,
+    /// Entries with the tick of their last use
+    entries: HashMap<u64, (StoredAnalysis, u64)>,
+    tick: u64,
+    dirty: bool,
+}
+
+impl AnalysisStore {
+    /// Store backed by `path`, loading it if it exists. A file from another store
+    /// version starts the store empty; a file that is not a store is an error.
+    pub fn open(path: impl AsRef<Path>, max_entries: usize) -> Result<Self> {
+        if max_entries == 0 {
+            return Err(EngineError::ConfigurationError(
+                "Analysis store needs room for at least one entry".to_string(),
+            ));
+        }
+
+        let mut store = AnalysisStore {
+            path: path.as_ref().to_path_buf(),
+            max_entries,
+            entries: HashMap::new(),
+            tick: 0,
+            dirty: false,
+        };
+        if store.path.exists() {
+            store.load()?;
+        }
+        Ok(store)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn max_entries(&self) -> usize {
+        self.max_entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Stored analysis of the position with this hash, marking it as recently used
+    pub fn get(&mut self, hash: u64) -> Option<StoredAnalysis> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(&hash).map(|(analysis, used)| {
+            *used = tick;
+            *analysis
+        })
+    }
+
+    /// Record an analysis, keeping a deeper one already stored for the same hash
+    pub fn insert(&mut self, hash: u64, analysis: StoredAnalysis) {
+        self.tick += 1;
+        let tick = self.tick;
+        match self.entries.get_mut(&hash) {
+            Some((stored, _)) if stored.depth > analysis.depth => return,
+            Some(entry) => *entry = (analysis, tick),
+            None => {
+                self.entries.insert(hash, (analysis, tick));
+                self.evict();
+            }
+        }
+        self.dirty = true;
+    }
+
+    /// Write the store to its file if anything changed since it was loaded or saved
+    pub fn save(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        // Write a sibling file and rename it so a crash never leaves a torn store
+        let temp = self.path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&temp)?);
+        writer.write_all(&ANALYSIS_STORE_MAGIC)?;
+        writer.write_all(&ANALYSIS_STORE_VERSION.to_le_bytes())?;
+        writer.write_all(&(self.entries.len() as u32).to_le_bytes())?;
+
+        // Oldest first, so a reload keeps the recency order
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_by_key(|(_, (_, used))| *used);
+        for (hash, (analysis, _)) in entries {
+            writer.write_all(&hash.to_le_bytes())?;
+            writer.write_all(&[analysis.depth])?;
+            writer.write_all(&analysis.score.to_le_bytes())?;
+            writer.write_all(&analysis.best_move)?;
+        }
+        writer.flush()?;
+        drop(writer);
+
+        fs::rename(&temp, &self.path)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn load(&mut self) -> Result<()> {
+        let mut bytes = Vec::new();
+        BufReader::new(File::open(&self.path)?).read_to_end(&mut bytes)?;
+
+        if bytes.len() < HEADER_SIZE || bytes[..8] != ANALYSIS_STORE_MAGIC {
+            return Err(EngineError::IoError(format!(
+                "{} is not an analysis store",
+                self.path.display()
+            )));
+        }
+        let version = u32::from_le_bytes(bytes[8..12].try_into().expect("4 bytes"));
+        if version != ANALYSIS_STORE_VERSION {
+            // Stale results; the next save replaces the file
+            return Ok(());
+        }
+
+        let count = u32::from_le_bytes(bytes[12..16].try_into().expect("4 bytes")) as usize;
+        let records = &bytes[HEADER_SIZE..];
+        if records.len() != count * RECORD_SIZE {
+            return Err(EngineError::IoError(format!(
+                "{} is truncated",
+                self.path.display()
+            )));
+        }
+
+        for record in records.chunks_exact(RECORD_SIZE) {
+            let hash = u64::from_le_bytes(record[0..8].try_into().expect("8 bytes"));
+            let analysis = StoredAnalysis {
+                depth: record[8],
+                score: i32::from_le_bytes(record[9..13].try_into().expect("4 bytes")),
+                best_move: record[13..18].try_into().expect("5 bytes"),
+            };
+            self.tick += 1;
+            self.entries.insert(hash, (analysis, self.tick));
+        }
+        self.evict();
+        Ok(())
+    }
+
+    /// Drop least recently used entries until the store fits its cap
+    fn evict(&mut self) {
+        if self.entries.len() <= self.max_entries {
+            return;
+        }
+
+        // Evict an eighth at a time so inserting into a full store stays cheap
+        let target = self.max_entries - self.max_entries / 8;
+        let mut ticks: Vec<u64> = self.entries.values().map(|(_, used)| *used).collect();
+        ticks.sort_unstable();
+        let cutoff = ticks[self.entries.len() - target];
+        self.entries.retain(|_, (_, used)| *used >= cutoff);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PieceType, Square};
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "analysis_store_{}_{}.bin",
+            name,
+            std::process::id()
+        ))
+    }
+
+    fn analysis(depth: u8) -> StoredAnalysis {
+        StoredAnalysis::new(depth, 25, Move::normal(Square::E2, Square::E4))
+    }
+
+    #[test]
+    fn test_save_and_reopen() {
+        let path = temp_path("reopen");
+        let mut store = AnalysisStore::open(&path, 16).unwrap();
+        assert!(store.is_empty());
+        store.insert(1, analysis(6));
+        store.insert(
+            2,
+            StoredAnalysis::new(
+                3,
+                -40,
+                Move::promotion(Square::A7, Square::A8, PieceType::Queen),
+            ),
+        );
+        store.save().unwrap();
+
+        let mut reopened = AnalysisStore::open(&path, 16).unwrap();
+        assert_eq!(reopened.len(), 2);
+        assert_eq!(reopened.get(1), Some(analysis(6)));
+        assert_eq!(reopened.get(2).unwrap().best_move_uci(), "a7a8q");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_deeper_entries_win_and_cap_evicts_least_recent() {
+        let path = temp_path("cap");
+        let mut store = AnalysisStore::open(&path, 8).unwrap();
+        store.insert(0, analysis(8));
+        store.insert(0, analysis(4));
+        assert_eq!(store.get(0).unwrap().depth, 8);
+
+        for hash in 1..=8 {
+            store.insert(hash, analysis(1));
+        }
+        assert!(store.len() <= 8);
+        // Entries 0 and 1 were the least recently used
+        assert!(store.get(0).is_none());
+        assert!(store.get(1).is_none());
+        assert!(store.get(8).is_some());
+    }
+
+    #[test]
+    fn test_other_versions_are_discarded_and_foreign_files_rejected() {
+        let path = temp_path("version");
+        let mut bytes = ANALYSIS_STORE_MAGIC.to_vec();
+        bytes.extend_from_slice(&(ANALYSIS_STORE_VERSION + 1).to_le_bytes());
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&[0; RECORD_SIZE]);
+        fs::write(&path, &bytes).unwrap();
+        assert!(AnalysisStore::open(&path, 8).unwrap().is_empty());
+
+        fs::write(&path, b"not a store at all").unwrap();
+        assert!(AnalysisStore::open(&path, 8).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_best_move_must_be_legal() {
+        let stored = analysis(5);
+        let start = GameState::new();
+        assert_eq!(
+            stored.best_move_in(&start).map(|mv| mv.to_uci()),
+            Some("e2e4".to_string())
+        );
+        let empty_file = GameState::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(stored.best_move_in(&empty_file), None);
+    }
+}
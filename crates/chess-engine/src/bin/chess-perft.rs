@@ -0,0 +1,176 @@
+use chess_core::moves::{perft_hashed, PerftTable};
+use chess_core::search::parallel::ParallelUtils;
+use chess_core::{MoveGenerator, Position};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Instant;
+
+const USAGE: &str =
+    "Usage: chess-perft --depth N [--fen FEN] [--hash MB] [--checkpoint FILE] [--resume]
+
+Counts leaf nodes below each root move and in total. --hash counts transposed
+positions once through a table of that size. --checkpoint writes the finished root
+moves to FILE after each one, and --resume continues the run recorded there.";
+
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+struct Options {
+    fen: String,
+    depth: u8,
+    hash_mb: usize,
+    checkpoint: Option<PathBuf>,
+    resume: bool,
+}
+
+/// Root moves finished so far, enough to continue an interrupted run
+#[derive(Debug, Serialize, Deserialize)]
+struct Checkpoint {
+    fen: String,
+    depth: u8,
+    /// Leaf nodes below each finished root move, by UCI move
+    subtotals: Vec<(String, u64)>,
+}
+
+impl Checkpoint {
+    fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Replace the file through a temporary one, so a crash leaves the old or the new
+    /// checkpoint but never a partly written one
+    fn save(&self, path: &Path) -> Result<(), String> {
+        let temporary = path.with_extension("tmp");
+        let text = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&temporary, text)
+            .and_then(|()| std::fs::rename(&temporary, path))
+            .map_err(|e| format!("cannot write {}: {}", path.display(), e))
+    }
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        fen: START_FEN.to_string(),
+        depth: 0,
+        hash_mb: 0,
+        checkpoint: None,
+        resume: false,
+    };
+    let mut args = std::env::args().skip(1);
+
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            "--resume" => {
+                options.resume = true;
+                continue;
+            }
+            _ => {}
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", flag))?;
+        let invalid = || format!("Invalid value for {}: {}", flag, value);
+
+        match flag.as_str() {
+            "--fen" => options.fen = value.clone(),
+            "--depth" => options.depth = value.parse().map_err(|_| invalid())?,
+            "--hash" => options.hash_mb = value.parse().map_err(|_| invalid())?,
+            "--checkpoint" => options.checkpoint = Some(PathBuf::from(&value)),
+            _ => return Err(format!("Unknown option: {}", flag)),
+        }
+    }
+
+    if options.depth == 0 {
+        return Err("--depth must be at least 1".to_string());
+    }
+    if options.resume && options.checkpoint.is_none() {
+        return Err("--resume needs --checkpoint".to_string());
+    }
+    Ok(options)
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let position = Position::from_fen(&options.fen).map_err(|e| e.to_string())?;
+    let move_generator = MoveGenerator::new();
+    let mut table = (options.hash_mb > 0).then(|| PerftTable::new(options.hash_mb));
+
+    let mut checkpoint = match &options.checkpoint {
+        Some(path) if options.resume => {
+            let checkpoint = Checkpoint::load(path)?;
+            if checkpoint.depth != options.depth || checkpoint.fen != position.to_fen() {
+                return Err(format!(
+                    "{} is a depth {} run of {}",
+                    path.display(),
+                    checkpoint.depth,
+                    checkpoint.fen
+                ));
+            }
+            eprintln!(
+                "resuming with {} root moves done",
+                checkpoint.subtotals.len()
+            );
+            checkpoint
+        }
+        _ => Checkpoint {
+            fen: position.to_fen(),
+            depth: options.depth,
+            subtotals: Vec::new(),
+        },
+    };
+
+    let start = Instant::now();
+    let mut searched = 0;
+    for mv in move_generator.generate_legal_moves(&position) {
+        let uci = mv.to_uci();
+        if let Some((_, nodes)) = checkpoint.subtotals.iter().find(|(done, _)| *done == uci) {
+            println!("{}: {}", uci, nodes);
+            continue;
+        }
+
+        let mut next = position.clone();
+        next.make_move(mv).map_err(|e| e.to_string())?;
+        let depth = options.depth - 1;
+        let nodes = match table.as_mut() {
+            Some(table) => perft_hashed(&next, depth, &move_generator, table),
+            None => ParallelUtils::sequential_perft(&next, depth, &move_generator),
+        };
+        println!("{}: {}", uci, nodes);
+        searched += nodes;
+
+        checkpoint.subtotals.push((uci, nodes));
+        if let Some(path) = &options.checkpoint {
+            checkpoint.save(path)?;
+        }
+    }
+
+    let elapsed = start.elapsed();
+    let total: u64 = checkpoint.subtotals.iter().map(|(_, nodes)| nodes).sum();
+    println!("\nNodes searched: {}", total);
+    eprintln!(
+        "{:.1}s, {} nodes/s this run{}",
+        elapsed.as_secs_f64(),
+        (searched as f64 / elapsed.as_secs_f64().max(1e-9)) as u64,
+        table
+            .map(|table| format!(", {} table hits", table.hits()))
+            .unwrap_or_default()
+    );
+    Ok(())
+}
+
+fn main() {
+    let options = parse_args().unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        process::exit(2);
+    });
+
+    if let Err(e) = run(options) {
+        eprintln!("chess-perft: {}", e);
+        process::exit(1);
+    }
+}
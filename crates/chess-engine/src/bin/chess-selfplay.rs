@@ -0,0 +1,101 @@
+use chess_engine::{GameResult, SelfPlay, SelfPlayConfig, TrainingExporter, TrainingFormat};
+use std::process;
+
+const USAGE: &str = "Usage: chess-selfplay --output FILE [--format text|bullet] [--games N] \
+[--depth N] [--random-plies N] [--temperature CP] [--temperature-plies N] [--max-plies N] [--seed N]";
+
+struct Options {
+    games: u32,
+    output: String,
+    format: TrainingFormat,
+    config: SelfPlayConfig,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        games: 1,
+        output: String::new(),
+        format: TrainingFormat::Text,
+        config: SelfPlayConfig::default(),
+    };
+    let mut args = std::env::args().skip(1);
+
+    while let Some(flag) = args.next() {
+        if flag == "--help" || flag == "-h" {
+            println!("{}", USAGE);
+            process::exit(0);
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", flag))?;
+        let invalid = || format!("Invalid value for {}: {}", flag, value);
+        let number = || value.parse::<u64>().map_err(|_| invalid());
+
+        match flag.as_str() {
+            "--output" => options.output = value.clone(),
+            "--format" => {
+                options.format = match value.as_str() {
+                    "text" => TrainingFormat::Text,
+                    "bullet" => TrainingFormat::BulletBinary,
+                    _ => return Err(invalid()),
+                }
+            }
+            "--games" => options.games = number()? as u32,
+            "--depth" => options.config.depth = number()?.clamp(1, u8::MAX as u64) as u8,
+            "--random-plies" => options.config.random_plies = number()? as u32,
+            "--temperature" => options.config.temperature = value.parse().map_err(|_| invalid())?,
+            "--temperature-plies" => options.config.temperature_plies = number()? as u32,
+            "--max-plies" => options.config.max_plies = number()? as u32,
+            "--seed" => options.config.seed = number()?,
+            _ => return Err(format!("Unknown option: {}", flag)),
+        }
+    }
+
+    if options.output.is_empty() {
+        return Err("--output is required".to_string());
+    }
+    Ok(options)
+}
+
+fn run(options: Options) -> chess_engine::Result<()> {
+    let mut selfplay = SelfPlay::new(options.config)?;
+    let mut exporter = TrainingExporter::create(&options.output, options.format)?;
+
+    for game_number in 1..=options.games {
+        let game = selfplay.play_game()?;
+        for sample in game.training_samples() {
+            exporter.write_sample(&sample)?;
+        }
+
+        let result = match game.result {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Ongoing => "*",
+        };
+        println!(
+            "game {} {} ({} plies): {}",
+            game_number,
+            result,
+            game.moves.len(),
+            game.uci_moves().join(" ")
+        );
+    }
+
+    let samples = exporter.samples_written();
+    exporter.finish()?;
+    eprintln!("wrote {} positions to {}", samples, options.output);
+    Ok(())
+}
+
+fn main() {
+    let options = parse_args().unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        process::exit(2);
+    });
+
+    if let Err(e) = run(options) {
+        eprintln!("chess-selfplay: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,76 @@
+use chess_core::utils::fen_check::{check_fen, epd_to_fen};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::process;
+
+const USAGE: &str = "Usage: validate-fen [FILE...]
+
+Checks one FEN or EPD record per line, read from the files or from standard input.
+Blank lines and lines starting with '#' are skipped. Exits with status 1 if any
+record fails.";
+
+struct Totals {
+    records: usize,
+    failures: usize,
+    moves: usize,
+}
+
+fn check_lines(name: &str, reader: impl BufRead, totals: &mut Totals) -> io::Result<()> {
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let record = line.trim();
+        if record.is_empty() || record.starts_with('#') {
+            continue;
+        }
+
+        totals.records += 1;
+        let result = match epd_to_fen(record) {
+            Some(fen) => check_fen(&fen),
+            None => check_fen(record),
+        };
+        match result {
+            Ok(report) => totals.moves += report.moves_checked,
+            Err(e) => {
+                totals.failures += 1;
+                println!("{}:{}: {}: {}", name, index + 1, e, record);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    let files: Vec<String> = std::env::args().skip(1).collect();
+    if files.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+
+    let mut totals = Totals {
+        records: 0,
+        failures: 0,
+        moves: 0,
+    };
+    let result = if files.is_empty() {
+        check_lines("<stdin>", io::stdin().lock(), &mut totals)
+    } else {
+        files.iter().try_for_each(|path| {
+            let file =
+                File::open(path).map_err(|e| io::Error::new(e.kind(), format!("{path}: {e}")))?;
+            check_lines(path, BufReader::new(file), &mut totals)
+        })
+    };
+
+    if let Err(e) = result {
+        eprintln!("validate-fen: {}", e);
+        process::exit(2);
+    }
+
+    eprintln!(
+        "{} records, {} failed, {} moves made and unmade",
+        totals.records, totals.failures, totals.moves
+    );
+    if totals.failures > 0 {
+        process::exit(1);
+    }
+}
@@ -1,11 +1,62 @@
-use crate::{ChessEngine, EngineConfig, EngineError, EventHandler, Result};
+use crate::{
+    AnalysisStore, BlunderMode, ChessEngine, ConfigFile, EngineConfig, EngineError, EventHandler,
+    MoveTemperature, NotationStyle, Pruning, Result,
+};
+use chess_core::utils::memory::TranspositionEntry;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
+/// Transposition table entries per megabyte
+const ENTRIES_PER_MB: usize = 1024 * 1024 / std::mem::size_of::<TranspositionEntry>();
+
+/// Search settings tuned for a kind of host
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Preset {
+    /// Phones and other battery-powered devices: shallow, quick, one thread, 4 MB hash
+    MobileFast,
+    /// Single-threaded WebAssembly in a browser tab with 16 MB of hash
+    BrowserWasm,
+    /// Desktop play: moderate depth, up to four threads and 64 MB of hash
+    Balanced,
+    /// Engine matches on a dedicated machine: every core and 256 MB of hash
+    Strongest,
+    /// Long server-side analysis with conservative pruning and 512 MB of hash
+    Analysis,
+}
+
+impl Preset {
+    /// Engine configuration of this preset; notation and debug settings stay at defaults
+    pub fn config(self) -> EngineConfig {
+        let cores = std::thread::available_parallelism()
+            .map(|cores| cores.get())
+            .unwrap_or(1)
+            .clamp(1, 16);
+        let (depth, time_limit_ms, thread_count, hash_mb, pruning) = match self {
+            Preset::MobileFast => (6, 300, 1, 4, Pruning::Full),
+            Preset::BrowserWasm => (8, 1_000, 1, 16, Pruning::Full),
+            Preset::Balanced => (10, 2_000, cores.min(4), 64, Pruning::Full),
+            Preset::Strongest => (20, 10_000, cores, 256, Pruning::Full),
+            Preset::Analysis => (20, 60_000, cores, 512, Pruning::Conservative),
+        };
+        EngineConfig {
+            depth,
+            time_limit_ms: Some(time_limit_ms),
+            enable_transposition_table: true,
+            transposition_table_size: hash_mb * ENTRIES_PER_MB,
+            thread_count,
+            pruning,
+            ..EngineConfig::default()
+        }
+    }
+}
+
 pub struct ChessEngineBuilder {
     config: EngineConfig,
     fen: Option<String>,
     event_handler: Option<Arc<Mutex<dyn EventHandler>>>,
     auto_initialize: bool,
+    analysis_store: Option<(PathBuf, usize)>,
 }
 
 impl ChessEngineBuilder {
@@ -15,7 +66,84 @@ impl ChessEngineBuilder {
             fen: None,
             event_handler: None,
             auto_initialize: true,
+            analysis_store: None,
+        }
+    }
+
+    /// Builder with the settings of a TOML or JSON config file; see `ConfigFile`
+    pub fn from_config_file(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new().with_config_file(&ConfigFile::load(path)?))
+    }
+
+    /// Start from the settings of `preset`, keeping the notation style, debug mode, move
+    /// temperature, blunder mode and search and thread pool overrides; `with_*` calls
+    /// made afterwards adjust individual settings
+    pub fn with_preset(mut self, preset: Preset) -> Self {
+        self.config = EngineConfig {
+            notation_style: self.config.notation_style,
+            debug_mode: self.config.debug_mode,
+            move_temperature: self.config.move_temperature,
+            blunder_mode: self.config.blunder_mode,
+            search: self.config.search.take(),
+            parallel: self.config.parallel.take(),
+            ..preset.config()
+        };
+        self
+    }
+
+    /// Apply the settings `file` sets: its preset first, then the individual settings.
+    /// The thread count comes from `parallel.num_threads` and the hash size from
+    /// `memory.tt_size_mb`.
+    pub fn with_config_file(mut self, file: &ConfigFile) -> Self {
+        if let Some(preset) = file.preset {
+            self = self.with_preset(preset);
+        }
+        if let Some(depth) = file.depth {
+            self = self.with_depth(depth);
+        }
+        if let Some(time_limit_ms) = file.time_limit_ms {
+            self = self.with_time_limit(time_limit_ms);
+        }
+        if let Some(pruning) = file.pruning {
+            self = self.with_pruning(pruning);
+        }
+        if let Some(search) = &file.search {
+            self.config.search = Some(search.clone());
+        }
+        if let Some(parallel) = &file.parallel {
+            self = self.with_threads(parallel.num_threads);
+            self.config.parallel = Some(parallel.clone());
         }
+        if let Some(memory) = &file.memory {
+            self.config.transposition_table_size = memory.tt_size_mb * ENTRIES_PER_MB;
+        }
+        self
+    }
+
+    pub fn with_pruning(mut self, pruning: Pruning) -> Self {
+        self.config.pruning = pruning;
+        self
+    }
+
+    /// Vary play by choosing among moves at most `window` centipawns worse than the
+    /// best, with softmax `temperature` in centipawns
+    pub fn with_move_temperature(mut self, temperature: f64, window: i32) -> Self {
+        self.config.move_temperature = Some(MoveTemperature {
+            temperature,
+            window,
+        });
+        self
+    }
+
+    /// Make a deliberate mistake on a `frequency` share of moves (0 to 1), giving away
+    /// between `min_loss` and `max_loss` centipawns
+    pub fn with_blunder_mode(mut self, frequency: f64, min_loss: i32, max_loss: i32) -> Self {
+        self.config.blunder_mode = Some(BlunderMode {
+            frequency,
+            min_loss,
+            max_loss,
+        });
+        self
     }
 
     pub fn with_depth(mut self, mut depth: u8) -> Self {
@@ -49,6 +177,11 @@ impl ChessEngineBuilder {
         self
     }
 
+    pub fn with_notation_style(mut self, style: NotationStyle) -> Self {
+        self.config.notation_style = style;
+        self
+    }
+
     pub fn with_debug_mode(mut self, enable: bool) -> Self {
         self.config.debug_mode = enable;
         self
@@ -69,6 +202,12 @@ impl ChessEngineBuilder {
         self
     }
 
+    /// Keep search results in the analysis store at `path`, holding up to `max_entries`
+    pub fn with_analysis_store(mut self, path: impl Into<PathBuf>, max_entries: usize) -> Self {
+        self.analysis_store = Some((path.into(), max_entries));
+        self
+    }
+
     pub fn build(self) -> Result<ChessEngine> {
         let mut engine = if let Some(fen) = &self.fen {
             let mut engine = ChessEngine::from_fen(fen)?;
@@ -82,6 +221,10 @@ impl ChessEngineBuilder {
             engine.set_event_handler(handler);
         }
 
+        if let Some((path, max_entries)) = self.analysis_store {
+            engine.set_analysis_store(Some(AnalysisStore::open(path, max_entries)?));
+        }
+
         if self.auto_initialize {
             engine.initialize()?;
         }
@@ -162,6 +305,7 @@ mod tests {
     fn test_builder_with_threads() {
         let engine = ChessEngineBuilder::new().with_threads(4).build().unwrap();
         assert_eq!(engine.get_config().thread_count, 4);
+        assert_eq!(engine.get_config().parallel_config().num_threads, 4);
     }
 
     #[test]
@@ -217,4 +361,49 @@ mod tests {
         assert!(engine.get_config().enable_transposition_table);
         assert!(engine.get_config().debug_mode);
     }
+
+    #[test]
+    fn test_presets_pass_validation() {
+        for preset in [
+            Preset::MobileFast,
+            Preset::BrowserWasm,
+            Preset::Balanced,
+            Preset::Strongest,
+            Preset::Analysis,
+        ] {
+            let config = preset.config();
+            assert!(config.time_limit_ms.is_some(), "{:?}", preset);
+            assert!(config.enable_transposition_table, "{:?}", preset);
+            assert!((1..=16).contains(&config.thread_count), "{:?}", preset);
+        }
+        assert_eq!(Preset::BrowserWasm.config().thread_count, 1);
+        assert_eq!(Preset::Analysis.config().hash_size_mb(), 512);
+        assert_eq!(Preset::Analysis.config().pruning, Pruning::Conservative);
+    }
+
+    #[test]
+    fn test_preset_applies_hash_and_later_overrides() {
+        let engine = ChessEngineBuilder::new()
+            .with_notation_style(NotationStyle::San)
+            .with_preset(Preset::MobileFast)
+            .with_depth(3)
+            .build_and_validate()
+            .unwrap();
+
+        assert_eq!(engine.get_config().depth, 3);
+        assert_eq!(engine.get_config().time_limit_ms, Some(300));
+        assert_eq!(engine.get_config().notation_style, NotationStyle::San);
+        assert_eq!(engine.get_hash_size(), 4);
+    }
+
+    #[test]
+    fn test_conservative_pruning_reaches_search() {
+        let mut engine = ChessEngineBuilder::new()
+            .with_depth(3)
+            .with_pruning(Pruning::Conservative)
+            .build()
+            .unwrap();
+        assert!(!engine.get_config().search_config().use_futility_pruning);
+        assert!(engine.find_best_move().unwrap().into_move().is_some());
+    }
 }
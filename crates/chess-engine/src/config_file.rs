@@ -0,0 +1,300 @@
+// Engine settings read from a TOML or JSON file
+//
+// Server operators tune the engine by editing a file instead of recompiling. Every key
+// is optional and falls back to the engine's default, while unknown keys are rejected
+// so a misspelt setting fails loudly instead of being ignored. Values are checked after
+// parsing, and each problem is reported with the path of its field, e.g.
+// `search.aspiration_window`.
+
+use crate::{EngineError, MemoryConfig, ParallelConfig, Preset, Pruning, Result, SearchConfig};
+use chess_core::search::engine::MAX_DEPTH;
+use chess_core::search::ThreadAffinity;
+use chess_core::MateVerification;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Deepest fixed depth, as `ChessEngineBuilder::with_depth` clamps to
+const MAX_ENGINE_DEPTH: u8 = 20;
+/// Most threads an engine accepts, as `ChessEngineBuilder::with_threads` clamps to
+const MAX_THREADS: usize = 16;
+/// Largest transposition table accepted, in megabytes
+const MAX_HASH_MB: usize = 64 * 1024;
+
+/// Contents of an engine config file. In TOML:
+///
+/// ```toml
+/// preset = "Balanced"
+/// depth = 12
+///
+/// [search]
+/// aspiration_window = 30
+/// mate_verification = "Prove"
+///
+/// [parallel]
+/// num_threads = 4
+///
+/// [memory]
+/// tt_size_mb = 128
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Preset the other settings adjust
+    pub preset: Option<Preset>,
+    pub depth: Option<u8>,
+    pub time_limit_ms: Option<u64>,
+    pub pruning: Option<Pruning>,
+    pub search: Option<SearchConfig>,
+    pub parallel: Option<ParallelConfig>,
+    /// Only `tt_size_mb` applies to an engine; the rest is for a `MemoryManager`
+    pub memory: Option<MemoryConfig>,
+}
+
+impl ConfigFile {
+    /// Read and validate a config file, parsed as TOML when its extension is `.toml`
+    /// and as JSON otherwise
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let is_toml = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+        let file = if is_toml {
+            Self::from_toml(&text)
+        } else {
+            Self::from_json(&text)
+        }
+        .map_err(|error| match error {
+            EngineError::ConfigurationError(msg) => {
+                EngineError::ConfigurationError(format!("{}: {}", path.display(), msg))
+            }
+            other => other,
+        })?;
+        Ok(file)
+    }
+
+    /// Parse and validate a JSON config
+    pub fn from_json(text: &str) -> Result<Self> {
+        let file: ConfigFile = serde_json::from_str(text)
+            .map_err(|error| EngineError::ConfigurationError(error.to_string()))?;
+        file.validate()?;
+        Ok(file)
+    }
+
+    /// Parse and validate a TOML config
+    #[cfg(feature = "toml")]
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let file: ConfigFile = toml::from_str(text)
+            .map_err(|error| EngineError::ConfigurationError(error.to_string()))?;
+        file.validate()?;
+        Ok(file)
+    }
+
+    /// Without the `toml` feature TOML configs are refused
+    #[cfg(not(feature = "toml"))]
+    pub fn from_toml(_text: &str) -> Result<Self> {
+        Err(EngineError::ConfigurationError(
+            "TOML config files need the `toml` feature".to_string(),
+        ))
+    }
+
+    /// Check every setting, reporting all out-of-range ones at once
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+        let mut check = |ok: bool, field: &str, requirement: &str, value: String| {
+            if !ok {
+                problems.push(format!("{} must be {}, got {}", field, requirement, value));
+            }
+        };
+
+        if let Some(depth) = self.depth {
+            check(
+                (1..=MAX_ENGINE_DEPTH).contains(&depth),
+                "depth",
+                &format!("between 1 and {}", MAX_ENGINE_DEPTH),
+                depth.to_string(),
+            );
+        }
+        if let Some(ms) = self.time_limit_ms {
+            check(ms > 0, "time_limit_ms", "positive", ms.to_string());
+        }
+
+        if let Some(search) = &self.search {
+            for (field, value) in [
+                ("razoring_margin", search.razoring_margin),
+                ("reverse_futility_margin", search.reverse_futility_margin),
+                ("delta_margin", search.delta_margin),
+                ("see_quiet_margin", search.see_quiet_margin),
+            ] {
+                check(
+                    value >= 0,
+                    &format!("search.{}", field),
+                    "at least 0",
+                    value.to_string(),
+                );
+            }
+            check(
+                search.aspiration_window > 0,
+                "search.aspiration_window",
+                "positive",
+                search.aspiration_window.to_string(),
+            );
+            check(
+                search.contempt.abs() <= 1000,
+                "search.contempt",
+                "between -1000 and 1000",
+                search.contempt.to_string(),
+            );
+            if let MateVerification::Deeper(plies) = search.mate_verification {
+                check(
+                    (1..=MAX_DEPTH).contains(&plies),
+                    "search.mate_verification.Deeper",
+                    &format!("between 1 and {}", MAX_DEPTH),
+                    plies.to_string(),
+                );
+            }
+        }
+
+        if let Some(parallel) = &self.parallel {
+            check(
+                (1..=MAX_THREADS).contains(&parallel.num_threads),
+                "parallel.num_threads",
+                &format!("between 1 and {}", MAX_THREADS),
+                parallel.num_threads.to_string(),
+            );
+            check(
+                parallel.chunk_size > 0,
+                "parallel.chunk_size",
+                "positive",
+                parallel.chunk_size.to_string(),
+            );
+            if let ThreadAffinity::Cores(cores) = &parallel.affinity {
+                check(
+                    !cores.is_empty(),
+                    "parallel.affinity.Cores",
+                    "a non-empty list",
+                    "[]".to_string(),
+                );
+            }
+        }
+
+        if let Some(memory) = &self.memory {
+            check(
+                (1..=MAX_HASH_MB).contains(&memory.tt_size_mb),
+                "memory.tt_size_mb",
+                &format!("between 1 and {}", MAX_HASH_MB),
+                memory.tt_size_mb.to_string(),
+            );
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(EngineError::ConfigurationError(problems.join("; ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChessEngineBuilder;
+
+    #[test]
+    fn test_json_sections_reach_the_engine() {
+        let file = ConfigFile::from_json(
+            r#"{
+                "preset": "MobileFast",
+                "depth": 3,
+                "search": { "aspiration_window": 25, "use_razoring": false },
+                "parallel": { "num_threads": 2, "priority": "Low" },
+                "memory": { "tt_size_mb": 8 }
+            }"#,
+        )
+        .unwrap();
+
+        let engine = ChessEngineBuilder::new()
+            .with_config_file(&file)
+            .auto_initialize(false)
+            .build()
+            .unwrap();
+        let config = engine.get_config();
+        assert_eq!(config.depth, 3);
+        assert_eq!(config.time_limit_ms, Some(300));
+        assert_eq!(config.thread_count, 2);
+        assert_eq!(config.hash_size_mb(), 8);
+
+        let search = config.search_config();
+        assert_eq!(search.aspiration_window, 25);
+        assert!(!search.use_razoring);
+        assert!(search.use_null_move_pruning);
+        assert_eq!(search.limits.depth, Some(3));
+        let parallel = config.parallel_config();
+        assert_eq!(parallel.num_threads, 2);
+        assert_eq!(parallel.priority, chess_core::search::ThreadPriority::Low);
+    }
+
+    #[test]
+    fn test_errors_name_the_field() {
+        let error = ConfigFile::from_json(r#"{ "search": { "aspiration_windw": 25 } }"#)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("aspiration_windw"), "{}", error);
+
+        let error = ConfigFile::from_json(
+            r#"{ "depth": 0, "search": { "aspiration_window": 0 }, "memory": { "tt_size_mb": 0 } }"#,
+        )
+        .unwrap_err()
+        .to_string();
+        for field in ["depth", "search.aspiration_window", "memory.tt_size_mb"] {
+            assert!(error.contains(&format!("{} must be", field)), "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_load_from_file() {
+        let path = std::env::temp_dir().join(format!("engine-config-{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"{ "pruning": "Conservative", "time_limit_ms": 0 }"#,
+        )
+        .unwrap();
+        let error = ChessEngineBuilder::from_config_file(&path)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(
+            error.contains("time_limit_ms must be positive"),
+            "{}",
+            error
+        );
+
+        std::fs::write(&path, r#"{ "pruning": "Conservative" }"#).unwrap();
+        let engine = ChessEngineBuilder::from_config_file(&path)
+            .unwrap()
+            .auto_initialize(false)
+            .build()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(!engine.get_config().search_config().use_futility_pruning);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_config() {
+        let file = ConfigFile::from_toml(
+            "depth = 4\n\n[search]\nmate_verification = { Deeper = 2 }\n\n[parallel]\nnum_threads = 1\n",
+        )
+        .unwrap();
+        assert_eq!(file.depth, Some(4));
+        assert_eq!(
+            file.search.unwrap().mate_verification,
+            MateVerification::Deeper(2)
+        );
+
+        let error = ConfigFile::from_toml("[memory]\ntt_size_mb = -1\n")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("tt_size_mb"), "{}", error);
+    }
+}
@@ -1,11 +1,24 @@
 use crate::{
-    event::DefaultEventHandler, EngineError, EventHandler, GameEvent, GameInfo, MoveResult, Result,
+    analysis_store::{AnalysisStore, StoredAnalysis},
+    event::{DefaultEventHandler, DrawReason},
+    recorder::GameRecorder,
+    selftest::{self, SelfTestReport},
+    AnalysisResult, BestMove, EngineError, EventHandler, GameEvent, GameInfo, MoveResult, Result,
 };
+use chess_core::game::notation;
+use chess_core::search::engine::{MATE_THRESHOLD, MAX_DEPTH};
+use chess_core::search::TablebaseProbe;
+use chess_core::utils::memory::TranspositionEntry;
+use chess_core::utils::zobrist::zobrist_keys;
 use chess_core::{
-    Color, Evaluator, GameResult, GameState, Move, MoveGenerator, Piece, Position, SearchConfig,
-    SearchEngine, Square,
+    BatchSearcher, ChessError, Color, EvalBreakdown, Evaluator, GameResult, GameState, Move,
+    MoveGenerator, NotationStyle, ParallelConfig, Piece, PieceType, Position, Score, SearchConfig,
+    SearchEngine, SearchLimits, SearchResult, SearchStatusHandle, Square, Variant, Wdl,
 };
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub struct EngineConfig {
@@ -16,6 +29,92 @@ pub struct EngineConfig {
     pub enable_book: bool,
     pub thread_count: usize,
     pub debug_mode: bool,
+    /// How moves are written in `MoveResult` and `AnalysisResult`
+    pub notation_style: NotationStyle,
+    /// How aggressively the search prunes
+    pub pruning: Pruning,
+    /// Pick `find_best_move`'s move at random among the near-best ones; `None` always
+    /// plays the best
+    pub move_temperature: Option<MoveTemperature>,
+    /// Let `find_best_move` make deliberate mistakes now and then; `None` never does
+    pub blunder_mode: Option<BlunderMode>,
+    /// Search settings to use instead of the defaults, e.g. from a config file. The
+    /// limits still come from `depth` and `time_limit_ms`, and `pruning` still applies.
+    pub search: Option<SearchConfig>,
+    /// Thread pool settings to use instead of the defaults; the pool size still comes
+    /// from `thread_count`
+    pub parallel: Option<ParallelConfig>,
+}
+
+/// Occasional deliberate mistakes of a chosen size, for trainer apps that teach
+/// punishing errors. Each one is reported with `GameEvent::DeliberateMistake`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlunderMode {
+    /// Chance from 0 to 1 that a move is a deliberate mistake
+    pub frequency: f64,
+    /// Fewest centipawns a mistake gives away
+    pub min_loss: i32,
+    /// Most centipawns a mistake gives away
+    pub max_loss: i32,
+}
+
+/// Random choice among the root moves close to the best, for natural variety without
+/// weakening play the way a skill level does
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoveTemperature {
+    /// Softmax temperature in centipawns: a move this much worse than the best is
+    /// played e times less often
+    pub temperature: f64,
+    /// Moves more than this many centipawns worse than the best are never played
+    pub window: i32,
+}
+
+impl MoveTemperature {
+    /// The move `fraction` (in `[0, 1)`) selects, each weighted by
+    /// `exp((score - best) / temperature)`; the best move without candidates
+    fn pick(&self, candidates: &[(Move, i32)], fraction: f64) -> Option<Move> {
+        let best = candidates.iter().map(|&(_, score)| score).max()?;
+        let temperature = self.temperature.max(f64::MIN_POSITIVE);
+        let weights: Vec<f64> = candidates
+            .iter()
+            .map(|&(_, score)| ((score - best) as f64 / temperature).exp())
+            .collect();
+
+        let mut target = fraction * weights.iter().sum::<f64>();
+        for (&(mv, _), weight) in candidates.iter().zip(&weights) {
+            if target < *weight {
+                return Some(mv);
+            }
+            target -= weight;
+        }
+        candidates.last().map(|&(mv, _)| mv)
+    }
+}
+
+/// Uniform fraction in `[0, 1)` from the standard library's randomly keyed hasher
+fn random_fraction() -> f64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::UNIX_EPOCH
+            .elapsed()
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Pruning profile of the search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Pruning {
+    /// Every pruning technique, for the most depth per second
+    #[default]
+    Full,
+    /// Skip the speculative forward pruning (razoring, futility, reverse futility and
+    /// SEE pruning), so deep analysis misses fewer tactics
+    Conservative,
 }
 
 impl Default for EngineConfig {
@@ -28,42 +127,128 @@ impl Default for EngineConfig {
             enable_book: false,
             thread_count: 1,
             debug_mode: false,
+            notation_style: NotationStyle::Uci,
+            pruning: Pruning::Full,
+            move_temperature: None,
+            blunder_mode: None,
+            search: None,
+            parallel: None,
         }
     }
 }
 
+impl EngineConfig {
+    /// Search limits matching `depth` and `time_limit_ms`
+    pub fn search_limits(&self) -> SearchLimits {
+        let limits = SearchLimits::default().with_depth(self.depth.clamp(1, MAX_DEPTH));
+        match self.time_limit_ms {
+            Some(ms) => limits.with_movetime(Duration::from_millis(ms)),
+            None => limits,
+        }
+    }
+
+    /// Search configuration with these limits and pruning profile
+    pub fn search_config(&self) -> SearchConfig {
+        let mut config = SearchConfig {
+            limits: self.search_limits(),
+            ..self.search.clone().unwrap_or_default()
+        };
+        if self.pruning == Pruning::Conservative {
+            config.use_razoring = false;
+            config.use_futility_pruning = false;
+            config.use_reverse_futility_pruning = false;
+            config.use_see_pruning = false;
+        }
+        config
+    }
+
+    /// Transposition table size in megabytes holding `transposition_table_size`
+    /// entries, rounded up to the table's power-of-two entry count
+    pub fn hash_size_mb(&self) -> usize {
+        let bytes = self.transposition_table_size.max(1).next_power_of_two()
+            * std::mem::size_of::<TranspositionEntry>();
+        bytes.div_ceil(1024 * 1024)
+    }
+
+    /// Parallel search settings whose dedicated thread pool has `thread_count` threads
+    pub fn parallel_config(&self) -> ParallelConfig {
+        self.parallel
+            .clone()
+            .unwrap_or_default()
+            .with_threads(self.thread_count)
+    }
+}
+
+/// The game an engine was playing, saved by `ChessEngine::snapshot`
+///
+/// Snapshots share the game record with the engine until either changes it, so taking
+/// one per explored variation is cheap. Clones share it too.
+#[derive(Clone)]
+pub struct EngineSnapshot {
+    game: Arc<GameRecorder>,
+}
+
+impl EngineSnapshot {
+    /// FEN of the saved position
+    pub fn fen(&self) -> String {
+        self.game.state().to_fen()
+    }
+}
+
 pub struct ChessEngine {
-    game_state: GameState,
+    /// Shared with snapshots and copied when changed while one is alive
+    game: Arc<GameRecorder>,
     config: EngineConfig,
     evaluator: Evaluator,
     #[allow(dead_code)]
     move_generator: MoveGenerator,
     search_engine: SearchEngine,
     event_handler: Arc<Mutex<dyn EventHandler>>,
+    /// Worker engines for `analyze_batch`, created on first use
+    batch_searcher: Option<BatchSearcher>,
+    /// Results of earlier searches, consulted before searching
+    analysis_store: Option<AnalysisStore>,
     initialized: bool,
 }
 
 impl ChessEngine {
     pub fn new() -> Self {
-        ChessEngine {
-            game_state: GameState::new(),
-            config: EngineConfig::default(),
-            evaluator: Evaluator::new(),
-            move_generator: MoveGenerator::new(),
-            search_engine: SearchEngine::new(SearchConfig::default()),
-            event_handler: Arc::new(Mutex::new(DefaultEventHandler::new())),
-            initialized: false,
-        }
+        Self::with_config(EngineConfig::default())
+    }
+
+    /// Build the process-wide move generation and hashing tables up front, so that the
+    /// first engine created or search run does not pay for them. Calling it again is free.
+    pub fn prewarm() {
+        MoveGenerator::prewarm();
+        zobrist_keys();
+    }
+
+    /// Run the embedded perft and tactics suite, to check that the library was built
+    /// and linked correctly on this platform. Takes well under a second in release builds.
+    pub fn selftest() -> SelfTestReport {
+        selftest::run()
     }
 
     pub fn with_config(config: EngineConfig) -> Self {
+        chess_core::log_info!(
+            "engine created: depth {}, {} threads, {} MB hash",
+            config.depth,
+            config.thread_count,
+            config.hash_size_mb()
+        );
+        let mut search_engine = SearchEngine::new(config.search_config());
+        if search_engine.hash_size_mb() != config.hash_size_mb() {
+            search_engine.set_hash_size(config.hash_size_mb());
+        }
         ChessEngine {
-            game_state: GameState::new(),
+            game: Arc::new(GameRecorder::new(GameState::new())),
+            search_engine,
             config,
             evaluator: Evaluator::new(),
             move_generator: MoveGenerator::new(),
-            search_engine: SearchEngine::new(SearchConfig::default()),
             event_handler: Arc::new(Mutex::new(DefaultEventHandler::new())),
+            batch_searcher: None,
+            analysis_store: None,
             initialized: false,
         }
     }
@@ -71,12 +256,14 @@ impl ChessEngine {
     pub fn from_fen(fen: &str) -> Result<Self> {
         let game_state = GameState::from_fen(fen)?;
         Ok(ChessEngine {
-            game_state,
+            game: Arc::new(GameRecorder::new(game_state)),
             config: EngineConfig::default(),
             evaluator: Evaluator::new(),
             move_generator: MoveGenerator::new(),
-            search_engine: SearchEngine::new(SearchConfig::default()),
+            search_engine: SearchEngine::new(EngineConfig::default().search_config()),
             event_handler: Arc::new(Mutex::new(DefaultEventHandler::new())),
+            batch_searcher: None,
+            analysis_store: None,
             initialized: false,
         })
     }
@@ -111,31 +298,59 @@ impl ChessEngine {
                 "Cannot change config after initialization".to_string(),
             ));
         }
+        chess_core::log_debug!("engine reconfigured: {:?}", config);
+        self.search_engine.set_config(config.search_config());
+        if self.search_engine.hash_size_mb() != config.hash_size_mb() {
+            self.search_engine.set_hash_size(config.hash_size_mb());
+        }
         self.config = config;
         Ok(())
     }
 
     pub fn reset(&mut self) -> Result<()> {
-        self.game_state = GameState::new();
+        self.game = Arc::new(GameRecorder::new(GameState::new()));
         self.emit_event(GameEvent::GameStarted);
         Ok(())
     }
 
     pub fn load_fen(&mut self, fen: &str) -> Result<()> {
-        self.game_state = GameState::from_fen(fen)?;
+        self.game = Arc::new(GameRecorder::new(GameState::from_fen(fen)?));
+        Ok(())
+    }
+
+    /// Load a FEN played under `variant`; Horde positions must be loaded this way
+    pub fn load_fen_with_variant(&mut self, fen: &str, variant: Variant) -> Result<()> {
+        self.game = Arc::new(GameRecorder::new(GameState::from_fen_with_variant(
+            fen, variant,
+        )?));
         Ok(())
     }
 
+    /// Start a new game from the initial position of `variant`
+    pub fn reset_to_variant(&mut self, variant: Variant) -> Result<()> {
+        self.load_fen_with_variant(variant.starting_fen(), variant)?;
+        self.emit_event(GameEvent::GameStarted);
+        Ok(())
+    }
+
+    pub fn get_variant(&self) -> Variant {
+        self.game.state().position.variant
+    }
+
     pub fn get_fen(&self) -> String {
-        self.game_state.to_fen()
+        self.game.state().to_fen()
     }
 
     pub fn get_position(&self) -> &Position {
-        &self.game_state.position
+        &self.game.state().position
+    }
+
+    pub(crate) fn game_state(&self) -> &GameState {
+        self.game.state()
     }
 
     pub fn get_side_to_move(&self) -> Color {
-        self.game_state.position.side_to_move
+        self.game.state().position.side_to_move
     }
 
     pub fn make_move(&mut self, mv: Move) -> Result<MoveResult> {
@@ -150,17 +365,21 @@ impl ChessEngine {
                 success: false,
                 game_result: None,
                 events,
+                notation: None,
             });
         }
 
-        let captured_piece = self.game_state.position.piece_at(mv.to);
+        let san = notation::move_to_san(self.game.state(), mv)?;
+        let written = self.format_move(mv)?;
+
+        let captured_piece = self.game.state().position.piece_at(mv.to);
         let _is_check_before = self
-            .game_state
-            .is_in_check(self.game_state.position.side_to_move);
+            .game
+            .state()
+            .is_in_check(self.game.state().position.side_to_move);
 
-        self.game_state.make_move(mv)?;
+        self.game_mut().play(mv)?;
 
-        let san = self.move_to_san(mv);
         events.push(GameEvent::MoveMade {
             mv,
             san,
@@ -178,7 +397,7 @@ impl ChessEngine {
             if let Some(promotion_piece) = mv.promotion_piece() {
                 let promoted_piece = Piece::new(
                     promotion_piece,
-                    self.game_state.position.side_to_move.opposite(),
+                    self.game.state().position.side_to_move.opposite(),
                 );
                 events.push(GameEvent::Promotion {
                     piece: promoted_piece,
@@ -195,13 +414,13 @@ impl ChessEngine {
                 _ => crate::event::CastleSide::Queenside,
             };
             events.push(GameEvent::Castle {
-                color: self.game_state.position.side_to_move.opposite(),
+                color: self.game.state().position.side_to_move.opposite(),
                 side,
             });
         }
 
         if mv.is_en_passant() {
-            let captured_square = match self.game_state.position.side_to_move {
+            let captured_square = match self.game.state().position.side_to_move {
                 Color::White => Square::from_file_rank(mv.to.file(), mv.to.rank() + 1),
                 Color::Black => Square::from_file_rank(mv.to.file(), mv.to.rank() - 1),
             }
@@ -210,50 +429,38 @@ impl ChessEngine {
         }
 
         let is_check_after = self
-            .game_state
-            .is_in_check(self.game_state.position.side_to_move);
+            .game
+            .state()
+            .is_in_check(self.game.state().position.side_to_move);
         if is_check_after {
             events.push(GameEvent::Check {
-                color: self.game_state.position.side_to_move,
+                color: self.game.state().position.side_to_move,
             });
         }
 
-        let game_result = self.game_state.game_result();
+        let game_result = self.get_game_result();
         let mut final_game_result = None;
 
         match game_result {
-            GameResult::WhiteWins => {
-                events.push(GameEvent::Checkmate {
-                    winner: Color::White,
-                });
-                events.push(GameEvent::GameEnded {
-                    result: game_result,
-                });
-                final_game_result = Some(game_result);
-            }
-            GameResult::BlackWins => {
-                events.push(GameEvent::Checkmate {
-                    winner: Color::Black,
-                });
+            GameResult::WhiteWins | GameResult::BlackWins => {
+                // Variant wins such as a third check are not checkmates
+                if self.game.state().position.variant_winner().is_none() {
+                    let winner = if game_result == GameResult::WhiteWins {
+                        Color::White
+                    } else {
+                        Color::Black
+                    };
+                    events.push(GameEvent::Checkmate { winner });
+                }
                 events.push(GameEvent::GameEnded {
                     result: game_result,
                 });
                 final_game_result = Some(game_result);
             }
             GameResult::Draw => {
-                let draw_reason = if self.game_state.is_stalemate() {
-                    crate::event::DrawReason::Stalemate
-                } else if self.game_state.is_fifty_move_rule() {
-                    crate::event::DrawReason::FiftyMoveRule
-                } else if self.game_state.is_threefold_repetition() {
-                    crate::event::DrawReason::ThreefoldRepetition
-                } else if self.game_state.is_insufficient_material() {
-                    crate::event::DrawReason::InsufficientMaterial
-                } else {
-                    crate::event::DrawReason::Agreement
-                };
-
-                if draw_reason == crate::event::DrawReason::Stalemate {
+                let draw_reason = self.get_draw_reason().unwrap_or(DrawReason::Agreement);
+
+                if draw_reason == DrawReason::Stalemate {
                     events.push(GameEvent::Stalemate);
                 }
 
@@ -276,72 +483,520 @@ impl ChessEngine {
             success: true,
             game_result: final_game_result,
             events,
+            notation: Some(written),
         })
     }
 
     pub fn make_move_from_uci(&mut self, uci: &str) -> Result<MoveResult> {
-        let mv: Move = uci.parse()?;
+        let mv = self.resolve_uci_move(uci)?;
         self.make_move(mv)
     }
 
+    /// Match a UCI string against the legal moves of the current position; castling
+    /// as king-takes-rook is accepted only in `NotationStyle::UciChess960`
+    pub fn resolve_uci_move(&self, uci: &str) -> Result<Move> {
+        Ok(self
+            .game
+            .state()
+            .resolve_uci_move_in(uci, self.config.notation_style)?)
+    }
+
     pub fn make_move_from_san(&mut self, san: &str) -> Result<MoveResult> {
         let mv = self.parse_san_move(san)?;
         self.make_move(mv)
     }
 
     pub fn is_legal_move(&self, mv: Move) -> bool {
-        self.game_state.is_legal_move(mv)
+        self.game.state().is_legal_move(mv)
+    }
+
+    /// Take back the last move, restoring the clocks and repetition counts; returns
+    /// `None` at the start of the game
+    pub fn undo_move(&mut self) -> Option<Move> {
+        self.game_mut().undo()
+    }
+
+    /// Replay the last move taken back; returns `None` if there is none, and playing a
+    /// new move discards the moves that could be replayed
+    pub fn redo_move(&mut self) -> Result<Option<Move>> {
+        Ok(self.game_mut().redo()?)
+    }
+
+    /// Save the game (position, move list, undo and redo history) to return to with
+    /// `restore`, e.g. before exploring a variation. The game is shared until it changes.
+    pub fn snapshot(&self) -> EngineSnapshot {
+        EngineSnapshot {
+            game: Arc::clone(&self.game),
+        }
+    }
+
+    /// Return to the game saved in `snapshot`, which stays usable for later restores.
+    /// Search caches are kept: they are keyed by position, so they stay valid.
+    pub fn restore(&mut self, snapshot: &EngineSnapshot) {
+        self.game = Arc::clone(&snapshot.game);
+    }
+
+    fn game_mut(&mut self) -> &mut GameRecorder {
+        Arc::make_mut(&mut self.game)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.game.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.game.can_redo()
+    }
+
+    pub fn notation_style(&self) -> NotationStyle {
+        self.config.notation_style
+    }
+
+    /// Style used for the moves in later `MoveResult`s and `AnalysisResult`s
+    pub fn set_notation_style(&mut self, style: NotationStyle) {
+        self.config.notation_style = style;
+    }
+
+    /// A legal move of the current position written in the configured notation style
+    pub fn format_move(&self, mv: Move) -> Result<String> {
+        Ok(notation::format_move(
+            self.game.state(),
+            mv,
+            self.config.notation_style,
+        )?)
+    }
+
+    /// A legal move of the current position in standard algebraic notation
+    pub fn move_to_san(&self, mv: Move) -> Result<String> {
+        Ok(notation::move_to_san(self.game.state(), mv)?)
     }
 
     pub fn get_legal_moves(&self) -> Vec<Move> {
-        self.game_state.generate_legal_moves()
+        self.game.state().generate_legal_moves()
+    }
+
+    /// Legal moves of the side to move's piece on `square`, empty if it has none there
+    pub fn get_legal_moves_from(&self, square: Square) -> Vec<Move> {
+        self.game.state().legal_moves_from(square)
+    }
+
+    /// Legal moves of the side to move's pieces of `piece_type`, drops included
+    pub fn get_legal_moves_of(&self, piece_type: PieceType) -> Vec<Move> {
+        self.game.state().legal_moves_of(piece_type)
+    }
+
+    /// How often the current position has occurred in this game, itself included
+    pub fn get_repetition_count(&self) -> u32 {
+        self.game.state().repetition_count()
+    }
+
+    /// Plies since the last capture or pawn move
+    pub fn get_halfmove_clock(&self) -> u32 {
+        self.game.state().position.halfmove_clock
     }
 
+    /// Snapshot of the game. The mate and stalemate flags stop at the first legal move
+    /// found; use `is_in_check` or `is_checkmate` to poll a single flag.
     pub fn get_game_info(&self) -> GameInfo {
-        let legal_moves = self.get_legal_moves();
+        let state = self.game.state();
+        let is_check = self.is_in_check();
+        let has_legal_move = state.has_legal_move();
+        let is_stalemate = !is_check && !has_legal_move;
         GameInfo {
             fen: self.get_fen(),
             side_to_move: self.get_side_to_move(),
-            legal_moves: legal_moves.clone(),
-            is_check: self.game_state.is_in_check(self.get_side_to_move()),
-            is_checkmate: self.game_state.is_checkmate(),
-            is_stalemate: self.game_state.is_stalemate(),
-            is_draw: self.game_state.is_draw(),
-            halfmove_clock: self.game_state.halfmove_clock,
-            fullmove_number: self.game_state.fullmove_number,
-            position_count: self.game_state.position_history.len(),
+            is_check,
+            is_checkmate: is_check && !has_legal_move,
+            is_stalemate,
+            is_draw: is_stalemate || state.is_draw_by_rule() || self.game.draw_agreed(),
+            legal_moves: self.get_legal_moves(),
+            halfmove_clock: state.position.halfmove_clock,
+            fullmove_number: state.position.fullmove_number,
+            position_count: state.position_history.len(),
+            repetition_count: state.repetition_count(),
         }
     }
 
+    /// Static evaluation in centipawns from the side to move's point of view
     pub fn evaluate(&self) -> i32 {
-        self.evaluator.evaluate(&self.game_state)
+        self.evaluator.evaluate(self.game.state())
+    }
+
+    /// Static evaluation in centipawns from White's point of view
+    pub fn evaluate_absolute(&self) -> i32 {
+        match self.get_side_to_move() {
+            Color::White => self.evaluate(),
+            Color::Black => -self.evaluate(),
+        }
+    }
+
+    /// The terms of the static evaluation, from White's point of view
+    pub fn get_evaluation_breakdown(&self) -> EvalBreakdown {
+        self.evaluator.evaluate_breakdown(self.game.state())
+    }
+
+    /// Win/draw/loss chances for the side to move, from the static evaluation
+    pub fn evaluate_wdl(&self) -> Wdl {
+        Score::for_position(self.evaluate(), &self.game.state().position).wdl()
     }
 
-    pub fn find_best_move(&mut self) -> Result<Option<Move>> {
+    /// Search the current position, or report the result if the game is already over
+    /// by checkmate, stalemate, the fifty-move rule, threefold repetition or
+    /// insufficient material
+    pub fn find_best_move(&mut self) -> Result<BestMove> {
         if !self.initialized {
             return Err(EngineError::NotInitialized);
         }
 
+        let result = self.get_game_result();
+        if result.is_game_over() {
+            return Ok(BestMove::GameOver(result));
+        }
+
         let legal_moves = self.get_legal_moves();
-        if legal_moves.is_empty() {
-            return Ok(None);
+        let Some(&fallback) = legal_moves.first() else {
+            return Err(EngineError::InvalidState(
+                "Ongoing game without legal moves".to_string(),
+            ));
+        };
+        // A search stopped before finishing depth 1 has no move of its own
+        let result = self.search()?;
+        let best_move = match (self.config.move_temperature, result.best_move) {
+            (Some(temperature), Some(_)) if result.evaluation.abs() <= MATE_THRESHOLD => {
+                self.sample_move(temperature, &result).or(result.best_move)
+            }
+            _ => result.best_move,
+        };
+
+        if let (Some(blunder_mode), Some(best)) = (self.config.blunder_mode, result.best_move) {
+            if random_fraction() < blunder_mode.frequency {
+                if let Some((mistake, loss)) = self.pick_mistake(blunder_mode, &result) {
+                    self.emit_event(GameEvent::DeliberateMistake {
+                        mv: mistake,
+                        best_move: best,
+                        loss,
+                    });
+                    return Ok(BestMove::Move(mistake));
+                }
+            }
         }
+        Ok(BestMove::Move(best_move.unwrap_or(fallback)))
+    }
+
+    /// A random root move losing between `min_loss` and `max_loss` centipawns against
+    /// `result`, with its loss; `None` when no move loses that much and no more
+    fn pick_mistake(
+        &mut self,
+        blunder_mode: BlunderMode,
+        result: &SearchResult,
+    ) -> Option<(Move, i32)> {
+        let floor = result
+            .evaluation
+            .saturating_sub(blunder_mode.max_loss.max(0))
+            - 1;
+        let mistakes: Vec<(Move, i32)> = self
+            .search_engine
+            .score_root_moves(&self.game.state().position, result.depth, floor)
+            .into_iter()
+            .map(|(mv, score)| (mv, result.evaluation - score))
+            .filter(|&(mv, loss)| Some(mv) != result.best_move && loss >= blunder_mode.min_loss)
+            .collect();
+        let index = (random_fraction() * mistakes.len() as f64) as usize;
+        mistakes.get(index).copied()
+    }
 
-        // Use the search engine to find the best move
-        let search_result = self.search_engine.search(&self.game_state.position);
-        Ok(search_result.best_move)
+    /// A root move within the temperature window of `result`, chosen at random
+    fn sample_move(&mut self, temperature: MoveTemperature, result: &SearchResult) -> Option<Move> {
+        let floor = result.evaluation.saturating_sub(temperature.window.max(0)) - 1;
+        let candidates =
+            self.search_engine
+                .score_root_moves(&self.game.state().position, result.depth, floor);
+        temperature.pick(&candidates, random_fraction())
     }
 
+    /// Handle for polling the progress of a search from another thread
+    pub fn search_status_handle(&self) -> SearchStatusHandle {
+        self.search_engine.status_handle()
+    }
+
+    /// Flag another thread sets to end `search` early with the best result so far;
+    /// `None` lets searches run to their limits
+    pub fn set_stop_signal(&mut self, stop_signal: Option<Arc<AtomicBool>>) {
+        self.search_engine.set_stop_signal(stop_signal);
+    }
+
+    /// Tablebases used to filter root moves in endgames; `None` turns them off
+    pub fn set_tablebase(&mut self, tablebase: Option<Arc<dyn TablebaseProbe>>) {
+        self.search_engine.set_tablebase(tablebase);
+    }
+
+    /// Resize the search's transposition table, clearing it
+    pub fn set_hash_size(&mut self, size_mb: usize) {
+        self.search_engine.set_hash_size(size_mb);
+    }
+
+    /// Put `config` and a `hash_mb` table back on an engine whatever was changed since,
+    /// initialized or not, as an `EnginePool` does on checkin. The transposition table
+    /// keeps its entries unless its size has to change.
+    pub(crate) fn restore_config(&mut self, config: EngineConfig, hash_mb: usize) {
+        self.search_engine.set_config(config.search_config());
+        if self.search_engine.hash_size_mb() != hash_mb {
+            self.search_engine.set_hash_size(hash_mb);
+        }
+        self.config = config;
+    }
+
+    /// Transposition table size in megabytes
+    pub fn get_hash_size(&self) -> usize {
+        self.search_engine.hash_size_mb()
+    }
+
+    /// Worker threads of later `analyze_batch` calls; the main search runs on the
+    /// calling thread
+    pub fn set_thread_count(&mut self, threads: usize) {
+        self.config.thread_count = threads.max(1);
+    }
+
+    /// Change the search depth and time limit; `None` removes the time limit
+    pub fn set_search_limits(&mut self, depth: u8, time_limit_ms: Option<u64>) {
+        let limits = SearchLimits::default().with_depth(depth.clamp(1, MAX_DEPTH));
+        self.set_limits(match time_limit_ms {
+            Some(ms) => limits.with_movetime(Duration::from_millis(ms)),
+            None => limits,
+        });
+    }
+
+    /// Limits applied to every following search
+    pub fn limits(&self) -> SearchLimits {
+        *self.search_engine.limits()
+    }
+
+    /// Replace the limits of every following search, e.g. with a game clock or a mate bound
+    pub fn set_limits(&mut self, limits: SearchLimits) {
+        self.search_engine.set_limits(limits);
+    }
+
+    /// Run a full search on the current position, returning depth, score and node counts
+    pub fn search(&mut self) -> Result<SearchResult> {
+        if !self.initialized {
+            return Err(EngineError::NotInitialized);
+        }
+
+        let hash = self.game.state().position.zobrist_hash();
+        let depth = self.search_engine.limits().max_depth();
+        if let Some(result) = self.stored_result(hash, depth) {
+            chess_core::log_debug!("analysis store answered {:016x} to depth {}", hash, depth);
+            return Ok(result);
+        }
+
+        self.search_engine
+            .set_game_history(self.game.state().position_history.keys().copied());
+        let result = self.search_engine.search(&self.game.state().position);
+        chess_core::log_info!(
+            "searched to depth {} in {} ms: {} nodes, score {}",
+            result.depth,
+            result.elapsed_time.as_millis(),
+            result.nodes_searched,
+            result.uci_score()
+        );
+
+        if let (Some(store), Some(best_move)) = (self.analysis_store.as_mut(), result.best_move) {
+            store.insert(
+                hash,
+                StoredAnalysis::new(result.depth, result.evaluation, best_move),
+            );
+        }
+        Ok(result)
+    }
+
+    /// Search the current position as if `excluded` were not available, for "what if
+    /// this move could not be played" analysis. The result is not kept in the analysis
+    /// store, since it is not the position's real value.
+    pub fn search_excluding(&mut self, excluded: Move) -> Result<SearchResult> {
+        if !self.initialized {
+            return Err(EngineError::NotInitialized);
+        }
+        if !self.is_legal_move(excluded) {
+            return Err(ChessError::InvalidMove(format!("{} is not legal here", excluded)).into());
+        }
+
+        self.search_engine
+            .set_game_history(self.game.state().position_history.keys().copied());
+        Ok(self
+            .search_engine
+            .search_excluding(&self.game.state().position, excluded))
+    }
+
+    /// Search the position after `predicted`, the expected reply, while the opponent
+    /// thinks. The game is left as it was however the search ends, so the actual reply
+    /// is played with `make_move` afterwards and pondering can restart from there.
+    pub fn ponder(&mut self, predicted: Move) -> Result<SearchResult> {
+        if !self.initialized {
+            return Err(EngineError::NotInitialized);
+        }
+
+        let search_engine = &mut self.search_engine;
+        Ok(Arc::make_mut(&mut self.game).with_move(predicted, |state| {
+            search_engine.set_game_history(state.position_history.keys().copied());
+            search_engine.search(&state.position)
+        })?)
+    }
+
+    /// Use `store` for results of earlier searches, which are returned instead of
+    /// searching when at least as deep as requested. Returns the store it replaces.
+    pub fn set_analysis_store(&mut self, store: Option<AnalysisStore>) -> Option<AnalysisStore> {
+        std::mem::replace(&mut self.analysis_store, store)
+    }
+
+    pub fn analysis_store(&self) -> Option<&AnalysisStore> {
+        self.analysis_store.as_ref()
+    }
+
+    /// Write new results of the attached analysis store to disk
+    pub fn save_analysis_store(&mut self) -> Result<()> {
+        match self.analysis_store.as_mut() {
+            Some(store) => store.save(),
+            None => Ok(()),
+        }
+    }
+
+    fn stored_result(&mut self, hash: u64, depth: u8) -> Option<SearchResult> {
+        let stored = self.analysis_store.as_mut()?.get(hash)?;
+        if stored.depth < depth {
+            return None;
+        }
+        let best_move = stored.best_move_in(self.game.state())?;
+
+        let mut principal_variation = [None; 16];
+        principal_variation[0] = Some(best_move);
+        Some(SearchResult {
+            best_move: Some(best_move),
+            evaluation: stored.score,
+            depth: stored.depth,
+            seldepth: stored.depth,
+            nodes_searched: 0,
+            elapsed_time: Duration::ZERO,
+            principal_variation,
+            root_fail: None,
+            aspiration_researches: 0,
+        })
+    }
+
+    /// Search many independent positions to `depth`, spread over `thread_count` workers.
+    ///
+    /// Results come back in input order; a FEN that fails to parse gets a result with
+    /// `error` set. The game state of this engine is left untouched.
+    pub fn analyze_batch<S: AsRef<str>>(&mut self, fens: &[S], depth: u8) -> Vec<AnalysisResult> {
+        let mut search_config = self.search_engine.config().clone();
+        search_config.limits.depth = Some(depth.clamp(1, MAX_DEPTH));
+
+        let parallel_config = self.config.parallel_config();
+        let reusable = self.batch_searcher.as_ref().is_some_and(|searcher| {
+            searcher.thread_pool().current_num_threads() == parallel_config.num_threads
+        });
+        let searcher = if reusable {
+            let searcher = self.batch_searcher.as_mut().expect("checked above");
+            searcher.set_search_config(search_config);
+            searcher
+        } else {
+            self.batch_searcher
+                .insert(BatchSearcher::new(parallel_config, search_config))
+        };
+
+        let style = self.config.notation_style;
+        let parsed: Vec<std::result::Result<Position, String>> = fens
+            .iter()
+            .map(|fen| Position::from_fen(fen.as_ref()).map_err(|e| e.to_string()))
+            .collect();
+        let positions: Vec<Position> = parsed
+            .iter()
+            .filter_map(|position| position.as_ref().ok().cloned())
+            .collect();
+        let mut searched = searcher.search_all(&positions).into_iter();
+
+        fens.iter()
+            .zip(parsed)
+            .map(|(fen, position)| {
+                let fen = fen.as_ref().to_string();
+                match position {
+                    Ok(position) => {
+                        let result = searched.next().expect("one result per position");
+                        let best_move_notation = result.best_move.and_then(|mv| {
+                            let game_state = GameState::from_fen(&position.to_fen()).ok()?;
+                            notation::format_move(&game_state, mv, style).ok()
+                        });
+                        AnalysisResult {
+                            fen,
+                            best_move: result.best_move,
+                            best_move_notation,
+                            evaluation: result.evaluation,
+                            depth: result.depth,
+                            nodes: result.nodes_searched,
+                            error: None,
+                        }
+                    }
+                    Err(error) => AnalysisResult {
+                        fen,
+                        best_move: None,
+                        best_move_notation: None,
+                        evaluation: 0,
+                        depth: 0,
+                        nodes: 0,
+                        error: Some(error),
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Result of the game, counting repetitions over the whole move history and a
+    /// draw agreed with `agree_draw`
     pub fn get_game_result(&self) -> GameResult {
-        self.game_state.game_result()
+        if self.game.draw_agreed() {
+            return GameResult::Draw;
+        }
+        self.game.state().game_result()
+    }
+
+    /// Why the game is drawn, or `None` if it is won or still going
+    pub fn get_draw_reason(&self) -> Option<DrawReason> {
+        if self.game.draw_agreed() {
+            return Some(DrawReason::Agreement);
+        }
+        self.game.state().draw_reason()
+    }
+
+    /// End the game as a draw by agreement. Taking back a move played before the
+    /// agreement withdraws it.
+    pub fn agree_draw(&mut self) -> Result<()> {
+        if self.is_game_over() {
+            return Err(EngineError::InvalidState(
+                "the game is already over".to_string(),
+            ));
+        }
+        self.game_mut().agree_draw();
+        self.emit_event(GameEvent::Draw {
+            reason: DrawReason::Agreement,
+        });
+        self.emit_event(GameEvent::GameEnded {
+            result: GameResult::Draw,
+        });
+        Ok(())
     }
 
     pub fn is_game_over(&self) -> bool {
         self.get_game_result().is_game_over()
     }
 
-    fn move_to_san(&self, mv: Move) -> String {
-        format!("{}", mv)
+    /// Whether the side to move is in check, without generating any moves
+    pub fn is_in_check(&self) -> bool {
+        let state = self.game.state();
+        state.is_in_check(state.position.side_to_move)
+    }
+
+    /// Whether the side to move is checkmated, stopping at the first legal move found
+    pub fn is_checkmate(&self) -> bool {
+        self.game.state().is_checkmate()
     }
 
     fn parse_san_move(&self, _san: &str) -> Result<Move> {
@@ -374,6 +1029,155 @@ mod tests {
         assert_eq!(engine.get_side_to_move(), Color::White);
     }
 
+    #[test]
+    fn test_undo_during_ponder_keeps_game_in_sync() {
+        let mut engine = ChessEngine::new();
+        engine.initialize().unwrap();
+        engine.set_search_limits(2, None);
+        engine.make_move_from_uci("e2e4").unwrap();
+        engine.make_move_from_uci("e7e5").unwrap();
+        let fen = engine.get_fen();
+
+        let predicted = engine.resolve_uci_move("g1f3").unwrap();
+        assert!(engine.ponder(predicted).unwrap().best_move.is_some());
+        assert_eq!(engine.get_fen(), fen);
+        assert_eq!(engine.game_state().move_history.len(), 2);
+
+        // The opponent takes back instead of replying; pondering restarts from there
+        engine.undo_move().unwrap();
+        let info = engine.get_game_info();
+        assert_eq!(info.position_count, 2);
+        assert_eq!(info.fullmove_number, 1);
+        let predicted = engine.resolve_uci_move("c7c5").unwrap();
+        engine.ponder(predicted).unwrap();
+        assert!(engine.can_redo());
+
+        engine.make_move_from_uci("c7c5").unwrap();
+        assert!(!engine.can_redo());
+        assert_eq!(engine.get_game_info().position_count, 3);
+    }
+
+    #[test]
+    fn test_restore_snapshot_after_exploring() {
+        let mut engine = ChessEngine::new();
+        engine.initialize().unwrap();
+        engine.make_move_from_uci("e2e4").unwrap();
+        let snapshot = engine.snapshot();
+        assert_eq!(snapshot.fen(), engine.get_fen());
+
+        for line in [["e7e5", "g1f3"], ["c7c5", "d2d4"]] {
+            for uci in line {
+                engine.make_move_from_uci(uci).unwrap();
+            }
+            assert_eq!(engine.game_state().move_history.len(), 3);
+            engine.restore(&snapshot);
+            assert_eq!(engine.get_fen(), snapshot.fen());
+        }
+
+        // The restored game keeps its history, and changing it leaves the snapshot alone
+        assert_eq!(
+            engine.undo_move(),
+            snapshot.game.state().move_history.first().copied()
+        );
+        assert!(engine.can_redo());
+        assert_eq!(snapshot.game.state().move_history.len(), 1);
+        assert!(!snapshot.game.can_redo());
+    }
+
+    #[test]
+    fn test_repetition_claims_after_takeback() {
+        let mut engine = ChessEngine::new();
+        engine.initialize().unwrap();
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8"].repeat(2) {
+            engine.make_move_from_uci(uci).unwrap();
+        }
+        assert!(engine.is_game_over());
+        assert_eq!(engine.get_repetition_count(), 3);
+
+        // Taking the repeating move back withdraws the claim
+        engine.undo_move().unwrap();
+        assert!(!engine.is_game_over());
+        assert_eq!(engine.get_repetition_count(), 2);
+        engine.set_search_limits(1, None);
+        assert!(matches!(
+            engine.find_best_move().unwrap(),
+            BestMove::Move(_)
+        ));
+
+        engine.redo_move().unwrap().unwrap();
+        assert!(engine.is_game_over());
+
+        engine.undo_move().unwrap();
+        engine.undo_move().unwrap();
+        engine.make_move_from_uci("b1c3").unwrap();
+        assert_eq!(engine.get_repetition_count(), 1);
+        assert_eq!(engine.redo_move().unwrap(), None);
+    }
+
+    #[test]
+    fn test_search_excluding_leaves_store_alone() {
+        let mut engine = ChessEngine::from_fen("4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1").unwrap();
+        engine.initialize().unwrap();
+        engine.set_search_limits(3, None);
+        let path = std::env::temp_dir().join(format!("excluding_store_{}.bin", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        engine.set_analysis_store(Some(AnalysisStore::open(&path, 64).unwrap()));
+
+        let capture = engine.resolve_uci_move("d2d5").unwrap();
+        let without = engine.search_excluding(capture).unwrap();
+        assert_ne!(without.best_move, Some(capture));
+        assert!(engine.analysis_store().unwrap().is_empty());
+        assert_eq!(engine.search().unwrap().best_move, Some(capture));
+
+        let illegal = Move::normal(Square::E1, Square::E2);
+        assert!(engine.search_excluding(illegal).is_err());
+    }
+
+    #[test]
+    fn test_draw_reasons() {
+        let mut engine = ChessEngine::new();
+        engine.initialize().unwrap();
+        for uci in ["g1f3", "g8f6", "f3g1"] {
+            engine.make_move_from_uci(uci).unwrap();
+        }
+        let result = engine.make_move_from_uci("f6g8").unwrap();
+        assert_eq!(result.game_result, None);
+        assert_eq!(engine.get_draw_reason(), None);
+
+        for uci in ["g1f3", "g8f6", "f3g1"] {
+            engine.make_move_from_uci(uci).unwrap();
+        }
+        let result = engine.make_move_from_uci("f6g8").unwrap();
+        assert_eq!(result.game_result, Some(GameResult::Draw));
+        assert!(result.events.contains(&GameEvent::Draw {
+            reason: DrawReason::ThreefoldRepetition
+        }));
+        assert_eq!(
+            engine.get_draw_reason(),
+            Some(DrawReason::ThreefoldRepetition)
+        );
+        assert!(engine.agree_draw().is_err());
+
+        engine.undo_move().unwrap();
+        engine.agree_draw().unwrap();
+        assert_eq!(engine.get_game_result(), GameResult::Draw);
+        assert_eq!(engine.get_draw_reason(), Some(DrawReason::Agreement));
+
+        // Taking back a move played before the agreement withdraws it
+        engine.undo_move().unwrap();
+        assert_eq!(engine.get_game_result(), GameResult::Ongoing);
+        assert_eq!(engine.get_draw_reason(), None);
+    }
+
+    #[test]
+    fn test_prewarm_before_creation() {
+        ChessEngine::prewarm();
+        ChessEngine::prewarm();
+        let mut engine = ChessEngine::new();
+        engine.initialize().unwrap();
+        assert_eq!(engine.get_legal_moves().len(), 20);
+    }
+
     #[test]
     fn test_engine_initialization() {
         let mut engine = ChessEngine::new();
@@ -400,6 +1204,19 @@ mod tests {
         assert_eq!(engine.get_side_to_move(), Color::Black);
     }
 
+    #[test]
+    fn test_make_capture_from_uci() {
+        let mut engine = ChessEngine::new();
+        engine.initialize().unwrap();
+
+        engine.make_move_from_uci("e2e4").unwrap();
+        engine.make_move_from_uci("d7d5").unwrap();
+        let result = engine.make_move_from_uci("e4d5").unwrap();
+
+        assert!(result.success);
+        assert!(engine.make_move_from_uci("e2e4").is_err());
+    }
+
     #[test]
     fn test_legal_moves() {
         let engine = ChessEngine::new();
@@ -420,6 +1237,41 @@ mod tests {
         assert!(!info.is_draw);
     }
 
+    #[test]
+    fn test_king_takes_rook_needs_chess960_notation() {
+        let mut engine = ChessEngine::new();
+        engine.initialize().unwrap();
+        engine
+            .load_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1")
+            .unwrap();
+        assert!(engine.resolve_uci_move("e1h1").is_err());
+
+        engine.set_notation_style(NotationStyle::UciChess960);
+        let result = engine.make_move_from_uci("e1h1").unwrap();
+        assert_eq!(result.notation.as_deref(), Some("e1h1"));
+        assert_eq!(engine.get_fen(), "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1");
+    }
+
+    #[test]
+    fn test_check_flags_match_game_info() {
+        let mut engine = ChessEngine::new();
+        assert!(!engine.is_in_check() && !engine.is_checkmate());
+
+        // Fool's mate
+        engine
+            .load_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+            .unwrap();
+        let info = engine.get_game_info();
+        assert!(engine.is_in_check() && info.is_check);
+        assert!(engine.is_checkmate() && info.is_checkmate);
+        assert!(info.legal_moves.is_empty() && !info.is_stalemate);
+
+        engine.load_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        let info = engine.get_game_info();
+        assert!(!engine.is_in_check() && !engine.is_checkmate());
+        assert!(info.is_stalemate && info.is_draw);
+    }
+
     #[test]
     fn test_evaluation() {
         let engine = ChessEngine::new();
@@ -432,4 +1284,263 @@ mod tests {
             score
         );
     }
+
+    #[test]
+    fn test_analyze_batch() {
+        let mut engine = crate::ChessEngineBuilder::new()
+            .with_threads(2)
+            .build()
+            .unwrap();
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "not a fen",
+            "7k/8/8/8/8/8/6q1/7K w - - 0 1",
+        ];
+        let before = engine.get_fen();
+        let results = engine.analyze_batch(&fens, 2);
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].fen, fens[0]);
+        assert!(results[0].best_move.is_some() && results[0].error.is_none());
+        assert!(results[1].error.is_some() && results[1].best_move.is_none());
+        assert_eq!(
+            results[2].best_move.map(|mv| mv.to_string()).as_deref(),
+            Some("h1g2")
+        );
+        assert_eq!(engine.get_fen(), before);
+
+        // The worker pool is kept for later batches
+        assert_eq!(engine.analyze_batch(&fens[..1], 1)[0].depth, 1);
+    }
+
+    #[test]
+    fn test_evaluate_wdl_follows_side_to_move() {
+        let mut engine = ChessEngine::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        assert!(engine.evaluate_wdl().win > 900);
+
+        engine.load_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap();
+        assert!(engine.evaluate_wdl().loss > 900);
+    }
+
+    #[test]
+    fn test_analysis_store_is_shared_across_engines() {
+        let path = std::env::temp_dir().join(format!("engine_store_{}.bin", std::process::id()));
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+
+        let mut first = ChessEngine::from_fen(fen).unwrap();
+        first.initialize().unwrap();
+        first.set_search_limits(3, None);
+        first.set_analysis_store(Some(AnalysisStore::open(&path, 64).unwrap()));
+        let searched = first.search().unwrap();
+        assert!(searched.nodes_searched > 0);
+        first.save_analysis_store().unwrap();
+
+        let mut second = ChessEngine::from_fen(fen).unwrap();
+        second.initialize().unwrap();
+        second.set_search_limits(3, None);
+        second.set_analysis_store(Some(AnalysisStore::open(&path, 64).unwrap()));
+        let cached = second.search().unwrap();
+        assert_eq!(cached.nodes_searched, 0);
+        assert_eq!(cached.best_move, searched.best_move);
+        assert_eq!(cached.evaluation, searched.evaluation);
+
+        // A deeper request than the stored one searches again
+        second.set_search_limits(4, None);
+        assert!(second.search().unwrap().nodes_searched > 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_find_best_move_reports_finished_games() {
+        let finished = [
+            // Checkmate, stalemate and the fifty-move rule
+            ("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1", GameResult::WhiteWins),
+            ("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", GameResult::Draw),
+            ("4k3/8/8/8/8/8/4P3/4K3 w - - 100 80", GameResult::Draw),
+        ];
+        for (fen, result) in finished {
+            let mut engine = ChessEngine::from_fen(fen).unwrap();
+            engine.initialize().unwrap();
+            assert_eq!(
+                engine.find_best_move().unwrap(),
+                BestMove::GameOver(result),
+                "{fen}"
+            );
+        }
+
+        // Threefold repetition by shuffling knights
+        let mut engine = ChessEngine::new();
+        engine.initialize().unwrap();
+        engine.set_search_limits(2, None);
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8"].repeat(3) {
+            engine.make_move_from_uci(uci).unwrap();
+        }
+        assert_eq!(
+            engine.find_best_move().unwrap(),
+            BestMove::GameOver(GameResult::Draw)
+        );
+
+        let mut fresh = ChessEngine::new();
+        assert!(matches!(
+            fresh.find_best_move(),
+            Err(EngineError::NotInitialized)
+        ));
+        fresh.initialize().unwrap();
+        fresh.set_search_limits(2, None);
+        assert!(fresh.find_best_move().unwrap().into_move().is_some());
+    }
+
+    #[test]
+    fn test_temperature_pick_weights_by_score() {
+        let e4 = Move::normal(Square::E2, Square::E4);
+        let d4 = Move::normal(Square::D2, Square::D4);
+        let temperature = MoveTemperature {
+            temperature: 10.0,
+            window: 50,
+        };
+        let candidates = [(e4, 30), (d4, 20)];
+        // e4 carries e times d4's weight, about 73% of the total
+        assert_eq!(temperature.pick(&candidates, 0.0), Some(e4));
+        assert_eq!(temperature.pick(&candidates, 0.7), Some(e4));
+        assert_eq!(temperature.pick(&candidates, 0.75), Some(d4));
+        assert_eq!(temperature.pick(&[], 0.5), None);
+    }
+
+    #[test]
+    fn test_move_temperature_varies_play() {
+        let config = EngineConfig {
+            depth: 2,
+            move_temperature: Some(MoveTemperature {
+                temperature: 50.0,
+                window: 100,
+            }),
+            ..EngineConfig::default()
+        };
+        let mut engine = ChessEngine::with_config(config.clone());
+        engine.initialize().unwrap();
+        let moves: std::collections::HashSet<_> = (0..20)
+            .map(|_| engine.find_best_move().unwrap().into_move().unwrap())
+            .collect();
+        assert!(moves.len() > 1);
+
+        // Nothing else comes close to taking the hanging queen
+        let mut engine = ChessEngine::from_fen("4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1").unwrap();
+        engine.set_config(config).unwrap();
+        engine.initialize().unwrap();
+        let capture = engine.resolve_uci_move("d2d5").unwrap();
+        for _ in 0..5 {
+            assert_eq!(engine.find_best_move().unwrap(), BestMove::Move(capture));
+        }
+    }
+
+    #[test]
+    fn test_blunder_mode_reports_mistakes() {
+        let mut engine = ChessEngine::with_config(EngineConfig {
+            depth: 2,
+            blunder_mode: Some(BlunderMode {
+                frequency: 1.0,
+                min_loss: 50,
+                max_loss: 400,
+            }),
+            ..EngineConfig::default()
+        });
+        let events = Arc::new(Mutex::new(DefaultEventHandler::new()));
+        engine.set_event_handler(events.clone());
+        engine.initialize().unwrap();
+
+        let played = engine.find_best_move().unwrap().into_move().unwrap();
+        let events = events.lock().unwrap();
+        let Some(GameEvent::DeliberateMistake {
+            mv,
+            best_move,
+            loss,
+        }) = events.get_events().last()
+        else {
+            panic!("no mistake reported: {:?}", events.get_events());
+        };
+        assert_eq!(*mv, played);
+        assert_ne!(*best_move, played);
+        assert!((50..=400).contains(loss), "lost {loss}");
+    }
+
+    #[test]
+    fn test_limits_follow_config_and_mate_bound() {
+        let engine = ChessEngine::with_config(EngineConfig {
+            depth: 4,
+            time_limit_ms: Some(250),
+            ..EngineConfig::default()
+        });
+        assert_eq!(engine.limits().depth, Some(4));
+        assert_eq!(engine.limits().movetime, Some(Duration::from_millis(250)));
+
+        // Mate in one searches a single ply
+        let mut engine = ChessEngine::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        engine.initialize().unwrap();
+        engine.set_limits(SearchLimits::default().with_mate(1));
+        let result = engine.search().unwrap();
+        assert_eq!(result.depth, 1);
+        assert_eq!(
+            result.best_move.map(|mv| mv.to_uci()),
+            Some("a1a8".to_string())
+        );
+    }
+
+    #[test]
+    fn test_move_results_use_notation_style() {
+        let mut engine = ChessEngine::new();
+        engine.initialize().unwrap();
+        let result = engine.make_move_from_uci("g1f3").unwrap();
+        assert_eq!(result.notation.as_deref(), Some("g1f3"));
+        assert!(result.events.iter().any(
+            |event| matches!(event, GameEvent::MoveMade { san, .. } if san.as_str() == "Nf3")
+        ));
+
+        engine.set_notation_style(NotationStyle::Figurine);
+        assert_eq!(
+            engine
+                .make_move_from_uci("e7e5")
+                .unwrap()
+                .notation
+                .as_deref(),
+            Some("e5")
+        );
+        assert_eq!(
+            engine
+                .make_move_from_uci("b1c3")
+                .unwrap()
+                .notation
+                .as_deref(),
+            Some("♘c3")
+        );
+
+        engine.set_notation_style(NotationStyle::San);
+        let analysis = engine.analyze_batch(&["6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1"], 2);
+        assert_eq!(analysis[0].best_move_notation.as_deref(), Some("Ra8#"));
+    }
+
+    #[test]
+    fn test_variant_games() {
+        let mut engine = ChessEngine::new();
+        engine.initialize().unwrap();
+        engine.reset_to_variant(Variant::Horde).unwrap();
+        assert_eq!(engine.get_variant(), Variant::Horde);
+        assert_eq!(engine.get_fen(), Variant::Horde.starting_fen());
+        engine.set_search_limits(2, None);
+        assert!(matches!(
+            engine.find_best_move().unwrap(),
+            BestMove::Move(_)
+        ));
+
+        // A third check ends the game without a checkmate event
+        engine
+            .load_fen("4k3/8/8/8/8/8/8/R3K3 w - - 1+3 0 1")
+            .unwrap();
+        assert_eq!(engine.get_variant(), Variant::ThreeCheck);
+        let result = engine.make_move_from_uci("a1a8").unwrap();
+        assert_eq!(result.game_result, Some(GameResult::WhiteWins));
+        assert!(!result
+            .events
+            .iter()
+            .any(|event| matches!(event, GameEvent::Checkmate { .. })));
+    }
 }
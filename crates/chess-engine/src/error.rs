@@ -6,6 +6,7 @@ pub enum EngineError {
     ChessError(ChessError),
     ConfigurationError(String),
     InvalidState(String),
+    IoError(String),
     NotInitialized,
 }
 
@@ -15,6 +16,7 @@ impl fmt::Display for EngineError {
             EngineError::ChessError(err) => write!(f, "Chess error: {}", err),
             EngineError::ConfigurationError(msg) => write!(f, "Configuration error: {}", msg),
             EngineError::InvalidState(msg) => write!(f, "Invalid state: {}", msg),
+            EngineError::IoError(msg) => write!(f, "I/O error: {}", msg),
             EngineError::NotInitialized => write!(f, "Engine not initialized"),
         }
     }
@@ -28,4 +30,10 @@ impl From<ChessError> for EngineError {
     }
 }
 
+impl From<std::io::Error> for EngineError {
+    fn from(err: std::io::Error) -> Self {
+        EngineError::IoError(err.to_string())
+    }
+}
+
 pub type Result<T> = std::result::Result<T, EngineError>;
@@ -1,27 +1,49 @@
 use crate::{Color, GameResult, Move, Piece, Square};
+pub use chess_core::DrawReason;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum GameEvent {
     GameStarted,
-    MoveMade { mv: Move, san: String, fen: String },
-    PieceCaptured { piece: Piece, square: Square },
-    Check { color: Color },
-    Checkmate { winner: Color },
+    MoveMade {
+        mv: Move,
+        san: String,
+        fen: String,
+    },
+    PieceCaptured {
+        piece: Piece,
+        square: Square,
+    },
+    Check {
+        color: Color,
+    },
+    Checkmate {
+        winner: Color,
+    },
     Stalemate,
-    Draw { reason: DrawReason },
-    Promotion { piece: Piece, square: Square },
-    Castle { color: Color, side: CastleSide },
-    EnPassant { captured_square: Square },
-    GameEnded { result: GameResult },
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum DrawReason {
-    Stalemate,
-    FiftyMoveRule,
-    ThreefoldRepetition,
-    InsufficientMaterial,
-    Agreement,
+    Draw {
+        reason: DrawReason,
+    },
+    Promotion {
+        piece: Piece,
+        square: Square,
+    },
+    Castle {
+        color: Color,
+        side: CastleSide,
+    },
+    EnPassant {
+        captured_square: Square,
+    },
+    GameEnded {
+        result: GameResult,
+    },
+    /// `find_best_move` chose `mv` on purpose in blunder mode, giving away `loss`
+    /// centipawns against `best_move`
+    DeliberateMistake {
+        mv: Move,
+        best_move: Move,
+        loss: i32,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -0,0 +1,504 @@
+// Perpetual analysis of subscribed positions
+//
+// A broadcast shows many boards at once, and each should carry an evaluation that keeps
+// improving while nobody moves. Hosts subscribe the boards' positions with a priority;
+// worker threads, each holding an engine from an `EnginePool`, repeatedly take the board
+// that has had the least search time for its priority and deepen it by one ply. A
+// board with twice the priority gets about twice the CPU. Every worker keeps its
+// engine, and so its transposition table, across boards, so a position that comes back
+// after a move on the board starts from what was already searched. The tables are not
+// shared: a position benefits only from what its current worker searched before.
+// Moving on a board stops the search of its old position at once.
+
+use crate::{ChessEngine, EngineConfig, EngineError, EnginePool, EnginePoolConfig, Result};
+use crate::{PooledEngine, SearchLimits, SearchResult};
+use chess_core::GameState;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Handle of a subscribed position
+pub type SubscriptionId = u64;
+
+/// Workers and resources of a `Kibitzer`
+#[derive(Debug, Clone)]
+pub struct KibitzerConfig {
+    /// Boards searched at the same time, one engine each
+    pub workers: usize,
+    /// Transposition table size of each worker's engine in megabytes; every worker has
+    /// its own table, so the kibitzer uses `workers * hash_mb` in total
+    pub hash_mb: usize,
+    /// Boards are not deepened past this depth until their position changes
+    pub max_depth: u8,
+    /// Settings of the workers' engines
+    pub engine_config: EngineConfig,
+}
+
+impl Default for KibitzerConfig {
+    fn default() -> Self {
+        KibitzerConfig {
+            workers: 2,
+            hash_mb: 64,
+            max_depth: 30,
+            engine_config: EngineConfig::default(),
+        }
+    }
+}
+
+/// Latest analysis of a subscribed position
+#[derive(Debug, Clone)]
+pub struct BoardAnalysis {
+    pub fen: String,
+    pub priority: u32,
+    /// Deepest completed search, `None` until the first one finishes
+    pub result: Option<SearchResult>,
+    /// Search time spent on this position
+    pub time_spent: Duration,
+    /// Whether the position will not be deepened further: it reached the depth cap,
+    /// the game is over there, or its search failed
+    pub finished: bool,
+}
+
+struct Board {
+    fen: String,
+    priority: u32,
+    result: Option<SearchResult>,
+    depth: u8,
+    time_spent: Duration,
+    finished: bool,
+    /// A worker is searching this board
+    busy: bool,
+    /// Bumped when the position changes, so a search of the old one is discarded
+    generation: u64,
+    /// Ends the search of this position when it is replaced or unsubscribed
+    stop: Arc<AtomicBool>,
+}
+
+impl Board {
+    fn new(fen: String, priority: u32) -> Self {
+        Board {
+            fen,
+            priority,
+            result: None,
+            depth: 0,
+            time_spent: Duration::ZERO,
+            finished: false,
+            busy: false,
+            generation: 0,
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn analysis(&self) -> BoardAnalysis {
+        BoardAnalysis {
+            fen: self.fen.clone(),
+            priority: self.priority,
+            result: self.result,
+            time_spent: self.time_spent,
+            finished: self.finished,
+        }
+    }
+
+    /// Search time per unit of priority; the board with the least is searched next
+    fn share_used(&self) -> f64 {
+        self.time_spent.as_secs_f64() / f64::from(self.priority)
+    }
+
+    /// Stop a worker still searching this position
+    fn abandon(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// A board handed to a worker
+struct Slice {
+    id: SubscriptionId,
+    fen: String,
+    depth: u8,
+    generation: u64,
+    stop: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+struct KibitzerState {
+    boards: HashMap<SubscriptionId, Board>,
+    next_id: SubscriptionId,
+    stopping: bool,
+}
+
+impl KibitzerState {
+    fn next_slice(&mut self) -> Option<Slice> {
+        let (&id, board) = self
+            .boards
+            .iter_mut()
+            .filter(|(_, board)| !board.busy && !board.finished)
+            .min_by(|(a_id, a), (b_id, b)| {
+                a.share_used()
+                    .total_cmp(&b.share_used())
+                    .then(a.depth.cmp(&b.depth))
+                    .then(a_id.cmp(b_id))
+            })?;
+        board.busy = true;
+        Some(Slice {
+            id,
+            fen: board.fen.clone(),
+            depth: board.depth + 1,
+            generation: board.generation,
+            stop: Arc::clone(&board.stop),
+        })
+    }
+}
+
+struct KibitzerShared {
+    max_depth: u8,
+    state: Mutex<KibitzerState>,
+    /// Signalled when there is a board to search or the kibitzer stops
+    work: Condvar,
+    /// Signalled when a search finishes
+    analysed: Condvar,
+}
+
+impl KibitzerShared {
+    fn state(&self) -> MutexGuard<'_, KibitzerState> {
+        // Boards stay consistent even if a holder of the lock panicked
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Continuously deepening analysis of subscribed positions, shared out among worker
+/// threads by priority. Dropping it stops the workers and their searches.
+pub struct Kibitzer {
+    shared: Arc<KibitzerShared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl Kibitzer {
+    pub fn new(config: KibitzerConfig) -> Result<Self> {
+        if config.max_depth == 0 {
+            return Err(EngineError::ConfigurationError(
+                "kibitzer max_depth must be at least 1".to_string(),
+            ));
+        }
+        let pool = EnginePool::new(EnginePoolConfig {
+            engines: config.workers,
+            hash_mb: config.hash_mb,
+            threads_per_engine: 1,
+            engine_config: config.engine_config,
+        })?;
+
+        let shared = Arc::new(KibitzerShared {
+            max_depth: config.max_depth,
+            state: Mutex::new(KibitzerState::default()),
+            work: Condvar::new(),
+            analysed: Condvar::new(),
+        });
+        let workers = (0..config.workers)
+            .map(|_| {
+                let engine = pool.checkout();
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || run_worker(engine, &shared))
+            })
+            .collect();
+
+        Ok(Kibitzer { shared, workers })
+    }
+
+    /// Start analysing `fen`; a board with a higher `priority` gets more search time
+    pub fn subscribe(&self, fen: &str, priority: u32) -> Result<SubscriptionId> {
+        let priority = Self::check(fen, priority)?;
+        let mut state = self.shared.state();
+        let id = state.next_id;
+        state.next_id += 1;
+        state
+            .boards
+            .insert(id, Board::new(fen.to_string(), priority));
+        drop(state);
+
+        self.shared.work.notify_one();
+        Ok(id)
+    }
+
+    /// Replace the position of a subscription, e.g. after a move on the board; a search
+    /// of the old position is stopped and analysis starts again from depth 1
+    pub fn update(&self, id: SubscriptionId, fen: &str) -> Result<()> {
+        Self::check(fen, 1)?;
+        let mut state = self.shared.state();
+        let board = state.boards.get_mut(&id).ok_or_else(|| Self::unknown(id))?;
+        board.abandon();
+        let generation = board.generation + 1;
+        let busy = board.busy;
+        *board = Board {
+            generation,
+            busy,
+            ..Board::new(fen.to_string(), board.priority)
+        };
+        drop(state);
+
+        self.shared.work.notify_one();
+        Ok(())
+    }
+
+    pub fn set_priority(&self, id: SubscriptionId, priority: u32) -> Result<()> {
+        if priority == 0 {
+            return Err(Self::zero_priority());
+        }
+        let mut state = self.shared.state();
+        let board = state.boards.get_mut(&id).ok_or_else(|| Self::unknown(id))?;
+        board.priority = priority;
+        Ok(())
+    }
+
+    /// Stop analysing a subscription, returning its last analysis
+    pub fn unsubscribe(&self, id: SubscriptionId) -> Option<BoardAnalysis> {
+        let board = self.shared.state().boards.remove(&id)?;
+        board.abandon();
+        Some(board.analysis())
+    }
+
+    pub fn analysis(&self, id: SubscriptionId) -> Option<BoardAnalysis> {
+        self.shared.state().boards.get(&id).map(Board::analysis)
+    }
+
+    /// Subscriptions in the order they were made
+    pub fn subscriptions(&self) -> Vec<SubscriptionId> {
+        let mut ids: Vec<_> = self.shared.state().boards.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Wait until the subscription has been searched to `depth` or will not be deepened
+    /// further, returning its analysis; `None` on timeout or when it is not subscribed
+    pub fn wait_for_depth(
+        &self,
+        id: SubscriptionId,
+        depth: u8,
+        timeout: Duration,
+    ) -> Option<BoardAnalysis> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.state();
+        loop {
+            let board = state.boards.get(&id)?;
+            if board.finished || board.depth >= depth {
+                return Some(board.analysis());
+            }
+            let remaining = deadline.checked_duration_since(Instant::now())?;
+            state = self
+                .shared
+                .analysed
+                .wait_timeout(state, remaining)
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .0;
+        }
+    }
+
+    fn check(fen: &str, priority: u32) -> Result<u32> {
+        GameState::from_fen(fen)?;
+        if priority == 0 {
+            return Err(Self::zero_priority());
+        }
+        Ok(priority)
+    }
+
+    fn unknown(id: SubscriptionId) -> EngineError {
+        EngineError::InvalidState(format!("no kibitzer subscription {}", id))
+    }
+
+    fn zero_priority() -> EngineError {
+        EngineError::ConfigurationError("kibitzer priority must be at least 1".to_string())
+    }
+}
+
+impl Drop for Kibitzer {
+    fn drop(&mut self) {
+        let mut state = self.shared.state();
+        state.stopping = true;
+        state.boards.values().for_each(Board::abandon);
+        drop(state);
+        self.shared.work.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn run_worker(mut engine: PooledEngine, shared: &KibitzerShared) {
+    loop {
+        let slice = {
+            let mut state = shared.state();
+            loop {
+                if state.stopping {
+                    return;
+                }
+                if let Some(slice) = state.next_slice() {
+                    break slice;
+                }
+                state = shared
+                    .work
+                    .wait(state)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+        };
+
+        let started = Instant::now();
+        let outcome = deepen(&mut engine, &slice);
+        let elapsed = started.elapsed();
+
+        let mut state = shared.state();
+        if let Some(board) = state.boards.get_mut(&slice.id) {
+            board.busy = false;
+            if board.generation == slice.generation {
+                board.time_spent += elapsed;
+                match outcome {
+                    Ok(result) => {
+                        board.depth = slice.depth;
+                        board.finished =
+                            result.best_move.is_none() || slice.depth >= shared.max_depth;
+                        board.result = Some(result);
+                    }
+                    Err(error) => {
+                        chess_core::log_warn!(
+                            "kibitzer stopped analysing {}: {}",
+                            slice.fen,
+                            error
+                        );
+                        board.finished = true;
+                    }
+                }
+            }
+        }
+        drop(state);
+
+        shared.analysed.notify_all();
+        // The board is free again, possibly for a worker that found nothing else
+        shared.work.notify_one();
+    }
+}
+
+/// Search `slice`'s position one ply deeper than before
+fn deepen(engine: &mut ChessEngine, slice: &Slice) -> Result<SearchResult> {
+    engine.load_fen(&slice.fen)?;
+    engine.set_limits(SearchLimits::default().with_depth(slice.depth));
+    engine.set_stop_signal(Some(Arc::clone(&slice.stop)));
+    let result = engine.search()?;
+    chess_core::log_trace!(
+        "kibitzer searched subscription {} to depth {}",
+        slice.id,
+        result.depth
+    );
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess_core::search::engine::MAX_DEPTH;
+
+    const WAIT: Duration = Duration::from_secs(30);
+
+    fn kibitzer(workers: usize, max_depth: u8) -> Kibitzer {
+        Kibitzer::new(KibitzerConfig {
+            workers,
+            hash_mb: 1,
+            max_depth,
+            engine_config: EngineConfig::default(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_subscriptions_deepen_to_the_cap() {
+        let kibitzer = kibitzer(2, 4);
+        let start = kibitzer.subscribe(&GameState::new().to_fen(), 1).unwrap();
+        let sicilian = kibitzer
+            .subscribe(
+                "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2",
+                3,
+            )
+            .unwrap();
+        assert_eq!(kibitzer.subscriptions(), vec![start, sicilian]);
+
+        for id in [start, sicilian] {
+            let analysis = kibitzer.wait_for_depth(id, 4, WAIT).unwrap();
+            assert!(analysis.finished);
+            let result = analysis.result.unwrap();
+            assert_eq!(result.depth, 4);
+            assert!(result.best_move.is_some());
+        }
+    }
+
+    #[test]
+    fn test_update_restarts_and_game_over_finishes() {
+        let kibitzer = kibitzer(1, 3);
+        let id = kibitzer.subscribe(&GameState::new().to_fen(), 2).unwrap();
+        kibitzer.wait_for_depth(id, 3, WAIT).unwrap();
+
+        // Fool's mate: White is checkmated, so there is nothing to deepen
+        let mated = "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3";
+        kibitzer.update(id, mated).unwrap();
+        let analysis = kibitzer.wait_for_depth(id, 3, WAIT).unwrap();
+        assert_eq!(analysis.fen, mated);
+        assert_eq!(analysis.priority, 2);
+        assert!(analysis.finished);
+        assert!(analysis.result.unwrap().best_move.is_none());
+
+        assert!(kibitzer.unsubscribe(id).is_some());
+        assert!(kibitzer.analysis(id).is_none());
+        assert!(kibitzer.update(id, mated).is_err());
+    }
+
+    #[test]
+    fn test_update_stops_the_old_search() {
+        // Without a stop the worker would keep deepening the old position towards
+        // depth 64 before getting to the new one
+        let kibitzer = kibitzer(1, MAX_DEPTH);
+        let id = kibitzer.subscribe(&GameState::new().to_fen(), 1).unwrap();
+        kibitzer.wait_for_depth(id, 2, WAIT).unwrap();
+        let old_search = Arc::clone(&kibitzer.shared.state().boards[&id].stop);
+
+        let sicilian = "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2";
+        kibitzer.update(id, sicilian).unwrap();
+        assert!(old_search.load(Ordering::Relaxed));
+        let analysis = kibitzer.wait_for_depth(id, 2, WAIT).unwrap();
+        assert_eq!(analysis.fen, sicilian);
+        assert!(analysis.result.is_some());
+
+        let new_search = Arc::clone(&kibitzer.shared.state().boards[&id].stop);
+        assert!(!new_search.load(Ordering::Relaxed));
+        kibitzer.unsubscribe(id).unwrap();
+        assert!(new_search.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_rejects_bad_subscriptions() {
+        let kibitzer = kibitzer(1, 2);
+        assert!(kibitzer.subscribe("not a fen", 1).is_err());
+        assert!(kibitzer.subscribe(&GameState::new().to_fen(), 0).is_err());
+        assert!(kibitzer.set_priority(7, 1).is_err());
+        assert!(Kibitzer::new(KibitzerConfig {
+            max_depth: 0,
+            ..KibitzerConfig::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_priority_shares_search_time() {
+        let mut state = KibitzerState::default();
+        let fen = GameState::new().to_fen();
+        let mut low = Board::new(fen.clone(), 1);
+        low.time_spent = Duration::from_millis(100);
+        let mut high = Board::new(fen, 4);
+        high.time_spent = Duration::from_millis(300);
+        state.boards.insert(0, low);
+        state.boards.insert(1, high);
+
+        // 300 ms at priority 4 is less of its share than 100 ms at priority 1
+        assert_eq!(state.next_slice().map(|slice| slice.id), Some(1));
+        assert_eq!(state.next_slice().map(|slice| slice.id), Some(0));
+        assert!(state.next_slice().is_none());
+    }
+}
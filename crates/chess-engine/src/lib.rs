@@ -1,26 +1,93 @@
+pub mod analysis_pgn;
+pub mod analysis_store;
 pub mod builder;
+pub mod config_file;
 pub mod engine;
 pub mod error;
 pub mod event;
+pub mod kibitzer;
+pub mod pgn;
+pub mod pool;
+mod recorder;
+pub mod selfplay;
+pub mod selftest;
+pub mod training;
 
+pub use chess_core::search::{TablebaseProbe, TbWdl};
+pub use chess_core::wire;
 pub use chess_core::{
-    Bitboard, CastlingRights, Color, Evaluator, GameResult, Move, MoveType, Piece, PieceType,
-    Position, Square,
+    Bitboard, CastlingRights, Color, EvalBreakdown, Evaluator, GameResult, MateVerification,
+    MemoryConfig, Move, MoveType, Nag, NagThresholds, NotationStyle, ParallelConfig, Piece,
+    PieceType, Position, RootFail, Score, SearchConfig, SearchLimits, SearchResult, SearchStatus,
+    SearchStatusHandle, Square, SquareChange, Variant, Wdl,
 };
 
-pub use builder::ChessEngineBuilder;
-pub use engine::{ChessEngine, EngineConfig};
+use serde::{Deserialize, Serialize};
+
+pub use analysis_pgn::AnalysisSession;
+pub use analysis_store::{AnalysisStore, StoredAnalysis};
+pub use builder::{ChessEngineBuilder, Preset};
+pub use config_file::ConfigFile;
+pub use engine::{
+    BlunderMode, ChessEngine, EngineConfig, EngineSnapshot, MoveTemperature, Pruning,
+};
 pub use error::{EngineError, Result};
-pub use event::{EventHandler, GameEvent};
+pub use event::{DrawReason, EventHandler, GameEvent};
+pub use kibitzer::{BoardAnalysis, Kibitzer, KibitzerConfig, SubscriptionId};
+pub use pgn::{PgnFilter, PgnGame, PgnReader};
+pub use pool::{EnginePool, EnginePoolConfig, PooledEngine};
+pub use selfplay::{SelfPlay, SelfPlayConfig, SelfPlayGame, SelfPlayMove};
+pub use selftest::{SelfTestCase, SelfTestReport};
+pub use training::{TrainingExporter, TrainingFormat, TrainingSample};
+
+/// Version of the native binding interfaces: the C API, the JNI functions and the Node
+/// module. Bumped whenever a binding function or a C struct changes incompatibly, so a
+/// wrapper loading a library built from other sources can refuse to run.
+pub const ABI_VERSION: u32 = 1;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MoveResult {
     pub success: bool,
     pub game_result: Option<GameResult>,
     pub events: Vec<GameEvent>,
+    /// The move in the engine's notation style; `None` when it was not played
+    pub notation: Option<String>,
+}
+
+/// Outcome of `ChessEngine::find_best_move`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BestMove {
+    Move(Move),
+    /// The game has already ended, so there is no move to play
+    GameOver(GameResult),
+}
+
+impl BestMove {
+    /// The move to play, or `None` when the game is over
+    pub fn into_move(self) -> Option<Move> {
+        match self {
+            BestMove::Move(mv) => Some(mv),
+            BestMove::GameOver(_) => None,
+        }
+    }
+}
+
+/// Outcome of one position from `ChessEngine::analyze_batch`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnalysisResult {
+    pub fen: String,
+    pub best_move: Option<Move>,
+    /// `best_move` in the engine's notation style
+    pub best_move_notation: Option<String>,
+    /// Centipawns from the side to move's point of view
+    pub evaluation: i32,
+    pub depth: u8,
+    pub nodes: u64,
+    /// Set when the FEN could not be parsed; the other fields are then empty
+    pub error: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameInfo {
     pub fen: String,
     pub side_to_move: Color,
@@ -32,4 +99,7 @@ pub struct GameInfo {
     pub halfmove_clock: u32,
     pub fullmove_number: u32,
     pub position_count: usize,
+    /// How often the current position has occurred, itself included; three allows a
+    /// draw claim
+    pub repetition_count: u32,
 }
@@ -0,0 +1,376 @@
+// Streaming PGN reader: games are parsed one at a time, so file size is not bounded by memory
+
+use crate::{GameResult, Result};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+/// One game from a PGN file
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PgnGame {
+    /// Tag pairs in file order
+    pub tags: Vec<(String, String)>,
+    /// Mainline moves in SAN as written, without move numbers, comments, NAGs or variations
+    pub moves: Vec<String>,
+    pub result: Option<GameResult>,
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn white_elo(&self) -> Option<u32> {
+        self.tag("WhiteElo").and_then(|elo| elo.parse().ok())
+    }
+
+    pub fn black_elo(&self) -> Option<u32> {
+        self.tag("BlackElo").and_then(|elo| elo.parse().ok())
+    }
+
+    pub fn eco(&self) -> Option<&str> {
+        self.tag("ECO")
+    }
+
+    /// Result from the movetext terminator, falling back to the `Result` tag
+    pub fn game_result(&self) -> Option<GameResult> {
+        self.result
+            .or_else(|| self.tag("Result").and_then(parse_result))
+    }
+}
+
+fn parse_result(token: &str) -> Option<GameResult> {
+    match token {
+        "1-0" => Some(GameResult::WhiteWins),
+        "0-1" => Some(GameResult::BlackWins),
+        "1/2-1/2" => Some(GameResult::Draw),
+        "*" => Some(GameResult::Ongoing),
+        _ => None,
+    }
+}
+
+/// Criteria a game must meet to be yielded; unset fields accept everything
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PgnFilter {
+    /// Both players must be rated at least this; games missing a rating are skipped
+    pub min_elo: Option<u32>,
+    /// Inclusive ECO code range such as `"B20"..="B99"`
+    pub eco_range: Option<RangeInclusive<String>>,
+    pub result: Option<GameResult>,
+}
+
+impl PgnFilter {
+    pub fn matches(&self, game: &PgnGame) -> bool {
+        if let Some(min_elo) = self.min_elo {
+            let rated = |elo: Option<u32>| elo.is_some_and(|elo| elo >= min_elo);
+            if !rated(game.white_elo()) || !rated(game.black_elo()) {
+                return false;
+            }
+        }
+        if let Some(range) = &self.eco_range {
+            match game.eco() {
+                Some(eco) if range.start().as_str() <= eco && eco <= range.end().as_str() => {}
+                _ => return false,
+            }
+        }
+        if let Some(result) = self.result {
+            if game.game_result() != Some(result) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Lazily yields the games of a PGN source that pass the filter.
+///
+/// Only the game being parsed is held in memory. Malformed tag lines are ignored, bytes
+/// that are not UTF-8 (such as Latin-1 names) are replaced with U+FFFD, and a game
+/// without a result terminator ends at the next tag section or end of input.
+pub struct PgnReader<R: BufRead> {
+    reader: R,
+    filter: PgnFilter,
+    bytes: Vec<u8>,
+    line: String,
+    /// Tag line that opened the next game while the previous one was still being read
+    pending_tag: Option<(String, String)>,
+    in_comment: bool,
+    variation_depth: u32,
+    finished: bool,
+}
+
+impl PgnReader<BufReader<File>> {
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::from_reader(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn from_reader(reader: R) -> Self {
+        PgnReader {
+            reader,
+            filter: PgnFilter::default(),
+            bytes: Vec::new(),
+            line: String::new(),
+            pending_tag: None,
+            in_comment: false,
+            variation_depth: 0,
+            finished: false,
+        }
+    }
+
+    /// Only yield games meeting all the given criteria
+    pub fn filter(
+        mut self,
+        min_elo: Option<u32>,
+        eco_range: Option<RangeInclusive<&str>>,
+        result: Option<GameResult>,
+    ) -> Self {
+        self.filter = PgnFilter {
+            min_elo,
+            eco_range: eco_range.map(|range| range.start().to_string()..=range.end().to_string()),
+            result,
+        };
+        self
+    }
+
+    pub fn with_filter(mut self, filter: PgnFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Parse the next game regardless of the filter; `None` at end of input
+    fn read_game(&mut self) -> Result<Option<PgnGame>> {
+        let mut game = PgnGame::default();
+        let mut has_content = false;
+        let mut in_movetext = false;
+        self.in_comment = false;
+        self.variation_depth = 0;
+
+        if let Some(tag) = self.pending_tag.take() {
+            game.tags.push(tag);
+            has_content = true;
+        }
+
+        loop {
+            self.bytes.clear();
+            if self.reader.read_until(b'\n', &mut self.bytes)? == 0 {
+                return Ok(has_content.then_some(game));
+            }
+            self.line.clear();
+            self.line.push_str(&String::from_utf8_lossy(&self.bytes));
+
+            let line = self.line.trim();
+            if line.is_empty() || (line.starts_with('%') && !self.in_comment) {
+                continue;
+            }
+
+            if line.starts_with('[') && !self.in_comment {
+                if let Some(tag) = parse_tag(line) {
+                    if in_movetext {
+                        // Unterminated game: this tag already belongs to the next one
+                        self.pending_tag = Some(tag);
+                        return Ok(Some(game));
+                    }
+                    game.tags.push(tag);
+                    has_content = true;
+                }
+                continue;
+            }
+
+            in_movetext = true;
+            has_content = true;
+            let line = std::mem::take(&mut self.line);
+            let done = self.scan_movetext(&line, &mut game);
+            self.line = line;
+            if done {
+                return Ok(Some(game));
+            }
+        }
+    }
+
+    /// Collect mainline SAN tokens from one line; true once the result terminator is seen
+    fn scan_movetext(&mut self, line: &str, game: &mut PgnGame) -> bool {
+        let mut token = String::new();
+        for c in line.chars() {
+            if self.in_comment {
+                self.in_comment = c != '}';
+                continue;
+            }
+            match c {
+                '{' | ';' | '(' | ')' => {
+                    if Self::finish_token(&mut token, self.variation_depth, game) {
+                        return true;
+                    }
+                    match c {
+                        '{' => self.in_comment = true,
+                        ';' => return false,
+                        '(' => self.variation_depth += 1,
+                        _ => self.variation_depth = self.variation_depth.saturating_sub(1),
+                    }
+                }
+                c if c.is_whitespace() => {
+                    if Self::finish_token(&mut token, self.variation_depth, game) {
+                        return true;
+                    }
+                }
+                c => token.push(c),
+            }
+        }
+        Self::finish_token(&mut token, self.variation_depth, game)
+    }
+
+    fn finish_token(token: &mut String, variation_depth: u32, game: &mut PgnGame) -> bool {
+        let raw = std::mem::take(token);
+        if variation_depth > 0 || raw.starts_with('$') {
+            return false;
+        }
+        if let Some(result) = parse_result(&raw) {
+            game.result = Some(result);
+            return true;
+        }
+
+        // "12.", "12...", or a move glued to its number such as "12.e4"
+        let san = raw
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .trim_start_matches('.')
+            .trim_end_matches(['!', '?']);
+        if !san.is_empty() {
+            game.moves.push(san.to_string());
+        }
+        false
+    }
+}
+
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?.trim();
+    let (name, value) = inner.split_once(char::is_whitespace)?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((name.to_string(), value.replace("\\\"", "\"")))
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.finished {
+            match self.read_game() {
+                Ok(Some(game)) if self.filter.matches(&game) => return Some(Ok(game)),
+                Ok(Some(_)) => continue,
+                Ok(None) => self.finished = true,
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EngineError;
+    use std::io::Cursor;
+
+    const SAMPLE: &str = r#"[Event "Casual"]
+[White "Alice"]
+[Black "Bob"]
+[WhiteElo "2400"]
+[BlackElo "2350"]
+[ECO "C20"]
+[Result "1-0"]
+
+1. e4 e5 2. Qh5 {threatening mate} Nc6 (2... g6 3. Qxe5+) 3. Bc4 Nf6?? 4. Qxf7# 1-0
+
+[White "Carol"]
+[Black "Dan"]
+[WhiteElo "1800"]
+[BlackElo "2500"]
+[ECO "B22"]
+
+1. e4 c5 2. c3 $1 ; main line
+d5 1/2-1/2
+
+[White "Eve"]
+[ECO "A00"]
+1.g4 e5 2.f3
+"#;
+
+    fn reader() -> PgnReader<Cursor<&'static str>> {
+        PgnReader::from_reader(Cursor::new(SAMPLE))
+    }
+
+    #[test]
+    fn test_reads_all_games_lazily() {
+        let games: Vec<PgnGame> = reader().map(|game| game.unwrap()).collect();
+        assert_eq!(games.len(), 3);
+
+        assert_eq!(games[0].tag("White"), Some("Alice"));
+        assert_eq!(
+            games[0].moves,
+            vec!["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"]
+        );
+        assert_eq!(games[0].game_result(), Some(GameResult::WhiteWins));
+
+        assert_eq!(games[1].moves, vec!["e4", "c5", "c3", "d5"]);
+        assert_eq!(games[1].game_result(), Some(GameResult::Draw));
+
+        // No terminator: the game ends at end of input
+        assert_eq!(games[2].moves, vec!["g4", "e5", "f3"]);
+        assert_eq!(games[2].game_result(), None);
+    }
+
+    #[test]
+    fn test_invalid_utf8_does_not_end_iteration() {
+        let mut pgn = b"[White \"Jos".to_vec();
+        pgn.push(0xE9);
+        pgn.extend_from_slice(b"\"]\n[Result \"1-0\"]\n\n1. e4 e5 1-0\n\n");
+        pgn.extend_from_slice(SAMPLE.as_bytes());
+
+        let games: Vec<PgnGame> = PgnReader::from_reader(pgn.as_slice())
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(games[0].tag("White"), Some("Jos\u{FFFD}"));
+        assert_eq!(games[0].moves, ["e4", "e5"]);
+        assert_eq!(
+            games.len(),
+            1 + PgnReader::from_reader(SAMPLE.as_bytes()).count()
+        );
+    }
+
+    #[test]
+    fn test_filters() {
+        let by_elo: Vec<_> = reader().filter(Some(2300), None, None).collect();
+        assert_eq!(by_elo.len(), 1);
+
+        let by_eco: Vec<_> = reader()
+            .filter(None, Some("B00"..="B99"), None)
+            .map(|game| game.unwrap())
+            .collect();
+        assert_eq!(by_eco.len(), 1);
+        assert_eq!(by_eco[0].eco(), Some("B22"));
+
+        let draws = reader().filter(None, None, Some(GameResult::Draw)).count();
+        assert_eq!(draws, 1);
+    }
+
+    #[test]
+    fn test_reads_from_file() {
+        let path = std::env::temp_dir().join(format!("pgn_reader_{}.pgn", std::process::id()));
+        std::fs::write(&path, SAMPLE).unwrap();
+        let count = PgnReader::new(&path).unwrap().count();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(count, 3);
+
+        assert!(matches!(
+            PgnReader::new(&path),
+            Err(EngineError::IoError(_))
+        ));
+    }
+}
@@ -0,0 +1,301 @@
+// Pool of reusable engines for servers analysing many games at once
+
+use crate::{ChessEngine, EngineConfig, EngineError, Result};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Size and per-engine resources of an `EnginePool`
+#[derive(Debug, Clone)]
+pub struct EnginePoolConfig {
+    /// Number of engines, and so of games analysed at the same time
+    pub engines: usize,
+    /// Transposition table size of each engine in megabytes
+    pub hash_mb: usize,
+    /// Worker threads of each engine's batch analysis
+    pub threads_per_engine: usize,
+    /// Settings every engine starts from, and returns to when checked in
+    pub engine_config: EngineConfig,
+}
+
+impl Default for EnginePoolConfig {
+    fn default() -> Self {
+        EnginePoolConfig {
+            engines: 4,
+            hash_mb: 16,
+            threads_per_engine: 1,
+            engine_config: EngineConfig::default(),
+        }
+    }
+}
+
+struct PoolShared {
+    config: EnginePoolConfig,
+    idle: Mutex<Vec<ChessEngine>>,
+    returned: Condvar,
+}
+
+impl PoolShared {
+    fn idle(&self) -> MutexGuard<'_, Vec<ChessEngine>> {
+        // An engine list stays consistent even if a holder of the lock panicked
+        self.idle
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A fixed set of initialized engines handed out one at a time.
+///
+/// Cloning the pool is cheap and shares the same engines, so it can be passed to every
+/// request handler or thread.
+#[derive(Clone)]
+pub struct EnginePool {
+    shared: Arc<PoolShared>,
+}
+
+impl EnginePool {
+    pub fn new(config: EnginePoolConfig) -> Result<Self> {
+        if config.engines == 0 {
+            return Err(EngineError::ConfigurationError(
+                "Engine pool needs at least one engine".to_string(),
+            ));
+        }
+
+        let engines = (0..config.engines)
+            .map(|_| Self::create_engine(&config))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(EnginePool {
+            shared: Arc::new(PoolShared {
+                config,
+                idle: Mutex::new(engines),
+                returned: Condvar::new(),
+            }),
+        })
+    }
+
+    pub fn config(&self) -> &EnginePoolConfig {
+        &self.shared.config
+    }
+
+    /// Total number of engines, checked out or not
+    pub fn size(&self) -> usize {
+        self.shared.config.engines
+    }
+
+    /// Engines waiting to be checked out
+    pub fn available(&self) -> usize {
+        self.shared.idle().len()
+    }
+
+    /// Take an engine, waiting until one is checked in if all are in use
+    pub fn checkout(&self) -> PooledEngine {
+        let mut idle = self.shared.idle();
+        loop {
+            if let Some(engine) = idle.pop() {
+                return self.pooled(engine);
+            }
+            idle = self
+                .shared
+                .returned
+                .wait(idle)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+    }
+
+    /// Take an engine if one is free right now
+    pub fn try_checkout(&self) -> Option<PooledEngine> {
+        let engine = self.shared.idle().pop()?;
+        Some(self.pooled(engine))
+    }
+
+    /// Take an engine, giving up after `timeout`
+    pub fn checkout_timeout(&self, timeout: Duration) -> Option<PooledEngine> {
+        let deadline = Instant::now() + timeout;
+        let mut idle = self.shared.idle();
+        loop {
+            if let Some(engine) = idle.pop() {
+                return Some(self.pooled(engine));
+            }
+            let remaining = deadline.checked_duration_since(Instant::now())?;
+            idle = self
+                .shared
+                .returned
+                .wait_timeout(idle, remaining)
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .0;
+        }
+    }
+
+    /// Return an engine early; dropping the `PooledEngine` does the same
+    pub fn checkin(&self, engine: PooledEngine) {
+        drop(engine);
+    }
+
+    fn pooled(&self, engine: ChessEngine) -> PooledEngine {
+        PooledEngine {
+            engine: Some(engine),
+            shared: Arc::clone(&self.shared),
+        }
+    }
+
+    fn create_engine(config: &EnginePoolConfig) -> Result<ChessEngine> {
+        let mut engine = ChessEngine::with_config(Self::engine_config(config));
+        engine.set_hash_size(config.hash_mb);
+        engine.initialize()?;
+        Self::restore_limits(&mut engine);
+        Ok(engine)
+    }
+
+    /// Settings of every engine: the pool's engine config with its thread count
+    fn engine_config(config: &EnginePoolConfig) -> EngineConfig {
+        let mut engine_config = config.engine_config.clone();
+        engine_config.thread_count = config.threads_per_engine.max(1);
+        engine_config
+    }
+
+    fn restore_limits(engine: &mut ChessEngine) {
+        let (depth, time_limit_ms) = {
+            let config = engine.get_config();
+            (config.depth, config.time_limit_ms)
+        };
+        engine.set_search_limits(depth, time_limit_ms);
+    }
+}
+
+/// An engine checked out of an `EnginePool`, returned to it when dropped.
+///
+/// The engine goes back reset to the starting position with the pool's settings, hash
+/// size, thread count and search limits; its transposition table is kept when its size
+/// was not changed, so later games profit from earlier searches.
+pub struct PooledEngine {
+    engine: Option<ChessEngine>,
+    shared: Arc<PoolShared>,
+}
+
+impl Deref for PooledEngine {
+    type Target = ChessEngine;
+
+    fn deref(&self) -> &ChessEngine {
+        self.engine.as_ref().expect("engine is present until drop")
+    }
+}
+
+impl DerefMut for PooledEngine {
+    fn deref_mut(&mut self) -> &mut ChessEngine {
+        self.engine.as_mut().expect("engine is present until drop")
+    }
+}
+
+impl Drop for PooledEngine {
+    fn drop(&mut self) {
+        let Some(mut engine) = self.engine.take() else {
+            return;
+        };
+        // Resetting to the starting position cannot fail
+        let _ = engine.reset();
+        engine.set_analysis_store(None);
+        engine.set_tablebase(None);
+        engine.set_stop_signal(None);
+        let config = &self.shared.config;
+        engine.restore_config(EnginePool::engine_config(config), config.hash_mb);
+        EnginePool::restore_limits(&mut engine);
+
+        self.shared.idle().push(engine);
+        self.shared.returned.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NotationStyle;
+    use std::thread;
+
+    fn small_pool(engines: usize) -> EnginePool {
+        EnginePool::new(EnginePoolConfig {
+            engines,
+            hash_mb: 1,
+            threads_per_engine: 2,
+            engine_config: EngineConfig {
+                depth: 2,
+                ..EngineConfig::default()
+            },
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_checkout_and_checkin() {
+        let pool = small_pool(2);
+        assert_eq!(pool.available(), 2);
+
+        let first = pool.checkout();
+        let second = pool.try_checkout().unwrap();
+        assert_eq!(pool.available(), 0);
+        assert!(pool.try_checkout().is_none());
+        assert!(pool.checkout_timeout(Duration::from_millis(10)).is_none());
+        assert_eq!(first.get_config().thread_count, 2);
+        assert!(first.is_initialized());
+
+        pool.checkin(first);
+        drop(second);
+        assert_eq!(pool.available(), 2);
+        assert!(EnginePool::new(EnginePoolConfig {
+            engines: 0,
+            ..EnginePoolConfig::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_engines_come_back_reset() {
+        let pool = small_pool(1);
+        {
+            let mut engine = pool.checkout();
+            engine.make_move_from_uci("e2e4").unwrap();
+            engine.set_search_limits(9, Some(5));
+        }
+
+        let mut engine = pool.checkout();
+        assert_eq!(engine.get_fen(), chess_core::GameState::new().to_fen());
+        assert_eq!(engine.search().unwrap().depth, 2);
+    }
+
+    #[test]
+    fn test_engines_come_back_with_the_pool_settings() {
+        let pool = small_pool(1);
+        let (hash_size, style) = {
+            let mut engine = pool.checkout();
+            let before = (engine.get_hash_size(), engine.get_config().notation_style);
+            engine.set_hash_size(4);
+            engine.set_thread_count(7);
+            engine.set_notation_style(NotationStyle::Figurine);
+            before
+        };
+
+        let engine = pool.checkout();
+        assert_eq!(engine.get_hash_size(), hash_size);
+        assert_eq!(engine.get_config().thread_count, 2);
+        assert_eq!(engine.get_config().notation_style, style);
+        assert_eq!(engine.get_config().depth, 2);
+    }
+
+    #[test]
+    fn test_concurrent_games_share_the_pool() {
+        let pool = small_pool(2);
+        let handles: Vec<_> = (0..6)
+            .map(|_| {
+                let pool = pool.clone();
+                thread::spawn(move || {
+                    let mut engine = pool.checkout();
+                    engine.search().unwrap().best_move
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert!(handle.join().unwrap().is_some());
+        }
+        assert_eq!(pool.available(), 2);
+    }
+}
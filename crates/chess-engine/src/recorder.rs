@@ -0,0 +1,180 @@
+// Bookkeeping of the game an engine plays
+//
+// Moves reach the game by being played, taken back, replayed or tried out while
+// pondering. Each path has to update the position, the move list, the repetition counts
+// and the move clocks together, or a takeback leaves stale repetitions behind and a
+// stopped ponder search leaves its guessed move on the board. The recorder owns the
+// game state so every change goes through one of its methods.
+
+use chess_core::{GameState, Move, Result, UndoInfo};
+
+#[derive(Clone)]
+pub(crate) struct GameRecorder {
+    state: GameState,
+    /// What each move of `state.move_history` needs to be taken back, in the same order
+    undo: Vec<UndoInfo>,
+    /// Moves taken back, the most recent last, replayed by `redo`
+    redo: Vec<Move>,
+    /// Number of moves played when the players agreed a draw; taking back any of them
+    /// withdraws the agreement
+    draw_agreed_at: Option<usize>,
+}
+
+impl GameRecorder {
+    pub fn new(state: GameState) -> Self {
+        GameRecorder {
+            state,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            draw_agreed_at: None,
+        }
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// Play a legal move; a new move discards the moves that could be redone
+    pub fn play(&mut self, mv: Move) -> Result<()> {
+        self.push(mv)?;
+        self.redo.clear();
+        Ok(())
+    }
+
+    /// Take back the last move, or return `None` at the start of the game
+    pub fn undo(&mut self) -> Option<Move> {
+        let mv = self.pop()?;
+        self.redo.push(mv);
+        Some(mv)
+    }
+
+    /// Replay the last move taken back, or return `None` if there is none
+    pub fn redo(&mut self) -> Result<Option<Move>> {
+        let Some(mv) = self.redo.pop() else {
+            return Ok(None);
+        };
+        if let Err(error) = self.push(mv) {
+            self.redo.push(mv);
+            return Err(error);
+        }
+        Ok(Some(mv))
+    }
+
+    /// Record that the players agreed a draw in the current position
+    pub fn agree_draw(&mut self) {
+        self.draw_agreed_at = Some(self.undo.len());
+    }
+
+    pub fn draw_agreed(&self) -> bool {
+        self.draw_agreed_at.is_some()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Run `f` on the game with `mv` played, then take `mv` back whatever `f` returns.
+    /// Moves waiting to be redone are kept.
+    pub fn with_move<R>(&mut self, mv: Move, f: impl FnOnce(&GameState) -> R) -> Result<R> {
+        self.push(mv)?;
+        let result = f(&self.state);
+        self.pop();
+        Ok(result)
+    }
+
+    fn push(&mut self, mv: Move) -> Result<()> {
+        let undo = self.state.make_move_with_undo(mv)?;
+        self.undo.push(undo);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<Move> {
+        let undo = self.undo.pop()?;
+        if self.draw_agreed_at > Some(self.undo.len()) {
+            self.draw_agreed_at = None;
+        }
+        self.state.undo_move(undo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play_all(recorder: &mut GameRecorder, moves: &[&str]) {
+        for uci in moves {
+            let mv = recorder.state().resolve_uci_move(uci).unwrap();
+            recorder.play(mv).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_undo_and_redo_keep_clocks() {
+        let mut recorder = GameRecorder::new(GameState::new());
+        play_all(&mut recorder, &["e2e4", "e7e5", "g1f3", "b8c6"]);
+        assert_eq!(recorder.state().position.halfmove_clock, 2);
+        assert_eq!(recorder.state().position.fullmove_number, 3);
+
+        recorder.undo().unwrap();
+        recorder.undo().unwrap();
+        assert_eq!(recorder.state().position.halfmove_clock, 0);
+        assert_eq!(recorder.state().position.fullmove_number, 2);
+
+        assert!(recorder.redo().unwrap().is_some());
+        assert!(recorder.redo().unwrap().is_some());
+        assert_eq!(recorder.redo().unwrap(), None);
+        assert_eq!(recorder.state().position.halfmove_clock, 2);
+        assert_eq!(recorder.state().move_history.len(), 4);
+    }
+
+    #[test]
+    fn test_new_move_clears_redo() {
+        let mut recorder = GameRecorder::new(GameState::new());
+        play_all(&mut recorder, &["e2e4"]);
+        recorder.undo().unwrap();
+        assert!(recorder.can_redo());
+
+        play_all(&mut recorder, &["d2d4"]);
+        assert!(!recorder.can_redo());
+        let d4 = recorder.state().move_history[0];
+        assert_eq!(recorder.undo(), Some(d4));
+        assert!(!recorder.can_undo());
+    }
+
+    #[test]
+    fn test_with_move_leaves_game_untouched() {
+        let mut recorder = GameRecorder::new(GameState::new());
+        play_all(&mut recorder, &["e2e4"]);
+        recorder.undo().unwrap();
+        let before = recorder.state().clone();
+
+        let reply = recorder.state().resolve_uci_move("d2d4").unwrap();
+        let halfmoves = recorder
+            .with_move(reply, |state| state.move_history.len())
+            .unwrap();
+        assert_eq!(halfmoves, 1);
+        assert_eq!(recorder.state(), &before);
+        assert!(recorder.can_redo());
+    }
+
+    #[test]
+    fn test_undo_withdraws_draw_agreement() {
+        let mut recorder = GameRecorder::new(GameState::new());
+        play_all(&mut recorder, &["e2e4", "e7e5"]);
+        recorder.agree_draw();
+        assert!(recorder.draw_agreed());
+
+        let reply = recorder.state().resolve_uci_move("g1f3").unwrap();
+        recorder.with_move(reply, |_| ()).unwrap();
+        assert!(recorder.draw_agreed());
+
+        recorder.undo().unwrap();
+        assert!(!recorder.draw_agreed());
+        recorder.redo().unwrap();
+        assert!(!recorder.draw_agreed());
+    }
+}
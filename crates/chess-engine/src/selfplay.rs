@@ -0,0 +1,272 @@
+// Self-play game generation for training data and tuning
+
+use crate::{ChessEngine, Color, GameResult, Move, Position, Result, TrainingSample};
+use chess_core::search::engine::MATE_VALUE;
+use chess_core::{Evaluator, GameState};
+
+const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Debug, Clone)]
+pub struct SelfPlayConfig {
+    /// Search depth for every move not chosen at random
+    pub depth: u8,
+    pub movetime_ms: Option<u64>,
+    /// Opening plies played uniformly at random, so games diverge
+    pub random_plies: u32,
+    /// Softmax temperature in centipawns over one-ply evaluations; 0 always plays the search move
+    pub temperature: f64,
+    /// Plies after the random opening during which `temperature` applies
+    pub temperature_plies: u32,
+    /// Games still running after this many plies are adjudicated as draws
+    pub max_plies: u32,
+    /// Same seed and config give the same games
+    pub seed: u64,
+    pub start_fen: Option<String>,
+}
+
+impl Default for SelfPlayConfig {
+    fn default() -> Self {
+        SelfPlayConfig {
+            depth: 4,
+            movetime_ms: None,
+            random_plies: 4,
+            temperature: 0.0,
+            temperature_plies: 0,
+            max_plies: 300,
+            seed: 0x9E37_79B9_7F4A_7C15,
+            start_fen: None,
+        }
+    }
+}
+
+/// One ply of a self-play game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfPlayMove {
+    /// Position before the move
+    pub position: Position,
+    pub mv: Move,
+    /// White-relative search score; `None` for random opening moves
+    pub score: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfPlayGame {
+    pub start_fen: String,
+    pub moves: Vec<SelfPlayMove>,
+    pub result: GameResult,
+    /// True when `max_plies` ended the game rather than the rules
+    pub adjudicated: bool,
+}
+
+impl SelfPlayGame {
+    /// Searched positions labelled with the final result; mate scores are skipped
+    pub fn training_samples(&self) -> impl Iterator<Item = TrainingSample> + '_ {
+        self.moves.iter().filter_map(move |record| {
+            let score = record.score?;
+            (score.abs() < MATE_VALUE - 100).then(|| TrainingSample {
+                position: record.position.clone(),
+                score,
+                result: self.result,
+            })
+        })
+    }
+
+    pub fn uci_moves(&self) -> Vec<String> {
+        self.moves
+            .iter()
+            .map(|record| record.mv.to_string())
+            .collect()
+    }
+}
+
+/// Small deterministic generator; self-play needs reproducibility, not cryptographic quality
+struct XorShift64(u64);
+
+impl XorShift64 {
+    fn new(seed: u64) -> Self {
+        XorShift64(seed.max(1))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+/// Plays an engine against itself, one game per `play_game` call
+pub struct SelfPlay {
+    engine: ChessEngine,
+    evaluator: Evaluator,
+    config: SelfPlayConfig,
+    rng: XorShift64,
+}
+
+impl SelfPlay {
+    pub fn new(config: SelfPlayConfig) -> Result<Self> {
+        let mut engine = ChessEngine::new();
+        engine.initialize()?;
+        engine.set_search_limits(config.depth, config.movetime_ms);
+        Ok(SelfPlay {
+            engine,
+            evaluator: Evaluator::new(),
+            rng: XorShift64::new(config.seed),
+            config,
+        })
+    }
+
+    pub fn config(&self) -> &SelfPlayConfig {
+        &self.config
+    }
+
+    pub fn play_game(&mut self) -> Result<SelfPlayGame> {
+        let start_fen = self
+            .config
+            .start_fen
+            .clone()
+            .unwrap_or_else(|| STARTING_FEN.to_string());
+        self.engine.load_fen(&start_fen)?;
+
+        let mut moves = Vec::new();
+        let mut ply = 0;
+        while self.engine.get_game_result() == GameResult::Ongoing {
+            if ply >= self.config.max_plies {
+                return Ok(SelfPlayGame {
+                    start_fen,
+                    moves,
+                    result: GameResult::Draw,
+                    adjudicated: true,
+                });
+            }
+
+            let legal_moves = self.engine.get_legal_moves();
+            if legal_moves.is_empty() {
+                break;
+            }
+            let position = self.engine.get_position().clone();
+
+            let (mv, score) = if ply < self.config.random_plies {
+                (legal_moves[self.rng.below(legal_moves.len())], None)
+            } else {
+                let result = self.engine.search()?;
+                let score = match position.side_to_move() {
+                    Color::White => result.evaluation,
+                    Color::Black => -result.evaluation,
+                };
+                let searched = result
+                    .best_move
+                    .filter(|mv| legal_moves.contains(mv))
+                    .unwrap_or(legal_moves[0]);
+
+                let mv = if self.config.temperature > 0.0
+                    && ply < self.config.random_plies + self.config.temperature_plies
+                {
+                    self.sample_move(&legal_moves)
+                } else {
+                    searched
+                };
+                (mv, Some(score))
+            };
+
+            self.engine.make_move(mv)?;
+            moves.push(SelfPlayMove {
+                position,
+                mv,
+                score,
+            });
+            ply += 1;
+        }
+
+        Ok(SelfPlayGame {
+            start_fen,
+            moves,
+            result: self.engine.get_game_result(),
+            adjudicated: false,
+        })
+    }
+
+    /// Pick a move with probability proportional to exp(score / temperature)
+    fn sample_move(&mut self, legal_moves: &[Move]) -> Move {
+        let state = self.engine.game_state();
+        let scores: Vec<f64> = legal_moves
+            .iter()
+            .map(|&mv| {
+                let mut child: GameState = state.clone();
+                if child.make_move(mv).is_err() {
+                    return f64::NEG_INFINITY;
+                }
+                // The evaluator scores for the side to move, which is now the opponent
+                -self.evaluator.evaluate(&child) as f64
+            })
+            .collect();
+
+        let best = scores.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let weights: Vec<f64> = scores
+            .iter()
+            .map(|score| ((score - best) / self.config.temperature).exp())
+            .collect();
+        let mut target = self.rng.next_f64() * weights.iter().sum::<f64>();
+        for (mv, weight) in legal_moves.iter().zip(&weights) {
+            if target < *weight {
+                return *mv;
+            }
+            target -= weight;
+        }
+        legal_moves[legal_moves.len() - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quick_config() -> SelfPlayConfig {
+        SelfPlayConfig {
+            depth: 1,
+            random_plies: 2,
+            temperature: 50.0,
+            temperature_plies: 4,
+            max_plies: 12,
+            ..SelfPlayConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_self_play_is_reproducible() {
+        let first = SelfPlay::new(quick_config()).unwrap().play_game().unwrap();
+        let second = SelfPlay::new(quick_config()).unwrap().play_game().unwrap();
+        assert_eq!(first.uci_moves(), second.uci_moves());
+
+        assert!(first.adjudicated);
+        assert_eq!(first.result, GameResult::Draw);
+        assert_eq!(first.moves.len(), 12);
+        assert!(first.moves[..2].iter().all(|record| record.score.is_none()));
+        assert_eq!(first.training_samples().count(), 10);
+    }
+
+    #[test]
+    fn test_self_play_finishes_decided_games() {
+        let mut selfplay = SelfPlay::new(SelfPlayConfig {
+            depth: 2,
+            random_plies: 0,
+            start_fen: Some("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1".to_string()),
+            ..SelfPlayConfig::default()
+        })
+        .unwrap();
+        let game = selfplay.play_game().unwrap();
+
+        assert_eq!(game.uci_moves(), vec!["a1a8"]);
+        assert_eq!(game.result, GameResult::WhiteWins);
+        assert!(!game.adjudicated);
+    }
+}
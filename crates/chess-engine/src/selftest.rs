@@ -0,0 +1,217 @@
+// Built-in self-test for checking a build of the library on a new platform
+//
+// Perft counts catch broken move generation, such as bit tricks or integer widths that
+// behave differently on a target; the tactical positions catch a search or evaluation
+// that runs but plays nonsense. Everything is embedded, so bindings can call it without
+// test files.
+
+use crate::{ChessEngine, Result};
+use chess_core::game::notation;
+use chess_core::utils::fen_check::epd_to_fen;
+use chess_core::GameState;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Perft positions with their node counts: FEN, depth, nodes
+const PERFT_SUITE: [(&str, u8, u64); 5] = [
+    (
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        3,
+        8_902,
+    ),
+    (
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        2,
+        2_039,
+    ),
+    ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 3, 2_812),
+    (
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        2,
+        264,
+    ),
+    (
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        2,
+        1_486,
+    ),
+];
+
+/// Tactical positions in EPD with their best moves (`bm`, in SAN) and names
+const TACTICS_SUITE: [&str; 12] = [
+    "6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8#; id \"back rank mate\";",
+    "7k/8/6K1/8/8/8/8/Q7 w - - bm Qa8# Qg7#; id \"queen mate\";",
+    "6rk/6pp/8/6N1/8/8/8/6K1 w - - bm Nf7#; id \"smothered mate\";",
+    "3r2k1/8/8/8/8/8/5PPP/6K1 b - - bm Rd1#; id \"back rank mate for black\";",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - bm Qxf7#; id \"scholar's mate\";",
+    "q3k3/8/8/1N6/8/8/8/4K3 w - - bm Nc7+; id \"knight fork\";",
+    "4k3/8/8/3q4/8/8/3R4/3K4 w - - bm Rxd5; id \"hanging queen\";",
+    "3rk3/8/8/8/3Q4/8/8/4K3 b - - bm Rxd4; id \"hanging queen for black\";",
+    "r1b1kbnr/pppp1ppp/2n5/4p3/2B1P2q/5N2/PPPP1PPP/RNBQK2R w KQkq - bm Nxh4; id \"attacked queen\";",
+    "8/P6k/8/8/8/8/8/K7 w - - bm a8=Q; id \"promotion\";",
+    "4k3/8/8/8/8/8/4r3/R3K3 w Q - bm Kxe2; id \"king takes undefended rook\";",
+    "k7/8/8/8/8/8/1R6/KR6 w - - bm Ra2#; id \"ladder mate\";",
+];
+
+/// Depth the tactical positions are searched to
+const TACTICS_DEPTH: u8 = 4;
+
+/// Outcome of one self-test case
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestCase {
+    pub name: String,
+    pub passed: bool,
+    /// What was expected and what came out
+    pub detail: String,
+}
+
+/// Outcome of `ChessEngine::selftest`, one case per perft or tactical position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestReport {
+    pub cases: Vec<SelfTestCase>,
+    pub elapsed: Duration,
+}
+
+impl SelfTestReport {
+    pub fn passed(&self) -> bool {
+        self.cases.iter().all(|case| case.passed)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &SelfTestCase> {
+        self.cases.iter().filter(|case| !case.passed)
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for case in &self.cases {
+            let status = if case.passed { "PASS" } else { "FAIL" };
+            writeln!(f, "{} {}: {}", status, case.name, case.detail)?;
+        }
+        let failed = self.failures().count();
+        write!(
+            f,
+            "{} of {} passed in {} ms",
+            self.cases.len() - failed,
+            self.cases.len(),
+            self.elapsed.as_millis()
+        )
+    }
+}
+
+pub(crate) fn run() -> SelfTestReport {
+    let start = Instant::now();
+    let mut cases: Vec<SelfTestCase> = PERFT_SUITE
+        .iter()
+        .map(|&(fen, depth, expected)| perft_case(fen, depth, expected))
+        .collect();
+    cases.extend(TACTICS_SUITE.iter().map(|epd| tactics_case(epd)));
+    SelfTestReport {
+        cases,
+        elapsed: start.elapsed(),
+    }
+}
+
+fn perft_case(fen: &str, depth: u8, expected: u64) -> SelfTestCase {
+    let name = format!("perft {} depth {}", fen, depth);
+    let nodes = GameState::from_fen(fen).and_then(|mut state| perft(&mut state, depth));
+    match nodes {
+        Ok(nodes) => SelfTestCase {
+            name,
+            passed: nodes == expected,
+            detail: format!("expected {} nodes, got {}", expected, nodes),
+        },
+        Err(error) => SelfTestCase {
+            name,
+            passed: false,
+            detail: error.to_string(),
+        },
+    }
+}
+
+fn perft(state: &mut GameState, depth: u8) -> chess_core::Result<u64> {
+    let moves = state.generate_legal_moves();
+    if depth <= 1 {
+        return Ok(if depth == 0 { 1 } else { moves.len() as u64 });
+    }
+
+    let mut nodes = 0;
+    for mv in moves {
+        let undo = state.make_move_with_undo(mv)?;
+        nodes += perft(state, depth - 1)?;
+        state.undo_move(undo);
+    }
+    Ok(nodes)
+}
+
+fn tactics_case(epd: &str) -> SelfTestCase {
+    let name = epd_field(epd, "id")
+        .map(|id| id.trim_matches('"').to_string())
+        .unwrap_or_else(|| epd.to_string());
+    let best_moves: Vec<&str> = epd_field(epd, "bm")
+        .map(|moves| moves.split_whitespace().collect())
+        .unwrap_or_default();
+
+    match solve(epd) {
+        Ok(Some(san)) => SelfTestCase {
+            name,
+            passed: best_moves.contains(&san.as_str()),
+            detail: format!("expected {}, played {}", best_moves.join(" or "), san),
+        },
+        Ok(None) => SelfTestCase {
+            name,
+            passed: false,
+            detail: "no move found".to_string(),
+        },
+        Err(error) => SelfTestCase {
+            name,
+            passed: false,
+            detail: error.to_string(),
+        },
+    }
+}
+
+/// The move the engine picks for the EPD position at `TACTICS_DEPTH`, in SAN
+fn solve(epd: &str) -> Result<Option<String>> {
+    let fen = epd_to_fen(epd).unwrap_or_default();
+    let mut engine = ChessEngine::from_fen(&fen)?;
+    engine.initialize()?;
+    engine.set_search_limits(TACTICS_DEPTH, None);
+    let Some(mv) = engine.search()?.best_move else {
+        return Ok(None);
+    };
+    Ok(Some(notation::move_to_san(
+        &GameState::from_fen(&fen)?,
+        mv,
+    )?))
+}
+
+/// Value of the EPD operation `opcode`, without its terminating semicolon
+fn epd_field<'a>(epd: &'a str, opcode: &str) -> Option<&'a str> {
+    // The operations follow the four position fields
+    let operations = epd.splitn(5, ' ').nth(4)?;
+    operations.split(';').find_map(|operation| {
+        let (name, value) = operation.trim().split_once(' ')?;
+        (name == opcode).then_some(value.trim())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selftest_passes() {
+        let report = run();
+        assert_eq!(report.cases.len(), PERFT_SUITE.len() + TACTICS_SUITE.len());
+        assert!(report.passed(), "{}", report);
+    }
+
+    #[test]
+    fn test_epd_field() {
+        let epd = TACTICS_SUITE[1];
+        assert_eq!(epd_field(epd, "bm"), Some("Qa8# Qg7#"));
+        assert_eq!(epd_field(epd, "id"), Some("\"queen mate\""));
+        assert_eq!(epd_field(epd, "am"), None);
+    }
+}
@@ -0,0 +1,301 @@
+// Export of evaluated positions in formats consumed by NNUE trainers
+
+use crate::{AnalysisResult, Color, EngineError, GameResult, PieceType, Position, Result};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Size of one record in the `BulletBinary` format
+pub const BULLET_RECORD_SIZE: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrainingFormat {
+    /// 32-byte records in bulletformat's `ChessBoard` layout, from the side to move's view
+    BulletBinary,
+    /// `<fen> | <score> | <result>` lines with White-relative score and 1.0/0.5/0.0 result
+    Text,
+}
+
+/// One position with its search score and the final result of its game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrainingSample {
+    pub position: Position,
+    /// Centipawns from White's point of view
+    pub score: i32,
+    /// Must be decided; ongoing games have no training target
+    pub result: GameResult,
+}
+
+impl TrainingSample {
+    /// Build a sample from a batch analysis, whose score is relative to the side to move
+    pub fn from_analysis(analysis: &AnalysisResult, result: GameResult) -> Result<Self> {
+        if let Some(error) = &analysis.error {
+            return Err(EngineError::InvalidState(error.clone()));
+        }
+        let position = Position::from_fen(&analysis.fen)?;
+        let score = match position.side_to_move() {
+            Color::White => analysis.evaluation,
+            Color::Black => -analysis.evaluation,
+        };
+        Ok(TrainingSample {
+            position,
+            score,
+            result,
+        })
+    }
+
+    /// Result as White's score: 1.0 win, 0.5 draw, 0.0 loss
+    fn white_result(&self) -> Result<f32> {
+        match self.result {
+            GameResult::WhiteWins => Ok(1.0),
+            GameResult::Draw => Ok(0.5),
+            GameResult::BlackWins => Ok(0.0),
+            GameResult::Ongoing => Err(EngineError::InvalidState(
+                "Training samples need a finished game result".to_string(),
+            )),
+        }
+    }
+
+    fn to_bullet_record(&self) -> Result<[u8; BULLET_RECORD_SIZE]> {
+        let stm = self.position.side_to_move();
+        let flip = stm == Color::Black;
+        // Vertical flip so the side to move always plays "up" the board
+        let orient = |bits: u64| if flip { bits.swap_bytes() } else { bits };
+
+        let ours = orient(self.position.pieces_of_color(stm).0);
+        let theirs = orient(self.position.pieces_of_color(stm.opposite()).0);
+        let occupied = ours | theirs;
+
+        let mut record = [0u8; BULLET_RECORD_SIZE];
+        record[0..8].copy_from_slice(&occupied.to_le_bytes());
+
+        let mut our_king = 0u8;
+        let mut their_king = 0u8;
+        let mut remaining = occupied;
+        let mut index = 0;
+        while remaining != 0 {
+            let square = remaining.trailing_zeros();
+            remaining &= remaining - 1;
+            let bit = 1u64 << square;
+
+            let colour = if ours & bit != 0 { 0 } else { 8 };
+            let piece = PieceType::ALL
+                .iter()
+                .position(|&piece_type| {
+                    let white = self.position.piece_bitboard(piece_type, Color::White).0;
+                    let black = self.position.piece_bitboard(piece_type, Color::Black).0;
+                    orient(white | black) & bit != 0
+                })
+                .unwrap_or(0) as u8;
+            if piece == PieceType::King as u8 {
+                if colour == 0 {
+                    our_king = square as u8;
+                } else {
+                    their_king = square as u8 ^ 56;
+                }
+            }
+
+            if index < 32 {
+                record[8 + index / 2] |= (colour | piece) << (4 * (index % 2));
+            }
+            index += 1;
+        }
+        if index > 32 {
+            return Err(EngineError::InvalidState(
+                "Position has more than 32 pieces".to_string(),
+            ));
+        }
+
+        let score = match stm {
+            Color::White => self.score,
+            Color::Black => -self.score,
+        }
+        .clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+        let white_result = self.white_result()?;
+        let stm_result = if flip {
+            1.0 - white_result
+        } else {
+            white_result
+        };
+
+        record[24..26].copy_from_slice(&score.to_le_bytes());
+        record[26] = (stm_result * 2.0) as u8;
+        record[27] = our_king;
+        record[28] = their_king;
+        Ok(record)
+    }
+
+    fn to_text_line(&self) -> Result<String> {
+        Ok(format!(
+            "{} | {} | {:.1}",
+            self.position.to_fen(),
+            self.score,
+            self.white_result()?
+        ))
+    }
+}
+
+/// Writes training samples to any byte sink in one of the supported formats
+pub struct TrainingExporter<W: Write> {
+    writer: W,
+    format: TrainingFormat,
+    samples_written: u64,
+}
+
+impl TrainingExporter<BufWriter<File>> {
+    pub fn create(path: impl AsRef<Path>, format: TrainingFormat) -> Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?), format))
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl TrainingExporter<zstd::stream::AutoFinishEncoder<'static, BufWriter<File>>> {
+    /// Write a zstd-compressed file; the frame is completed when the writer is dropped
+    pub fn create_zstd(path: impl AsRef<Path>, format: TrainingFormat, level: i32) -> Result<Self> {
+        let encoder = zstd::Encoder::new(BufWriter::new(File::create(path)?), level)?;
+        Ok(Self::new(encoder.auto_finish(), format))
+    }
+}
+
+impl<W: Write> TrainingExporter<W> {
+    pub fn new(writer: W, format: TrainingFormat) -> Self {
+        TrainingExporter {
+            writer,
+            format,
+            samples_written: 0,
+        }
+    }
+
+    pub fn format(&self) -> TrainingFormat {
+        self.format
+    }
+
+    pub fn samples_written(&self) -> u64 {
+        self.samples_written
+    }
+
+    pub fn write_sample(&mut self, sample: &TrainingSample) -> Result<()> {
+        match self.format {
+            TrainingFormat::BulletBinary => self.writer.write_all(&sample.to_bullet_record()?)?,
+            TrainingFormat::Text => writeln!(self.writer, "{}", sample.to_text_line()?)?,
+        }
+        self.samples_written += 1;
+        Ok(())
+    }
+
+    /// Write every scored position of one game, labelled with the game's result
+    pub fn write_game<'a>(
+        &mut self,
+        positions: impl IntoIterator<Item = (&'a Position, i32)>,
+        result: GameResult,
+    ) -> Result<()> {
+        for (position, score) in positions {
+            self.write_sample(&TrainingSample {
+                position: position.clone(),
+                score,
+                result,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Flush and return the underlying writer
+    pub fn finish(mut self) -> Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(fen: &str, score: i32, result: GameResult) -> TrainingSample {
+        TrainingSample {
+            position: Position::from_fen(fen).unwrap(),
+            score,
+            result,
+        }
+    }
+
+    #[test]
+    fn test_bullet_record_layout() {
+        let start = sample(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            35,
+            GameResult::WhiteWins,
+        );
+        let record = start.to_bullet_record().unwrap();
+
+        assert_eq!(
+            u64::from_le_bytes(record[0..8].try_into().unwrap()),
+            0xFFFF00000000FFFF
+        );
+        // a1 rook (3) and b1 knight (1) share the first byte; black pieces have bit 3 set
+        assert_eq!(record[8], 0x13);
+        assert_eq!(record[16], 0x88);
+        assert_eq!(record[20], 0x9B);
+        assert_eq!(i16::from_le_bytes([record[24], record[25]]), 35);
+        assert_eq!(record[26], 2);
+        assert_eq!(record[27], 4); // e1
+        assert_eq!(record[28], 4); // e8 flipped to e1
+    }
+
+    #[test]
+    fn test_black_to_move_is_mirrored() {
+        let white = sample(
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+            100,
+            GameResult::WhiteWins,
+        );
+        let black = sample(
+            "4k3/4p3/8/8/8/8/8/4K3 b - - 0 1",
+            -100,
+            GameResult::BlackWins,
+        );
+        assert_eq!(
+            white.to_bullet_record().unwrap(),
+            black.to_bullet_record().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_text_export_and_ongoing_rejection() {
+        let mut exporter = TrainingExporter::new(Vec::new(), TrainingFormat::Text);
+        let position = Position::starting_position();
+        exporter
+            .write_game([(&position, 20), (&position, -5)], GameResult::Draw)
+            .unwrap();
+        assert!(exporter
+            .write_sample(&sample(
+                "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+                0,
+                GameResult::Ongoing
+            ))
+            .is_err());
+        assert_eq!(exporter.samples_written(), 2);
+
+        let text = String::from_utf8(exporter.finish().unwrap()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines[0],
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 | 20 | 0.5"
+        );
+        assert!(lines[1].ends_with("| -5 | 0.5"));
+    }
+
+    #[test]
+    fn test_sample_from_analysis() {
+        let analysis = AnalysisResult {
+            fen: "4k3/8/8/8/8/8/4P3/4K3 b - - 0 1".to_string(),
+            best_move: None,
+            best_move_notation: None,
+            evaluation: -120,
+            depth: 4,
+            nodes: 10,
+            error: None,
+        };
+        let sample = TrainingSample::from_analysis(&analysis, GameResult::WhiteWins).unwrap();
+        assert_eq!(sample.score, 120);
+    }
+}
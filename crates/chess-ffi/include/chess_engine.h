@@ -0,0 +1,467 @@
+/* Chess Engine Rust C API. Generated by cbindgen; do not edit by hand. */
+
+#ifndef CHESS_ENGINE_H
+#define CHESS_ENGINE_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Events kept for a host that stops polling; the oldest are dropped beyond this
+#define MAX_QUEUED_EVENTS 1024
+
+// Version of this C interface, returned by `chess_engine_abi_version`. Bumped whenever
+// a function signature or a struct layout changes incompatibly; appending a function
+// keeps the version. Matches `chess_engine::ABI_VERSION`.
+#define CHESS_ENGINE_ABI_VERSION 1
+
+#define CHESS_MOVE_NORMAL 0
+
+#define CHESS_MOVE_CAPTURE 1
+
+#define CHESS_MOVE_EN_PASSANT 2
+
+#define CHESS_MOVE_CASTLE 3
+
+#define CHESS_MOVE_PROMOTION 4
+
+#define CHESS_MOVE_PROMOTION_CAPTURE 5
+
+// A piece from the hand in drop variants; `from` equals `to` and `promotion` names the piece
+#define CHESS_MOVE_DROP 6
+
+// Promotion value for moves that do not promote
+#define CHESS_PIECE_NONE 0
+
+#define CHESS_PIECE_KNIGHT 1
+
+#define CHESS_PIECE_BISHOP 2
+
+#define CHESS_PIECE_ROOK 3
+
+#define CHESS_PIECE_QUEEN 4
+
+// Only used for the piece of a `CHESS_MOVE_DROP`
+#define CHESS_PIECE_PAWN 5
+
+#define CHESS_MAX_PV_LENGTH 16
+
+// `chess_engine_find_best_cmove` failed: invalid handle, uninitialized engine or null output
+#define CHESS_BEST_MOVE_ERROR 0
+
+// `chess_engine_find_best_cmove` wrote the best move
+#define CHESS_BEST_MOVE_FOUND 1
+
+// `chess_engine_find_best_cmove` found the game already over and wrote its result
+#define CHESS_BEST_MOVE_GAME_OVER 2
+
+// Coordinate notation such as `g1f3`
+#define CHESS_NOTATION_UCI 0
+
+// Standard algebraic notation such as `Nf3`
+#define CHESS_NOTATION_SAN 1
+
+// Algebraic notation with piece glyphs such as `♘f3`, UTF-8 encoded
+#define CHESS_NOTATION_FIGURINE 2
+
+// Coordinate notation with castling as the king taking its rook (`e1h1`), for GUIs in
+// UCI_Chess960 mode; moves passed in may then castle that way too
+#define CHESS_NOTATION_UCI_CHESS960 3
+
+#define CHESS_EVENT_GAME_STARTED 1
+
+#define CHESS_EVENT_MOVE_MADE 2
+
+#define CHESS_EVENT_PIECE_CAPTURED 3
+
+#define CHESS_EVENT_CHECK 4
+
+#define CHESS_EVENT_CHECKMATE 5
+
+#define CHESS_EVENT_STALEMATE 6
+
+#define CHESS_EVENT_DRAW 7
+
+#define CHESS_EVENT_PROMOTION 8
+
+#define CHESS_EVENT_CASTLE 9
+
+#define CHESS_EVENT_EN_PASSANT 10
+
+#define CHESS_EVENT_GAME_ENDED 11
+
+// Blunder mode chose `mv` on purpose; `best_move` was best and `detail` is the loss
+// in centipawns
+#define CHESS_EVENT_DELIBERATE_MISTAKE 12
+
+#define CHESS_DRAW_STALEMATE 0
+
+#define CHESS_DRAW_FIFTY_MOVE_RULE 1
+
+#define CHESS_DRAW_THREEFOLD_REPETITION 2
+
+#define CHESS_DRAW_INSUFFICIENT_MATERIAL 3
+
+#define CHESS_DRAW_AGREEMENT 4
+
+#define CHESS_CASTLE_KINGSIDE 0
+
+#define CHESS_CASTLE_QUEENSIDE 1
+
+// Score `chess_engine_evaluate_fens` writes for a null or unparseable FEN
+#define CHESS_EVAL_INVALID_FEN INT32_MIN
+
+// The root score fit the first aspiration window of the last iteration
+#define CHESS_ROOT_STABLE 0
+
+// The root score failed high and was re-searched
+#define CHESS_ROOT_FAIL_HIGH 1
+
+// The root score failed low and was re-searched
+#define CHESS_ROOT_FAIL_LOW 2
+
+// Log level that passes nothing on
+#define CHESS_LOG_OFF 0
+
+#define CHESS_LOG_ERROR 1
+
+#define CHESS_LOG_WARN 2
+
+#define CHESS_LOG_INFO 3
+
+#define CHESS_LOG_DEBUG 4
+
+#define CHESS_LOG_TRACE 5
+
+typedef long EngineId;
+
+// A move with squares indexed 0 (a1) to 63 (h8)
+typedef struct CMove {
+  uint8_t from;
+  uint8_t to;
+  // One of the `CHESS_MOVE_*` constants
+  uint8_t move_type;
+  // One of the `CHESS_PIECE_*` constants
+  uint8_t promotion;
+} CMove;
+
+typedef struct CSearchResult {
+  struct CMove best_move;
+  // False when the side to move has no legal moves
+  bool has_best_move;
+  // Centipawns from the side to move's point of view
+  int evaluation;
+  uint8_t depth;
+  uint64_t nodes;
+  uint64_t elapsed_ms;
+  uint8_t pv_length;
+  struct CMove pv[CHESS_MAX_PV_LENGTH];
+  // Aspiration re-searches over the whole search
+  uint32_t aspiration_researches;
+  // One of the `CHESS_ROOT_*` constants; anything but stable marks a score still moving
+  int root_fail;
+  // Deepest ply reached, quiescence and extensions included
+  uint8_t seldepth;
+} CSearchResult;
+
+// Limits of one search; a zero field is unset. Times are in milliseconds.
+typedef struct CSearchLimits {
+  uint8_t depth;
+  uint64_t nodes;
+  uint64_t movetime_ms;
+  uint64_t wtime_ms;
+  uint64_t btime_ms;
+  uint64_t winc_ms;
+  uint64_t binc_ms;
+  uint32_t movestogo;
+  // Look for a mate in at most this many moves
+  uint8_t mate;
+  bool infinite;
+} CSearchLimits;
+
+// Live counters of a running (or the most recent) search
+typedef struct CSearchStatus {
+  uint8_t depth;
+  // Transposition table fill in permille
+  uint16_t hashfull;
+  uint64_t nodes;
+  uint64_t nps;
+  uint64_t tbhits;
+  uint64_t elapsed_ms;
+  // Deepest ply reached so far in the current iteration
+  uint8_t seldepth;
+} CSearchStatus;
+
+typedef struct CGameInfo {
+  // 0 for White, 1 for Black
+  int side_to_move;
+  bool is_check;
+  bool is_checkmate;
+  bool is_stalemate;
+  bool is_draw;
+  // Same codes as `chess_engine_get_game_result`
+  int result;
+  uint32_t halfmove_clock;
+  uint32_t fullmove_number;
+  uint32_t legal_move_count;
+  // How often the current position has occurred, itself included
+  uint32_t repetition_count;
+} CGameInfo;
+
+// A game event; fields that do not apply to `kind` are zero
+typedef struct CGameEvent {
+  // One of the `CHESS_EVENT_*` constants
+  int kind;
+  // The move played, for `CHESS_EVENT_MOVE_MADE` and `CHESS_EVENT_DELIBERATE_MISTAKE`
+  struct CMove mv;
+  // Square of the captured or promoted piece, or of the pawn taken en passant
+  uint8_t square;
+  // Captured or promoted piece, as a signed board code
+  int8_t piece;
+  // Side in check, winner of a checkmate or side castling: 0 for White, 1 for Black
+  int color;
+  // Game result code for `GAME_ENDED`, `CHESS_DRAW_*` for `DRAW`, `CHESS_CASTLE_*`,
+  // or centipawns lost for `DELIBERATE_MISTAKE`
+  int detail;
+  // The move the engine judged best, for `CHESS_EVENT_DELIBERATE_MISTAKE`
+  struct CMove best_move;
+} CGameEvent;
+
+// Function receiving each log record: its level (`CHESS_LOG_ERROR` to
+// `CHESS_LOG_TRACE`), the module that logged it, the message and the host's pointer.
+// The strings are only valid during the call.
+typedef void (*ChessLogCallback)(int, const char*, const char*, void*);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+EngineId chess_engine_create(void);
+
+// # Safety
+// The caller must ensure that `fen` points to a valid, null-terminated C string.
+EngineId chess_engine_create_from_fen(const char *fen);
+
+int chess_engine_initialize(EngineId engine_id);
+
+// Returns 1 if the engine was destroyed, 0 if the handle was stale or already destroyed
+int chess_engine_destroy(EngineId engine_id);
+
+// Returns 1 if `engine_id` refers to a live engine, 0 otherwise
+int chess_engine_exists(EngineId engine_id);
+
+char *chess_engine_get_fen(EngineId engine_id);
+
+// # Safety
+// The caller must ensure that `fen` points to a valid, null-terminated C string.
+int chess_engine_load_fen(EngineId engine_id, const char *fen);
+
+int chess_engine_get_side_to_move(EngineId engine_id);
+
+// # Safety
+// The caller must ensure that `uci_move` points to a valid, null-terminated C string.
+int chess_engine_make_move(EngineId engine_id, const char *uci_move);
+
+// # Safety
+// The caller must ensure that `uci_move` points to a valid, null-terminated C string.
+int chess_engine_is_legal_move(EngineId engine_id, const char *uci_move);
+
+int chess_engine_get_legal_moves_count(EngineId engine_id);
+
+int chess_engine_is_in_check(EngineId engine_id);
+
+int chess_engine_is_checkmate(EngineId engine_id);
+
+int chess_engine_is_game_over(EngineId engine_id);
+
+int chess_engine_get_game_result(EngineId engine_id);
+
+// `CHESS_DRAW_*` code of why the game is drawn, or -1 if it is not drawn or the
+// engine is unknown
+int chess_engine_get_draw_reason(EngineId engine_id);
+
+// End the game as a draw by agreement: 1 on success, 0 if the game is already over,
+// -1 if the engine is unknown. Undoing a move played before it withdraws the agreement.
+int chess_engine_agree_draw(EngineId engine_id);
+
+// How often the current position has occurred in the game, itself included; 3 or more
+// lets the player claim a draw. Returns -1 for an unknown engine.
+int chess_engine_get_position_repetition_count(EngineId engine_id);
+
+// Plies since the last capture or pawn move; 100 or more lets the player claim a
+// draw. Returns -1 for an unknown engine.
+int chess_engine_get_halfmove_clock(EngineId engine_id);
+
+// Static evaluation in centipawns, positive when the side to move stands better
+int chess_engine_evaluate(EngineId engine_id);
+
+// Static evaluation in centipawns, positive when White stands better
+int chess_engine_evaluate_absolute(EngineId engine_id);
+
+// The static evaluation split into its terms, as a JSON object such as
+// `{"material":100,"king_safety":-40,...,"total":75,"side_to_move_total":-75}`.
+// Terms and `total` are centipawns from White's point of view. Returns null for an
+// unknown engine; free the result with `chess_engine_free_string`.
+char *chess_engine_evaluate_detailed_json(EngineId engine_id);
+
+// Statically evaluate `count` FENs on a shared multi-threaded evaluator, writing the
+// score of `fens[i]` to `out_scores[i]` in centipawns for its side to move. Null or
+// unparseable FENs score `CHESS_EVAL_INVALID_FEN`. Needs no engine handle; the worker
+// threads and evaluation cache are kept between calls. Returns the number of FENs
+// evaluated, or -1 when `fens` or `out_scores` is null.
+//
+// # Safety
+// `fens` must point to `count` pointers that are null or valid, null-terminated C strings,
+// and `out_scores` to `count` writable `int32_t`s.
+int chess_engine_evaluate_fens(const char *const *fens, size_t count, int32_t *out_scores);
+
+// Run the built-in perft and tactics self-test, to check that this build of the
+// library works on the platform. Returns 1 when every case passed, 0 when one failed
+// and -1 if the test could not run. When `report` is not null it receives one
+// `PASS`/`FAIL` line per case and a summary, to free with `chess_engine_free_string`.
+//
+// # Safety
+// `report` must be null or point to a writable `char *`.
+int chess_engine_selftest(char **report);
+
+// The `CHESS_ENGINE_ABI_VERSION` the library was built with. Hosts compare it with the
+// value in the header they were compiled against and refuse to run on a mismatch.
+uint32_t chess_engine_abi_version(void);
+
+char *chess_engine_find_best_move(EngineId engine_id);
+
+// Choose the `CHESS_NOTATION_*` style of `chess_engine_format_move`; returns 0 for an
+// unknown engine or style
+int chess_engine_set_notation_style(EngineId engine_id, int style);
+
+// A legal UCI move of the current position in standard algebraic notation, or null.
+// Free the result with `chess_engine_free_string`.
+//
+// # Safety
+// `uci_move` must point to a valid, null-terminated C string.
+char *chess_engine_move_to_san(EngineId engine_id, const char *uci_move);
+
+// A legal UCI move of the current position in the engine's notation style, or null.
+// Free the result with `chess_engine_free_string`.
+//
+// # Safety
+// `uci_move` must point to a valid, null-terminated C string.
+char *chess_engine_format_move(EngineId engine_id, const char *uci_move);
+
+// Search for the best move, telling a finished game apart from a failure.
+//
+// Returns `CHESS_BEST_MOVE_FOUND` with the move in `out`, `CHESS_BEST_MOVE_GAME_OVER`
+// with the `chess_engine_get_game_result` code in `result` when the game has already
+// ended, or `CHESS_BEST_MOVE_ERROR`.
+//
+// # Safety
+// `out` must point to a writable `CMove`; `result` must be writable or null.
+int chess_engine_find_best_cmove(EngineId engine_id, struct CMove *out, int *result);
+
+// Copy up to `capacity` legal moves into `out` and return the total number of legal
+// moves, or -1 for an invalid handle. Call with `capacity` 0 to query the count.
+// Moves are sorted by from-square, then to-square, so the order is stable across calls.
+//
+// # Safety
+// `out` must point to at least `capacity` writable `CMove`s, or be null when `capacity` is 0.
+int chess_engine_get_legal_moves(EngineId engine_id, struct CMove *out, size_t capacity);
+
+// Like `chess_engine_get_legal_moves`, for the moves of the side to move's piece on
+// `square` (0 for a1 to 63 for h8) only; 0 when it has no piece there and -1 for an
+// invalid handle or square
+//
+// # Safety
+// `out` must point to at least `capacity` writable `CMove`s, or be null when `capacity` is 0.
+int chess_engine_get_legal_moves_from(EngineId engine_id,
+                                      uint8_t square,
+                                      struct CMove *out,
+                                      size_t capacity);
+
+// Play a move given as a `CMove`; only `from`, `to` and `promotion` are read
+int chess_engine_make_cmove(EngineId engine_id, struct CMove mv);
+
+// # Safety
+// `out` must point to a writable `CSearchResult`.
+int chess_engine_search(EngineId engine_id, struct CSearchResult *out);
+
+// Search once with `limits` instead of the configured depth and time limit
+//
+// # Safety
+// `limits` must point to a readable `CSearchLimits` and `out` to a writable
+// `CSearchResult`.
+int chess_engine_search_with_limits(EngineId engine_id,
+                                    const struct CSearchLimits *limits,
+                                    struct CSearchResult *out);
+
+// Read search progress; safe to call from another thread while `chess_engine_search` runs.
+//
+// # Safety
+// `out` must point to a writable `CSearchStatus`.
+int chess_engine_get_search_status(EngineId engine_id, struct CSearchStatus *out);
+
+// Set the search depth (clamped to 1-64) and time limit for later searches;
+// a `time_limit_ms` of 0 means no time limit
+int chess_engine_configure(EngineId engine_id, int depth, uint64_t time_limit_ms);
+
+// Store limits that every later search honours, `chess_engine_find_best_move`
+// included, so hosts configure once instead of passing limits per call. A `depth` of 0
+// keeps the engine's configured depth, a `movetime_ms` or `nodes` of 0 sets no such
+// limit, and a `hash_mb` of 0 keeps the table size. Changing `hash_mb` clears the
+// transposition table. Searches run on the calling thread.
+int chess_engine_set_limits(EngineId engine_id,
+                            int depth,
+                            uint64_t movetime_ms,
+                            uint64_t nodes,
+                            int hash_mb);
+
+// Write the board as 64 bytes indexed by square (a1 = 0): 0 for empty, 1-6 for White
+// pawn through king, and -1 to -6 for the Black pieces.
+//
+// # Safety
+// `out` must point to at least 64 writable bytes.
+int chess_engine_get_board(EngineId engine_id, int8_t *out);
+
+// # Safety
+// `out` must point to a writable `CGameInfo`.
+int chess_engine_get_game_info(EngineId engine_id, struct CGameInfo *out);
+
+// Take the oldest pending game event of the engine: moves, captures, checks,
+// promotions, castling, draws and the game's end, in the order they happened.
+// Returns 1 when an event was written, 0 when none is pending and -1 for an unknown
+// engine or a null `out`. Up to 1024 unread events are kept.
+//
+// # Safety
+// `out` must point to a writable `CGameEvent`.
+int chess_engine_poll_event(EngineId engine_id, struct CGameEvent *out);
+
+// Message of the last panic caught on this thread, or null if none occurred.
+// Reading it clears it; free the result with `chess_engine_free_string`.
+char *chess_engine_take_last_panic(void);
+
+// Pass the engine's log records to `callback` along with `user_data`, or stop passing
+// them on with a null callback. Records come from any thread, so the callback must be
+// thread-safe. Returns 1 on success, 0 if the library was built without the `log`
+// feature and -1 if the process already has another logger.
+int chess_engine_set_log_callback(ChessLogCallback callback, void *user_data);
+
+// Set the level (`CHESS_LOG_OFF` to `CHESS_LOG_TRACE`) of the records passed to the
+// log callback from `module` and the modules inside it, such as `chess_core::search`,
+// or of every other module when `module` is null. Without any call the level is
+// `CHESS_LOG_INFO`. Returns 1 on success, 0 if the library was built without the `log`
+// feature and -1 for an unknown level.
+//
+// # Safety
+// `module` must be null or point to a valid, null-terminated C string.
+int chess_engine_set_log_level(const char *module, int level);
+
+// # Safety
+// The caller must ensure that `s` was allocated by this library and is not used after this call.
+void chess_engine_free_string(char *s);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CHESS_ENGINE_H */
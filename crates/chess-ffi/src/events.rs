@@ -0,0 +1,58 @@
+// Per-engine queue of game events for hosts that poll instead of taking callbacks
+// The queue is installed as the engine's event handler when the engine is registered,
+// and read without locking the engine, so events can be drained during a search
+
+use chess_engine::{EventHandler, GameEvent};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Events kept for a host that stops polling; the oldest are dropped beyond this
+pub const MAX_QUEUED_EVENTS: usize = 1024;
+
+#[derive(Default)]
+pub struct EventQueue {
+    events: VecDeque<GameEvent>,
+}
+
+impl EventQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pop(&mut self) -> Option<GameEvent> {
+        self.events.pop_front()
+    }
+}
+
+impl EventHandler for EventQueue {
+    fn handle_event(&mut self, event: &GameEvent) {
+        if self.events.len() == MAX_QUEUED_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event.clone());
+    }
+}
+
+pub type SharedEventQueue = Arc<Mutex<EventQueue>>;
+
+pub fn pop_event(queue: &SharedEventQueue) -> Option<GameEvent> {
+    queue.lock().unwrap_or_else(PoisonError::into_inner).pop()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_drops_oldest_events() {
+        let mut queue = EventQueue::new();
+        queue.handle_event(&GameEvent::Stalemate);
+        for _ in 0..MAX_QUEUED_EVENTS {
+            queue.handle_event(&GameEvent::GameStarted);
+        }
+        // The stalemate was pushed out by the newer events
+        let drained = std::iter::from_fn(|| queue.pop()).collect::<Vec<_>>();
+        assert_eq!(drained.len(), MAX_QUEUED_EVENTS);
+        assert!(!drained.contains(&GameEvent::Stalemate));
+    }
+}
@@ -1,144 +1,181 @@
-use chess_core::{Move, PieceType, Square};
-use chess_engine::{ChessEngine, Color, GameResult};
-use std::collections::HashMap;
-use std::ffi::{CStr, CString};
-use std::os::raw::{c_char, c_int, c_long};
-use std::str::FromStr;
-use std::sync::{
-    atomic::{AtomicI64, Ordering},
-    Arc, Mutex, OnceLock,
-};
+use chess_core::search::engine::MAX_DEPTH;
+use chess_core::search::{ParallelConfig, ParallelEvaluator};
+use chess_engine::{BestMove, ChessEngine, Position, SearchLimits, Square};
+use std::cell::RefCell;
+use std::ffi::{c_void, CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+use std::time::Duration;
+
+mod events;
+#[cfg(feature = "log")]
+mod logging;
+mod registry;
+mod types;
+
+pub use registry::EngineId;
+use registry::{EngineRegistry, SharedEngine};
+pub use types::*;
 
 #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
 use wasm_bindgen::prelude::*;
 
-type EngineId = c_long;
-static ENGINE_COUNTER: AtomicI64 = AtomicI64::new(0);
-static ENGINES: OnceLock<Arc<Mutex<HashMap<EngineId, ChessEngine>>>> = OnceLock::new();
+static ENGINES: OnceLock<Arc<Mutex<EngineRegistry>>> = OnceLock::new();
 
-fn get_engines() -> &'static Arc<Mutex<HashMap<EngineId, ChessEngine>>> {
-    ENGINES.get_or_init(|| Arc::new(Mutex::new(HashMap::new())))
+fn get_engines() -> &'static Arc<Mutex<EngineRegistry>> {
+    ENGINES.get_or_init(|| Arc::new(Mutex::new(EngineRegistry::new())))
 }
 
-fn get_next_id() -> EngineId {
-    (ENGINE_COUNTER.fetch_add(1, Ordering::Relaxed) + 1) as EngineId
+/// Lock the engine registry, recovering it if a caught panic poisoned the lock
+fn lock_engines() -> MutexGuard<'static, EngineRegistry> {
+    get_engines().lock().unwrap_or_else(PoisonError::into_inner)
 }
 
-// C FFI Functions
+fn get_engine(engine_id: EngineId) -> Option<SharedEngine> {
+    lock_engines().get(engine_id)
+}
 
-#[no_mangle]
-pub extern "C" fn chess_engine_create() -> EngineId {
-    let engine = ChessEngine::new();
-    let id = get_next_id();
+fn lock_engine(engine: &Mutex<ChessEngine>) -> MutexGuard<'_, ChessEngine> {
+    engine.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+thread_local! {
+    static LAST_PANIC: RefCell<Option<String>> = const { RefCell::new(None) };
+}
 
-    let engines = get_engines();
-    if let Ok(mut engines_map) = engines.lock() {
-        engines_map.insert(id, engine);
+/// Run an entry point, turning a panic into `fallback` instead of unwinding into the host
+fn guard<T>(fallback: T, f: impl FnOnce() -> T) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(value) => value,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            chess_core::log_error!("panic caught at the C API: {}", message);
+            LAST_PANIC.with(|last| *last.borrow_mut() = Some(message));
+            fallback
+        }
     }
+}
+
+// C FFI Functions
 
-    id
+#[no_mangle]
+pub extern "C" fn chess_engine_create() -> EngineId {
+    guard(-1, || lock_engines().insert(ChessEngine::new()))
 }
 
 /// # Safety
 /// The caller must ensure that `fen` points to a valid, null-terminated C string.
 #[no_mangle]
 pub unsafe extern "C" fn chess_engine_create_from_fen(fen: *const c_char) -> EngineId {
-    if fen.is_null() {
-        return -1;
-    }
+    guard(-1, || {
+        if fen.is_null() {
+            return -1;
+        }
 
-    let fen_str = match CStr::from_ptr(fen).to_str() {
-        Ok(s) => s,
-        Err(_) => return -1,
-    };
+        let fen_str = match CStr::from_ptr(fen).to_str() {
+            Ok(s) => s,
+            Err(_) => return -1,
+        };
 
-    match ChessEngine::from_fen(fen_str) {
-        Ok(engine) => {
-            let id = get_next_id();
-            let engines = get_engines();
-            if let Ok(mut engines_map) = engines.lock() {
-                engines_map.insert(id, engine);
-            }
-            id
+        match ChessEngine::from_fen(fen_str) {
+            Ok(engine) => lock_engines().insert(engine),
+            Err(_) => -1,
         }
-        Err(_) => -1,
-    }
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn chess_engine_initialize(engine_id: EngineId) -> c_int {
-    let engines = get_engines();
-    if let Ok(mut engines_map) = engines.lock() {
-        if let Some(engine) = engines_map.get_mut(&engine_id) {
+    guard(0, || {
+        if let Some(engine) = get_engine(engine_id) {
+            let mut engine = lock_engine(&engine);
             return match engine.initialize() {
                 Ok(_) => 1,
                 Err(_) => 0,
             };
         }
-    }
-    0
+        0
+    })
 }
 
+/// Returns 1 if the engine was destroyed, 0 if the handle was stale or already destroyed
 #[no_mangle]
-pub extern "C" fn chess_engine_destroy(engine_id: EngineId) {
-    let engines = get_engines();
-    if let Ok(mut engines_map) = engines.lock() {
-        engines_map.remove(&engine_id);
-    }
+pub extern "C" fn chess_engine_destroy(engine_id: EngineId) -> c_int {
+    guard(0, || {
+        if lock_engines().remove(engine_id).is_some() {
+            1
+        } else {
+            0
+        }
+    })
+}
+
+/// Returns 1 if `engine_id` refers to a live engine, 0 otherwise
+#[no_mangle]
+pub extern "C" fn chess_engine_exists(engine_id: EngineId) -> c_int {
+    guard(0, || {
+        if lock_engines().contains(engine_id) {
+            1
+        } else {
+            0
+        }
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn chess_engine_get_fen(engine_id: EngineId) -> *mut c_char {
-    let engines = get_engines();
-    if let Ok(engines_map) = engines.lock() {
-        if let Some(engine) = engines_map.get(&engine_id) {
+    guard(std::ptr::null_mut(), || {
+        if let Some(engine) = get_engine(engine_id) {
+            let engine = lock_engine(&engine);
             let fen = engine.get_fen();
             return match CString::new(fen) {
                 Ok(cstring) => cstring.into_raw(),
                 Err(_) => std::ptr::null_mut(),
             };
         }
-    }
-    std::ptr::null_mut()
+        std::ptr::null_mut()
+    })
 }
 
 /// # Safety
 /// The caller must ensure that `fen` points to a valid, null-terminated C string.
 #[no_mangle]
 pub unsafe extern "C" fn chess_engine_load_fen(engine_id: EngineId, fen: *const c_char) -> c_int {
-    if fen.is_null() {
-        return 0;
-    }
+    guard(0, || {
+        if fen.is_null() {
+            return 0;
+        }
 
-    let fen_str = match CStr::from_ptr(fen).to_str() {
-        Ok(s) => s,
-        Err(_) => return 0,
-    };
+        let fen_str = match CStr::from_ptr(fen).to_str() {
+            Ok(s) => s,
+            Err(_) => return 0,
+        };
 
-    let engines = get_engines();
-    if let Ok(mut engines_map) = engines.lock() {
-        if let Some(engine) = engines_map.get_mut(&engine_id) {
+        if let Some(engine) = get_engine(engine_id) {
+            let mut engine = lock_engine(&engine);
             return match engine.load_fen(fen_str) {
                 Ok(_) => 1,
                 Err(_) => 0,
             };
         }
-    }
-    0
+        0
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn chess_engine_get_side_to_move(engine_id: EngineId) -> c_int {
-    let engines = get_engines();
-    if let Ok(engines_map) = engines.lock() {
-        if let Some(engine) = engines_map.get(&engine_id) {
-            return match engine.get_side_to_move() {
-                Color::White => 0,
-                Color::Black => 1,
-            };
+    guard(-1, || {
+        if let Some(engine) = get_engine(engine_id) {
+            let engine = lock_engine(&engine);
+            return color_code(engine.get_side_to_move());
         }
-    }
-    -1
+        -1
+    })
 }
 
 /// # Safety
@@ -148,18 +185,18 @@ pub unsafe extern "C" fn chess_engine_make_move(
     engine_id: EngineId,
     uci_move: *const c_char,
 ) -> c_int {
-    if uci_move.is_null() {
-        return 0;
-    }
+    guard(0, || {
+        if uci_move.is_null() {
+            return 0;
+        }
 
-    let uci_str = match CStr::from_ptr(uci_move).to_str() {
-        Ok(s) => s,
-        Err(_) => return 0,
-    };
+        let uci_str = match CStr::from_ptr(uci_move).to_str() {
+            Ok(s) => s,
+            Err(_) => return 0,
+        };
 
-    let engines = get_engines();
-    if let Ok(mut engines_map) = engines.lock() {
-        if let Some(engine) = engines_map.get_mut(&engine_id) {
+        if let Some(engine) = get_engine(engine_id) {
+            let mut engine = lock_engine(&engine);
             return match engine.make_move_from_uci(uci_str) {
                 Ok(result) => {
                     if result.success {
@@ -171,8 +208,8 @@ pub unsafe extern "C" fn chess_engine_make_move(
                 Err(_) => 0,
             };
         }
-    }
-    0
+        0
+    })
 }
 
 /// # Safety
@@ -182,129 +219,286 @@ pub unsafe extern "C" fn chess_engine_is_legal_move(
     engine_id: EngineId,
     uci_move: *const c_char,
 ) -> c_int {
-    if uci_move.is_null() {
-        return 0;
-    }
-
-    let uci_str = match CStr::from_ptr(uci_move).to_str() {
-        Ok(s) => s,
-        Err(_) => return 0,
-    };
+    guard(0, || {
+        if uci_move.is_null() {
+            return 0;
+        }
 
-    let engines = get_engines();
-    if let Ok(engines_map) = engines.lock() {
-        if let Some(engine) = engines_map.get(&engine_id) {
-            // Parse UCI string (e.g., "e2e4" or "e7e8q")
-            if uci_str.len() < 4 {
-                return 0;
-            }
-
-            let from = Square::from_str(&uci_str[0..2]);
-            let to = Square::from_str(&uci_str[2..4]);
-
-            match (from, to) {
-                (Ok(from), Ok(to)) => {
-                    // Handle promotion
-                    let mv = if uci_str.len() == 5 {
-                        match uci_str.chars().nth(4) {
-                            Some('q') => Move::promotion(from, to, PieceType::Queen),
-                            Some('r') => Move::promotion(from, to, PieceType::Rook),
-                            Some('b') => Move::promotion(from, to, PieceType::Bishop),
-                            Some('n') => Move::promotion(from, to, PieceType::Knight),
-                            _ => return 0,
-                        }
-                    } else {
-                        Move::normal(from, to)
-                    };
+        let uci_str = match CStr::from_ptr(uci_move).to_str() {
+            Ok(s) => s,
+            Err(_) => return 0,
+        };
 
-                    return if engine.is_legal_move(mv) { 1 } else { 0 };
-                }
-                _ => return 0,
-            }
+        if let Some(engine) = get_engine(engine_id) {
+            let engine = lock_engine(&engine);
+            return if engine.resolve_uci_move(uci_str).is_ok() {
+                1
+            } else {
+                0
+            };
         }
-    }
-    0
+        0
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn chess_engine_get_legal_moves_count(engine_id: EngineId) -> c_int {
-    let engines = get_engines();
-    if let Ok(engines_map) = engines.lock() {
-        if let Some(engine) = engines_map.get(&engine_id) {
+    guard(-1, || {
+        if let Some(engine) = get_engine(engine_id) {
+            let engine = lock_engine(&engine);
             return engine.get_legal_moves().len() as c_int;
         }
-    }
-    -1
+        -1
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn chess_engine_is_in_check(engine_id: EngineId) -> c_int {
-    let engines = get_engines();
-    if let Ok(engines_map) = engines.lock() {
-        if let Some(engine) = engines_map.get(&engine_id) {
-            let info = engine.get_game_info();
-            return if info.is_check { 1 } else { 0 };
+    guard(0, || {
+        if let Some(engine) = get_engine(engine_id) {
+            let engine = lock_engine(&engine);
+            return if engine.is_in_check() { 1 } else { 0 };
         }
-    }
-    0
+        0
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn chess_engine_is_checkmate(engine_id: EngineId) -> c_int {
-    let engines = get_engines();
-    if let Ok(engines_map) = engines.lock() {
-        if let Some(engine) = engines_map.get(&engine_id) {
-            let info = engine.get_game_info();
-            return if info.is_checkmate { 1 } else { 0 };
+    guard(0, || {
+        if let Some(engine) = get_engine(engine_id) {
+            let engine = lock_engine(&engine);
+            return if engine.is_checkmate() { 1 } else { 0 };
         }
-    }
-    0
+        0
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn chess_engine_is_game_over(engine_id: EngineId) -> c_int {
-    let engines = get_engines();
-    if let Ok(engines_map) = engines.lock() {
-        if let Some(engine) = engines_map.get(&engine_id) {
+    guard(0, || {
+        if let Some(engine) = get_engine(engine_id) {
+            let engine = lock_engine(&engine);
             return if engine.is_game_over() { 1 } else { 0 };
         }
-    }
-    0
+        0
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn chess_engine_get_game_result(engine_id: EngineId) -> c_int {
-    let engines = get_engines();
-    if let Ok(engines_map) = engines.lock() {
-        if let Some(engine) = engines_map.get(&engine_id) {
-            return match engine.get_game_result() {
-                GameResult::Ongoing => 0,
-                GameResult::WhiteWins => 1,
-                GameResult::BlackWins => 2,
-                GameResult::Draw => 3,
-            };
+    guard(-1, || {
+        if let Some(engine) = get_engine(engine_id) {
+            let engine = lock_engine(&engine);
+            return game_result_code(engine.get_game_result());
         }
-    }
-    -1
+        -1
+    })
 }
 
+/// `CHESS_DRAW_*` code of why the game is drawn, or -1 if it is not drawn or the
+/// engine is unknown
+#[no_mangle]
+pub extern "C" fn chess_engine_get_draw_reason(engine_id: EngineId) -> c_int {
+    guard(-1, || {
+        if let Some(engine) = get_engine(engine_id) {
+            let engine = lock_engine(&engine);
+            return engine.get_draw_reason().map_or(-1, draw_reason_code);
+        }
+        -1
+    })
+}
+
+/// End the game as a draw by agreement: 1 on success, 0 if the game is already over,
+/// -1 if the engine is unknown. Undoing a move played before it withdraws the agreement.
+#[no_mangle]
+pub extern "C" fn chess_engine_agree_draw(engine_id: EngineId) -> c_int {
+    guard(-1, || {
+        if let Some(engine) = get_engine(engine_id) {
+            let mut engine = lock_engine(&engine);
+            return engine.agree_draw().is_ok() as c_int;
+        }
+        -1
+    })
+}
+
+/// How often the current position has occurred in the game, itself included; 3 or more
+/// lets the player claim a draw. Returns -1 for an unknown engine.
+#[no_mangle]
+pub extern "C" fn chess_engine_get_position_repetition_count(engine_id: EngineId) -> c_int {
+    guard(-1, || {
+        if let Some(engine) = get_engine(engine_id) {
+            let engine = lock_engine(&engine);
+            return engine.get_repetition_count() as c_int;
+        }
+        -1
+    })
+}
+
+/// Plies since the last capture or pawn move; 100 or more lets the player claim a
+/// draw. Returns -1 for an unknown engine.
+#[no_mangle]
+pub extern "C" fn chess_engine_get_halfmove_clock(engine_id: EngineId) -> c_int {
+    guard(-1, || {
+        if let Some(engine) = get_engine(engine_id) {
+            let engine = lock_engine(&engine);
+            return engine.get_halfmove_clock() as c_int;
+        }
+        -1
+    })
+}
+
+/// Static evaluation in centipawns, positive when the side to move stands better
 #[no_mangle]
 pub extern "C" fn chess_engine_evaluate(engine_id: EngineId) -> c_int {
-    let engines = get_engines();
-    if let Ok(engines_map) = engines.lock() {
-        if let Some(engine) = engines_map.get(&engine_id) {
+    guard(0, || {
+        if let Some(engine) = get_engine(engine_id) {
+            let engine = lock_engine(&engine);
             return engine.evaluate();
         }
+        0
+    })
+}
+
+/// Static evaluation in centipawns, positive when White stands better
+#[no_mangle]
+pub extern "C" fn chess_engine_evaluate_absolute(engine_id: EngineId) -> c_int {
+    guard(0, || {
+        if let Some(engine) = get_engine(engine_id) {
+            let engine = lock_engine(&engine);
+            return engine.evaluate_absolute();
+        }
+        0
+    })
+}
+
+/// The static evaluation split into its terms, as a JSON object such as
+/// `{"material":100,"king_safety":-40,...,"total":75,"side_to_move_total":-75}`.
+/// Terms and `total` are centipawns from White's point of view. Returns null for an
+/// unknown engine; free the result with `chess_engine_free_string`.
+#[no_mangle]
+pub extern "C" fn chess_engine_evaluate_detailed_json(engine_id: EngineId) -> *mut c_char {
+    guard(std::ptr::null_mut(), || {
+        let Some(engine) = get_engine(engine_id) else {
+            return std::ptr::null_mut();
+        };
+        let engine = lock_engine(&engine);
+        let json = breakdown_json(
+            &engine.get_evaluation_breakdown(),
+            engine.get_side_to_move(),
+        );
+        CString::new(json).map_or(std::ptr::null_mut(), CString::into_raw)
+    })
+}
+
+/// FENs parsed and evaluated together, bounding the positions held at once
+const EVALUATE_FENS_CHUNK: usize = 4096;
+
+/// Evaluator behind `chess_engine_evaluate_fens`, kept for the whole process so its
+/// thread pool and evaluation cache are reused across calls
+fn bulk_evaluator() -> &'static ParallelEvaluator {
+    static EVALUATOR: OnceLock<ParallelEvaluator> = OnceLock::new();
+    EVALUATOR.get_or_init(|| ParallelEvaluator::new(ParallelConfig::default()))
+}
+
+/// Score each FEN from its side to move's point of view, passing `write` the index and
+/// score, or `CHESS_EVAL_INVALID_FEN` for a FEN that is missing or does not parse.
+/// Returns the number of FENs evaluated.
+fn score_fens<'a>(
+    fens: impl IntoIterator<Item = Option<&'a str>>,
+    mut write: impl FnMut(usize, i32),
+) -> usize {
+    let evaluator = bulk_evaluator();
+    let mut fens = fens.into_iter().enumerate().peekable();
+    let mut evaluated = 0;
+    while fens.peek().is_some() {
+        let mut indices = Vec::with_capacity(EVALUATE_FENS_CHUNK);
+        let mut positions = Vec::with_capacity(EVALUATE_FENS_CHUNK);
+        for (index, fen) in fens.by_ref().take(EVALUATE_FENS_CHUNK) {
+            match fen.and_then(|fen| Position::from_fen(fen).ok()) {
+                Some(position) => {
+                    indices.push(index);
+                    positions.push(position);
+                }
+                None => write(index, CHESS_EVAL_INVALID_FEN),
+            }
+        }
+        for (index, score) in indices.into_iter().zip(evaluator.bulk_evaluate(&positions)) {
+            write(index, score);
+        }
+        evaluated += positions.len();
     }
-    0
+    evaluated
+}
+
+/// Statically evaluate `count` FENs on a shared multi-threaded evaluator, writing the
+/// score of `fens[i]` to `out_scores[i]` in centipawns for its side to move. Null or
+/// unparseable FENs score `CHESS_EVAL_INVALID_FEN`. Needs no engine handle; the worker
+/// threads and evaluation cache are kept between calls. Returns the number of FENs
+/// evaluated, or -1 when `fens` or `out_scores` is null.
+///
+/// # Safety
+/// `fens` must point to `count` pointers that are null or valid, null-terminated C strings,
+/// and `out_scores` to `count` writable `int32_t`s.
+#[no_mangle]
+pub unsafe extern "C" fn chess_engine_evaluate_fens(
+    fens: *const *const c_char,
+    count: usize,
+    out_scores: *mut i32,
+) -> c_int {
+    guard(-1, || {
+        if count == 0 {
+            return 0;
+        }
+        if fens.is_null() || out_scores.is_null() {
+            return -1;
+        }
+
+        let fens = std::slice::from_raw_parts(fens, count);
+        let scores = std::slice::from_raw_parts_mut(out_scores, count);
+        let fens = fens.iter().map(|&fen| {
+            (!fen.is_null())
+                .then(|| CStr::from_ptr(fen).to_str().ok())
+                .flatten()
+        });
+        let evaluated = score_fens(fens, |index, score| scores[index] = score);
+        c_int::try_from(evaluated).unwrap_or(c_int::MAX)
+    })
+}
+
+/// Run the built-in perft and tactics self-test, to check that this build of the
+/// library works on the platform. Returns 1 when every case passed, 0 when one failed
+/// and -1 if the test could not run. When `report` is not null it receives one
+/// `PASS`/`FAIL` line per case and a summary, to free with `chess_engine_free_string`.
+///
+/// # Safety
+/// `report` must be null or point to a writable `char *`.
+#[no_mangle]
+pub unsafe extern "C" fn chess_engine_selftest(report: *mut *mut c_char) -> c_int {
+    guard(-1, || {
+        let result = ChessEngine::selftest();
+        if !report.is_null() {
+            report.write(
+                CString::new(result.to_string()).map_or(std::ptr::null_mut(), CString::into_raw),
+            );
+        }
+        c_int::from(result.passed())
+    })
+}
+
+/// The `CHESS_ENGINE_ABI_VERSION` the library was built with. Hosts compare it with the
+/// value in the header they were compiled against and refuse to run on a mismatch.
+#[no_mangle]
+pub extern "C" fn chess_engine_abi_version() -> u32 {
+    CHESS_ENGINE_ABI_VERSION
 }
 
 #[no_mangle]
 pub extern "C" fn chess_engine_find_best_move(engine_id: EngineId) -> *mut c_char {
-    let engines = get_engines();
-    if let Ok(mut engines_map) = engines.lock() {
-        if let Some(engine) = engines_map.get_mut(&engine_id) {
-            if let Ok(Some(best_move)) = engine.find_best_move() {
+    guard(std::ptr::null_mut(), || {
+        if let Some(engine) = get_engine(engine_id) {
+            let mut engine = lock_engine(&engine);
+            if let Ok(BestMove::Move(best_move)) = engine.find_best_move() {
                 let move_str = best_move.to_string();
                 return match CString::new(move_str) {
                     Ok(cstring) => cstring.into_raw(),
@@ -312,27 +506,514 @@ pub extern "C" fn chess_engine_find_best_move(engine_id: EngineId) -> *mut c_cha
                 };
             }
         }
+        std::ptr::null_mut()
+    })
+}
+
+/// Choose the `CHESS_NOTATION_*` style of `chess_engine_format_move`; returns 0 for an
+/// unknown engine or style
+#[no_mangle]
+pub extern "C" fn chess_engine_set_notation_style(engine_id: EngineId, style: c_int) -> c_int {
+    guard(0, || {
+        if let (Some(engine), Some(style)) = (get_engine(engine_id), notation_style(style)) {
+            lock_engine(&engine).set_notation_style(style);
+            return 1;
+        }
+        0
+    })
+}
+
+/// Write a legal UCI move of the current position with `format`, returning an owned
+/// C string or null
+unsafe fn write_move(
+    engine_id: EngineId,
+    uci_move: *const c_char,
+    format: impl Fn(&ChessEngine, chess_engine::Move) -> Option<String>,
+) -> *mut c_char {
+    if uci_move.is_null() {
+        return std::ptr::null_mut();
+    }
+    let Ok(uci_str) = CStr::from_ptr(uci_move).to_str() else {
+        return std::ptr::null_mut();
+    };
+
+    if let Some(engine) = get_engine(engine_id) {
+        let engine = lock_engine(&engine);
+        let written = engine
+            .resolve_uci_move(uci_str)
+            .ok()
+            .and_then(|mv| format(&engine, mv));
+        if let Some(cstring) = written.and_then(|text| CString::new(text).ok()) {
+            return cstring.into_raw();
+        }
     }
     std::ptr::null_mut()
 }
 
+/// A legal UCI move of the current position in standard algebraic notation, or null.
+/// Free the result with `chess_engine_free_string`.
+///
+/// # Safety
+/// `uci_move` must point to a valid, null-terminated C string.
+#[no_mangle]
+pub unsafe extern "C" fn chess_engine_move_to_san(
+    engine_id: EngineId,
+    uci_move: *const c_char,
+) -> *mut c_char {
+    guard(std::ptr::null_mut(), || {
+        write_move(engine_id, uci_move, |engine, mv| {
+            engine.move_to_san(mv).ok()
+        })
+    })
+}
+
+/// A legal UCI move of the current position in the engine's notation style, or null.
+/// Free the result with `chess_engine_free_string`.
+///
+/// # Safety
+/// `uci_move` must point to a valid, null-terminated C string.
+#[no_mangle]
+pub unsafe extern "C" fn chess_engine_format_move(
+    engine_id: EngineId,
+    uci_move: *const c_char,
+) -> *mut c_char {
+    guard(std::ptr::null_mut(), || {
+        write_move(engine_id, uci_move, |engine, mv| {
+            engine.format_move(mv).ok()
+        })
+    })
+}
+
+/// Search for the best move, telling a finished game apart from a failure.
+///
+/// Returns `CHESS_BEST_MOVE_FOUND` with the move in `out`, `CHESS_BEST_MOVE_GAME_OVER`
+/// with the `chess_engine_get_game_result` code in `result` when the game has already
+/// ended, or `CHESS_BEST_MOVE_ERROR`.
+///
+/// # Safety
+/// `out` must point to a writable `CMove`; `result` must be writable or null.
+#[no_mangle]
+pub unsafe extern "C" fn chess_engine_find_best_cmove(
+    engine_id: EngineId,
+    out: *mut CMove,
+    result: *mut c_int,
+) -> c_int {
+    guard(CHESS_BEST_MOVE_ERROR, || {
+        if out.is_null() {
+            return CHESS_BEST_MOVE_ERROR;
+        }
+
+        let Some(engine) = get_engine(engine_id) else {
+            return CHESS_BEST_MOVE_ERROR;
+        };
+        let outcome = lock_engine(&engine).find_best_move();
+        match outcome {
+            Ok(BestMove::Move(mv)) => {
+                out.write(CMove::from(mv));
+                CHESS_BEST_MOVE_FOUND
+            }
+            Ok(BestMove::GameOver(game_result)) => {
+                if !result.is_null() {
+                    result.write(game_result_code(game_result));
+                }
+                CHESS_BEST_MOVE_GAME_OVER
+            }
+            Err(_) => CHESS_BEST_MOVE_ERROR,
+        }
+    })
+}
+
+/// Copy up to `capacity` legal moves into `out` and return the total number of legal
+/// moves, or -1 for an invalid handle. Call with `capacity` 0 to query the count.
+/// Moves are sorted by from-square, then to-square, so the order is stable across calls.
+///
+/// # Safety
+/// `out` must point to at least `capacity` writable `CMove`s, or be null when `capacity` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn chess_engine_get_legal_moves(
+    engine_id: EngineId,
+    out: *mut CMove,
+    capacity: usize,
+) -> c_int {
+    guard(-1, || {
+        let Some(engine) = get_engine(engine_id) else {
+            return -1;
+        };
+        let engine = lock_engine(&engine);
+
+        let moves = engine.get_legal_moves();
+        if !out.is_null() {
+            for (i, mv) in moves.iter().take(capacity).enumerate() {
+                out.add(i).write(CMove::from(*mv));
+            }
+        }
+        moves.len() as c_int
+    })
+}
+
+/// Like `chess_engine_get_legal_moves`, for the moves of the side to move's piece on
+/// `square` (0 for a1 to 63 for h8) only; 0 when it has no piece there and -1 for an
+/// invalid handle or square
+///
+/// # Safety
+/// `out` must point to at least `capacity` writable `CMove`s, or be null when `capacity` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn chess_engine_get_legal_moves_from(
+    engine_id: EngineId,
+    square: u8,
+    out: *mut CMove,
+    capacity: usize,
+) -> c_int {
+    guard(-1, || {
+        let (Some(engine), Some(square)) = (get_engine(engine_id), Square::new(square)) else {
+            return -1;
+        };
+        let engine = lock_engine(&engine);
+
+        let moves = engine.get_legal_moves_from(square);
+        if !out.is_null() {
+            for (i, mv) in moves.iter().take(capacity).enumerate() {
+                out.add(i).write(CMove::from(*mv));
+            }
+        }
+        moves.len() as c_int
+    })
+}
+
+/// Play a move given as a `CMove`; only `from`, `to` and `promotion` are read
+#[no_mangle]
+pub extern "C" fn chess_engine_make_cmove(engine_id: EngineId, mv: CMove) -> c_int {
+    guard(0, || {
+        let Some(uci) = mv.to_uci() else {
+            return 0;
+        };
+
+        if let Some(engine) = get_engine(engine_id) {
+            let mut engine = lock_engine(&engine);
+            return match engine.make_move_from_uci(&uci) {
+                Ok(result) if result.success => 1,
+                _ => 0,
+            };
+        }
+        0
+    })
+}
+
+/// # Safety
+/// `out` must point to a writable `CSearchResult`.
+#[no_mangle]
+pub unsafe extern "C" fn chess_engine_search(
+    engine_id: EngineId,
+    out: *mut CSearchResult,
+) -> c_int {
+    guard(0, || {
+        if out.is_null() {
+            return 0;
+        }
+
+        if let Some(engine) = get_engine(engine_id) {
+            let mut engine = lock_engine(&engine);
+            if let Ok(result) = engine.search() {
+                out.write(CSearchResult::from(&result));
+                return 1;
+            }
+        }
+        0
+    })
+}
+
+/// Search once with `limits` instead of the configured depth and time limit
+///
+/// # Safety
+/// `limits` must point to a readable `CSearchLimits` and `out` to a writable
+/// `CSearchResult`.
+#[no_mangle]
+pub unsafe extern "C" fn chess_engine_search_with_limits(
+    engine_id: EngineId,
+    limits: *const CSearchLimits,
+    out: *mut CSearchResult,
+) -> c_int {
+    guard(0, || {
+        if limits.is_null() || out.is_null() {
+            return 0;
+        }
+
+        if let Some(engine) = get_engine(engine_id) {
+            let mut engine = lock_engine(&engine);
+            let configured = engine.limits();
+            engine.set_limits(SearchLimits::from(&*limits));
+            let result = engine.search();
+            engine.set_limits(configured);
+            if let Ok(result) = result {
+                out.write(CSearchResult::from(&result));
+                return 1;
+            }
+        }
+        0
+    })
+}
+
+/// Read search progress; safe to call from another thread while `chess_engine_search` runs.
+///
+/// # Safety
+/// `out` must point to a writable `CSearchStatus`.
+#[no_mangle]
+pub unsafe extern "C" fn chess_engine_get_search_status(
+    engine_id: EngineId,
+    out: *mut CSearchStatus,
+) -> c_int {
+    guard(0, || {
+        if out.is_null() {
+            return 0;
+        }
+
+        match lock_engines().status(engine_id) {
+            Some(status) => {
+                out.write(CSearchStatus::from(status.snapshot()));
+                1
+            }
+            None => 0,
+        }
+    })
+}
+
+/// Set the search depth (clamped to 1-64) and time limit for later searches;
+/// a `time_limit_ms` of 0 means no time limit
+#[no_mangle]
+pub extern "C" fn chess_engine_configure(
+    engine_id: EngineId,
+    depth: c_int,
+    time_limit_ms: u64,
+) -> c_int {
+    guard(0, || {
+        if let Some(engine) = get_engine(engine_id) {
+            let time_limit = (time_limit_ms > 0).then_some(time_limit_ms);
+            lock_engine(&engine).set_search_limits(depth.clamp(1, 64) as u8, time_limit);
+            return 1;
+        }
+        0
+    })
+}
+
+/// Store limits that every later search honours, `chess_engine_find_best_move`
+/// included, so hosts configure once instead of passing limits per call. A `depth` of 0
+/// keeps the engine's configured depth, a `movetime_ms` or `nodes` of 0 sets no such
+/// limit, and a `hash_mb` of 0 keeps the table size. Changing `hash_mb` clears the
+/// transposition table. Searches run on the calling thread.
+#[no_mangle]
+pub extern "C" fn chess_engine_set_limits(
+    engine_id: EngineId,
+    depth: c_int,
+    movetime_ms: u64,
+    nodes: u64,
+    hash_mb: c_int,
+) -> c_int {
+    guard(0, || {
+        if depth < 0 || hash_mb < 0 {
+            return 0;
+        }
+        let Some(engine) = get_engine(engine_id) else {
+            return 0;
+        };
+        let mut engine = lock_engine(&engine);
+
+        let depth = if depth > 0 {
+            depth.min(u8::MAX as c_int) as u8
+        } else {
+            engine.get_config().depth
+        };
+        let mut limits = SearchLimits::default().with_depth(depth.clamp(1, MAX_DEPTH));
+        if movetime_ms > 0 {
+            limits = limits.with_movetime(Duration::from_millis(movetime_ms));
+        }
+        if nodes > 0 {
+            limits = limits.with_nodes(nodes);
+        }
+        engine.set_limits(limits);
+
+        if hash_mb > 0 && engine.get_hash_size() != hash_mb as usize {
+            engine.set_hash_size(hash_mb as usize);
+        }
+        1
+    })
+}
+
+/// Write the board as 64 bytes indexed by square (a1 = 0): 0 for empty, 1-6 for White
+/// pawn through king, and -1 to -6 for the Black pieces.
+///
+/// # Safety
+/// `out` must point to at least 64 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn chess_engine_get_board(engine_id: EngineId, out: *mut i8) -> c_int {
+    guard(0, || {
+        if out.is_null() {
+            return 0;
+        }
+
+        if let Some(engine) = get_engine(engine_id) {
+            let engine = lock_engine(&engine);
+            for square in Square::ALL {
+                let code = match engine.get_position().piece_at(square) {
+                    Some(piece) => piece_code(piece),
+                    None => 0,
+                };
+                out.add(square.index() as usize).write(code);
+            }
+            return 1;
+        }
+        0
+    })
+}
+
+/// # Safety
+/// `out` must point to a writable `CGameInfo`.
+#[no_mangle]
+pub unsafe extern "C" fn chess_engine_get_game_info(
+    engine_id: EngineId,
+    out: *mut CGameInfo,
+) -> c_int {
+    guard(0, || {
+        if out.is_null() {
+            return 0;
+        }
+
+        if let Some(engine) = get_engine(engine_id) {
+            let engine = lock_engine(&engine);
+            out.write(CGameInfo::new(
+                &engine.get_game_info(),
+                engine.get_game_result(),
+            ));
+            return 1;
+        }
+        0
+    })
+}
+
+/// Take the oldest pending game event of the engine: moves, captures, checks,
+/// promotions, castling, draws and the game's end, in the order they happened.
+/// Returns 1 when an event was written, 0 when none is pending and -1 for an unknown
+/// engine or a null `out`. Up to 1024 unread events are kept.
+///
+/// # Safety
+/// `out` must point to a writable `CGameEvent`.
+#[no_mangle]
+pub unsafe extern "C" fn chess_engine_poll_event(
+    engine_id: EngineId,
+    out: *mut CGameEvent,
+) -> c_int {
+    guard(-1, || {
+        if out.is_null() {
+            return -1;
+        }
+
+        let Some(queue) = lock_engines().events(engine_id) else {
+            return -1;
+        };
+        match events::pop_event(&queue) {
+            Some(event) => {
+                out.write(CGameEvent::from(&event));
+                1
+            }
+            None => 0,
+        }
+    })
+}
+
+/// Message of the last panic caught on this thread, or null if none occurred.
+/// Reading it clears it; free the result with `chess_engine_free_string`.
+#[no_mangle]
+pub extern "C" fn chess_engine_take_last_panic() -> *mut c_char {
+    LAST_PANIC
+        .with(|last| last.borrow_mut().take())
+        .and_then(|message| CString::new(message).ok())
+        .map_or(std::ptr::null_mut(), CString::into_raw)
+}
+
+/// Pass the engine's log records to `callback` along with `user_data`, or stop passing
+/// them on with a null callback. Records come from any thread, so the callback must be
+/// thread-safe. Returns 1 on success, 0 if the library was built without the `log`
+/// feature and -1 if the process already has another logger.
+#[no_mangle]
+pub extern "C" fn chess_engine_set_log_callback(
+    callback: ChessLogCallback,
+    user_data: *mut c_void,
+) -> c_int {
+    guard(-1, || {
+        #[cfg(feature = "log")]
+        {
+            if logging::set_callback(callback, user_data) {
+                1
+            } else {
+                -1
+            }
+        }
+        #[cfg(not(feature = "log"))]
+        {
+            let _ = (callback, user_data);
+            0
+        }
+    })
+}
+
+/// Set the level (`CHESS_LOG_OFF` to `CHESS_LOG_TRACE`) of the records passed to the
+/// log callback from `module` and the modules inside it, such as `chess_core::search`,
+/// or of every other module when `module` is null. Without any call the level is
+/// `CHESS_LOG_INFO`. Returns 1 on success, 0 if the library was built without the `log`
+/// feature and -1 for an unknown level.
+///
+/// # Safety
+/// `module` must be null or point to a valid, null-terminated C string.
+#[no_mangle]
+pub unsafe extern "C" fn chess_engine_set_log_level(module: *const c_char, level: c_int) -> c_int {
+    guard(-1, || {
+        #[cfg(feature = "log")]
+        {
+            let Some(level) = logging::level_filter(level) else {
+                return -1;
+            };
+            let module = (!module.is_null()).then(|| CStr::from_ptr(module).to_string_lossy());
+            logging::set_level(module.as_deref(), level);
+            1
+        }
+        #[cfg(not(feature = "log"))]
+        {
+            let _ = module;
+            if (CHESS_LOG_OFF..=CHESS_LOG_TRACE).contains(&level) {
+                0
+            } else {
+                -1
+            }
+        }
+    })
+}
+
 /// # Safety
 /// The caller must ensure that `s` was allocated by this library and is not used after this call.
 #[no_mangle]
 pub unsafe extern "C" fn chess_engine_free_string(s: *mut c_char) {
-    if !s.is_null() {
-        let _ = CString::from_raw(s);
-    }
+    guard((), || {
+        if !s.is_null() {
+            let _ = CString::from_raw(s);
+        }
+    })
 }
 
 // WASM Bindings
 #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
 mod wasm {
     use super::*;
+    use chess_core::GameState;
+    use chess_engine::event::{CastleSide, DrawReason};
+    use chess_engine::{Color, GameEvent, GameResult, Piece};
+    use js_sys::{Array, Function, Object, Reflect};
+
+    /// Most legal moves one piece can have: a queen's 27, or a pawn's 12 promotions
+    const MAX_MOVES_FROM_SQUARE: usize = 32;
 
     #[wasm_bindgen]
     pub struct WasmChessEngine {
         engine_id: EngineId,
+        event_callback: Option<Function>,
     }
 
     #[wasm_bindgen]
@@ -341,7 +1022,10 @@ mod wasm {
         pub fn new() -> WasmChessEngine {
             let engine_id = chess_engine_create();
             chess_engine_initialize(engine_id);
-            WasmChessEngine { engine_id }
+            WasmChessEngine {
+                engine_id,
+                event_callback: None,
+            }
         }
 
         #[wasm_bindgen]
@@ -350,12 +1034,24 @@ mod wasm {
             let engine_id = unsafe { chess_engine_create_from_fen(fen_cstr.as_ptr()) };
             if engine_id != -1 {
                 chess_engine_initialize(engine_id);
-                Some(WasmChessEngine { engine_id })
+                Some(WasmChessEngine {
+                    engine_id,
+                    event_callback: None,
+                })
             } else {
                 None
             }
         }
 
+        /// Call `callback` with an object such as `{ type: "check", color: "black" }` for
+        /// every game event, starting with those already pending. Events are delivered
+        /// when the call that caused them returns; `undefined` stops the calls.
+        #[wasm_bindgen]
+        pub fn set_event_callback(&mut self, callback: Option<Function>) {
+            self.event_callback = callback;
+            self.dispatch_events();
+        }
+
         #[wasm_bindgen]
         pub fn get_fen(&self) -> String {
             let fen_ptr = chess_engine_get_fen(self.engine_id);
@@ -370,11 +1066,13 @@ mod wasm {
 
         #[wasm_bindgen]
         pub fn load_fen(&mut self, fen: &str) -> bool {
-            if let Ok(fen_cstr) = CString::new(fen) {
+            let loaded = if let Ok(fen_cstr) = CString::new(fen) {
                 unsafe { chess_engine_load_fen(self.engine_id, fen_cstr.as_ptr()) == 1 }
             } else {
                 false
-            }
+            };
+            self.dispatch_events();
+            loaded
         }
 
         #[wasm_bindgen]
@@ -384,11 +1082,13 @@ mod wasm {
 
         #[wasm_bindgen]
         pub fn make_move(&mut self, uci_move: &str) -> bool {
-            if let Ok(move_cstr) = CString::new(uci_move) {
+            let made = if let Ok(move_cstr) = CString::new(uci_move) {
                 unsafe { chess_engine_make_move(self.engine_id, move_cstr.as_ptr()) == 1 }
             } else {
                 false
-            }
+            };
+            self.dispatch_events();
+            made
         }
 
         #[wasm_bindgen]
@@ -425,11 +1125,117 @@ mod wasm {
             chess_engine_get_game_result(self.engine_id)
         }
 
+        /// Why the game is drawn, as the names used by draw events, or `undefined`
+        #[wasm_bindgen]
+        pub fn get_draw_reason(&self) -> Option<String> {
+            guard(None, || {
+                let engine = get_engine(self.engine_id)?;
+                let reason = lock_engine(&engine).get_draw_reason()?;
+                Some(draw_reason_name(reason).to_string())
+            })
+        }
+
+        #[wasm_bindgen]
+        pub fn agree_draw(&self) -> bool {
+            chess_engine_agree_draw(self.engine_id) == 1
+        }
+
+        #[wasm_bindgen]
+        pub fn get_position_repetition_count(&self) -> i32 {
+            chess_engine_get_position_repetition_count(self.engine_id)
+        }
+
+        #[wasm_bindgen]
+        pub fn get_halfmove_clock(&self) -> i32 {
+            chess_engine_get_halfmove_clock(self.engine_id)
+        }
+
+        /// Centipawns from the side to move's point of view
         #[wasm_bindgen]
         pub fn evaluate(&self) -> i32 {
             chess_engine_evaluate(self.engine_id)
         }
 
+        /// Centipawns from White's point of view
+        #[wasm_bindgen]
+        pub fn evaluate_absolute(&self) -> i32 {
+            chess_engine_evaluate_absolute(self.engine_id)
+        }
+
+        /// The evaluation terms as JSON, see `chess_engine_evaluate_detailed_json`
+        #[wasm_bindgen]
+        pub fn evaluate_detailed_json(&self) -> Option<String> {
+            let json_ptr = chess_engine_evaluate_detailed_json(self.engine_id);
+            if json_ptr.is_null() {
+                return None;
+            }
+            let json = unsafe { CStr::from_ptr(json_ptr).to_string_lossy().into_owned() };
+            unsafe { chess_engine_free_string(json_ptr) };
+            Some(json)
+        }
+
+        /// Squares that changed from the position `fen` to the current one, for
+        /// animating a transition: `[{ square: "e1", before: "K", after: null }, ...]`
+        /// with castling rooks and pawns taken en passant included
+        #[wasm_bindgen]
+        pub fn diff_from_fen(&self, fen: &str) -> Option<Array> {
+            let from = GameState::from_fen(fen).ok()?;
+            let current = GameState::from_fen(&self.get_fen()).ok()?;
+            let changes = from.diff(&current);
+
+            let piece = |piece: Option<Piece>| {
+                piece.map_or(JsValue::NULL, |piece| {
+                    JsValue::from_str(&piece.symbol().to_string())
+                })
+            };
+            Some(
+                changes
+                    .into_iter()
+                    .map(|change| {
+                        let object = Object::new();
+                        let set = |key: &str, value: JsValue| {
+                            let _ = Reflect::set(&object, &JsValue::from_str(key), &value);
+                        };
+                        set("square", JsValue::from_str(&change.square.to_string()));
+                        set("before", piece(change.before));
+                        set("after", piece(change.after));
+                        JsValue::from(object)
+                    })
+                    .collect(),
+            )
+        }
+
+        /// Legal moves in UCI of the side to move's piece on `square` (such as `"e2"`),
+        /// for highlighting targets when a piece is clicked
+        #[wasm_bindgen]
+        pub fn get_legal_moves_from(&self, square: &str) -> Array {
+            let Ok(square) = square.parse::<Square>() else {
+                return Array::new();
+            };
+            let mut moves = [CMove::default(); MAX_MOVES_FROM_SQUARE];
+            let count = unsafe {
+                chess_engine_get_legal_moves_from(
+                    self.engine_id,
+                    square.index(),
+                    moves.as_mut_ptr(),
+                    moves.len(),
+                )
+            };
+            moves
+                .iter()
+                .take(count.max(0) as usize)
+                .filter_map(|mv| mv.to_uci())
+                .map(|uci| JsValue::from_str(&uci))
+                .collect()
+        }
+
+        /// Run the built-in self-test and return its report; the last line reads
+        /// `N of N passed` when this build works
+        #[wasm_bindgen]
+        pub fn selftest() -> String {
+            ChessEngine::selftest().to_string()
+        }
+
         #[wasm_bindgen]
         pub fn find_best_move(&self) -> Option<String> {
             let move_ptr = chess_engine_find_best_move(self.engine_id);
@@ -441,11 +1247,178 @@ mod wasm {
                 None
             }
         }
+
+        /// Search the current position to exactly `depth` plies and return
+        /// `{ bestMove, score, depth, seldepth, nodes, timeMs, pv }`, with `score` in
+        /// centipawns for the side to move and `bestMove` null when it has no moves.
+        /// The transposition table carries over, so calling this with depths 1, 2, 3...
+        /// and yielding to the page in between shows progress at little extra cost.
+        #[wasm_bindgen]
+        pub fn search_to_depth(&self, depth: u8) -> Option<Object> {
+            let limits = CSearchLimits {
+                depth: depth.max(1),
+                ..CSearchLimits::default()
+            };
+            let mut result = CSearchResult::default();
+            let found =
+                unsafe { chess_engine_search_with_limits(self.engine_id, &limits, &mut result) };
+            if found != 1 {
+                return None;
+            }
+
+            let object = Object::new();
+            let set = |key: &str, value: JsValue| {
+                let _ = Reflect::set(&object, &JsValue::from_str(key), &value);
+            };
+            let best_move = result
+                .has_best_move
+                .then(|| result.best_move.to_uci())
+                .flatten();
+            set(
+                "bestMove",
+                best_move.map_or(JsValue::NULL, |uci| JsValue::from_str(&uci)),
+            );
+            set("score", JsValue::from(result.evaluation));
+            set("depth", JsValue::from(result.depth));
+            set("seldepth", JsValue::from(result.seldepth));
+            set("nodes", JsValue::from(result.nodes as f64));
+            set("timeMs", JsValue::from(result.elapsed_ms as f64));
+            let pv: Array = result.pv[..result.pv_length as usize]
+                .iter()
+                .filter_map(|mv| mv.to_uci())
+                .map(|uci| JsValue::from_str(&uci))
+                .collect();
+            set("pv", pv.into());
+            Some(object)
+        }
+
+        /// Take back the last move and return it in UCI, or `undefined` at the start
+        /// of the game
+        #[wasm_bindgen]
+        pub fn undo_move(&self) -> Option<String> {
+            let engine = get_engine(self.engine_id)?;
+            let undone = lock_engine(&engine).undo_move();
+            undone.map(|mv| mv.to_string())
+        }
+
+        #[wasm_bindgen]
+        pub fn can_undo(&self) -> bool {
+            get_engine(self.engine_id).is_some_and(|engine| lock_engine(&engine).can_undo())
+        }
+    }
+
+    impl WasmChessEngine {
+        /// Hand the pending events to the callback, or leave them queued without one
+        fn dispatch_events(&self) {
+            let Some(callback) = &self.event_callback else {
+                return;
+            };
+            let Some(queue) = lock_engines().events(self.engine_id) else {
+                return;
+            };
+            while let Some(event) = events::pop_event(&queue) {
+                let _ = callback.call1(&JsValue::NULL, &event_object(&event));
+            }
+        }
+    }
+
+    fn color_name(color: Color) -> &'static str {
+        match color {
+            Color::White => "white",
+            Color::Black => "black",
+        }
+    }
+
+    fn draw_reason_name(reason: DrawReason) -> &'static str {
+        match reason {
+            DrawReason::Stalemate => "stalemate",
+            DrawReason::FiftyMoveRule => "fiftyMoveRule",
+            DrawReason::ThreefoldRepetition => "threefoldRepetition",
+            DrawReason::InsufficientMaterial => "insufficientMaterial",
+            DrawReason::Agreement => "agreement",
+        }
+    }
+
+    /// The event as a plain JS object with a `type` field and the event's details
+    fn event_object(event: &GameEvent) -> JsValue {
+        let object = Object::new();
+        let set = |key: &str, value: JsValue| {
+            let _ = Reflect::set(&object, &JsValue::from_str(key), &value);
+        };
+        let text = |value: &str| JsValue::from_str(value);
+
+        let kind = match event {
+            GameEvent::GameStarted => "gameStarted",
+            GameEvent::MoveMade { mv, san, fen } => {
+                set("move", text(&mv.to_uci()));
+                set("san", text(san));
+                set("fen", text(fen));
+                "moveMade"
+            }
+            GameEvent::PieceCaptured { piece, square } => {
+                set("piece", text(&piece.symbol().to_string()));
+                set("square", text(&square.to_string()));
+                "pieceCaptured"
+            }
+            GameEvent::Check { color } => {
+                set("color", text(color_name(*color)));
+                "check"
+            }
+            GameEvent::Checkmate { winner } => {
+                set("winner", text(color_name(*winner)));
+                "checkmate"
+            }
+            GameEvent::Stalemate => "stalemate",
+            GameEvent::Draw { reason } => {
+                set("reason", text(draw_reason_name(*reason)));
+                "draw"
+            }
+            GameEvent::Promotion { piece, square } => {
+                set("piece", text(&piece.symbol().to_string()));
+                set("square", text(&square.to_string()));
+                "promotion"
+            }
+            GameEvent::Castle { color, side } => {
+                set("color", text(color_name(*color)));
+                let side = match side {
+                    CastleSide::Kingside => "kingside",
+                    CastleSide::Queenside => "queenside",
+                };
+                set("side", text(side));
+                "castle"
+            }
+            GameEvent::EnPassant { captured_square } => {
+                set("square", text(&captured_square.to_string()));
+                "enPassant"
+            }
+            GameEvent::GameEnded { result } => {
+                let result = match result {
+                    GameResult::WhiteWins => "1-0",
+                    GameResult::BlackWins => "0-1",
+                    GameResult::Draw => "1/2-1/2",
+                    GameResult::Ongoing => "*",
+                };
+                set("result", text(result));
+                "gameEnded"
+            }
+            GameEvent::DeliberateMistake {
+                mv,
+                best_move,
+                loss,
+            } => {
+                set("move", text(&mv.to_uci()));
+                set("bestMove", text(&best_move.to_uci()));
+                set("loss", JsValue::from_f64(*loss as f64));
+                "deliberateMistake"
+            }
+        };
+        set("type", text(kind));
+        object.into()
     }
 
     impl Drop for WasmChessEngine {
         fn drop(&mut self) {
-            chess_engine_destroy(self.engine_id);
+            let _ = chess_engine_destroy(self.engine_id);
         }
     }
 }
@@ -457,6 +1430,7 @@ pub use wasm::*;
 #[cfg(feature = "python")]
 mod python {
     use super::*;
+    use ::chess_engine::ChessEngineBuilder;
     use pyo3::prelude::*;
 
     #[pyclass]
@@ -535,10 +1509,34 @@ mod python {
             chess_engine_get_game_result(self.engine_id)
         }
 
+        /// `CHESS_DRAW_*` code of why the game is drawn, or -1
+        fn get_draw_reason(&self) -> i32 {
+            chess_engine_get_draw_reason(self.engine_id)
+        }
+
+        /// End the game as a draw by agreement; false if it is already over
+        fn agree_draw(&self) -> bool {
+            chess_engine_agree_draw(self.engine_id) == 1
+        }
+
+        fn get_position_repetition_count(&self) -> i32 {
+            chess_engine_get_position_repetition_count(self.engine_id)
+        }
+
+        fn get_halfmove_clock(&self) -> i32 {
+            chess_engine_get_halfmove_clock(self.engine_id)
+        }
+
+        /// Centipawns from the side to move's point of view
         fn evaluate(&self) -> i32 {
             chess_engine_evaluate(self.engine_id)
         }
 
+        /// Centipawns from White's point of view
+        fn evaluate_absolute(&self) -> i32 {
+            chess_engine_evaluate_absolute(self.engine_id)
+        }
+
         fn find_best_move(&self) -> Option<String> {
             let move_ptr = chess_engine_find_best_move(self.engine_id);
             if !move_ptr.is_null() {
@@ -553,16 +1551,557 @@ mod python {
 
     impl Drop for PyChessEngine {
         fn drop(&mut self) {
-            chess_engine_destroy(self.engine_id);
+            let _ = chess_engine_destroy(self.engine_id);
         }
     }
 
+    /// (fen, best_move, evaluation, depth, nodes, error) for one analysed position
+    type PyAnalysis = (String, Option<String>, i32, u8, u64, Option<String>);
+
+    /// Search every FEN to `depth` on `threads` workers, releasing the GIL meanwhile
+    #[pyfunction]
+    #[pyo3(signature = (fens, depth, threads = 1))]
+    fn analyze_batch(
+        py: Python<'_>,
+        fens: Vec<String>,
+        depth: u8,
+        threads: usize,
+    ) -> PyResult<Vec<PyAnalysis>> {
+        let mut engine = ChessEngineBuilder::new()
+            .with_threads(threads)
+            .build()
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+        let results = py.detach(|| engine.analyze_batch(&fens, depth));
+
+        Ok(results
+            .into_iter()
+            .map(|result| {
+                (
+                    result.fen,
+                    result.best_move.map(|mv| mv.to_string()),
+                    result.evaluation,
+                    result.depth,
+                    result.nodes,
+                    result.error,
+                )
+            })
+            .collect())
+    }
+
+    /// Static evaluation of every FEN in centipawns for its side to move, `None` where
+    /// the FEN does not parse; runs on all cores with the GIL released
+    #[pyfunction]
+    fn evaluate_fens(py: Python<'_>, fens: Vec<String>) -> Vec<Option<i32>> {
+        py.detach(|| {
+            let mut scores = vec![None; fens.len()];
+            super::score_fens(fens.iter().map(|fen| Some(fen.as_str())), |index, score| {
+                scores[index] = (score != CHESS_EVAL_INVALID_FEN).then_some(score);
+            });
+            scores
+        })
+    }
+
+    /// Run the built-in self-test; returns whether it passed and the per-case report
+    #[pyfunction]
+    fn selftest(py: Python<'_>) -> (bool, String) {
+        let report = py.detach(ChessEngine::selftest);
+        (report.passed(), report.to_string())
+    }
+
     #[pymodule]
     fn chess_engine(m: &Bound<'_, PyModule>) -> PyResult<()> {
         m.add_class::<PyChessEngine>()?;
+        m.add("ABI_VERSION", CHESS_ENGINE_ABI_VERSION)?;
+        m.add_function(wrap_pyfunction!(analyze_batch, m)?)?;
+        m.add_function(wrap_pyfunction!(evaluate_fens, m)?)?;
+        m.add_function(wrap_pyfunction!(selftest, m)?)?;
         Ok(())
     }
 }
 
 #[cfg(feature = "python")]
 pub use python::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_log_callback_receives_search_records() {
+        static RECORDS: Mutex<Vec<(c_int, String)>> = Mutex::new(Vec::new());
+
+        unsafe extern "C" fn record(
+            level: c_int,
+            target: *const c_char,
+            _message: *const c_char,
+            _user_data: *mut c_void,
+        ) {
+            let target = CStr::from_ptr(target).to_string_lossy().into_owned();
+            RECORDS.lock().unwrap().push((level, target));
+        }
+
+        let search_module = CString::new("chess_core::search").unwrap();
+        assert_eq!(
+            unsafe { chess_engine_set_log_level(std::ptr::null(), 42) },
+            -1
+        );
+        unsafe {
+            assert_eq!(
+                chess_engine_set_log_level(std::ptr::null(), CHESS_LOG_OFF),
+                1
+            );
+            assert_eq!(
+                chess_engine_set_log_level(search_module.as_ptr(), CHESS_LOG_DEBUG),
+                1
+            );
+        }
+        assert_eq!(
+            chess_engine_set_log_callback(Some(record), std::ptr::null_mut()),
+            1
+        );
+
+        let id = chess_engine_create();
+        chess_engine_initialize(id);
+        chess_engine_configure(id, 3, 0);
+        let best = chess_engine_find_best_move(id);
+        unsafe { chess_engine_free_string(best) };
+        chess_engine_destroy(id);
+        assert_eq!(chess_engine_set_log_callback(None, std::ptr::null_mut()), 1);
+
+        let records = RECORDS.lock().unwrap();
+        assert!(records.iter().any(|(level, target)| {
+            *level == CHESS_LOG_DEBUG && target == "chess_core::search::engine"
+        }));
+        assert!(records
+            .iter()
+            .all(|(_, target)| target.starts_with("chess_core::search")));
+    }
+
+    #[test]
+    fn test_guard_turns_panic_into_fallback() {
+        assert_eq!(guard(-1, || -> c_int { panic!("boom") }), -1);
+
+        let message = chess_engine_take_last_panic();
+        assert!(!message.is_null());
+        let text = unsafe { CStr::from_ptr(message).to_string_lossy().into_owned() };
+        unsafe { chess_engine_free_string(message) };
+        assert_eq!(text, "boom");
+        assert!(chess_engine_take_last_panic().is_null());
+
+        // The registry stays usable after a caught panic
+        let id = chess_engine_create();
+        assert!(id > 0);
+        assert_eq!(chess_engine_get_legal_moves_count(id), 20);
+        assert_eq!(chess_engine_destroy(id), 1);
+    }
+    #[test]
+    fn test_destroyed_handle_is_detected() {
+        let id = chess_engine_create();
+        assert_eq!(chess_engine_exists(id), 1);
+        assert_eq!(chess_engine_destroy(id), 1);
+
+        assert_eq!(chess_engine_exists(id), 0);
+        assert_eq!(chess_engine_destroy(id), 0);
+        assert_eq!(chess_engine_get_legal_moves_count(id), -1);
+    }
+    #[test]
+    fn test_struct_api() {
+        let id = chess_engine_create();
+        assert_eq!(chess_engine_initialize(id), 1);
+
+        let count = unsafe { chess_engine_get_legal_moves(id, std::ptr::null_mut(), 0) };
+        assert_eq!(count, 20);
+        let mut moves = vec![CMove::default(); count as usize];
+        let written = unsafe { chess_engine_get_legal_moves(id, moves.as_mut_ptr(), moves.len()) };
+        assert_eq!(written, 20);
+
+        let e2e4 = moves
+            .iter()
+            .copied()
+            .find(|mv| mv.to_uci().as_deref() == Some("e2e4"))
+            .unwrap();
+        let mut from_g1 = [CMove::default(); 4];
+        let count = unsafe {
+            chess_engine_get_legal_moves_from(id, Square::G1.index(), from_g1.as_mut_ptr(), 4)
+        };
+        assert_eq!(count, 2);
+        assert!(from_g1[..2].iter().all(|mv| mv.from == Square::G1.index()));
+        assert_eq!(
+            unsafe { chess_engine_get_legal_moves_from(id, 64, std::ptr::null_mut(), 0) },
+            -1
+        );
+
+        assert_eq!(chess_engine_make_cmove(id, e2e4), 1);
+        assert_eq!(chess_engine_make_cmove(id, e2e4), 0);
+
+        let mut info = CGameInfo::default();
+        assert_eq!(unsafe { chess_engine_get_game_info(id, &mut info) }, 1);
+        assert_eq!(info.side_to_move, 1);
+        assert_eq!(info.legal_move_count, 20);
+        assert_eq!(info.result, 0);
+        assert!(!info.is_check);
+
+        let mut board = [0i8; 64];
+        assert_eq!(unsafe { chess_engine_get_board(id, board.as_mut_ptr()) }, 1);
+        assert_eq!(board[Square::E4.index() as usize], 1);
+        assert_eq!(board[Square::E2.index() as usize], 0);
+        assert_eq!(board[Square::E8.index() as usize], -6);
+
+        assert_eq!(chess_engine_destroy(id), 1);
+        assert_eq!(unsafe { chess_engine_get_game_info(id, &mut info) }, 0);
+    }
+
+    #[test]
+    fn test_set_limits_persist_across_searches() {
+        let id = chess_engine_create();
+        assert_eq!(chess_engine_initialize(id), 1);
+        assert_eq!(chess_engine_set_limits(id, 2, 0, 0, 4), 1);
+
+        for _ in 0..2 {
+            let mut result = CSearchResult::default();
+            assert_eq!(unsafe { chess_engine_search(id, &mut result) }, 1);
+            assert_eq!(result.depth, 2);
+            let best_move = chess_engine_find_best_move(id);
+            assert!(!best_move.is_null());
+            unsafe { chess_engine_free_string(best_move) };
+        }
+        {
+            let engine = get_engine(id).unwrap();
+            let engine = lock_engine(&engine);
+            assert_eq!(engine.get_hash_size(), 4);
+        }
+
+        // Zero depth falls back to the configured one, and a node limit cuts it short
+        assert_eq!(chess_engine_set_limits(id, 0, 0, 500, 0), 1);
+        let mut result = CSearchResult::default();
+        assert_eq!(unsafe { chess_engine_search(id, &mut result) }, 1);
+        assert!(result.nodes < 5_000);
+        assert_eq!(chess_engine_set_limits(id, -1, 0, 0, 0), 0);
+        assert_eq!(chess_engine_destroy(id), 1);
+        assert_eq!(chess_engine_set_limits(id, 2, 0, 0, 0), 0);
+    }
+
+    #[test]
+    fn test_search_status_during_search() {
+        let id = chess_engine_create();
+        assert_eq!(chess_engine_initialize(id), 1);
+        assert_eq!(chess_engine_configure(id, 3, 0), 1);
+
+        let worker = std::thread::spawn(move || {
+            let mut result = CSearchResult::default();
+            let ok = unsafe { chess_engine_search(id, &mut result) };
+            (ok, result)
+        });
+        // Polling must not wait for the search to release the engine
+        let mut status = CSearchStatus::default();
+        assert_eq!(
+            unsafe { chess_engine_get_search_status(id, &mut status) },
+            1
+        );
+
+        let (ok, result) = worker.join().unwrap();
+        assert_eq!(ok, 1);
+        assert!(result.has_best_move);
+        assert_eq!(result.depth, 3);
+
+        assert_eq!(
+            unsafe { chess_engine_get_search_status(id, &mut status) },
+            1
+        );
+        assert_eq!(status.nodes, result.nodes);
+        assert_eq!(chess_engine_destroy(id), 1);
+        assert_eq!(
+            unsafe { chess_engine_get_search_status(id, &mut status) },
+            0
+        );
+    }
+
+    #[test]
+    fn test_repetition_count_and_halfmove_clock() {
+        let id = chess_engine_create();
+        assert_eq!(chess_engine_initialize(id), 1);
+        assert_eq!(chess_engine_get_position_repetition_count(id), 1);
+        assert_eq!(chess_engine_get_halfmove_clock(id), 0);
+
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1"] {
+            let uci = CString::new(uci).unwrap();
+            assert_eq!(unsafe { chess_engine_make_move(id, uci.as_ptr()) }, 1);
+        }
+        assert_eq!(chess_engine_get_halfmove_clock(id), 7);
+        // Black to move, as after 1. Nf3 Nf6 2. Ng1
+        assert_eq!(chess_engine_get_position_repetition_count(id), 2);
+
+        let mut info = CGameInfo::default();
+        assert_eq!(unsafe { chess_engine_get_game_info(id, &mut info) }, 1);
+        assert_eq!(info.repetition_count, 2);
+        assert_eq!(info.halfmove_clock, 7);
+
+        assert_eq!(chess_engine_destroy(id), 1);
+        assert_eq!(chess_engine_get_position_repetition_count(id), -1);
+        assert_eq!(chess_engine_get_halfmove_clock(id), -1);
+    }
+
+    #[test]
+    fn test_draw_reason_and_agreement() {
+        let id = chess_engine_create();
+        assert_eq!(chess_engine_initialize(id), 1);
+        assert_eq!(chess_engine_get_draw_reason(id), -1);
+
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8"].repeat(2) {
+            let uci = CString::new(uci).unwrap();
+            assert_eq!(unsafe { chess_engine_make_move(id, uci.as_ptr()) }, 1);
+        }
+        assert_eq!(
+            chess_engine_get_draw_reason(id),
+            CHESS_DRAW_THREEFOLD_REPETITION
+        );
+        assert_eq!(chess_engine_agree_draw(id), 0);
+        assert_eq!(chess_engine_destroy(id), 1);
+
+        let id = chess_engine_create();
+        assert_eq!(chess_engine_initialize(id), 1);
+        assert_eq!(chess_engine_agree_draw(id), 1);
+        assert_eq!(chess_engine_get_draw_reason(id), CHESS_DRAW_AGREEMENT);
+        assert_eq!(chess_engine_get_game_result(id), 3);
+
+        assert_eq!(chess_engine_destroy(id), 1);
+        assert_eq!(chess_engine_get_draw_reason(id), -1);
+        assert_eq!(chess_engine_agree_draw(id), -1);
+    }
+
+    #[test]
+    fn test_evaluate_conventions() {
+        let fen = CString::new("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap();
+        let id = unsafe { chess_engine_create_from_fen(fen.as_ptr()) };
+        assert!(chess_engine_evaluate(id) < -500);
+        assert_eq!(
+            chess_engine_evaluate_absolute(id),
+            -chess_engine_evaluate(id)
+        );
+        assert_eq!(chess_engine_destroy(id), 1);
+    }
+
+    #[test]
+    fn test_move_notation() {
+        let id = chess_engine_create();
+        assert_eq!(chess_engine_initialize(id), 1);
+        let take = |text: *mut c_char| {
+            assert!(!text.is_null());
+            let owned = unsafe { CStr::from_ptr(text) }
+                .to_str()
+                .unwrap()
+                .to_string();
+            unsafe { chess_engine_free_string(text) };
+            owned
+        };
+        let knight = CString::new("g1f3").unwrap();
+
+        assert_eq!(
+            take(unsafe { chess_engine_move_to_san(id, knight.as_ptr()) }),
+            "Nf3"
+        );
+        assert_eq!(
+            take(unsafe { chess_engine_format_move(id, knight.as_ptr()) }),
+            "g1f3"
+        );
+        assert_eq!(
+            chess_engine_set_notation_style(id, CHESS_NOTATION_FIGURINE),
+            1
+        );
+        assert_eq!(
+            take(unsafe { chess_engine_format_move(id, knight.as_ptr()) }),
+            "♘f3"
+        );
+        assert_eq!(
+            chess_engine_set_notation_style(id, CHESS_NOTATION_UCI_CHESS960),
+            1
+        );
+        assert_eq!(
+            take(unsafe { chess_engine_format_move(id, knight.as_ptr()) }),
+            "g1f3"
+        );
+        assert_eq!(chess_engine_set_notation_style(id, 7), 0);
+
+        let illegal = CString::new("e2e5").unwrap();
+        assert!(unsafe { chess_engine_move_to_san(id, illegal.as_ptr()) }.is_null());
+        assert!(unsafe { chess_engine_format_move(id, std::ptr::null()) }.is_null());
+        assert_eq!(chess_engine_destroy(id), 1);
+    }
+
+    #[test]
+    fn test_search_with_limits_leaves_configuration() {
+        let id = chess_engine_create();
+        assert_eq!(chess_engine_initialize(id), 1);
+        assert_eq!(chess_engine_configure(id, 3, 0), 1);
+
+        let limits = CSearchLimits {
+            depth: 1,
+            ..CSearchLimits::default()
+        };
+        let mut result = CSearchResult::default();
+        assert_eq!(
+            unsafe { chess_engine_search_with_limits(id, &limits, &mut result) },
+            1
+        );
+        assert_eq!(result.depth, 1);
+        assert_eq!(
+            unsafe { chess_engine_search_with_limits(id, std::ptr::null(), &mut result) },
+            0
+        );
+
+        assert_eq!(unsafe { chess_engine_search(id, &mut result) }, 1);
+        assert_eq!(result.depth, 3);
+        assert_eq!(chess_engine_destroy(id), 1);
+    }
+
+    #[test]
+    fn test_find_best_cmove_distinguishes_game_over() {
+        let mut mv = CMove::default();
+        let mut result = -1;
+
+        let id = chess_engine_create();
+        assert_eq!(
+            unsafe { chess_engine_find_best_cmove(id, &mut mv, &mut result) },
+            CHESS_BEST_MOVE_ERROR
+        );
+        assert_eq!(chess_engine_initialize(id), 1);
+        assert_eq!(chess_engine_configure(id, 2, 0), 1);
+        assert_eq!(
+            unsafe { chess_engine_find_best_cmove(id, &mut mv, &mut result) },
+            CHESS_BEST_MOVE_FOUND
+        );
+        assert_ne!(mv.from, mv.to);
+        assert_eq!(chess_engine_destroy(id), 1);
+
+        let fen = CString::new("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        let mated = unsafe { chess_engine_create_from_fen(fen.as_ptr()) };
+        assert_eq!(chess_engine_initialize(mated), 1);
+        assert_eq!(
+            unsafe { chess_engine_find_best_cmove(mated, &mut mv, &mut result) },
+            CHESS_BEST_MOVE_GAME_OVER
+        );
+        assert_eq!(
+            result,
+            game_result_code(chess_engine::GameResult::WhiteWins)
+        );
+        assert!(chess_engine_find_best_move(mated).is_null());
+        assert_eq!(chess_engine_destroy(mated), 1);
+
+        assert_eq!(
+            unsafe { chess_engine_find_best_cmove(mated, &mut mv, std::ptr::null_mut()) },
+            CHESS_BEST_MOVE_ERROR
+        );
+    }
+
+    #[test]
+    fn test_poll_event_reports_events_in_order() {
+        let mut event = CGameEvent::default();
+        let id = chess_engine_create();
+        assert_eq!(chess_engine_initialize(id), 1);
+        while unsafe { chess_engine_poll_event(id, &mut event) } == 1 {}
+
+        for uci in ["e2e4", "d7d5", "e4d5"] {
+            let uci = CString::new(uci).unwrap();
+            assert_eq!(unsafe { chess_engine_make_move(id, uci.as_ptr()) }, 1);
+        }
+        let mut kinds = Vec::new();
+        while unsafe { chess_engine_poll_event(id, &mut event) } == 1 {
+            kinds.push(event.kind);
+            if event.kind == CHESS_EVENT_PIECE_CAPTURED {
+                assert_eq!(event.square, 35);
+                assert_eq!(event.piece, -1);
+            }
+        }
+        assert_eq!(
+            kinds,
+            vec![
+                CHESS_EVENT_MOVE_MADE,
+                CHESS_EVENT_MOVE_MADE,
+                CHESS_EVENT_MOVE_MADE,
+                CHESS_EVENT_PIECE_CAPTURED
+            ]
+        );
+        assert_eq!(unsafe { chess_engine_poll_event(id, &mut event) }, 0);
+        assert_eq!(
+            unsafe { chess_engine_poll_event(id, std::ptr::null_mut()) },
+            -1
+        );
+
+        assert_eq!(chess_engine_destroy(id), 1);
+        assert_eq!(unsafe { chess_engine_poll_event(id, &mut event) }, -1);
+    }
+
+    #[test]
+    fn test_evaluate_detailed_json() {
+        let fen = CString::new("4k3/8/8/8/8/8/8/R3K3 b - - 0 1").unwrap();
+        let id = unsafe { chess_engine_create_from_fen(fen.as_ptr()) };
+        let json_ptr = chess_engine_evaluate_detailed_json(id);
+        assert!(!json_ptr.is_null());
+        let json = unsafe { CStr::from_ptr(json_ptr).to_string_lossy().into_owned() };
+        unsafe { chess_engine_free_string(json_ptr) };
+
+        assert!(json.starts_with("{\"material\":"));
+        assert!(json.contains("\"known_ending\":false"));
+        let total = chess_engine_evaluate_absolute(id);
+        assert!(json.contains(&format!("\"total\":{},", total)));
+        assert!(json.ends_with(&format!("\"side_to_move_total\":{}}}", -total)));
+
+        assert_eq!(chess_engine_destroy(id), 1);
+        assert!(chess_engine_evaluate_detailed_json(id).is_null());
+    }
+
+    #[test]
+    fn test_evaluate_fens() {
+        let fens = [
+            CString::new("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap(),
+            CString::new("4k3/8/8/8/8/8/8/R3K3 b - - 0 1").unwrap(),
+            CString::new("not a fen").unwrap(),
+        ];
+        let mut pointers: Vec<*const c_char> = fens.iter().map(|fen| fen.as_ptr()).collect();
+        pointers.push(std::ptr::null());
+        let mut scores = [0; 4];
+
+        let evaluated = unsafe {
+            chess_engine_evaluate_fens(pointers.as_ptr(), pointers.len(), scores.as_mut_ptr())
+        };
+        assert_eq!(evaluated, 2);
+        assert!(scores[0] > 300);
+        assert!(scores[1] < -300);
+        assert_eq!(scores[2], CHESS_EVAL_INVALID_FEN);
+        assert_eq!(scores[3], CHESS_EVAL_INVALID_FEN);
+
+        // Enough positions for the parallel path give the same scores
+        let many = vec![pointers[0]; 500];
+        let mut many_scores = vec![0; many.len()];
+        let evaluated = unsafe {
+            chess_engine_evaluate_fens(many.as_ptr(), many.len(), many_scores.as_mut_ptr())
+        };
+        assert_eq!(evaluated, 500);
+        assert!(many_scores.iter().all(|&score| score == scores[0]));
+
+        let null = unsafe { chess_engine_evaluate_fens(std::ptr::null(), 1, scores.as_mut_ptr()) };
+        assert_eq!(null, -1);
+    }
+
+    #[test]
+    fn test_abi_version() {
+        assert_eq!(chess_engine_abi_version(), CHESS_ENGINE_ABI_VERSION);
+        assert_eq!(CHESS_ENGINE_ABI_VERSION, chess_engine::ABI_VERSION);
+    }
+
+    #[test]
+    fn test_selftest() {
+        let mut report = std::ptr::null_mut();
+        assert_eq!(unsafe { chess_engine_selftest(&mut report) }, 1);
+        let text = unsafe { CStr::from_ptr(report) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        unsafe { chess_engine_free_string(report) };
+        assert!(text.starts_with("PASS "));
+        assert!(!text.contains("FAIL"));
+
+        assert_eq!(unsafe { chess_engine_selftest(std::ptr::null_mut()) }, 1);
+    }
+}
@@ -0,0 +1,180 @@
+// Host logging callback for the C API
+//
+// Embedded hosts (Android, iOS) have no terminal to read the engine's log from, so the
+// records go to a function the host registers, which can pass them on to logcat or
+// os_log. Levels are set per module: the longest registered module path that a
+// record's target starts with decides whether it is passed on.
+
+use crate::{
+    ChessLogCallback, CHESS_LOG_DEBUG, CHESS_LOG_ERROR, CHESS_LOG_INFO, CHESS_LOG_OFF,
+    CHESS_LOG_TRACE, CHESS_LOG_WARN,
+};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::ffi::{c_void, CString};
+use std::os::raw::c_int;
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+/// Level of records passed on when no module level applies
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::Info;
+
+struct LoggerState {
+    callback: ChessLogCallback,
+    /// The host's pointer, kept as an address; it is only ever handed back
+    user_data: usize,
+    default_level: LevelFilter,
+    /// Module paths with their levels
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl LoggerState {
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .filter(|(module, _)| {
+                target
+                    .strip_prefix(module.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map_or(self.default_level, |&(_, level)| level)
+    }
+
+    /// Most detailed level any module asks for, or off without a callback
+    fn max_level(&self) -> LevelFilter {
+        if self.callback.is_none() {
+            return LevelFilter::Off;
+        }
+        self.modules
+            .iter()
+            .map(|&(_, level)| level)
+            .fold(self.default_level, |max, level| max.max(level))
+    }
+}
+
+struct HostLogger {
+    state: RwLock<LoggerState>,
+}
+
+static LOGGER: HostLogger = HostLogger {
+    state: RwLock::new(LoggerState {
+        callback: None,
+        user_data: 0,
+        default_level: DEFAULT_LEVEL,
+        modules: Vec::new(),
+    }),
+};
+
+/// Whether `LOGGER` became the process's logger; another one may have been set first
+static INSTALLED: OnceLock<bool> = OnceLock::new();
+
+impl Log for HostLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
+        state.callback.is_some() && metadata.level() <= state.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        // Copied out so the callback runs without the lock and may log or reconfigure
+        let (callback, user_data) = {
+            let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
+            if record.level() > state.level_for(record.target()) {
+                return;
+            }
+            (state.callback, state.user_data)
+        };
+        let Some(callback) = callback else {
+            return;
+        };
+
+        let text = |s: String| CString::new(s.replace('\0', " ")).unwrap_or_default();
+        let target = text(record.target().to_string());
+        let message = text(record.args().to_string());
+        // SAFETY: the host registered `callback` to be called with these arguments
+        unsafe {
+            callback(
+                level_code(record.level()),
+                target.as_ptr(),
+                message.as_ptr(),
+                user_data as *mut c_void,
+            )
+        };
+    }
+
+    fn flush(&self) {}
+}
+
+fn level_code(level: Level) -> c_int {
+    match level {
+        Level::Error => CHESS_LOG_ERROR,
+        Level::Warn => CHESS_LOG_WARN,
+        Level::Info => CHESS_LOG_INFO,
+        Level::Debug => CHESS_LOG_DEBUG,
+        Level::Trace => CHESS_LOG_TRACE,
+    }
+}
+
+pub fn level_filter(code: c_int) -> Option<LevelFilter> {
+    match code {
+        CHESS_LOG_OFF => Some(LevelFilter::Off),
+        CHESS_LOG_ERROR => Some(LevelFilter::Error),
+        CHESS_LOG_WARN => Some(LevelFilter::Warn),
+        CHESS_LOG_INFO => Some(LevelFilter::Info),
+        CHESS_LOG_DEBUG => Some(LevelFilter::Debug),
+        CHESS_LOG_TRACE => Some(LevelFilter::Trace),
+        _ => None,
+    }
+}
+
+/// Register `callback`, or stop logging with `None`. Returns false if another logger
+/// already owns the process's `log` facade.
+pub fn set_callback(callback: ChessLogCallback, user_data: *mut c_void) -> bool {
+    if !*INSTALLED.get_or_init(|| log::set_logger(&LOGGER).is_ok()) {
+        return false;
+    }
+    let mut state = LOGGER.state.write().unwrap_or_else(PoisonError::into_inner);
+    state.callback = callback;
+    state.user_data = user_data as usize;
+    log::set_max_level(state.max_level());
+    true
+}
+
+/// Set the level of `module` and the modules inside it, or the default level for `None`
+pub fn set_level(module: Option<&str>, level: LevelFilter) {
+    let mut state = LOGGER.state.write().unwrap_or_else(PoisonError::into_inner);
+    match module {
+        None => state.default_level = level,
+        Some(module) => {
+            state.modules.retain(|(existing, _)| existing != module);
+            state.modules.push((module.to_string(), level));
+        }
+    }
+    if *INSTALLED.get().unwrap_or(&false) {
+        log::set_max_level(state.max_level());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_most_specific_module_level_applies() {
+        let state = LoggerState {
+            callback: None,
+            user_data: 0,
+            default_level: LevelFilter::Warn,
+            modules: vec![
+                ("chess_core".to_string(), LevelFilter::Info),
+                ("chess_core::search".to_string(), LevelFilter::Trace),
+            ],
+        };
+        assert_eq!(
+            state.level_for("chess_core::search::engine"),
+            LevelFilter::Trace
+        );
+        assert_eq!(state.level_for("chess_core::evaluation"), LevelFilter::Info);
+        assert_eq!(state.level_for("chess_core_extra"), LevelFilter::Warn);
+        assert_eq!(state.level_for("chess_engine::engine"), LevelFilter::Warn);
+        assert_eq!(state.max_level(), LevelFilter::Off);
+    }
+}
@@ -0,0 +1,176 @@
+// Generational handle registry for engines owned by the FFI layer
+// A handle packs a slot index and that slot's generation, so a destroyed handle
+// never aliases a newer engine that happens to reuse the same slot. A slot whose
+// generations are used up is retired rather than wrapped around to generation 1.
+
+use crate::events::{EventQueue, SharedEventQueue};
+use chess_engine::{ChessEngine, SearchStatusHandle};
+use std::os::raw::c_long;
+use std::sync::{Arc, Mutex};
+
+pub type EngineId = c_long;
+/// Engines are locked individually so a long search does not block the registry
+pub type SharedEngine = Arc<Mutex<ChessEngine>>;
+
+// Handles stay within 31 bits so they are positive even where `c_long` is 32-bit
+const SLOT_BITS: u32 = 20;
+const SLOT_MASK: EngineId = (1 << SLOT_BITS) - 1;
+const GENERATION_MASK: u32 = (1 << 11) - 1;
+
+struct Slot {
+    generation: u32,
+    engine: Option<SharedEngine>,
+    /// Cloned out of the engine so progress can be read while it is locked in a search
+    status: Option<SearchStatusHandle>,
+    /// The engine's event handler, polled by the host
+    events: Option<SharedEventQueue>,
+}
+
+#[derive(Default)]
+pub struct EngineRegistry {
+    slots: Vec<Slot>,
+    free: Vec<usize>,
+}
+
+impl EngineRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store an engine and return its handle, or -1 if every slot is in use
+    pub fn insert(&mut self, mut engine: ChessEngine) -> EngineId {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None if self.slots.len() <= SLOT_MASK as usize => {
+                self.slots.push(Slot {
+                    generation: 1,
+                    engine: None,
+                    status: None,
+                    events: None,
+                });
+                self.slots.len() - 1
+            }
+            None => return -1,
+        };
+
+        let events = Arc::new(Mutex::new(EventQueue::new()));
+        engine.set_event_handler(events.clone());
+
+        let slot = &mut self.slots[index];
+        slot.status = Some(engine.search_status_handle());
+        slot.events = Some(events);
+        slot.engine = Some(Arc::new(Mutex::new(engine)));
+        Self::encode(index, slot.generation)
+    }
+
+    pub fn get(&self, id: EngineId) -> Option<SharedEngine> {
+        self.slot(id)?.engine.clone()
+    }
+
+    pub fn status(&self, id: EngineId) -> Option<SearchStatusHandle> {
+        self.slot(id)?.status.clone()
+    }
+
+    pub fn events(&self, id: EngineId) -> Option<SharedEventQueue> {
+        self.slot(id)?.events.clone()
+    }
+
+    pub fn contains(&self, id: EngineId) -> bool {
+        self.slot(id).is_some_and(|slot| slot.engine.is_some())
+    }
+
+    /// Remove an engine; stale and already-destroyed handles return `None`
+    pub fn remove(&mut self, id: EngineId) -> Option<SharedEngine> {
+        let (index, generation) = Self::decode(id)?;
+        let slot = self.slots.get_mut(index)?;
+        if slot.generation != generation {
+            return None;
+        }
+
+        let engine = slot.engine.take()?;
+        slot.status = None;
+        slot.events = None;
+        if slot.generation == GENERATION_MASK {
+            // Generation 0 never decodes, so the retired slot rejects every handle
+            slot.generation = 0;
+        } else {
+            slot.generation += 1;
+            self.free.push(index);
+        }
+        Some(engine)
+    }
+
+    fn slot(&self, id: EngineId) -> Option<&Slot> {
+        let (index, generation) = Self::decode(id)?;
+        let slot = self.slots.get(index)?;
+        (slot.generation == generation).then_some(slot)
+    }
+
+    fn encode(index: usize, generation: u32) -> EngineId {
+        ((generation as EngineId) << SLOT_BITS) | index as EngineId
+    }
+
+    fn decode(id: EngineId) -> Option<(usize, u32)> {
+        if id <= 0 {
+            return None;
+        }
+        let generation = (id >> SLOT_BITS) as u32;
+        if generation == 0 || generation > GENERATION_MASK {
+            return None;
+        }
+        Some(((id & SLOT_MASK) as usize, generation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_handle_is_rejected_after_slot_reuse() {
+        let mut registry = EngineRegistry::new();
+        let first = registry.insert(ChessEngine::new());
+        assert!(first > 0);
+        assert!(registry.contains(first));
+
+        assert!(registry.remove(first).is_some());
+        assert!(registry.remove(first).is_none(), "double destroy must fail");
+
+        let second = registry.insert(ChessEngine::new());
+        assert_ne!(first, second);
+        assert!(!registry.contains(first));
+        assert!(registry.get(first).is_none());
+        assert!(registry.contains(second));
+    }
+
+    #[test]
+    fn test_slot_is_retired_instead_of_reusing_generations() {
+        let mut registry = EngineRegistry::new();
+        let first = registry.insert(ChessEngine::new());
+        assert!(registry.remove(first).is_some());
+
+        for _ in 0..2048 {
+            let id = registry.insert(ChessEngine::new());
+            assert_ne!(id, first);
+            assert!(!registry.contains(first));
+            assert!(registry.remove(id).is_some());
+        }
+        assert!(registry.get(first).is_none());
+        assert!(registry.remove(first).is_none());
+        assert!(
+            registry.slots.len() > 1,
+            "the first slot must have been retired"
+        );
+    }
+
+    #[test]
+    fn test_invalid_handles() {
+        let mut registry = EngineRegistry::new();
+        registry.insert(ChessEngine::new());
+
+        for id in [-1, 0, 1, 12345, EngineId::MAX] {
+            assert!(!registry.contains(id));
+            assert!(registry.status(id).is_none());
+        }
+    }
+}
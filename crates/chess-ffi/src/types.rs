@@ -0,0 +1,519 @@
+// C-layout structs shared with C, C++, Swift and Kotlin consumers
+// Field order and sizes are part of the ABI: append fields, never reorder them
+
+use chess_engine::event::{CastleSide, DrawReason};
+use chess_engine::{
+    Color, EvalBreakdown, GameEvent, GameInfo, GameResult, Move, MoveType, NotationStyle, Piece,
+    PieceType, RootFail, SearchLimits, SearchResult, SearchStatus,
+};
+use std::ffi::c_void;
+use std::os::raw::{c_char, c_int};
+use std::time::Duration;
+
+/// Version of this C interface, returned by `chess_engine_abi_version`. Bumped whenever
+/// a function signature or a struct layout changes incompatibly; appending a function
+/// keeps the version. Matches `chess_engine::ABI_VERSION`.
+pub const CHESS_ENGINE_ABI_VERSION: u32 = 1;
+
+pub const CHESS_MOVE_NORMAL: u8 = 0;
+pub const CHESS_MOVE_CAPTURE: u8 = 1;
+pub const CHESS_MOVE_EN_PASSANT: u8 = 2;
+pub const CHESS_MOVE_CASTLE: u8 = 3;
+pub const CHESS_MOVE_PROMOTION: u8 = 4;
+pub const CHESS_MOVE_PROMOTION_CAPTURE: u8 = 5;
+/// A piece from the hand in drop variants; `from` equals `to` and `promotion` names the piece
+pub const CHESS_MOVE_DROP: u8 = 6;
+
+/// Promotion value for moves that do not promote
+pub const CHESS_PIECE_NONE: u8 = 0;
+pub const CHESS_PIECE_KNIGHT: u8 = 1;
+pub const CHESS_PIECE_BISHOP: u8 = 2;
+pub const CHESS_PIECE_ROOK: u8 = 3;
+pub const CHESS_PIECE_QUEEN: u8 = 4;
+/// Only used for the piece of a `CHESS_MOVE_DROP`
+pub const CHESS_PIECE_PAWN: u8 = 5;
+
+pub const CHESS_MAX_PV_LENGTH: usize = 16;
+
+/// `chess_engine_find_best_cmove` failed: invalid handle, uninitialized engine or null output
+pub const CHESS_BEST_MOVE_ERROR: c_int = 0;
+/// `chess_engine_find_best_cmove` wrote the best move
+pub const CHESS_BEST_MOVE_FOUND: c_int = 1;
+/// `chess_engine_find_best_cmove` found the game already over and wrote its result
+pub const CHESS_BEST_MOVE_GAME_OVER: c_int = 2;
+
+/// Coordinate notation such as `g1f3`
+pub const CHESS_NOTATION_UCI: c_int = 0;
+/// Standard algebraic notation such as `Nf3`
+pub const CHESS_NOTATION_SAN: c_int = 1;
+/// Algebraic notation with piece glyphs such as `♘f3`, UTF-8 encoded
+pub const CHESS_NOTATION_FIGURINE: c_int = 2;
+/// Coordinate notation with castling as the king taking its rook (`e1h1`), for GUIs in
+/// UCI_Chess960 mode; moves passed in may then castle that way too
+pub const CHESS_NOTATION_UCI_CHESS960: c_int = 3;
+
+pub const CHESS_EVENT_GAME_STARTED: c_int = 1;
+pub const CHESS_EVENT_MOVE_MADE: c_int = 2;
+pub const CHESS_EVENT_PIECE_CAPTURED: c_int = 3;
+pub const CHESS_EVENT_CHECK: c_int = 4;
+pub const CHESS_EVENT_CHECKMATE: c_int = 5;
+pub const CHESS_EVENT_STALEMATE: c_int = 6;
+pub const CHESS_EVENT_DRAW: c_int = 7;
+pub const CHESS_EVENT_PROMOTION: c_int = 8;
+pub const CHESS_EVENT_CASTLE: c_int = 9;
+pub const CHESS_EVENT_EN_PASSANT: c_int = 10;
+pub const CHESS_EVENT_GAME_ENDED: c_int = 11;
+/// Blunder mode chose `mv` on purpose; `best_move` was best and `detail` is the loss
+/// in centipawns
+pub const CHESS_EVENT_DELIBERATE_MISTAKE: c_int = 12;
+
+pub const CHESS_DRAW_STALEMATE: c_int = 0;
+pub const CHESS_DRAW_FIFTY_MOVE_RULE: c_int = 1;
+pub const CHESS_DRAW_THREEFOLD_REPETITION: c_int = 2;
+pub const CHESS_DRAW_INSUFFICIENT_MATERIAL: c_int = 3;
+pub const CHESS_DRAW_AGREEMENT: c_int = 4;
+
+pub const CHESS_CASTLE_KINGSIDE: c_int = 0;
+pub const CHESS_CASTLE_QUEENSIDE: c_int = 1;
+
+/// Score `chess_engine_evaluate_fens` writes for a null or unparseable FEN
+pub const CHESS_EVAL_INVALID_FEN: i32 = i32::MIN;
+
+/// The root score fit the first aspiration window of the last iteration
+pub const CHESS_ROOT_STABLE: c_int = 0;
+/// The root score failed high and was re-searched
+pub const CHESS_ROOT_FAIL_HIGH: c_int = 1;
+/// The root score failed low and was re-searched
+pub const CHESS_ROOT_FAIL_LOW: c_int = 2;
+
+/// Log level that passes nothing on
+pub const CHESS_LOG_OFF: c_int = 0;
+pub const CHESS_LOG_ERROR: c_int = 1;
+pub const CHESS_LOG_WARN: c_int = 2;
+pub const CHESS_LOG_INFO: c_int = 3;
+pub const CHESS_LOG_DEBUG: c_int = 4;
+pub const CHESS_LOG_TRACE: c_int = 5;
+
+/// Function receiving each log record: its level (`CHESS_LOG_ERROR` to
+/// `CHESS_LOG_TRACE`), the module that logged it, the message and the host's pointer.
+/// The strings are only valid during the call.
+pub type ChessLogCallback =
+    Option<unsafe extern "C" fn(c_int, *const c_char, *const c_char, *mut c_void)>;
+
+pub fn notation_style(code: c_int) -> Option<NotationStyle> {
+    match code {
+        CHESS_NOTATION_UCI => Some(NotationStyle::Uci),
+        CHESS_NOTATION_SAN => Some(NotationStyle::San),
+        CHESS_NOTATION_FIGURINE => Some(NotationStyle::Figurine),
+        CHESS_NOTATION_UCI_CHESS960 => Some(NotationStyle::UciChess960),
+        _ => None,
+    }
+}
+
+/// A move with squares indexed 0 (a1) to 63 (h8)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CMove {
+    pub from: u8,
+    pub to: u8,
+    /// One of the `CHESS_MOVE_*` constants
+    pub move_type: u8,
+    /// One of the `CHESS_PIECE_*` constants
+    pub promotion: u8,
+}
+
+impl From<Move> for CMove {
+    fn from(mv: Move) -> Self {
+        let (move_type, promotion) = match mv.move_type {
+            MoveType::Normal => (CHESS_MOVE_NORMAL, None),
+            MoveType::Capture => (CHESS_MOVE_CAPTURE, None),
+            MoveType::EnPassant => (CHESS_MOVE_EN_PASSANT, None),
+            MoveType::Castle => (CHESS_MOVE_CASTLE, None),
+            MoveType::Promotion { piece } => (CHESS_MOVE_PROMOTION, Some(piece)),
+            MoveType::PromotionCapture { piece } => (CHESS_MOVE_PROMOTION_CAPTURE, Some(piece)),
+            MoveType::Drop { piece } => (CHESS_MOVE_DROP, Some(piece)),
+        };
+
+        CMove {
+            from: mv.from.index(),
+            to: mv.to.index(),
+            move_type,
+            promotion: match promotion {
+                Some(PieceType::Knight) => CHESS_PIECE_KNIGHT,
+                Some(PieceType::Bishop) => CHESS_PIECE_BISHOP,
+                Some(PieceType::Rook) => CHESS_PIECE_ROOK,
+                Some(PieceType::Queen) => CHESS_PIECE_QUEEN,
+                Some(PieceType::Pawn) => CHESS_PIECE_PAWN,
+                _ => CHESS_PIECE_NONE,
+            },
+        }
+    }
+}
+
+impl CMove {
+    /// UCI text for the move; `move_type` is ignored since legality resolves it
+    pub fn to_uci(self) -> Option<String> {
+        let from = chess_engine::Square::new(self.from)?;
+        let to = chess_engine::Square::new(self.to)?;
+        if self.move_type == CHESS_MOVE_DROP {
+            let piece = match self.promotion {
+                CHESS_PIECE_PAWN => 'P',
+                CHESS_PIECE_KNIGHT => 'N',
+                CHESS_PIECE_BISHOP => 'B',
+                CHESS_PIECE_ROOK => 'R',
+                CHESS_PIECE_QUEEN => 'Q',
+                _ => return None,
+            };
+            return Some(format!("{}@{}", piece, to));
+        }
+        let promotion = match self.promotion {
+            CHESS_PIECE_NONE => "",
+            CHESS_PIECE_KNIGHT => "n",
+            CHESS_PIECE_BISHOP => "b",
+            CHESS_PIECE_ROOK => "r",
+            CHESS_PIECE_QUEEN => "q",
+            _ => return None,
+        };
+        Some(format!("{}{}{}", from, to, promotion))
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CSearchResult {
+    pub best_move: CMove,
+    /// False when the side to move has no legal moves
+    pub has_best_move: bool,
+    /// Centipawns from the side to move's point of view
+    pub evaluation: c_int,
+    pub depth: u8,
+    pub nodes: u64,
+    pub elapsed_ms: u64,
+    pub pv_length: u8,
+    pub pv: [CMove; CHESS_MAX_PV_LENGTH],
+    /// Aspiration re-searches over the whole search
+    pub aspiration_researches: u32,
+    /// One of the `CHESS_ROOT_*` constants; anything but stable marks a score still moving
+    pub root_fail: c_int,
+    /// Deepest ply reached, quiescence and extensions included
+    pub seldepth: u8,
+}
+
+impl From<&SearchResult> for CSearchResult {
+    fn from(result: &SearchResult) -> Self {
+        let mut pv = [CMove::default(); CHESS_MAX_PV_LENGTH];
+        let mut pv_length = 0;
+        for mv in result.principal_variation.iter().map_while(|mv| *mv) {
+            pv[pv_length] = CMove::from(mv);
+            pv_length += 1;
+        }
+
+        CSearchResult {
+            best_move: result.best_move.map(CMove::from).unwrap_or_default(),
+            has_best_move: result.best_move.is_some(),
+            evaluation: result.evaluation,
+            depth: result.depth,
+            nodes: result.nodes_searched,
+            elapsed_ms: result.elapsed_time.as_millis() as u64,
+            pv_length: pv_length as u8,
+            pv,
+            aspiration_researches: result.aspiration_researches,
+            root_fail: match result.root_fail {
+                None => CHESS_ROOT_STABLE,
+                Some(RootFail::High) => CHESS_ROOT_FAIL_HIGH,
+                Some(RootFail::Low) => CHESS_ROOT_FAIL_LOW,
+            },
+            seldepth: result.seldepth,
+        }
+    }
+}
+
+/// Limits of one search; a zero field is unset. Times are in milliseconds.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CSearchLimits {
+    pub depth: u8,
+    pub nodes: u64,
+    pub movetime_ms: u64,
+    pub wtime_ms: u64,
+    pub btime_ms: u64,
+    pub winc_ms: u64,
+    pub binc_ms: u64,
+    pub movestogo: u32,
+    /// Look for a mate in at most this many moves
+    pub mate: u8,
+    pub infinite: bool,
+}
+
+impl From<&CSearchLimits> for SearchLimits {
+    fn from(limits: &CSearchLimits) -> Self {
+        let millis = |ms: u64| (ms > 0).then(|| Duration::from_millis(ms));
+        SearchLimits {
+            depth: (limits.depth > 0).then_some(limits.depth),
+            nodes: (limits.nodes > 0).then_some(limits.nodes),
+            movetime: millis(limits.movetime_ms),
+            wtime: millis(limits.wtime_ms),
+            btime: millis(limits.btime_ms),
+            winc: millis(limits.winc_ms),
+            binc: millis(limits.binc_ms),
+            movestogo: (limits.movestogo > 0).then_some(limits.movestogo),
+            mate: (limits.mate > 0).then_some(limits.mate),
+            infinite: limits.infinite,
+        }
+    }
+}
+
+/// Live counters of a running (or the most recent) search
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CSearchStatus {
+    pub depth: u8,
+    /// Transposition table fill in permille
+    pub hashfull: u16,
+    pub nodes: u64,
+    pub nps: u64,
+    pub tbhits: u64,
+    pub elapsed_ms: u64,
+    /// Deepest ply reached so far in the current iteration
+    pub seldepth: u8,
+}
+
+impl From<SearchStatus> for CSearchStatus {
+    fn from(status: SearchStatus) -> Self {
+        CSearchStatus {
+            depth: status.depth,
+            hashfull: status.hashfull,
+            nodes: status.nodes,
+            nps: status.nps,
+            tbhits: status.tbhits,
+            elapsed_ms: status.elapsed.as_millis() as u64,
+            seldepth: status.seldepth,
+        }
+    }
+}
+
+/// A game event; fields that do not apply to `kind` are zero
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CGameEvent {
+    /// One of the `CHESS_EVENT_*` constants
+    pub kind: c_int,
+    /// The move played, for `CHESS_EVENT_MOVE_MADE` and `CHESS_EVENT_DELIBERATE_MISTAKE`
+    pub mv: CMove,
+    /// Square of the captured or promoted piece, or of the pawn taken en passant
+    pub square: u8,
+    /// Captured or promoted piece, as a signed board code
+    pub piece: i8,
+    /// Side in check, winner of a checkmate or side castling: 0 for White, 1 for Black
+    pub color: c_int,
+    /// Game result code for `GAME_ENDED`, `CHESS_DRAW_*` for `DRAW`, `CHESS_CASTLE_*`,
+    /// or centipawns lost for `DELIBERATE_MISTAKE`
+    pub detail: c_int,
+    /// The move the engine judged best, for `CHESS_EVENT_DELIBERATE_MISTAKE`
+    pub best_move: CMove,
+}
+
+impl From<&GameEvent> for CGameEvent {
+    fn from(event: &GameEvent) -> Self {
+        let empty = CGameEvent::default();
+        match event {
+            GameEvent::GameStarted => CGameEvent {
+                kind: CHESS_EVENT_GAME_STARTED,
+                ..empty
+            },
+            GameEvent::MoveMade { mv, .. } => CGameEvent {
+                kind: CHESS_EVENT_MOVE_MADE,
+                mv: CMove::from(*mv),
+                ..empty
+            },
+            GameEvent::PieceCaptured { piece, square } => CGameEvent {
+                kind: CHESS_EVENT_PIECE_CAPTURED,
+                square: square.index(),
+                piece: piece_code(*piece),
+                ..empty
+            },
+            GameEvent::Check { color } => CGameEvent {
+                kind: CHESS_EVENT_CHECK,
+                color: color_code(*color),
+                ..empty
+            },
+            GameEvent::Checkmate { winner } => CGameEvent {
+                kind: CHESS_EVENT_CHECKMATE,
+                color: color_code(*winner),
+                ..empty
+            },
+            GameEvent::Stalemate => CGameEvent {
+                kind: CHESS_EVENT_STALEMATE,
+                ..empty
+            },
+            GameEvent::Draw { reason } => CGameEvent {
+                kind: CHESS_EVENT_DRAW,
+                detail: draw_reason_code(*reason),
+                ..empty
+            },
+            GameEvent::Promotion { piece, square } => CGameEvent {
+                kind: CHESS_EVENT_PROMOTION,
+                square: square.index(),
+                piece: piece_code(*piece),
+                ..empty
+            },
+            GameEvent::Castle { color, side } => CGameEvent {
+                kind: CHESS_EVENT_CASTLE,
+                color: color_code(*color),
+                detail: match side {
+                    CastleSide::Kingside => CHESS_CASTLE_KINGSIDE,
+                    CastleSide::Queenside => CHESS_CASTLE_QUEENSIDE,
+                },
+                ..empty
+            },
+            GameEvent::EnPassant { captured_square } => CGameEvent {
+                kind: CHESS_EVENT_EN_PASSANT,
+                square: captured_square.index(),
+                ..empty
+            },
+            GameEvent::GameEnded { result } => CGameEvent {
+                kind: CHESS_EVENT_GAME_ENDED,
+                detail: game_result_code(*result),
+                ..empty
+            },
+            GameEvent::DeliberateMistake {
+                mv,
+                best_move,
+                loss,
+            } => CGameEvent {
+                kind: CHESS_EVENT_DELIBERATE_MISTAKE,
+                mv: CMove::from(*mv),
+                detail: *loss,
+                best_move: CMove::from(*best_move),
+                ..empty
+            },
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CGameInfo {
+    /// 0 for White, 1 for Black
+    pub side_to_move: c_int,
+    pub is_check: bool,
+    pub is_checkmate: bool,
+    pub is_stalemate: bool,
+    pub is_draw: bool,
+    /// Same codes as `chess_engine_get_game_result`
+    pub result: c_int,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+    pub legal_move_count: u32,
+    /// How often the current position has occurred, itself included
+    pub repetition_count: u32,
+}
+
+impl CGameInfo {
+    pub fn new(info: &GameInfo, result: GameResult) -> Self {
+        CGameInfo {
+            side_to_move: color_code(info.side_to_move),
+            is_check: info.is_check,
+            is_checkmate: info.is_checkmate,
+            is_stalemate: info.is_stalemate,
+            is_draw: info.is_draw,
+            result: game_result_code(result),
+            halfmove_clock: info.halfmove_clock,
+            fullmove_number: info.fullmove_number,
+            legal_move_count: info.legal_moves.len() as u32,
+            repetition_count: info.repetition_count,
+        }
+    }
+}
+
+pub fn color_code(color: Color) -> c_int {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+/// Signed piece code used by board arrays: 1-6 for White pawn through king, negated for Black
+pub fn piece_code(piece: Piece) -> i8 {
+    let code = piece.piece_type.index() as i8 + 1;
+    match piece.color {
+        Color::White => code,
+        Color::Black => -code,
+    }
+}
+
+pub fn game_result_code(result: GameResult) -> c_int {
+    match result {
+        GameResult::Ongoing => 0,
+        GameResult::WhiteWins => 1,
+        GameResult::BlackWins => 2,
+        GameResult::Draw => 3,
+    }
+}
+
+/// `CHESS_DRAW_*` code of a draw reason
+pub fn draw_reason_code(reason: DrawReason) -> c_int {
+    match reason {
+        DrawReason::Stalemate => CHESS_DRAW_STALEMATE,
+        DrawReason::FiftyMoveRule => CHESS_DRAW_FIFTY_MOVE_RULE,
+        DrawReason::ThreefoldRepetition => CHESS_DRAW_THREEFOLD_REPETITION,
+        DrawReason::InsufficientMaterial => CHESS_DRAW_INSUFFICIENT_MATERIAL,
+        DrawReason::Agreement => CHESS_DRAW_AGREEMENT,
+    }
+}
+
+/// JSON object of the evaluation terms in White-relative centipawns, plus the total
+/// from the side to move's point of view
+pub fn breakdown_json(breakdown: &EvalBreakdown, side_to_move: Color) -> String {
+    let side_total = match side_to_move {
+        Color::White => breakdown.total,
+        Color::Black => -breakdown.total,
+    };
+    format!(
+        concat!(
+            "{{\"material\":{},\"piece_squares\":{},\"mobility\":{},",
+            "\"king_safety\":{},\"pawn_structure\":{},\"piece_activity\":{},",
+            "\"threats\":{},\"scale\":{},\"known_ending\":{},\"total\":{},",
+            "\"side_to_move_total\":{}}}"
+        ),
+        breakdown.material,
+        breakdown.piece_squares,
+        breakdown.mobility,
+        breakdown.king_safety,
+        breakdown.pawn_structure,
+        breakdown.piece_activity,
+        breakdown.threats,
+        breakdown.scale,
+        breakdown.known_ending,
+        breakdown.total,
+        side_total
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess_engine::Square;
+
+    #[test]
+    fn test_cmove_round_trips_through_uci() {
+        let mv = Move::promotion_capture(Square::B7, Square::A8, PieceType::Knight);
+        let cmove = CMove::from(mv);
+        assert_eq!(cmove.from, Square::B7.index());
+        assert_eq!(cmove.to, Square::A8.index());
+        assert_eq!(cmove.move_type, CHESS_MOVE_PROMOTION_CAPTURE);
+        assert_eq!(cmove.promotion, CHESS_PIECE_KNIGHT);
+        assert_eq!(cmove.to_uci().as_deref(), Some("b7a8n"));
+
+        let drop = CMove::from(Move::drop(PieceType::Pawn, Square::E4));
+        assert_eq!(drop.move_type, CHESS_MOVE_DROP);
+        assert_eq!(drop.promotion, CHESS_PIECE_PAWN);
+        assert_eq!(drop.to_uci().as_deref(), Some("P@e4"));
+
+        let invalid = CMove {
+            from: 64,
+            ..CMove::default()
+        };
+        assert!(invalid.to_uci().is_none());
+    }
+}
@@ -0,0 +1,120 @@
+package com.chess.engine
+
+import kotlinx.coroutines.suspendCancellableCoroutine
+import java.io.Closeable
+import kotlin.coroutines.resume
+
+/**
+ * Kotlin wrapper over the `chess_jni` native library.
+ *
+ * Each instance owns one native engine; call [close] (or use `use {}`) to free it.
+ */
+class ChessEngine private constructor(private val handle: Long) : Closeable {
+
+    constructor() : this(createEngine())
+
+    init {
+        require(handle > 0) { "Failed to create native chess engine" }
+        initialize(handle)
+    }
+
+    private var closed = false
+
+    val fen: String
+        get() = getFen(checkedHandle())
+
+    /** 0 for White, 1 for Black */
+    val sideToMove: Int
+        get() = getSideToMove(checkedHandle())
+
+    val isInCheck: Boolean
+        get() = isInCheck(checkedHandle())
+
+    /** 0 ongoing, 1 White wins, 2 Black wins, 3 draw */
+    val gameResult: Int
+        get() = getGameResult(checkedHandle())
+
+    /**
+     * The board as 64 bytes indexed by square (a1 = 0): 0 for empty, 1..6 for White
+     * pawn, knight, bishop, rook, queen, king and -1..-6 for the Black pieces.
+     */
+    fun boardArray(): ByteArray = getBoardArray(checkedHandle())
+
+    fun legalMoves(): List<String> = getLegalMoves(checkedHandle()).toList()
+
+    fun loadFen(fen: String): Boolean = loadFen(checkedHandle(), fen)
+
+    fun isLegalMove(uciMove: String): Boolean = isLegalMove(checkedHandle(), uciMove)
+
+    fun makeMove(uciMove: String): Boolean = makeMove(checkedHandle(), uciMove)
+
+    /** Centipawns from the side to move's point of view */
+    fun evaluate(): Int = evaluate(checkedHandle())
+
+    /** Centipawns from White's point of view */
+    fun evaluateAbsolute(): Int = evaluateAbsolute(checkedHandle())
+
+    /** Blocks the calling thread; prefer [bestMove] from coroutines */
+    fun findBestMoveBlocking(): String? = findBestMove(checkedHandle())
+
+    fun findBestMoveAsync(callback: SearchCallback): Boolean =
+        findBestMoveAsync(checkedHandle(), callback)
+
+    /** Suspends until the native search finishes, without blocking a dispatcher thread */
+    suspend fun bestMove(): String? = suspendCancellableCoroutine { continuation ->
+        val started = findBestMoveAsync(checkedHandle()) { bestMove ->
+            if (continuation.isActive) continuation.resume(bestMove)
+        }
+        if (!started) continuation.resume(null)
+    }
+
+    override fun close() {
+        if (!closed) {
+            closed = true
+            destroyEngine(handle)
+        }
+    }
+
+    private fun checkedHandle(): Long {
+        check(!closed) { "ChessEngine has been closed" }
+        return handle
+    }
+
+    companion object {
+        /** Version of the native interface this wrapper was written against */
+        const val ABI_VERSION = 1
+
+        init {
+            System.loadLibrary("chess_jni")
+            val found = abiVersion()
+            check(found == ABI_VERSION) {
+                "chess_jni has ABI version $found but this wrapper needs $ABI_VERSION"
+            }
+        }
+
+        /** Returns null when the FEN cannot be parsed */
+        fun fromFen(fen: String): ChessEngine? {
+            val handle = createEngineFromFen(fen)
+            return if (handle > 0) ChessEngine(handle) else null
+        }
+
+        @JvmStatic private external fun abiVersion(): Int
+        @JvmStatic private external fun createEngine(): Long
+        @JvmStatic private external fun createEngineFromFen(fen: String): Long
+        @JvmStatic private external fun destroyEngine(engineId: Long): Boolean
+        @JvmStatic private external fun initialize(engineId: Long): Boolean
+        @JvmStatic private external fun getFen(engineId: Long): String
+        @JvmStatic private external fun loadFen(engineId: Long, fen: String): Boolean
+        @JvmStatic private external fun isLegalMove(engineId: Long, uciMove: String): Boolean
+        @JvmStatic private external fun makeMove(engineId: Long, uciMove: String): Boolean
+        @JvmStatic private external fun getLegalMoves(engineId: Long): Array<String>
+        @JvmStatic private external fun getBoardArray(engineId: Long): ByteArray
+        @JvmStatic private external fun getSideToMove(engineId: Long): Int
+        @JvmStatic private external fun isInCheck(engineId: Long): Boolean
+        @JvmStatic private external fun getGameResult(engineId: Long): Int
+        @JvmStatic private external fun evaluate(engineId: Long): Int
+        @JvmStatic private external fun evaluateAbsolute(engineId: Long): Int
+        @JvmStatic private external fun findBestMove(engineId: Long): String?
+        @JvmStatic private external fun findBestMoveAsync(engineId: Long, callback: SearchCallback): Boolean
+    }
+}
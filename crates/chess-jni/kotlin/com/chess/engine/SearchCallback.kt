@@ -0,0 +1,11 @@
+package com.chess.engine
+
+/**
+ * Receives the result of [ChessEngine.findBestMoveAsync].
+ *
+ * Called on a native worker thread; post back to the main thread before touching UI.
+ */
+fun interface SearchCallback {
+    /** [bestMove] is in UCI form (e.g. "e2e4"), or null when there is no legal move */
+    fun onSearchComplete(bestMove: String?)
+}
@@ -1,28 +1,69 @@
-use chess_core::{Move, PieceType, Square};
-use chess_engine::ChessEngine;
-use jni::objects::{JClass, JString};
-use jni::sys::{jboolean, jlong, jstring};
+use chess_engine::{BestMove, ChessEngine, Color, GameResult, Move, Square};
+use jni::objects::{JClass, JObject, JString, JValue};
+use jni::sys::{jboolean, jbyteArray, jint, jlong, jobjectArray, jstring};
 use jni::JNIEnv;
 use std::collections::HashMap;
-use std::str::FromStr;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::{
     atomic::{AtomicI64, Ordering},
-    Arc, Mutex, OnceLock,
+    Arc, Mutex, MutexGuard, OnceLock, PoisonError,
 };
+use std::thread;
 
 type EngineId = i64;
+/// Each engine has its own lock so a background search does not block other engines
+type SharedEngine = Arc<Mutex<ChessEngine>>;
 
 static ENGINE_COUNTER: AtomicI64 = AtomicI64::new(0);
-static ENGINES: OnceLock<Arc<Mutex<HashMap<EngineId, ChessEngine>>>> = OnceLock::new();
+static ENGINES: OnceLock<Arc<Mutex<HashMap<EngineId, SharedEngine>>>> = OnceLock::new();
 
-fn get_engines() -> &'static Arc<Mutex<HashMap<EngineId, ChessEngine>>> {
+fn get_engines() -> &'static Arc<Mutex<HashMap<EngineId, SharedEngine>>> {
     ENGINES.get_or_init(|| Arc::new(Mutex::new(HashMap::new())))
 }
 
+/// Lock the engine registry, recovering it if a caught panic poisoned the lock
+fn lock_engines() -> MutexGuard<'static, HashMap<EngineId, SharedEngine>> {
+    get_engines().lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn get_engine(engine_id: EngineId) -> Option<SharedEngine> {
+    lock_engines().get(&engine_id).cloned()
+}
+
+fn lock_engine(engine: &Mutex<ChessEngine>) -> MutexGuard<'_, ChessEngine> {
+    engine.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Run an entry point, rethrowing a panic as a Java `RuntimeException` instead of
+/// unwinding into the JVM
+fn guard<T>(env: JNIEnv, fallback: T, f: impl FnOnce() -> T) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(value) => value,
+        Err(payload) => {
+            let message = panic_message(payload.as_ref());
+            if !env.exception_check().unwrap_or(true) {
+                let _ = env.throw_new(
+                    "java/lang/RuntimeException",
+                    format!("chess engine panicked: {}", message),
+                );
+            }
+            fallback
+        }
+    }
+}
+
 fn get_next_id() -> EngineId {
     ENGINE_COUNTER.fetch_add(1, Ordering::Relaxed) + 1
 }
 
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
 fn string_to_jstring<'a>(env: JNIEnv<'a>, s: &str) -> jstring {
     env.new_string(s)
         .expect("Failed to create JString")
@@ -33,35 +74,73 @@ fn jstring_to_string<'a>(env: JNIEnv<'a>, jstr: JString<'a>) -> String {
     env.get_string(jstr).expect("Failed to get JString").into()
 }
 
+/// `chess_engine::ABI_VERSION`, checked by the Kotlin wrapper as the library loads
 #[no_mangle]
-pub extern "system" fn Java_com_chess_engine_ChessEngine_createEngine(
+pub extern "system" fn Java_com_chess_engine_ChessEngine_abiVersion(
     _env: JNIEnv,
     _class: JClass,
+) -> jint {
+    chess_engine::ABI_VERSION as jint
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_chess_engine_ChessEngine_createEngine(
+    env: JNIEnv,
+    _class: JClass,
 ) -> jlong {
-    let engine = ChessEngine::new();
-    let id = get_next_id();
+    guard(env, -1, || {
+        let id = get_next_id();
+        lock_engines().insert(id, Arc::new(Mutex::new(ChessEngine::new())));
+        id
+    })
+}
 
-    let engines = get_engines();
-    if let Ok(mut engines_map) = engines.lock() {
-        engines_map.insert(id, engine);
-        return id;
-    }
-    -1
+#[no_mangle]
+pub extern "system" fn Java_com_chess_engine_ChessEngine_createEngineFromFen(
+    env: JNIEnv,
+    _class: JClass,
+    fen: JString,
+) -> jlong {
+    guard(env, -1, || {
+        let fen_str = jstring_to_string(env, fen);
+        match ChessEngine::from_fen(&fen_str) {
+            Ok(engine) => {
+                let id = get_next_id();
+                lock_engines().insert(id, Arc::new(Mutex::new(engine)));
+                id
+            }
+            Err(_) => -1,
+        }
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_chess_engine_ChessEngine_initialize(
+    env: JNIEnv,
+    _class: JClass,
+    engine_id: jlong,
+) -> jboolean {
+    guard(env, 0, || {
+        if let Some(engine) = get_engine(engine_id) {
+            return lock_engine(&engine).initialize().is_ok() as jboolean;
+        }
+        0
+    })
 }
 
 #[no_mangle]
 pub extern "system" fn Java_com_chess_engine_ChessEngine_destroyEngine(
-    _env: JNIEnv,
+    env: JNIEnv,
     _class: JClass,
     engine_id: jlong,
 ) -> jboolean {
-    let engines = get_engines();
-    if let Ok(mut engines_map) = engines.lock() {
+    guard(env, 0, || {
+        let mut engines_map = lock_engines();
         if engines_map.remove(&engine_id).is_some() {
             return 1;
         }
-    }
-    0
+        0
+    })
 }
 
 #[no_mangle]
@@ -70,14 +149,13 @@ pub extern "system" fn Java_com_chess_engine_ChessEngine_getFen(
     _class: JClass,
     engine_id: jlong,
 ) -> jstring {
-    let engines = get_engines();
-    if let Ok(engines_map) = engines.lock() {
-        if let Some(engine) = engines_map.get(&engine_id) {
-            let fen = engine.get_fen();
+    guard(env, std::ptr::null_mut(), || {
+        if let Some(engine) = get_engine(engine_id) {
+            let fen = lock_engine(&engine).get_fen();
             return string_to_jstring(env, &fen);
         }
-    }
-    string_to_jstring(env, "")
+        string_to_jstring(env, "")
+    })
 }
 
 #[no_mangle]
@@ -87,39 +165,262 @@ pub extern "system" fn Java_com_chess_engine_ChessEngine_isLegalMove(
     engine_id: jlong,
     uci_move: JString,
 ) -> jboolean {
-    let uci_str = jstring_to_string(env, uci_move);
-    let engines = get_engines();
-
-    if let Ok(engines_map) = engines.lock() {
-        if let Some(engine) = engines_map.get(&engine_id) {
-            // Parse UCI string (e.g., "e2e4" or "e7e8q")
-            if uci_str.len() < 4 {
-                return 0;
-            }
+    guard(env, 0, || {
+        let uci_str = jstring_to_string(env, uci_move);
+        if let Some(engine) = get_engine(engine_id) {
+            return if lock_engine(&engine).resolve_uci_move(&uci_str).is_ok() {
+                1
+            } else {
+                0
+            };
+        }
+        0
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_chess_engine_ChessEngine_loadFen(
+    env: JNIEnv,
+    _class: JClass,
+    engine_id: jlong,
+    fen: JString,
+) -> jboolean {
+    guard(env, 0, || {
+        let fen_str = jstring_to_string(env, fen);
+        if let Some(engine) = get_engine(engine_id) {
+            return lock_engine(&engine).load_fen(&fen_str).is_ok() as jboolean;
+        }
+        0
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_chess_engine_ChessEngine_makeMove(
+    env: JNIEnv,
+    _class: JClass,
+    engine_id: jlong,
+    uci_move: JString,
+) -> jboolean {
+    guard(env, 0, || {
+        let uci_str = jstring_to_string(env, uci_move);
+        if let Some(engine) = get_engine(engine_id) {
+            return match lock_engine(&engine).make_move_from_uci(&uci_str) {
+                Ok(result) if result.success => 1,
+                _ => 0,
+            };
+        }
+        0
+    })
+}
+
+/// Legal moves as a `String[]` of UCI moves
+#[no_mangle]
+pub extern "system" fn Java_com_chess_engine_ChessEngine_getLegalMoves(
+    env: JNIEnv,
+    _class: JClass,
+    engine_id: jlong,
+) -> jobjectArray {
+    guard(env, std::ptr::null_mut(), || {
+        let moves = match get_engine(engine_id) {
+            Some(engine) => lock_engine(&engine).get_legal_moves(),
+            None => Vec::new(),
+        };
 
-            let from = Square::from_str(&uci_str[0..2]);
-            let to = Square::from_str(&uci_str[2..4]);
-
-            match (from, to) {
-                (Ok(from), Ok(to)) => {
-                    // Handle promotion
-                    let mv = if uci_str.len() == 5 {
-                        match uci_str.chars().nth(4) {
-                            Some('q') => Move::promotion(from, to, PieceType::Queen),
-                            Some('r') => Move::promotion(from, to, PieceType::Rook),
-                            Some('b') => Move::promotion(from, to, PieceType::Bishop),
-                            Some('n') => Move::promotion(from, to, PieceType::Knight),
-                            _ => return 0,
-                        }
-                    } else {
-                        Move::normal(from, to)
-                    };
-
-                    return if engine.is_legal_move(mv) { 1 } else { 0 };
-                }
-                _ => return 0,
+        let array = env
+            .new_object_array(moves.len() as i32, "java/lang/String", JObject::null())
+            .expect("Failed to create String[]");
+        for (i, mv) in moves.iter().enumerate() {
+            let element = env
+                .new_string(mv.to_string())
+                .expect("Failed to create JString");
+            env.set_object_array_element(array, i as i32, element)
+                .expect("Failed to set array element");
+            // Free each string now rather than accumulating one local ref per move
+            let _ = env.delete_local_ref(element.into());
+        }
+        array
+    })
+}
+
+/// 64 bytes indexed by square (a1 = 0): 0 for empty, 1-6 for White pawn through
+/// king, and -1 to -6 for the Black pieces
+#[no_mangle]
+pub extern "system" fn Java_com_chess_engine_ChessEngine_getBoardArray(
+    env: JNIEnv,
+    _class: JClass,
+    engine_id: jlong,
+) -> jbyteArray {
+    guard(env, std::ptr::null_mut(), || {
+        let Some(engine) = get_engine(engine_id) else {
+            return std::ptr::null_mut();
+        };
+
+        let engine = lock_engine(&engine);
+        let mut board = [0u8; 64];
+        for square in Square::ALL {
+            if let Some(piece) = engine.get_position().piece_at(square) {
+                let code = piece.piece_type.index() as i8 + 1;
+                board[square.index() as usize] = match piece.color {
+                    Color::White => code,
+                    Color::Black => -code,
+                } as u8;
             }
         }
-    }
-    0
+        env.byte_array_from_slice(&board)
+            .expect("Failed to create byte[]")
+    })
+}
+
+/// 0 for White, 1 for Black, -1 for an unknown engine
+#[no_mangle]
+pub extern "system" fn Java_com_chess_engine_ChessEngine_getSideToMove(
+    env: JNIEnv,
+    _class: JClass,
+    engine_id: jlong,
+) -> jint {
+    guard(env, -1, || {
+        if let Some(engine) = get_engine(engine_id) {
+            return match lock_engine(&engine).get_side_to_move() {
+                Color::White => 0,
+                Color::Black => 1,
+            };
+        }
+        -1
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_com_chess_engine_ChessEngine_isInCheck(
+    env: JNIEnv,
+    _class: JClass,
+    engine_id: jlong,
+) -> jboolean {
+    guard(env, 0, || {
+        if let Some(engine) = get_engine(engine_id) {
+            return lock_engine(&engine).is_in_check() as jboolean;
+        }
+        0
+    })
+}
+
+/// 0 ongoing, 1 White wins, 2 Black wins, 3 draw, -1 for an unknown engine
+#[no_mangle]
+pub extern "system" fn Java_com_chess_engine_ChessEngine_getGameResult(
+    env: JNIEnv,
+    _class: JClass,
+    engine_id: jlong,
+) -> jint {
+    guard(env, -1, || {
+        if let Some(engine) = get_engine(engine_id) {
+            return match lock_engine(&engine).get_game_result() {
+                GameResult::Ongoing => 0,
+                GameResult::WhiteWins => 1,
+                GameResult::BlackWins => 2,
+                GameResult::Draw => 3,
+            };
+        }
+        -1
+    })
+}
+
+/// Centipawns from the side to move's point of view
+#[no_mangle]
+pub extern "system" fn Java_com_chess_engine_ChessEngine_evaluate(
+    env: JNIEnv,
+    _class: JClass,
+    engine_id: jlong,
+) -> jint {
+    guard(env, 0, || {
+        if let Some(engine) = get_engine(engine_id) {
+            return lock_engine(&engine).evaluate();
+        }
+        0
+    })
+}
+
+/// Centipawns from White's point of view
+#[no_mangle]
+pub extern "system" fn Java_com_chess_engine_ChessEngine_evaluateAbsolute(
+    env: JNIEnv,
+    _class: JClass,
+    engine_id: jlong,
+) -> jint {
+    guard(env, 0, || {
+        if let Some(engine) = get_engine(engine_id) {
+            return lock_engine(&engine).evaluate_absolute();
+        }
+        0
+    })
+}
+
+/// Blocking search; returns the best move in UCI form or null
+#[no_mangle]
+pub extern "system" fn Java_com_chess_engine_ChessEngine_findBestMove(
+    env: JNIEnv,
+    _class: JClass,
+    engine_id: jlong,
+) -> jstring {
+    guard(env, std::ptr::null_mut(), || {
+        match get_engine(engine_id).and_then(|engine| search_best_move(&engine)) {
+            Some(best_move) => string_to_jstring(env, &best_move.to_string()),
+            None => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Search on a background thread and report through
+/// `SearchCallback.onSearchComplete(String)`, passing null when there is no move.
+/// Returns false if the engine does not exist or the callback could not be retained.
+#[no_mangle]
+pub extern "system" fn Java_com_chess_engine_ChessEngine_findBestMoveAsync(
+    env: JNIEnv,
+    _class: JClass,
+    engine_id: jlong,
+    callback: JObject,
+) -> jboolean {
+    guard(env, 0, || {
+        let Some(engine) = get_engine(engine_id) else {
+            return 0;
+        };
+        // The local `callback` reference dies when this call returns, so keep a
+        // global reference alive for the worker thread
+        let (Ok(vm), Ok(callback)) = (env.get_java_vm(), env.new_global_ref(callback)) else {
+            return 0;
+        };
+
+        thread::spawn(move || {
+            let best_move = search_best_move(&engine);
+
+            let Ok(env) = vm.attach_current_thread() else {
+                return;
+            };
+            let argument = match best_move.map(|mv| env.new_string(mv.to_string())) {
+                Some(Ok(jstr)) => JObject::from(jstr),
+                _ => JObject::null(),
+            };
+            let _ = env.call_method(
+                callback.as_obj(),
+                "onSearchComplete",
+                "(Ljava/lang/String;)V",
+                &[JValue::Object(argument)],
+            );
+            // Nothing above us can handle an exception thrown by the callback
+            if env.exception_check().unwrap_or(false) {
+                let _ = env.exception_describe();
+                let _ = env.exception_clear();
+            }
+        });
+        1
+    })
+}
+
+/// Run a search, treating a panic as "no move" so worker threads never unwind
+fn search_best_move(engine: &Mutex<ChessEngine>) -> Option<Move> {
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        lock_engine(engine)
+            .find_best_move()
+            .ok()
+            .and_then(BestMove::into_move)
+    }))
+    .unwrap_or(None)
 }
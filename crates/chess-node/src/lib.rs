@@ -0,0 +1,189 @@
+// Node.js bindings mirroring the WASM API, with searches running on the libuv thread pool
+
+use chess_engine::{BestMove, ChessEngine, Color, GameResult};
+use napi::bindgen_prelude::AsyncTask;
+use napi::{Env, Error, Result, Status, Task};
+use napi_derive::napi;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+type SharedEngine = Arc<Mutex<ChessEngine>>;
+
+fn lock_engine(engine: &Mutex<ChessEngine>) -> MutexGuard<'_, ChessEngine> {
+    engine.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn initialized(mut engine: ChessEngine) -> SharedEngine {
+    // A fresh engine cannot already be initialized, so this never fails
+    let _ = engine.initialize();
+    Arc::new(Mutex::new(engine))
+}
+
+/// `chess_engine::ABI_VERSION` of this build, for wrappers to check as the module loads
+#[napi]
+pub fn abi_version() -> u32 {
+    chess_engine::ABI_VERSION
+}
+
+#[napi(js_name = "ChessEngine")]
+pub struct NodeChessEngine {
+    engine: SharedEngine,
+}
+
+impl Default for NodeChessEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[napi]
+impl NodeChessEngine {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        NodeChessEngine {
+            engine: initialized(ChessEngine::new()),
+        }
+    }
+
+    #[napi(factory)]
+    pub fn from_fen(fen: String) -> Result<Self> {
+        let engine = ChessEngine::from_fen(&fen)
+            .map_err(|e| Error::new(Status::InvalidArg, format!("Invalid FEN: {}", e)))?;
+        Ok(NodeChessEngine {
+            engine: initialized(engine),
+        })
+    }
+
+    #[napi]
+    pub fn get_fen(&self) -> String {
+        lock_engine(&self.engine).get_fen()
+    }
+
+    #[napi]
+    pub fn load_fen(&self, fen: String) -> bool {
+        lock_engine(&self.engine).load_fen(&fen).is_ok()
+    }
+
+    /// 0 for White, 1 for Black
+    #[napi]
+    pub fn get_side_to_move(&self) -> i32 {
+        match lock_engine(&self.engine).get_side_to_move() {
+            Color::White => 0,
+            Color::Black => 1,
+        }
+    }
+
+    #[napi]
+    pub fn make_move(&self, uci_move: String) -> bool {
+        matches!(
+            lock_engine(&self.engine).make_move_from_uci(&uci_move),
+            Ok(result) if result.success
+        )
+    }
+
+    #[napi]
+    pub fn is_legal_move(&self, uci_move: String) -> bool {
+        lock_engine(&self.engine)
+            .resolve_uci_move(&uci_move)
+            .is_ok()
+    }
+
+    #[napi]
+    pub fn get_legal_moves_count(&self) -> i32 {
+        lock_engine(&self.engine).get_legal_moves().len() as i32
+    }
+
+    #[napi]
+    pub fn get_legal_moves(&self) -> Vec<String> {
+        lock_engine(&self.engine)
+            .get_legal_moves()
+            .iter()
+            .map(|mv| mv.to_string())
+            .collect()
+    }
+
+    #[napi]
+    pub fn is_in_check(&self) -> bool {
+        lock_engine(&self.engine).is_in_check()
+    }
+
+    #[napi]
+    pub fn is_checkmate(&self) -> bool {
+        lock_engine(&self.engine).is_checkmate()
+    }
+
+    #[napi]
+    pub fn is_game_over(&self) -> bool {
+        lock_engine(&self.engine).is_game_over()
+    }
+
+    /// 0 ongoing, 1 White wins, 2 Black wins, 3 draw
+    #[napi]
+    pub fn get_game_result(&self) -> i32 {
+        match lock_engine(&self.engine).get_game_result() {
+            GameResult::Ongoing => 0,
+            GameResult::WhiteWins => 1,
+            GameResult::BlackWins => 2,
+            GameResult::Draw => 3,
+        }
+    }
+
+    /// Centipawns from the side to move's point of view
+    #[napi]
+    pub fn evaluate(&self) -> i32 {
+        lock_engine(&self.engine).evaluate()
+    }
+
+    /// Centipawns from White's point of view
+    #[napi]
+    pub fn evaluate_absolute(&self) -> i32 {
+        lock_engine(&self.engine).evaluate_absolute()
+    }
+
+    /// Set the search depth and time limit; omit `timeLimitMs` to search to full depth
+    #[napi]
+    pub fn set_search_limits(&self, depth: u32, time_limit_ms: Option<u32>) {
+        lock_engine(&self.engine).set_search_limits(
+            depth.min(u8::MAX as u32) as u8,
+            time_limit_ms.map(u64::from),
+        );
+    }
+
+    /// Blocking search on the calling (JS) thread
+    #[napi]
+    pub fn find_best_move(&self) -> Option<String> {
+        search_best_move(&self.engine)
+    }
+
+    /// Search on the libuv thread pool; resolves to the best move or `null`
+    #[napi(ts_return_type = "Promise<string | null>")]
+    pub fn find_best_move_async(&self) -> AsyncTask<SearchTask> {
+        AsyncTask::new(SearchTask {
+            engine: Arc::clone(&self.engine),
+        })
+    }
+}
+
+fn search_best_move(engine: &Mutex<ChessEngine>) -> Option<String> {
+    lock_engine(engine)
+        .find_best_move()
+        .ok()
+        .and_then(BestMove::into_move)
+        .map(|mv| mv.to_string())
+}
+
+pub struct SearchTask {
+    engine: SharedEngine,
+}
+
+impl Task for SearchTask {
+    type Output = Option<String>;
+    type JsValue = Option<String>;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        Ok(search_best_move(&self.engine))
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+/// Server-wide settings, including the resource limits applied to every session
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub bind_address: String,
+    pub max_sessions: usize,
+    /// Requested analysis depth is clamped to this
+    pub max_depth: u8,
+    /// Requested analysis time is clamped to this, and used when none is given
+    pub max_movetime: Duration,
+    /// Sessions untouched for this long are dropped to make room for new ones
+    pub idle_timeout: Duration,
+    /// Interval between streamed `info` events
+    pub progress_interval: Duration,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            bind_address: "127.0.0.1:8080".to_string(),
+            max_sessions: 64,
+            max_depth: 20,
+            max_movetime: Duration::from_secs(30),
+            idle_timeout: Duration::from_secs(600),
+            progress_interval: Duration::from_millis(100),
+        }
+    }
+}
@@ -0,0 +1,150 @@
+// Minimal HTTP/1.1 handling: one request per connection, bodies sized by Content-Length
+
+use serde_json::Value;
+use std::io::{self, BufRead, Write};
+
+/// Largest request body accepted, to bound memory per connection
+pub const MAX_BODY_SIZE: usize = 64 * 1024;
+const MAX_HEADER_LINES: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn path_segments(&self) -> Vec<&str> {
+        self.path.split('/').filter(|s| !s.is_empty()).collect()
+    }
+
+    pub fn query_flag(&self, name: &str) -> bool {
+        self.query.split('&').any(|pair| {
+            let mut parts = pair.splitn(2, '=');
+            parts.next() == Some(name) && matches!(parts.next(), None | Some("true") | Some("1"))
+        })
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+pub fn read_request(reader: &mut impl BufRead) -> io::Result<Request> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(invalid("connection closed before request line"));
+    }
+
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(invalid("malformed request line"));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_uppercase(),
+        path: path.to_string(),
+        query: query.to_string(),
+        body: Vec::new(),
+    };
+
+    let mut content_length = 0usize;
+    for _ in 0..MAX_HEADER_LINES {
+        line.clear();
+        reader.read_line(&mut line)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body)?;
+            request.body = body;
+            return Ok(request);
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| invalid("invalid Content-Length"))?;
+                if content_length > MAX_BODY_SIZE {
+                    return Err(invalid("request body too large"));
+                }
+            }
+        }
+    }
+    Err(invalid("too many headers"))
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        422 => "Unprocessable Entity",
+        429 => "Too Many Requests",
+        _ => "Internal Server Error",
+    }
+}
+
+pub fn write_json(writer: &mut impl Write, status: u16, body: &Value) -> io::Result<()> {
+    let body = body.to_string();
+    write!(
+        writer,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason_phrase(status),
+        body.len(),
+        body
+    )?;
+    writer.flush()
+}
+
+pub fn write_error(writer: &mut impl Write, status: u16, message: &str) -> io::Result<()> {
+    write_json(writer, status, &serde_json::json!({ "error": message }))
+}
+
+/// Start a Server-Sent Events response; follow with `write_event` calls
+pub fn write_event_stream_header(writer: &mut impl Write) -> io::Result<()> {
+    write!(
+        writer,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n"
+    )?;
+    writer.flush()
+}
+
+pub fn write_event(writer: &mut impl Write, event: &str, data: &Value) -> io::Result<()> {
+    write!(writer, "event: {}\ndata: {}\n\n", event, data)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_request_with_body() {
+        let raw = "POST /sessions/abc/move?stream=true HTTP/1.1\r\nHost: x\r\nContent-Length: 15\r\n\r\n{\"move\":\"e2e4\"}";
+        let request = read_request(&mut Cursor::new(raw)).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path_segments(), vec!["sessions", "abc", "move"]);
+        assert!(request.query_flag("stream"));
+        assert!(!request.query_flag("other"));
+        assert_eq!(request.body, b"{\"move\":\"e2e4\"}");
+    }
+
+    #[test]
+    fn test_oversized_body_is_rejected() {
+        let raw = format!(
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY_SIZE + 1
+        );
+        assert!(read_request(&mut Cursor::new(raw)).is_err());
+    }
+}
@@ -0,0 +1,13 @@
+//! HTTP/JSON analysis server.
+//!
+//! Clients create a session, set its position and request analysis; with
+//! `?stream=true` search progress is sent as Server-Sent Events.
+
+pub mod config;
+pub mod http;
+pub mod server;
+pub mod session;
+
+pub use config::ServerConfig;
+pub use server::Server;
+pub use session::{AnalysisRequest, Session, SessionError, SessionManager, SessionState};
@@ -0,0 +1,54 @@
+use chess_server::{Server, ServerConfig};
+use std::process;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: chess-server [--bind ADDR] [--max-sessions N] [--max-depth N] \
+[--max-movetime-ms MS] [--idle-timeout-secs SECS]";
+
+fn parse_args() -> Result<ServerConfig, String> {
+    let mut config = ServerConfig::default();
+    let mut args = std::env::args().skip(1);
+
+    while let Some(flag) = args.next() {
+        if flag == "--help" || flag == "-h" {
+            println!("{}", USAGE);
+            process::exit(0);
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", flag))?;
+        let number = || {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid value for {}: {}", flag, value))
+        };
+        match flag.as_str() {
+            "--bind" => config.bind_address = value.clone(),
+            "--max-sessions" => config.max_sessions = number()? as usize,
+            "--max-depth" => config.max_depth = number()?.clamp(1, u8::MAX as u64) as u8,
+            "--max-movetime-ms" => config.max_movetime = Duration::from_millis(number()?.max(1)),
+            "--idle-timeout-secs" => config.idle_timeout = Duration::from_secs(number()?),
+            _ => return Err(format!("Unknown option: {}", flag)),
+        }
+    }
+    Ok(config)
+}
+
+fn main() {
+    let config = parse_args().unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        process::exit(2);
+    });
+
+    let server = Server::bind(config).unwrap_or_else(|e| {
+        eprintln!("chess-server: failed to bind: {}", e);
+        process::exit(1);
+    });
+    if let Ok(addr) = server.local_addr() {
+        println!("chess-server listening on http://{}", addr);
+    }
+    if let Err(e) = server.run() {
+        eprintln!("chess-server: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,192 @@
+// TCP listener and request routing; each connection is served on its own thread
+
+use crate::http::{self, Request};
+use crate::session::{AnalysisRequest, SessionError, SessionManager};
+use crate::ServerConfig;
+use chess_engine::{wire, SearchStatus};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{self, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Connections that send nothing for this long are dropped
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct Server {
+    listener: TcpListener,
+    sessions: Arc<SessionManager>,
+}
+
+#[derive(Deserialize)]
+struct PositionRequest {
+    fen: Option<String>,
+    #[serde(default)]
+    moves: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct MoveRequest {
+    #[serde(rename = "move")]
+    uci: String,
+}
+
+impl Server {
+    pub fn bind(config: ServerConfig) -> io::Result<Self> {
+        let listener = TcpListener::bind(&config.bind_address)?;
+        Ok(Server {
+            listener,
+            sessions: Arc::new(SessionManager::new(config)),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn sessions(&self) -> &Arc<SessionManager> {
+        &self.sessions
+    }
+
+    /// Accept connections until the listener fails
+    pub fn run(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("chess-server: accept failed: {}", e);
+                    continue;
+                }
+            };
+            let sessions = Arc::clone(&self.sessions);
+            thread::spawn(move || {
+                if let Err(e) = handle_connection(stream, &sessions) {
+                    eprintln!("chess-server: connection error: {}", e);
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, sessions: &SessionManager) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let request = match http::read_request(&mut BufReader::new(&stream)) {
+        Ok(request) => request,
+        Err(e) => return http::write_error(&mut stream, 400, &e.to_string()),
+    };
+    route(&request, sessions, &mut stream)
+}
+
+fn route(request: &Request, sessions: &SessionManager, out: &mut impl Write) -> io::Result<()> {
+    let segments = request.path_segments();
+    let result = match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["health"]) => Ok((200, json!({ "status": "ok", "sessions": sessions.len() }))),
+        ("GET", ["schema"]) => Ok((
+            200,
+            serde_json::from_str(wire::JSON_SCHEMA).expect("bundled schema is valid JSON"),
+        )),
+        ("POST", ["sessions"]) => sessions
+            .create()
+            .map(|id| (201, json!({ "session_id": id }))),
+        ("GET", ["sessions", id]) => sessions.get(id).map(|s| (200, json!(s.state()))),
+        ("DELETE", ["sessions", id]) => sessions.remove(id).map(|_| (200, json!({}))),
+        ("PUT", ["sessions", id, "position"]) => {
+            let body: PositionRequest = match parse_body(request) {
+                Ok(body) => body,
+                Err(message) => return http::write_error(out, 400, &message),
+            };
+            sessions.get(id).and_then(|s| {
+                s.set_position(body.fen.as_deref(), &body.moves)?;
+                Ok((200, json!(s.state())))
+            })
+        }
+        ("POST", ["sessions", id, "move"]) => {
+            let body: MoveRequest = match parse_body(request) {
+                Ok(body) => body,
+                Err(message) => return http::write_error(out, 400, &message),
+            };
+            sessions.get(id).and_then(|s| {
+                s.make_move(&body.uci)?;
+                Ok((200, json!(s.state())))
+            })
+        }
+        ("POST", ["sessions", id, "analyze"]) => {
+            let body: AnalysisRequest = if request.body.is_empty() {
+                AnalysisRequest::default()
+            } else {
+                match parse_body(request) {
+                    Ok(body) => body,
+                    Err(message) => return http::write_error(out, 400, &message),
+                }
+            };
+            return analyze(id, body, request.query_flag("stream"), sessions, out);
+        }
+        (_, ["health"]) | (_, ["sessions", ..]) => {
+            return http::write_error(out, 405, "Method not allowed")
+        }
+        _ => return http::write_error(out, 404, "Not found"),
+    };
+
+    match result {
+        Ok((status, body)) => http::write_json(out, status, &body),
+        Err(e) => http::write_error(out, e.status_code(), &e.to_string()),
+    }
+}
+
+/// Run an analysis, either answering once it completes or, when streaming,
+/// sending `info` events while it runs and a final `bestmove` event
+fn analyze(
+    id: &str,
+    request: AnalysisRequest,
+    stream: bool,
+    sessions: &SessionManager,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let session = match sessions.get(id) {
+        Ok(session) => session,
+        Err(e) => return http::write_error(out, e.status_code(), &e.to_string()),
+    };
+    if session.is_searching() {
+        let e = SessionError::Busy;
+        return http::write_error(out, e.status_code(), &e.to_string());
+    }
+
+    if !stream {
+        return match session.analyze(request, sessions.config(), |_| {}) {
+            Ok(result) => http::write_json(out, 200, &json!(result)),
+            Err(e) => http::write_error(out, e.status_code(), &e.to_string()),
+        };
+    }
+
+    http::write_event_stream_header(out)?;
+    // Keep searching if the client disconnects; the session stays consistent either way
+    let mut connected = true;
+    let outcome = session.analyze(request, sessions.config(), |status| {
+        if connected {
+            connected = http::write_event(out, "info", &status_json(&status)).is_ok();
+        }
+    });
+    match outcome {
+        Ok(result) => http::write_event(out, "bestmove", &json!(result)),
+        Err(e) => http::write_event(out, "error", &json!({ "error": e.to_string() })),
+    }
+}
+
+fn parse_body<T: for<'de> Deserialize<'de>>(request: &Request) -> Result<T, String> {
+    serde_json::from_slice(&request.body).map_err(|e| format!("Invalid JSON body: {}", e))
+}
+
+fn status_json(status: &SearchStatus) -> Value {
+    json!({
+        "depth": status.depth,
+        "seldepth": status.seldepth,
+        "nodes": status.nodes,
+        "nps": status.nps,
+        "hashfull": status.hashfull,
+        "tbhits": status.tbhits,
+        "time_ms": status.elapsed.as_millis() as u64,
+    })
+}
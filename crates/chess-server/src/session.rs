@@ -0,0 +1,357 @@
+// Per-client engine sessions and the limits applied to them
+
+use crate::ServerConfig;
+use chess_engine::{
+    ChessEngine, Color, GameResult, SearchLimits, SearchResult, SearchStatus, SearchStatusHandle,
+};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionError {
+    NotFound,
+    LimitReached,
+    /// The session already has an analysis running
+    Busy,
+    InvalidRequest(String),
+}
+
+impl SessionError {
+    pub fn status_code(&self) -> u16 {
+        match self {
+            SessionError::NotFound => 404,
+            SessionError::LimitReached => 429,
+            SessionError::Busy => 409,
+            SessionError::InvalidRequest(_) => 422,
+        }
+    }
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::NotFound => write!(f, "Session not found"),
+            SessionError::LimitReached => write!(f, "Session limit reached"),
+            SessionError::Busy => write!(f, "Analysis already running for this session"),
+            SessionError::InvalidRequest(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+/// Snapshot of a session's game, as returned to clients
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SessionState {
+    pub fen: String,
+    pub side_to_move: &'static str,
+    pub legal_moves: Vec<String>,
+    pub is_check: bool,
+    pub result: &'static str,
+}
+
+/// Depth and time requested for one analysis; clamped to the server limits
+#[derive(Debug, Clone, Copy, Default, serde::Deserialize)]
+pub struct AnalysisRequest {
+    pub depth: Option<u8>,
+    pub movetime_ms: Option<u64>,
+}
+
+pub struct Session {
+    engine: Mutex<ChessEngine>,
+    status: SearchStatusHandle,
+    searching: AtomicBool,
+    last_used: Mutex<Instant>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl Session {
+    fn new() -> Self {
+        let mut engine = ChessEngine::new();
+        // A fresh engine is never initialized yet
+        let _ = engine.initialize();
+        Session {
+            status: engine.search_status_handle(),
+            engine: Mutex::new(engine),
+            searching: AtomicBool::new(false),
+            last_used: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn touch(&self) {
+        *lock(&self.last_used) = Instant::now();
+    }
+
+    fn idle_for(&self) -> Duration {
+        lock(&self.last_used).elapsed()
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.searching.load(Ordering::Acquire)
+    }
+
+    pub fn state(&self) -> SessionState {
+        let engine = lock(&self.engine);
+        let info = engine.get_game_info();
+        SessionState {
+            fen: info.fen,
+            side_to_move: match info.side_to_move {
+                Color::White => "white",
+                Color::Black => "black",
+            },
+            legal_moves: info.legal_moves.iter().map(|mv| mv.to_string()).collect(),
+            is_check: info.is_check,
+            result: match engine.get_game_result() {
+                GameResult::Ongoing => "ongoing",
+                GameResult::WhiteWins => "white_wins",
+                GameResult::BlackWins => "black_wins",
+                GameResult::Draw => "draw",
+            },
+        }
+    }
+
+    /// Load a FEN (or the starting position when `None`) and play `moves` from it. On an
+    /// invalid FEN or an illegal move the session keeps its previous position.
+    pub fn set_position(&self, fen: Option<&str>, moves: &[String]) -> Result<(), SessionError> {
+        self.ensure_idle()?;
+        let mut engine = lock(&self.engine);
+        let previous = engine.snapshot();
+        let fen = fen.unwrap_or("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        engine
+            .load_fen(fen)
+            .map_err(|e| SessionError::InvalidRequest(format!("Invalid FEN: {}", e)))?;
+        for uci in moves {
+            if let Err(e) = Self::play(&mut engine, uci) {
+                engine.restore(&previous);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn make_move(&self, uci: &str) -> Result<(), SessionError> {
+        self.ensure_idle()?;
+        Self::play(&mut lock(&self.engine), uci)
+    }
+
+    fn play(engine: &mut ChessEngine, uci: &str) -> Result<(), SessionError> {
+        match engine.make_move_from_uci(uci) {
+            Ok(result) if result.success => Ok(()),
+            _ => Err(SessionError::InvalidRequest(format!(
+                "Illegal move: {}",
+                uci
+            ))),
+        }
+    }
+
+    fn ensure_idle(&self) -> Result<(), SessionError> {
+        if self.is_searching() {
+            Err(SessionError::Busy)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Search the current position, reporting progress every `progress_interval`
+    /// until it finishes. Only one analysis may run per session at a time.
+    pub fn analyze(
+        &self,
+        request: AnalysisRequest,
+        config: &ServerConfig,
+        mut on_progress: impl FnMut(SearchStatus),
+    ) -> Result<SearchResult, SessionError> {
+        if self
+            .searching
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return Err(SessionError::Busy);
+        }
+
+        let depth = request
+            .depth
+            .unwrap_or(config.max_depth)
+            .clamp(1, config.max_depth);
+        let max_ms = config.max_movetime.as_millis() as u64;
+        let movetime = request.movetime_ms.unwrap_or(max_ms).clamp(1, max_ms);
+
+        let result = thread::scope(|scope| {
+            let search = scope.spawn(|| {
+                let mut engine = lock(&self.engine);
+                engine.set_limits(
+                    SearchLimits::default()
+                        .with_depth(depth)
+                        .with_movetime(Duration::from_millis(movetime)),
+                );
+                engine.search()
+            });
+
+            while !search.is_finished() {
+                thread::sleep(config.progress_interval);
+                on_progress(self.status.snapshot());
+            }
+            search.join()
+        });
+
+        self.searching.store(false, Ordering::Release);
+        self.touch();
+        match result {
+            Ok(Ok(result)) => Ok(result),
+            Ok(Err(e)) => Err(SessionError::InvalidRequest(e.to_string())),
+            Err(_) => Err(SessionError::InvalidRequest("Search failed".to_string())),
+        }
+    }
+}
+
+/// Owns every live session and enforces the session count and idle limits
+pub struct SessionManager {
+    config: ServerConfig,
+    sessions: Mutex<HashMap<String, Arc<Session>>>,
+    counter: AtomicU64,
+    id_hasher: RandomState,
+}
+
+impl SessionManager {
+    pub fn new(config: ServerConfig) -> Self {
+        SessionManager {
+            config,
+            sessions: Mutex::new(HashMap::new()),
+            counter: AtomicU64::new(0),
+            id_hasher: RandomState::new(),
+        }
+    }
+
+    pub fn config(&self) -> &ServerConfig {
+        &self.config
+    }
+
+    pub fn len(&self) -> usize {
+        lock(&self.sessions).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn create(&self) -> Result<String, SessionError> {
+        self.expire_idle();
+
+        let mut sessions = lock(&self.sessions);
+        if sessions.len() >= self.config.max_sessions {
+            return Err(SessionError::LimitReached);
+        }
+
+        let id = self.next_id();
+        sessions.insert(id.clone(), Arc::new(Session::new()));
+        Ok(id)
+    }
+
+    pub fn get(&self, id: &str) -> Result<Arc<Session>, SessionError> {
+        let session = lock(&self.sessions)
+            .get(id)
+            .cloned()
+            .ok_or(SessionError::NotFound)?;
+        session.touch();
+        Ok(session)
+    }
+
+    pub fn remove(&self, id: &str) -> Result<(), SessionError> {
+        lock(&self.sessions)
+            .remove(id)
+            .map(|_| ())
+            .ok_or(SessionError::NotFound)
+    }
+
+    /// Drop sessions idle longer than the timeout; returns how many were removed
+    pub fn expire_idle(&self) -> usize {
+        let mut sessions = lock(&self.sessions);
+        let before = sessions.len();
+        sessions.retain(|_, session| {
+            session.is_searching() || session.idle_for() < self.config.idle_timeout
+        });
+        before - sessions.len()
+    }
+
+    /// Unpredictable ids, so one client cannot guess another's session
+    fn next_id(&self) -> String {
+        let mut hasher = self.id_hasher.build_hasher();
+        self.counter
+            .fetch_add(1, Ordering::Relaxed)
+            .hash(&mut hasher);
+        Instant::now().hash(&mut hasher);
+        format!("{:016x}", hasher.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> ServerConfig {
+        ServerConfig {
+            max_sessions: 2,
+            max_depth: 2,
+            max_movetime: Duration::from_secs(5),
+            progress_interval: Duration::from_millis(5),
+            ..ServerConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_session_limit_and_removal() {
+        let manager = SessionManager::new(test_config());
+        let a = manager.create().unwrap();
+        let b = manager.create().unwrap();
+        assert_ne!(a, b);
+        assert_eq!(manager.create(), Err(SessionError::LimitReached));
+
+        manager.remove(&a).unwrap();
+        assert_eq!(manager.remove(&a), Err(SessionError::NotFound));
+        assert!(manager.create().is_ok());
+    }
+
+    #[test]
+    fn test_idle_sessions_expire() {
+        let manager = SessionManager::new(ServerConfig {
+            idle_timeout: Duration::ZERO,
+            ..test_config()
+        });
+        manager.create().unwrap();
+        assert_eq!(manager.expire_idle(), 1);
+        assert!(manager.is_empty());
+    }
+
+    #[test]
+    fn test_position_and_analysis() {
+        let config = test_config();
+        let manager = SessionManager::new(config.clone());
+        let session = manager.get(&manager.create().unwrap()).unwrap();
+
+        session
+            .set_position(None, &["e2e4".to_string(), "e7e5".to_string()])
+            .unwrap();
+        assert_eq!(session.state().side_to_move, "white");
+        assert!(matches!(
+            session.make_move("e2e4"),
+            Err(SessionError::InvalidRequest(_))
+        ));
+
+        let request = AnalysisRequest {
+            depth: Some(50),
+            movetime_ms: None,
+        };
+        let result = session.analyze(request, &config, |_| {}).unwrap();
+        assert!(result.best_move.is_some());
+        assert!(result.depth <= config.max_depth);
+        assert!(!session.is_searching());
+    }
+}
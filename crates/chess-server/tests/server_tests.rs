@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod server_tests {
+    use chess_server::{Server, ServerConfig};
+    use serde_json::Value;
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpStream};
+    use std::thread;
+    use std::time::Duration;
+
+    fn start_server(max_sessions: usize) -> SocketAddr {
+        let server = Server::bind(ServerConfig {
+            bind_address: "127.0.0.1:0".to_string(),
+            max_sessions,
+            max_depth: 2,
+            max_movetime: Duration::from_secs(5),
+            progress_interval: Duration::from_millis(5),
+            ..ServerConfig::default()
+        })
+        .unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        addr
+    }
+
+    /// Send one request and return the status code and raw body
+    fn send(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        (status, body.to_string())
+    }
+
+    fn send_json(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let (status, body) = send(addr, method, path, body);
+        (status, serde_json::from_str(&body).unwrap())
+    }
+
+    fn create_session(addr: SocketAddr) -> String {
+        let (status, body) = send_json(addr, "POST", "/sessions", "");
+        assert_eq!(status, 201);
+        body["session_id"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_session_lifecycle() {
+        let addr = start_server(4);
+        let id = create_session(addr);
+
+        let (status, state) = send_json(
+            addr,
+            "PUT",
+            &format!("/sessions/{}/position", id),
+            r#"{"moves":["e2e4","e7e5"]}"#,
+        );
+        assert_eq!(status, 200);
+        assert_eq!(state["side_to_move"], "white");
+
+        let (status, state) = send_json(
+            addr,
+            "POST",
+            &format!("/sessions/{}/move", id),
+            r#"{"move":"g1f3"}"#,
+        );
+        assert_eq!(status, 200);
+        assert_eq!(state["side_to_move"], "black");
+
+        let (status, _) = send_json(
+            addr,
+            "POST",
+            &format!("/sessions/{}/move", id),
+            r#"{"move":"e1e8"}"#,
+        );
+        assert_eq!(status, 422);
+
+        let (status, _) = send_json(addr, "DELETE", &format!("/sessions/{}", id), "");
+        assert_eq!(status, 200);
+        let (status, _) = send_json(addr, "GET", &format!("/sessions/{}", id), "");
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn test_rejected_position_leaves_session_unchanged() {
+        let addr = start_server(4);
+        let id = create_session(addr);
+        let path = format!("/sessions/{}/position", id);
+
+        let (status, before) = send_json(addr, "PUT", &path, r#"{"moves":["d2d4"]}"#);
+        assert_eq!(status, 200);
+
+        // The third move is illegal after the first two were played
+        let (status, _) = send_json(
+            addr,
+            "PUT",
+            &path,
+            r#"{"fen":"4k3/8/8/8/8/8/8/R3K3 w Q - 0 1","moves":["a1a7","e8d8","e1e3"]}"#,
+        );
+        assert_eq!(status, 422);
+
+        let (status, after) = send_json(addr, "GET", &format!("/sessions/{}", id), "");
+        assert_eq!(status, 200);
+        assert_eq!(after, before);
+    }
+
+    #[test]
+    fn test_analysis_returns_best_move() {
+        let addr = start_server(4);
+        let id = create_session(addr);
+
+        let (status, result) = send_json(
+            addr,
+            "POST",
+            &format!("/sessions/{}/analyze", id),
+            r#"{"depth":2}"#,
+        );
+        assert_eq!(status, 200);
+        assert!(result["best_move"].is_string());
+        assert!(result["depth"].as_u64().unwrap() <= 2);
+
+        // Every field the schema requires is present
+        let (status, schema) = send_json(addr, "GET", "/schema", "");
+        assert_eq!(status, 200);
+        for field in schema["$defs"]["SearchResult"]["required"]
+            .as_array()
+            .unwrap()
+        {
+            let name = field.as_str().unwrap();
+            let nullable = name == "best_move" || name == "root_fail";
+            assert!(result
+                .get(name)
+                .is_some_and(|value| nullable || !value.is_null()));
+        }
+    }
+
+    #[test]
+    fn test_streamed_analysis_ends_with_bestmove() {
+        let addr = start_server(4);
+        let id = create_session(addr);
+
+        let (status, body) = send(
+            addr,
+            "POST",
+            &format!("/sessions/{}/analyze?stream=true", id),
+            r#"{"depth":2}"#,
+        );
+        assert_eq!(status, 200);
+
+        let events: Vec<&str> = body
+            .lines()
+            .filter_map(|line| line.strip_prefix("event: "))
+            .collect();
+        assert_eq!(events.last(), Some(&"bestmove"));
+        assert!(events[..events.len() - 1].iter().all(|e| *e == "info"));
+    }
+
+    #[test]
+    fn test_session_limit_and_bad_requests() {
+        let addr = start_server(1);
+        let id = create_session(addr);
+
+        let (status, _) = send_json(addr, "POST", "/sessions", "");
+        assert_eq!(status, 429);
+
+        let (status, _) = send_json(
+            addr,
+            "PUT",
+            &format!("/sessions/{}/position", id),
+            "not json",
+        );
+        assert_eq!(status, 400);
+
+        let (status, _) = send_json(addr, "GET", "/unknown", "");
+        assert_eq!(status, 404);
+    }
+}
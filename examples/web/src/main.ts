@@ -0,0 +1,195 @@
+// Minimal chessboard playing against the engine, driven only through WasmChessEngine.
+//
+// The player has White. Clicking a piece highlights its legal moves, clicking a target
+// plays the move, and the engine answers with an iterative search whose progress and
+// score are shown as it deepens.
+
+import init, { WasmChessEngine } from "../pkg/chess_ffi.js";
+
+/** Result of `WasmChessEngine.search_to_depth` */
+interface SearchInfo {
+  bestMove: string | null;
+  /** Centipawns for the side to move */
+  score: number;
+  depth: number;
+  seldepth: number;
+  nodes: number;
+  timeMs: number;
+  pv: string[];
+}
+
+const MAX_DEPTH = 12;
+/** No new iteration starts once the engine has thought this long */
+const THINK_TIME_MS = 1500;
+/** Scores beyond this are mates, as `MATE_THRESHOLD` in the search */
+const MATE_THRESHOLD = 31900;
+
+const GLYPHS: Record<string, string> = {
+  K: "♔", Q: "♕", R: "♖", B: "♗", N: "♘", P: "♙",
+  k: "♚", q: "♛", r: "♜", b: "♝", n: "♞", p: "♟",
+};
+const FILES = "abcdefgh";
+
+const boardElement = document.getElementById("board")!;
+const statusElement = document.getElementById("status")!;
+const progressElement = document.getElementById("progress")!;
+const evalWhite = document.getElementById("eval-white")!;
+const undoButton = document.getElementById("undo") as HTMLButtonElement;
+const newGameButton = document.getElementById("new-game") as HTMLButtonElement;
+
+let engine: WasmChessEngine;
+let selected: string | null = null;
+let targets: string[] = [];
+let lastMove: string | null = null;
+let thinking = false;
+
+/** Piece letters by square name, read from the FEN's placement field */
+function pieces(fen: string): Map<string, string> {
+  const board = new Map<string, string>();
+  fen.split(" ")[0].split("/").forEach((row, rowIndex) => {
+    let file = 0;
+    for (const symbol of row) {
+      if (/\d/.test(symbol)) {
+        file += Number(symbol);
+      } else {
+        board.set(FILES[file] + String(8 - rowIndex), symbol);
+        file += 1;
+      }
+    }
+  });
+  return board;
+}
+
+function render(): void {
+  const board = pieces(engine.get_fen());
+  boardElement.replaceChildren();
+  for (let rank = 8; rank >= 1; rank--) {
+    for (let file = 0; file < 8; file++) {
+      const square = FILES[file] + String(rank);
+      const cell = document.createElement("div");
+      cell.className = `square ${(file + rank) % 2 === 0 ? "light" : "dark"}`;
+      if (square === selected) cell.classList.add("selected");
+      if (targets.some((uci) => uci.slice(2, 4) === square)) cell.classList.add("target");
+      if (lastMove && (lastMove.slice(0, 2) === square || lastMove.slice(2, 4) === square)) {
+        cell.classList.add("last-move");
+      }
+      cell.textContent = GLYPHS[board.get(square) ?? ""] ?? "";
+      cell.addEventListener("click", () => onSquareClick(square));
+      boardElement.appendChild(cell);
+    }
+  }
+  undoButton.disabled = thinking || !engine.can_undo();
+  statusElement.textContent = status();
+}
+
+function status(): string {
+  if (engine.is_game_over()) {
+    switch (engine.get_game_result()) {
+      case 1: return "White wins";
+      case 2: return "Black wins";
+      default: return `Draw (${engine.get_draw_reason() ?? "agreed"})`;
+    }
+  }
+  if (thinking) return "Engine is thinking…";
+  return engine.is_in_check() ? "Your move — check!" : "Your move";
+}
+
+/** Fill the bar from a White-relative score */
+function showEvaluation(whiteScore: number): void {
+  const clamped = Math.max(-MATE_THRESHOLD, Math.min(MATE_THRESHOLD, whiteScore));
+  const whiteShare = 1 / (1 + Math.pow(10, -clamped / 400));
+  evalWhite.style.height = `${(whiteShare * 100).toFixed(1)}%`;
+}
+
+function formatScore(score: number): string {
+  if (Math.abs(score) > MATE_THRESHOLD) {
+    return score > 0 ? "mate" : "mated";
+  }
+  return (score >= 0 ? "+" : "") + (score / 100).toFixed(2);
+}
+
+function onSquareClick(square: string): void {
+  if (thinking || engine.is_game_over()) return;
+
+  const moves = targets.filter((uci) => uci.slice(2, 4) === square);
+  if (moves.length > 0) {
+    // Several moves to one square are promotions; always promote to a queen
+    const uci = moves.find((candidate) => candidate.endsWith("q")) ?? moves[0];
+    selected = null;
+    targets = [];
+    if (engine.make_move(uci)) {
+      lastMove = uci;
+      showEvaluation(engine.evaluate_absolute());
+      render();
+      void engineReply();
+    }
+    return;
+  }
+
+  const legal = engine.get_legal_moves_from(square) as string[];
+  selected = legal.length > 0 ? square : null;
+  targets = legal;
+  render();
+}
+
+/** Deepen one ply at a time, yielding to the page between iterations */
+async function engineReply(): Promise<void> {
+  if (engine.is_game_over()) return;
+  thinking = true;
+  render();
+
+  const sign = engine.get_side_to_move() === 0 ? 1 : -1;
+  const start = performance.now();
+  let best: SearchInfo | undefined;
+  for (let depth = 1; depth <= MAX_DEPTH; depth++) {
+    const info = engine.search_to_depth(depth) as SearchInfo | undefined;
+    if (!info || !info.bestMove) break;
+    best = info;
+    progressElement.textContent =
+      `depth ${info.depth}/${info.seldepth}  ${formatScore(info.score)}  ` +
+      `${info.nodes} nodes\n${info.pv.join(" ")}`;
+    showEvaluation(sign * info.score);
+    await new Promise((resolve) => setTimeout(resolve, 0));
+    if (Math.abs(info.score) > MATE_THRESHOLD || performance.now() - start > THINK_TIME_MS) {
+      break;
+    }
+  }
+
+  if (best?.bestMove && engine.make_move(best.bestMove)) {
+    lastMove = best.bestMove;
+  }
+  thinking = false;
+  render();
+}
+
+/** Take back the engine's reply and the player's move before it */
+function undo(): void {
+  if (thinking) return;
+  engine.undo_move();
+  if (engine.get_side_to_move() !== 0) {
+    engine.undo_move();
+  }
+  selected = null;
+  targets = [];
+  lastMove = null;
+  showEvaluation(engine.evaluate_absolute());
+  render();
+}
+
+function newGame(): void {
+  if (thinking) return;
+  engine.free();
+  engine = new WasmChessEngine();
+  selected = null;
+  targets = [];
+  lastMove = null;
+  progressElement.textContent = "";
+  showEvaluation(0);
+  render();
+}
+
+await init();
+engine = new WasmChessEngine();
+undoButton.addEventListener("click", undo);
+newGameButton.addEventListener("click", newGame);
+render();
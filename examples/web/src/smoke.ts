@@ -0,0 +1,47 @@
+// Exercises the WasmChessEngine calls the demo relies on, under Node, so that a change
+// to the binding that breaks the page fails `npm test` instead.
+
+import { readFileSync } from "node:fs";
+import { strict as assert } from "node:assert";
+import { initSync, WasmChessEngine } from "../pkg/chess_ffi.js";
+
+initSync({ module: readFileSync(new URL("../pkg/chess_ffi_bg.wasm", import.meta.url)) });
+
+const engine = new WasmChessEngine();
+
+// Legal-move highlighting
+const knightMoves = engine.get_legal_moves_from("g1") as string[];
+assert.deepEqual([...knightMoves].sort(), ["g1f3", "g1h3"]);
+assert.equal((engine.get_legal_moves_from("e4") as string[]).length, 0);
+
+// Moves and undo
+assert.ok(engine.make_move("e2e4"));
+assert.ok(engine.can_undo());
+assert.equal(engine.undo_move(), "e2e4");
+assert.ok(!engine.can_undo());
+assert.equal(engine.undo_move(), undefined);
+
+// Iterative search with progress
+let previousDepth = 0;
+for (let depth = 1; depth <= 4; depth++) {
+  const info = engine.search_to_depth(depth) as {
+    bestMove: string | null;
+    depth: number;
+    pv: string[];
+  };
+  assert.ok(info.bestMove);
+  assert.equal(info.depth, depth);
+  assert.equal(info.pv[0], info.bestMove);
+  assert.ok(info.depth > previousDepth);
+  previousDepth = info.depth;
+}
+
+// Evaluation bar input, and a finished game
+assert.equal(typeof engine.evaluate_absolute(), "number");
+const mated = WasmChessEngine.from_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1");
+assert.ok(mated?.is_game_over());
+assert.equal(mated?.get_game_result(), 1);
+const noMove = mated?.search_to_depth(2) as { bestMove: string | null } | undefined;
+assert.equal(noMove?.bestMove, null);
+
+console.log("chess-gui-demo smoke test passed");
@@ -0,0 +1,14 @@
+// FEN parsing, UCI round trips and make/unmake of every generated move
+//
+//   cargo +nightly fuzz run fen
+
+#![no_main]
+
+use chess_core::utils::fen_check::check_fen;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = check_fen(text);
+    }
+});
@@ -0,0 +1,30 @@
+// A FEN on the first line followed by UCI moves, played through GameState the way the
+// bindings do
+//
+//   cargo +nightly fuzz run moves
+
+#![no_main]
+
+use chess_core::GameState;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    let mut lines = text.lines();
+    let Some(Ok(mut game)) = lines.next().map(GameState::from_fen) else {
+        return;
+    };
+
+    for uci in lines.flat_map(str::split_whitespace) {
+        let Ok(mv) = game.resolve_uci_move(uci) else {
+            continue;
+        };
+        if game.make_move(mv).is_err() {
+            break;
+        }
+        let _ = game.to_fen();
+        let _ = game.game_result();
+    }
+});
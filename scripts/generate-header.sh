@@ -0,0 +1,29 @@
+#!/bin/bash
+set -e
+
+# Regenerate the C header for the FFI crate
+# Usage: ./scripts/generate-header.sh [--check]
+
+if ! command -v cbindgen >/dev/null 2>&1; then
+    echo "cbindgen not found; install it with: cargo install cbindgen"
+    exit 1
+fi
+
+CRATE_DIR="crates/chess-ffi"
+HEADER="$CRATE_DIR/include/chess_engine.h"
+
+if [ "$1" = "--check" ]; then
+    GENERATED=$(mktemp)
+    cbindgen --config "$CRATE_DIR/cbindgen.toml" --crate chess-ffi --output "$GENERATED" "$CRATE_DIR"
+    if ! diff -u "$HEADER" "$GENERATED"; then
+        echo "$HEADER is out of date; run ./scripts/generate-header.sh"
+        rm -f "$GENERATED"
+        exit 1
+    fi
+    rm -f "$GENERATED"
+    echo "$HEADER is up to date"
+else
+    mkdir -p "$CRATE_DIR/include"
+    cbindgen --config "$CRATE_DIR/cbindgen.toml" --crate chess-ffi --output "$HEADER" "$CRATE_DIR"
+    echo "Wrote $HEADER"
+fi
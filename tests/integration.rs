@@ -1,5 +1,5 @@
 use chess_core::{GameState, MoveGenerator};
-use chess_engine::{ChessEngineBuilder, SearchConfig};
+use chess_engine::{BestMove, ChessEngineBuilder, SearchConfig};
 
 #[test]
 fn test_engine_integration() {
@@ -44,7 +44,7 @@ fn test_search_integration() {
     let best_move = engine.find_best_move();
     assert!(best_move.is_ok());
     
-    if let Ok(Some(mv)) = best_move {
+    if let Ok(BestMove::Move(mv)) = best_move {
         println!("✅ Search integration test passed: found move {}", mv);
     } else {
         println!("✅ Search integration test passed: no move found (valid for some positions)");